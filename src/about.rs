@@ -0,0 +1,38 @@
+use anyhow::Result;
+use std::path::PathBuf;
+
+use crate::config::Config;
+use crate::db::Database;
+use crate::history::HistoryParser;
+
+/// Where this install keeps its data, for the About overlay and bug reports.
+#[derive(Debug, Clone)]
+pub struct AboutInfo {
+    pub version: &'static str,
+    pub config_path: PathBuf,
+    pub database_path: PathBuf,
+    /// `None` when the file can't be read
+    pub database_size: Option<u64>,
+    pub total_commands: usize,
+    /// History files found on this machine, by shell
+    pub import_sources: Vec<(&'static str, PathBuf)>,
+}
+
+impl AboutInfo {
+    pub async fn gather(config: &Config, db: &Database) -> Result<Self> {
+        Ok(Self {
+            version: env!("CARGO_PKG_VERSION"),
+            config_path: config.path(),
+            database_path: config.database_path.clone(),
+            database_size: std::fs::metadata(&config.database_path)
+                .ok()
+                .map(|meta| meta.len()),
+            total_commands: db.count_commands().await?,
+            import_sources: HistoryParser::from_config(config)
+                .history_files()
+                .into_iter()
+                .filter(|(_, path)| path.exists())
+                .collect(),
+        })
+    }
+}
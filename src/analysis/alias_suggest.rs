@@ -1,7 +1,16 @@
-use std::collections::HashMap;
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
 
+use super::normalize::{normalize_command, NUMBER_ARG, PATH_ARG, STRING_ARG};
 use crate::history::Command;
 
+/// Names that would hide a real command or a near-universal alias when typed.
+const SHADOWED_NAMES: &[&str] = &[
+    "l", "d", "c", "g", "k", "n", "y", "w", "bc", "cc", "cd", "cp", "dc", "dd", "df", "du", "ln",
+    "ls", "mv", "ps", "rm", "sh", "su", "vi", "wc",
+];
+
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub struct AliasSuggestion {
@@ -55,11 +64,21 @@ impl AliasSuggester {
         // Count command frequencies with better analysis
         let mut command_counts: HashMap<String, usize> = HashMap::new();
         let mut command_contexts: HashMap<String, Vec<String>> = HashMap::new();
+        // The text each normalized command was typed as, until runs differ
+        let mut typed_as: HashMap<String, Option<&str>> = HashMap::new();
 
         for cmd in commands_to_analyze {
             // Normalize command (remove variable parts like file paths, IDs)
-            let normalized_cmd = self.normalize_command(&cmd.command);
+            let normalized_cmd = normalize_command(&cmd.command);
             *command_counts.entry(normalized_cmd.clone()).or_insert(0) += 1;
+            typed_as
+                .entry(normalized_cmd.clone())
+                .and_modify(|typed| {
+                    if *typed != Some(cmd.command.trim()) {
+                        *typed = None;
+                    }
+                })
+                .or_insert(Some(cmd.command.trim()));
 
             // Track command contexts for better alias suggestions
             command_contexts
@@ -70,9 +89,18 @@ impl AliasSuggester {
 
         // Generate suggestions with enhanced logic
         let mut suggestions = Vec::new();
-        let mut total_potential_savings = 0;
 
-        for (command, count) in &command_counts {
+        // An alias can't fill in arguments that vary, so it stops before them
+        let mut alias_counts: HashMap<String, usize> = HashMap::new();
+        for (normalized, count) in command_counts {
+            let command = match typed_as[&normalized] {
+                Some(typed) => typed.to_string(),
+                None => fixed_prefix(&normalized),
+            };
+            *alias_counts.entry(command).or_insert(0) += count;
+        }
+
+        for (command, count) in &alias_counts {
             // Enhanced thresholds based on command complexity
             let min_frequency = if command.contains("git") || command.contains("docker") {
                 2
@@ -87,7 +115,6 @@ impl AliasSuggester {
 
             if *count >= min_frequency && command.len() > min_length {
                 if let Some(suggestion) = self.generate_alias_suggestion(command, *count) {
-                    total_potential_savings += suggestion.total_time_saved;
                     suggestions.push(suggestion);
                 }
             }
@@ -99,9 +126,14 @@ impl AliasSuggester {
                 a.frequency * a.time_saved_per_use * self.calculate_complexity_score(&a.command);
             let score_b =
                 b.frequency * b.time_saved_per_use * self.calculate_complexity_score(&b.command);
-            score_b.cmp(&score_a)
+            // Break ties on the command so the order is stable between runs
+            score_b
+                .cmp(&score_a)
+                .then_with(|| a.command.cmp(&b.command))
         });
+        let mut suggestions = self.resolve_overlaps(suggestions);
         suggestions.truncate(25); // Top 25 suggestions
+        let total_potential_savings = suggestions.iter().map(|s| s.total_time_saved).sum();
 
         // Enhanced existing alias detection
         let existing_aliases_usage = self.detect_existing_aliases(commands);
@@ -113,31 +145,43 @@ impl AliasSuggester {
         }
     }
 
-    fn normalize_command(&self, command: &str) -> String {
-        let normalized = command.to_string();
-
-        // Simple pattern replacements without regex for now
-        // Replace numbers with placeholder
-        let words: Vec<&str> = normalized.split_whitespace().collect();
-        let mut new_words = Vec::new();
-
-        for word in words {
-            if word.chars().all(|c| c.is_ascii_digit()) {
-                new_words.push("N");
-            } else if word.contains('/')
-                && (word.ends_with(".txt")
-                    || word.ends_with(".log")
-                    || word.ends_with(".json")
-                    || word.ends_with(".yaml")
-                    || word.ends_with(".yml"))
-            {
-                new_words.push("/FILE");
-            } else {
-                new_words.push(word);
+    /// Walks `suggestions` best first, dropping any whose name and command both
+    /// extend (or are extended by) a better one's, like `gc` for `git commit`
+    /// next to `gcm` for `git commit -m wip`. Names that are already taken or
+    /// would shadow a common command get the first free numeric suffix.
+    fn resolve_overlaps(&self, suggestions: Vec<AliasSuggestion>) -> Vec<AliasSuggestion> {
+        let mut kept: Vec<AliasSuggestion> = Vec::new();
+
+        for mut suggestion in suggestions {
+            let dominated = kept.iter().any(|better| {
+                is_prefix_either_way(&better.suggested_alias, &suggestion.suggested_alias)
+                    && extends_either_way(&better.command, &suggestion.command)
+            });
+            if dominated {
+                continue;
             }
+
+            let taken = |name: &str| {
+                SHADOWED_NAMES.contains(&name) || kept.iter().any(|s| s.suggested_alias == name)
+            };
+            if taken(&suggestion.suggested_alias) {
+                let base = suggestion.suggested_alias.clone();
+                let name = (2..)
+                    .map(|n| format!("{}{}", base, n))
+                    .find(|name| !taken(name))
+                    .unwrap_or(base);
+                suggestion.time_saved_per_use = suggestion.command.len().saturating_sub(name.len());
+                suggestion.total_time_saved = suggestion.time_saved_per_use * suggestion.frequency;
+                suggestion.suggested_alias = name;
+                if suggestion.time_saved_per_use < 3 {
+                    continue;
+                }
+            }
+
+            kept.push(suggestion);
         }
 
-        new_words.join(" ")
+        kept
     }
 
     fn calculate_complexity_score(&self, command: &str) -> usize {
@@ -394,22 +438,11 @@ impl AliasSuggester {
         let mut output = String::new();
 
         match shell {
-            "bash" | "zsh" => {
+            "bash" | "zsh" | "fish" => {
                 output.push_str("# Generated aliases by Whiskerlog\n");
                 for suggestion in suggestions.iter().take(10) {
-                    output.push_str(&format!(
-                        "alias {}='{}'\n",
-                        suggestion.suggested_alias, suggestion.command
-                    ));
-                }
-            }
-            "fish" => {
-                output.push_str("# Generated aliases by Whiskerlog\n");
-                for suggestion in suggestions.iter().take(10) {
-                    output.push_str(&format!(
-                        "alias {} '{}'\n",
-                        suggestion.suggested_alias, suggestion.command
-                    ));
+                    output.push_str(&self.format_alias(suggestion, shell));
+                    output.push('\n');
                 }
             }
             _ => {
@@ -420,6 +453,70 @@ impl AliasSuggester {
         output
     }
 
+    /// A single alias definition line for `shell`. Fish takes the name and body
+    /// as separate arguments and escapes quotes with a backslash inside them.
+    pub fn format_alias(&self, suggestion: &AliasSuggestion, shell: &str) -> String {
+        if shell == "fish" {
+            let body = suggestion
+                .command
+                .replace('\\', "\\\\")
+                .replace('\'', "\\'");
+            format!("alias {} '{}'", suggestion.suggested_alias, body)
+        } else {
+            let body = suggestion.command.replace('\'', "'\\''");
+            format!("alias {}='{}'", suggestion.suggested_alias, body)
+        }
+    }
+
+    /// Append aliases for `shell` to `path` under a timestamped banner, skipping
+    /// any whose name is already defined in the file. Returns how many were written.
+    pub fn append_shell_aliases(
+        &self,
+        suggestions: &[AliasSuggestion],
+        shell: &str,
+        path: &Path,
+    ) -> Result<usize> {
+        let existing = if path.exists() {
+            std::fs::read_to_string(path)?
+        } else {
+            String::new()
+        };
+        let defined: HashSet<&str> = existing.lines().filter_map(defined_alias_name).collect();
+
+        let lines: Vec<String> = suggestions
+            .iter()
+            .take(10)
+            .filter(|s| !defined.contains(s.suggested_alias.as_str()))
+            .map(|s| self.format_alias(s, shell))
+            .collect();
+        if lines.is_empty() {
+            return Ok(0);
+        }
+
+        let mut output = existing;
+        if !output.is_empty() && !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            output.push('\n');
+        }
+        output.push_str(&format!(
+            "# Whiskerlog generated {}\n",
+            chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+        ));
+        for line in &lines {
+            output.push_str(line);
+            output.push('\n');
+        }
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, output)?;
+
+        Ok(lines.len())
+    }
+
     pub fn calculate_efficiency_gain(&self, analysis: &AliasAnalysis) -> f32 {
         if analysis.potential_savings == 0 {
             return 0.0;
@@ -433,3 +530,35 @@ impl AliasSuggester {
         (time_saved_minutes * 10.0).min(100.0)
     }
 }
+
+/// The alias name defined on a line, for both `alias n='...'` and fish's `alias n '...'`.
+fn defined_alias_name(line: &str) -> Option<&str> {
+    let rest = line.trim_start().strip_prefix("alias ")?.trim_start();
+    let end = rest.find(['=', ' ']).unwrap_or(rest.len());
+    Some(&rest[..end]).filter(|name| !name.is_empty())
+}
+
+fn is_prefix_either_way(a: &str, b: &str) -> bool {
+    a.starts_with(b) || b.starts_with(a)
+}
+
+/// Whether one command is the other with extra arguments on the end.
+fn extends_either_way(a: &str, b: &str) -> bool {
+    let a: Vec<&str> = a.split_whitespace().collect();
+    let b: Vec<&str> = b.split_whitespace().collect();
+    let shared = a.len().min(b.len());
+    a[..shared] == b[..shared]
+}
+
+/// The words of a normalized command before its first placeholder.
+fn fixed_prefix(normalized: &str) -> String {
+    normalized
+        .split(' ')
+        .take_while(|word| {
+            ![STRING_ARG, PATH_ARG, NUMBER_ARG]
+                .iter()
+                .any(|p| word.contains(p))
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -1,7 +1,66 @@
+use regex::Regex;
 use std::collections::HashMap;
 
+use crate::history::detector::{DangerDetector, DangerResult};
 use crate::history::Command;
 
+/// A safer way to do what a matching command does.
+struct SaferRule {
+    /// Regex matched anywhere in the command
+    pattern: &'static str,
+    advice: &'static [&'static str],
+}
+
+/// Add a row to teach `suggest_safer` about another risky command.
+const SAFER_RULES: &[SaferRule] = &[
+    SaferRule {
+        pattern: r"\brm\s+(-\w*([rR]f|f[rR])\w*|-r\s+-f|-f\s+-r|--recursive\s+--force|--force\s+--recursive)\b",
+        advice: &[
+            "Use `trash` (trash-cli) so the files can be restored",
+            "Add `-i` to confirm each deletion",
+        ],
+    },
+    SaferRule {
+        pattern: r"\bsudo\s+rm\b",
+        advice: &["Double-check the path before deleting as root"],
+    },
+    SaferRule {
+        pattern: r"\bchmod\s+(-R\s+)?0?777\b",
+        advice: &[
+            "Use least-privilege modes: 755 for directories and scripts, 644 for files",
+            "Give a specific user or group access with `chown` or `chgrp` instead",
+        ],
+    },
+    SaferRule {
+        pattern: r"\bgit\s+push\b.*\s(--force|-f)(\s|$)",
+        advice: &["Use `--force-with-lease` so commits you haven't fetched aren't overwritten"],
+    },
+    SaferRule {
+        pattern: r"\bgit\s+reset\s+--hard\b",
+        advice: &["Run `git stash` first, or use `git reset --keep` to keep local changes"],
+    },
+    SaferRule {
+        pattern: r"\bgit\s+clean\s+-\w*f",
+        advice: &["Preview what would be deleted with `git clean -n` first"],
+    },
+    SaferRule {
+        pattern: r"\b(curl|wget)\b[^|]*\|\s*(sudo\s+)?(ba|z)?sh\b",
+        advice: &["Download the script, read it, then run it"],
+    },
+    SaferRule {
+        pattern: r"\bdd\b.*\bof=/dev/",
+        advice: &["Confirm the target device with `lsblk` before writing to it"],
+    },
+    SaferRule {
+        pattern: r"\bmkfs(\.\w+)?\s",
+        advice: &["Confirm the device with `lsblk -f` before formatting it"],
+    },
+    SaferRule {
+        pattern: r"\bkill\s+-(9|KILL|SIGKILL)\b",
+        advice: &["Try a plain `kill` (SIGTERM) first so the process can clean up"],
+    },
+];
+
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub struct DangerAnalysis {
@@ -31,8 +90,10 @@ pub struct RiskyCommand {
     pub safer_alternatives: Vec<String>,
 }
 
-#[allow(dead_code)]
-pub struct DangerAnalyzer;
+pub struct DangerAnalyzer {
+    detector: DangerDetector,
+    safer_rules: Vec<(Regex, &'static [&'static str])>,
+}
 
 #[allow(dead_code)]
 impl Default for DangerAnalyzer {
@@ -43,7 +104,30 @@ impl Default for DangerAnalyzer {
 
 impl DangerAnalyzer {
     pub fn new() -> Self {
-        Self
+        Self {
+            detector: DangerDetector::new(),
+            safer_rules: compile_safer_rules(),
+        }
+    }
+
+    /// Score with the same rules and threshold the importer uses.
+    pub fn from_config(config: &crate::config::Config) -> Self {
+        Self {
+            detector: DangerDetector::with_rules(&config.danger_rules, config.danger_threshold),
+            safer_rules: compile_safer_rules(),
+        }
+    }
+
+    /// Score `command` as if it were being imported, without it having to be
+    /// in the history: the capped score and the reasons of every matching rule.
+    pub fn explain(&self, command: &str) -> (f32, Vec<String>) {
+        let result = self.assess(command);
+        (result.score, result.reasons)
+    }
+
+    /// Score and flag `command` exactly as the importer would.
+    pub fn assess(&self, command: &str) -> DangerResult {
+        self.detector.assess(command)
     }
 
     #[allow(dead_code)]
@@ -133,7 +217,7 @@ impl DangerAnalyzer {
         let mut risky_commands: Vec<_> = command_risks
             .into_iter()
             .map(|(command, (count, max_danger_score, reasons))| {
-                let safer_alternatives = self.suggest_safer_alternatives(&command);
+                let safer_alternatives = self.suggest_safer(&command);
                 RiskyCommand {
                     command,
                     count,
@@ -155,43 +239,20 @@ impl DangerAnalyzer {
         risky_commands
     }
 
-    #[allow(dead_code)]
-    fn suggest_safer_alternatives(&self, command: &str) -> Vec<String> {
-        let mut alternatives = Vec::new();
-
-        if command.contains("rm -rf") {
-            alternatives.push("Use 'rm -i' for interactive deletion".to_string());
-            alternatives.push("Move to trash instead of permanent deletion".to_string());
-            alternatives.push("Use 'find' with '-delete' for more control".to_string());
-        }
-
-        if command.contains("chmod 777") {
-            alternatives.push("Use more restrictive permissions like 755 or 644".to_string());
-            alternatives.push("Set specific user/group permissions instead".to_string());
-        }
-
-        if command.contains("sudo rm") {
-            alternatives.push("Double-check the path before running".to_string());
-            alternatives.push("Use 'sudo -l' to verify permissions first".to_string());
-        }
-
-        if command.contains("curl") && command.contains("| bash") {
-            alternatives.push("Download script first, then review before executing".to_string());
-            alternatives.push("Use package manager instead of direct script execution".to_string());
-        }
-
-        if command.contains("dd") {
-            alternatives.push("Double-check input and output devices".to_string());
-            alternatives.push("Use 'lsblk' to verify device names first".to_string());
-            alternatives.push("Consider using 'cp' for file copying instead".to_string());
-        }
-
-        if alternatives.is_empty() {
-            alternatives.push("Review command carefully before execution".to_string());
-            alternatives.push("Test in a safe environment first".to_string());
+    /// Safer ways to do what `command` does, from every rule in [`SAFER_RULES`]
+    /// that matches. Empty when there's no known safer form.
+    pub fn suggest_safer(&self, command: &str) -> Vec<String> {
+        let mut suggestions: Vec<String> = Vec::new();
+        for (pattern, advice) in &self.safer_rules {
+            if pattern.is_match(command) {
+                for line in *advice {
+                    if !suggestions.iter().any(|s| s == line) {
+                        suggestions.push(line.to_string());
+                    }
+                }
+            }
         }
-
-        alternatives
+        suggestions
     }
 
     #[allow(dead_code)]
@@ -267,3 +328,16 @@ impl DangerAnalyzer {
         (safe_ratio - danger_penalty).clamp(0.0, 1.0)
     }
 }
+
+fn compile_safer_rules() -> Vec<(Regex, &'static [&'static str])> {
+    SAFER_RULES
+        .iter()
+        .filter_map(|rule| match Regex::new(rule.pattern) {
+            Ok(regex) => Some((regex, rule.advice)),
+            Err(err) => {
+                log::warn!("Skipping safer-command rule '{}': {}", rule.pattern, err);
+                None
+            }
+        })
+        .collect()
+}
@@ -14,6 +14,17 @@ pub struct ExperimentAnalysis {
     pub recommendations: Vec<String>,
 }
 
+impl ExperimentAnalysis {
+    /// Knowledge gaps found from `tool`'s failed commands.
+    pub fn knowledge_gaps_for(&self, tool: &str) -> Vec<&KnowledgeGap> {
+        let area = gap_area(tool);
+        self.knowledge_gaps
+            .iter()
+            .filter(|gap| gap.area == area)
+            .collect()
+    }
+}
+
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub struct ExperimentSession {
@@ -86,7 +97,9 @@ pub enum Priority {
 }
 
 #[allow(dead_code)]
-pub struct ExperimentDetector;
+pub struct ExperimentDetector {
+    idle_threshold: chrono::Duration,
+}
 
 #[allow(dead_code)]
 impl Default for ExperimentDetector {
@@ -97,7 +110,20 @@ impl Default for ExperimentDetector {
 
 impl ExperimentDetector {
     pub fn new() -> Self {
-        Self
+        Self::with_idle_threshold(super::sessions::DEFAULT_IDLE_MINUTES)
+    }
+
+    /// Split sessions after `minutes` without a command instead of the default.
+    #[allow(dead_code)]
+    pub fn with_idle_threshold(minutes: i64) -> Self {
+        Self {
+            idle_threshold: chrono::Duration::minutes(minutes),
+        }
+    }
+
+    /// Session split as configured.
+    pub fn from_config(config: &crate::config::Config) -> Self {
+        Self::with_idle_threshold(config.session_idle_minutes)
     }
 
     #[allow(dead_code)]
@@ -126,19 +152,11 @@ impl ExperimentDetector {
 
     #[allow(dead_code)]
     fn identify_experiment_sessions(&self, commands: &[Command]) -> Vec<ExperimentSession> {
-        let mut sessions = HashMap::new();
-
-        // Group commands by session
-        for cmd in commands {
-            sessions
-                .entry(cmd.session_id.clone())
-                .or_insert(Vec::new())
-                .push(cmd);
-        }
-
         let mut experiment_sessions = Vec::new();
 
-        for (session_id, session_commands) in sessions {
+        for session in super::sessions::split_sessions(commands, self.idle_threshold) {
+            let session_id = session.id;
+            let session_commands = session.commands;
             let experiment_count = session_commands.iter().filter(|c| c.is_experiment).count();
             let total_count = session_commands.len();
             let experiment_ratio = experiment_count as f32 / total_count as f32;
@@ -442,10 +460,12 @@ impl ExperimentDetector {
             }
         }
 
+        // Ties by name, so the Experiments tab's selection stays put across refreshes
         explorations.sort_by(|a, b| {
             b.exploration_commands
                 .len()
                 .cmp(&a.exploration_commands.len())
+                .then_with(|| a.tool.cmp(&b.tool))
         });
         explorations.truncate(10);
         explorations
@@ -521,7 +541,7 @@ impl ExperimentDetector {
             for (tool, failure_count) in tool_failures {
                 if failure_count >= 3 {
                     gaps.push(KnowledgeGap {
-                        area: format!("{} usage", tool),
+                        area: gap_area(&tool),
                         indicators: vec![format!("{} failed commands", failure_count)],
                         suggested_resources: vec![
                             format!("man {}", tool),
@@ -644,3 +664,7 @@ impl ExperimentDetector {
         score.min(1.0)
     }
 }
+
+fn gap_area(tool: &str) -> String {
+    format!("{} usage", tool)
+}
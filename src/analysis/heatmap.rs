@@ -1,6 +1,8 @@
-use chrono::{Datelike, Duration, Timelike, Utc, Weekday};
+use chrono::{DateTime, Datelike, Duration, Local, Timelike, Utc, Weekday};
+use chrono_tz::Tz;
 use std::collections::HashMap;
 
+use super::stats::busiest;
 use crate::history::Command;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -19,11 +21,168 @@ pub enum ViewMode {
     Failed,
 }
 
+impl TimeRange {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TimeRange::Day => "day",
+            TimeRange::Week => "week",
+            TimeRange::Month => "month",
+            TimeRange::Year => "year",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "day" => Some(TimeRange::Day),
+            "week" => Some(TimeRange::Week),
+            "month" => Some(TimeRange::Month),
+            "year" => Some(TimeRange::Year),
+            _ => None,
+        }
+    }
+}
+
+impl ViewMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ViewMode::All => "all",
+            ViewMode::Dangerous => "dangerous",
+            ViewMode::Experiments => "experiments",
+            ViewMode::Failed => "failed",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "all" => Some(ViewMode::All),
+            "dangerous" => Some(ViewMode::Dangerous),
+            "experiments" => Some(ViewMode::Experiments),
+            "failed" => Some(ViewMode::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// Commands shown in a cell's breakdown.
+pub const CELL_TOP_COMMANDS: usize = 3;
+
 #[derive(Debug, Clone)]
 pub struct HeatmapData {
     pub grid: [[f32; 7]; 24], // [hour][day_of_week] = activity_level (0.0 to 1.0)
     pub max_activity: f32,
     pub total_commands: usize,
+    /// Breakdown per cell, hour-major like `grid`
+    cells: Vec<HeatmapCell>,
+}
+
+impl HeatmapData {
+    /// Commands in the given local hour and weekday, 0 outside the grid.
+    pub fn count(&self, hour: usize, day: usize) -> usize {
+        self.cell(hour, day).map_or(0, |cell| cell.command_count)
+    }
+
+    /// The level of every cell on `scale`, from 0 for no commands up to
+    /// `HEATMAP_LEVELS`, hour-major like `grid`.
+    pub fn levels(&self, scale: &HeatmapScale) -> [[u8; 7]; 24] {
+        let mut busy: Vec<usize> = self
+            .cells
+            .iter()
+            .map(|cell| cell.command_count)
+            .filter(|&count| count > 0)
+            .collect();
+        busy.sort_unstable();
+
+        let mut levels = [[0u8; 7]; 24];
+        for (hour, row) in levels.iter_mut().enumerate() {
+            for (day, level) in row.iter_mut().enumerate() {
+                let count = self.count(hour, day);
+                if count == 0 {
+                    continue;
+                }
+                *level = match scale {
+                    HeatmapScale::Percentile => {
+                        // Share of the busy cells at or below this one
+                        let rank = busy.partition_point(|&other| other <= count);
+                        (rank * HEATMAP_LEVELS as usize).div_ceil(busy.len()) as u8
+                    }
+                    HeatmapScale::Absolute(thresholds) => {
+                        1 + thresholds.iter().filter(|&&start| count >= start).count() as u8
+                    }
+                };
+            }
+        }
+        levels
+    }
+
+    /// What ran in the given local hour on the given weekday (Monday is 0);
+    /// `None` outside the grid.
+    pub fn cell(&self, hour: usize, day: usize) -> Option<&HeatmapCell> {
+        if hour < 24 && day < 7 {
+            self.cells.get(hour * 7 + day)
+        } else {
+            None
+        }
+    }
+}
+
+/// Activity levels a busy heatmap cell is drawn at.
+pub const HEATMAP_LEVELS: u8 = 5;
+
+/// How cell counts map to the heatmap's activity levels.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum HeatmapScale {
+    /// Relative to the data: a cell's level follows its rank among the busy
+    /// cells, so the busiest one is at the top however few commands it holds
+    #[default]
+    Percentile,
+    /// Commands in a cell at which levels 2 to 5 start
+    Absolute([usize; 4]),
+}
+
+impl HeatmapScale {
+    /// `heatmap_thresholds` from the config when it's four rising counts,
+    /// otherwise percentiles.
+    pub fn from_config(config: &crate::config::Config) -> Self {
+        match config.heatmap_thresholds.as_deref() {
+            None => Self::Percentile,
+            Some(&[a, b, c, d]) if 0 < a && a < b && b < c && c < d => Self::Absolute([a, b, c, d]),
+            Some(thresholds) => {
+                log::warn!(
+                    "heatmap_thresholds should be four rising command counts, got {:?}; using percentiles",
+                    thresholds
+                );
+                Self::Percentile
+            }
+        }
+    }
+}
+
+/// The commands behind one heatmap cell.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HeatmapCell {
+    pub command_count: usize,
+    pub dangerous_count: usize,
+    pub experiment_count: usize,
+    /// Most run first, then alphabetical
+    pub top_commands: Vec<(String, usize)>,
+}
+
+impl HeatmapCell {
+    pub fn dangerous_ratio(&self) -> f32 {
+        ratio(self.dangerous_count, self.command_count)
+    }
+
+    pub fn experiment_ratio(&self) -> f32 {
+        ratio(self.experiment_count, self.command_count)
+    }
+}
+
+fn ratio(part: usize, total: usize) -> f32 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f32 / total as f32
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -34,7 +193,100 @@ pub struct ActivityPeriod {
     pub command_count: usize,
 }
 
-pub struct HeatmapAnalyzer;
+/// Which local hours and days count as work, weekend and late night.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkSchedule {
+    pub work_hours_start: u32,
+    /// Exclusive
+    pub work_hours_end: u32,
+    pub weekend_days: Vec<Weekday>,
+    pub late_night_start: u32,
+    /// Exclusive; earlier than the start when the window crosses midnight
+    pub late_night_end: u32,
+}
+
+impl Default for WorkSchedule {
+    fn default() -> Self {
+        Self {
+            work_hours_start: 9,
+            work_hours_end: 17,
+            weekend_days: vec![Weekday::Sat, Weekday::Sun],
+            late_night_start: 22,
+            late_night_end: 6,
+        }
+    }
+}
+
+impl WorkSchedule {
+    /// Hours outside 0-23 and unknown day names are ignored in favour of the defaults.
+    pub fn from_config(config: &crate::config::Config) -> Self {
+        let default = Self::default();
+        let valid = |start: u32, end: u32| start < 24 && end < 24;
+
+        let (work_hours_start, work_hours_end) =
+            if valid(config.work_hours_start, config.work_hours_end) {
+                (config.work_hours_start, config.work_hours_end)
+            } else {
+                log::warn!("Ignoring work hours outside 0-23 in config");
+                (default.work_hours_start, default.work_hours_end)
+            };
+        let (late_night_start, late_night_end) =
+            if valid(config.late_night_start, config.late_night_end) {
+                (config.late_night_start, config.late_night_end)
+            } else {
+                log::warn!("Ignoring late night hours outside 0-23 in config");
+                (default.late_night_start, default.late_night_end)
+            };
+
+        let weekend_days = config
+            .weekend_days
+            .iter()
+            .filter_map(|day| match day.parse::<Weekday>() {
+                Ok(day) => Some(day),
+                Err(_) => {
+                    log::warn!("Ignoring unknown weekend day '{}' in config", day);
+                    None
+                }
+            })
+            .collect();
+
+        Self {
+            work_hours_start,
+            work_hours_end,
+            weekend_days,
+            late_night_start,
+            late_night_end,
+        }
+    }
+
+    pub fn is_work_hour(&self, hour: u32) -> bool {
+        in_window(hour, self.work_hours_start, self.work_hours_end)
+    }
+
+    pub fn is_late_night(&self, hour: u32) -> bool {
+        in_window(hour, self.late_night_start, self.late_night_end)
+    }
+
+    pub fn is_weekend(&self, day: Weekday) -> bool {
+        self.weekend_days.contains(&day)
+    }
+}
+
+/// Whether `hour` falls in `[start, end)`, wrapping past midnight when `end < start`.
+fn in_window(hour: u32, start: u32, end: u32) -> bool {
+    if start <= end {
+        (start..end).contains(&hour)
+    } else {
+        hour >= start || hour < end
+    }
+}
+
+/// Buckets commands by hour and weekday in the user's zone rather than UTC.
+pub struct HeatmapAnalyzer {
+    // `None` means the system's local zone
+    timezone: Option<Tz>,
+    schedule: WorkSchedule,
+}
 
 impl Default for HeatmapAnalyzer {
     fn default() -> Self {
@@ -44,7 +296,51 @@ impl Default for HeatmapAnalyzer {
 
 impl HeatmapAnalyzer {
     pub fn new() -> Self {
-        Self
+        Self {
+            timezone: None,
+            schedule: WorkSchedule::default(),
+        }
+    }
+
+    /// Pin bucketing to an IANA zone, e.g. when reviewing a server's history
+    /// from a machine in a different zone.
+    pub fn with_timezone(timezone: Tz) -> Self {
+        Self {
+            timezone: Some(timezone),
+            schedule: WorkSchedule::default(),
+        }
+    }
+
+    /// Use the zone and work schedule from the config, falling back to the local
+    /// zone when it's unset or not a valid IANA name.
+    pub fn from_config(config: &crate::config::Config) -> Self {
+        let analyzer = match config.timezone.as_deref().map(str::parse::<Tz>) {
+            Some(Ok(timezone)) => Self::with_timezone(timezone),
+            Some(Err(err)) => {
+                log::warn!("Ignoring invalid timezone in config: {}", err);
+                Self::new()
+            }
+            None => Self::new(),
+        };
+
+        Self {
+            schedule: WorkSchedule::from_config(config),
+            ..analyzer
+        }
+    }
+
+    /// Hour of day and weekday of `timestamp` in the configured zone.
+    fn local_hour_and_day(&self, timestamp: &DateTime<Utc>) -> (u32, Weekday) {
+        match self.timezone {
+            Some(tz) => {
+                let local = timestamp.with_timezone(&tz);
+                (local.hour(), local.weekday())
+            }
+            None => {
+                let local = timestamp.with_timezone(&Local);
+                (local.hour(), local.weekday())
+            }
+        }
     }
 
     pub fn generate_heatmap(
@@ -58,14 +354,35 @@ impl HeatmapAnalyzer {
 
         let mut activity_grid = [[0usize; 7]; 24]; // [hour][day_of_week] = count
         let mut max_count = 0usize;
+        let mut cells = vec![HeatmapCell::default(); 24 * 7];
+        let mut cell_commands: Vec<HashMap<&str, usize>> = vec![HashMap::new(); 24 * 7];
 
         // Count commands by hour and day of week
         for cmd in &filtered_commands {
-            let hour = cmd.timestamp.hour() as usize;
-            let day_of_week = self.weekday_to_index(cmd.timestamp.weekday());
+            let (hour, weekday) = self.local_hour_and_day(&cmd.timestamp);
+            let hour = hour as usize;
+            let day_of_week = self.weekday_to_index(weekday);
 
             activity_grid[hour][day_of_week] += 1;
             max_count = max_count.max(activity_grid[hour][day_of_week]);
+
+            let cell = &mut cells[hour * 7 + day_of_week];
+            cell.command_count += 1;
+            cell.dangerous_count += usize::from(cmd.is_dangerous);
+            cell.experiment_count += usize::from(cmd.is_experiment);
+            *cell_commands[hour * 7 + day_of_week]
+                .entry(cmd.command.as_str())
+                .or_insert(0) += 1;
+        }
+
+        for (cell, commands) in cells.iter_mut().zip(cell_commands) {
+            let mut top: Vec<(String, usize)> = commands
+                .into_iter()
+                .map(|(command, count)| (command.to_string(), count))
+                .collect();
+            top.sort_by_key(|(command, count)| (std::cmp::Reverse(*count), command.clone()));
+            top.truncate(CELL_TOP_COMMANDS);
+            cell.top_commands = top;
         }
 
         // Normalize to 0.0-1.0 range
@@ -82,6 +399,7 @@ impl HeatmapAnalyzer {
             grid: normalized_grid,
             max_activity: max_count as f32,
             total_commands: filtered_commands.len(),
+            cells,
         }
     }
 
@@ -166,26 +484,26 @@ impl HeatmapAnalyzer {
     pub fn analyze_work_patterns(&self, commands: &[Command]) -> WorkPatternAnalysis {
         let mut weekday_commands = 0;
         let mut weekend_commands = 0;
-        let mut work_hours_commands = 0; // 9 AM to 5 PM
-        let mut late_night_commands = 0; // 10 PM to 6 AM
+        let mut work_hours_commands = 0;
+        let mut late_night_commands = 0;
 
         for cmd in commands {
-            let hour = cmd.timestamp.hour();
-            let weekday = cmd.timestamp.weekday();
+            let (hour, weekday) = self.local_hour_and_day(&cmd.timestamp);
 
             // Weekday vs weekend
-            match weekday {
-                Weekday::Sat | Weekday::Sun => weekend_commands += 1,
-                _ => weekday_commands += 1,
+            if self.schedule.is_weekend(weekday) {
+                weekend_commands += 1;
+            } else {
+                weekday_commands += 1;
             }
 
             // Work hours vs other times
-            if (9..17).contains(&hour) {
+            if self.schedule.is_work_hour(hour) {
                 work_hours_commands += 1;
             }
 
             // Late night activity
-            if !(6..22).contains(&hour) {
+            if self.schedule.is_late_night(hour) {
                 late_night_commands += 1;
             }
         }
@@ -215,6 +533,7 @@ impl HeatmapAnalyzer {
             },
             most_active_day: self.find_most_active_day(commands),
             most_active_hour: self.find_most_active_hour(commands),
+            schedule: self.schedule.clone(),
         }
     }
 
@@ -243,32 +562,30 @@ impl HeatmapAnalyzer {
         }
     }
 
+    /// Busiest local weekday, the one closest to Monday on a tie.
     fn find_most_active_day(&self, commands: &[Command]) -> Weekday {
-        let mut day_counts = HashMap::new();
+        let mut day_counts = [0; 7];
 
         for cmd in commands {
-            *day_counts.entry(cmd.timestamp.weekday()).or_insert(0) += 1;
+            let (_, weekday) = self.local_hour_and_day(&cmd.timestamp);
+            day_counts[self.weekday_to_index(weekday)] += 1;
         }
 
-        day_counts
-            .into_iter()
-            .max_by_key(|(_, count)| *count)
-            .map(|(day, _)| day)
+        busiest(&day_counts)
+            .map(|day| self.index_to_weekday(day))
             .unwrap_or(Weekday::Mon)
     }
 
+    /// Busiest local hour, the earliest one on a tie.
     fn find_most_active_hour(&self, commands: &[Command]) -> u32 {
-        let mut hour_counts = HashMap::new();
+        let mut hour_counts = [0; 24];
 
         for cmd in commands {
-            *hour_counts.entry(cmd.timestamp.hour()).or_insert(0) += 1;
+            let (hour, _) = self.local_hour_and_day(&cmd.timestamp);
+            hour_counts[hour as usize] += 1;
         }
 
-        hour_counts
-            .into_iter()
-            .max_by_key(|(_, count)| *count)
-            .map(|(hour, _)| hour)
-            .unwrap_or(12)
+        busiest(&hour_counts).map(|hour| hour as u32).unwrap_or(12)
     }
 }
 
@@ -280,4 +597,6 @@ pub struct WorkPatternAnalysis {
     pub late_night_ratio: f32,
     pub most_active_day: Weekday,
     pub most_active_hour: u32,
+    /// The windows the ratios were computed against
+    pub schedule: WorkSchedule,
 }
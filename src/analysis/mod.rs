@@ -3,7 +3,10 @@ pub mod danger;
 pub mod experiment_detector;
 pub mod heatmap;
 pub mod network_analyzer;
+pub mod normalize;
 pub mod package_tracker;
+pub mod projects;
+pub mod sessions;
 pub mod stats;
 
 // Re-export commonly used analyzers
@@ -20,4 +23,6 @@ pub use network_analyzer::NetworkAnalyzer;
 #[allow(unused_imports)]
 pub use package_tracker::PackageTracker;
 #[allow(unused_imports)]
+pub use projects::ProjectAnalyzer;
+#[allow(unused_imports)]
 pub use stats::StatsAnalyzer;
@@ -9,6 +9,7 @@ pub struct NetworkAnalysis {
     pub unique_endpoints: usize,
     pub protocol_breakdown: HashMap<String, usize>,
     pub security_issues: Vec<SecurityIssue>,
+    /// Every endpoint, busiest first
     pub top_endpoints: Vec<EndpointStats>,
     pub connection_patterns: Vec<ConnectionPattern>,
 }
@@ -41,6 +42,8 @@ pub struct EndpointStats {
     pub first_seen: DateTime<Utc>,
     pub last_seen: DateTime<Utc>,
     pub is_secure: bool,
+    /// Matches `trusted_network_hosts`, so it raises no security issues
+    pub is_trusted: bool,
     pub success_rate: f32,
 }
 
@@ -52,7 +55,10 @@ pub struct ConnectionPattern {
     pub risk_level: SecuritySeverity,
 }
 
-pub struct NetworkAnalyzer;
+pub struct NetworkAnalyzer {
+    /// Host names, or `*.domain` suffix wildcards, lowercased
+    trusted_hosts: Vec<String>,
+}
 
 impl Default for NetworkAnalyzer {
     fn default() -> Self {
@@ -62,7 +68,40 @@ impl Default for NetworkAnalyzer {
 
 impl NetworkAnalyzer {
     pub fn new() -> Self {
-        Self
+        Self {
+            trusted_hosts: Vec::new(),
+        }
+    }
+
+    pub fn from_config(config: &crate::config::Config) -> Self {
+        Self::with_trusted_hosts(&config.trusted_network_hosts)
+    }
+
+    /// Raise no security issues for endpoints on these hosts; they're still counted.
+    pub fn with_trusted_hosts(hosts: &[String]) -> Self {
+        Self {
+            trusted_hosts: hosts
+                .iter()
+                .map(|host| host.trim().to_lowercase())
+                .filter(|host| !host.is_empty())
+                .collect(),
+        }
+    }
+
+    /// Whether the host of a normalized `scheme://host[:port]` endpoint is trusted.
+    pub fn is_trusted_endpoint(&self, endpoint: &str) -> bool {
+        let Some(host) = endpoint_host(endpoint) else {
+            return false;
+        };
+        let host = host.to_lowercase();
+        self.trusted_hosts
+            .iter()
+            .any(|pattern| match pattern.strip_prefix("*.") {
+                Some(domain) => host
+                    .strip_suffix(domain)
+                    .is_some_and(|sub| sub.len() > 1 && sub.ends_with('.')),
+                None => host == *pattern,
+            })
     }
 
     pub fn analyze_network_activity(&self, commands: &[Command]) -> NetworkAnalysis {
@@ -71,24 +110,83 @@ impl NetworkAnalyzer {
             .filter(|cmd| !cmd.network_endpoints.is_empty())
             .collect();
 
-        let mut endpoint_stats = HashMap::new();
         let mut protocol_counts = HashMap::new();
-
-        // Collect endpoint statistics
         for cmd in &network_commands {
             for endpoint in &cmd.network_endpoints {
-                let protocol = self.extract_protocol(endpoint);
-                *protocol_counts.entry(protocol.clone()).or_insert(0) += 1;
+                *protocol_counts
+                    .entry(self.extract_protocol(endpoint))
+                    .or_insert(0) += 1;
+            }
+        }
+        let endpoint_stats = self.collect_endpoint_stats(network_commands.iter().copied());
+
+        let mut top_endpoints: Vec<_> = endpoint_stats.into_values().collect();
+        top_endpoints.sort_by(|a, b| b.usage_count.cmp(&a.usage_count));
+
+        let security_issues = self.identify_security_issues(&network_commands);
+        let connection_patterns = self.analyze_connection_patterns(&network_commands);
+
+        NetworkAnalysis {
+            total_network_commands: network_commands.len(),
+            unique_endpoints: top_endpoints.len(),
+            protocol_breakdown: protocol_counts,
+            security_issues,
+            top_endpoints,
+            connection_patterns,
+        }
+    }
 
+    /// Endpoint stats per `host_id`, busiest endpoint first, so it's clear
+    /// which environment talked to what. Commands without a host count as `local`.
+    pub fn endpoints_by_host(&self, commands: &[Command]) -> HashMap<String, Vec<EndpointStats>> {
+        let mut by_host: HashMap<&str, Vec<&Command>> = HashMap::new();
+        for cmd in commands
+            .iter()
+            .filter(|cmd| !cmd.network_endpoints.is_empty())
+        {
+            let host = if cmd.host_id.is_empty() {
+                "local"
+            } else {
+                cmd.host_id.as_str()
+            };
+            by_host.entry(host).or_default().push(cmd);
+        }
+
+        by_host
+            .into_iter()
+            .map(|(host, host_commands)| {
+                let mut endpoints: Vec<_> = self
+                    .collect_endpoint_stats(host_commands.into_iter())
+                    .into_values()
+                    .collect();
+                endpoints.sort_by(|a, b| {
+                    b.usage_count
+                        .cmp(&a.usage_count)
+                        .then_with(|| a.endpoint.cmp(&b.endpoint))
+                });
+                (host.to_string(), endpoints)
+            })
+            .collect()
+    }
+
+    /// Usage and first/last sighting of every endpoint `commands` contacted.
+    fn collect_endpoint_stats<'a>(
+        &self,
+        commands: impl Iterator<Item = &'a Command>,
+    ) -> HashMap<String, EndpointStats> {
+        let mut endpoint_stats = HashMap::new();
+        for cmd in commands {
+            for endpoint in &cmd.network_endpoints {
                 let stats = endpoint_stats
                     .entry(endpoint.clone())
                     .or_insert(EndpointStats {
                         endpoint: endpoint.clone(),
-                        protocol: protocol.clone(),
+                        protocol: self.extract_protocol(endpoint),
                         usage_count: 0,
                         first_seen: cmd.timestamp,
                         last_seen: cmd.timestamp,
                         is_secure: self.is_secure_endpoint(endpoint),
+                        is_trusted: self.is_trusted_endpoint(endpoint),
                         success_rate: 0.0,
                     });
 
@@ -102,22 +200,7 @@ impl NetworkAnalyzer {
         for stats in endpoint_stats.values_mut() {
             stats.success_rate = 0.95; // Placeholder
         }
-
-        let mut top_endpoints: Vec<_> = endpoint_stats.into_values().collect();
-        top_endpoints.sort_by(|a, b| b.usage_count.cmp(&a.usage_count));
-        top_endpoints.truncate(20);
-
-        let security_issues = self.identify_security_issues(&network_commands);
-        let connection_patterns = self.analyze_connection_patterns(&network_commands);
-
-        NetworkAnalysis {
-            total_network_commands: network_commands.len(),
-            unique_endpoints: top_endpoints.len(),
-            protocol_breakdown: protocol_counts,
-            security_issues,
-            top_endpoints,
-            connection_patterns,
-        }
+        endpoint_stats
     }
 
     fn extract_protocol(&self, endpoint: &str) -> String {
@@ -129,6 +212,12 @@ impl NetworkAnalyzer {
             "SSH".to_string()
         } else if endpoint.starts_with("db://") {
             "Database".to_string()
+        } else if endpoint.starts_with("ftp://") {
+            "FTP".to_string()
+        } else if endpoint.starts_with("telnet://") {
+            "Telnet".to_string()
+        } else if endpoint.starts_with("tcp://") {
+            "TCP".to_string()
         } else if endpoint.contains(":22") {
             "SSH".to_string()
         } else if endpoint.contains(":80") {
@@ -141,6 +230,10 @@ impl NetworkAnalyzer {
     }
 
     fn is_secure_endpoint(&self, endpoint: &str) -> bool {
+        if crate::history::detector::is_insecure_endpoint(endpoint) {
+            return false;
+        }
+
         endpoint.starts_with("https://")
             || endpoint.starts_with("ssh://")
             || endpoint.contains(":22")
@@ -150,11 +243,13 @@ impl NetworkAnalyzer {
     fn identify_security_issues(&self, commands: &[&Command]) -> Vec<SecurityIssue> {
         let mut issues = Vec::new();
 
-        // Check for insecure HTTP usage
+        // Check for cleartext protocols (http, telnet, ftp)
         let mut insecure_http_commands = Vec::new();
         for cmd in commands {
             for endpoint in &cmd.network_endpoints {
-                if endpoint.starts_with("http://") {
+                if crate::history::detector::is_insecure_endpoint(endpoint)
+                    && !self.is_trusted_endpoint(endpoint)
+                {
                     insecure_http_commands.push(cmd.command.clone());
                     break;
                 }
@@ -165,12 +260,12 @@ impl NetworkAnalyzer {
             issues.push(SecurityIssue {
                 issue_type: "Insecure HTTP".to_string(),
                 description: format!(
-                    "{} commands using insecure HTTP protocol",
+                    "{} commands using insecure cleartext protocols",
                     insecure_http_commands.len()
                 ),
                 severity: SecuritySeverity::Medium,
                 affected_commands: insecure_http_commands,
-                recommendation: "Use HTTPS instead of HTTP for secure communication".to_string(),
+                recommendation: "Use HTTPS, SSH or SFTP instead of cleartext protocols".to_string(),
             });
         }
 
@@ -198,7 +293,7 @@ impl NetworkAnalyzer {
         let mut suspicious_endpoints = Vec::new();
         for cmd in commands {
             for endpoint in &cmd.network_endpoints {
-                if self.is_suspicious_endpoint(endpoint) {
+                if self.is_suspicious_endpoint(endpoint) && !self.is_trusted_endpoint(endpoint) {
                     suspicious_endpoints.push(cmd.command.clone());
                     break;
                 }
@@ -351,3 +446,18 @@ impl NetworkAnalyzer {
         score.clamp(0.0, 1.0)
     }
 }
+
+/// The host part of `scheme://host[:port]`, brackets kept for IPv6.
+pub(crate) fn endpoint_host(endpoint: &str) -> Option<&str> {
+    let (_, rest) = endpoint.split_once("://")?;
+    let authority = rest.split('/').next().unwrap_or(rest);
+    let authority = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let host = if authority.starts_with('[') {
+        authority.split_inclusive(']').next().unwrap_or(authority)
+    } else {
+        authority.split(':').next().unwrap_or(authority)
+    };
+    (!host.is_empty()).then_some(host)
+}
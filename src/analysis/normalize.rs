@@ -0,0 +1,196 @@
+/// Placeholder for a quoted argument, such as a commit message.
+pub const STRING_ARG: &str = "<str>";
+/// Placeholder for one or more file system paths.
+pub const PATH_ARG: &str = "<path>";
+/// Placeholder for a plain number.
+pub const NUMBER_ARG: &str = "N";
+
+/// Tools whose first argument picks what they do, so it's worth keeping.
+/// Anything else's first argument is data, like the host in `ssh prod`.
+const MULTI_COMMAND_TOOLS: &[&str] = &[
+    "apt",
+    "apt-get",
+    "brew",
+    "cargo",
+    "composer",
+    "conda",
+    "dnf",
+    "docker",
+    "docker-compose",
+    "gcloud",
+    "gh",
+    "git",
+    "go",
+    "gradle",
+    "helm",
+    "journalctl",
+    "kubectl",
+    "make",
+    "minikube",
+    "mvn",
+    "npm",
+    "pacman",
+    "pip",
+    "pip3",
+    "pipenv",
+    "pnpm",
+    "podman",
+    "poetry",
+    "rustup",
+    "snap",
+    "systemctl",
+    "terraform",
+    "tmux",
+    "vagrant",
+    "yarn",
+    "yum",
+];
+
+/// The shape of `command` for grouping runs that only differ in their
+/// arguments: the tool and its subcommand are kept as typed, quoted arguments,
+/// paths and numbers become placeholders, and a run of paths becomes one.
+/// `git commit -m "fix"` and `git commit -m 'typo'` both give
+/// `git commit -m <str>`.
+pub fn normalize_command(command: &str) -> String {
+    let mut words: Vec<String> = Vec::new();
+    let mut tool = String::new();
+    let mut position = 0;
+
+    for (word, quoted) in split_words(command) {
+        let normalized = match position {
+            // `sudo` doesn't take the tool's place
+            0 if word == "sudo" => {
+                words.push(word);
+                continue;
+            }
+            0 => {
+                tool = word.clone();
+                word
+            }
+            1 if !quoted && is_subcommand(&tool, &word) => word,
+            _ => normalize_argument(&word, quoted),
+        };
+        position += 1;
+
+        // Collapse runs of the same placeholder, e.g. several paths
+        let is_placeholder = [STRING_ARG, PATH_ARG, NUMBER_ARG].contains(&normalized.as_str());
+        if is_placeholder && words.last() == Some(&normalized) {
+            continue;
+        }
+        words.push(normalized);
+    }
+
+    words.join(" ")
+}
+
+/// Just the tool and its subcommand, with every argument dropped: leading
+/// `VAR=value` assignments go, a tool run by path keeps only its file name, and
+/// a `sudo` in front stays. A subcommand is only kept after a known
+/// multi-command tool, and only when `keep_subcommand` accepts it. `/opt/bin/kubectl get pods -n prod` gives
+/// `kubectl get`.
+pub fn strip_arguments(command: &str, keep_subcommand: impl Fn(&str) -> bool) -> String {
+    let mut rest = split_words(command)
+        .into_iter()
+        .skip_while(|(word, quoted)| !quoted && word.contains('='))
+        .peekable();
+
+    let mut words = Vec::new();
+    if let Some((sudo, _)) = rest.next_if(|(word, _)| word == "sudo") {
+        words.push(sudo);
+    }
+    let tool = match rest.next() {
+        Some((tool, _)) => tool.rsplit('/').next().unwrap_or(&tool).to_string(),
+        None => return words.join(" "),
+    };
+    words.push(tool.clone());
+    if let Some((word, false)) = rest.next() {
+        if is_subcommand(&tool, &word) && keep_subcommand(&word) {
+            words.push(word);
+        }
+    }
+
+    words.join(" ")
+}
+
+fn normalize_argument(word: &str, quoted: bool) -> String {
+    // Keep the option name of `--file=/tmp/x` and normalize only its value
+    if let Some((flag, value)) = word.split_once('=') {
+        if flag.starts_with('-') && !flag.contains(['"', '\'']) {
+            return format!("{}={}", flag, normalize_argument(value, quoted));
+        }
+    }
+
+    if quoted {
+        STRING_ARG.to_string()
+    } else if !word.is_empty() && word.chars().all(|c| c.is_ascii_digit()) {
+        NUMBER_ARG.to_string()
+    } else if word.contains('/') || word.starts_with('~') {
+        PATH_ARG.to_string()
+    } else {
+        word.to_string()
+    }
+}
+
+/// Plain words like `commit` or `port-forward` after a tool from
+/// [`MULTI_COMMAND_TOOLS`], not flags, paths or values.
+fn is_subcommand(tool: &str, word: &str) -> bool {
+    let name = tool.rsplit('/').next().unwrap_or(tool);
+    MULTI_COMMAND_TOOLS.contains(&name)
+        && word.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Splits on unquoted whitespace like a shell would, dropping the quotes, and
+/// marks which words had a quoted part.
+fn split_words(command: &str) -> Vec<(String, bool)> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut quoted = false;
+    let mut in_word = false;
+    let mut chars = command.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' | '"' => {
+                in_word = true;
+                quoted = true;
+                while let Some(inner) = chars.next() {
+                    if inner == c {
+                        break;
+                    }
+                    if inner == '\\' && c == '"' {
+                        if let Some(escaped) = chars.next() {
+                            word.push(escaped);
+                        }
+                        continue;
+                    }
+                    word.push(inner);
+                }
+            }
+            '\\' => {
+                in_word = true;
+                if let Some(escaped) = chars.next() {
+                    word.push(escaped);
+                }
+            }
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push((std::mem::take(&mut word), quoted));
+                    quoted = false;
+                    in_word = false;
+                }
+            }
+            c => {
+                in_word = true;
+                word.push(c);
+            }
+        }
+    }
+    if in_word {
+        words.push((word, quoted));
+    }
+
+    words
+}
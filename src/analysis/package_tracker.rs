@@ -1,4 +1,5 @@
 use chrono::{DateTime, Utc};
+use std::cmp::Ordering;
 use std::collections::HashMap;
 
 use crate::history::Command;
@@ -32,6 +33,15 @@ pub struct PackageStats {
     pub versions_seen: Vec<String>,
 }
 
+/// One install/remove/update of a package, used for the per-package timeline.
+#[derive(Debug, Clone)]
+pub struct PackageEvent {
+    pub timestamp: DateTime<Utc>,
+    pub manager: String,
+    pub action: String,
+    pub version: Option<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct PackageTrend {
     pub package: String,
@@ -39,6 +49,8 @@ pub struct PackageTrend {
     pub trend_type: TrendType,
     pub frequency: usize,
     pub time_span_days: i64,
+    /// For `QuickRemoval`, hours between the install and the removal
+    pub hours_to_removal: Option<i64>,
 }
 
 #[derive(Debug, Clone)]
@@ -103,6 +115,27 @@ impl PackageTracker {
         }
     }
 
+    /// Every operation on `package` across all managers, oldest first.
+    pub fn package_timeline(&self, commands: &[Command], package: &str) -> Vec<PackageEvent> {
+        let mut events: Vec<PackageEvent> = commands
+            .iter()
+            .flat_map(|cmd| {
+                cmd.packages_used
+                    .iter()
+                    .filter(|pkg| pkg.name == package)
+                    .map(|pkg| PackageEvent {
+                        timestamp: cmd.timestamp,
+                        manager: pkg.manager.clone(),
+                        action: pkg.action.clone(),
+                        version: pkg.version.clone(),
+                    })
+            })
+            .collect();
+
+        events.sort_by_key(|event| event.timestamp);
+        events
+    }
+
     fn analyze_package_managers(&self, commands: &[&Command]) -> Vec<ManagerStats> {
         let mut manager_data: HashMap<
             String,
@@ -160,9 +193,13 @@ impl PackageTracker {
         let mut managers: Vec<_> = manager_data
             .into_iter()
             .map(|(manager, (installs, removes, updates, packages))| {
+                // Every package is kept so the UI can page through all of them
                 let mut top_packages: Vec<_> = packages.into_values().collect();
-                top_packages.sort_by(|a, b| b.install_count.cmp(&a.install_count));
-                top_packages.truncate(10);
+                top_packages.sort_by(|a, b| {
+                    b.install_count
+                        .cmp(&a.install_count)
+                        .then_with(|| a.name.cmp(&b.name))
+                });
 
                 ManagerStats {
                     manager,
@@ -214,6 +251,7 @@ impl PackageTracker {
                     trend_type: TrendType::FrequentInstalls,
                     frequency: installs,
                     time_span_days: self.calculate_time_span(&timeline),
+                    hours_to_removal: None,
                 });
             }
 
@@ -225,11 +263,14 @@ impl PackageTracker {
                     trend_type: TrendType::RepeatedInstalls,
                     frequency: installs - removes,
                     time_span_days: self.calculate_time_span(&timeline),
+                    hours_to_removal: None,
                 });
             }
 
             // Quick removal (installed then quickly removed)
-            if let Some(quick_removal) = self.detect_quick_removal(&timeline) {
+            if let Some(quick_removal) =
+                self.detect_quick_removal(&manager, &package_name, &timeline)
+            {
                 trends.push(quick_removal);
             }
         }
@@ -250,7 +291,15 @@ impl PackageTracker {
         (*last - *first).num_days()
     }
 
-    fn detect_quick_removal(&self, timeline: &[(DateTime<Utc>, String)]) -> Option<PackageTrend> {
+    fn detect_quick_removal(
+        &self,
+        manager: &str,
+        package: &str,
+        timeline: &[(DateTime<Utc>, String)],
+    ) -> Option<PackageTrend> {
+        let mut timeline = timeline.to_vec();
+        timeline.sort_by_key(|(time, _)| *time);
+
         // Look for install followed by remove within 24 hours
         for i in 0..timeline.len().saturating_sub(1) {
             if timeline[i].1 == "install" {
@@ -258,13 +307,13 @@ impl PackageTracker {
                     if timeline[j].1 == "remove" || timeline[j].1 == "uninstall" {
                         let time_diff = timeline[j].0 - timeline[i].0;
                         if time_diff.num_hours() <= 24 {
-                            // Quick removal detected
                             return Some(PackageTrend {
-                                package: "".to_string(), // Would need package name from context
-                                manager: "".to_string(), // Would need manager from context
+                                package: package.to_string(),
+                                manager: manager.to_string(),
                                 trend_type: TrendType::QuickRemoval,
                                 frequency: 1,
                                 time_span_days: 0,
+                                hours_to_removal: Some(time_diff.num_hours()),
                             });
                         }
                         break;
@@ -277,25 +326,42 @@ impl PackageTracker {
 
     fn detect_version_conflicts(&self, commands: &[&Command]) -> Vec<VersionConflict> {
         let mut conflicts = Vec::new();
-        let mut package_versions: HashMap<(String, String), Vec<String>> = HashMap::new();
+        // Every versioned install per package, oldest first
+        let mut install_history: HashMap<(String, String), Vec<String>> = HashMap::new();
 
-        // Collect all versions seen for each package
-        for cmd in commands {
+        let mut chronological = commands.to_vec();
+        chronological.sort_by_key(|cmd| cmd.timestamp);
+
+        for cmd in chronological {
             for package in &cmd.packages_used {
+                if matches!(package.action.as_str(), "remove" | "uninstall") {
+                    continue;
+                }
                 if let Some(version) = &package.version {
                     let key = (package.manager.clone(), package.name.clone());
-                    let versions = package_versions.entry(key).or_default();
-                    if !versions.contains(version) {
-                        versions.push(version.clone());
-                    }
+                    install_history
+                        .entry(key)
+                        .or_default()
+                        .push(version.clone());
                 }
             }
         }
 
+        let package_versions = install_history.into_iter().map(|(key, history)| {
+            let downgraded = self.has_version_downgrade(&history);
+            let mut versions: Vec<String> = Vec::new();
+            for version in history {
+                if !versions.contains(&version) {
+                    versions.push(version);
+                }
+            }
+            (key, (versions, downgraded))
+        });
+
         // Identify conflicts
-        for ((manager, package), versions) in package_versions {
+        for ((manager, package), (versions, downgraded)) in package_versions {
             if versions.len() > 1 {
-                let conflict_type = if self.has_version_downgrade(&versions) {
+                let conflict_type = if downgraded {
                     ConflictType::DowngradeDetected
                 } else {
                     ConflictType::MultipleVersions
@@ -328,13 +394,18 @@ impl PackageTracker {
         conflicts
     }
 
-    fn has_version_downgrade(&self, versions: &[String]) -> bool {
-        // Simple heuristic: if we see a lower version after a higher one
-        // This is a simplified check and would need more sophisticated version parsing
-        versions.len() > 1
-            && versions
-                .iter()
-                .any(|v| v.contains("0.") || v.starts_with("1."))
+    /// Whether any install (in the given chronological order) is older than a
+    /// version installed before it.
+    pub fn has_version_downgrade(&self, versions: &[String]) -> bool {
+        let mut highest: Option<&str> = None;
+        for version in versions {
+            match highest.map(|high| compare_versions(version, high)) {
+                Some(Ordering::Less) => return true,
+                Some(Ordering::Equal) => {}
+                Some(Ordering::Greater) | None => highest = Some(version),
+            }
+        }
+        false
     }
 
     fn generate_recommendations(
@@ -449,3 +520,32 @@ impl PackageTracker {
         score.clamp(0.0, 1.0)
     }
 }
+
+/// Semver ordering when both sides parse (after an optional `v`), otherwise a
+/// component-wise compare where numeric parts compare as numbers.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parse = |v: &str| semver::Version::parse(v.trim().trim_start_matches('v')).ok();
+    match (parse(a), parse(b)) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        _ => version_parts(a).cmp(&version_parts(b)),
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum VersionPart {
+    Number(u64),
+    Text(String),
+}
+
+fn version_parts(version: &str) -> Vec<VersionPart> {
+    version
+        .trim()
+        .trim_start_matches('v')
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| match part.parse() {
+            Ok(number) => VersionPart::Number(number),
+            Err(_) => VersionPart::Text(part.to_string()),
+        })
+        .collect()
+}
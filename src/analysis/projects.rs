@@ -0,0 +1,178 @@
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::history::Command;
+
+/// Bucket for commands recorded without a working directory.
+pub const UNKNOWN_PROJECT: &str = "unknown";
+
+const TOP_TOOLS: usize = 5;
+
+#[derive(Debug, Clone)]
+pub struct ProjectAnalysis {
+    /// Most commands first
+    pub projects: Vec<ProjectStats>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProjectStats {
+    /// Last path component of the root, or [`UNKNOWN_PROJECT`]
+    pub name: String,
+    /// Repo root or project directory; `None` for the unknown bucket
+    pub root: Option<String>,
+    pub command_count: usize,
+    pub dangerous_count: usize,
+    /// Most used first words, ties broken by name
+    pub top_tools: Vec<(String, usize)>,
+    pub last_active: DateTime<Utc>,
+}
+
+impl ProjectStats {
+    pub fn danger_ratio(&self) -> f64 {
+        if self.command_count == 0 {
+            0.0
+        } else {
+            self.dangerous_count as f64 / self.command_count as f64
+        }
+    }
+}
+
+/// Groups commands into projects by working directory. A directory inside a
+/// git checkout belongs to the repo root; otherwise anything under
+/// `~/projects/<name>` belongs to `<name>`, and any other directory stands alone.
+/// Only directories on this machine are looked up on disk, and each one only
+/// once per analyzer.
+pub struct ProjectAnalyzer {
+    projects_dir: Option<PathBuf>,
+    /// Roots already resolved, by local directory
+    roots: HashMap<String, String>,
+}
+
+impl Default for ProjectAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProjectAnalyzer {
+    pub fn new() -> Self {
+        Self {
+            projects_dir: dirs::home_dir().map(|home| home.join("projects")),
+            roots: HashMap::new(),
+        }
+    }
+
+    /// Use `dir` instead of `~/projects` as the directory holding projects.
+    #[allow(dead_code)]
+    pub fn with_projects_dir(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            projects_dir: Some(dir.into()),
+            roots: HashMap::new(),
+        }
+    }
+
+    pub fn analyze_projects(&mut self, commands: &[Command]) -> ProjectAnalysis {
+        let mut groups: HashMap<Option<String>, Vec<&Command>> = HashMap::new();
+
+        for cmd in commands {
+            let root = match cmd.working_directory.as_deref() {
+                // Another machine's paths mean nothing on this disk
+                Some(dir) if !dir.is_empty() && !is_local(cmd) => {
+                    Some(self.directory_root(Path::new(dir)))
+                }
+                Some(dir) if !dir.is_empty() => Some(match self.roots.get(dir) {
+                    Some(root) => root.clone(),
+                    None => {
+                        let root = self.project_root(Path::new(dir));
+                        self.roots.insert(dir.to_string(), root.clone());
+                        root
+                    }
+                }),
+                _ => None,
+            };
+            groups.entry(root).or_default().push(cmd);
+        }
+
+        let mut projects: Vec<ProjectStats> = groups
+            .into_iter()
+            .map(|(root, commands)| Self::project_stats(root, &commands))
+            .collect();
+        projects.sort_by(|a, b| {
+            b.command_count
+                .cmp(&a.command_count)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+
+        ProjectAnalysis { projects }
+    }
+
+    /// The directory `dir` is grouped under.
+    pub fn project_root(&self, dir: &Path) -> String {
+        if let Some(repo) = dir.ancestors().find(|p| p.join(".git").exists()) {
+            return repo.display().to_string();
+        }
+        self.directory_root(dir)
+    }
+
+    /// The directory `dir` is grouped under without looking at the disk.
+    fn directory_root(&self, dir: &Path) -> String {
+        if let Some(projects_dir) = &self.projects_dir {
+            if let Ok(rest) = dir.strip_prefix(projects_dir) {
+                if let Some(top) = rest.components().next() {
+                    return projects_dir.join(top).display().to_string();
+                }
+            }
+        }
+
+        dir.display().to_string()
+    }
+
+    fn project_stats(root: Option<String>, commands: &[&Command]) -> ProjectStats {
+        let mut tools: HashMap<&str, usize> = HashMap::new();
+        for cmd in commands {
+            let mut words = cmd.command.split_whitespace();
+            let mut tool = words.next();
+            if tool == Some("sudo") {
+                tool = words.next();
+            }
+            if let Some(tool) = tool {
+                *tools.entry(tool).or_insert(0) += 1;
+            }
+        }
+
+        let mut top_tools: Vec<(String, usize)> = tools
+            .into_iter()
+            .map(|(tool, count)| (tool.to_string(), count))
+            .collect();
+        top_tools.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        top_tools.truncate(TOP_TOOLS);
+
+        let name = match &root {
+            Some(root) => Path::new(root)
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| root.clone()),
+            None => UNKNOWN_PROJECT.to_string(),
+        };
+
+        ProjectStats {
+            name,
+            root,
+            command_count: commands.len(),
+            dangerous_count: commands.iter().filter(|cmd| cmd.is_dangerous).count(),
+            top_tools,
+            last_active: commands
+                .iter()
+                .map(|cmd| cmd.timestamp)
+                .max()
+                .unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+/// Whether the command ran on this machine rather than over `ssh:`, in
+/// `docker:` or another remote target.
+fn is_local(command: &Command) -> bool {
+    !command.host_id.contains(':')
+}
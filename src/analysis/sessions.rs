@@ -0,0 +1,115 @@
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+
+use crate::history::Command;
+
+/// Gap between two commands after which they count as separate sessions.
+pub const DEFAULT_IDLE_MINUTES: i64 = 30;
+
+/// A run of commands from one shell session with no long idle gaps.
+#[derive(Debug, Clone)]
+pub struct CommandSession<'a> {
+    pub id: String,
+    /// Oldest first
+    pub commands: Vec<&'a Command>,
+}
+
+impl CommandSession<'_> {
+    pub fn start(&self) -> DateTime<Utc> {
+        self.commands[0].timestamp
+    }
+
+    pub fn end(&self) -> DateTime<Utc> {
+        self.commands[self.commands.len() - 1].timestamp
+    }
+}
+
+/// What the Sessions tab lists about one session, kept so a frame doesn't
+/// split the history again.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionSummary {
+    pub id: String,
+    pub start: DateTime<Utc>,
+    pub duration_minutes: i64,
+    pub command_count: usize,
+    pub dangerous_count: usize,
+    pub experiment_count: usize,
+    pub host_count: usize,
+}
+
+impl SessionSummary {
+    pub fn new(session: &CommandSession) -> Self {
+        let start = session.start();
+        let hosts: std::collections::HashSet<&str> = session
+            .commands
+            .iter()
+            .map(|c| c.host_id.as_str())
+            .collect();
+        Self {
+            id: session.id.clone(),
+            start,
+            duration_minutes: (session.end() - start).num_minutes(),
+            command_count: session.commands.len(),
+            dangerous_count: session.commands.iter().filter(|c| c.is_dangerous).count(),
+            experiment_count: session.commands.iter().filter(|c| c.is_experiment).count(),
+            host_count: hosts.len(),
+        }
+    }
+}
+
+/// Every session `split_sessions` finds, most recent first.
+pub fn summarize_sessions(commands: &[Command], idle: Duration) -> Vec<SessionSummary> {
+    let mut summaries: Vec<SessionSummary> = split_sessions(commands, idle)
+        .iter()
+        .map(SessionSummary::new)
+        .collect();
+    summaries.reverse();
+    summaries
+}
+
+/// Groups commands by `session_id`, then splits each group wherever
+/// consecutive commands are more than `idle` apart. Plain history files share a
+/// single session id, so this is what actually separates sittings. The first
+/// part keeps the stored id and later parts get `-2`, `-3`, ... appended.
+/// Sessions come back ordered by start time.
+pub fn split_sessions(commands: &[Command], idle: Duration) -> Vec<CommandSession<'_>> {
+    let mut by_id: HashMap<&str, Vec<&Command>> = HashMap::new();
+    for cmd in commands {
+        by_id.entry(cmd.session_id.as_str()).or_default().push(cmd);
+    }
+
+    let mut sessions = Vec::new();
+    for (id, mut group) in by_id {
+        group.sort_by_key(|cmd| cmd.timestamp);
+
+        let mut current: Vec<&Command> = Vec::new();
+        let mut part = 1;
+        for cmd in group {
+            if let Some(last) = current.last() {
+                if cmd.timestamp - last.timestamp > idle {
+                    sessions.push(CommandSession {
+                        id: part_id(id, part),
+                        commands: std::mem::take(&mut current),
+                    });
+                    part += 1;
+                }
+            }
+            current.push(cmd);
+        }
+        sessions.push(CommandSession {
+            id: part_id(id, part),
+            commands: current,
+        });
+    }
+
+    sessions.sort_by(|a, b| a.start().cmp(&b.start()).then_with(|| a.id.cmp(&b.id)));
+    sessions
+}
+
+pub(crate) fn part_id(id: &str, part: usize) -> String {
+    if part == 1 {
+        id.to_string()
+    } else {
+        format!("{}-{}", id, part)
+    }
+}
@@ -1,6 +1,9 @@
-use chrono::{DateTime, Datelike, Duration, Timelike, Utc};
-use std::collections::HashMap;
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Timelike, Utc};
+use chrono_tz::Tz;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
+use super::normalize::normalize_command;
+use super::sessions::{part_id, split_sessions, DEFAULT_IDLE_MINUTES};
 use crate::history::Command;
 
 #[derive(Debug, Clone)]
@@ -14,6 +17,9 @@ pub struct CommandStats {
     pub most_active_hour: u32,
     pub most_active_day: chrono::Weekday,
     pub top_commands: Vec<CommandFrequency>,
+    /// See [`StatsTally::failure_hotspots`]
+    pub failure_hotspots: Vec<(String, usize, f32)>,
+    pub dangerous_commands: usize,
     pub shell_distribution: HashMap<String, usize>,
     pub host_distribution: HashMap<String, usize>,
 }
@@ -21,11 +27,31 @@ pub struct CommandStats {
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub struct CommandFrequency {
+    /// What runs are grouped by: the normalized command, or the exact text
     pub command: String,
+    /// The group's most recent run, as typed
+    pub example: String,
     pub count: usize,
     pub percentage: f32,
     pub last_used: DateTime<Utc>,
     pub average_duration: Option<f64>,
+    /// Runs that exited non-zero
+    pub failures: usize,
+    /// Runs per day (UTC)
+    pub daily_counts: BTreeMap<NaiveDate, usize>,
+}
+
+impl CommandFrequency {
+    /// Runs on each of the `days` days up to and including `end`, oldest first.
+    pub fn runs_until(&self, end: NaiveDate, days: usize) -> Vec<u64> {
+        (0..days as i64)
+            .rev()
+            .map(|back| {
+                let date = end - Duration::days(back);
+                self.daily_counts.get(&date).copied().unwrap_or(0) as u64
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -47,6 +73,10 @@ pub struct ProductivityStats {
     pub improvement_suggestions: Vec<String>,
     pub peak_hours: Vec<u32>,
     pub workflow_patterns: Vec<WorkflowPattern>,
+    /// Consecutive active days up to today, or up to yesterday while today
+    /// has no commands yet
+    pub current_streak_days: usize,
+    pub longest_streak_days: usize,
 }
 
 #[derive(Debug, Clone)]
@@ -58,7 +88,12 @@ pub struct WorkflowPattern {
     pub description: String,
 }
 
-pub struct StatsAnalyzer;
+pub struct StatsAnalyzer {
+    idle_threshold: Duration,
+    normalize_commands: bool,
+    /// Zone that decides which day a command falls on; the local one when unset
+    timezone: Option<Tz>,
+}
 
 impl Default for StatsAnalyzer {
     fn default() -> Self {
@@ -68,130 +103,131 @@ impl Default for StatsAnalyzer {
 
 impl StatsAnalyzer {
     pub fn new() -> Self {
-        Self
+        Self {
+            idle_threshold: Duration::minutes(DEFAULT_IDLE_MINUTES),
+            normalize_commands: true,
+            timezone: None,
+        }
     }
 
-    pub fn analyze_commands(&self, commands: &[Command]) -> CommandStats {
-        if commands.is_empty() {
-            return CommandStats {
-                total_commands: 0,
-                unique_commands: 0,
-                success_rate: 0.0,
-                average_duration: None,
-                commands_per_day: 0.0,
-                most_active_hour: 12,
-                most_active_day: chrono::Weekday::Mon,
-                top_commands: Vec::new(),
-                shell_distribution: HashMap::new(),
-                host_distribution: HashMap::new(),
-            };
+    /// Put commands on days in `tz` rather than the local zone.
+    #[allow(dead_code)]
+    pub fn with_timezone(self, tz: Tz) -> Self {
+        Self {
+            timezone: Some(tz),
+            ..self
         }
+    }
 
-        let total_commands = commands.len();
-        let unique_commands = self.count_unique_commands(commands);
-        let success_rate = self.calculate_success_rate(commands);
-        let average_duration = self.calculate_average_duration(commands);
-        let commands_per_day = self.calculate_commands_per_day(commands);
-        let most_active_hour = self.find_most_active_hour(commands);
-        let most_active_day = self.find_most_active_day(commands);
-        let top_commands = self.get_top_commands(commands, 10);
-        let shell_distribution = self.get_shell_distribution(commands);
-        let host_distribution = self.get_host_distribution(commands);
+    /// Split sessions after `minutes` without a command instead of the default.
+    #[allow(dead_code)]
+    pub fn with_idle_threshold(minutes: i64) -> Self {
+        Self {
+            idle_threshold: Duration::minutes(minutes),
+            ..Self::new()
+        }
+    }
 
-        CommandStats {
-            total_commands,
-            unique_commands,
-            success_rate,
-            average_duration,
-            commands_per_day,
-            most_active_hour,
-            most_active_day,
-            top_commands,
-            shell_distribution,
-            host_distribution,
+    /// Session split and top command grouping as configured.
+    pub fn from_config(config: &crate::config::Config) -> Self {
+        Self {
+            idle_threshold: Duration::minutes(config.session_idle_minutes),
+            normalize_commands: config.normalize_top_commands,
+            // The heatmap already warns about a zone that doesn't parse
+            timezone: config
+                .timezone
+                .as_deref()
+                .and_then(|name| name.parse::<Tz>().ok()),
         }
     }
 
-    pub fn analyze_sessions(&self, commands: &[Command]) -> SessionStats {
-        let mut sessions: HashMap<String, Vec<&Command>> = HashMap::new();
+    /// Commands run on each of the `days` days up to and including today (UTC),
+    /// oldest first. Days without commands are kept as zeros.
+    pub fn commands_per_day_series(
+        &self,
+        commands: &[Command],
+        days: usize,
+    ) -> Vec<(NaiveDate, usize)> {
+        self.commands_per_day_until(commands, Utc::now().date_naive(), days)
+    }
 
-        // Group commands by session
-        for cmd in commands {
-            sessions
-                .entry(cmd.session_id.clone())
-                .or_default()
-                .push(cmd);
+    /// `commands_per_day_series` ending on `end` instead of today.
+    pub fn commands_per_day_until(
+        &self,
+        commands: &[Command],
+        end: NaiveDate,
+        days: usize,
+    ) -> Vec<(NaiveDate, usize)> {
+        if days == 0 {
+            return Vec::new();
         }
+        let start = end - Duration::days(days as i64 - 1);
 
-        let total_sessions = sessions.len();
-
-        if total_sessions == 0 {
-            return SessionStats {
-                total_sessions: 0,
-                average_session_length: 0.0,
-                average_commands_per_session: 0.0,
-                longest_session: Duration::zero(),
-                most_productive_session: String::new(),
-                session_distribution: HashMap::new(),
-            };
+        let mut counts = vec![0; days];
+        for cmd in commands {
+            let date = cmd.timestamp.date_naive();
+            if (start..=end).contains(&date) {
+                counts[(date - start).num_days() as usize] += 1;
+            }
         }
 
-        let mut session_lengths = Vec::new();
-        let mut session_command_counts = Vec::new();
-        let mut longest_session = Duration::zero();
-        let mut most_productive_session = String::new();
-        let mut max_commands = 0;
-        let mut shell_distribution = HashMap::new();
+        counts
+            .into_iter()
+            .enumerate()
+            .map(|(offset, count)| (start + Duration::days(offset as i64), count))
+            .collect()
+    }
 
-        for (session_id, session_commands) in &sessions {
-            let command_count = session_commands.len();
-            session_command_counts.push(command_count);
+    pub fn analyze_commands(&self, commands: &[Command]) -> CommandStats {
+        self.tally(commands).command_stats()
+    }
 
-            if command_count > max_commands {
-                max_commands = command_count;
-                most_productive_session = session_id.clone();
-            }
+    /// Commands that failed at least once, with their failure count and rate.
+    #[allow(dead_code)]
+    pub fn failure_hotspots(&self, commands: &[Command]) -> Vec<(String, usize, f32)> {
+        self.tally(commands).failure_hotspots(usize::MAX)
+    }
 
-            // Calculate session duration
-            if let (Some(first), Some(last)) = (
-                session_commands.iter().map(|c| c.timestamp).min(),
-                session_commands.iter().map(|c| c.timestamp).max(),
-            ) {
-                let duration = last - first;
-                session_lengths.push(duration.num_minutes() as f64);
+    /// Commands run exactly once.
+    #[allow(dead_code)]
+    pub fn one_off_commands(&self, commands: &[Command]) -> Vec<CommandFrequency> {
+        self.rarely_used(commands, 1)
+    }
 
-                if duration > longest_session {
-                    longest_session = duration;
-                }
-            }
+    /// See [`StatsTally::rarely_used`].
+    #[allow(dead_code)]
+    pub fn rarely_used(&self, commands: &[Command], max_runs: usize) -> Vec<CommandFrequency> {
+        self.tally(commands).rarely_used(max_runs)
+    }
 
-            // Track shell distribution
-            for cmd in session_commands {
-                *shell_distribution.entry(cmd.shell.clone()).or_insert(0) += 1;
-            }
-        }
+    /// See [`StatsTally::slowest_commands`].
+    #[allow(dead_code)]
+    pub fn slowest_commands(&self, commands: &[Command], limit: usize) -> Vec<CommandFrequency> {
+        self.tally(commands).slowest_commands(limit)
+    }
 
-        let average_session_length = if !session_lengths.is_empty() {
-            session_lengths.iter().sum::<f64>() / session_lengths.len() as f64
-        } else {
-            0.0
-        };
+    /// See [`StatsTally::duration_histogram`].
+    #[allow(dead_code)]
+    pub fn duration_histogram(&self, commands: &[Command]) -> DurationHistogram {
+        self.tally(commands).duration_histogram()
+    }
 
-        let average_commands_per_session = if !session_command_counts.is_empty() {
-            session_command_counts.iter().sum::<usize>() as f32
-                / session_command_counts.len() as f32
-        } else {
-            0.0
-        };
+    #[allow(dead_code)]
+    pub fn analyze_sessions(&self, commands: &[Command]) -> SessionStats {
+        self.tally(commands).session_stats()
+    }
 
-        SessionStats {
-            total_sessions,
-            average_session_length,
-            average_commands_per_session,
-            longest_session,
-            most_productive_session,
-            session_distribution: shell_distribution,
+    /// Running totals over `commands`, which new commands can then be folded
+    /// into with [`StatsTally::observe`] instead of going over them all again.
+    pub fn tally(&self, commands: &[Command]) -> StatsTally {
+        let mut ordered: Vec<&Command> = commands.iter().collect();
+        ordered.sort_by_key(|cmd| cmd.timestamp);
+
+        let mut tally = StatsTally::new(self.idle_threshold, self.normalize_commands);
+        for cmd in ordered {
+            tally.observe(cmd);
         }
+        tally
     }
 
     pub fn analyze_productivity(&self, commands: &[Command]) -> ProductivityStats {
@@ -200,6 +236,8 @@ impl StatsAnalyzer {
         let improvement_suggestions = self.generate_improvement_suggestions(commands);
         let peak_hours = self.identify_peak_hours(commands);
         let workflow_patterns = self.detect_workflow_patterns(commands);
+        let today = self.local_date(&Utc::now());
+        let (current_streak_days, longest_streak_days) = self.activity_streaks(commands, today);
 
         ProductivityStats {
             productivity_score,
@@ -207,6 +245,43 @@ impl StatsAnalyzer {
             improvement_suggestions,
             peak_hours,
             workflow_patterns,
+            current_streak_days,
+            longest_streak_days,
+        }
+    }
+
+    /// The current and the longest run of consecutive days with at least one
+    /// command, in local dates. The current run may end on `today` or the day
+    /// before, since a day without commands yet isn't over.
+    pub fn activity_streaks(&self, commands: &[Command], today: NaiveDate) -> (usize, usize) {
+        let days: BTreeSet<NaiveDate> = commands
+            .iter()
+            .map(|cmd| self.local_date(&cmd.timestamp))
+            .collect();
+
+        let mut longest = 0;
+        let mut run = 0;
+        let mut previous: Option<NaiveDate> = None;
+        for &day in &days {
+            run = match previous {
+                Some(prev) if day - prev == Duration::days(1) => run + 1,
+                _ => 1,
+            };
+            longest = longest.max(run);
+            previous = Some(day);
+        }
+
+        let current = match previous {
+            Some(last) if last == today || last == today - Duration::days(1) => run,
+            _ => 0,
+        };
+        (current, longest)
+    }
+
+    fn local_date(&self, timestamp: &DateTime<Utc>) -> NaiveDate {
+        match self.timezone {
+            Some(tz) => timestamp.with_timezone(&tz).date_naive(),
+            None => timestamp.with_timezone(&Local).date_naive(),
         }
     }
 
@@ -237,102 +312,6 @@ impl StatsAnalyzer {
         }
     }
 
-    fn calculate_commands_per_day(&self, commands: &[Command]) -> f32 {
-        if commands.is_empty() {
-            return 0.0;
-        }
-
-        let first = commands.iter().map(|c| c.timestamp).min().unwrap();
-        let last = commands.iter().map(|c| c.timestamp).max().unwrap();
-        let days = (last - first).num_days().max(1) as f32;
-
-        commands.len() as f32 / days
-    }
-
-    fn find_most_active_hour(&self, commands: &[Command]) -> u32 {
-        let mut hour_counts = HashMap::new();
-
-        for cmd in commands {
-            *hour_counts.entry(cmd.timestamp.hour()).or_insert(0) += 1;
-        }
-
-        hour_counts
-            .into_iter()
-            .max_by_key(|(_, count)| *count)
-            .map(|(hour, _)| hour)
-            .unwrap_or(12)
-    }
-
-    fn find_most_active_day(&self, commands: &[Command]) -> chrono::Weekday {
-        let mut day_counts = HashMap::new();
-
-        for cmd in commands {
-            *day_counts.entry(cmd.timestamp.weekday()).or_insert(0) += 1;
-        }
-
-        day_counts
-            .into_iter()
-            .max_by_key(|(_, count)| *count)
-            .map(|(day, _)| day)
-            .unwrap_or(chrono::Weekday::Mon)
-    }
-
-    fn get_top_commands(&self, commands: &[Command], limit: usize) -> Vec<CommandFrequency> {
-        let mut command_stats: HashMap<String, (usize, DateTime<Utc>, Vec<u64>)> = HashMap::new();
-
-        for cmd in commands {
-            let entry =
-                command_stats
-                    .entry(cmd.command.clone())
-                    .or_insert((0, cmd.timestamp, Vec::new()));
-            entry.0 += 1; // count
-            entry.1 = entry.1.max(cmd.timestamp); // last used
-            if let Some(duration) = cmd.duration {
-                entry.2.push(duration); // durations
-            }
-        }
-
-        let total_commands = commands.len() as f32;
-        let mut frequencies: Vec<_> = command_stats
-            .into_iter()
-            .map(|(command, (count, last_used, durations))| {
-                let average_duration = if durations.is_empty() {
-                    None
-                } else {
-                    Some(durations.iter().sum::<u64>() as f64 / durations.len() as f64)
-                };
-
-                CommandFrequency {
-                    command,
-                    count,
-                    percentage: (count as f32 / total_commands) * 100.0,
-                    last_used,
-                    average_duration,
-                }
-            })
-            .collect();
-
-        frequencies.sort_by(|a, b| b.count.cmp(&a.count));
-        frequencies.truncate(limit);
-        frequencies
-    }
-
-    fn get_shell_distribution(&self, commands: &[Command]) -> HashMap<String, usize> {
-        let mut distribution = HashMap::new();
-        for cmd in commands {
-            *distribution.entry(cmd.shell.clone()).or_insert(0) += 1;
-        }
-        distribution
-    }
-
-    fn get_host_distribution(&self, commands: &[Command]) -> HashMap<String, usize> {
-        let mut distribution = HashMap::new();
-        for cmd in commands {
-            *distribution.entry(cmd.host_id.clone()).or_insert(0) += 1;
-        }
-        distribution
-    }
-
     fn calculate_productivity_score(&self, commands: &[Command]) -> f32 {
         if commands.is_empty() {
             return 0.0;
@@ -439,7 +418,7 @@ impl StatsAnalyzer {
         }
 
         // Check for repetitive commands that could be aliased
-        let top_commands = self.get_top_commands(commands, 5);
+        let top_commands = self.tally(commands).top_commands(5);
         for cmd_freq in &top_commands {
             if cmd_freq.command.len() > 20 && cmd_freq.count > 5 {
                 suggestions.push(format!(
@@ -522,26 +501,14 @@ impl StatsAnalyzer {
     ) -> HashMap<Vec<String>, usize> {
         let mut sequences = HashMap::new();
 
-        // Group commands by session first
-        let mut sessions: HashMap<String, Vec<&Command>> = HashMap::new();
-        for cmd in commands {
-            sessions
-                .entry(cmd.session_id.clone())
-                .or_default()
-                .push(cmd);
-        }
-
-        for session_commands in sessions.values() {
-            if session_commands.len() < length {
+        // Sequences never span two sessions
+        for session in split_sessions(commands, self.idle_threshold) {
+            if session.commands.len() < length {
                 continue;
             }
 
-            // Sort by timestamp within session
-            let mut sorted_commands = session_commands.clone();
-            sorted_commands.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
-
             // Extract sequences
-            for window in sorted_commands.windows(length) {
+            for window in session.commands.windows(length) {
                 let sequence: Vec<String> = window
                     .iter()
                     .map(|cmd| {
@@ -583,3 +550,415 @@ impl StatsAnalyzer {
         }
     }
 }
+
+/// Runs with a known duration, by how long they took.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DurationHistogram {
+    pub under_100ms: usize,
+    pub under_1s: usize,
+    pub under_10s: usize,
+    pub over_10s: usize,
+}
+
+impl DurationHistogram {
+    fn add(&mut self, duration_ms: u64) {
+        match duration_ms {
+            0..100 => self.under_100ms += 1,
+            100..1_000 => self.under_1s += 1,
+            1_000..10_000 => self.under_10s += 1,
+            _ => self.over_10s += 1,
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.under_100ms + self.under_1s + self.under_10s + self.over_10s
+    }
+
+    /// Each bucket with its label, fastest first.
+    pub fn buckets(&self) -> [(&'static str, usize); 4] {
+        [
+            ("<100ms", self.under_100ms),
+            ("<1s", self.under_1s),
+            ("<10s", self.under_10s),
+            ("≥10s", self.over_10s),
+        ]
+    }
+}
+
+/// The aggregates behind `CommandStats` and `SessionStats`, kept so commands
+/// imported while the TUI is open can be added one at a time.
+#[derive(Debug, Clone)]
+pub struct StatsTally {
+    idle_threshold: Duration,
+    normalize_commands: bool,
+    total: usize,
+    unique: HashSet<String>,
+    with_exit_code: usize,
+    successful: usize,
+    duration_total: u64,
+    durations: usize,
+    duration_histogram: DurationHistogram,
+    dangerous: usize,
+    first_seen: Option<DateTime<Utc>>,
+    last_seen: Option<DateTime<Utc>>,
+    hour_counts: [usize; 24],
+    day_counts: [usize; 7],
+    by_command: HashMap<String, CommandTally>,
+    shells: HashMap<String, usize>,
+    hosts: HashMap<String, usize>,
+    /// Parts of each session id split at idle gaps, oldest first
+    sessions: HashMap<String, Vec<SessionPart>>,
+    /// The same totals over each shell's commands on their own
+    by_shell: HashMap<String, StatsTally>,
+}
+
+#[derive(Debug, Clone)]
+struct CommandTally {
+    count: usize,
+    example: String,
+    last_used: DateTime<Utc>,
+    duration_total: u64,
+    durations: usize,
+    daily_counts: BTreeMap<NaiveDate, usize>,
+    /// Runs with a known exit code, and the ones of those that weren't 0
+    with_exit_code: usize,
+    failures: usize,
+}
+
+#[derive(Debug, Clone)]
+struct SessionPart {
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    commands: usize,
+}
+
+impl StatsTally {
+    fn new(idle_threshold: Duration, normalize_commands: bool) -> Self {
+        Self {
+            idle_threshold,
+            normalize_commands,
+            total: 0,
+            unique: HashSet::new(),
+            with_exit_code: 0,
+            successful: 0,
+            duration_total: 0,
+            durations: 0,
+            duration_histogram: DurationHistogram::default(),
+            dangerous: 0,
+            first_seen: None,
+            last_seen: None,
+            hour_counts: [0; 24],
+            day_counts: [0; 7],
+            by_command: HashMap::new(),
+            shells: HashMap::new(),
+            hosts: HashMap::new(),
+            sessions: HashMap::new(),
+            by_shell: HashMap::new(),
+        }
+    }
+
+    /// Fold in one more command. Commands have to come oldest first: one older
+    /// than the newest already seen in its session isn't placed in the right
+    /// session part, so callers tally the whole history again instead.
+    pub fn observe(&mut self, cmd: &Command) {
+        let key = if self.normalize_commands {
+            normalize_command(&cmd.command)
+        } else {
+            cmd.command.clone()
+        };
+        let (idle_threshold, normalize_commands) = (self.idle_threshold, self.normalize_commands);
+        self.by_shell
+            .entry(cmd.shell.clone())
+            .or_insert_with(|| StatsTally::new(idle_threshold, normalize_commands))
+            .add(cmd, key.clone());
+        self.add(cmd, key);
+    }
+
+    /// [`CommandStats`] over each shell's commands on their own, busiest shell
+    /// first.
+    pub fn shell_stats(&self) -> Vec<(String, CommandStats)> {
+        let mut shells: Vec<(String, CommandStats)> = self
+            .by_shell
+            .iter()
+            .map(|(shell, tally)| (shell.clone(), tally.command_stats()))
+            .collect();
+        shells
+            .sort_by_key(|(shell, stats)| (std::cmp::Reverse(stats.total_commands), shell.clone()));
+        shells
+    }
+
+    /// `observe` without the per-shell totals, grouping `cmd` under `key`.
+    fn add(&mut self, cmd: &Command, key: String) {
+        self.total += 1;
+        if !self.unique.contains(&cmd.command) {
+            self.unique.insert(cmd.command.clone());
+        }
+        if let Some(code) = cmd.exit_code {
+            self.with_exit_code += 1;
+            if code == 0 {
+                self.successful += 1;
+            }
+        }
+        if let Some(duration) = cmd.duration {
+            self.duration_total += duration;
+            self.durations += 1;
+            self.duration_histogram.add(duration);
+        }
+        self.dangerous += usize::from(cmd.is_dangerous);
+        self.first_seen = Some(
+            self.first_seen
+                .map_or(cmd.timestamp, |t| t.min(cmd.timestamp)),
+        );
+        self.last_seen = Some(
+            self.last_seen
+                .map_or(cmd.timestamp, |t| t.max(cmd.timestamp)),
+        );
+        self.hour_counts[cmd.timestamp.hour() as usize] += 1;
+        self.day_counts[cmd.timestamp.weekday().num_days_from_monday() as usize] += 1;
+
+        let entry = self.by_command.entry(key).or_insert_with(|| CommandTally {
+            count: 0,
+            example: cmd.command.clone(),
+            last_used: cmd.timestamp,
+            duration_total: 0,
+            durations: 0,
+            daily_counts: BTreeMap::new(),
+            with_exit_code: 0,
+            failures: 0,
+        });
+        entry.count += 1;
+        *entry
+            .daily_counts
+            .entry(cmd.timestamp.date_naive())
+            .or_insert(0) += 1;
+        if cmd.timestamp >= entry.last_used {
+            // The most recent run is the example
+            entry.example = cmd.command.clone();
+            entry.last_used = cmd.timestamp;
+        }
+        if let Some(duration) = cmd.duration {
+            entry.duration_total += duration;
+            entry.durations += 1;
+        }
+        if let Some(code) = cmd.exit_code {
+            entry.with_exit_code += 1;
+            entry.failures += usize::from(code != 0);
+        }
+
+        *self.shells.entry(cmd.shell.clone()).or_insert(0) += 1;
+        *self.hosts.entry(cmd.host_id.clone()).or_insert(0) += 1;
+
+        let parts = self.sessions.entry(cmd.session_id.clone()).or_default();
+        match parts.last_mut() {
+            Some(part) if cmd.timestamp - part.end <= self.idle_threshold => {
+                part.end = part.end.max(cmd.timestamp);
+                part.commands += 1;
+            }
+            _ => parts.push(SessionPart {
+                start: cmd.timestamp,
+                end: cmd.timestamp,
+                commands: 1,
+            }),
+        }
+    }
+
+    pub fn command_stats(&self) -> CommandStats {
+        let (Some(first), Some(last)) = (self.first_seen, self.last_seen) else {
+            return CommandStats {
+                total_commands: 0,
+                unique_commands: 0,
+                success_rate: 0.0,
+                average_duration: None,
+                commands_per_day: 0.0,
+                most_active_hour: 12,
+                most_active_day: chrono::Weekday::Mon,
+                top_commands: Vec::new(),
+                failure_hotspots: Vec::new(),
+                dangerous_commands: 0,
+                shell_distribution: HashMap::new(),
+                host_distribution: HashMap::new(),
+            };
+        };
+
+        CommandStats {
+            total_commands: self.total,
+            unique_commands: self.unique.len(),
+            // Assume success if no exit codes
+            success_rate: if self.with_exit_code == 0 {
+                1.0
+            } else {
+                self.successful as f32 / self.with_exit_code as f32
+            },
+            average_duration: average(self.duration_total, self.durations),
+            commands_per_day: self.total as f32 / (last - first).num_days().max(1) as f32,
+            // Earliest hour and the day closest to Monday on a tie
+            most_active_hour: busiest(&self.hour_counts)
+                .map(|hour| hour as u32)
+                .unwrap_or(12),
+            most_active_day: busiest(&self.day_counts)
+                .and_then(|day| chrono::Weekday::try_from(day as u8).ok())
+                .unwrap_or(chrono::Weekday::Mon),
+            top_commands: self.top_commands(10),
+            failure_hotspots: self.failure_hotspots(10),
+            dangerous_commands: self.dangerous,
+            shell_distribution: self.shells.clone(),
+            host_distribution: self.hosts.clone(),
+        }
+    }
+
+    /// The `limit` most run commands, alphabetical on a tie.
+    pub fn top_commands(&self, limit: usize) -> Vec<CommandFrequency> {
+        let mut ranked: Vec<(&String, &CommandTally)> = self.by_command.iter().collect();
+        ranked.sort_by(|a, b| b.1.count.cmp(&a.1.count).then_with(|| a.0.cmp(b.0)));
+        ranked.truncate(limit);
+
+        ranked
+            .into_iter()
+            .map(|(command, tally)| self.frequency(command, tally))
+            .collect()
+    }
+
+    /// Commands run at most `max_runs` times: fewest runs first, then the
+    /// longest unused, then alphabetical.
+    pub fn rarely_used(&self, max_runs: usize) -> Vec<CommandFrequency> {
+        let mut rare: Vec<(&String, &CommandTally)> = self
+            .by_command
+            .iter()
+            .filter(|(_, tally)| tally.count <= max_runs)
+            .collect();
+        rare.sort_by_key(|(command, tally)| (tally.count, tally.last_used, *command));
+
+        rare.into_iter()
+            .map(|(command, tally)| self.frequency(command, tally))
+            .collect()
+    }
+
+    /// The `limit` commands with the longest average duration, alphabetical
+    /// on a tie. Commands never timed are left out rather than counted as instant.
+    pub fn slowest_commands(&self, limit: usize) -> Vec<CommandFrequency> {
+        let mut timed: Vec<(&String, &CommandTally, f64)> = self
+            .by_command
+            .iter()
+            .filter_map(|(command, tally)| {
+                average(tally.duration_total, tally.durations)
+                    .map(|average| (command, tally, average))
+            })
+            .collect();
+        timed.sort_by(|a, b| b.2.total_cmp(&a.2).then_with(|| a.0.cmp(b.0)));
+        timed.truncate(limit);
+
+        timed
+            .into_iter()
+            .map(|(command, tally, _)| self.frequency(command, tally))
+            .collect()
+    }
+
+    /// How long the timed runs took.
+    pub fn duration_histogram(&self) -> DurationHistogram {
+        self.duration_histogram
+    }
+
+    fn frequency(&self, command: &str, tally: &CommandTally) -> CommandFrequency {
+        CommandFrequency {
+            command: command.to_string(),
+            example: tally.example.clone(),
+            count: tally.count,
+            percentage: (tally.count as f32 / self.total as f32) * 100.0,
+            last_used: tally.last_used,
+            average_duration: average(tally.duration_total, tally.durations),
+            failures: tally.failures,
+            daily_counts: tally.daily_counts.clone(),
+        }
+    }
+
+    /// Up to `limit` commands that failed at least once, as (command, failures,
+    /// failure rate), most failures first, then highest rate, then alphabetical.
+    /// Runs without an exit code don't count towards the rate either way.
+    pub fn failure_hotspots(&self, limit: usize) -> Vec<(String, usize, f32)> {
+        let mut failing: Vec<(&String, &CommandTally)> = self
+            .by_command
+            .iter()
+            .filter(|(_, tally)| tally.failures > 0)
+            .collect();
+        // With equal failures, fewer runs means the higher rate
+        failing.sort_by_key(|(command, tally)| {
+            (
+                std::cmp::Reverse(tally.failures),
+                tally.with_exit_code,
+                *command,
+            )
+        });
+        failing.truncate(limit);
+
+        failing
+            .into_iter()
+            .map(|(command, tally)| {
+                (
+                    command.clone(),
+                    tally.failures,
+                    tally.failures as f32 / tally.with_exit_code as f32,
+                )
+            })
+            .collect()
+    }
+
+    /// Sessions as `split_sessions` cuts them.
+    pub fn session_stats(&self) -> SessionStats {
+        let parts: Vec<(&str, usize, &SessionPart)> = self
+            .sessions
+            .iter()
+            .flat_map(|(id, parts)| {
+                parts
+                    .iter()
+                    .enumerate()
+                    .map(move |(index, part)| (id.as_str(), index + 1, part))
+            })
+            .collect();
+
+        // The busiest session, the one that started first on a tie
+        let Some(&(busiest_id, busiest_part, _)) = parts.iter().min_by(|a, b| {
+            b.2.commands
+                .cmp(&a.2.commands)
+                .then(a.2.start.cmp(&b.2.start))
+                .then_with(|| part_id(a.0, a.1).cmp(&part_id(b.0, b.1)))
+        }) else {
+            return SessionStats {
+                total_sessions: 0,
+                average_session_length: 0.0,
+                average_commands_per_session: 0.0,
+                longest_session: Duration::zero(),
+                most_productive_session: String::new(),
+                session_distribution: HashMap::new(),
+            };
+        };
+
+        let lengths = parts.iter().map(|(_, _, part)| part.end - part.start);
+        SessionStats {
+            total_sessions: parts.len(),
+            average_session_length: lengths
+                .clone()
+                .map(|length| length.num_minutes() as f64)
+                .sum::<f64>()
+                / parts.len() as f64,
+            average_commands_per_session: self.total as f32 / parts.len() as f32,
+            longest_session: lengths.max().unwrap_or_else(Duration::zero),
+            most_productive_session: part_id(busiest_id, busiest_part),
+            session_distribution: self.shells.clone(),
+        }
+    }
+}
+
+fn average(total: u64, count: usize) -> Option<f64> {
+    (count > 0).then(|| total as f64 / count as f64)
+}
+
+/// Index of the highest of `counts`, ordered hour-first or Monday-first, taking the
+/// lowest index on a tie so the pick doesn't change between refreshes. `None` when
+/// every count is zero.
+pub(crate) fn busiest(counts: &[usize]) -> Option<usize> {
+    let max = *counts.iter().max()?;
+    if max == 0 {
+        return None;
+    }
+    counts.iter().position(|&count| count == max)
+}
@@ -1,9 +1,32 @@
 use anyhow::Result;
-
-use crate::analysis::stats::{CommandStats, ProductivityStats, SessionStats, StatsAnalyzer};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::{HashMap, HashSet};
+
+use crate::about::AboutInfo;
+use crate::analysis::alias_suggest::{AliasAnalysis, AliasSuggester, AliasSuggestion};
+use crate::analysis::experiment_detector::{ExperimentAnalysis, ExperimentDetector};
+use crate::analysis::heatmap::{
+    HeatmapAnalyzer, HeatmapData, TimeRange, ViewMode, WorkPatternAnalysis,
+};
+use crate::analysis::network_analyzer::{EndpointStats, NetworkAnalysis, NetworkAnalyzer};
+use crate::analysis::normalize::normalize_command;
+use crate::analysis::package_tracker::{PackageAnalysis, PackageTracker};
+use crate::analysis::projects::{ProjectAnalysis, ProjectAnalyzer};
+use crate::analysis::stats::{
+    CommandStats, ProductivityStats, SessionStats, StatsAnalyzer, StatsTally,
+};
 use crate::config::Config;
-use crate::db::Database;
-use crate::history::{Command, HistoryParser};
+use crate::db::{CommandFilter, Database};
+use crate::docs::DocViewer;
+use crate::history::background::{BackgroundImport, BackgroundReenrich};
+use crate::history::host_aliases::HostAliases;
+use crate::history::watcher::HistoryWatcher;
+use crate::history::{parse_tags, Command, HistoryParser};
+use crate::ui::hosts::HostAnalysis;
+use crate::ui::search_query::SearchQuery;
+
+/// Rows assumed visible in a tab that hasn't been drawn yet.
+const DEFAULT_VIEWPORT_HEIGHT: usize = 20;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Tab {
@@ -18,6 +41,7 @@ pub enum Tab {
     Network,
     Packages,
     Experiments,
+    Projects,
 }
 
 impl Tab {
@@ -34,6 +58,7 @@ impl Tab {
             Tab::Network,
             Tab::Packages,
             Tab::Experiments,
+            Tab::Projects,
         ]
     }
 
@@ -50,8 +75,43 @@ impl Tab {
             Tab::Network => "Network",
             Tab::Packages => "Packages",
             Tab::Experiments => "Experiments",
+            Tab::Projects => "Projects",
         }
     }
+
+    /// Whether the tab takes Shift+R for itself (Recent, Risky, Risk, refresh
+    /// or search text), leaving re-enrichment to the other tabs.
+    pub fn binds_shift_r(&self) -> bool {
+        matches!(
+            self,
+            Tab::Commands | Tab::Search | Tab::Heatmap | Tab::Aliases | Tab::Network
+        )
+    }
+}
+
+/// Text typed into the tag prompt and what it's for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TagInput {
+    pub purpose: TagInputPurpose,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TagInputPurpose {
+    /// Replace the tags of the stored command with this id
+    Edit(i64),
+    /// Show only commands with the tag on the Commands tab
+    Filter,
+}
+
+/// Stored commands that touched one package, newest first.
+#[derive(Debug, Clone)]
+pub struct PackageCommands {
+    pub manager: String,
+    pub name: String,
+    pub commands: Vec<Command>,
+    /// First command shown
+    pub offset: usize,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -81,6 +141,36 @@ pub enum SearchFilter {
     Experiments,
 }
 
+impl SearchFilter {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SearchFilter::None => "None",
+            SearchFilter::Failed => "Failed",
+            SearchFilter::Dangerous => "Dangerous",
+            SearchFilter::Recent => "Recent",
+            SearchFilter::Experiments => "Experiments",
+        }
+    }
+
+    /// The F key that turns this filter on and off.
+    pub fn function_key(&self) -> Option<u8> {
+        match self {
+            SearchFilter::None => None,
+            SearchFilter::Failed => Some(1),
+            SearchFilter::Dangerous => Some(2),
+            SearchFilter::Recent => Some(3),
+            SearchFilter::Experiments => Some(4),
+        }
+    }
+}
+
+/// How the Search tab reads the query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchKind {
+    Fuzzy,
+    Regex,
+}
+
 pub struct App {
     #[allow(dead_code)]
     pub config: Config,
@@ -89,26 +179,96 @@ pub struct App {
     pub current_tab: Tab,
     pub tab_index: usize,
     pub commands: Vec<Command>,
-    pub filtered_commands: Vec<Command>,
+    // Commands tab rows, paged from the database around `scroll_offset`
+    pub command_window: CommandWindow,
+    /// Leaves out stored rows the import filter would skip now
+    stored_filter: CommandFilter,
     pub search_mode: bool,
     pub search_query: String,
     pub search_filter: SearchFilter,
+    pub search_results: Vec<crate::ui::search::SearchMatch>,
+    /// Commands the query matched, including those past the shown results
+    pub search_total: usize,
+    pub search_kind: SearchKind,
+    /// Why the regex query doesn't compile, shown instead of results
+    pub search_error: Option<String>,
     pub help_visible: bool,
+    /// `q` was pressed under `confirm_quit` and the prompt is waiting for y or n
+    pub pending_quit: bool,
+    /// Shift+R was pressed and the prompt is waiting for y or n
+    pub pending_reenrich: bool,
+    /// Version and data locations, while the About overlay is open
+    pub about: Option<AboutInfo>,
+    pub detail_visible: bool,
+    pub detail_command: Option<Command>,
+    /// Text typed into the Dangerous tab's command checker while it's open
+    pub danger_check: Option<String>,
+    pub danger_analyzer: crate::analysis::DangerAnalyzer,
     pub scroll_offset: usize,
     pub selected_index: usize,
     pub stats: AppStats,
     pub sort_by: SortBy,
     pub filter_by: FilterBy,
+    /// Host picked from the Hosts tab; the Commands tab only shows its commands
+    pub host_filter: Option<String>,
+    host_aliases: HostAliases,
+    /// Every host id as stored, before aliasing, for the host filter's query
+    host_ids: Vec<String>,
+    /// Commands tab shows only commands with this tag
+    pub tag_filter: Option<String>,
+    /// Open while tags are typed for the detail command or the Commands filter
+    pub tag_input: Option<TagInput>,
+    pub command_counts: HashMap<String, usize>,
+    /// Per-host aggregates from the database, for the Hosts tab
+    pub hosts: HostAnalysis,
     // Heatmap state
     pub heatmap_time_range: crate::analysis::heatmap::TimeRange,
     pub heatmap_view_mode: crate::analysis::heatmap::ViewMode,
+    pub heatmap_cursor: crate::ui::heatmap::HeatmapCursor,
+    pub heatmap_scale: crate::analysis::heatmap::HeatmapScale,
+    pub heatmap_ramp: crate::ui::heatmap::HeatmapRamp,
+    // Packages state
+    pub package_filter: crate::ui::packages::PackageFilter,
+    pub package_detail_open: bool,
+    /// The stored commands behind the package in the detail popup, once opened
+    pub package_commands: Option<PackageCommands>,
+    // Experiments state
+    pub experiment_detail_open: bool,
+    /// tldr or man when installed, for reading up on a tool with `o`
+    pub doc_viewer: Option<DocViewer>,
+    /// Tool whose page to show once the main loop has handed over the terminal
+    doc_request: Option<String>,
+    pub network_filter: crate::ui::network::NetworkFilter,
+    pub network_sort: crate::ui::network::NetworkSort,
+    pub alias_sort: crate::ui::aliases::AliasSort,
+    pub alias_filter: crate::ui::aliases::AliasFilter,
     // Enhanced analytics
     pub command_stats: Option<CommandStats>,
     pub session_stats: Option<SessionStats>,
     pub productivity_stats: Option<ProductivityStats>,
+    /// What `command_stats` and `session_stats` come from; live imports are
+    /// folded into it, and `None` has the next refresh tally everything again
+    stats_tally: Option<StatsTally>,
     // Performance optimization
-    pub last_analysis_update: std::time::Instant,
-    pub analysis_cache_valid: bool,
+    pub analytics_freshness: AnalyticsFreshness,
+    analyses: CachedAnalyses,
+    // Keeps the project roots it found on disk between refreshes
+    project_analyzer: ProjectAnalyzer,
+    // Pending config write, debounced so rapid key presses don't thrash the disk
+    config_dirty_since: Option<std::time::Instant>,
+    // Transient footer message, e.g. after copying an alias
+    status_message: Option<(String, std::time::Instant)>,
+    // Kept alive so X11/Wayland selections survive after the copy returns
+    clipboard: Option<arboard::Clipboard>,
+    pub theme: crate::ui::theme::Theme,
+    /// The import of history appended since the last launch, until it's done
+    import: Option<BackgroundImport>,
+    /// Rescoring of the stored history, until it's done
+    reenrich: Option<BackgroundReenrich>,
+    /// Imports new history in the background; `None` when `auto_import` is off
+    watcher: Option<HistoryWatcher>,
+    // List rows each tab had room for when last drawn, 0 until then
+    viewport_heights: Vec<std::cell::Cell<usize>>,
 }
 
 #[derive(Debug, Default)]
@@ -120,106 +280,203 @@ pub struct AppStats {
     pub hosts_count: usize,
     pub dangerous_commands: usize,
     pub network_endpoints: usize,
+    #[allow(dead_code)]
     pub packages_used: usize,
     pub experiment_sessions: usize,
+    // What the distinct counts count, so imported commands can be added
+    seen: SeenValues,
 }
 
-impl App {
-    pub async fn new() -> Result<Self> {
-        let config = Config::load_or_create()?;
+#[derive(Debug, Default)]
+struct SeenValues {
+    commands: HashSet<String>,
+    hosts: HashSet<String>,
+    endpoints: HashSet<String>,
+    packages: HashSet<(String, String)>,
+}
 
-        let mut db = Database::new(&config.database_path).await?;
+impl AppStats {
+    /// Count one more command; `total_sessions` is left to the caller, since
+    /// sessions depend on the commands around it.
+    fn observe(&mut self, cmd: &Command) {
+        self.total_commands += 1;
+        self.dangerous_commands += usize::from(cmd.is_dangerous);
+        self.experiment_sessions += usize::from(cmd.is_experiment);
+
+        let seen = &mut self.seen;
+        if !seen.commands.contains(&cmd.command) {
+            seen.commands.insert(cmd.command.clone());
+        }
+        if !seen.hosts.contains(&cmd.host_id) {
+            seen.hosts.insert(cmd.host_id.clone());
+        }
+        for endpoint in &cmd.network_endpoints {
+            if !seen.endpoints.contains(endpoint) {
+                seen.endpoints.insert(endpoint.clone());
+            }
+        }
+        for package in &cmd.packages_used {
+            seen.packages
+                .insert((package.manager.clone(), package.name.clone()));
+        }
 
-        // Parse and import history on first run
-        let parser = HistoryParser::new();
-        let commands = parser.parse_all_histories().await?;
+        self.unique_commands = seen.commands.len();
+        self.hosts_count = seen.hosts.len();
+        self.network_endpoints = seen.endpoints.len();
+        self.packages_used = seen.packages.len();
+    }
+}
 
-        // Store commands in database
-        for command in &commands {
-            db.insert_command(command).await?;
+impl App {
+    pub async fn new(config: Config) -> Result<Self> {
+        let mut db = Database::new(&config.database_path).await?;
+        if let Some(cutoff) = config.retention_cutoff() {
+            db.prune_older_than(cutoff).await?;
         }
 
-        let stats = Self::calculate_stats(&commands);
-
-        let filtered_commands = commands.clone();
+        // Also hides rows imported before a pattern was added
+        let stored_filter = crate::history::filter::ImportFilter::from_config(&config)
+            .exclude_from(CommandFilter::new());
+        let mut commands = db.get_commands_where(&stored_filter).await?;
+        let host_aliases = HostAliases::from_config(&config);
+        host_aliases.apply(&mut commands);
+
+        // Import only history files that changed since the last launch, in the
+        // background so the UI comes up with what's already stored. Only started
+        // once that's loaded, or the rows it adds would be read in twice
+        let parser = HistoryParser::from_config(&config);
+        let history_files: Vec<_> = parser
+            .history_files()
+            .into_iter()
+            .filter(|(_, path)| path.exists())
+            .collect();
+        let import = BackgroundImport::start(&config, history_files)
+            .await
+            .map_err(|err| log::warn!("History import failed to start: {}", err))
+            .ok();
+
+        let stats = Self::calculate_stats(&commands, config.session_idle_minutes);
+        let theme = crate::ui::theme::Theme::from_config(&config.theme);
+        let danger_analyzer = crate::analysis::DangerAnalyzer::from_config(&config);
+        let (hosts, host_ids) = Self::host_analysis(&db, &config, &host_aliases).await?;
 
         // Initialize enhanced analytics
-        let analyzer = StatsAnalyzer::new();
-        let command_stats = Some(analyzer.analyze_commands(&commands));
-        let session_stats = Some(analyzer.analyze_sessions(&commands));
+        let analyzer = StatsAnalyzer::from_config(&config);
+        let stats_tally = analyzer.tally(&commands);
+        let command_stats = Some(stats_tally.command_stats());
+        let session_stats = Some(stats_tally.session_stats());
         let productivity_stats = Some(analyzer.analyze_productivity(&commands));
 
+        let heatmap_time_range =
+            crate::analysis::heatmap::TimeRange::from_name(&config.heatmap_time_range)
+                .unwrap_or(crate::analysis::heatmap::TimeRange::Week);
+        let heatmap_view_mode =
+            crate::analysis::heatmap::ViewMode::from_name(&config.heatmap_view_mode)
+                .unwrap_or(crate::analysis::heatmap::ViewMode::All);
+        let heatmap_scale = crate::analysis::heatmap::HeatmapScale::from_config(&config);
+        let heatmap_ramp = crate::ui::heatmap::HeatmapRamp::from_config(&config);
+        let mut project_analyzer = ProjectAnalyzer::new();
+        let analyses = CachedAnalyses::new(
+            &commands,
+            &stats_tally,
+            &config,
+            heatmap_time_range,
+            heatmap_view_mode,
+            &mut project_analyzer,
+        );
+        let analytics_freshness = AnalyticsFreshness::new(config.analytics_refresh_interval());
+        // Start on the busiest hour of the busiest day
+        let heatmap_cursor = crate::ui::heatmap::HeatmapCursor {
+            hour: analyses.work_patterns.most_active_hour as usize,
+            day: analyses
+                .work_patterns
+                .most_active_day
+                .num_days_from_monday() as usize,
+        };
+
         Ok(Self {
             config,
             db,
             current_tab: Tab::Summary,
             tab_index: 0,
             commands,
-            filtered_commands,
+            command_window: CommandWindow::default(),
+            stored_filter,
             search_mode: false,
             search_query: String::new(),
             search_filter: SearchFilter::None,
+            search_results: Vec::new(),
+            search_total: 0,
+            search_kind: SearchKind::Fuzzy,
+            search_error: None,
             help_visible: false,
+            pending_quit: false,
+            pending_reenrich: false,
+            about: None,
+            detail_visible: false,
+            detail_command: None,
             scroll_offset: 0,
             selected_index: 0,
             stats,
             sort_by: SortBy::Time,
             filter_by: FilterBy::All,
-            // Initialize heatmap state
-            heatmap_time_range: crate::analysis::heatmap::TimeRange::Week,
-            heatmap_view_mode: crate::analysis::heatmap::ViewMode::All,
+            host_filter: None,
+            host_aliases,
+            host_ids,
+            tag_filter: None,
+            tag_input: None,
+            command_counts: HashMap::new(),
+            hosts,
+            danger_check: None,
+            danger_analyzer,
+            // Restore heatmap state from the last session
+            heatmap_time_range,
+            heatmap_view_mode,
+            heatmap_cursor,
+            heatmap_scale,
+            heatmap_ramp,
+            package_filter: crate::ui::packages::PackageFilter::All,
+            package_detail_open: false,
+            package_commands: None,
+            experiment_detail_open: false,
+            doc_viewer: DocViewer::find(),
+            doc_request: None,
+            network_filter: crate::ui::network::NetworkFilter::All,
+            network_sort: crate::ui::network::NetworkSort::Usage,
+            alias_sort: crate::ui::aliases::AliasSort::Impact,
+            alias_filter: crate::ui::aliases::AliasFilter::All,
             // Enhanced analytics
             command_stats,
             session_stats,
             productivity_stats,
+            stats_tally: Some(stats_tally),
             // Performance optimization
-            last_analysis_update: std::time::Instant::now(),
-            analysis_cache_valid: true,
+            analytics_freshness,
+            analyses,
+            project_analyzer,
+            config_dirty_since: None,
+            status_message: None,
+            clipboard: None,
+            theme,
+            import,
+            reenrich: None,
+            // Started once the startup import is done, so the two don't race
+            watcher: None,
+            viewport_heights: Tab::all().iter().map(|_| Default::default()).collect(),
         })
     }
 
-    fn calculate_stats(commands: &[Command]) -> AppStats {
-        let mut unique_commands = std::collections::HashSet::new();
-        let mut hosts = std::collections::HashSet::new();
-        let mut network_endpoints = std::collections::HashSet::new();
-        let mut packages = std::collections::HashSet::new();
-        let mut sessions = std::collections::HashSet::new();
-
-        let mut dangerous_count = 0;
-        let mut experiment_count = 0;
-
+    fn calculate_stats(commands: &[Command], idle_minutes: i64) -> AppStats {
+        let mut stats = AppStats::default();
         for cmd in commands {
-            unique_commands.insert(&cmd.command);
-            hosts.insert(&cmd.host_id);
-            sessions.insert(&cmd.session_id);
-
-            for endpoint in &cmd.network_endpoints {
-                network_endpoints.insert(endpoint);
-            }
-
-            for package in &cmd.packages_used {
-                packages.insert((&package.manager, &package.name));
-            }
-
-            if cmd.is_dangerous {
-                dangerous_count += 1;
-            }
-
-            if cmd.is_experiment {
-                experiment_count += 1;
-            }
-        }
-
-        AppStats {
-            total_commands: commands.len(),
-            unique_commands: unique_commands.len(),
-            total_sessions: sessions.len(),
-            hosts_count: hosts.len(),
-            dangerous_commands: dangerous_count,
-            network_endpoints: network_endpoints.len(),
-            packages_used: packages.len(),
-            experiment_sessions: experiment_count,
+            stats.observe(cmd);
         }
+        stats.total_sessions = crate::analysis::sessions::split_sessions(
+            commands,
+            chrono::Duration::minutes(idle_minutes),
+        )
+        .len();
+        stats
     }
 
     pub fn next_tab(&mut self) {
@@ -245,38 +502,99 @@ impl App {
         self.reset_navigation();
     }
 
+    /// Whether `q` should exit now. Under `confirm_quit` it opens the prompt
+    /// instead and `confirm_quit` decides.
+    pub fn request_quit(&mut self) -> bool {
+        if self.config.confirm_quit {
+            self.pending_quit = true;
+            false
+        } else {
+            true
+        }
+    }
+
+    /// Answer the quit prompt: whether to exit.
+    pub fn confirm_quit(&mut self, confirmed: bool) -> bool {
+        self.pending_quit = false;
+        confirmed
+    }
+
     pub fn toggle_help(&mut self) {
         self.help_visible = !self.help_visible;
     }
 
     pub fn scroll_up(&mut self) {
-        if self.selected_index > 0 {
-            self.selected_index -= 1;
+        if let Some(package_commands) = &mut self.package_commands {
+            package_commands.offset = package_commands.offset.saturating_sub(1);
+            return;
         }
-        // Update scroll offset to keep selection visible
-        if self.selected_index < self.scroll_offset {
-            self.scroll_offset = self.selected_index;
+        if self.current_tab == Tab::Heatmap {
+            self.heatmap_cursor = self.heatmap_cursor.move_by(-1, 0);
+            return;
         }
+        self.move_selection(-1);
     }
 
     pub fn scroll_down(&mut self) {
-        let max_items = self.get_current_items_count();
-        if max_items > 0 && self.selected_index < max_items - 1 {
-            self.selected_index += 1;
+        if let Some(package_commands) = &mut self.package_commands {
+            package_commands.offset = (package_commands.offset + 1)
+                .min(package_commands.commands.len().saturating_sub(1));
+            return;
+        }
+        if self.current_tab == Tab::Heatmap {
+            self.heatmap_cursor = self.heatmap_cursor.move_by(1, 0);
+            return;
         }
-        // Update scroll offset to keep selection visible (assuming 20 visible items)
-        let visible_items = 20;
-        if self.selected_index >= self.scroll_offset + visible_items {
-            self.scroll_offset = self.selected_index - visible_items + 1;
+        self.move_selection(1);
+    }
+
+    /// Move the selection `delta` rows and scroll just enough to keep it on screen.
+    fn move_selection(&mut self, delta: isize) {
+        let scroll = ListScroll {
+            selected: self.selected_index,
+            offset: self.scroll_offset,
+        }
+        .move_by(
+            delta,
+            self.get_current_items_count(),
+            self.viewport_height(),
+        );
+        self.selected_index = scroll.selected;
+        self.scroll_offset = scroll.offset;
+    }
+
+    /// Called while drawing with the number of list rows the current tab shows.
+    pub fn set_viewport_height(&self, height: usize) {
+        if let Some(cell) = self.viewport_heights.get(self.tab_index) {
+            cell.set(height);
+        }
+    }
+
+    fn viewport_height(&self) -> usize {
+        match self
+            .viewport_heights
+            .get(self.tab_index)
+            .map(|cell| cell.get())
+        {
+            Some(height) if height > 0 => height,
+            _ => DEFAULT_VIEWPORT_HEIGHT,
         }
     }
 
     pub fn scroll_left(&mut self) {
+        if self.current_tab == Tab::Heatmap {
+            self.heatmap_cursor = self.heatmap_cursor.move_by(0, -1);
+            return;
+        }
         // Tab-specific left navigation - for now, just move selection up
         self.scroll_up();
     }
 
     pub fn scroll_right(&mut self) {
+        if self.current_tab == Tab::Heatmap {
+            self.heatmap_cursor = self.heatmap_cursor.move_by(0, 1);
+            return;
+        }
         // Tab-specific right navigation - for now, just move selection down
         self.scroll_down();
     }
@@ -287,44 +605,317 @@ impl App {
         } else {
             // Tab-specific enter action
             match self.current_tab {
-                Tab::Commands => {
-                    // Execute selected command or show details
+                Tab::Commands | Tab::Search | Tab::Dangerous => {
+                    let selected = self.selected_command().cloned();
+                    self.toggle_detail(selected);
                 }
                 Tab::Sessions => {
                     // Jump to selected session
                 }
-                Tab::Search => {
-                    // Execute search or show command details
-                }
                 Tab::Hosts => {
-                    // Connect to selected host or show host details
+                    let host = self
+                        .hosts
+                        .hosts
+                        .get(self.selected_index)
+                        .map(|host| host.host_id.clone());
+                    if let Some(host) = host {
+                        self.show_host_commands(host);
+                    }
+                }
+                Tab::Packages => {
+                    if self.package_commands.is_some() {
+                        self.package_commands = None;
+                    } else {
+                        self.package_detail_open =
+                            !self.package_detail_open && !self.package_rows().is_empty();
+                    }
                 }
-                Tab::Dangerous => {
-                    // Show command details or safety information
+                Tab::Experiments => {
+                    self.experiment_detail_open = !self.experiment_detail_open
+                        && !self.analyses.experiments.tool_exploration.is_empty();
                 }
                 _ => {}
             }
         }
     }
 
+    /// The command under the cursor: the one in the detail popup while it's
+    /// open, otherwise the selected row of a command list. On the Hosts tab
+    /// it's the selected host's most recent command.
+    pub fn selected_command(&self) -> Option<&Command> {
+        if self.detail_visible {
+            return self.detail_command.as_ref();
+        }
+        match self.current_tab {
+            Tab::Commands => self.command_window.visible(self.selected_index, 1).first(),
+            Tab::Search => self
+                .search_results
+                .get(self.selected_index)
+                .map(|m| &m.command),
+            Tab::Dangerous => self
+                .commands
+                .iter()
+                .filter(|cmd| cmd.is_dangerous)
+                .nth(self.selected_index),
+            Tab::Hosts => {
+                let host = self.hosts.hosts.get(self.selected_index)?;
+                self.commands
+                    .iter()
+                    .filter(|cmd| cmd.host_id == host.host_id)
+                    .max_by_key(|cmd| cmd.timestamp)
+            }
+            _ => None,
+        }
+    }
+
+    /// Ask for the selected explored tool's tldr or man page, which the main
+    /// loop opens with the TUI suspended.
+    pub fn request_docs(&mut self) {
+        let Some(tool) = self
+            .analyses
+            .experiments
+            .tool_exploration
+            .get(self.selected_index)
+            .map(|exploration| exploration.tool.clone())
+        else {
+            self.set_status_message("No tool selected");
+            return;
+        };
+        if self.doc_viewer.is_none() {
+            self.set_status_message("Install tldr or man to read up on tools from here");
+            return;
+        }
+        self.doc_request = Some(tool);
+    }
+
+    /// The viewer and tool of a pending `request_docs`, clearing it.
+    pub fn take_doc_request(&mut self) -> Option<(DocViewer, String)> {
+        let tool = self.doc_request.take()?;
+        Some((self.doc_viewer?, tool))
+    }
+
+    /// Copy the selected command's text so it can be pasted back into a shell.
+    pub fn yank_selected_command(&mut self) {
+        match self.selected_command().map(|cmd| cmd.command.clone()) {
+            Some(command) => self.copy_text(&command),
+            None => self.set_status_message("No command selected"),
+        }
+    }
+
+    /// Switch to the Commands tab showing only commands run on `host`.
+    fn show_host_commands(&mut self, host: String) {
+        self.host_filter = Some(host);
+        self.apply_filters_and_sort();
+        if let Some(index) = Tab::all().iter().position(|tab| *tab == Tab::Commands) {
+            self.jump_to_tab(index);
+        }
+    }
+
+    /// Open the detail popup for `command`, or close it if it's already open.
+    fn toggle_detail(&mut self, command: Option<Command>) {
+        if self.detail_visible {
+            self.close_detail();
+        } else if let Some(command) = command {
+            self.detail_command = Some(command);
+            self.detail_visible = true;
+        }
+    }
+
+    /// Flip whether the command in the detail popup counts as an experiment. The
+    /// choice is stored as a manual override, which detection leaves alone.
+    pub async fn toggle_experiment_mark(&mut self) {
+        let Some(command) = &self.detail_command else {
+            return;
+        };
+        let Some(id) = command.id else {
+            self.set_status_message("Only stored commands can be marked");
+            return;
+        };
+        let is_experiment = !command.is_experiment;
+
+        match self.db.set_experiment(id, is_experiment).await {
+            Ok(true) => {}
+            Ok(false) => {
+                self.set_status_message("That command is no longer stored");
+                return;
+            }
+            Err(err) => {
+                self.set_status_message(format!("Failed to mark command: {}", err));
+                return;
+            }
+        }
+
+        for cmd in self
+            .commands
+            .iter_mut()
+            .chain(self.detail_command.iter_mut())
+            .filter(|cmd| cmd.id == Some(id))
+        {
+            cmd.is_experiment = is_experiment;
+        }
+        self.stats = Self::calculate_stats(&self.commands, self.config.session_idle_minutes);
+        self.refresh_hosts().await;
+        self.apply_filters_and_sort();
+        if !self.search_query.is_empty() {
+            self.update_search_results();
+        }
+        self.invalidate_analytics_cache();
+        self.set_status_message(if is_experiment {
+            "Marked as an experiment"
+        } else {
+            "No longer marked as an experiment"
+        });
+    }
+
+    /// Start editing the detail command's tags, pre-filled with the current ones.
+    pub fn open_tag_editor(&mut self) {
+        let Some(command) = &self.detail_command else {
+            return;
+        };
+        let Some(id) = command.id else {
+            self.set_status_message("Only stored commands can be tagged");
+            return;
+        };
+        self.tag_input = Some(TagInput {
+            purpose: TagInputPurpose::Edit(id),
+            text: command.tags.join(", "),
+        });
+    }
+
+    /// Start typing the tag the Commands tab is filtered by.
+    pub fn open_tag_filter(&mut self) {
+        self.tag_input = Some(TagInput {
+            purpose: TagInputPurpose::Filter,
+            text: self.tag_filter.clone().unwrap_or_default(),
+        });
+    }
+
+    pub fn close_tag_input(&mut self) {
+        self.tag_input = None;
+    }
+
+    /// Save the typed tags, or filter by the typed tag; an empty filter clears it.
+    pub async fn submit_tag_input(&mut self) {
+        let Some(input) = self.tag_input.take() else {
+            return;
+        };
+        let tags = parse_tags(&input.text);
+
+        match input.purpose {
+            TagInputPurpose::Filter => {
+                self.tag_filter = tags.into_iter().next();
+                self.apply_filters_and_sort();
+                self.reset_navigation();
+            }
+            TagInputPurpose::Edit(id) => {
+                match self.db.set_tags(id, &tags).await {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        self.set_status_message("That command is no longer stored");
+                        return;
+                    }
+                    Err(err) => {
+                        self.set_status_message(format!("Failed to tag command: {}", err));
+                        return;
+                    }
+                }
+
+                for cmd in self
+                    .commands
+                    .iter_mut()
+                    .chain(self.detail_command.iter_mut())
+                    .filter(|cmd| cmd.id == Some(id))
+                {
+                    cmd.tags = tags.clone();
+                }
+                self.apply_filters_and_sort();
+                self.set_status_message(if tags.is_empty() {
+                    "Tags cleared".to_string()
+                } else {
+                    format!("Tagged {}", tags.join(", "))
+                });
+            }
+        }
+    }
+
+    fn close_detail(&mut self) {
+        self.detail_visible = false;
+        self.detail_command = None;
+    }
+
+    pub fn open_danger_check(&mut self) {
+        self.danger_check = Some(String::new());
+    }
+
+    pub fn close_danger_check(&mut self) {
+        self.danger_check = None;
+    }
+
     pub fn handle_escape(&mut self) {
-        if self.help_visible {
+        if self.danger_check.is_some() {
+            self.close_danger_check();
+        } else if self.about.is_some() {
+            self.about = None;
+        } else if self.help_visible {
             self.help_visible = false;
+        } else if self.detail_visible {
+            self.close_detail();
+        } else if self.package_commands.is_some() {
+            self.package_commands = None;
+        } else if self.package_detail_open {
+            self.package_detail_open = false;
+        } else if self.experiment_detail_open {
+            self.experiment_detail_open = false;
+        } else if self.current_tab == Tab::Commands && self.tag_filter.is_some() {
+            self.tag_filter = None;
+            self.apply_filters_and_sort();
+            self.reset_navigation();
+        } else if matches!(self.current_tab, Tab::Commands | Tab::Network)
+            && self.host_filter.is_some()
+        {
+            self.host_filter = None;
+            self.apply_filters_and_sort();
+            self.reset_navigation();
         } else if self.current_tab == Tab::Search
             && (!self.search_query.is_empty() || self.search_mode)
         {
             self.search_mode = false;
             self.search_query.clear();
+            self.update_search_results();
             self.reset_navigation();
         }
     }
 
+    /// The character `key` types into the Search query while one is being
+    /// typed, where it wins over shortcuts: `-`, digits, `hjkl`, `=`, `/` and `?`
+    /// are all part of queries. Ctrl and Alt chords stay shortcuts.
+    pub fn query_char(&self, key: &KeyEvent) -> Option<char> {
+        let KeyCode::Char(c) = key.code else {
+            return None;
+        };
+        let typing = self.current_tab == Tab::Search && self.search_mode;
+        let chord = key
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+        (typing && !chord).then_some(c)
+    }
+
     pub fn handle_char(&mut self, c: char) {
+        if let Some(input) = &mut self.danger_check {
+            input.push(c);
+            return;
+        }
+        if let Some(input) = &mut self.tag_input {
+            input.text.push(c);
+            return;
+        }
+
         match self.current_tab {
             Tab::Search => {
                 // In Search tab, always add characters to search query
                 self.search_query.push(c);
                 self.search_mode = true;
+                self.update_search_results();
                 self.reset_navigation(); // Reset selection when search changes
             }
             Tab::Commands => {
@@ -357,18 +948,49 @@ impl App {
                 // Handle network-specific keys
                 self.handle_network_key(c);
             }
+            Tab::Packages => {
+                // Handle package ecosystem filter keys
+                self.handle_package_key(c);
+            }
+            Tab::Dangerous if c.eq_ignore_ascii_case(&'c') => self.open_danger_check(),
             _ => {
                 // For other tabs, ignore character input
             }
         }
     }
 
+    /// Bracketed paste arrives as one piece, so it goes into the search query
+    /// (or the command checker) whole instead of being read as keybindings.
+    /// Other tabs ignore it.
+    pub fn handle_paste(&mut self, text: &str) {
+        let text = paste_text(text);
+        if text.is_empty() {
+            return;
+        }
+
+        if let Some(input) = &mut self.danger_check {
+            input.push_str(&text);
+        } else if let Some(input) = &mut self.tag_input {
+            input.text.push_str(&text);
+        } else if self.current_tab == Tab::Search {
+            self.search_query.push_str(&text);
+            self.search_mode = true;
+            self.update_search_results();
+            self.reset_navigation();
+        }
+    }
+
     pub fn handle_backspace(&mut self) {
-        if self.current_tab == Tab::Search {
+        if let Some(input) = &mut self.danger_check {
+            input.pop();
+        } else if let Some(input) = &mut self.tag_input {
+            input.text.pop();
+        } else if self.current_tab == Tab::Search {
             self.search_query.pop();
             if self.search_query.is_empty() {
                 self.search_mode = false;
             }
+            self.update_search_results();
             self.reset_navigation(); // Reset selection when search changes
         }
     }
@@ -376,64 +998,159 @@ impl App {
     fn reset_navigation(&mut self) {
         self.scroll_offset = 0;
         self.selected_index = 0;
+        self.package_detail_open = false;
+        self.package_commands = None;
+        self.experiment_detail_open = false;
+        self.close_detail();
+        self.close_danger_check();
+    }
+
+    /// List every stored command that touched the package in the detail popup,
+    /// through the manager it's listed under.
+    pub async fn show_package_commands(&mut self) {
+        let Some((manager, stats)) = self.package_rows().into_iter().nth(self.selected_index)
+        else {
+            return;
+        };
+
+        match self
+            .db
+            .commands_with_package(Some(&manager), &stats.name)
+            .await
+        {
+            Ok(commands) => {
+                self.package_commands = Some(PackageCommands {
+                    manager,
+                    name: stats.name,
+                    commands,
+                    offset: 0,
+                })
+            }
+            Err(err) => {
+                self.set_status_message(format!("Failed to load {} history: {}", stats.name, err))
+            }
+        }
+    }
+
+    /// Open the About overlay, or close it if it's open. The database size and
+    /// count are read on opening.
+    pub async fn toggle_about(&mut self) {
+        if self.about.take().is_some() {
+            return;
+        }
+        match AboutInfo::gather(&self.config, &self.db).await {
+            Ok(about) => self.about = Some(about),
+            Err(err) => self.set_status_message(format!("Failed to read database: {}", err)),
+        }
+    }
+
+    /// The rows of the Packages tab's package list under the current filter.
+    pub fn package_rows(&self) -> Vec<(String, crate::analysis::package_tracker::PackageStats)> {
+        let filtered = crate::ui::packages::apply_package_filter(
+            &self.analyses.packages,
+            &self.package_filter,
+        );
+        crate::ui::packages::package_rows(&filtered)
+    }
+
+    /// The endpoints listed in the Network tab under the current filter and sort.
+    pub fn network_rows(&self) -> Vec<crate::analysis::network_analyzer::EndpointStats> {
+        crate::ui::network::endpoint_rows(
+            &self.analyses.network,
+            &self.network_filter,
+            &self.network_sort,
+        )
     }
 
     fn get_current_items_count(&self) -> usize {
         match self.current_tab {
-            Tab::Commands => self.filtered_commands.len(),
+            Tab::Commands => self.command_window.total,
             Tab::Sessions => self.stats.total_sessions,
             Tab::Hosts => self.get_hosts_count(),
             Tab::Dangerous => self.stats.dangerous_commands,
-            Tab::Network => self.stats.network_endpoints,
-            Tab::Packages => self.stats.packages_used,
-            Tab::Experiments => self.stats.experiment_sessions,
+            Tab::Network => self.network_rows().len(),
+            Tab::Packages => self.package_rows().len(),
+            Tab::Experiments => self.analyses.experiments.tool_exploration.len(),
+            Tab::Projects => self.analyses.projects.projects.len(),
+            Tab::Aliases => self.alias_suggestions().len().min(10),
+            Tab::Search => self.search_results.len(),
             _ => 10, // Default for other tabs
         }
     }
 
     fn get_hosts_count(&self) -> usize {
-        let mut hosts = std::collections::HashSet::new();
-        for cmd in &self.commands {
-            hosts.insert(&cmd.host_id);
-        }
-        hosts.len()
+        self.hosts.hosts.len()
     }
 
+    /// Results are kept up to date as the query changes; Enter just stops typing.
     fn execute_search(&mut self) {
-        // Implement fuzzy search logic
         self.search_mode = false;
     }
 
+    fn update_search_results(&mut self) {
+        self.search_error = None;
+        let results = match self.search_kind {
+            SearchKind::Fuzzy => match SearchQuery::parse(&self.search_query) {
+                Ok(query) if query.is_single_term() => crate::ui::search::fuzzy_search(
+                    &self.commands,
+                    &self.search_query,
+                    &self.search_filter,
+                ),
+                Ok(query) => {
+                    crate::ui::search::query_search(&self.commands, &query, &self.search_filter)
+                }
+                Err(err) => {
+                    self.search_error = Some(err.to_string());
+                    Default::default()
+                }
+            },
+            SearchKind::Regex => crate::ui::search::regex_search(
+                &self.commands,
+                &self.search_query,
+                &self.search_filter,
+            )
+            .unwrap_or_else(|err| {
+                self.search_error = Some(err.to_string());
+                Default::default()
+            }),
+        };
+        self.search_results = results.matches;
+        self.search_total = results.total;
+    }
+
+    /// Whether the fuzzy-mode query uses the AND/OR/NOT syntax rather than
+    /// being fuzzy-matched whole, so results have no score to show.
+    pub fn search_uses_query_syntax(&self) -> bool {
+        self.search_kind == SearchKind::Fuzzy
+            && SearchQuery::parse(&self.search_query)
+                .is_ok_and(|query| !query.is_empty() && !query.is_single_term())
+    }
+
+    /// Switch the Search tab between fuzzy and regex matching.
+    pub fn toggle_search_kind(&mut self) {
+        self.search_kind = match self.search_kind {
+            SearchKind::Fuzzy => SearchKind::Regex,
+            SearchKind::Regex => SearchKind::Fuzzy,
+        };
+        self.update_search_results();
+        self.reset_navigation();
+    }
+
     pub fn scroll_to_top(&mut self) {
         self.selected_index = 0;
         self.scroll_offset = 0;
     }
 
     pub fn scroll_to_bottom(&mut self) {
-        let max_items = self.get_current_items_count();
-        self.selected_index = max_items.saturating_sub(1);
+        self.move_selection(isize::MAX);
     }
 
     pub fn page_up(&mut self) {
-        let page_size = 10;
-        if self.selected_index >= page_size {
-            self.selected_index -= page_size;
-        } else {
-            self.selected_index = 0;
-        }
-        if self.selected_index < self.scroll_offset {
-            self.scroll_offset = self.selected_index;
-        }
+        self.move_selection(-(self.viewport_height() as isize));
     }
 
     pub fn page_down(&mut self) {
-        let page_size = 10;
-        let max_items = self.get_current_items_count();
-        if self.selected_index + page_size < max_items {
-            self.selected_index += page_size;
-        } else {
-            self.selected_index = max_items.saturating_sub(1);
-        }
+        self.move_selection(self.viewport_height() as isize);
     }
 
     pub fn jump_to_tab(&mut self, index: usize) {
@@ -458,72 +1175,129 @@ impl App {
     }
 
     fn apply_filters_and_sort(&mut self) {
-        // Apply filters first
-        self.filtered_commands = match self.filter_by {
-            FilterBy::All => self.commands.clone(),
-            FilterBy::Failed => self
-                .commands
-                .iter()
-                .filter(|cmd| cmd.exit_code.is_some() && cmd.exit_code.unwrap() != 0)
-                .cloned()
-                .collect(),
-            FilterBy::Experiments => self
-                .commands
-                .iter()
-                .filter(|cmd| cmd.is_experiment)
-                .cloned()
-                .collect(),
-            FilterBy::Recent => {
-                let mut recent = self.commands.clone();
-                recent.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
-                recent.into_iter().take(100).collect()
+        self.command_counts.clear();
+        self.command_window.invalidate();
+
+        // Count mode collapses repeated commands into a single row (the most recent
+        // run) and keeps the run counts for the list badge. That needs every matching
+        // row, so it is the one mode still built in memory.
+        if self.sort_by == SortBy::Count {
+            let host_commands = self.commands.iter().filter(|cmd| {
+                self.host_filter
+                    .as_ref()
+                    .is_none_or(|host| cmd.host_id == *host)
+                    && self
+                        .tag_filter
+                        .as_ref()
+                        .is_none_or(|tag| cmd.tags.contains(tag))
+            });
+            let filtered: Vec<Command> = match self.filter_by {
+                FilterBy::All => host_commands.cloned().collect(),
+                FilterBy::Failed => host_commands
+                    .filter(|cmd| cmd.exit_code.is_some_and(|code| code != 0))
+                    .cloned()
+                    .collect(),
+                FilterBy::Experiments => host_commands
+                    .filter(|cmd| cmd.is_experiment)
+                    .cloned()
+                    .collect(),
+                FilterBy::Recent => {
+                    let mut recent: Vec<Command> = host_commands.cloned().collect();
+                    recent.sort_by_key(|cmd| std::cmp::Reverse(cmd.timestamp));
+                    recent.into_iter().take(100).collect()
+                }
+            };
+
+            let (collapsed, counts) = collapse_by_frequency(filtered);
+            self.command_counts = counts;
+            self.command_window.set_all(collapsed);
+        }
+    }
+
+    /// The row filter and SQL `ORDER BY` fragment for the current filter and sort.
+    fn command_query(&self) -> (CommandFilter, &'static str) {
+        let mut base = self.stored_filter.clone();
+        if let Some(host) = &self.host_filter {
+            // The filter is the name shown, which may stand for several stored ids
+            let mut ids = self.host_aliases.ids_for(host, &self.host_ids);
+            if ids.is_empty() {
+                ids.push(host.clone());
             }
+            let placeholders = vec!["?"; ids.len()].join(", ");
+            base = base.and_bound(format!("host_id IN ({})", placeholders), ids);
+        }
+        if let Some(tag) = &self.tag_filter {
+            base = base.tagged(tag);
+        }
+        let filter = match self.filter_by {
+            FilterBy::All => base,
+            FilterBy::Failed => base.and("exit_code IS NOT NULL AND exit_code != 0"),
+            FilterBy::Experiments => base.and("is_experiment = 1"),
+            FilterBy::Recent => CommandFilter::newest(&base, 100),
         };
 
-        // Apply sorting
-        match self.sort_by {
-            SortBy::Time => {
-                self.filtered_commands
-                    .sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
-            }
-            SortBy::Count => {
-                // Sort by command frequency (would need to implement frequency counting)
-                self.filtered_commands
-                    .sort_by(|a, b| a.command.cmp(&b.command));
-            }
-            SortBy::Host => {
-                self.filtered_commands
-                    .sort_by(|a, b| a.host_id.cmp(&b.host_id));
-            }
-            SortBy::Danger => {
-                self.filtered_commands
-                    .sort_by(|a, b| match (a.is_dangerous, b.is_dangerous) {
-                        (true, false) => std::cmp::Ordering::Less,
-                        (false, true) => std::cmp::Ordering::Greater,
-                        _ => a.timestamp.cmp(&b.timestamp),
-                    });
-            }
-            SortBy::Success => {
-                self.filtered_commands
-                    .sort_by(|a, b| match (a.exit_code, b.exit_code) {
-                        (Some(0), Some(x)) if x != 0 => std::cmp::Ordering::Less,
-                        (Some(x), Some(0)) if x != 0 => std::cmp::Ordering::Greater,
-                        _ => a.timestamp.cmp(&b.timestamp),
-                    });
+        let order_by = match self.sort_by {
+            SortBy::Time | SortBy::Count => "timestamp DESC",
+            SortBy::Host => "host_id ASC, timestamp DESC",
+            SortBy::Danger => "is_dangerous DESC, timestamp ASC",
+            SortBy::Success => "(exit_code IS NOT NULL AND exit_code != 0) ASC, timestamp ASC",
+            SortBy::Length => "LENGTH(command) DESC",
+        };
+
+        (filter, order_by)
+    }
+
+    /// Fetch the page of Commands tab rows around `scroll_offset` if the current
+    /// window doesn't cover it. Called once per frame before drawing.
+    pub async fn refresh_command_page(&mut self) {
+        let Some(start) = self
+            .command_window
+            .page_needed(self.scroll_offset, CommandWindow::VISIBLE_ROWS)
+        else {
+            return;
+        };
+
+        let (filter, order_by) = self.command_query();
+        let page = async {
+            let total = self.db.count_commands_where(&filter).await?;
+            let rows = self
+                .db
+                .load_commands_paginated(&filter, order_by, start, CommandWindow::PAGE_SIZE)
+                .await?;
+            anyhow::Ok((total, rows))
+        }
+        .await;
+
+        match page {
+            Ok((total, mut rows)) => {
+                self.host_aliases.apply(&mut rows);
+                self.command_window.set_page(start, rows, total)
             }
-            SortBy::Length => {
-                self.filtered_commands
-                    .sort_by(|a, b| b.command.len().cmp(&a.command.len()));
+            Err(err) => {
+                log::warn!("Failed to load commands page: {}", err);
+                self.command_window.set_page(start, Vec::new(), 0);
             }
         }
     }
 
-    pub fn get_filtered_commands(&self) -> &[Command] {
-        &self.filtered_commands
+    /// The Commands tab rows from `offset`, at most `count` of them.
+    pub fn visible_commands(&self, offset: usize, count: usize) -> &[Command] {
+        self.command_window.visible(offset, count)
+    }
+
+    /// Number of runs folded into this row when sorting by count.
+    pub fn command_count(&self, command: &Command) -> Option<usize> {
+        if self.command_counts.is_empty() {
+            return None;
+        }
+        self.command_counts
+            .get(&normalize_command(&command.command))
+            .copied()
     }
 
     pub fn set_search_filter(&mut self, filter: SearchFilter) {
         self.search_filter = filter;
+        self.update_search_results();
         self.reset_navigation();
     }
 
@@ -534,12 +1308,18 @@ impl App {
     pub fn handle_function_key(&mut self, key: u8) {
         // Only handle function keys in Search tab
         if self.current_tab == Tab::Search {
-            match key {
-                1 => self.set_search_filter(SearchFilter::Failed),
-                2 => self.set_search_filter(SearchFilter::Dangerous),
-                3 => self.set_search_filter(SearchFilter::Recent),
-                4 => self.set_search_filter(SearchFilter::Experiments),
-                _ => {}
+            let filter = match key {
+                1 => SearchFilter::Failed,
+                2 => SearchFilter::Dangerous,
+                3 => SearchFilter::Recent,
+                4 => SearchFilter::Experiments,
+                _ => return,
+            };
+            // The active filter's key switches it off again
+            if self.search_filter == filter {
+                self.set_search_filter(SearchFilter::None);
+            } else {
+                self.set_search_filter(filter);
             }
         }
     }
@@ -564,124 +1344,469 @@ impl App {
 
     pub fn set_heatmap_time_range(&mut self, time_range: crate::analysis::heatmap::TimeRange) {
         self.heatmap_time_range = time_range;
+        self.config.heatmap_time_range = time_range.as_str().to_string();
+        self.analyses.update_heatmap(
+            &self.commands,
+            &self.config,
+            time_range,
+            self.heatmap_view_mode,
+        );
+        self.mark_config_dirty();
         self.reset_navigation();
     }
 
     pub fn set_heatmap_view_mode(&mut self, view_mode: crate::analysis::heatmap::ViewMode) {
         self.heatmap_view_mode = view_mode;
+        self.config.heatmap_view_mode = view_mode.as_str().to_string();
+        self.analyses.update_heatmap(
+            &self.commands,
+            &self.config,
+            self.heatmap_time_range,
+            view_mode,
+        );
+        self.mark_config_dirty();
         self.reset_navigation();
     }
 
+    fn mark_config_dirty(&mut self) {
+        if self.config_dirty_since.is_none() {
+            self.config_dirty_since = Some(std::time::Instant::now());
+        }
+    }
+
+    /// Write pending config changes once they've settled for a second, or immediately
+    /// when `force` is set (e.g. on quit). Saving is best-effort and never fails the UI.
+    pub fn flush_config(&mut self, force: bool) {
+        let Some(since) = self.config_dirty_since else {
+            return;
+        };
+
+        if force || since.elapsed() >= std::time::Duration::from_secs(1) {
+            if let Err(err) = self.config.save() {
+                log::warn!("Failed to save config: {}", err);
+            }
+            self.config_dirty_since = None;
+        }
+    }
+
     pub fn handle_alias_key(&mut self, key: char) {
+        use crate::ui::aliases::{AliasFilter, AliasSort};
+
         if self.current_tab == Tab::Aliases {
             match key.to_ascii_uppercase() {
-                // Sorting keys
-                'S' => {
-                    // Sort by savings - this would be implemented with alias state
-                    self.reset_navigation();
-                }
-                'F' => {
-                    // Sort by frequency
-                    self.reset_navigation();
-                }
-                'L' => {
-                    // Sort by length
-                    self.reset_navigation();
-                }
+                // Sorting keys (F is taken by the fish export)
+                'S' => self.set_alias_sort(AliasSort::Savings),
+                'U' => self.set_alias_sort(AliasSort::Frequency),
+                'L' => self.set_alias_sort(AliasSort::Length),
                 // Filter keys
-                'G' => {
-                    // Filter Git commands
-                    self.reset_navigation();
-                }
-                'D' => {
-                    // Filter Docker commands
-                    self.reset_navigation();
-                }
-                'A' => {
-                    // Show all commands
-                    self.reset_navigation();
-                }
+                'G' => self.set_alias_filter(AliasFilter::Git),
+                'D' => self.set_alias_filter(AliasFilter::Docker),
+                'A' => self.set_alias_filter(AliasFilter::All),
                 // Action keys
                 'E' => {
-                    // Export aliases
-                    // This would trigger export functionality
-                }
-                'C' => {
-                    // Copy selected alias
-                    // This would copy to clipboard
+                    let shell = self.config.shell.clone();
+                    self.export_aliases(&shell);
                 }
+                'C' => self.copy_selected_alias(),
                 'R' => {
                     // Refresh analysis
                     self.reset_navigation();
                 }
                 // Shell export keys
-                'B' => {
-                    // Export for Bash
-                    // This would generate bash aliases
-                }
-                'Z' => {
-                    // Export for Zsh
-                    // This would generate zsh aliases
+                'B' => self.export_aliases("bash"),
+                'Z' => self.export_aliases("zsh"),
+                'F' => self.export_aliases("fish"),
+                _ => {}
+            }
+        }
+    }
+
+    /// Suggestions as listed in the Aliases tab, under the current sort and filter.
+    pub fn alias_suggestions(&self) -> Vec<AliasSuggestion> {
+        crate::ui::aliases::alias_rows(
+            &self.analyses.aliases.suggestions,
+            &self.alias_filter,
+            &self.alias_sort,
+        )
+    }
+
+    pub fn set_alias_sort(&mut self, sort: crate::ui::aliases::AliasSort) {
+        self.alias_sort = sort;
+        self.reset_navigation();
+    }
+
+    pub fn set_alias_filter(&mut self, filter: crate::ui::aliases::AliasFilter) {
+        self.alias_filter = filter;
+        self.reset_navigation();
+    }
+
+    fn copy_selected_alias(&mut self) {
+        let suggestions = self.alias_suggestions();
+        let Some(suggestion) = suggestions.get(self.selected_index) else {
+            self.set_status_message("No alias suggestion selected");
+            return;
+        };
+
+        let line = AliasSuggester::new().format_alias(suggestion, &self.config.shell);
+        self.copy_text(&line);
+    }
+
+    /// Put `text` on the clipboard and say so in the status bar.
+    fn copy_text(&mut self, text: &str) {
+        let message = match self.copy_to_clipboard(text) {
+            Ok(()) => format!("Copied: {}", text),
+            Err(err) => {
+                // Headless sessions (e.g. ssh without X11) have no clipboard
+                log::warn!("Clipboard unavailable: {}", err);
+                match write_clipboard_fallback(text) {
+                    Ok(path) => format!("No clipboard, saved to {}", path.display()),
+                    Err(err) => format!("Copy failed: {}", err),
                 }
+            }
+        };
+        self.set_status_message(message);
+    }
+
+    fn export_aliases(&mut self, shell: &str) {
+        let path = dirs::config_dir()
+            .unwrap_or_else(|| std::path::PathBuf::from("."))
+            .join("whiskerlog")
+            .join(format!("aliases.{}", shell));
+
+        let suggestions = self.alias_suggestions();
+        let message = match AliasSuggester::new().append_shell_aliases(&suggestions, shell, &path) {
+            Ok(0) => format!("No new aliases for {}", path.display()),
+            Ok(count) => format!(
+                "Wrote {} aliases to {} - add `source {}` to your {} rc",
+                count,
+                path.display(),
+                path.display(),
+                shell
+            ),
+            Err(err) => format!("Alias export failed: {}", err),
+        };
+        self.set_status_message(message);
+    }
+
+    /// Ask before re-running enrichment over the stored history, since it
+    /// rewrites every row.
+    pub fn request_reenrich(&mut self) {
+        if self.is_reenriching() {
+            self.set_status_message("Re-enrichment is already running".to_string());
+        } else {
+            self.pending_reenrich = true;
+        }
+    }
+
+    /// Answer the re-enrichment prompt, starting it in the background on yes.
+    pub async fn confirm_reenrich(&mut self, confirmed: bool) {
+        self.pending_reenrich = false;
+        if !confirmed {
+            return;
+        }
+        match BackgroundReenrich::start(&self.config).await {
+            Ok(job) => {
+                self.reenrich = Some(job);
+                self.set_status_message("Re-enriching history...".to_string());
+            }
+            Err(err) => self.set_status_message(format!("Re-enrichment failed: {}", err)),
+        }
+    }
+
+    /// Whether a re-enrichment is still running.
+    pub fn is_reenriching(&self) -> bool {
+        self.reenrich.is_some()
+    }
+
+    /// Once the background re-enrichment is done, reload the rescored history.
+    pub async fn finish_reenrich(&mut self) {
+        let Some(result) = self.reenrich.as_ref().and_then(BackgroundReenrich::poll) else {
+            return;
+        };
+        self.reenrich = None;
+        let updated = match result {
+            Ok(updated) => updated,
+            Err(err) => {
+                self.set_status_message(format!("Re-enrichment failed: {}", err));
+                return;
+            }
+        };
+
+        self.danger_analyzer = crate::analysis::DangerAnalyzer::from_config(&self.config);
+        match self.db.get_commands_where(&self.stored_filter).await {
+            Ok(mut commands) => {
+                self.host_aliases.apply(&mut commands);
+                self.commands = commands;
+            }
+            Err(err) => log::warn!("Failed to reload commands: {}", err),
+        }
+        self.stats = Self::calculate_stats(&self.commands, self.config.session_idle_minutes);
+        self.stats_tally = None;
+        self.refresh_hosts().await;
+        self.apply_filters_and_sort();
+        self.reset_navigation();
+        if !self.search_query.is_empty() {
+            self.update_search_results();
+        }
+        self.invalidate_analytics_cache();
+        self.set_status_message(format!("Re-enriched history, {} commands changed", updated));
+    }
+
+    /// Write a Markdown report of the current history next to the database.
+    pub fn write_report(&mut self) {
+        use crate::report::{self, Report, ReportFormat};
+
+        let dir = self
+            .config
+            .database_path
+            .parent()
+            .unwrap_or_else(|| std::path::Path::new("."));
+        let path = report::report_path(dir, chrono::Local::now().date_naive(), ReportFormat::Md);
+        let report = Report::build(&self.commands, &self.config);
+        let written = std::fs::File::create(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|file| {
+                report::write_report(
+                    &report,
+                    ReportFormat::Md,
+                    &mut std::io::BufWriter::new(file),
+                )
+            });
+
+        let message = match written {
+            Ok(()) => format!("Report written to {}", path.display()),
+            Err(err) => format!("Report failed: {}", err),
+        };
+        self.set_status_message(message);
+    }
+
+    fn copy_to_clipboard(&mut self, text: &str) -> Result<()> {
+        if self.clipboard.is_none() {
+            self.clipboard = Some(arboard::Clipboard::new()?);
+        }
+        if let Some(clipboard) = self.clipboard.as_mut() {
+            clipboard.set_text(text)?;
+        }
+        Ok(())
+    }
+
+    pub fn set_status_message(&mut self, message: impl Into<String>) {
+        self.status_message = Some((message.into(), std::time::Instant::now()));
+    }
+
+    /// The current footer message, if it was set in the last few seconds.
+    pub fn status_message(&self) -> Option<&str> {
+        self.status_message
+            .as_ref()
+            .filter(|(_, set_at)| set_at.elapsed() < std::time::Duration::from_secs(4))
+            .map(|(message, _)| message.as_str())
+    }
+
+    pub fn handle_package_key(&mut self, key: char) {
+        use crate::ui::packages::PackageFilter;
+
+        if self.current_tab == Tab::Packages {
+            match key.to_ascii_uppercase() {
+                'L' => self.set_package_filter(PackageFilter::Linux),
+                'P' => self.set_package_filter(PackageFilter::Programming),
+                'C' => self.set_package_filter(PackageFilter::Container),
+                'S' => self.set_package_filter(PackageFilter::System),
+                'A' => self.set_package_filter(PackageFilter::All),
                 _ => {}
             }
         }
     }
 
+    pub fn set_package_filter(&mut self, filter: crate::ui::packages::PackageFilter) {
+        self.package_filter = filter;
+        self.reset_navigation();
+    }
+
     pub fn handle_network_key(&mut self, key: char) {
+        use crate::ui::network::{NetworkFilter, NetworkSort};
+
         if self.current_tab == Tab::Network {
             match key.to_ascii_uppercase() {
                 // Filter keys
-                'S' => {
-                    // Filter secure endpoints only
-                    self.reset_navigation();
-                }
-                'I' => {
-                    // Filter insecure endpoints only
-                    self.reset_navigation();
-                }
-                'A' => {
-                    // Show all endpoints
-                    self.reset_navigation();
-                }
+                'S' => self.set_network_filter(NetworkFilter::Secure),
+                'I' => self.set_network_filter(NetworkFilter::Insecure),
+                'A' => self.set_network_filter(NetworkFilter::All),
                 // Sort keys
-                'U' => {
-                    // Sort by usage count
-                    self.reset_navigation();
-                }
-                'T' => {
-                    // Sort by time (most recent first)
-                    self.reset_navigation();
-                }
-                'R' => {
-                    // Sort by risk level
-                    self.reset_navigation();
-                }
+                'U' => self.set_network_sort(NetworkSort::Usage),
+                'T' => self.set_network_sort(NetworkSort::Time),
+                'R' => self.set_network_sort(NetworkSort::Risk),
                 _ => {}
             }
         }
     }
 
+    pub fn set_network_filter(&mut self, filter: crate::ui::network::NetworkFilter) {
+        self.network_filter = filter;
+        self.reset_navigation();
+    }
+
+    pub fn set_network_sort(&mut self, sort: crate::ui::network::NetworkSort) {
+        self.network_sort = sort;
+        self.reset_navigation();
+    }
+
     // Enhanced analytics methods
     pub fn refresh_analytics(&mut self) {
         let now = std::time::Instant::now();
 
-        // Only refresh if cache is invalid or it's been more than 30 seconds
-        if !self.analysis_cache_valid
-            || now.duration_since(self.last_analysis_update).as_secs() > 30
-        {
-            let analyzer = StatsAnalyzer::new();
-            self.command_stats = Some(analyzer.analyze_commands(&self.commands));
-            self.session_stats = Some(analyzer.analyze_sessions(&self.commands));
+        // Only refresh if cache is invalid or older than `analytics_refresh_secs`
+        if self.analytics_freshness.needs_refresh(now) {
+            let analyzer = StatsAnalyzer::from_config(&self.config);
+            let tally = self
+                .stats_tally
+                .get_or_insert_with(|| analyzer.tally(&self.commands));
+            self.command_stats = Some(tally.command_stats());
+            self.session_stats = Some(tally.session_stats());
             self.productivity_stats = Some(analyzer.analyze_productivity(&self.commands));
+            self.analyses = CachedAnalyses::new(
+                &self.commands,
+                tally,
+                &self.config,
+                self.heatmap_time_range,
+                self.heatmap_view_mode,
+                &mut self.project_analyzer,
+            );
+
+            self.analytics_freshness.mark_fresh(now);
+        }
+    }
 
-            self.last_analysis_update = now;
-            self.analysis_cache_valid = true;
+    /// The per-tab analyses, as of the last refresh. Tabs read these
+    /// instead of re-running analyzers on every frame.
+    pub fn analysis(&self) -> &CachedAnalyses {
+        &self.analyses
+    }
+
+    /// Add the commands the startup import and the history watcher stored
+    /// since the last frame.
+    pub async fn import_live_commands(&mut self) {
+        if let Some(import) = &mut self.import {
+            import.poll();
+            let new_commands = import.take_commands();
+            let finished = import.finished.take();
+            self.add_imported_commands(new_commands).await;
+
+            if let Some(result) = finished {
+                self.import = None;
+                if let Err(err) = result {
+                    log::warn!("History import failed: {}", err);
+                    self.set_status_message(format!("History import failed: {}", err));
+                }
+                self.start_watcher().await;
+            }
+        }
+
+        if let Some(watcher) = &mut self.watcher {
+            let new_commands = watcher.drain();
+            self.add_imported_commands(new_commands).await;
+        }
+    }
+
+    /// `(stored, parsed)` counts while the startup import runs.
+    pub fn import_progress(&self) -> Option<(usize, usize)> {
+        self.import
+            .as_ref()
+            .map(|import| (import.stored, import.parsed))
+    }
+
+    /// Keep importing while the TUI is open; without it the history is as of launch.
+    async fn start_watcher(&mut self) {
+        if !self.config.auto_import {
+            return;
+        }
+        self.watcher = HistoryWatcher::start(&self.config)
+            .await
+            .map_err(|err| log::warn!("Live history import disabled: {}", err))
+            .ok();
+    }
+
+    /// Add commands the import just stored, folding them into the stats rather
+    /// than going over the whole history again. The per-tab analyses catch up
+    /// at the next periodic refresh.
+    async fn add_imported_commands(&mut self, mut new_commands: Vec<Command>) {
+        if new_commands.is_empty() {
+            return;
+        }
+
+        self.host_aliases.apply(&mut new_commands);
+        new_commands.sort_by_key(|cmd| cmd.timestamp);
+        for cmd in &new_commands {
+            self.stats.observe(cmd);
+        }
+
+        let loaded = self.commands.len();
+        let appended = self
+            .commands
+            .last()
+            .is_none_or(|last| new_commands[0].timestamp >= last.timestamp);
+        if appended {
+            self.commands.extend(new_commands);
+        } else {
+            for cmd in new_commands {
+                let at = self
+                    .commands
+                    .partition_point(|other| other.timestamp <= cmd.timestamp);
+                self.commands.insert(at, cmd);
+            }
+        }
+
+        match &mut self.stats_tally {
+            Some(tally) if appended => {
+                for cmd in &self.commands[loaded..] {
+                    tally.observe(cmd);
+                }
+            }
+            // Older commands land in the middle of sessions, so those are
+            // tallied from scratch
+            _ => {
+                self.stats_tally =
+                    Some(StatsAnalyzer::from_config(&self.config).tally(&self.commands))
+            }
+        }
+        if let Some(tally) = &self.stats_tally {
+            let session_stats = tally.session_stats();
+            self.stats.total_sessions = session_stats.total_sessions;
+            self.command_stats = Some(tally.command_stats());
+            self.session_stats = Some(session_stats);
+        }
+
+        self.refresh_hosts().await;
+        self.apply_filters_and_sort();
+        if !self.search_query.is_empty() {
+            self.update_search_results();
+        }
+    }
+
+    /// The Hosts tab's analysis, and every host id as stored.
+    async fn host_analysis(
+        db: &Database,
+        config: &Config,
+        aliases: &HostAliases,
+    ) -> Result<(HostAnalysis, Vec<String>)> {
+        let summaries = db.host_summaries().await?;
+        let host_ids = summaries.iter().map(|host| host.host_id.clone()).collect();
+        let mut hosts = HostAnalysis::from_summaries(&aliases.merge_summaries(summaries));
+        hosts.flag_dangerous_hosts(config.host_danger_threshold);
+        Ok((hosts, host_ids))
+    }
+
+    async fn refresh_hosts(&mut self) {
+        match Self::host_analysis(&self.db, &self.config, &self.host_aliases).await {
+            Ok((hosts, host_ids)) => {
+                self.hosts = hosts;
+                self.host_ids = host_ids;
+            }
+            Err(err) => log::warn!("Failed to summarize hosts: {}", err),
         }
     }
 
     pub fn invalidate_analytics_cache(&mut self) {
-        self.analysis_cache_valid = false;
+        self.analytics_freshness.invalidate();
     }
 
     #[allow(dead_code)]
@@ -715,8 +1840,258 @@ impl App {
     // Background analytics update
     pub fn update_analytics_background(&mut self) {
         // This would be called periodically to update analytics without blocking UI
-        if !self.analysis_cache_valid {
+        if !self.analytics_freshness.is_valid() {
             self.refresh_analytics();
         }
     }
 }
+
+/// Results of the analyzers behind the Summary trend and the Packages,
+/// Network, Aliases, Heatmap and Experiments tabs, computed together so
+/// drawing a frame never walks the history.
+#[derive(Debug, Clone)]
+pub struct CachedAnalyses {
+    pub packages: PackageAnalysis,
+    pub network: NetworkAnalysis,
+    /// Endpoints each host contacted, for the Network tab under a host filter
+    pub endpoints_by_host: HashMap<String, Vec<EndpointStats>>,
+    pub aliases: AliasAnalysis,
+    /// For the heatmap's current time range and view mode
+    pub heatmap: HeatmapData,
+    pub work_patterns: WorkPatternAnalysis,
+    pub projects: ProjectAnalysis,
+    pub experiments: ExperimentAnalysis,
+    /// Commands per day for the Summary tab's trend, oldest first
+    pub daily_activity: Vec<(chrono::NaiveDate, usize)>,
+    /// Stats over each shell's commands, busiest shell first
+    pub shells: Vec<(String, CommandStats)>,
+    /// For the Sessions tab, most recent first
+    pub sessions: Vec<crate::analysis::sessions::SessionSummary>,
+}
+
+impl CachedAnalyses {
+    pub fn new(
+        commands: &[Command],
+        tally: &StatsTally,
+        config: &Config,
+        time_range: TimeRange,
+        view_mode: ViewMode,
+        project_analyzer: &mut ProjectAnalyzer,
+    ) -> Self {
+        let heatmap_analyzer = HeatmapAnalyzer::from_config(config);
+        let network_analyzer = NetworkAnalyzer::from_config(config);
+        let stats_analyzer = StatsAnalyzer::from_config(config);
+
+        Self {
+            packages: PackageTracker::new().analyze_package_usage(commands),
+            network: network_analyzer.analyze_network_activity(commands),
+            endpoints_by_host: network_analyzer.endpoints_by_host(commands),
+            aliases: AliasSuggester::new().analyze_alias_opportunities(commands),
+            heatmap: heatmap_analyzer.generate_heatmap(commands, time_range, view_mode),
+            work_patterns: heatmap_analyzer.analyze_work_patterns(commands),
+            projects: project_analyzer.analyze_projects(commands),
+            experiments: ExperimentDetector::from_config(config).analyze_experiments(commands),
+            daily_activity: stats_analyzer
+                .commands_per_day_series(commands, crate::ui::summary::TREND_DAYS),
+            shells: tally.shell_stats(),
+            sessions: crate::analysis::sessions::summarize_sessions(
+                commands,
+                chrono::Duration::minutes(config.session_idle_minutes),
+            ),
+        }
+    }
+
+    /// Regenerate only the heatmap grid, after its time range or view mode changed.
+    pub fn update_heatmap(
+        &mut self,
+        commands: &[Command],
+        config: &Config,
+        time_range: TimeRange,
+        view_mode: ViewMode,
+    ) {
+        self.heatmap =
+            HeatmapAnalyzer::from_config(config).generate_heatmap(commands, time_range, view_mode);
+    }
+}
+
+/// Pasted text as a single-line query: line breaks and tabs become spaces and
+/// other control characters are dropped.
+pub fn paste_text(text: &str) -> String {
+    text.split(['\r', '\n'])
+        .filter(|line| !line.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .filter_map(|c| match c {
+            '\t' => Some(' '),
+            c if c.is_control() => None,
+            c => Some(c),
+        })
+        .collect()
+}
+
+/// Whether the cached analytics can still be shown: they go stale when
+/// invalidated, or `max_age` after they were computed.
+#[derive(Debug, Clone, Copy)]
+pub struct AnalyticsFreshness {
+    max_age: std::time::Duration,
+    updated: std::time::Instant,
+    valid: bool,
+}
+
+impl AnalyticsFreshness {
+    /// Fresh as of now.
+    pub fn new(max_age: std::time::Duration) -> Self {
+        Self {
+            max_age,
+            updated: std::time::Instant::now(),
+            valid: true,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.valid
+    }
+
+    pub fn needs_refresh(&self, now: std::time::Instant) -> bool {
+        !self.valid || now.saturating_duration_since(self.updated) > self.max_age
+    }
+
+    pub fn mark_fresh(&mut self, now: std::time::Instant) {
+        self.updated = now;
+        self.valid = true;
+    }
+
+    pub fn invalidate(&mut self) {
+        self.valid = false;
+    }
+}
+
+/// Selected row and first visible row of a scrolling list.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ListScroll {
+    pub selected: usize,
+    pub offset: usize,
+}
+
+impl ListScroll {
+    /// Move the selection `delta` rows (negative is up) within `count` items,
+    /// then scroll so it's one of the `height` rows on screen.
+    pub fn move_by(self, delta: isize, count: usize, height: usize) -> Self {
+        let last = count.saturating_sub(1);
+        let selected = self.selected.saturating_add_signed(delta).min(last);
+        let height = height.max(1);
+
+        let offset = if selected < self.offset {
+            selected
+        } else if selected >= self.offset + height {
+            selected + 1 - height
+        } else {
+            self.offset
+        };
+        Self { selected, offset }
+    }
+}
+
+/// A window of Commands tab rows, so large histories aren't held twice in memory.
+#[derive(Debug)]
+pub struct CommandWindow {
+    /// Index of `rows[0]` in the full result set
+    pub start: usize,
+    pub rows: Vec<Command>,
+    /// Size of the full result set, used for the scroll position
+    pub total: usize,
+    stale: bool,
+}
+
+impl Default for CommandWindow {
+    // Starts stale so the first frame loads a page
+    fn default() -> Self {
+        Self {
+            start: 0,
+            rows: Vec::new(),
+            total: 0,
+            stale: true,
+        }
+    }
+}
+
+impl CommandWindow {
+    pub const PAGE_SIZE: usize = 200;
+    /// Upper bound on rows on screen at once
+    pub const VISIBLE_ROWS: usize = 100;
+
+    pub fn invalidate(&mut self) {
+        self.stale = true;
+    }
+
+    /// Where the next page should start so rows `offset..offset + visible` are
+    /// loaded, or `None` if the current window already covers them.
+    pub fn page_needed(&self, offset: usize, visible: usize) -> Option<usize> {
+        let end = (offset + visible).min(self.total);
+        let covered = offset >= self.start && end <= self.start + self.rows.len();
+        if !self.stale && covered {
+            return None;
+        }
+
+        // Leave some rows above the offset so scrolling back up doesn't refetch
+        Some(offset.saturating_sub(Self::PAGE_SIZE / 4))
+    }
+
+    pub fn set_page(&mut self, start: usize, rows: Vec<Command>, total: usize) {
+        self.start = start;
+        self.rows = rows;
+        self.total = total;
+        self.stale = false;
+    }
+
+    /// Hold the whole result set, for views that can't be paged from SQL.
+    pub fn set_all(&mut self, rows: Vec<Command>) {
+        let total = rows.len();
+        self.set_page(0, rows, total);
+    }
+
+    pub fn visible(&self, offset: usize, count: usize) -> &[Command] {
+        if offset < self.start {
+            return &[];
+        }
+        let from = (offset - self.start).min(self.rows.len());
+        let to = (from + count).min(self.rows.len());
+        &self.rows[from..to]
+    }
+}
+
+/// Collapse repeated commands into one row each, ordered by how often they were run
+/// (most recent first on ties), and return the run count for every normalized command.
+pub fn collapse_by_frequency(mut commands: Vec<Command>) -> (Vec<Command>, HashMap<String, usize>) {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for cmd in &commands {
+        *counts.entry(normalize_command(&cmd.command)).or_insert(0) += 1;
+    }
+
+    // Keep the most recent run of each command
+    commands.sort_by_key(|cmd| std::cmp::Reverse(cmd.timestamp));
+    let mut seen = HashSet::new();
+    commands.retain(|cmd| seen.insert(normalize_command(&cmd.command)));
+
+    commands.sort_by_cached_key(|cmd| {
+        let count = counts[&normalize_command(&cmd.command)];
+        (std::cmp::Reverse(count), std::cmp::Reverse(cmd.timestamp))
+    });
+
+    (commands, counts)
+}
+
+/// Saves `text` to a new file only the user can read. The name is random and
+/// the file is created fresh, so a planted symlink can't redirect the write.
+fn write_clipboard_fallback(text: &str) -> Result<std::path::PathBuf> {
+    use std::io::Write;
+
+    let mut file = tempfile::Builder::new()
+        .prefix("whiskerlog-clipboard-")
+        .suffix(".txt")
+        .tempfile()?;
+    writeln!(file, "{}", text)?;
+    let (_, path) = file.keep()?;
+    Ok(path)
+}
@@ -1,15 +1,231 @@
 use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
     pub database_path: PathBuf,
     pub history_paths: Vec<PathBuf>,
     pub redaction_enabled: bool,
+    /// Extra regexes to mask on import; a capture group limits the mask to that group
+    #[serde(default)]
+    pub redaction_patterns: Vec<String>,
+    /// Watch history files and import new commands while the TUI is open
     pub auto_import: bool,
     pub danger_threshold: f32,
+    /// Hosts whose mean danger score goes above this are flagged in the Hosts tab
+    #[serde(default = "default_host_danger_threshold")]
+    pub host_danger_threshold: f32,
+    /// Names one machine goes by, mapped to the host it's shown as, e.g.
+    /// `"prod.internal" = "prod"`; patterns may use `*` and `?`
+    #[serde(default)]
+    pub host_aliases: HashMap<String, String>,
     pub experiment_detection: bool,
+    /// Last heatmap time range ("day", "week", "month" or "year")
+    #[serde(default = "default_heatmap_time_range")]
+    pub heatmap_time_range: String,
+    /// Last heatmap view mode ("all", "dangerous", "experiments" or "failed")
+    #[serde(default = "default_heatmap_view_mode")]
+    pub heatmap_view_mode: String,
+    /// Commands per cell at which the heatmap's levels 2 to 5 start, e.g.
+    /// `[5, 20, 50, 100]`; unset shades cells by percentile of the data shown
+    #[serde(default)]
+    pub heatmap_thresholds: Option<Vec<usize>>,
+    /// How heatmap levels are drawn: "blocks" (a ramp of shade characters)
+    /// or "solid" (full cells in a ramp of colors)
+    #[serde(default = "default_heatmap_ramp")]
+    pub heatmap_ramp: String,
+    /// How many nested ssh/docker/kubectl shells to track when attributing hosts
+    #[serde(default = "default_max_exec_depth")]
+    pub max_exec_depth: usize,
+    /// Extra danger rules, merged over the built-ins by `pattern`
+    #[serde(default)]
+    pub danger_rules: Vec<DangerRule>,
+    /// Shell to generate aliases for ("bash", "zsh" or "fish"), taken from $SHELL by default
+    #[serde(default = "default_shell")]
+    pub shell: String,
+    /// IANA zone (e.g. "Europe/Berlin") used to bucket activity by hour and day;
+    /// unset means the system's local zone
+    #[serde(default)]
+    pub timezone: Option<String>,
+    /// Commands to leave out of the import: a word prefix (`ls`) or a glob (`cd *`)
+    #[serde(default)]
+    pub ignore_patterns: Vec<String>,
+    /// Skip commands run in these directories or below; `~` and `$HOME` are expanded
+    #[serde(default)]
+    pub ignore_directories: Vec<String>,
+    /// Commands longer than this many characters are handled by
+    /// `long_command_policy` on import; unset means no limit
+    #[serde(default)]
+    pub max_command_length: Option<usize>,
+    /// "truncate" (keeping the full text for the detail view), "skip" or "keep"
+    #[serde(default = "default_long_command_policy")]
+    pub long_command_policy: String,
+    /// Hosts whose endpoints raise no network security issues, e.g. an intranet
+    /// behind a VPN; `*.corp.local` matches any subdomain of `corp.local`
+    #[serde(default)]
+    pub trusted_network_hosts: Vec<String>,
+    /// Minutes without a command after which a new session starts
+    #[serde(default = "default_session_idle_minutes")]
+    pub session_idle_minutes: i64,
+    /// How bash, zsh and fish history without session ids is split into
+    /// sessions on import: "idle_gap" or "per_day"
+    #[serde(default = "default_session_strategy")]
+    pub session_strategy: String,
+    /// Color theme ("dark", "cyberpunk", "matrix" or "monochrome"); `NO_COLOR`
+    /// in the environment forces monochrome
+    #[serde(default = "default_theme")]
+    pub theme: String,
+    /// Local hour business hours start, for the heatmap's work pattern
+    #[serde(default = "default_work_hours_start")]
+    pub work_hours_start: u32,
+    /// Local hour business hours end (exclusive)
+    #[serde(default = "default_work_hours_end")]
+    pub work_hours_end: u32,
+    /// Days that count as the weekend, e.g. ["fri", "sat"]
+    #[serde(default = "default_weekend_days")]
+    pub weekend_days: Vec<String>,
+    /// Local hour late night starts
+    #[serde(default = "default_late_night_start")]
+    pub late_night_start: u32,
+    /// Local hour late night ends (exclusive); may be before the start
+    #[serde(default = "default_late_night_end")]
+    pub late_night_end: u32,
+    /// Delete commands older than this many days at startup; unset keeps everything
+    #[serde(default)]
+    pub retention_days: Option<u32>,
+    /// Milliseconds the TUI waits for a key before redrawing, kept between
+    /// `MIN_UI_POLL_MS` and `MAX_UI_POLL_MS`; higher saves battery
+    #[serde(default = "default_ui_poll_ms")]
+    pub ui_poll_ms: u64,
+    /// Seconds before the open TUI's analytics count as stale and are recomputed
+    #[serde(default = "default_analytics_refresh_secs")]
+    pub analytics_refresh_secs: u64,
+    /// Count top commands by their shape (`git commit -m <str>`) rather than the exact text
+    #[serde(default = "default_true")]
+    pub normalize_top_commands: bool,
+    /// Ask "Quit? (y/n)" on `q` instead of exiting straight away
+    #[serde(default)]
+    pub confirm_quit: bool,
+    /// Set after the first-run import screen, so it's only shown once
+    #[serde(default)]
+    pub onboarded: bool,
+    /// File this config was read from and `save` writes to; the default
+    /// location when unset
+    #[serde(skip)]
+    pub config_path: Option<PathBuf>,
+    /// The file's `database_path` while `--db` overrides it, so saving doesn't
+    /// persist the override
+    #[serde(skip)]
+    pub configured_database_path: Option<PathBuf>,
+}
+
+/// A pattern that contributes `score` to a command's danger score when it matches.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DangerRule {
+    pub pattern: String,
+    /// Treat `pattern` as a regex; otherwise it's a plain substring
+    #[serde(default)]
+    pub is_regex: bool,
+    pub score: f32,
+    pub reason: String,
+    /// Set to false to switch off a built-in rule with the same pattern
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_host_danger_threshold() -> f32 {
+    0.5
+}
+
+fn default_heatmap_time_range() -> String {
+    "week".to_string()
+}
+
+fn default_heatmap_view_mode() -> String {
+    "all".to_string()
+}
+
+fn default_heatmap_ramp() -> String {
+    "blocks".to_string()
+}
+
+fn default_max_exec_depth() -> usize {
+    3
+}
+
+pub const MIN_UI_POLL_MS: u64 = 16;
+pub const MAX_UI_POLL_MS: u64 = 500;
+
+fn default_ui_poll_ms() -> u64 {
+    100
+}
+
+fn default_analytics_refresh_secs() -> u64 {
+    30
+}
+
+fn default_work_hours_start() -> u32 {
+    9
+}
+
+fn default_work_hours_end() -> u32 {
+    17
+}
+
+fn default_weekend_days() -> Vec<String> {
+    vec!["sat".to_string(), "sun".to_string()]
+}
+
+fn default_late_night_start() -> u32 {
+    22
+}
+
+fn default_late_night_end() -> u32 {
+    6
+}
+
+fn default_theme() -> String {
+    "dark".to_string()
+}
+
+fn default_session_idle_minutes() -> i64 {
+    crate::analysis::sessions::DEFAULT_IDLE_MINUTES
+}
+
+fn default_long_command_policy() -> String {
+    "truncate".to_string()
+}
+
+fn default_session_strategy() -> String {
+    "idle_gap".to_string()
+}
+
+fn default_shell() -> String {
+    std::env::var("SHELL")
+        .ok()
+        .and_then(|path| path.rsplit('/').next().map(str::to_string))
+        .filter(|name| matches!(name.as_str(), "bash" | "zsh" | "fish"))
+        .unwrap_or_else(|| "bash".to_string())
+}
+
+/// Overrides the config file's location.
+const CONFIG_ENV: &str = "WHISKERLOG_CONFIG";
+/// Overrides the directory holding the database and the hook log.
+const DATA_ENV: &str = "WHISKERLOG_DATA";
+const DATABASE_FILE: &str = "history.db";
+
+/// The path in `var`, if it's set and not empty.
+fn env_path(var: &str) -> Option<PathBuf> {
+    std::env::var_os(var)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
 }
 
 impl Default for Config {
@@ -22,51 +238,174 @@ impl Default for Config {
             .unwrap_or_else(|| PathBuf::from("."))
             .join("whiskerlog");
 
+        let mut history_paths = vec![
+            dirs::home_dir().unwrap_or_default().join(".bash_history"),
+            dirs::home_dir().unwrap_or_default().join(".zsh_history"),
+            dirs::home_dir()
+                .unwrap_or_default()
+                .join(".local/share/fish/fish_history"),
+            dirs::home_dir()
+                .unwrap_or_default()
+                .join(".local/share/atuin/history.db"),
+            crate::history::parser::nushell_history_path(),
+        ];
+        if cfg!(windows) {
+            history_paths.push(crate::history::parser::powershell_history_path());
+        }
+
         Self {
-            database_path: data_dir.join("history.db"),
-            history_paths: vec![
-                dirs::home_dir().unwrap_or_default().join(".bash_history"),
-                dirs::home_dir().unwrap_or_default().join(".zsh_history"),
-                dirs::home_dir()
-                    .unwrap_or_default()
-                    .join(".local/share/fish/fish_history"),
-            ],
+            database_path: data_dir.join(DATABASE_FILE),
+            history_paths,
             redaction_enabled: true,
+            redaction_patterns: Vec::new(),
             auto_import: true,
             danger_threshold: 0.7,
+            host_danger_threshold: default_host_danger_threshold(),
+            host_aliases: HashMap::new(),
             experiment_detection: true,
+            heatmap_time_range: default_heatmap_time_range(),
+            heatmap_view_mode: default_heatmap_view_mode(),
+            heatmap_thresholds: None,
+            heatmap_ramp: default_heatmap_ramp(),
+            max_exec_depth: default_max_exec_depth(),
+            danger_rules: Vec::new(),
+            shell: default_shell(),
+            timezone: None,
+            ignore_patterns: Vec::new(),
+            ignore_directories: Vec::new(),
+            max_command_length: None,
+            long_command_policy: default_long_command_policy(),
+            trusted_network_hosts: Vec::new(),
+            session_idle_minutes: default_session_idle_minutes(),
+            session_strategy: default_session_strategy(),
+            theme: default_theme(),
+            work_hours_start: default_work_hours_start(),
+            work_hours_end: default_work_hours_end(),
+            weekend_days: default_weekend_days(),
+            late_night_start: default_late_night_start(),
+            late_night_end: default_late_night_end(),
+            retention_days: None,
+            ui_poll_ms: default_ui_poll_ms(),
+            analytics_refresh_secs: default_analytics_refresh_secs(),
+            normalize_top_commands: true,
+            confirm_quit: false,
+            onboarded: false,
+            config_path: None,
+            configured_database_path: None,
         }
     }
 }
 
 impl Config {
-    pub fn load_or_create() -> Result<Self> {
-        let config_dir = dirs::config_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("whiskerlog");
+    /// Read the config, creating it with defaults if it's missing. The file is
+    /// `config_path`, else `$WHISKERLOG_CONFIG`, else `config.toml` in the
+    /// platform's config directory. The database is `database_path`, else
+    /// `history.db` in `$WHISKERLOG_DATA`, else whatever the file says; those
+    /// overrides last for this run and aren't saved.
+    pub fn load_or_create(
+        config_path: Option<&Path>,
+        database_path: Option<&Path>,
+    ) -> Result<Self> {
+        let config_path = config_path
+            .map(Path::to_path_buf)
+            .or_else(|| env_path(CONFIG_ENV))
+            .unwrap_or_else(Self::default_path);
+        let mut config = Self::read(&config_path)?;
 
-        let config_path = config_dir.join("config.toml");
+        let database_path = database_path
+            .map(Path::to_path_buf)
+            .or_else(|| env_path(DATA_ENV).map(|dir| dir.join(DATABASE_FILE)));
+        if let Some(database_path) = database_path {
+            config.override_database_path(database_path);
+        }
 
-        if config_path.exists() {
-            let content = std::fs::read_to_string(&config_path)?;
-            let config: Config = toml::from_str(&content)?;
-            Ok(config)
-        } else {
-            let config = Config::default();
+        // Saved after the override, so only the overriding data directory is created
+        if !config_path.exists() {
             config.save()?;
-            Ok(config)
         }
+        Ok(config)
     }
 
-    pub fn save(&self) -> Result<()> {
-        let config_dir = dirs::config_dir()
+    /// Read the config at `path`, creating it with defaults if it's missing.
+    /// `save` writes back to the same file.
+    #[allow(dead_code)]
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let config = Self::read(path)?;
+        if !path.exists() {
+            config.save()?;
+        }
+        Ok(config)
+    }
+
+    /// The config at `path`, or the defaults if there's no file yet.
+    fn read(path: &Path) -> Result<Self> {
+        let mut config: Config = if path.exists() {
+            let content = std::fs::read_to_string(path)?;
+            toml::from_str(&content)?
+        } else {
+            Config::default()
+        };
+        anyhow::ensure!(
+            config.session_idle_minutes > 0,
+            "session_idle_minutes in {} must be at least 1, not {}",
+            path.display(),
+            config.session_idle_minutes
+        );
+        config.config_path = Some(path.to_path_buf());
+        Ok(config)
+    }
+
+    /// The file `save` writes to.
+    pub fn path(&self) -> PathBuf {
+        self.config_path.clone().unwrap_or_else(Self::default_path)
+    }
+
+    fn default_path() -> PathBuf {
+        dirs::config_dir()
             .unwrap_or_else(|| PathBuf::from("."))
-            .join("whiskerlog");
+            .join("whiskerlog")
+            .join("config.toml")
+    }
+
+    /// Use `path` as the database for this run without changing the file.
+    pub fn override_database_path(&mut self, path: PathBuf) {
+        let configured = std::mem::replace(&mut self.database_path, path);
+        self.configured_database_path.get_or_insert(configured);
+    }
 
-        std::fs::create_dir_all(&config_dir)?;
+    /// Commands before this are dropped under `retention_days`, counted back from now.
+    pub fn retention_cutoff(&self) -> Option<DateTime<Utc>> {
+        self.retention_days
+            .map(|days| Utc::now() - Duration::days(i64::from(days)))
+    }
+
+    /// `ui_poll_ms`, brought within the supported range.
+    pub fn ui_poll_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.ui_poll_ms.clamp(MIN_UI_POLL_MS, MAX_UI_POLL_MS))
+    }
+
+    /// `analytics_refresh_secs`, at least a second.
+    pub fn analytics_refresh_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.analytics_refresh_secs.max(1))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let config_path = self.path();
+        if let Some(config_dir) = config_path.parent() {
+            std::fs::create_dir_all(config_dir)?;
+        }
 
-        let config_path = config_dir.join("config.toml");
-        let content = toml::to_string_pretty(self)?;
+        let content = match &self.configured_database_path {
+            Some(configured) => {
+                let mut value = toml::Table::try_from(self)?;
+                value.insert(
+                    "database_path".to_string(),
+                    toml::Value::try_from(configured)?,
+                );
+                toml::to_string_pretty(&value)?
+            }
+            None => toml::to_string_pretty(self)?,
+        };
         std::fs::write(config_path, content)?;
 
         // Also ensure data directory exists
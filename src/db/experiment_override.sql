@@ -0,0 +1,3 @@
+-- Set when the user marked or unmarked a command as an experiment themselves,
+-- so re-running detection leaves `is_experiment` alone
+ALTER TABLE commands ADD COLUMN manual_experiment_override INTEGER NOT NULL DEFAULT 0;
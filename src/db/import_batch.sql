@@ -0,0 +1,9 @@
+-- One row per import, so the most recent one can be undone
+CREATE TABLE IF NOT EXISTS import_batch (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    imported_at INTEGER NOT NULL, -- unix seconds
+    source TEXT NOT NULL,
+    inserted INTEGER NOT NULL,
+    min_rowid INTEGER NOT NULL,
+    max_rowid INTEGER NOT NULL
+);
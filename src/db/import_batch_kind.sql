@@ -0,0 +1,3 @@
+-- What started each import, so undo can skip the ones the watcher made.
+-- Earlier batches can't be told apart and stay undoable.
+ALTER TABLE import_batch ADD COLUMN kind TEXT NOT NULL DEFAULT 'manual';
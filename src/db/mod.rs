@@ -1,37 +1,654 @@
-use anyhow::Result;
-use chrono::TimeZone;
-use rusqlite::{params, Connection};
+use anyhow::{Context, Result};
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, TransactionBehavior};
 use std::path::Path;
+use std::time::Duration;
 
-use crate::history::Command;
+use crate::analysis::DangerAnalyzer;
+use crate::history::enricher::is_derived_session;
+use crate::history::{normalize_tags, Command, CommandEnricher, PackageRef};
+
+/// Schema migrations in order. Each runs once and bumps `schema_version` to its
+/// number; add new entries rather than editing shipped ones. The first is the
+/// original schema, written with `IF NOT EXISTS` so databases created before
+/// versioning upgrade in place.
+pub const MIGRATIONS: &[(u32, &str)] = &[
+    (1, include_str!("schema.sql")),
+    (2, include_str!("import_batch.sql")),
+    (3, include_str!("prune_state.sql")),
+    (4, include_str!("experiment_override.sql")),
+    (5, include_str!("original_command.sql")),
+    (6, include_str!("tags.sql")),
+    (7, include_str!("import_batch_kind.sql")),
+];
+
+/// How long a connection waits on another process's lock before giving up.
+const BUSY_TIMEOUT_MS: u64 = 5000;
+
+/// Rows `reenrich` loads at a time.
+const REENRICH_PAGE: usize = 500;
+
+/// Whether a column's text parses as the type `row_to_command` reads it into.
+type JsonCheck = fn(&str) -> bool;
+
+/// The `commands` columns holding `serde_json` text.
+const JSON_COLUMNS: &[(&str, JsonCheck)] = &[
+    ("network_endpoints", parses::<Vec<String>>),
+    ("packages_used", parses::<Vec<PackageRef>>),
+    ("experiment_tags", parses::<Vec<String>>),
+    ("danger_reasons", parses::<Vec<String>>),
+    ("tags", parses::<Vec<String>>),
+];
 
 pub struct Database {
     connection: Connection,
 }
 
+/// How far a history file has been imported, used to skip unchanged files.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportState {
+    pub path: String,
+    pub byte_offset: u64,
+    pub last_line_hash: Option<String>,
+    pub modified: Option<i64>,
+}
+
+/// Conditions on the `commands` table, `AND`ed together, with the values their
+/// `?` placeholders bind, for [`Database::load_commands_paginated`] and
+/// [`Database::count_commands_where`]. The SQL is written by the caller; anything
+/// that may come from the user goes in as a parameter.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CommandFilter {
+    conditions: Vec<String>,
+    params: Vec<Value>,
+}
+
+impl CommandFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a condition that binds nothing.
+    pub fn and(self, condition: impl Into<String>) -> Self {
+        self.and_bound(condition, Vec::<Value>::new())
+    }
+
+    /// Adds `condition`, whose `?` placeholders take `params` in order.
+    pub fn and_bound<I>(mut self, condition: impl Into<String>, params: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Value>,
+    {
+        self.conditions.push(condition.into());
+        self.params.extend(params.into_iter().map(Into::into));
+        self
+    }
+
+    /// Keeps rows tagged `tag`, which is bound, so it may come from the user.
+    pub fn tagged(self, tag: &str) -> Self {
+        self.and_bound(
+            "EXISTS (SELECT 1 FROM json_each(commands.tags) WHERE value = ?)",
+            [tag.to_string()],
+        )
+    }
+
+    /// The `limit` newest rows that `inner` matches.
+    pub fn newest(inner: &CommandFilter, limit: usize) -> Self {
+        Self::new().and_bound(
+            format!(
+                "id IN (SELECT id FROM commands {} ORDER BY timestamp DESC LIMIT {})",
+                inner.where_clause(),
+                limit
+            ),
+            inner.params.clone(),
+        )
+    }
+
+    /// `WHERE` and the conditions, or nothing when there are none.
+    fn where_clause(&self) -> String {
+        if self.conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", self.conditions.join(" AND "))
+        }
+    }
+}
+
+/// What started an import, recorded with its batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportKind {
+    /// Read from a shell history file, at startup or by the watcher
+    History,
+    /// Asked for by the user, like `whiskerlog import`; only these can be undone
+    Manual,
+}
+
+impl ImportKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            ImportKind::History => "history",
+            ImportKind::Manual => "manual",
+        }
+    }
+}
+
+/// Outcome of [`Database::import_json`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JsonImport {
+    /// Commands that weren't already stored
+    pub imported: usize,
+    /// Malformed records that were skipped
+    pub failed: usize,
+}
+
+/// A stored command whose JSON columns don't parse; reading it quietly
+/// treats them as empty.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BadJsonRow {
+    pub id: i64,
+    pub columns: Vec<&'static str>,
+}
+
+/// Outcome of [`Database::check_integrity`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntegrityReport {
+    /// What `PRAGMA integrity_check` found, empty when it reports ok
+    pub integrity_errors: Vec<String>,
+    pub rows_checked: usize,
+    pub bad_json: Vec<BadJsonRow>,
+}
+
+impl IntegrityReport {
+    pub fn is_healthy(&self) -> bool {
+        self.integrity_errors.is_empty() && self.bad_json.is_empty()
+    }
+}
+
+/// Aggregates over one host's stored commands, from [`Database::host_summaries`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct HostSummary {
+    pub host_id: String,
+    pub total_commands: usize,
+    /// Commands that recorded a duration
+    pub timed_commands: usize,
+    pub dangerous_commands: usize,
+    pub experiment_commands: usize,
+    pub last_seen: DateTime<Utc>,
+    /// Mean over the commands that recorded a duration, if any did
+    pub avg_duration_ms: Option<u64>,
+    /// Mean danger score over all the host's commands
+    pub avg_danger_score: f32,
+}
+
+impl ImportState {
+    /// State for a file that has never been imported.
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_string_lossy().to_string(),
+            byte_offset: 0,
+            last_line_hash: None,
+            modified: None,
+        }
+    }
+}
+
 impl Database {
+    /// Opens the database in WAL mode so a background import can write while the
+    /// UI (or a second instance) reads. SQLite keeps `-wal` and `-shm` files next
+    /// to the database while it is open.
     pub async fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
         let connection = Connection::open(path)?;
+        // Pragmas must be set before the schema batch touches the file
+        connection.busy_timeout(Duration::from_millis(BUSY_TIMEOUT_MS))?;
+        connection.query_row("PRAGMA journal_mode=WAL", [], |row| row.get::<_, String>(0))?;
+
         let mut db = Self { connection };
         db.initialize().await?;
         Ok(db)
     }
 
+    /// Applies every migration newer than the stored schema version, each in its
+    /// own transaction so a failure leaves the database at the last good version.
     async fn initialize(&mut self) -> Result<()> {
-        let sql = include_str!("schema.sql");
-        self.connection.execute_batch(sql)?;
+        self.connection.execute_batch(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER NOT NULL)",
+        )?;
+
+        for (version, sql) in MIGRATIONS {
+            // Immediate so a second instance opening at the same time waits
+            // instead of applying the same migration twice
+            let tx = self
+                .connection
+                .transaction_with_behavior(TransactionBehavior::Immediate)?;
+            if Self::stored_version(&tx)? >= *version {
+                continue;
+            }
+
+            Self::apply_migration(&tx, *version, sql)?;
+            tx.commit()?;
+        }
+
+        Ok(())
+    }
+
+    fn apply_migration(tx: &rusqlite::Transaction, version: u32, sql: &str) -> Result<()> {
+        tx.execute_batch(sql)
+            .with_context(|| format!("Failed to apply schema migration {}", version))?;
+        tx.execute("DELETE FROM schema_version", [])?;
+        tx.execute(
+            "INSERT INTO schema_version (version) VALUES (?1)",
+            params![version],
+        )?;
+        Ok(())
+    }
+
+    /// Drops all stored history and import bookkeeping, then rebuilds the schema
+    /// from the migrations, all in one transaction so a failure leaves the
+    /// database as it was. The next launch re-imports every history file.
+    pub async fn reset(&mut self) -> Result<()> {
+        let tx = self
+            .connection
+            .transaction_with_behavior(TransactionBehavior::Immediate)?;
+        tx.execute_batch(
+            "DROP TABLE IF EXISTS commands_fts;
+             DROP TABLE IF EXISTS commands;
+             DROP TABLE IF EXISTS import_state;
+             DROP TABLE IF EXISTS import_batch;
+             DROP TABLE IF EXISTS prune_state;
+             DELETE FROM schema_version;",
+        )?;
+        for (version, sql) in MIGRATIONS {
+            Self::apply_migration(&tx, *version, sql)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Reclaims the space left behind by large deletions.
+    pub async fn vacuum(&self) -> Result<()> {
+        self.connection.execute_batch("VACUUM")?;
         Ok(())
     }
 
+    /// Run SQLite's own integrity check and look for stored commands whose
+    /// JSON columns don't parse, logging each one found.
+    pub async fn check_integrity(&self) -> Result<IntegrityReport> {
+        let mut report = IntegrityReport::default();
+
+        let mut stmt = self.connection.prepare("PRAGMA integrity_check")?;
+        report.integrity_errors = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .filter(|message| !matches!(message.as_deref(), Ok("ok")))
+            .collect::<rusqlite::Result<_>>()?;
+
+        let columns: Vec<&str> = JSON_COLUMNS.iter().map(|(column, _)| *column).collect();
+        let mut stmt = self.connection.prepare(&format!(
+            "SELECT id, {} FROM commands ORDER BY id",
+            columns.join(", ")
+        ))?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            report.rows_checked += 1;
+            let mut bad = Vec::new();
+            for (i, (column, parses)) in JSON_COLUMNS.iter().enumerate() {
+                // A NULL or non-text value can't be read as JSON either
+                let text: Option<String> = row.get(i + 1).ok().flatten();
+                if !text.as_deref().is_some_and(parses) {
+                    bad.push(*column);
+                }
+            }
+            if !bad.is_empty() {
+                let id = row.get(0)?;
+                log::warn!("Command {} has malformed JSON in {}", id, bad.join(", "));
+                report.bad_json.push(BadJsonRow { id, columns: bad });
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Reset the malformed JSON columns `report` found to `[]`, in one
+    /// transaction. Returns how many rows changed.
+    pub async fn repair_json(&mut self, report: &IntegrityReport) -> Result<usize> {
+        let tx = self.connection.transaction()?;
+        let mut repaired = 0;
+        for row in &report.bad_json {
+            let assignments: Vec<String> = row
+                .columns
+                .iter()
+                .map(|column| format!("{} = '[]'", column))
+                .collect();
+            repaired += tx.execute(
+                &format!(
+                    "UPDATE commands SET {} WHERE id = ?1",
+                    assignments.join(", ")
+                ),
+                params![row.id],
+            )?;
+        }
+        tx.commit()?;
+        Ok(repaired)
+    }
+
+    /// The schema version this database has been migrated to.
+    #[allow(dead_code)]
+    pub async fn schema_version(&self) -> Result<u32> {
+        Self::stored_version(&self.connection)
+    }
+
+    fn stored_version(conn: &Connection) -> Result<u32> {
+        let version: Option<u32> =
+            conn.query_row("SELECT MAX(version) FROM schema_version", [], |row| {
+                row.get(0)
+            })?;
+        Ok(version.unwrap_or(0))
+    }
+
+    #[allow(dead_code)]
     pub async fn insert_command(&mut self, command: &Command) -> Result<i64> {
-        let _id = self.connection.execute(
+        self.insert_commands(std::slice::from_ref(command)).await?;
+        Ok(self.connection.last_insert_rowid())
+    }
+
+    /// Insert a batch of commands in a single transaction, returning the number of rows written.
+    /// Nothing is written if any command fails [`Command::validate`], and any
+    /// other failure rolls the whole batch back.
+    #[allow(dead_code)]
+    pub async fn insert_commands(&mut self, commands: &[Command]) -> Result<usize> {
+        for command in commands {
+            command.validate()?;
+        }
+        let tx = self.connection.transaction()?;
+        let inserted = Self::insert_all(&tx, commands, false, &mut |_| {})?;
+        tx.commit()?;
+        Ok(inserted.len())
+    }
+
+    /// Commands are considered the same when command text, timestamp and
+    /// session all match, so identical commands in different sessions are kept.
+    #[allow(dead_code)]
+    pub async fn command_exists(&self, command: &Command) -> Result<bool> {
+        Self::exists_in(&self.connection, command)
+    }
+
+    /// Insert only the commands that aren't already stored, returning how many were added.
+    #[allow(dead_code)]
+    pub async fn insert_commands_dedup(&mut self, commands: &[Command]) -> Result<usize> {
+        let tx = self.connection.transaction()?;
+        let inserted = Self::insert_all(&tx, commands, true, &mut |_| {})?;
+        tx.commit()?;
+        Ok(inserted.len())
+    }
+
+    /// Like `insert_commands_dedup`, but logs the rows added as one import from
+    /// `source`, which `undo_last_import` can revert when it's a manual one, and
+    /// returns the commands that were added, with their new ids, instead of a count.
+    pub async fn insert_new_commands(
+        &mut self,
+        source: &str,
+        kind: ImportKind,
+        commands: Vec<Command>,
+    ) -> Result<Vec<Command>> {
+        self.insert_new_commands_with_progress(source, kind, commands, &mut |_| {})
+            .await
+    }
+
+    /// `insert_new_commands`, calling `progress` with the running count of rows
+    /// written after each one.
+    pub async fn insert_new_commands_with_progress(
+        &mut self,
+        source: &str,
+        kind: ImportKind,
+        commands: Vec<Command>,
+        progress: &mut dyn FnMut(usize),
+    ) -> Result<Vec<Command>> {
+        let tx = self.connection.transaction()?;
+        let inserted = Self::insert_all(&tx, &commands, true, progress)?;
+        // Same transaction, so no other writer's rows can land inside the range
+        Self::record_batch(&tx, source, kind, &inserted)?;
+        tx.commit()?;
+
+        let mut inserted = inserted.into_iter().peekable();
+        Ok(commands
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, mut command)| {
+                let (_, id) = inserted.next_if(|(i, _)| *i == index)?;
+                command.id = Some(id);
+                Some(command)
+            })
+            .collect())
+    }
+
+    /// Load a JSON array or NDJSON file written by `whiskerlog export`, skipping commands
+    /// that are already stored and records that don't parse.
+    pub async fn import_json(&mut self, path: &Path) -> Result<JsonImport> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let parsed = crate::export::read_commands(&text)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+        let imported = self
+            .insert_new_commands(&path.to_string_lossy(), ImportKind::Manual, parsed.commands)
+            .await?
+            .len();
+        Ok(JsonImport {
+            imported,
+            failed: parsed.failed,
+        })
+    }
+
+    /// Deletes the commands added by the most recent manual import and forgets
+    /// that import, returning how many commands were removed. History the
+    /// watcher picked up since then is left alone.
+    pub async fn undo_last_import(&mut self) -> Result<usize> {
+        let tx = self.connection.transaction()?;
+        let batch: Option<(i64, i64, i64)> = tx
+            .query_row(
+                "SELECT id, min_rowid, max_rowid FROM import_batch
+                 WHERE kind = ?1 ORDER BY id DESC LIMIT 1",
+                params![ImportKind::Manual.as_str()],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()?;
+        let Some((batch_id, min_rowid, max_rowid)) = batch else {
+            return Ok(0);
+        };
+
+        let removed = tx.execute(
+            "DELETE FROM commands WHERE id BETWEEN ?1 AND ?2",
+            params![min_rowid, max_rowid],
+        )?;
+        tx.execute("DELETE FROM import_batch WHERE id = ?1", params![batch_id])?;
+        tx.commit()?;
+        Ok(removed)
+    }
+
+    /// Deletes every command run before `cutoff` and returns how many went.
+    /// Import offsets are left alone, and later imports skip anything before the
+    /// cutoff, so history files and re-read sources don't bring pruned commands back.
+    pub async fn prune_older_than(&mut self, cutoff: DateTime<Utc>) -> Result<usize> {
+        let cutoff = cutoff.timestamp();
+        let tx = self.connection.transaction()?;
+        // The delete trigger drops the matching full-text rows
+        let removed = tx.execute("DELETE FROM commands WHERE timestamp < ?1", params![cutoff])?;
+        tx.execute(
+            "INSERT INTO prune_state (id, pruned_before) VALUES (1, ?1)
+             ON CONFLICT(id) DO UPDATE SET
+                pruned_before = MAX(pruned_before, excluded.pruned_before)",
+            params![cutoff],
+        )?;
+        tx.commit()?;
+        Ok(removed)
+    }
+
+    /// Mark or unmark command `id` as an experiment, overriding the detector.
+    /// Returns false if there's no such command.
+    pub async fn set_experiment(&mut self, id: i64, is_experiment: bool) -> Result<bool> {
+        let updated = self.connection.execute(
+            "UPDATE commands SET is_experiment = ?1, manual_experiment_override = 1 WHERE id = ?2",
+            params![is_experiment, id],
+        )?;
+        Ok(updated > 0)
+    }
+
+    /// Replace the tags of stored command `id`; false when there's no such row.
+    pub async fn set_tags(&mut self, id: i64, tags: &[String]) -> Result<bool> {
+        let updated = self.connection.execute(
+            "UPDATE commands SET tags = ?1 WHERE id = ?2",
+            params![serde_json::to_string(&normalize_tags(tags))?, id],
+        )?;
+        Ok(updated > 0)
+    }
+
+    /// Run stored commands through the current redaction, detection and danger
+    /// rules again, so rule changes reach history imported before them.
+    /// Experiment flags set by hand are kept. One transaction, read a page at a
+    /// time; returns how many rows changed.
+    pub async fn reenrich(
+        &mut self,
+        enricher: &CommandEnricher,
+        analyzer: &DangerAnalyzer,
+    ) -> Result<usize> {
+        let tx = self.connection.transaction()?;
+        let mut updated = 0;
+        let mut last_id = 0;
+
+        loop {
+            let page: Vec<(Command, bool)> = {
+                let mut stmt =
+                    tx.prepare_cached("SELECT * FROM commands WHERE id > ?1 ORDER BY id LIMIT ?2")?;
+                let rows = stmt.query_map(params![last_id, REENRICH_PAGE as i64], |row| {
+                    Ok((row_to_command(row)?, row.get("manual_experiment_override")?))
+                })?;
+                rows.collect::<rusqlite::Result<_>>()?
+            };
+            let Some((last, _)) = page.last() else {
+                break;
+            };
+            last_id = last.id.unwrap_or(i64::MAX);
+
+            let mut stmt = tx.prepare_cached(
+                "UPDATE commands SET
+                    command = ?1, original_command = ?2, network_endpoints = ?3,
+                    packages_used = ?4, is_experiment = ?5, experiment_tags = ?6,
+                    is_dangerous = ?7, danger_score = ?8, danger_reasons = ?9
+                 WHERE id = ?10",
+            )?;
+            for (stored, manual_experiment) in page {
+                let mut command = stored.clone();
+                enricher.reenrich(&mut command);
+                if manual_experiment {
+                    command.is_experiment = stored.is_experiment;
+                    command.experiment_tags = stored.experiment_tags.clone();
+                }
+                let danger = analyzer.assess(&command.command);
+                command.is_dangerous = danger.is_dangerous;
+                command.danger_score = danger.score;
+                command.danger_reasons = danger.reasons;
+
+                let columns = enrichment_columns(&command);
+                if columns == enrichment_columns(&stored) {
+                    continue;
+                }
+                let (
+                    text,
+                    original,
+                    endpoints,
+                    packages,
+                    experiment,
+                    tags,
+                    dangerous,
+                    score,
+                    reasons,
+                ) = columns;
+                stmt.execute(params![
+                    text, original, endpoints, packages, experiment, tags, dangerous, score,
+                    reasons, command.id
+                ])?;
+                updated += 1;
+            }
+        }
+
+        tx.commit()?;
+        Ok(updated)
+    }
+
+    /// Unix seconds before which commands have been pruned, if ever.
+    fn pruned_before(conn: &Connection) -> Result<Option<i64>> {
+        let cutoff = conn
+            .query_row(
+                "SELECT pruned_before FROM prune_state WHERE id = 1",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(cutoff)
+    }
+
+    fn record_batch(
+        conn: &Connection,
+        source: &str,
+        kind: ImportKind,
+        inserted: &[(usize, i64)],
+    ) -> Result<()> {
+        let (Some(min_rowid), Some(max_rowid)) = (
+            inserted.iter().map(|(_, id)| *id).min(),
+            inserted.iter().map(|(_, id)| *id).max(),
+        ) else {
+            return Ok(());
+        };
+
+        conn.execute(
+            "INSERT INTO import_batch (imported_at, source, inserted, min_rowid, max_rowid, kind)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                Utc::now().timestamp(),
+                source,
+                inserted.len(),
+                min_rowid,
+                max_rowid,
+                kind.as_str()
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Positions in `commands` of the rows written, with their new ids. With
+    /// `skip_existing`, commands already stored or from before the last prune are
+    /// left out.
+    fn insert_all(
+        conn: &Connection,
+        commands: &[Command],
+        skip_existing: bool,
+        progress: &mut dyn FnMut(usize),
+    ) -> Result<Vec<(usize, i64)>> {
+        let mut stmt = conn.prepare_cached(
             "INSERT INTO commands (
                 command, timestamp, exit_code, duration, working_directory,
                 session_id, host_id, network_endpoints, packages_used,
                 is_experiment, experiment_tags, is_dangerous, danger_score,
-                danger_reasons, shell
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
-            params![
+                danger_reasons, shell, original_command, tags
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)",
+        )?;
+
+        let pruned_before = if skip_existing {
+            Self::pruned_before(conn)?
+        } else {
+            None
+        };
+
+        let mut inserted = Vec::new();
+        for (index, command) in commands.iter().enumerate() {
+            if skip_existing
+                && (pruned_before.is_some_and(|cutoff| command.timestamp.timestamp() < cutoff)
+                    || Self::exists_in(conn, command)?)
+            {
+                continue;
+            }
+
+            stmt.execute(params![
                 command.command,
                 command.timestamp.timestamp(),
                 command.exit_code,
@@ -47,10 +664,88 @@ impl Database {
                 command.danger_score,
                 serde_json::to_string(&command.danger_reasons).unwrap_or_default(),
                 command.shell,
-            ],
+                command.original_command,
+                serde_json::to_string(&command.tags).unwrap_or_default(),
+            ])?;
+            inserted.push((index, conn.last_insert_rowid()));
+            progress(inserted.len());
+        }
+
+        Ok(inserted)
+    }
+
+    fn exists_in(conn: &Connection, command: &Command) -> Result<bool> {
+        // A made-up session can change when a file is read again, so it doesn't
+        // tell apart two runs of the same command at the same time
+        if is_derived_session(&command.shell, &command.session_id) {
+            let mut stmt = conn.prepare_cached(
+                "SELECT session_id FROM commands WHERE command = ?1 AND timestamp = ?2 AND shell = ?3",
+            )?;
+            let sessions = stmt.query_map(
+                params![
+                    command.command,
+                    command.timestamp.timestamp(),
+                    command.shell
+                ],
+                |row| row.get::<_, String>(0),
+            )?;
+            for session in sessions {
+                if is_derived_session(&command.shell, &session?) {
+                    return Ok(true);
+                }
+            }
+            return Ok(false);
+        }
+
+        let mut stmt = conn.prepare_cached(
+            "SELECT 1 FROM commands WHERE command = ?1 AND timestamp = ?2 AND session_id = ?3 LIMIT 1",
         )?;
+        let exists = stmt.exists(params![
+            command.command,
+            command.timestamp.timestamp(),
+            command.session_id
+        ])?;
 
-        Ok(self.connection.last_insert_rowid())
+        Ok(exists)
+    }
+
+    pub async fn get_import_state(&self, path: &str) -> Result<Option<ImportState>> {
+        let state = self
+            .connection
+            .query_row(
+                "SELECT path, byte_offset, last_line_hash, modified_at FROM import_state WHERE path = ?1",
+                params![path],
+                |row| {
+                    Ok(ImportState {
+                        path: row.get(0)?,
+                        byte_offset: row.get::<_, i64>(1)? as u64,
+                        last_line_hash: row.get(2)?,
+                        modified: row.get(3)?,
+                    })
+                },
+            )
+            .optional()?;
+
+        Ok(state)
+    }
+
+    pub async fn set_import_state(&mut self, state: &ImportState) -> Result<()> {
+        self.connection.execute(
+            "INSERT INTO import_state (path, byte_offset, last_line_hash, modified_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, CURRENT_TIMESTAMP)
+             ON CONFLICT(path) DO UPDATE SET
+                byte_offset = excluded.byte_offset,
+                last_line_hash = excluded.last_line_hash,
+                modified_at = excluded.modified_at,
+                updated_at = excluded.updated_at",
+            params![
+                state.path,
+                state.byte_offset as i64,
+                state.last_line_hash,
+                state.modified
+            ],
+        )?;
+        Ok(())
     }
 
     #[allow(dead_code)]
@@ -59,39 +754,167 @@ impl Database {
         offset: usize,
         limit: usize,
     ) -> Result<Vec<Command>> {
+        self.load_commands_paginated(&CommandFilter::new(), "timestamp DESC", offset, limit)
+            .await
+    }
+
+    /// One page of commands matching `filter`, in `order_by` order. `order_by`
+    /// is a trusted SQL fragment built by the caller, never user input; `id` is
+    /// appended as a tie-breaker so pages don't overlap.
+    pub async fn load_commands_paginated(
+        &mut self,
+        filter: &CommandFilter,
+        order_by: &str,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<Command>> {
+        let sql = format!(
+            "SELECT * FROM commands {} ORDER BY {}, id LIMIT ? OFFSET ?",
+            filter.where_clause(),
+            order_by
+        );
+        let params = filter
+            .params
+            .iter()
+            .cloned()
+            .chain([Value::Integer(limit as i64), Value::Integer(offset as i64)]);
+
+        let mut stmt = self.connection.prepare(&sql)?;
+        let command_iter = stmt.query_map(params_from_iter(params), row_to_command)?;
+
+        let mut commands = Vec::new();
+        for command in command_iter {
+            commands.push(command?);
+        }
+
+        Ok(commands)
+    }
+
+    /// Every command matching `filter`, oldest first.
+    pub async fn get_commands_where(&mut self, filter: &CommandFilter) -> Result<Vec<Command>> {
         let sql = format!(
-            "SELECT * FROM commands ORDER BY timestamp DESC LIMIT {} OFFSET {}",
-            limit, offset
+            "SELECT * FROM commands {} ORDER BY timestamp, id",
+            filter.where_clause()
         );
 
         let mut stmt = self.connection.prepare(&sql)?;
-        let command_iter = stmt.query_map([], |row| {
-            Ok(Command {
-                id: Some(row.get(0)?),
-                command: row.get(1)?,
-                timestamp: chrono::Utc
-                    .timestamp_opt(row.get(2)?, 0)
+        let command_iter = stmt.query_map(params_from_iter(&filter.params), row_to_command)?;
+
+        let mut commands = Vec::new();
+        for command in command_iter {
+            commands.push(command?);
+        }
+
+        Ok(commands)
+    }
+
+    /// Number of commands matching `filter`, for sizing paged views.
+    pub async fn count_commands_where(&self, filter: &CommandFilter) -> Result<usize> {
+        let sql = format!("SELECT COUNT(*) FROM commands {}", filter.where_clause());
+        let count: i64 =
+            self.connection
+                .query_row(&sql, params_from_iter(&filter.params), |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
+    /// One summary per host, busiest first, aggregated in SQL so nothing has to
+    /// load every row.
+    pub async fn host_summaries(&self) -> Result<Vec<HostSummary>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT host_id, COUNT(*) AS c, SUM(is_dangerous), SUM(is_experiment),
+                    MAX(timestamp), AVG(duration), AVG(danger_score), COUNT(duration)
+             FROM commands GROUP BY host_id ORDER BY c DESC, host_id ASC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(HostSummary {
+                host_id: row.get(0)?,
+                total_commands: row.get::<_, i64>(1)? as usize,
+                dangerous_commands: row.get::<_, i64>(2)? as usize,
+                experiment_commands: row.get::<_, i64>(3)? as usize,
+                last_seen: Utc
+                    .timestamp_opt(row.get(4)?, 0)
                     .single()
-                    .unwrap_or_else(chrono::Utc::now),
-                exit_code: row.get(3)?,
-                duration: row.get::<_, Option<i64>>(4)?.map(|d| d as u64),
-                working_directory: row.get(5)?,
-                session_id: row.get(6)?,
-                host_id: row.get(7)?,
-                network_endpoints: serde_json::from_str(&row.get::<_, String>(8)?)
-                    .unwrap_or_default(),
-                packages_used: serde_json::from_str(&row.get::<_, String>(9)?).unwrap_or_default(),
-                is_experiment: row.get(10)?,
-                experiment_tags: serde_json::from_str(&row.get::<_, String>(11)?)
-                    .unwrap_or_default(),
-                is_dangerous: row.get(12)?,
-                danger_score: row.get(13)?,
-                danger_reasons: serde_json::from_str(&row.get::<_, String>(14)?)
-                    .unwrap_or_default(),
-                shell: row.get(15)?,
+                    .unwrap_or_else(Utc::now),
+                // AVG skips NULL durations, so only commands that recorded one count
+                avg_duration_ms: row.get::<_, Option<f64>>(5)?.map(|avg| avg.round() as u64),
+                avg_danger_score: row.get::<_, Option<f64>>(6)?.unwrap_or(0.0) as f32,
+                timed_commands: row.get::<_, i64>(7)? as usize,
             })
         })?;
 
+        let mut summaries = Vec::new();
+        for row in rows {
+            summaries.push(row?);
+        }
+        Ok(summaries)
+    }
+
+    #[allow(dead_code)]
+    pub async fn count_commands(&self) -> Result<usize> {
+        self.count_commands_where(&CommandFilter::new()).await
+    }
+
+    /// Number of commands in the half-open range `[start, end)`.
+    #[allow(dead_code)]
+    pub async fn count_commands_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<usize> {
+        if end <= start {
+            return Ok(0);
+        }
+
+        let count: i64 = self.connection.query_row(
+            "SELECT COUNT(*) FROM commands WHERE timestamp >= ?1 AND timestamp < ?2",
+            params![start.timestamp(), end.timestamp()],
+            |row| row.get(0),
+        )?;
+        Ok(count as usize)
+    }
+
+    /// Commands in the half-open range `[start, end)`, newest first. Timestamps
+    /// are compared as stored epoch seconds so the timestamp index is used.
+    pub async fn get_commands_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<Command>> {
+        if end <= start {
+            return Ok(Vec::new());
+        }
+
+        let mut stmt = self.connection.prepare(
+            "SELECT * FROM commands WHERE timestamp >= ?1 AND timestamp < ?2 ORDER BY timestamp DESC, id",
+        )?;
+        let command_iter =
+            stmt.query_map(params![start.timestamp(), end.timestamp()], row_to_command)?;
+
+        let mut commands = Vec::new();
+        for command in command_iter {
+            commands.push(command?);
+        }
+
+        Ok(commands)
+    }
+
+    /// Commands whose enrichment recorded package `name`, from any manager or
+    /// just `manager`, newest first. Names and managers match case-insensitively.
+    pub async fn commands_with_package(
+        &self,
+        manager: Option<&str>,
+        name: &str,
+    ) -> Result<Vec<Command>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT * FROM commands WHERE EXISTS (
+                SELECT 1 FROM json_each(commands.packages_used)
+                WHERE json_extract(value, '$.name') = ?1 COLLATE NOCASE
+                    AND (?2 IS NULL OR json_extract(value, '$.manager') = ?2 COLLATE NOCASE)
+            )
+            ORDER BY timestamp DESC, id DESC",
+        )?;
+        let command_iter = stmt.query_map(params![name, manager], row_to_command)?;
+
         let mut commands = Vec::new();
         for command in command_iter {
             commands.push(command?);
@@ -100,7 +923,6 @@ impl Database {
         Ok(commands)
     }
 
-    #[allow(dead_code)]
     pub async fn get_commands(&mut self, limit: Option<usize>) -> Result<Vec<Command>> {
         let sql = match limit {
             Some(l) => format!("SELECT * FROM commands ORDER BY timestamp DESC LIMIT {}", l),
@@ -108,32 +930,7 @@ impl Database {
         };
 
         let mut stmt = self.connection.prepare(&sql)?;
-        let command_iter = stmt.query_map([], |row| {
-            Ok(Command {
-                id: Some(row.get(0)?),
-                command: row.get(1)?,
-                timestamp: chrono::Utc
-                    .timestamp_opt(row.get(2)?, 0)
-                    .single()
-                    .unwrap_or_else(chrono::Utc::now),
-                exit_code: row.get(3)?,
-                duration: row.get::<_, Option<i64>>(4)?.map(|d| d as u64),
-                working_directory: row.get(5)?,
-                session_id: row.get(6)?,
-                host_id: row.get(7)?,
-                network_endpoints: serde_json::from_str(&row.get::<_, String>(8)?)
-                    .unwrap_or_default(),
-                packages_used: serde_json::from_str(&row.get::<_, String>(9)?).unwrap_or_default(),
-                is_experiment: row.get(10)?,
-                experiment_tags: serde_json::from_str(&row.get::<_, String>(11)?)
-                    .unwrap_or_default(),
-                is_dangerous: row.get(12)?,
-                danger_score: row.get(13)?,
-                danger_reasons: serde_json::from_str(&row.get::<_, String>(14)?)
-                    .unwrap_or_default(),
-                shell: row.get(15)?,
-            })
-        })?;
+        let command_iter = stmt.query_map([], row_to_command)?;
 
         let mut commands = Vec::new();
         for command in command_iter {
@@ -143,3 +940,60 @@ impl Database {
         Ok(commands)
     }
 }
+
+/// What `reenrich` can change about a row, as stored.
+type EnrichmentColumns<'a> = (
+    &'a str,
+    Option<&'a str>,
+    String,
+    String,
+    bool,
+    String,
+    bool,
+    f32,
+    String,
+);
+
+fn enrichment_columns(command: &Command) -> EnrichmentColumns<'_> {
+    (
+        &command.command,
+        command.original_command.as_deref(),
+        serde_json::to_string(&command.network_endpoints).unwrap_or_default(),
+        serde_json::to_string(&command.packages_used).unwrap_or_default(),
+        command.is_experiment,
+        serde_json::to_string(&command.experiment_tags).unwrap_or_default(),
+        command.is_dangerous,
+        command.danger_score,
+        serde_json::to_string(&command.danger_reasons).unwrap_or_default(),
+    )
+}
+
+fn parses<T: serde::de::DeserializeOwned>(text: &str) -> bool {
+    serde_json::from_str::<T>(text).is_ok()
+}
+
+fn row_to_command(row: &rusqlite::Row) -> rusqlite::Result<Command> {
+    Ok(Command {
+        id: Some(row.get(0)?),
+        command: row.get(1)?,
+        timestamp: chrono::Utc
+            .timestamp_opt(row.get(2)?, 0)
+            .single()
+            .unwrap_or_else(chrono::Utc::now),
+        exit_code: row.get(3)?,
+        duration: row.get::<_, Option<i64>>(4)?.map(|d| d as u64),
+        working_directory: row.get(5)?,
+        session_id: row.get(6)?,
+        host_id: row.get(7)?,
+        network_endpoints: serde_json::from_str(&row.get::<_, String>(8)?).unwrap_or_default(),
+        packages_used: serde_json::from_str(&row.get::<_, String>(9)?).unwrap_or_default(),
+        is_experiment: row.get(10)?,
+        experiment_tags: serde_json::from_str(&row.get::<_, String>(11)?).unwrap_or_default(),
+        is_dangerous: row.get(12)?,
+        danger_score: row.get(13)?,
+        danger_reasons: serde_json::from_str(&row.get::<_, String>(14)?).unwrap_or_default(),
+        shell: row.get(15)?,
+        original_command: row.get("original_command")?,
+        tags: serde_json::from_str(&row.get::<_, String>("tags")?).unwrap_or_default(),
+    })
+}
@@ -0,0 +1,2 @@
+-- The full text of a command stored cut down to `max_command_length`
+ALTER TABLE commands ADD COLUMN original_command TEXT;
@@ -0,0 +1,6 @@
+-- Everything before `pruned_before` has been deleted on purpose, so imports
+-- don't bring it back
+CREATE TABLE IF NOT EXISTS prune_state (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    pruned_before INTEGER NOT NULL -- unix seconds
+);
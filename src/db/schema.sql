@@ -24,6 +24,16 @@ CREATE INDEX IF NOT EXISTS idx_commands_host ON commands(host_id);
 CREATE INDEX IF NOT EXISTS idx_commands_dangerous ON commands(is_dangerous);
 CREATE INDEX IF NOT EXISTS idx_commands_experiment ON commands(is_experiment);
 CREATE INDEX IF NOT EXISTS idx_commands_shell ON commands(shell);
+CREATE INDEX IF NOT EXISTS idx_commands_dedup ON commands(command, timestamp, session_id);
+
+-- Per-file import bookkeeping so history isn't re-imported on every launch
+CREATE TABLE IF NOT EXISTS import_state (
+    path TEXT PRIMARY KEY,
+    byte_offset INTEGER NOT NULL DEFAULT 0,
+    last_line_hash TEXT,
+    modified_at INTEGER, -- file mtime, unix seconds
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
 
 -- Full-text search support
 CREATE VIRTUAL TABLE IF NOT EXISTS commands_fts USING fts5(
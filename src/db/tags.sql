@@ -0,0 +1,2 @@
+-- Labels the user gave a command, as a JSON array of strings
+ALTER TABLE commands ADD COLUMN tags TEXT NOT NULL DEFAULT '[]';
@@ -0,0 +1,89 @@
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+/// A program the TUI can hand the terminal to for reading up on a tool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocViewer {
+    Tldr,
+    Man,
+}
+
+impl DocViewer {
+    pub fn program(self) -> &'static str {
+        match self {
+            DocViewer::Tldr => "tldr",
+            DocViewer::Man => "man",
+        }
+    }
+
+    /// The viewer to use on this machine: tldr for its short examples, else
+    /// man, or `None` when neither is on `PATH`.
+    pub fn find() -> Option<Self> {
+        let path = std::env::var_os("PATH")?;
+        Self::find_in(&path)
+    }
+
+    /// `find` against the given `PATH` value.
+    pub fn find_in(path: &OsStr) -> Option<Self> {
+        [DocViewer::Tldr, DocViewer::Man]
+            .into_iter()
+            .find(|viewer| find_program(viewer.program(), path).is_some())
+    }
+
+    /// The command showing `tool`'s page. The tool is passed as an argument,
+    /// never through a shell.
+    pub fn command(self, tool: &str) -> std::process::Command {
+        let mut command = std::process::Command::new(self.program());
+        command.arg(tool);
+        command
+    }
+
+    /// Whether the viewer waits on its own for the page to be read. man runs
+    /// a pager; tldr prints and exits, so its output needs one.
+    pub fn pages_itself(self) -> bool {
+        self == DocViewer::Man
+    }
+}
+
+/// The pager to read printed docs in: `$PAGER`, else `less -R`, or `None`
+/// when that isn't on `PATH`.
+pub fn pager() -> Option<std::process::Command> {
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    pager_in(std::env::var("PAGER").ok().as_deref(), &path)
+}
+
+/// `pager` with the given `$PAGER` and `PATH` values. The pager's words are
+/// split on whitespace, never run through a shell.
+pub fn pager_in(pager: Option<&str>, path: &OsStr) -> Option<std::process::Command> {
+    let pager = pager.filter(|p| !p.trim().is_empty()).unwrap_or("less -R");
+    let mut words = pager.split_whitespace();
+    let program = words.next()?;
+    let program = if program.contains('/') {
+        Some(PathBuf::from(program)).filter(|p| is_executable(p))?
+    } else {
+        find_program(program, path)?
+    };
+
+    let mut command = std::process::Command::new(program);
+    command.args(words);
+    Some(command)
+}
+
+/// The first executable file named `program` in the directories of `path`.
+pub fn find_program(program: &str, path: &OsStr) -> Option<PathBuf> {
+    std::env::split_paths(path)
+        .map(|dir| dir.join(program))
+        .find(|candidate| is_executable(candidate))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file() || path.with_extension("exe").is_file()
+}
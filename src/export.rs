@@ -0,0 +1,256 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+
+use crate::analysis::network_analyzer::endpoint_host;
+use crate::analysis::normalize::strip_arguments;
+use crate::history::enricher::CommandEnricher;
+use crate::history::Command;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    Json,
+    Ndjson,
+    Csv,
+}
+
+const CSV_HEADER: &str = "id,command,timestamp,exit_code,duration,working_directory,session_id,host_id,network_endpoints,packages_used,is_experiment,experiment_tags,is_dangerous,danger_score,danger_reasons,shell,tags";
+
+/// Accepts either a plain date (`2024-01-31`, midnight UTC) or a full RFC 3339 timestamp.
+pub fn parse_since(value: &str) -> Result<DateTime<Utc>> {
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc());
+    }
+    parse_timestamp("--since", value)
+}
+
+/// The end of a window, exclusive: a plain date means the midnight after it,
+/// so `--until 2024-01-31` still includes that day.
+pub fn parse_until(value: &str) -> Result<DateTime<Utc>> {
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        let next = date.succ_opt().unwrap_or(date);
+        return Ok(next.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc());
+    }
+    parse_timestamp("--until", value)
+}
+
+fn parse_timestamp(flag: &str, value: &str) -> Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|_| anyhow!("Invalid {} value '{}', expected YYYY-MM-DD", flag, value))
+}
+
+pub fn write_commands<W: Write>(
+    commands: &[Command],
+    format: ExportFormat,
+    writer: &mut W,
+) -> Result<()> {
+    match format {
+        ExportFormat::Json => {
+            serde_json::to_writer_pretty(&mut *writer, commands)?;
+            writeln!(writer)?;
+        }
+        ExportFormat::Ndjson => {
+            for command in commands {
+                serde_json::to_writer(&mut *writer, command)?;
+                writeln!(writer)?;
+            }
+        }
+        ExportFormat::Csv => {
+            writeln!(writer, "{}", CSV_HEADER)?;
+            for command in commands {
+                writeln!(writer, "{}", csv_row(command))?;
+            }
+        }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Scrub `commands` for sharing: arguments are dropped down to the tool and its
+/// subcommand, tags are dropped, working directories become `<dir-N>`, and
+/// remote hosts and endpoint hosts become `<host-N>`. Tokens are numbered in
+/// order of first appearance, so the same directory or host maps to the same
+/// token throughout one export.
+pub fn anonymize(commands: &mut [Command]) {
+    // Names of machines and users, which a would-be subcommand mustn't be
+    let mut host_words: HashSet<String> = HashSet::new();
+    for cmd in commands.iter() {
+        let machine = cmd
+            .host_id
+            .split_once(':')
+            .map_or(&*cmd.host_id, |(_, rest)| rest);
+        host_words.extend(
+            machine
+                .split(['@', '/'])
+                .filter(|word| !word.is_empty())
+                .map(str::to_lowercase),
+        );
+        host_words.extend(
+            cmd.network_endpoints
+                .iter()
+                .filter_map(|endpoint| endpoint_host(endpoint))
+                .map(str::to_lowercase),
+        );
+    }
+
+    let mut directories = Tokens::new("dir");
+    let mut hosts = Tokens::new("host");
+    for cmd in commands {
+        cmd.command = strip_arguments(&cmd.command, |word| {
+            !host_words.contains(&word.to_lowercase())
+        });
+        cmd.original_command = None;
+        // Tags are the user's own words, like `incident-acme`
+        cmd.tags.clear();
+        cmd.working_directory = cmd
+            .working_directory
+            .as_deref()
+            .map(|dir| directories.get(dir));
+        // This machine's own commands aren't named after it
+        if cmd.host_id != "local" {
+            cmd.host_id = hosts.get(&cmd.host_id);
+        }
+        cmd.network_endpoints = cmd
+            .network_endpoints
+            .iter()
+            .filter_map(|endpoint| {
+                let (scheme, _) = endpoint.split_once("://")?;
+                let host = endpoint_host(endpoint)?;
+                Some(format!("{}://{}", scheme, hosts.get(host)))
+            })
+            .collect();
+    }
+}
+
+/// Numbered stand-ins for the values of one kind, handed out in order.
+struct Tokens {
+    prefix: &'static str,
+    assigned: HashMap<String, String>,
+}
+
+impl Tokens {
+    fn new(prefix: &'static str) -> Self {
+        Self {
+            prefix,
+            assigned: HashMap::new(),
+        }
+    }
+
+    fn get(&mut self, value: &str) -> String {
+        let next = self.assigned.len() + 1;
+        self.assigned
+            .entry(value.to_string())
+            .or_insert_with(|| format!("<{}-{}>", self.prefix, next))
+            .clone()
+    }
+}
+
+/// Commands read back from a JSON or NDJSON export.
+#[derive(Debug, Default)]
+pub struct ParsedCommands {
+    pub commands: Vec<Command>,
+    /// Records that weren't valid commands and were skipped
+    pub failed: usize,
+}
+
+/// Reads what [`write_commands`] produces for `Json` or `Ndjson`. A document
+/// starting with `[` is a JSON array, anything else is one command per line.
+/// Commands with an empty session id get one derived from their timestamps,
+/// as history without sessions does. Malformed records, and commands that fail
+/// [`Command::validate`], are counted and skipped; only an unreadable array fails.
+pub fn read_commands(text: &str) -> Result<ParsedCommands> {
+    // Each record with where it came from, for the warnings
+    let mut records: Vec<(String, Result<Command>)> = Vec::new();
+
+    if text.trim_start().starts_with('[') {
+        let values: Vec<serde_json::Value> =
+            serde_json::from_str(text).map_err(|err| anyhow!("Invalid JSON array: {}", err))?;
+        for (index, value) in values.into_iter().enumerate() {
+            let record = serde_json::from_value(value).map_err(anyhow::Error::from);
+            records.push((format!("record {}", index + 1), record));
+        }
+    } else {
+        for (number, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record = serde_json::from_str(line).map_err(anyhow::Error::from);
+            records.push((format!("line {}", number + 1), record));
+        }
+    }
+
+    let mut sessionless: Vec<&mut Command> = records
+        .iter_mut()
+        .filter_map(|(_, record)| record.as_mut().ok())
+        .filter(|command| command.session_id.is_empty())
+        .collect();
+    sessionless.sort_by_key(|command| command.timestamp);
+    CommandEnricher::new().derive_sessions(sessionless);
+
+    let mut parsed = ParsedCommands::default();
+    for (source, record) in records {
+        match record.and_then(valid) {
+            Ok(command) => parsed.commands.push(command),
+            Err(err) => {
+                log::warn!("Skipping {}: {}", source, err);
+                parsed.failed += 1;
+            }
+        }
+    }
+
+    Ok(parsed)
+}
+
+fn valid(command: Command) -> Result<Command> {
+    command.validate()?;
+    Ok(command)
+}
+
+fn csv_row(command: &Command) -> String {
+    // List fields are flattened to `;`-joined strings, packages as manager:name[@version]
+    let packages: Vec<String> = command
+        .packages_used
+        .iter()
+        .map(|pkg| match &pkg.version {
+            Some(version) => format!("{}:{}@{}", pkg.manager, pkg.name, version),
+            None => format!("{}:{}", pkg.manager, pkg.name),
+        })
+        .collect();
+
+    let fields = [
+        command.id.map(|id| id.to_string()).unwrap_or_default(),
+        command.command.clone(),
+        command.timestamp.to_rfc3339(),
+        command.exit_code.map(|c| c.to_string()).unwrap_or_default(),
+        command.duration.map(|d| d.to_string()).unwrap_or_default(),
+        command.working_directory.clone().unwrap_or_default(),
+        command.session_id.clone(),
+        command.host_id.clone(),
+        command.network_endpoints.join(";"),
+        packages.join(";"),
+        command.is_experiment.to_string(),
+        command.experiment_tags.join(";"),
+        command.is_dangerous.to_string(),
+        command.danger_score.to_string(),
+        command.danger_reasons.join(";"),
+        command.shell.clone(),
+        command.tags.join(";"),
+    ];
+
+    fields
+        .iter()
+        .map(|field| csv_escape(field))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+pub(crate) fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
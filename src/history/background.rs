@@ -0,0 +1,173 @@
+use anyhow::Result;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+
+use super::watcher::{import_appended_with_progress, ImportProgress};
+use super::{Command, HistoryParser};
+use crate::config::Config;
+use crate::db::Database;
+
+/// Rows between progress updates sent back while a file is being stored.
+const PROGRESS_EVERY: usize = 250;
+
+enum ImportEvent {
+    /// Started on the file at this index
+    File(usize),
+    /// Totals across all files so far
+    Progress {
+        parsed: usize,
+        stored: usize,
+    },
+    /// The commands one file added
+    Imported(Vec<Command>),
+    Finished(std::result::Result<usize, String>),
+}
+
+/// Imports history files on a thread of its own, over its own database
+/// connection, so the screen keeps drawing while a big history is read in.
+pub struct BackgroundImport {
+    receiver: Receiver<ImportEvent>,
+    pub current_file: usize,
+    /// Commands read from the files so far
+    pub parsed: usize,
+    /// Of those, the ones that weren't stored yet and now are
+    pub stored: usize,
+    /// Set once the import is over: the commands stored, or why it stopped
+    pub finished: Option<std::result::Result<usize, String>>,
+    imported: Vec<Command>,
+}
+
+impl BackgroundImport {
+    /// Start importing whatever was appended to each shell's history file since
+    /// the last import, in order.
+    pub async fn start(config: &Config, files: Vec<(&'static str, PathBuf)>) -> Result<Self> {
+        let parser = HistoryParser::from_config(config);
+        let mut db = Database::new(&config.database_path).await?;
+
+        let (tx, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            // Parsing and the database aren't Send, so they get this thread's own runtime
+            let runtime = match tokio::runtime::Builder::new_current_thread().build() {
+                Ok(runtime) => runtime,
+                Err(err) => {
+                    let _ = tx.send(ImportEvent::Finished(Err(err.to_string())));
+                    return;
+                }
+            };
+
+            let (mut parsed, mut stored) = (0, 0);
+            for (index, (shell, path)) in files.iter().enumerate() {
+                let _ = tx.send(ImportEvent::File(index));
+                let mut file_parsed = 0;
+                let mut progress = |file: ImportProgress| {
+                    file_parsed = file.parsed;
+                    if file.stored == 0 || file.stored.is_multiple_of(PROGRESS_EVERY) {
+                        let _ = tx.send(ImportEvent::Progress {
+                            parsed: parsed + file.parsed,
+                            stored: stored + file.stored,
+                        });
+                    }
+                };
+                let result = runtime.block_on(import_appended_with_progress(
+                    &parser,
+                    &mut db,
+                    shell,
+                    path,
+                    &mut progress,
+                ));
+                match result {
+                    Ok(commands) => {
+                        parsed += file_parsed;
+                        stored += commands.len();
+                        let _ = tx.send(ImportEvent::Progress { parsed, stored });
+                        if !commands.is_empty() && tx.send(ImportEvent::Imported(commands)).is_err()
+                        {
+                            return;
+                        }
+                    }
+                    Err(err) => {
+                        let message = format!("{}: {}", path.display(), err);
+                        let _ = tx.send(ImportEvent::Finished(Err(message)));
+                        return;
+                    }
+                }
+            }
+            let _ = tx.send(ImportEvent::Finished(Ok(stored)));
+        });
+
+        Ok(Self {
+            receiver,
+            current_file: 0,
+            parsed: 0,
+            stored: 0,
+            finished: None,
+            imported: Vec::new(),
+        })
+    }
+
+    /// Take in whatever the import thread reported since the last call.
+    pub fn poll(&mut self) {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(ImportEvent::File(index)) => self.current_file = index,
+                Ok(ImportEvent::Progress { parsed, stored }) => {
+                    self.parsed = parsed;
+                    self.stored = stored;
+                }
+                Ok(ImportEvent::Imported(commands)) => self.imported.extend(commands),
+                Ok(ImportEvent::Finished(result)) => self.finished = Some(result),
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    // The thread died without saying how it went
+                    if self.finished.is_none() {
+                        self.finished = Some(Err("import stopped unexpectedly".to_string()));
+                    }
+                    break;
+                }
+            }
+        }
+    }
+
+    /// The commands stored since the last call, with their ids.
+    pub fn take_commands(&mut self) -> Vec<Command> {
+        std::mem::take(&mut self.imported)
+    }
+}
+
+/// Re-runs enrichment and danger scoring over the stored history on a thread
+/// of its own, like [`BackgroundImport`], since it rewrites every row.
+pub struct BackgroundReenrich {
+    receiver: Receiver<std::result::Result<usize, String>>,
+}
+
+impl BackgroundReenrich {
+    /// Start rescoring with `config`'s current rules.
+    pub async fn start(config: &Config) -> Result<Self> {
+        let enricher = super::CommandEnricher::from_config(config);
+        let analyzer = crate::analysis::DangerAnalyzer::from_config(config);
+        let mut db = Database::new(&config.database_path).await?;
+
+        let (tx, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let result = tokio::runtime::Builder::new_current_thread()
+                .build()
+                .map_err(anyhow::Error::from)
+                .and_then(|runtime| runtime.block_on(db.reenrich(&enricher, &analyzer)));
+            let _ = tx.send(result.map_err(|err| err.to_string()));
+        });
+
+        Ok(Self { receiver })
+    }
+
+    /// How many commands changed once it's done, or why it stopped; `None`
+    /// while it runs.
+    pub fn poll(&self) -> Option<std::result::Result<usize, String>> {
+        match self.receiver.try_recv() {
+            Ok(result) => Some(result),
+            Err(mpsc::TryRecvError::Empty) => None,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                Some(Err("re-enrichment stopped unexpectedly".to_string()))
+            }
+        }
+    }
+}
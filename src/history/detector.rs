@@ -1,11 +1,8 @@
 use super::PackageRef;
+use crate::config::DangerRule;
 use regex::Regex;
 
-pub struct HostDetector {
-    ssh_regex: Regex,
-    docker_regex: Regex,
-    kubectl_regex: Regex,
-}
+pub struct HostDetector;
 
 impl Default for HostDetector {
     fn default() -> Self {
@@ -15,41 +12,270 @@ impl Default for HostDetector {
 
 impl HostDetector {
     pub fn new() -> Self {
-        Self {
-            ssh_regex: Regex::new(r"ssh\s+(?:(\w+)@)?([^\s]+)").unwrap(),
-            docker_regex: Regex::new(r"docker\s+(?:exec|run).*?(?:-it\s+)?([^\s]+)").unwrap(),
-            kubectl_regex: Regex::new(r"kubectl\s+exec.*?([^\s]+)").unwrap(),
-        }
+        Self
     }
 
     pub fn detect(&self, command: &str) -> String {
         // Check for SSH
-        if let Some(captures) = self.ssh_regex.captures(command) {
-            let user = captures.get(1).map(|m| m.as_str()).unwrap_or("unknown");
-            let host = captures.get(2).unwrap().as_str();
-            return format!("ssh:{}@{}", user, host);
+        if let Some(target) = parse_ssh(command) {
+            return target.host_id();
         }
 
-        // Check for Docker
-        if let Some(captures) = self.docker_regex.captures(command) {
-            let container = captures.get(1).unwrap().as_str();
-            return format!("docker:{}", container);
+        // Check for Docker and Kubernetes
+        if let Some(target) = parse_docker(command).or_else(|| parse_kubectl_exec(command)) {
+            return target.host_id;
         }
 
-        // Check for Kubernetes
-        if let Some(captures) = self.kubectl_regex.captures(command) {
-            let pod = captures.get(1).unwrap().as_str();
-            return format!("k8s:{}", pod);
+        "local".to_string()
+    }
+
+    /// The remote context an interactive shell command opens, if any. Commands that
+    /// run a one-shot remote command (`ssh host uptime`) don't open a context.
+    pub fn interactive_context(&self, command: &str) -> Option<String> {
+        if let Some(target) = parse_ssh(command) {
+            return (!target.has_remote_command).then(|| target.host_id());
         }
 
-        "local".to_string()
+        parse_docker(command)
+            .or_else(|| parse_kubectl_exec(command))
+            .filter(|target| target.opens_shell)
+            .map(|target| target.host_id)
+    }
+
+    /// Whether the command leaves the current (remote) shell.
+    pub fn is_context_exit(&self, command: &str) -> bool {
+        matches!(command.trim(), "exit" | "logout") || command.trim().starts_with("exit ")
+    }
+}
+
+/// A container or pod targeted by `docker exec/run` or `kubectl exec`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecTarget {
+    pub host_id: String,
+    /// Whether the command starts an interactive shell inside the target
+    pub opens_shell: bool,
+}
+
+/// Parse `docker exec [opts] CONTAINER CMD` and `docker run [opts] IMAGE [CMD]`.
+pub fn parse_docker(command: &str) -> Option<ExecTarget> {
+    const SHORT_WITH_ARG: &str = "eupvwmhlc";
+    const LONG_WITH_ARG: &[&str] = &[
+        "--env",
+        "--env-file",
+        "--user",
+        "--workdir",
+        "--name",
+        "--network",
+        "--publish",
+        "--volume",
+        "--mount",
+        "--entrypoint",
+        "--label",
+        "--detach-keys",
+        "--hostname",
+        "--memory",
+        "--platform",
+    ];
+
+    let mut tokens = command.split_whitespace();
+    if tokens.next()? != "docker" {
+        return None;
+    }
+    let subcommand = tokens.next()?;
+    if subcommand != "exec" && subcommand != "run" {
+        return None;
+    }
+
+    let mut rest: Vec<&str> = tokens.collect();
+    let target = take_positional(&mut rest, SHORT_WITH_ARG, LONG_WITH_ARG)?;
+
+    let opens_shell = if subcommand == "run" && rest.is_empty() {
+        // `docker run -it ubuntu` with no command starts the image's default shell
+        command.split_whitespace().any(|t| {
+            t.strip_prefix('-')
+                .is_some_and(|f| !f.starts_with('-') && f.contains('i') && f.contains('t'))
+        })
+    } else {
+        is_shell_invocation(&rest)
+    };
+
+    Some(ExecTarget {
+        host_id: format!("docker:{}", target),
+        opens_shell,
+    })
+}
+
+/// Parse `kubectl [-n ns] exec [-n ns] [-it] POD [-c container] [--] CMD`.
+pub fn parse_kubectl_exec(command: &str) -> Option<ExecTarget> {
+    const SHORT_WITH_ARG: &str = "nc";
+    const LONG_WITH_ARG: &[&str] = &["--namespace", "--container", "--context", "--kubeconfig"];
+
+    let mut tokens = command.split_whitespace();
+    if tokens.next()? != "kubectl" {
+        return None;
+    }
+
+    let tokens: Vec<&str> = tokens.collect();
+    let exec_pos = tokens.iter().position(|t| *t == "exec")?;
+    let (before_cmd, remote_cmd) = match tokens.iter().position(|t| *t == "--") {
+        Some(dash) => (&tokens[..dash], &tokens[dash + 1..]),
+        None => (&tokens[..], &tokens[tokens.len()..]),
+    };
+
+    let namespace = before_cmd.iter().enumerate().find_map(|(i, t)| match *t {
+        "-n" | "--namespace" => before_cmd.get(i + 1).copied(),
+        _ => t
+            .strip_prefix("--namespace=")
+            .or_else(|| t.strip_prefix("-n").filter(|v| !v.is_empty())),
+    });
+
+    let mut rest: Vec<&str> = before_cmd.get(exec_pos + 1..)?.to_vec();
+    let pod = take_positional(&mut rest, SHORT_WITH_ARG, LONG_WITH_ARG)?;
+    let pod = pod.strip_prefix("pod/").unwrap_or(pod);
+
+    // Legacy form without `--` leaves the command in the remaining positionals
+    let command_args: Vec<&str> = if remote_cmd.is_empty() {
+        rest.into_iter().filter(|t| !t.starts_with('-')).collect()
+    } else {
+        remote_cmd.to_vec()
+    };
+
+    let host_id = match namespace {
+        Some(ns) => format!("k8s:{}/{}", ns, pod),
+        None => format!("k8s:{}", pod),
+    };
+
+    Some(ExecTarget {
+        host_id,
+        opens_shell: is_shell_invocation(&command_args),
+    })
+}
+
+/// Remove and return the first positional argument, skipping options (and their
+/// values) listed in `short_with_arg` / `long_with_arg`. `args` is left holding
+/// everything after the positional.
+fn take_positional<'a>(
+    args: &mut Vec<&'a str>,
+    short_with_arg: &str,
+    long_with_arg: &[&str],
+) -> Option<&'a str> {
+    let mut i = 0;
+    while i < args.len() {
+        let token = args[i];
+        if token == "--" {
+            i += 1;
+            continue;
+        }
+        if token.starts_with("--") {
+            let takes_value = !token.contains('=') && long_with_arg.contains(&token);
+            i += if takes_value { 2 } else { 1 };
+            continue;
+        }
+        if let Some(flags) = token.strip_prefix('-') {
+            // Grouped short flags (`-it`); a trailing arg-taking flag consumes the next token
+            let takes_value = flags
+                .chars()
+                .last()
+                .is_some_and(|f| short_with_arg.contains(f))
+                && flags.len() == 1;
+            i += if takes_value { 2 } else { 1 };
+            continue;
+        }
+
+        let positional = args[i];
+        *args = args.split_off(i + 1);
+        return Some(positional);
+    }
+
+    None
+}
+
+/// Whether a command line starts an interactive shell (`bash`, `/bin/sh`, ...).
+fn is_shell_invocation(args: &[&str]) -> bool {
+    const SHELLS: &[&str] = &["sh", "bash", "zsh", "ash", "dash", "ksh", "fish"];
+
+    match args.first() {
+        Some(program) => {
+            let name = program.rsplit('/').next().unwrap_or(program);
+            SHELLS.contains(&name) && !args.contains(&"-c")
+        }
+        None => false,
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct SshTarget {
+    pub user: Option<String>,
+    pub host: String,
+    pub has_remote_command: bool,
+}
+
+impl SshTarget {
+    pub fn host_id(&self) -> String {
+        format!(
+            "ssh:{}@{}",
+            self.user.as_deref().unwrap_or("unknown"),
+            self.host
+        )
+    }
+}
+
+/// Parse the destination of an `ssh` invocation, skipping options and their arguments.
+pub fn parse_ssh(command: &str) -> Option<SshTarget> {
+    // ssh options that take an argument
+    const OPTS_WITH_ARG: &str = "BbcDEeFIiJLlmOoPpQRSWw";
+
+    let mut tokens = command.split_whitespace();
+    if tokens.next()? != "ssh" {
+        return None;
+    }
+
+    let mut user = None;
+    let mut destination = None;
+    while let Some(token) = tokens.next() {
+        if let Some(flags) = token.strip_prefix('-') {
+            // Flags can be grouped (`-tA`) and the last one may take an argument (`-p22`)
+            for (i, flag) in flags.char_indices() {
+                if OPTS_WITH_ARG.contains(flag) {
+                    let attached = &flags[i + flag.len_utf8()..];
+                    let value = if attached.is_empty() {
+                        tokens.next().map(str::to_string)
+                    } else {
+                        Some(attached.to_string())
+                    };
+                    if flag == 'l' {
+                        user = value;
+                    }
+                    break;
+                }
+            }
+            continue;
+        }
+
+        destination = Some(token);
+        break;
+    }
+
+    let destination = destination?.trim_start_matches("ssh://");
+    let (dest_user, host) = match destination.split_once('@') {
+        Some((u, h)) => (Some(u.to_string()), h),
+        None => (None, destination),
+    };
+    // Drop a `:port` suffix from ssh:// URLs
+    let host = host.split(':').next().unwrap_or(host);
+    if host.is_empty() {
+        return None;
+    }
+
+    Some(SshTarget {
+        user: dest_user.or(user),
+        host: host.to_string(),
+        has_remote_command: tokens.next().is_some(),
+    })
+}
+
 pub struct NetworkDetector {
-    curl_regex: Regex,
-    wget_regex: Regex,
-    ssh_regex: Regex,
+    url_regex: Regex,
     db_regex: Regex,
 }
 
@@ -59,56 +285,156 @@ impl Default for NetworkDetector {
     }
 }
 
+/// Schemes that send traffic in the clear.
+pub const INSECURE_SCHEMES: &[&str] = &["http", "telnet", "ftp"];
+
+/// Whether a normalized endpoint uses a cleartext protocol.
+pub fn is_insecure_endpoint(endpoint: &str) -> bool {
+    endpoint
+        .split_once("://")
+        .is_some_and(|(scheme, _)| INSECURE_SCHEMES.contains(&scheme))
+}
+
 impl NetworkDetector {
     pub fn new() -> Self {
         Self {
-            curl_regex: Regex::new(r"curl\s+.*?(https?://[^\s]+)").unwrap(),
-            wget_regex: Regex::new(r"wget\s+.*?(https?://[^\s]+)").unwrap(),
-            ssh_regex: Regex::new(r"ssh\s+(?:\w+@)?([^\s]+)").unwrap(),
-            db_regex: Regex::new(r"(?:psql|mysql|redis-cli).*?(?:-h\s+([^\s]+)|@([^\s]+))")
+            url_regex: Regex::new(r#"\b[a-zA-Z][a-zA-Z0-9+.-]*://[^\s'"<>]+"#).unwrap(),
+            db_regex: Regex::new(r"^(?:psql|mysql|redis-cli|mongo|mongosh)\s.*?-h\s*([^\s]+)")
                 .unwrap(),
         }
     }
 
+    /// Collect normalized `scheme://host[:port]` endpoints a command talks to.
     pub fn detect(&self, command: &str) -> Vec<String> {
         let mut endpoints = Vec::new();
 
-        // HTTP/HTTPS endpoints
-        for regex in [&self.curl_regex, &self.wget_regex] {
-            if let Some(captures) = regex.captures(command) {
-                if let Some(url) = captures.get(1) {
-                    endpoints.push(url.as_str().to_string());
-                }
+        // Explicit URLs (curl, wget, git clone, psql postgres://..., ...)
+        for url in self.url_regex.find_iter(command) {
+            if let Some(endpoint) = normalize_url(url.as_str()) {
+                endpoints.push(endpoint);
             }
         }
 
         // SSH endpoints
-        if let Some(captures) = self.ssh_regex.captures(command) {
-            if let Some(host) = captures.get(1) {
-                endpoints.push(format!("ssh://{}", host.as_str()));
-            }
+        if let Some(target) = parse_ssh(command) {
+            endpoints.push(format!("ssh://{}", target.host));
         }
 
-        // Database endpoints
+        // Database clients pointed at a host with -h
         if let Some(captures) = self.db_regex.captures(command) {
-            let host = captures.get(1).or(captures.get(2));
-            if let Some(host) = host {
-                endpoints.push(format!("db://{}", host.as_str()));
-            }
+            endpoints.push(format!("db://{}", &captures[1]));
+        }
+
+        // Raw TCP connections
+        if let Some(endpoint) = parse_raw_connection(command) {
+            endpoints.push(endpoint);
         }
 
+        let mut seen = std::collections::HashSet::new();
+        endpoints.retain(|endpoint| seen.insert(endpoint.clone()));
         endpoints
     }
 }
 
-pub struct PackageDetector {
-    npm_regex: Regex,
-    apt_regex: Regex,
-    pip_regex: Regex,
-    cargo_regex: Regex,
-    brew_regex: Regex,
+/// Reduce a URL to `scheme://host[:port]`, dropping credentials, path, query and fragment.
+/// Database URLs are folded into the `db://` scheme the network analyzer groups on.
+fn normalize_url(url: &str) -> Option<String> {
+    let (scheme, rest) = url.split_once("://")?;
+    let scheme = scheme.to_ascii_lowercase();
+
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let authority = authority.rsplit('@').next().unwrap_or_default();
+
+    // IPv6 literals keep their brackets: http://[::1]:8080
+    let (host, port) = if let Some(bracketed) = authority.strip_prefix('[') {
+        let (addr, after) = bracketed.split_once(']')?;
+        (format!("[{}]", addr), after.strip_prefix(':'))
+    } else {
+        match authority.split_once(':') {
+            Some((host, port)) => (host.to_string(), Some(port)),
+            None => (authority.to_string(), None),
+        }
+    };
+
+    if host.is_empty() || host == "[]" {
+        return None;
+    }
+
+    let host = host.to_ascii_lowercase();
+    let port = port.filter(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()));
+
+    let scheme = match scheme.as_str() {
+        "postgres" | "postgresql" | "mysql" | "redis" | "rediss" | "mongodb" | "mongodb+srv" => {
+            "db".to_string()
+        }
+        _ => scheme,
+    };
+
+    Some(match port {
+        Some(port) => format!("{}://{}:{}", scheme, host, port),
+        None => format!("{}://{}", scheme, host),
+    })
+}
+
+/// `nc host 443`, `telnet host 23` and friends.
+fn parse_raw_connection(command: &str) -> Option<String> {
+    let mut tokens = command.split_whitespace();
+    let (scheme, value_flags): (&str, &[&str]) = match tokens.next()? {
+        "nc" | "ncat" | "netcat" => (
+            "tcp",
+            &[
+                "-w",
+                "-i",
+                "-p",
+                "-s",
+                "-q",
+                "-x",
+                "-X",
+                "-e",
+                "-c",
+                "-I",
+                "-O",
+                "-T",
+                "-V",
+                "-P",
+                "-W",
+                "--wait",
+                "--idle-timeout",
+                "--source",
+                "--source-port",
+                "--proxy",
+                "--proxy-type",
+                "--exec",
+                "--sh-exec",
+            ],
+        ),
+        "telnet" => ("telnet", &["-b", "-e", "-l", "-n", "-k", "-X"]),
+        _ => return None,
+    };
+
+    // `nc -w 5 host 443`: the option's value isn't the host
+    let mut skip_next = false;
+    let mut positionals = tokens.filter(|token| {
+        if std::mem::take(&mut skip_next) {
+            return false;
+        }
+        if token.starts_with('-') {
+            skip_next = value_flags.contains(token);
+            return false;
+        }
+        true
+    });
+    let host = positionals.next()?;
+    Some(match positionals.next() {
+        Some(port) if port.chars().all(|c| c.is_ascii_digit()) => {
+            format!("{}://{}:{}", scheme, host, port)
+        }
+        _ => format!("{}://{}", scheme, host),
+    })
 }
 
+pub struct PackageDetector;
+
 impl Default for PackageDetector {
     fn default() -> Self {
         Self::new()
@@ -117,41 +443,181 @@ impl Default for PackageDetector {
 
 impl PackageDetector {
     pub fn new() -> Self {
-        Self {
-            npm_regex: Regex::new(r"npm\s+(install|remove|update)\s+([^\s]+)").unwrap(),
-            apt_regex: Regex::new(r"(?:apt|apt-get)\s+(install|remove|update)\s+([^\s]+)").unwrap(),
-            pip_regex: Regex::new(r"pip\s+(install|uninstall)\s+([^\s]+)").unwrap(),
-            cargo_regex: Regex::new(r"cargo\s+(install|uninstall)\s+([^\s]+)").unwrap(),
-            brew_regex: Regex::new(r"brew\s+(install|uninstall|update)\s+([^\s]+)").unwrap(),
-        }
+        Self
     }
 
+    /// Detect package operations in every segment of a (possibly chained) command.
     pub fn detect(&self, command: &str) -> Vec<PackageRef> {
-        let mut packages = Vec::new();
-
-        let detectors = [
-            ("npm", &self.npm_regex),
-            ("apt", &self.apt_regex),
-            ("pip", &self.pip_regex),
-            ("cargo", &self.cargo_regex),
-            ("brew", &self.brew_regex),
-        ];
-
-        for (manager, regex) in detectors {
-            if let Some(captures) = regex.captures(command) {
-                let action = captures.get(1).unwrap().as_str();
-                let name = captures.get(2).unwrap().as_str();
-
-                packages.push(PackageRef {
-                    manager: manager.to_string(),
-                    name: name.to_string(),
-                    version: None, // Could be enhanced to extract version
-                    action: action.to_string(),
-                });
-            }
+        command
+            .split(['&', '|', ';'])
+            .flat_map(detect_package_segment)
+            .collect()
+    }
+}
+
+fn detect_package_segment(segment: &str) -> Vec<PackageRef> {
+    let mut tokens: Vec<&str> = segment.split_whitespace().collect();
+
+    // Drop privilege escalation and env assignments: `sudo -E FOO=1 apt install x`
+    while let Some(first) = tokens.first() {
+        if *first == "sudo" || *first == "env" || first.contains('=') || first.starts_with('-') {
+            tokens.remove(0);
+        } else {
+            break;
+        }
+    }
+
+    // `python -m pip install x`
+    if tokens.len() > 2 && tokens[0].starts_with("python") && tokens[1] == "-m" {
+        tokens.drain(..2);
+    }
+
+    let Some((&program, rest)) = tokens.split_first() else {
+        return Vec::new();
+    };
+
+    let (manager, value_flags): (&str, &[&str]) = match program {
+        "apt" | "apt-get" => ("apt", &["-o", "-t", "--target-release"]),
+        "dnf" | "yum" => (program, &["--repo", "--enablerepo", "--disablerepo"]),
+        "npm" => ("npm", &["--registry", "--prefix", "--tag"]),
+        "yarn" => ("yarn", &["--registry", "--cwd"]),
+        "pnpm" => ("pnpm", &["--registry", "--filter", "-C", "--dir"]),
+        "pip" | "pip3" => (
+            "pip",
+            &[
+                "-r",
+                "-c",
+                "-e",
+                "-t",
+                "-i",
+                "--requirement",
+                "--constraint",
+                "--editable",
+                "--target",
+                "--index-url",
+                "--extra-index-url",
+                "--prefix",
+            ],
+        ),
+        "cargo" => (
+            "cargo",
+            &[
+                "--version",
+                "--vers",
+                "--git",
+                "--path",
+                "--branch",
+                "--tag",
+                "--rev",
+                "--root",
+                "--features",
+                "-F",
+                "--registry",
+            ],
+        ),
+        "brew" => ("brew", &[]),
+        "gem" => ("gem", &["-v", "--version", "-i", "--install-dir"]),
+        "go" => ("go", &[]),
+        _ => return Vec::new(),
+    };
+
+    // `yarn global add x` / `pnpm -g add x`
+    let mut rest: Vec<&str> = rest.iter().copied().filter(|t| *t != "global").collect();
+    let verb_pos = rest.iter().position(|t| !t.starts_with('-'));
+    let Some(verb_pos) = verb_pos else {
+        return Vec::new();
+    };
+    let verb = rest.remove(verb_pos);
+
+    let upgrade_flag = rest
+        .iter()
+        .any(|t| matches!(*t, "-U" | "--upgrade" | "--only-upgrade"));
+    let Some(action) = package_action(manager, verb, upgrade_flag) else {
+        return Vec::new();
+    };
+
+    // cargo/gem pass the version as a flag
+    let flag_version = rest
+        .windows(2)
+        .find(|pair| matches!(pair[0], "--version" | "--vers" | "-v") && manager != "npm")
+        .map(|pair| pair[1].to_string());
+
+    let mut packages = Vec::new();
+    let mut skip_next = false;
+    for token in rest {
+        if skip_next {
+            skip_next = false;
+            continue;
+        }
+        if token.starts_with('-') {
+            skip_next = value_flags.contains(&token);
+            continue;
+        }
+
+        let token = token.trim_matches(|c| c == '\'' || c == '"');
+        if token.is_empty()
+            || token.starts_with('.')
+            || token.starts_with('/')
+            || token.contains("://")
+        {
+            continue;
+        }
+
+        let (name, version) = split_package_version(manager, token);
+        if name.is_empty() {
+            continue;
         }
 
-        packages
+        packages.push(PackageRef {
+            manager: manager.to_string(),
+            name: name.to_string(),
+            version: version.or_else(|| flag_version.clone()),
+            action: action.to_string(),
+        });
+    }
+
+    packages
+}
+
+/// Map a manager's subcommand onto install/remove/update.
+fn package_action(manager: &str, verb: &str, upgrade_flag: bool) -> Option<&'static str> {
+    let action = match (manager, verb) {
+        (_, "install" | "i" | "in" | "add" | "reinstall" | "get") => "install",
+        (_, "remove" | "uninstall" | "rm" | "un" | "purge" | "erase" | "autoremove") => "remove",
+        (_, "update" | "upgrade" | "up") => "update",
+        _ => return None,
+    };
+
+    if action == "install" && upgrade_flag {
+        return Some("update");
+    }
+    Some(action)
+}
+
+/// Split a package spec into name and pinned version using each manager's syntax.
+fn split_package_version<'a>(manager: &str, spec: &'a str) -> (&'a str, Option<String>) {
+    match manager {
+        // pkg@1.2.3, @scope/pkg@1.2.3, module@v1.2.3
+        "npm" | "yarn" | "pnpm" | "cargo" | "go" => match spec.rfind('@') {
+            Some(at) if at > 0 => (&spec[..at], Some(spec[at + 1..].to_string())),
+            _ => (spec, None),
+        },
+        // numpy==1.2, requests[security]>=2.0 (only exact pins carry a version)
+        "pip" => {
+            let name_end = spec
+                .find(|c: char| "=<>!~[;".contains(c))
+                .unwrap_or(spec.len());
+            let version = spec
+                .split_once("==")
+                .map(|(_, v)| v.trim_start_matches('=').to_string());
+            (&spec[..name_end], version)
+        }
+        // curl=7.81.0-1ubuntu1
+        "apt" | "dnf" | "yum" => match spec.split_once('=') {
+            Some((name, version)) => (name, Some(version.to_string())),
+            None => (spec, None),
+        },
+        _ => (spec, None),
     }
 }
 
@@ -162,8 +628,28 @@ pub struct DangerResult {
 }
 
 pub struct DangerDetector {
-    dangerous_commands: Vec<(&'static str, f32, &'static str)>,
-    dangerous_patterns: Vec<(Regex, f32, &'static str)>,
+    rules: Vec<CompiledDangerRule>,
+    threshold: f32,
+}
+
+struct CompiledDangerRule {
+    matcher: RuleMatcher,
+    score: f32,
+    reason: String,
+}
+
+enum RuleMatcher {
+    Regex(Regex),
+    Literal(String),
+}
+
+impl RuleMatcher {
+    fn is_match(&self, command: &str) -> bool {
+        match self {
+            RuleMatcher::Regex(regex) => regex.is_match(command),
+            RuleMatcher::Literal(literal) => command.contains(literal.as_str()),
+        }
+    }
 }
 
 impl Default for DangerDetector {
@@ -172,81 +658,188 @@ impl Default for DangerDetector {
     }
 }
 
+/// The rules Whiskerlog ships with. User rules in the config with the same
+/// `pattern` replace these, so weights can be tuned or rules disabled.
+pub fn builtin_danger_rules() -> Vec<DangerRule> {
+    let rule = |pattern: &str, score: f32, reason: &str| DangerRule {
+        pattern: pattern.to_string(),
+        is_regex: true,
+        score,
+        reason: reason.to_string(),
+        enabled: true,
+    };
+
+    vec![
+        rule(r"rm\s+-rf\s+/", 1.0, "Recursive delete from root"),
+        rule(r"chmod\s+777", 0.8, "Overly permissive permissions"),
+        rule(r"sudo\s+rm", 0.7, "Privileged file deletion"),
+        rule(r"dd\s+.*of=/dev/", 0.9, "Direct disk write"),
+        rule(r"mkfs", 0.9, "Filesystem creation"),
+        // A downloader piped (possibly through tee etc.) into an interpreter,
+        // optionally under sudo. `&&`, `;` and `||` end the match.
+        rule(
+            r"\b(?:curl|wget|fetch)\b[^;&]*[^|;&]\|\s*(?:sudo\s+(?:-\S+\s+)*)?(?:\S*/)?(?:bash|sh|zsh|python[0-9.]*)(?:\s|$)",
+            0.9,
+            "Remote script piped directly to shell",
+        ),
+        // Risky programs, matched on the first word. A plain `rm` reaches the
+        // default threshold on its own, as it did before scores accumulated
+        rule(r"^rm(?:\s|$)", 0.7, "File deletion"),
+        rule(r"^rmdir(?:\s|$)", 0.5, "Directory deletion"),
+        rule(r"^mv(?:\s|$)", 0.3, "File movement"),
+        rule(r"^cp(?:\s|$)", 0.2, "File copying"),
+        rule(r"^chmod(?:\s|$)", 0.4, "Permission change"),
+        rule(r"^chown(?:\s|$)", 0.4, "Ownership change"),
+        rule(r"^sudo(?:\s|$)", 0.5, "Privileged execution"),
+        rule(r"^doas(?:\s|$)", 0.5, "Privileged execution"),
+        // `sudo -i`, `sudo su -`, `doas -s`, `sudo bash` with nothing after
+        rule(
+            r"^(?:sudo|doas)\s+(?:-u\s+\S+\s+|-\S+\s+)*(?:-[A-Za-z]*[is]|su(?:\s+-l?|\s+--login|\s+root)*|(?:\S*/)?(?:ba|z|k|fi)?sh)\s*$",
+            0.8,
+            "Interactive root shell",
+        ),
+    ]
+}
+
+/// Rules matching the command a leading `sudo` or `doas` runs score this much
+/// more, since the same command does more damage as root.
+pub const ESCALATION_MULTIPLIER: f32 = 1.5;
+
+/// The command a leading `sudo` or `doas` runs, without their options:
+/// `rm -rf /var` for `sudo -E -u root rm -rf /var`. `None` when the command
+/// isn't escalated or runs nothing itself (`sudo -i`).
+pub fn strip_privilege_prefix(command: &str) -> Option<&str> {
+    // Options of sudo and doas that take a value
+    const OPTS_WITH_ARG: &str = "CDghprTtUu";
+    const LONG_WITH_ARG: &[&str] = &[
+        "--chdir",
+        "--chroot",
+        "--close-from",
+        "--command-timeout",
+        "--group",
+        "--host",
+        "--other-user",
+        "--prompt",
+        "--role",
+        "--type",
+        "--user",
+    ];
+
+    let (program, mut rest) = split_first_word(command);
+    if program != "sudo" && program != "doas" {
+        return None;
+    }
+
+    loop {
+        let (word, after) = split_first_word(rest);
+        if word == "--" {
+            rest = after;
+            break;
+        }
+        let Some(flags) = word.strip_prefix('-').filter(|flags| !flags.is_empty()) else {
+            break;
+        };
+        rest = after;
+
+        if flags.starts_with('-') {
+            if !flags.contains('=') && LONG_WITH_ARG.contains(&word) {
+                rest = split_first_word(rest).1;
+            }
+            continue;
+        }
+        // Flags can be grouped (`-Eu root`) and the one taking a value may
+        // have it attached (`-uroot`)
+        if let Some(i) = flags.find(|flag| OPTS_WITH_ARG.contains(flag)) {
+            if i + 1 == flags.len() {
+                rest = split_first_word(rest).1;
+            }
+        }
+    }
+
+    let inner = rest.trim();
+    (!inner.is_empty()).then_some(inner)
+}
+
+fn split_first_word(text: &str) -> (&str, &str) {
+    let text = text.trim_start();
+    text.split_once(char::is_whitespace).unwrap_or((text, ""))
+}
+
 impl DangerDetector {
     pub fn new() -> Self {
-        let dangerous_patterns = vec![
-            (
-                Regex::new(r"rm\s+-rf\s+/").unwrap(),
-                1.0,
-                "Recursive delete from root",
-            ),
-            (
-                Regex::new(r"chmod\s+777").unwrap(),
-                0.8,
-                "Overly permissive permissions",
-            ),
-            (
-                Regex::new(r"sudo\s+rm").unwrap(),
-                0.7,
-                "Privileged file deletion",
-            ),
-            (
-                Regex::new(r"dd\s+.*of=/dev/").unwrap(),
-                0.9,
-                "Direct disk write",
-            ),
-            (Regex::new(r"mkfs").unwrap(), 0.9, "Filesystem creation"),
-            (
-                Regex::new(r"curl.*\|\s*(?:bash|sh)").unwrap(),
-                0.8,
-                "Pipe to shell execution",
-            ),
-            (
-                Regex::new(r"wget.*-O-.*\|\s*(?:bash|sh)").unwrap(),
-                0.8,
-                "Pipe to shell execution",
-            ),
-        ];
+        Self::with_rules(&[], 0.7)
+    }
 
-        Self {
-            dangerous_commands: vec![
-                ("rm", 0.6, "File deletion"),
-                ("rmdir", 0.5, "Directory deletion"),
-                ("mv", 0.3, "File movement"),
-                ("cp", 0.2, "File copying"),
-                ("chmod", 0.4, "Permission change"),
-                ("chown", 0.4, "Ownership change"),
-                ("sudo", 0.5, "Privileged execution"),
-            ],
-            dangerous_patterns,
+    /// Build a detector from the built-in rules merged with `custom` ones.
+    /// Invalid regexes are logged and skipped rather than failing the import.
+    pub fn with_rules(custom: &[DangerRule], threshold: f32) -> Self {
+        let mut merged = builtin_danger_rules();
+        for rule in custom {
+            match merged.iter_mut().find(|r| r.pattern == rule.pattern) {
+                Some(existing) => *existing = rule.clone(),
+                None => merged.push(rule.clone()),
+            }
         }
+
+        let rules = merged
+            .into_iter()
+            .filter(|rule| rule.enabled)
+            .filter_map(|rule| {
+                let matcher = if rule.is_regex {
+                    match Regex::new(&rule.pattern) {
+                        Ok(regex) => RuleMatcher::Regex(regex),
+                        Err(err) => {
+                            log::warn!("Ignoring invalid danger rule {:?}: {}", rule.pattern, err);
+                            return None;
+                        }
+                    }
+                } else {
+                    RuleMatcher::Literal(rule.pattern)
+                };
+
+                Some(CompiledDangerRule {
+                    matcher,
+                    score: rule.score,
+                    reason: rule.reason,
+                })
+            })
+            .collect();
+
+        Self { rules, threshold }
     }
 
+    /// Sum the scores of every matching rule (capped at 1.0) and flag the command
+    /// as dangerous once the total reaches the threshold.
+    ///
+    /// Under a leading `sudo` or `doas`, rules are also matched against the
+    /// command being escalated, and those below the cap that match it count
+    /// [`ESCALATION_MULTIPLIER`] times their score, up to the cap.
     pub fn assess(&self, command: &str) -> DangerResult {
+        let command = command.trim_start();
+        let escalated = strip_privilege_prefix(command);
         let mut score: f32 = 0.0;
-        let mut reasons = Vec::new();
-
-        // Check dangerous patterns first (higher priority)
-        for (pattern, pattern_score, reason) in &self.dangerous_patterns {
-            if pattern.is_match(command) {
-                score = score.max(*pattern_score);
-                reasons.push(reason.to_string());
+        let mut reasons: Vec<String> = Vec::new();
+
+        for rule in &self.rules {
+            if escalated.is_some_and(|inner| rule.matcher.is_match(inner)) {
+                score += if rule.score < 1.0 {
+                    (rule.score * ESCALATION_MULTIPLIER).min(1.0)
+                } else {
+                    rule.score
+                };
+            } else if rule.matcher.is_match(command) {
+                score += rule.score;
+            } else {
+                continue;
             }
-        }
-
-        // Check dangerous commands
-        let first_word = command.split_whitespace().next().unwrap_or("");
-        for (cmd, cmd_score, reason) in &self.dangerous_commands {
-            if first_word == *cmd {
-                score = score.max(*cmd_score);
-                if !reasons.iter().any(|r| r.contains(reason)) {
-                    reasons.push(reason.to_string());
-                }
+            if !reasons.contains(&rule.reason) {
+                reasons.push(rule.reason.clone());
             }
         }
 
+        let score = score.min(1.0);
         DangerResult {
-            is_dangerous: score > 0.5,
+            is_dangerous: score > 0.0 && score >= self.threshold,
             score,
             reasons,
         }
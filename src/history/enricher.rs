@@ -1,5 +1,46 @@
+use chrono::{DateTime, Duration, Local, Utc};
+use chrono_tz::Tz;
+use std::collections::HashMap;
+
 use super::Command;
+use crate::analysis::sessions::DEFAULT_IDLE_MINUTES;
 use crate::history::detector::*;
+use crate::history::redactor::Redactor;
+
+/// How commands from history files that don't record a session get one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SessionStrategy {
+    /// A new session once the shell has been idle for longer than this
+    IdleGap(Duration),
+    /// One session per calendar day in this zone, the local one when unset
+    PerDay(Option<Tz>),
+}
+
+impl Default for SessionStrategy {
+    fn default() -> Self {
+        SessionStrategy::IdleGap(Duration::minutes(DEFAULT_IDLE_MINUTES))
+    }
+}
+
+impl SessionStrategy {
+    /// `session_strategy` from the config, with the idle gap it already uses
+    /// for splitting sessions and its timezone for day boundaries.
+    pub fn from_config(config: &crate::config::Config) -> Self {
+        match config.session_strategy.to_ascii_lowercase().as_str() {
+            "per_day" => SessionStrategy::PerDay(
+                config
+                    .timezone
+                    .as_deref()
+                    .and_then(|name| name.parse::<Tz>().ok()),
+            ),
+            "idle_gap" => SessionStrategy::IdleGap(Duration::minutes(config.session_idle_minutes)),
+            other => {
+                log::warn!("Unknown session_strategy {:?}, using idle_gap", other);
+                SessionStrategy::IdleGap(Duration::minutes(config.session_idle_minutes))
+            }
+        }
+    }
+}
 
 pub struct CommandEnricher {
     host_detector: HostDetector,
@@ -7,6 +48,9 @@ pub struct CommandEnricher {
     package_detector: PackageDetector,
     danger_detector: DangerDetector,
     experiment_detector: ExperimentDetector,
+    max_context_depth: usize,
+    redactor: Option<Redactor>,
+    session_strategy: SessionStrategy,
 }
 
 impl Default for CommandEnricher {
@@ -23,30 +67,203 @@ impl CommandEnricher {
             package_detector: PackageDetector::new(),
             danger_detector: DangerDetector::new(),
             experiment_detector: ExperimentDetector::new(),
+            max_context_depth: 3,
+            redactor: None,
+            session_strategy: SessionStrategy::default(),
+        }
+    }
+
+    /// The enricher the importer uses for `config`: its danger rules, session
+    /// strategy, context depth and, when enabled, redaction.
+    pub fn from_config(config: &crate::config::Config) -> Self {
+        let enricher = Self::new()
+            .with_max_context_depth(config.max_exec_depth)
+            .with_session_strategy(SessionStrategy::from_config(config))
+            .with_danger_rules(&config.danger_rules, config.danger_threshold);
+        if config.redaction_enabled {
+            enricher.with_redaction(&config.redaction_patterns)
+        } else {
+            enricher
         }
     }
 
+    pub fn with_danger_rules(
+        mut self,
+        rules: &[crate::config::DangerRule],
+        threshold: f32,
+    ) -> Self {
+        self.danger_detector = DangerDetector::with_rules(rules, threshold);
+        self
+    }
+
+    /// Mask secrets in command text before anything else looks at it.
+    pub fn with_redaction(mut self, extra_patterns: &[String]) -> Self {
+        self.redactor = Some(Redactor::new(extra_patterns));
+        self
+    }
+
+    /// Limit how many nested ssh/docker/kubectl shells are tracked, so a missed
+    /// `exit` can't mislabel the rest of the history.
+    pub fn with_max_context_depth(mut self, depth: usize) -> Self {
+        self.max_context_depth = depth;
+        self
+    }
+
+    pub fn with_session_strategy(mut self, strategy: SessionStrategy) -> Self {
+        self.session_strategy = strategy;
+        self
+    }
+
     pub async fn enrich(&self, mut command: Command) -> Command {
+        if let Some(redactor) = &self.redactor {
+            command.command = redactor.redact(&command.command);
+        }
+
         // Detect host context (ssh, docker, k8s)
         command.host_id = self.host_detector.detect(&command.command);
 
-        // Detect network endpoints
-        command.network_endpoints = self.network_detector.detect(&command.command);
-
-        // Detect package operations
-        command.packages_used = self.package_detector.detect(&command.command);
-
         // Assess danger level
         let danger_result = self.danger_detector.assess(&command.command);
         command.is_dangerous = danger_result.is_dangerous;
         command.danger_score = danger_result.score;
         command.danger_reasons = danger_result.reasons;
 
+        self.detect_usage(&mut command);
+        command
+    }
+
+    /// Redo redaction and the endpoint, package and experiment detection of
+    /// `enrich` on a stored command. The host and session it was attributed to
+    /// at import are kept, and danger is left to the caller's
+    /// [`crate::analysis::DangerAnalyzer`].
+    pub fn reenrich(&self, command: &mut Command) {
+        if let Some(redactor) = &self.redactor {
+            command.command = redactor.redact(&command.command);
+            if let Some(original) = &mut command.original_command {
+                *original = redactor.redact(original);
+            }
+        }
+        self.detect_usage(command);
+    }
+
+    /// Network endpoints, package operations and experiment tags.
+    fn detect_usage(&self, command: &mut Command) {
+        command.network_endpoints = self.network_detector.detect(&command.command);
+        command.packages_used = self.package_detector.detect(&command.command);
+
         // Detect if this is an experimental/learning command
         let experiment_result = self.experiment_detector.detect(&command.command);
         command.is_experiment = experiment_result.is_experiment;
         command.experiment_tags = experiment_result.tags;
+    }
 
-        command
+    /// Attribute commands to the remote host or container they were typed into.
+    ///
+    /// This is a session-scoped heuristic: after an interactive `ssh user@host`,
+    /// `docker exec -it web bash` or `kubectl exec -it pod -- sh` in a session,
+    /// following commands in that session are attributed to `ssh:user@host`,
+    /// `docker:web` or `k8s:ns/pod` until an `exit`/`logout`. Nested shells stack up
+    /// to `max_context_depth`. One-shot remote commands only tag themselves, which
+    /// `enrich` already does. `commands` must be in chronological order.
+    pub fn attribute_remote_sessions(&self, commands: &mut [Command]) {
+        let mut contexts: std::collections::HashMap<String, Vec<String>> =
+            std::collections::HashMap::new();
+
+        for command in commands.iter_mut() {
+            let stack = contexts.entry(command.session_id.clone()).or_default();
+
+            if let Some(remote) = stack.last() {
+                command.host_id = remote.clone();
+                if self.host_detector.is_context_exit(&command.command) {
+                    stack.pop();
+                    continue;
+                }
+            }
+
+            if let Some(context) = self.host_detector.interactive_context(&command.command) {
+                if stack.len() < self.max_context_depth {
+                    stack.push(context);
+                }
+            }
+        }
+    }
+
+    /// Give commands whose history doesn't record a session (an empty id, or the
+    /// per-file `bash-history` style ones) a session of their own. Under
+    /// `IdleGap` a session is named after its first command, e.g.
+    /// `bash-20240301-091500`; under `PerDay` after its day, e.g.
+    /// `bash-2024-03-01`. Ids depend only on the commands, so importing the same
+    /// history again gives the same ones. Each shell's commands must come in
+    /// chronological order.
+    pub fn derive_sessions<'a>(&self, commands: impl IntoIterator<Item = &'a mut Command>) {
+        // Per shell: when the current session's last command ran, and its id
+        let mut runs: HashMap<String, (DateTime<Utc>, String)> = HashMap::new();
+
+        for command in commands {
+            if !is_file_session(&command.session_id) {
+                continue;
+            }
+            let shell = if command.shell.is_empty() {
+                "shell"
+            } else {
+                command.shell.as_str()
+            };
+
+            command.session_id = match self.session_strategy {
+                SessionStrategy::IdleGap(gap) => {
+                    let run = runs
+                        .entry(shell.to_string())
+                        .and_modify(|(last, id)| {
+                            if command.timestamp - *last > gap {
+                                *id = idle_session_id(shell, command.timestamp);
+                            }
+                            *last = command.timestamp;
+                        })
+                        .or_insert_with(|| {
+                            (command.timestamp, idle_session_id(shell, command.timestamp))
+                        });
+                    run.1.clone()
+                }
+                SessionStrategy::PerDay(timezone) => {
+                    let day = match timezone {
+                        Some(tz) => command.timestamp.with_timezone(&tz).date_naive(),
+                        None => command.timestamp.with_timezone(&Local).date_naive(),
+                    };
+                    format!("{}-{}", shell, day.format("%Y-%m-%d"))
+                }
+            };
+        }
     }
 }
+
+/// Ids the parsers use for a whole history file, which carry no session.
+fn is_file_session(id: &str) -> bool {
+    id.is_empty() || id.ends_with("-history")
+}
+
+/// Whether `id` is one `derive_sessions` made up for `shell`'s commands rather
+/// than one the shell recorded. Which derived session a command lands in
+/// depends on what else was read with it, so the same command can get a
+/// different one when its file is read again.
+pub fn is_derived_session(shell: &str, id: &str) -> bool {
+    let shell = if shell.is_empty() { "shell" } else { shell };
+    let Some(rest) = id
+        .strip_prefix(shell)
+        .and_then(|rest| rest.strip_prefix('-'))
+    else {
+        return false;
+    };
+    // `9` stands for any digit
+    let has_shape = |pattern: &str| {
+        rest.len() == pattern.len()
+            && rest.chars().zip(pattern.chars()).all(|(c, p)| match p {
+                '9' => c.is_ascii_digit(),
+                p => c == p,
+            })
+    };
+    has_shape("99999999-999999") || has_shape("9999-99-99")
+}
+
+fn idle_session_id(shell: &str, start: DateTime<Utc>) -> String {
+    format!("{}-{}", shell, start.format("%Y%m%d-%H%M%S"))
+}
@@ -0,0 +1,147 @@
+use regex::Regex;
+use rusqlite::types::Value;
+use std::path::PathBuf;
+
+use super::Command;
+use crate::config::Config;
+use crate::db::CommandFilter;
+
+/// SQL for the command text trimmed the way `is_ignored` trims it.
+const TRIMMED_COMMAND: &str = "trim(command, char(32, 9, 10, 13))";
+
+/// Decides which commands are left out of the import entirely.
+///
+/// Command patterns containing `*` or `?` are globs matched against the whole
+/// command; anything else is a word prefix, so `ls` drops `ls` and `ls -la` but
+/// keeps `lsblk`. Directories drop every command run in them or below them.
+#[derive(Default)]
+pub struct ImportFilter {
+    globs: Vec<Regex>,
+    /// The glob patterns as SQLite `GLOB` patterns, for `exclude_from`
+    sql_globs: Vec<String>,
+    prefixes: Vec<String>,
+    directories: Vec<PathBuf>,
+}
+
+impl ImportFilter {
+    pub fn new(patterns: &[String], directories: &[String]) -> Self {
+        let mut filter = Self::default();
+
+        for pattern in patterns.iter().map(|p| p.trim()).filter(|p| !p.is_empty()) {
+            if pattern.contains(['*', '?']) {
+                match Regex::new(&glob_to_regex(pattern)) {
+                    Ok(regex) => {
+                        filter.globs.push(regex);
+                        // `[` would start a character class in SQLite
+                        filter.sql_globs.push(pattern.replace('[', "[[]"));
+                    }
+                    Err(err) => {
+                        log::warn!("Ignoring invalid ignore pattern {:?}: {}", pattern, err)
+                    }
+                }
+            } else {
+                filter.prefixes.push(pattern.to_string());
+            }
+        }
+
+        filter.directories = directories
+            .iter()
+            .filter(|d| !d.trim().is_empty())
+            .map(|d| expand_home(d.trim()))
+            .collect();
+
+        filter
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(&config.ignore_patterns, &config.ignore_directories)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.globs.is_empty() && self.prefixes.is_empty() && self.directories.is_empty()
+    }
+
+    pub fn is_ignored(&self, command: &Command) -> bool {
+        let text = command.command.trim();
+
+        let prefix_match = self.prefixes.iter().any(|prefix| {
+            text.strip_prefix(prefix.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+        });
+
+        prefix_match
+            || self.globs.iter().any(|glob| glob.is_match(text))
+            || command.working_directory.as_deref().is_some_and(|cwd| {
+                let cwd = std::path::Path::new(cwd);
+                self.directories.iter().any(|dir| cwd.starts_with(dir))
+            })
+    }
+
+    /// `filter` narrowed to the stored rows this filter doesn't ignore, so views
+    /// paged from the database hide what an import would have skipped.
+    pub fn exclude_from(&self, mut filter: CommandFilter) -> CommandFilter {
+        for prefix in &self.prefixes {
+            let len = prefix.chars().count() as i64;
+            filter = filter.and_bound(
+                format!(
+                    "NOT ({t} = ? OR (substr({t}, 1, ?) = ? AND substr({t}, ?, 1) IN (char(32), char(9), char(10), char(13))))",
+                    t = TRIMMED_COMMAND
+                ),
+                [
+                    Value::Text(prefix.clone()),
+                    Value::Integer(len),
+                    Value::Text(prefix.clone()),
+                    Value::Integer(len + 1),
+                ],
+            );
+        }
+        for glob in &self.sql_globs {
+            filter = filter.and_bound(format!("NOT ({} GLOB ?)", TRIMMED_COMMAND), [glob.clone()]);
+        }
+        for dir in &self.directories {
+            let dir = dir.to_string_lossy();
+            let below = format!("{}/", dir.trim_end_matches('/'));
+            filter = filter.and_bound(
+                "(working_directory IS NULL OR NOT (working_directory = ? OR substr(working_directory, 1, ?) = ?))",
+                [
+                    Value::Text(dir.to_string()),
+                    Value::Integer(below.chars().count() as i64),
+                    Value::Text(below),
+                ],
+            );
+        }
+        filter
+    }
+
+    /// Drop ignored commands in place.
+    pub fn apply(&self, commands: &mut Vec<Command>) {
+        if !self.is_empty() {
+            commands.retain(|cmd| !self.is_ignored(cmd));
+        }
+    }
+}
+
+pub(crate) fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::from("^");
+    for c in glob.chars() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            _ => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    regex
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    let home = dirs::home_dir().unwrap_or_default();
+    if let Some(rest) = path
+        .strip_prefix("$HOME")
+        .or_else(|| path.strip_prefix("~"))
+    {
+        home.join(rest.trim_start_matches('/'))
+    } else {
+        PathBuf::from(path)
+    }
+}
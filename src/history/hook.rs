@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+
+/// Seconds the hook's start time may differ from the shell's own history stamp.
+const CLOCK_SLACK_SECS: i64 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum HookShell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+/// One line of the capture log:
+/// `shell\ttimestamp\texit\tduration\tsession\tcwd\tcommand`, with the start time
+/// in epoch seconds and the duration in milliseconds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HookEntry {
+    pub shell: String,
+    pub timestamp: i64,
+    pub exit_code: i32,
+    pub duration: u64,
+    pub session_id: String,
+    pub working_directory: Option<String>,
+    pub command: String,
+}
+
+/// Where the hooks append and the parser looks for the capture log: next to the
+/// database, so it follows a configured `database_path`.
+pub fn hook_log_path(config: &Config) -> PathBuf {
+    config
+        .database_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join("hook.log")
+}
+
+/// Snippet for the shell's rc file that appends every command to `log_path`.
+/// Sourcing it twice in one shell doesn't register the hooks twice.
+pub fn hook_script(shell: HookShell, log_path: &Path) -> String {
+    let log = log_path.display().to_string();
+    match shell {
+        HookShell::Bash => format!(
+            r#"# Whiskerlog shell integration for bash 5+.
+# Add to ~/.bashrc: eval "$(whiskerlog print-hook bash)"
+__whiskerlog_log='{log}'
+: "${{__whiskerlog_session:=bash-$$-$EPOCHSECONDS}}"
+mkdir -p "$(dirname "$__whiskerlog_log")"
+
+__whiskerlog_preexec() {{
+    [ -n "$__whiskerlog_start" ] && return
+    __whiskerlog_start=${{EPOCHREALTIME/[.,]/}}
+    __whiskerlog_cwd=$PWD
+}}
+
+__whiskerlog_precmd() {{
+    local num cmd end
+    read -r num cmd <<< "$(HISTTIMEFORMAT= history 1)"
+    # Skip the first prompt and empty ones, which leave the last history entry unchanged
+    if [ -n "$__whiskerlog_last" ] && [ -n "$cmd" ] && [ "$num" != "$__whiskerlog_last" ]; then
+        end=${{EPOCHREALTIME/[.,]/}}
+        printf 'bash\t%s\t%s\t%s\t%s\t%s\t%s\n' "$((__whiskerlog_start / 1000000))" \
+            "$__whiskerlog_exit" "$(((end - __whiskerlog_start) / 1000))" \
+            "$__whiskerlog_session" "$__whiskerlog_cwd" "$cmd" >> "$__whiskerlog_log"
+    fi
+    __whiskerlog_last=${{num:-0}}
+    __whiskerlog_start=
+}}
+
+trap '__whiskerlog_preexec' DEBUG
+case "$PROMPT_COMMAND" in
+    *__whiskerlog_precmd*) ;;
+    *) PROMPT_COMMAND="__whiskerlog_exit=\$?; ${{PROMPT_COMMAND:+${{PROMPT_COMMAND%;}}; }}__whiskerlog_precmd" ;;
+esac
+"#,
+            log = shell_quote(&log)
+        ),
+        HookShell::Zsh => format!(
+            r#"# Whiskerlog shell integration for zsh.
+# Add to ~/.zshrc: eval "$(whiskerlog print-hook zsh)"
+zmodload zsh/datetime
+__whiskerlog_log='{log}'
+: ${{__whiskerlog_session:=zsh-$$-$EPOCHSECONDS}}
+mkdir -p "${{__whiskerlog_log:h}}"
+
+__whiskerlog_preexec() {{
+    __whiskerlog_cmd=$1
+    __whiskerlog_cwd=$PWD
+    __whiskerlog_start=$EPOCHREALTIME
+}}
+
+__whiskerlog_precmd() {{
+    local code=$?
+    [[ -z $__whiskerlog_start ]] && return
+    local -i duration=$(( (EPOCHREALTIME - __whiskerlog_start) * 1000 ))
+    printf 'zsh\t%s\t%s\t%s\t%s\t%s\t%s\n' "${{__whiskerlog_start%.*}}" "$code" "$duration" \
+        "$__whiskerlog_session" "$__whiskerlog_cwd" "${{__whiskerlog_cmd%%$'\n'*}}" >> "$__whiskerlog_log"
+    __whiskerlog_start=
+}}
+
+autoload -Uz add-zsh-hook
+add-zsh-hook preexec __whiskerlog_preexec
+add-zsh-hook precmd __whiskerlog_precmd
+"#,
+            log = shell_quote(&log)
+        ),
+        HookShell::Fish => format!(
+            r#"# Whiskerlog shell integration for fish.
+# Add to ~/.config/fish/config.fish: whiskerlog print-hook fish | source
+set -g __whiskerlog_log '{log}'
+set -q __whiskerlog_session; or set -g __whiskerlog_session fish-$fish_pid-(date +%s)
+mkdir -p (dirname $__whiskerlog_log)
+
+function __whiskerlog_preexec --on-event fish_preexec
+    set -g __whiskerlog_cwd $PWD
+    set -g __whiskerlog_start (date +%s)
+end
+
+function __whiskerlog_postexec --on-event fish_postexec
+    set -l code $status
+    set -q __whiskerlog_start; or return
+    set -l cmd (string split -m1 \n -- $argv[1])
+    printf 'fish\t%s\t%s\t%s\t%s\t%s\t%s\n' $__whiskerlog_start $code $CMD_DURATION \
+        "$__whiskerlog_session" "$__whiskerlog_cwd" "$cmd[1]" >> $__whiskerlog_log
+    set -e __whiskerlog_start
+end
+"#,
+            log = fish_quote(&log)
+        ),
+    }
+}
+
+/// Escape for use inside single quotes in bash and zsh.
+fn shell_quote(text: &str) -> String {
+    text.replace('\'', r"'\''")
+}
+
+/// Escape for use inside single quotes in fish.
+fn fish_quote(text: &str) -> String {
+    text.replace('\\', r"\\").replace('\'', r"\'")
+}
+
+/// Entries `shell` wrote to the capture log at `path`, oldest first. A missing
+/// file yields no entries and malformed lines are skipped.
+pub fn read_hook_log(path: &Path, shell: &str) -> Vec<HookEntry> {
+    let Ok(bytes) = fs::read(path) else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&bytes)
+        .lines()
+        .filter_map(parse_hook_line)
+        .filter(|entry| entry.shell == shell)
+        .collect()
+}
+
+pub fn parse_hook_line(line: &str) -> Option<HookEntry> {
+    // The command goes last so tabs inside it survive
+    let mut fields = line.splitn(7, '\t');
+    let shell = fields.next()?.trim();
+    let timestamp = fields.next()?.trim().parse().ok()?;
+    let exit_code = fields.next()?.trim().parse().ok()?;
+    let duration = fields.next()?.trim().parse().ok()?;
+    let session_id = fields.next()?;
+    let working_directory = fields.next()?;
+    let command = fields.next()?.trim_end_matches('\r');
+    if shell.is_empty() || session_id.is_empty() || command.trim().is_empty() {
+        return None;
+    }
+
+    Some(HookEntry {
+        shell: shell.to_string(),
+        timestamp,
+        exit_code,
+        duration,
+        session_id: session_id.to_string(),
+        working_directory: (!working_directory.is_empty()).then(|| working_directory.to_string()),
+        command: command.to_string(),
+    })
+}
+
+/// Pairs history entries (command text and the shell's own timestamp, if it
+/// recorded one) with log entries for the same command. Both are walked newest
+/// first so a history file read from an offset lines up with the end of the log.
+/// When the shell recorded a timestamp, log entries that are clearly later belong
+/// to runs it didn't keep (`ignoredups`, `erasedups`) and are dropped; ones
+/// clearly earlier don't match.
+pub fn match_hook_entries(
+    history: &[(&str, Option<i64>)],
+    log: Vec<HookEntry>,
+) -> Vec<Option<HookEntry>> {
+    let mut by_command: HashMap<String, Vec<HookEntry>> = HashMap::new();
+    for entry in log {
+        by_command
+            .entry(entry.command.clone())
+            .or_default()
+            .push(entry);
+    }
+
+    let mut matched = vec![None; history.len()];
+    for (i, (command, time)) in history.iter().enumerate().rev() {
+        let Some(candidates) = by_command.get_mut(*command) else {
+            continue;
+        };
+        if let Some(time) = time {
+            while candidates
+                .last()
+                .is_some_and(|entry| entry.timestamp > time + CLOCK_SLACK_SECS)
+            {
+                candidates.pop();
+            }
+            if candidates
+                .last()
+                .is_some_and(|entry| entry.timestamp < time - CLOCK_SLACK_SECS)
+            {
+                continue;
+            }
+        }
+        matched[i] = candidates.pop();
+    }
+
+    matched
+}
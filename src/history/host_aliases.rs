@@ -0,0 +1,152 @@
+use regex::Regex;
+use std::collections::HashMap;
+
+use super::filter::glob_to_regex;
+use super::Command;
+use crate::config::Config;
+use crate::db::HostSummary;
+
+/// Collapses the names one machine goes by (`prod`, `prod.internal`,
+/// `10.0.0.5`) into a single canonical host.
+///
+/// A pattern matches either the whole host id (`ssh:deploy@prod`) or just its
+/// host name (`prod`), and may use `*` and `?` globs. Exact patterns win over
+/// globs; hosts nothing matches keep their id. Stored host ids are left as
+/// recorded, so changing the aliases regroups the existing history too.
+#[derive(Default)]
+pub struct HostAliases {
+    exact: HashMap<String, String>,
+    /// Tried in pattern order, so overlapping globs resolve the same way each run
+    globs: Vec<(Regex, String)>,
+}
+
+impl HostAliases {
+    pub fn new(aliases: &HashMap<String, String>) -> Self {
+        let mut host_aliases = Self::default();
+
+        let mut patterns: Vec<_> = aliases
+            .iter()
+            .map(|(pattern, canonical)| (pattern.trim(), canonical.trim()))
+            .filter(|(pattern, canonical)| !pattern.is_empty() && !canonical.is_empty())
+            .collect();
+        patterns.sort_unstable();
+
+        for (pattern, canonical) in patterns {
+            if pattern.contains(['*', '?']) {
+                match Regex::new(&glob_to_regex(pattern)) {
+                    Ok(regex) => host_aliases.globs.push((regex, canonical.to_string())),
+                    Err(err) => log::warn!("Ignoring invalid host alias {:?}: {}", pattern, err),
+                }
+            } else {
+                host_aliases
+                    .exact
+                    .insert(pattern.to_string(), canonical.to_string());
+            }
+        }
+
+        host_aliases
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(&config.host_aliases)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.exact.is_empty() && self.globs.is_empty()
+    }
+
+    /// The name `host_id` is shown and filtered under.
+    pub fn canonical<'a>(&'a self, host_id: &'a str) -> &'a str {
+        let name = host_name(host_id);
+        if let Some(canonical) = self.exact.get(host_id).or_else(|| self.exact.get(name)) {
+            return canonical;
+        }
+
+        self.globs
+            .iter()
+            .find(|(glob, _)| glob.is_match(host_id) || glob.is_match(name))
+            .map_or(host_id, |(_, canonical)| canonical.as_str())
+    }
+
+    /// The ids among `host_ids` that are shown as `canonical`.
+    pub fn ids_for(&self, canonical: &str, host_ids: &[String]) -> Vec<String> {
+        host_ids
+            .iter()
+            .filter(|id| self.canonical(id) == canonical)
+            .cloned()
+            .collect()
+    }
+
+    /// Rename every command's host to its canonical name.
+    pub fn apply(&self, commands: &mut [Command]) {
+        if self.is_empty() {
+            return;
+        }
+        for cmd in commands {
+            let canonical = self.canonical(&cmd.host_id);
+            if canonical != cmd.host_id {
+                cmd.host_id = canonical.to_string();
+            }
+        }
+    }
+
+    /// Combine the summaries of hosts that share a canonical name, busiest
+    /// first and then by name, like [`crate::db::Database::host_summaries`].
+    pub fn merge_summaries(&self, summaries: Vec<HostSummary>) -> Vec<HostSummary> {
+        if self.is_empty() {
+            return summaries;
+        }
+
+        let mut merged: Vec<HostSummary> = Vec::new();
+        let mut index: HashMap<String, usize> = HashMap::new();
+        for summary in summaries {
+            let canonical = self.canonical(&summary.host_id).to_string();
+            let Some(&i) = index.get(&canonical) else {
+                index.insert(canonical.clone(), merged.len());
+                merged.push(HostSummary {
+                    host_id: canonical,
+                    ..summary
+                });
+                continue;
+            };
+
+            let host = &mut merged[i];
+            let weighted = |a: f64, a_count: usize, b: f64, b_count: usize| {
+                (a * a_count as f64 + b * b_count as f64) / (a_count + b_count) as f64
+            };
+            host.avg_danger_score = weighted(
+                host.avg_danger_score as f64,
+                host.total_commands,
+                summary.avg_danger_score as f64,
+                summary.total_commands,
+            ) as f32;
+            host.avg_duration_ms = match (host.avg_duration_ms, summary.avg_duration_ms) {
+                (Some(a), Some(b)) => Some(
+                    weighted(
+                        a as f64,
+                        host.timed_commands,
+                        b as f64,
+                        summary.timed_commands,
+                    )
+                    .round() as u64,
+                ),
+                (a, b) => a.or(b),
+            };
+            host.total_commands += summary.total_commands;
+            host.timed_commands += summary.timed_commands;
+            host.dangerous_commands += summary.dangerous_commands;
+            host.experiment_commands += summary.experiment_commands;
+            host.last_seen = host.last_seen.max(summary.last_seen);
+        }
+
+        merged.sort_by_key(|host| (std::cmp::Reverse(host.total_commands), host.host_id.clone()));
+        merged
+    }
+}
+
+/// The machine part of a host id: `prod` for `ssh:deploy@prod`, `app` for
+/// `docker:app`.
+fn host_name(host_id: &str) -> &str {
+    let rest = host_id.split_once(':').map_or(host_id, |(_, rest)| rest);
+    rest.rsplit_once('@').map_or(rest, |(_, host)| host)
+}
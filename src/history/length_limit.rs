@@ -0,0 +1,81 @@
+use super::Command;
+use crate::config::Config;
+
+/// Appended to a command cut down to the length limit.
+pub const ELISION_MARKER: &str = "…";
+
+/// What happens to commands longer than `max_command_length`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LongCommandPolicy {
+    /// Store the first characters with `ELISION_MARKER`, keeping the full text
+    /// in `original_command`
+    #[default]
+    Truncate,
+    /// Leave the command out of the import
+    Skip,
+    /// Store it as it is
+    Keep,
+}
+
+impl LongCommandPolicy {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "truncate" => Some(Self::Truncate),
+            "skip" => Some(Self::Skip),
+            "keep" => Some(Self::Keep),
+            _ => None,
+        }
+    }
+}
+
+/// Keeps pasted blobs thousands of characters long out of the stored command
+/// text, where they'd stretch every list and slow down fuzzy matching.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LengthLimit {
+    max_chars: Option<usize>,
+    policy: LongCommandPolicy,
+}
+
+impl LengthLimit {
+    pub fn new(max_chars: Option<usize>, policy: LongCommandPolicy) -> Self {
+        Self { max_chars, policy }
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        let policy = LongCommandPolicy::parse(&config.long_command_policy).unwrap_or_else(|| {
+            log::warn!(
+                "Unknown long_command_policy {:?}, using truncate",
+                config.long_command_policy
+            );
+            LongCommandPolicy::Truncate
+        });
+        Self::new(config.max_command_length, policy)
+    }
+
+    fn is_too_long(&self, command: &str) -> bool {
+        self.max_chars
+            .is_some_and(|max| command.chars().nth(max).is_some())
+    }
+
+    /// Truncate or drop the commands over the limit in place.
+    pub fn apply(&self, commands: &mut Vec<Command>) {
+        let Some(max) = self.max_chars else {
+            return;
+        };
+
+        match self.policy {
+            LongCommandPolicy::Keep => {}
+            LongCommandPolicy::Skip => commands.retain(|cmd| !self.is_too_long(&cmd.command)),
+            LongCommandPolicy::Truncate => {
+                for cmd in commands
+                    .iter_mut()
+                    .filter(|cmd| self.is_too_long(&cmd.command))
+                {
+                    let mut truncated: String = cmd.command.chars().take(max).collect();
+                    truncated.push_str(ELISION_MARKER);
+                    cmd.original_command = Some(std::mem::replace(&mut cmd.command, truncated));
+                }
+            }
+        }
+    }
+}
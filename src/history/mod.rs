@@ -1,11 +1,19 @@
+pub mod background;
 pub mod detector;
 pub mod enricher;
+pub mod filter;
+pub mod hook;
+pub mod host_aliases;
+pub mod length_limit;
 pub mod parser;
+pub mod redactor;
+pub mod watcher;
 
 pub use enricher::CommandEnricher;
 pub use parser::HistoryParser;
 // pub use detector::*; // Unused for now
 
+use anyhow::{bail, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -27,11 +35,35 @@ pub struct Command {
     pub danger_score: f32,
     pub danger_reasons: Vec<String>,
     pub shell: String,
+    /// The full text when `command` was cut down to `max_command_length`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_command: Option<String>,
+    /// Labels the user gave the command, e.g. `incident-2024`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+/// Tags as stored: `#` prefixes and surrounding space dropped, empty ones
+/// skipped and each kept once, in the order given.
+pub fn normalize_tags<S: AsRef<str>>(tags: impl IntoIterator<Item = S>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.as_ref().trim().trim_start_matches('#');
+        if !tag.is_empty() && !normalized.iter().any(|seen| seen == tag) {
+            normalized.push(tag.to_string());
+        }
+    }
+    normalized
+}
+
+/// Tags typed into the tag input, separated by commas or spaces.
+pub fn parse_tags(input: &str) -> Vec<String> {
+    normalize_tags(input.split([',', ' ']))
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PackageRef {
-    pub manager: String, // "apt", "npm", "pip", "cargo", "brew"
+    pub manager: String, // "apt", "npm", "yarn", "pnpm", "pip", "cargo", "brew", "gem", "go", "dnf"
     pub name: String,    // "docker", "pandas", "ripgrep"
     pub version: Option<String>,
     pub action: String, // "install", "remove", "update"
@@ -82,6 +114,141 @@ impl Default for Command {
             danger_score: 0.0,
             danger_reasons: Vec::new(),
             shell: "unknown".to_string(),
+            original_command: None,
+            tags: Vec::new(),
+        }
+    }
+}
+
+impl Command {
+    /// Start a command typed at `timestamp`. Unlike spreading
+    /// `..Default::default()`, the builder won't hand back a command without
+    /// a session or with a danger score that doesn't add up.
+    #[allow(dead_code)]
+    pub fn builder(command: impl Into<String>, timestamp: DateTime<Utc>) -> CommandBuilder {
+        CommandBuilder {
+            command: Command {
+                command: command.into(),
+                timestamp,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Check what every stored command should satisfy: some command text, a
+    /// session id, a danger score from 0 to 1, and a nonzero score when the
+    /// command is flagged as dangerous.
+    pub fn validate(&self) -> Result<()> {
+        if self.command.trim().is_empty() {
+            bail!("command text is empty");
         }
+        if self.session_id.is_empty() {
+            bail!("command {:?} has no session id", self.command);
+        }
+        if !(0.0..=1.0).contains(&self.danger_score) {
+            bail!(
+                "danger score {} of {:?} is outside 0 to 1",
+                self.danger_score,
+                self.command
+            );
+        }
+        if self.is_dangerous && self.danger_score == 0.0 {
+            bail!(
+                "command {:?} is flagged dangerous with a zero danger score",
+                self.command
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Typed setters for the optional parts of a [`Command`], from
+/// [`Command::builder`].
+#[derive(Debug, Clone)]
+#[must_use]
+#[allow(dead_code)]
+pub struct CommandBuilder {
+    command: Command,
+}
+
+#[allow(dead_code)]
+impl CommandBuilder {
+    pub fn id(mut self, id: i64) -> Self {
+        self.command.id = Some(id);
+        self
+    }
+
+    pub fn session_id(mut self, session_id: impl Into<String>) -> Self {
+        self.command.session_id = session_id.into();
+        self
+    }
+
+    pub fn shell(mut self, shell: impl Into<String>) -> Self {
+        self.command.shell = shell.into();
+        self
+    }
+
+    pub fn host_id(mut self, host_id: impl Into<String>) -> Self {
+        self.command.host_id = host_id.into();
+        self
+    }
+
+    pub fn exit_code(mut self, exit_code: impl Into<Option<i32>>) -> Self {
+        self.command.exit_code = exit_code.into();
+        self
+    }
+
+    pub fn duration_ms(mut self, duration: impl Into<Option<u64>>) -> Self {
+        self.command.duration = duration.into();
+        self
+    }
+
+    pub fn working_directory(mut self, directory: impl Into<String>) -> Self {
+        self.command.working_directory = Some(directory.into());
+        self
+    }
+
+    pub fn network_endpoints(mut self, endpoints: Vec<String>) -> Self {
+        self.command.network_endpoints = endpoints;
+        self
+    }
+
+    pub fn packages(mut self, packages: Vec<PackageRef>) -> Self {
+        self.command.packages_used = packages;
+        self
+    }
+
+    /// Tag the command as an experiment.
+    pub fn experiment(mut self, tags: Vec<String>) -> Self {
+        self.command.is_experiment = true;
+        self.command.experiment_tags = tags;
+        self
+    }
+
+    pub fn tags(mut self, tags: &[&str]) -> Self {
+        self.command.tags = normalize_tags(tags);
+        self
+    }
+
+    /// A score below the danger threshold, so the command isn't flagged.
+    pub fn danger_score(mut self, score: f32, reasons: Vec<String>) -> Self {
+        self.command.is_dangerous = false;
+        self.command.danger_score = score;
+        self.command.danger_reasons = reasons;
+        self
+    }
+
+    /// Flag the command as dangerous with `score`.
+    pub fn dangerous(mut self, score: f32, reasons: Vec<String>) -> Self {
+        self.command.is_dangerous = true;
+        self.command.danger_score = score;
+        self.command.danger_reasons = reasons;
+        self
+    }
+
+    /// The command, or why it breaks [`Command::validate`].
+    pub fn build(self) -> Result<Command> {
+        self.command.validate()?;
+        Ok(self.command)
     }
 }
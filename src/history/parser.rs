@@ -1,15 +1,27 @@
 use anyhow::Result;
-use chrono::{TimeZone, Utc};
+use chrono::{DateTime, TimeZone, Utc};
 use regex::Regex;
 use std::fs;
+use std::path::{Path, PathBuf};
 
+use super::filter::ImportFilter;
+use super::hook;
+use super::length_limit::LengthLimit;
 use super::{Command, CommandEnricher};
+use crate::config::Config;
+use crate::db::ImportState;
 
 pub struct HistoryParser {
     enricher: CommandEnricher,
+    filter: ImportFilter,
+    length_limit: LengthLimit,
     #[allow(dead_code)]
     bash_regex: Regex,
     zsh_regex: Regex,
+    /// Capture log written by the shell hooks, merged into bash, zsh and fish history
+    hook_log: Option<PathBuf>,
+    /// The configured `history_paths`, read instead of the built-in list when set
+    history_paths: Option<Vec<PathBuf>>,
 }
 
 impl Default for HistoryParser {
@@ -22,29 +34,44 @@ impl HistoryParser {
     pub fn new() -> Self {
         Self {
             enricher: CommandEnricher::new(),
+            filter: ImportFilter::default(),
+            length_limit: LengthLimit::default(),
             // Bash history format: command (no timestamp by default)
             bash_regex: Regex::new(r"^(.+)$").unwrap(),
-            // Zsh history format: : timestamp:duration;command
-            zsh_regex: Regex::new(r"^: (\d+):(\d+);(.+)$").unwrap(),
+            // Zsh EXTENDED_HISTORY format: : <epoch>:<elapsed seconds>;command
+            zsh_regex: Regex::new(r"(?s)^:\s*(\d+):(\d+);(.*)$").unwrap(),
+            hook_log: None,
+            history_paths: None,
         }
     }
 
-    pub async fn parse_all_histories(&self) -> Result<Vec<Command>> {
-        let mut all_commands = Vec::new();
+    /// Merge the shell hooks' capture log at `path` into parsed history.
+    pub fn with_hook_log(mut self, path: impl Into<PathBuf>) -> Self {
+        self.hook_log = Some(path.into());
+        self
+    }
 
-        // Parse bash history
-        if let Ok(commands) = self.parse_bash_history().await {
-            all_commands.extend(commands);
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            enricher: CommandEnricher::from_config(config),
+            filter: ImportFilter::from_config(config),
+            length_limit: LengthLimit::from_config(config),
+            history_paths: Some(config.history_paths.clone()),
+            ..Self::new()
         }
+        .with_hook_log(hook::hook_log_path(config))
+    }
 
-        // Parse zsh history
-        if let Ok(commands) = self.parse_zsh_history().await {
-            all_commands.extend(commands);
-        }
+    #[allow(dead_code)]
+    pub async fn parse_all_histories(&self) -> Result<Vec<Command>> {
+        let mut all_commands = Vec::new();
 
-        // Parse fish history
-        if let Ok(commands) = self.parse_fish_history().await {
-            all_commands.extend(commands);
+        // One unreadable source shouldn't cost the commands from the others
+        for (shell, path) in self.history_files() {
+            match self.parse_history_file(shell, &path).await {
+                Ok(commands) => all_commands.extend(commands),
+                Err(err) => log::warn!("Skipping history file {}: {}", path.display(), err),
+            }
         }
 
         // Sort by timestamp
@@ -53,150 +80,882 @@ impl HistoryParser {
         Ok(all_commands)
     }
 
-    async fn parse_bash_history(&self) -> Result<Vec<Command>> {
-        let home = dirs::home_dir().unwrap_or_default();
-        let history_path = home.join(".bash_history");
+    /// The history files Whiskerlog knows how to read, paired with their shell:
+    /// the configured `history_paths`, or every shell's usual file without a
+    /// config. The hook capture log comes last, as `"hook"`, so commands from
+    /// shells that haven't written their history file yet still show up.
+    pub fn history_files(&self) -> Vec<(&'static str, PathBuf)> {
+        let mut files = match &self.history_paths {
+            Some(paths) => paths
+                .iter()
+                .map(|path| (shell_for_path(path), path.clone()))
+                .collect(),
+            None => {
+                let home = dirs::home_dir().unwrap_or_default();
+                vec![
+                    ("bash", home.join(".bash_history")),
+                    ("zsh", home.join(".zsh_history")),
+                    ("fish", home.join(".local/share/fish/fish_history")),
+                    ("atuin", home.join(".local/share/atuin/history.db")),
+                    ("powershell", powershell_history_path()),
+                    ("nu", nushell_history_path()),
+                ]
+            }
+        };
+        if let Some(hook_log) = &self.hook_log {
+            files.push(("hook", hook_log.clone()));
+        }
+        files
+    }
 
-        if !history_path.exists() {
+    pub async fn parse_history_file(&self, shell: &str, path: &Path) -> Result<Vec<Command>> {
+        if !path.exists() {
             return Ok(Vec::new());
         }
 
-        let content = fs::read_to_string(&history_path)?;
+        if shell == "atuin" {
+            return self.parse_atuin(path).await;
+        }
+        if shell == "nu" && is_sqlite(path) {
+            return self.parse_nushell_sqlite(path).await;
+        }
+
+        let modified = modified_time(&fs::metadata(path)?);
+        let content = decode_history(shell, &fs::read(path)?);
+        Ok(self.parse_content(shell, &content, modified).await)
+    }
+
+    /// Parse only what was appended to `shell`'s history file since `state` was
+    /// recorded.
+    ///
+    /// Falls back to a full re-parse when the file shrank or the content before the
+    /// stored offset changed (truncated or rotated history). A last line without its
+    /// newline is still being written, so it's left for the next call.
+    #[allow(dead_code)]
+    pub async fn parse_new_since(
+        &self,
+        shell: &str,
+        state: &ImportState,
+    ) -> Result<(Vec<Command>, ImportState)> {
+        let (commands, new_state, _) = self.read_new_since(shell, state).await?;
+        Ok((commands, new_state))
+    }
+
+    /// `parse_new_since`, also saying whether the file was read again from the
+    /// start after an earlier import, so its older lines may already be stored.
+    pub(crate) async fn read_new_since(
+        &self,
+        shell: &str,
+        state: &ImportState,
+    ) -> Result<(Vec<Command>, ImportState, bool)> {
+        let path = Path::new(&state.path);
+        let metadata = fs::metadata(path)?;
+        let sqlite = is_sqlite(path);
+        let (size, modified) = if sqlite {
+            sqlite_signature(path, &metadata)
+        } else {
+            (metadata.len(), epoch_secs(&metadata))
+        };
+
+        // Nothing to do if neither size nor mtime moved
+        if size == state.byte_offset && modified.is_some() && modified == state.modified {
+            return Ok((Vec::new(), state.clone(), false));
+        }
+
+        // SQLite sources (Atuin, Nushell) are re-read whole, relying on dedup on insert
+        if sqlite {
+            let commands = self.parse_history_file(shell, path).await?;
+            let new_state = ImportState {
+                path: state.path.clone(),
+                byte_offset: size,
+                last_line_hash: None,
+                modified,
+            };
+            return Ok((commands, new_state, false));
+        }
+
+        let mut content = fs::read(path)?;
+        content.truncate(
+            content
+                .iter()
+                .rposition(|&b| b == b'\n')
+                .map_or(0, |i| i + 1),
+        );
+        let offset = state.byte_offset as usize;
+        let start = if offset > 0
+            && offset <= content.len()
+            && last_line_hash(&content[..offset]) == state.last_line_hash
+        {
+            offset
+        } else {
+            0
+        };
+
+        let appended = decode_history(shell, &content[start..]);
+        let commands = self
+            .parse_content(shell, &appended, modified_time(&metadata))
+            .await;
+
+        let new_state = ImportState {
+            path: state.path.clone(),
+            byte_offset: content.len() as u64,
+            last_line_hash: last_line_hash(&content),
+            modified,
+        };
+
+        Ok((commands, new_state, start == 0 && offset > 0))
+    }
+
+    /// Import history from Atuin's SQLite database, opened read-only.
+    pub async fn parse_atuin(&self, path: &Path) -> Result<Vec<Command>> {
+        let connection = rusqlite::Connection::open_with_flags(
+            path,
+            rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY | rusqlite::OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+
+        // Older Atuin versions have no soft-delete column
+        let has_deleted_at = connection
+            .prepare("SELECT deleted_at FROM history LIMIT 0")
+            .is_ok();
+        let sql = if has_deleted_at {
+            "SELECT command, cwd, exit, duration, session, hostname, timestamp
+             FROM history WHERE deleted_at IS NULL ORDER BY timestamp"
+        } else {
+            "SELECT command, cwd, exit, duration, session, hostname, timestamp
+             FROM history ORDER BY timestamp"
+        };
+
+        let mut stmt = connection.prepare(sql)?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                lossy_text(row, 0)?.unwrap_or_default(),
+                lossy_text(row, 1)?,
+                row.get::<_, Option<i64>>(2)?,
+                row.get::<_, Option<i64>>(3)?,
+                lossy_text(row, 4)?,
+                lossy_text(row, 5)?,
+                row.get::<_, i64>(6)?,
+            ))
+        })?;
+
         let mut commands = Vec::new();
-        let session_id = format!("bash-{}", chrono::Utc::now().timestamp());
+        for row in rows {
+            let (cmd_text, cwd, exit, duration_ns, session, hostname, timestamp_ns) = row?;
 
-        for (line_num, line) in content.lines().enumerate() {
-            if line.trim().is_empty() || line.starts_with('#') {
-                continue;
+            let mut command = Command {
+                command: cmd_text,
+                // Atuin stores timestamps and durations in nanoseconds
+                timestamp: Utc.timestamp_nanos(timestamp_ns),
+                exit_code: exit.map(|e| e as i32),
+                duration: duration_ns
+                    .filter(|d| *d >= 0)
+                    .map(|d| (d / 1_000_000) as u64),
+                working_directory: cwd,
+                session_id: session.unwrap_or_else(|| "atuin".to_string()),
+                shell: "atuin".to_string(),
+                ..Default::default()
+            };
+
+            command = self.enricher.enrich(command).await;
+
+            // Atuin records "hostname:user"; keep it unless the enricher found a remote target
+            if command.host_id == "local" {
+                if let Some(host) = hostname.as_deref().and_then(|h| h.split(':').next()) {
+                    if !host.is_empty() {
+                        command.host_id = host.to_string();
+                    }
+                }
             }
 
+            commands.push(command);
+        }
+
+        self.filter.apply(&mut commands);
+        self.length_limit.apply(&mut commands);
+        Ok(commands)
+    }
+
+    /// Import Nushell history from whichever backend exists under the nushell
+    /// config directory, preferring `history.sqlite3` over `history.txt`.
+    #[allow(dead_code)]
+    pub async fn parse_nushell(&self) -> Result<Vec<Command>> {
+        self.parse_history_file("nu", &nushell_history_path()).await
+    }
+
+    /// Map Nushell's SQLite `history` table onto commands, opened read-only.
+    pub async fn parse_nushell_sqlite(&self, path: &Path) -> Result<Vec<Command>> {
+        let connection = rusqlite::Connection::open_with_flags(
+            path,
+            rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY | rusqlite::OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+
+        let mut stmt = connection.prepare(
+            "SELECT command_line, cwd, exit_status, duration_ms, session_id, hostname, start_timestamp
+             FROM history ORDER BY start_timestamp, id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                lossy_text(row, 0)?.unwrap_or_default(),
+                lossy_text(row, 1)?,
+                row.get::<_, Option<i64>>(2)?,
+                row.get::<_, Option<i64>>(3)?,
+                row.get::<_, Option<i64>>(4)?,
+                lossy_text(row, 5)?,
+                row.get::<_, Option<i64>>(6)?,
+            ))
+        })?;
+
+        let mut commands = Vec::new();
+        for row in rows {
+            let (cmd_text, cwd, exit, duration_ms, session, hostname, start_ms) = row?;
+
             let mut command = Command {
-                command: line.to_string(),
-                timestamp: Utc::now() - chrono::Duration::minutes(line_num as i64),
-                session_id: session_id.clone(),
-                shell: "bash".to_string(),
+                command: cmd_text,
+                // Nushell stores milliseconds since the epoch
+                timestamp: start_ms
+                    .and_then(|ms| Utc.timestamp_millis_opt(ms).single())
+                    .unwrap_or_else(Utc::now),
+                exit_code: exit.map(|e| e as i32),
+                duration: duration_ms.filter(|d| *d >= 0).map(|d| d as u64),
+                working_directory: cwd,
+                session_id: session
+                    .map(|id| format!("nu-{}", id))
+                    .unwrap_or_else(|| "nu-history".to_string()),
+                shell: "nu".to_string(),
                 ..Default::default()
             };
 
-            // Enrich the command with additional metadata
             command = self.enricher.enrich(command).await;
+
+            // Keep the recorded hostname unless the enricher found a remote target
+            if command.host_id == "local" {
+                if let Some(host) = hostname.filter(|h| !h.is_empty()) {
+                    command.host_id = host;
+                }
+            }
+
             commands.push(command);
         }
 
+        self.filter.apply(&mut commands);
+        self.length_limit.apply(&mut commands);
         Ok(commands)
     }
 
-    async fn parse_zsh_history(&self) -> Result<Vec<Command>> {
-        let home = dirs::home_dir().unwrap_or_default();
-        let history_path = home.join(".zsh_history");
+    /// `modified` is when the file last changed, which entries without a
+    /// time of their own are dated from.
+    async fn parse_content(
+        &self,
+        shell: &str,
+        content: &str,
+        modified: DateTime<Utc>,
+    ) -> Vec<Command> {
+        let mut commands = match shell {
+            "bash" => {
+                self.merge_hook_log(shell, parse_bash_history(content, modified))
+                    .await
+            }
+            "zsh" => {
+                let entries = self.parse_zsh_history(content, modified);
+                self.merge_hook_log(shell, entries).await
+            }
+            "fish" => {
+                self.merge_hook_log(shell, parse_fish_history(content, modified))
+                    .await
+            }
+            "powershell" => self.parse_powershell_history(content, modified).await,
+            "nu" => self.parse_nushell_text(content, modified).await,
+            "hook" => self.parse_hook_log(content).await,
+            _ => Vec::new(),
+        };
+
+        // Attribute first so an ignored `exit` still closes its session
+        self.enricher.attribute_remote_sessions(&mut commands);
+        self.filter.apply(&mut commands);
+        self.length_limit.apply(&mut commands);
+        commands
+    }
 
-        if !history_path.exists() {
-            return Ok(Vec::new());
+    /// The capture log read as history of its own. A command later found in its
+    /// shell's history file merges to the same text, time and session, so it's
+    /// only stored once.
+    async fn parse_hook_log(&self, content: &str) -> Vec<Command> {
+        let mut commands = Vec::new();
+        for entry in content.lines().filter_map(hook::parse_hook_line) {
+            let command = Command {
+                command: entry.command,
+                timestamp: Utc
+                    .timestamp_opt(entry.timestamp, 0)
+                    .single()
+                    .unwrap_or_else(Utc::now),
+                exit_code: Some(entry.exit_code),
+                duration: Some(entry.duration),
+                working_directory: entry.working_directory,
+                session_id: entry.session_id,
+                shell: entry.shell,
+                ..Default::default()
+            };
+            commands.push(self.enricher.enrich(command).await);
+        }
+        commands
+    }
+
+    /// Fill in what the shell hook captured for each command, then enrich. Each
+    /// command comes paired with the time the shell itself recorded for it, if
+    /// any, which decides which log entry matches; without a capture log the
+    /// commands pass through as-is.
+    async fn merge_hook_log(
+        &self,
+        shell: &str,
+        entries: Vec<(Command, Option<i64>)>,
+    ) -> Vec<Command> {
+        let hook_log = self
+            .hook_log
+            .as_deref()
+            .map(|path| hook::read_hook_log(path, shell))
+            .unwrap_or_default();
+        let history: Vec<(&str, Option<i64>)> = entries
+            .iter()
+            .map(|(command, time)| (command.command.as_str(), *time))
+            .collect();
+        let matched = hook::match_hook_entries(&history, hook_log);
+
+        let mut commands = Vec::with_capacity(entries.len());
+        let mut recorded_times = Vec::with_capacity(entries.len());
+        for ((mut command, time), hook_entry) in entries.into_iter().zip(matched) {
+            recorded_times.push(time.is_some() || hook_entry.is_some());
+            if let Some(entry) = hook_entry {
+                // The hook's time, like the rest, must match what importing the
+                // capture log itself stored
+                if let Some(timestamp) = Utc.timestamp_opt(entry.timestamp, 0).single() {
+                    command.timestamp = timestamp;
+                }
+                command.exit_code = Some(entry.exit_code);
+                command.duration = Some(entry.duration);
+                command.session_id = entry.session_id;
+                command.working_directory = entry.working_directory;
+            }
+
+            // Enrich the command with additional metadata
+            commands.push(self.enricher.enrich(command).await);
+        }
+
+        // Commands without a recorded time have made-up ones, which would give
+        // them a different session on every import, so they keep the file's
+        self.enricher.derive_sessions(
+            commands
+                .iter_mut()
+                .zip(&recorded_times)
+                .filter(|(_, recorded)| **recorded)
+                .map(|(command, _)| command),
+        );
+        commands
+    }
+
+    /// PSReadLine's ConsoleHost_history.txt: one command per line, no timestamps,
+    /// with a trailing backtick continuing a command onto the next line.
+    async fn parse_powershell_history(
+        &self,
+        content: &str,
+        modified: DateTime<Utc>,
+    ) -> Vec<Command> {
+        let mut entries: Vec<String> = Vec::new();
+        let mut current = String::new();
+        for line in content.trim_start_matches('\u{feff}').lines() {
+            if !current.is_empty() {
+                current.push('\n');
+            }
+            current.push_str(line);
+            if !line.ends_with('`') {
+                entries.push(std::mem::take(&mut current));
+            }
+        }
+        if !current.is_empty() {
+            entries.push(current);
         }
 
-        let content = fs::read_to_string(&history_path)?;
         let mut commands = Vec::new();
-        let session_id = format!("zsh-{}", chrono::Utc::now().timestamp());
+        let session_id = "powershell-history".to_string();
+        let total = entries.len();
 
-        for line in content.lines() {
+        for (index, entry) in entries.iter().enumerate() {
+            if entry.trim().is_empty() {
+                continue;
+            }
+
+            // No timestamps in the file; keep file order with the last line newest
+            let mut command = Command {
+                command: entry.to_string(),
+                timestamp: untimed_timestamp(modified, index, total),
+                session_id: session_id.clone(),
+                shell: "powershell".to_string(),
+                ..Default::default()
+            };
+
+            command = self.enricher.enrich(command).await;
+            commands.push(command);
+        }
+
+        commands
+    }
+
+    /// Nushell's plain-text backend: one command per line, with embedded newlines
+    /// written as `<\n>` by reedline.
+    async fn parse_nushell_text(&self, content: &str, modified: DateTime<Utc>) -> Vec<Command> {
+        let mut commands = Vec::new();
+        let session_id = "nu-history".to_string();
+        let lines: Vec<&str> = content.lines().collect();
+        let total = lines.len();
+
+        for (index, line) in lines.iter().enumerate() {
             if line.trim().is_empty() {
                 continue;
             }
 
-            let mut command = if let Some(captures) = self.zsh_regex.captures(line) {
-                let timestamp = captures.get(1).unwrap().as_str().parse::<i64>()?;
+            let mut command = Command {
+                command: line.replace("<\\n>", "\n"),
+                timestamp: untimed_timestamp(modified, index, total),
+                session_id: session_id.clone(),
+                shell: "nu".to_string(),
+                ..Default::default()
+            };
+
+            command = self.enricher.enrich(command).await;
+            commands.push(command);
+        }
+
+        commands
+    }
+
+    fn parse_zsh_history(
+        &self,
+        content: &str,
+        modified: DateTime<Utc>,
+    ) -> Vec<(Command, Option<i64>)> {
+        let mut entries = Vec::new();
+        let session_id = "zsh-history".to_string();
+
+        // EXTENDED_HISTORY headers mark where entries start, a surer boundary
+        // than a trailing backslash
+        let lines = if content.lines().any(|line| self.zsh_regex.is_match(line)) {
+            self.split_at_zsh_headers(content)
+        } else {
+            join_continued_lines(content, true)
+        };
+        for (index, line) in lines.iter().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let entry = if let Some(captures) = self.zsh_regex.captures(line) {
+                let epoch = captures.get(1).unwrap().as_str();
+                let Some((timestamp, time)) = epoch
+                    .parse::<i64>()
+                    .ok()
+                    .and_then(|secs| Some((secs, Utc.timestamp_opt(secs, 0).single()?)))
+                else {
+                    log::warn!("Skipping zsh history entry with bad time {:?}", epoch);
+                    continue;
+                };
                 let duration = captures.get(2).unwrap().as_str().parse::<u64>().ok();
                 let cmd_text = captures.get(3).unwrap().as_str();
 
-                Command {
+                let command = Command {
                     command: cmd_text.to_string(),
-                    timestamp: Utc
-                        .timestamp_opt(timestamp, 0)
-                        .single()
-                        .unwrap_or_else(Utc::now),
+                    timestamp: time,
                     duration: duration.map(|d| d * 1000), // convert to milliseconds
                     session_id: session_id.clone(),
                     shell: "zsh".to_string(),
                     ..Default::default()
-                }
+                };
+                (command, Some(timestamp))
             } else {
-                // Fallback for malformed lines
-                Command {
+                // Plain history without EXTENDED_HISTORY
+                let command = Command {
                     command: line.to_string(),
-                    timestamp: Utc::now(),
+                    timestamp: untimed_timestamp(modified, index, lines.len()),
                     session_id: session_id.clone(),
                     shell: "zsh".to_string(),
                     ..Default::default()
-                }
+                };
+                (command, None)
             };
 
-            // Enrich the command with additional metadata
-            command = self.enricher.enrich(command).await;
-            commands.push(command);
+            entries.push(entry);
         }
 
-        Ok(commands)
+        entries
     }
 
-    async fn parse_fish_history(&self) -> Result<Vec<Command>> {
-        let home = dirs::home_dir().unwrap_or_default();
-        let history_path = home.join(".local/share/fish/fish_history");
+    /// Entries of an EXTENDED_HISTORY file. zsh writes an embedded newline as a
+    /// backslash and a newline, so a line after a trailing backslash continues
+    /// the entry unless it's a `: <epoch>:<dur>;` header; then the backslash
+    /// ended the command itself. Other lines are bare entries of their own.
+    fn split_at_zsh_headers(&self, content: &str) -> Vec<String> {
+        let mut entries: Vec<String> = Vec::new();
 
-        if !history_path.exists() {
-            return Ok(Vec::new());
+        for line in content.lines() {
+            match entries.last_mut() {
+                Some(entry) if entry.ends_with('\\') && !self.zsh_regex.is_match(line) => {
+                    entry.pop();
+                    entry.push('\n');
+                    entry.push_str(line);
+                }
+                _ => entries.push(line.to_string()),
+            }
         }
 
-        let content = fs::read_to_string(&history_path)?;
-        let mut commands = Vec::new();
-        let session_id = format!("fish-{}", chrono::Utc::now().timestamp());
+        entries
+    }
+}
 
-        let mut current_command = None;
-        let mut current_timestamp = None;
+/// Plain `.bash_history`, with `#<epoch>` lines from `HISTTIMEFORMAT` taken as the
+/// time of the command after them.
+fn parse_bash_history(content: &str, modified: DateTime<Utc>) -> Vec<(Command, Option<i64>)> {
+    let mut entries = Vec::new();
+    let session_id = "bash-history".to_string();
+
+    let mut pending_time = None;
+    let lines = join_continued_lines(content, false);
+    for (index, line) in lines.iter().enumerate() {
+        if let Some(epoch) = line.strip_prefix('#').and_then(|t| t.parse::<i64>().ok()) {
+            pending_time = Some(epoch);
+            continue;
+        }
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
 
-        for line in content.lines() {
-            if let Some(stripped) = line.strip_prefix("- cmd: ") {
-                current_command = Some(stripped.to_string());
-            } else if let Some(stripped) = line.strip_prefix("  when: ") {
-                if let Ok(timestamp) = stripped.parse::<i64>() {
-                    current_timestamp = Some(
-                        Utc.timestamp_opt(timestamp, 0)
-                            .single()
-                            .unwrap_or_else(Utc::now),
-                    );
+        let time = pending_time.take();
+        let command = Command {
+            command: line.to_string(),
+            timestamp: time
+                .and_then(|secs| Utc.timestamp_opt(secs, 0).single())
+                .unwrap_or_else(|| untimed_timestamp(modified, index, lines.len())),
+            session_id: session_id.clone(),
+            shell: "bash".to_string(),
+            ..Default::default()
+        };
+        entries.push((command, time));
+    }
+
+    entries
+}
+
+fn parse_fish_history(content: &str, modified: DateTime<Utc>) -> Vec<(Command, Option<i64>)> {
+    let mut entries = Vec::new();
+    let session_id = "fish-history".to_string();
+
+    // Entries without a `when` are rare enough to share the file's time
+    let fish_command = |cmd_text: String, timestamp: Option<i64>| {
+        let command = Command {
+            command: cmd_text,
+            timestamp: timestamp
+                .and_then(|secs| Utc.timestamp_opt(secs, 0).single())
+                .unwrap_or(modified),
+            session_id: session_id.clone(),
+            shell: "fish".to_string(),
+            ..Default::default()
+        };
+        (command, timestamp)
+    };
+
+    let mut current_command = None;
+    let mut current_timestamp = None;
+
+    for line in content.lines() {
+        if let Some(stripped) = line.strip_prefix("- cmd: ") {
+            current_command = Some(stripped.to_string());
+        } else if let Some(stripped) = line.strip_prefix("  when: ") {
+            if let Ok(timestamp) = stripped.parse::<i64>() {
+                current_timestamp = Some(timestamp);
+            }
+        } else if line.trim().is_empty() && current_command.is_some() {
+            // End of entry
+            if let Some(cmd_text) = current_command.take() {
+                entries.push(fish_command(cmd_text, current_timestamp));
+            }
+            current_timestamp = None;
+        }
+    }
+
+    // Handle last entry if file doesn't end with blank line
+    if let Some(cmd_text) = current_command {
+        entries.push(fish_command(cmd_text, current_timestamp));
+    }
+
+    entries
+}
+
+/// When `metadata`'s file last changed, to the second like stored timestamps.
+fn modified_time(metadata: &fs::Metadata) -> DateTime<Utc> {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .and_then(|d| Utc.timestamp_opt(d.as_secs() as i64, 0).single())
+        .unwrap_or_else(|| Utc.timestamp_opt(Utc::now().timestamp(), 0).unwrap())
+}
+
+/// A time for entry `index` of `count` in a file that records none: a minute
+/// apart, the last one at the file's modification time. The mtime is used
+/// rather than the current time on purpose: it only moves when the file
+/// changes, so reading the same file again finds the same commands. When it
+/// does change, the import skips the lines it already stored instead.
+fn untimed_timestamp(modified: DateTime<Utc>, index: usize, count: usize) -> DateTime<Utc> {
+    modified - chrono::Duration::minutes(count.saturating_sub(index + 1) as i64)
+}
+
+/// Upper bound on physical lines joined into one command, so a stray quote
+/// can't swallow the rest of the history.
+const MAX_CONTINUATION_LINES: usize = 50;
+
+/// Join physical history lines into logical commands, preserving embedded newlines.
+///
+/// A trailing backslash, an open heredoc or an unterminated quote continues the
+/// command onto the next line. zsh escapes every embedded newline with a backslash,
+/// so `strip_backslash` removes that escape and only backslashes join lines.
+fn join_continued_lines(content: &str, strip_backslash: bool) -> Vec<String> {
+    let mut entries = Vec::new();
+    let mut current = String::new();
+    let mut line_count = 0;
+    let mut heredoc: Option<String> = None;
+
+    for line in content.lines() {
+        if line_count > 0 {
+            current.push('\n');
+        }
+        current.push_str(line);
+        line_count += 1;
+
+        if line_count >= MAX_CONTINUATION_LINES {
+            entries.push(std::mem::take(&mut current));
+            line_count = 0;
+            heredoc = None;
+            continue;
+        }
+
+        if ends_with_line_continuation(line) {
+            if strip_backslash {
+                current.pop();
+            }
+            continue;
+        }
+
+        if strip_backslash {
+            entries.push(std::mem::take(&mut current));
+            line_count = 0;
+            continue;
+        }
+
+        match &heredoc {
+            Some(delimiter) if line.trim() != delimiter => continue,
+            Some(_) => heredoc = None,
+            None => {
+                if let Some(delimiter) = heredoc_delimiter(line) {
+                    heredoc = Some(delimiter);
+                    continue;
                 }
-            } else if line.trim().is_empty() && current_command.is_some() {
-                // End of entry
-                if let Some(cmd_text) = current_command.take() {
-                    let mut command = Command {
-                        command: cmd_text,
-                        timestamp: current_timestamp.unwrap_or_else(Utc::now),
-                        session_id: session_id.clone(),
-                        shell: "fish".to_string(),
-                        ..Default::default()
-                    };
-
-                    // Enrich the command with additional metadata
-                    command = self.enricher.enrich(command).await;
-                    commands.push(command);
+                if has_unclosed_quote(&current) {
+                    continue;
                 }
-                current_timestamp = None;
             }
         }
 
-        // Handle last entry if file doesn't end with blank line
-        if let Some(cmd_text) = current_command {
-            let mut command = Command {
-                command: cmd_text,
-                timestamp: current_timestamp.unwrap_or_else(Utc::now),
-                session_id: session_id.clone(),
-                shell: "fish".to_string(),
-                ..Default::default()
-            };
+        entries.push(std::mem::take(&mut current));
+        line_count = 0;
+    }
 
-            command = self.enricher.enrich(command).await;
-            commands.push(command);
+    if !current.is_empty() {
+        entries.push(current);
+    }
+
+    entries
+}
+
+fn ends_with_line_continuation(line: &str) -> bool {
+    line.chars().rev().take_while(|c| *c == '\\').count() % 2 == 1
+}
+
+fn heredoc_delimiter(line: &str) -> Option<String> {
+    let start = line.find("<<")?;
+    // Ignore `<<` inside quotes
+    if has_unclosed_quote(&line[..start]) {
+        return None;
+    }
+    let rest = line[start + 2..].trim_start_matches('-');
+    // `<<<` is a here-string, not a heredoc
+    if rest.starts_with('<') {
+        return None;
+    }
+
+    let delimiter: String = rest
+        .trim_start()
+        .trim_start_matches(['\'', '"'])
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || *c == '_')
+        .collect();
+
+    // A leading digit is a bit shift like `$((1 << 4))`, not a heredoc
+    let is_word = delimiter
+        .chars()
+        .next()
+        .is_some_and(|c| !c.is_ascii_digit());
+    is_word.then_some(delimiter)
+}
+
+fn has_unclosed_quote(text: &str) -> bool {
+    let mut in_single = false;
+    let mut in_double = false;
+    let mut escaped = false;
+
+    for c in text.chars() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' if !in_single => escaped = true,
+            '\'' if !in_double => in_single = !in_single,
+            '"' if !in_single => in_double = !in_double,
+            _ => {}
         }
+    }
 
-        Ok(commands)
+    in_single || in_double
+}
+
+/// Where PSReadLine keeps history: `%APPDATA%\Microsoft\Windows\PowerShell\PSReadLine`
+/// on Windows and `~/.local/share/powershell/PSReadLine` for pwsh elsewhere.
+pub fn powershell_history_path() -> PathBuf {
+    let data_dir = dirs::data_dir().unwrap_or_default();
+    let dir = if cfg!(windows) {
+        data_dir
+            .join("Microsoft")
+            .join("Windows")
+            .join("PowerShell")
+    } else {
+        data_dir.join("powershell")
+    };
+    dir.join("PSReadLine").join("ConsoleHost_history.txt")
+}
+
+/// Nushell's history under its config dir: the SQLite backend if present,
+/// otherwise the plain-text one.
+pub fn nushell_history_path() -> PathBuf {
+    let dir = dirs::config_dir().unwrap_or_default().join("nushell");
+    let sqlite = dir.join("history.sqlite3");
+    if sqlite.exists() {
+        sqlite
+    } else {
+        dir.join("history.txt")
     }
 }
+
+fn is_sqlite(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("db" | "sqlite" | "sqlite3")
+    )
+}
+
+/// The `-wal` file SQLite keeps next to a database while it's open.
+pub(crate) fn wal_path(path: &Path) -> PathBuf {
+    let mut wal = path.as_os_str().to_owned();
+    wal.push("-wal");
+    PathBuf::from(wal)
+}
+
+fn epoch_secs(metadata: &fs::Metadata) -> Option<i64> {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+}
+
+/// Combined size and latest mtime of a SQLite database and its `-wal` file,
+/// since a writer in WAL mode often leaves the main file untouched.
+fn sqlite_signature(path: &Path, metadata: &fs::Metadata) -> (u64, Option<i64>) {
+    match fs::metadata(wal_path(path)) {
+        Ok(wal) => (
+            metadata.len() + wal.len(),
+            epoch_secs(metadata).max(epoch_secs(&wal)),
+        ),
+        Err(_) => (metadata.len(), epoch_secs(metadata)),
+    }
+}
+
+/// Guess the shell that wrote a configured history file from its path.
+fn shell_for_path(path: &Path) -> &'static str {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    if path.to_string_lossy().contains("nushell") {
+        "nu"
+    } else if path.to_string_lossy().contains("atuin") || name.ends_with(".db") {
+        "atuin"
+    } else if name.contains("zsh") {
+        "zsh"
+    } else if name.contains("fish") {
+        "fish"
+    } else if name.contains("consolehost_history") {
+        "powershell"
+    } else {
+        "bash"
+    }
+}
+
+/// FNV-1a hash of the last non-blank line, stable across builds so it can be stored.
+fn last_line_hash(content: &[u8]) -> Option<String> {
+    let last_line = content
+        .split(|b| *b == b'\n')
+        .rev()
+        .find(|line| !line.iter().all(u8::is_ascii_whitespace))?;
+
+    let hash = last_line.iter().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    });
+
+    Some(format!("{:016x}", hash))
+}
+
+/// A text column from another tool's database, with invalid UTF-8 replaced
+/// rather than failing the whole import. Blobs are read the same way.
+fn lossy_text(row: &rusqlite::Row, index: usize) -> rusqlite::Result<Option<String>> {
+    use rusqlite::types::ValueRef;
+
+    Ok(match row.get_ref(index)? {
+        ValueRef::Null => None,
+        ValueRef::Text(bytes) | ValueRef::Blob(bytes) => {
+            Some(String::from_utf8_lossy(bytes).into_owned())
+        }
+        ValueRef::Integer(value) => Some(value.to_string()),
+        ValueRef::Real(value) => Some(value.to_string()),
+    })
+}
+
+/// Decode raw history bytes, undoing zsh's metafied encoding where needed.
+fn decode_history(shell: &str, bytes: &[u8]) -> String {
+    if shell != "zsh" {
+        return String::from_utf8_lossy(bytes).to_string();
+    }
+
+    // zsh escapes bytes >= 0x83 as 0x83 followed by the byte XOR 0x20
+    const META: u8 = 0x83;
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut iter = bytes.iter();
+    while let Some(&byte) = iter.next() {
+        if byte == META {
+            if let Some(&next) = iter.next() {
+                decoded.push(next ^ 0x20);
+            }
+        } else {
+            decoded.push(byte);
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).to_string()
+}
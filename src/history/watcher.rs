@@ -0,0 +1,244 @@
+use anyhow::Result;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+
+use super::parser::wal_path;
+use super::{Command, HistoryParser};
+use crate::config::Config;
+use crate::db::{CommandFilter, Database, ImportKind, ImportState};
+
+/// Import whatever was appended to `shell`'s history file at `path` since the
+/// last import and return the commands that weren't stored yet. A source that
+/// can't be read is logged and skipped rather than failing.
+pub async fn import_appended(
+    parser: &HistoryParser,
+    db: &mut Database,
+    shell: &str,
+    path: &Path,
+) -> Result<Vec<Command>> {
+    import_appended_with_progress(parser, db, shell, path, &mut |_| {}).await
+}
+
+/// How far an import of one file has got.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImportProgress {
+    /// Commands read from the file
+    pub parsed: usize,
+    /// Of those, the ones stored so far
+    pub stored: usize,
+}
+
+/// `import_appended`, calling `progress` once the file is parsed and again
+/// after each command is stored.
+pub async fn import_appended_with_progress(
+    parser: &HistoryParser,
+    db: &mut Database,
+    shell: &str,
+    path: &Path,
+    progress: &mut dyn FnMut(ImportProgress),
+) -> Result<Vec<Command>> {
+    let path = path.to_string_lossy();
+    let state = db
+        .get_import_state(&path)
+        .await?
+        .unwrap_or_else(|| ImportState::new(path.as_ref()));
+
+    let (mut commands, new_state, reread) = match parser.read_new_since(shell, &state).await {
+        Ok(result) => result,
+        Err(err) => {
+            log::warn!("Skipping history import from {}: {}", path, err);
+            return Ok(Vec::new());
+        }
+    };
+    if new_state == state {
+        return Ok(Vec::new());
+    }
+    if reread {
+        skip_stored_untimed(db, shell, &mut commands).await?;
+    }
+
+    let parsed = commands.len();
+    progress(ImportProgress { parsed, stored: 0 });
+    let inserted = db
+        .insert_new_commands_with_progress(&path, ImportKind::History, commands, &mut |stored| {
+            progress(ImportProgress { parsed, stored })
+        })
+        .await?;
+    db.set_import_state(&new_state).await?;
+    Ok(inserted)
+}
+
+/// Drop the commands at the start of a re-read file that were stored by an
+/// earlier import but carry no time of their own. Their made-up times follow
+/// the file's mtime, so dedup on insert can't recognise them once the file has
+/// changed, e.g. after bash trimmed it from the top. The longest run of stored
+/// commands that the file now starts with is taken to be those lines.
+async fn skip_stored_untimed(
+    db: &mut Database,
+    shell: &str,
+    commands: &mut Vec<Command>,
+) -> Result<()> {
+    let Some(session) = untimed_session(shell) else {
+        return Ok(());
+    };
+    let untimed: Vec<&str> = commands
+        .iter()
+        .filter(|command| command.session_id == session)
+        .map(|command| command.command.as_str())
+        .collect();
+    if untimed.is_empty() {
+        return Ok(());
+    }
+
+    let stored = CommandFilter::new().and_bound(
+        "shell = ? AND session_id = ?",
+        [shell.to_string(), session.clone()],
+    );
+    let stored: Vec<String> = db
+        .get_commands_where(&CommandFilter::newest(&stored, untimed.len()))
+        .await?
+        .into_iter()
+        .map(|command| command.command)
+        .collect();
+
+    let mut skip = overlap(&stored, &untimed);
+    commands.retain(|command| {
+        if skip > 0 && command.session_id == session {
+            skip -= 1;
+            return false;
+        }
+        true
+    });
+    Ok(())
+}
+
+/// The per-file session of `shell`'s history when it doesn't record times.
+fn untimed_session(shell: &str) -> Option<String> {
+    matches!(shell, "bash" | "zsh" | "powershell" | "nu").then(|| format!("{}-history", shell))
+}
+
+/// Length of the longest run at the end of `stored` that `parsed` starts with.
+fn overlap(stored: &[String], parsed: &[&str]) -> usize {
+    // Knuth-Morris-Pratt: `fallback[i]` is the longest proper border of `parsed[..=i]`
+    let mut fallback = vec![0; parsed.len()];
+    let mut len = 0;
+    for i in 1..parsed.len() {
+        while len > 0 && parsed[i] != parsed[len] {
+            len = fallback[len - 1];
+        }
+        if parsed[i] == parsed[len] {
+            len += 1;
+        }
+        fallback[i] = len;
+    }
+
+    let mut matched = 0;
+    for command in stored {
+        if matched == parsed.len() {
+            matched = fallback[matched - 1];
+        }
+        while matched > 0 && command != parsed[matched] {
+            matched = fallback[matched - 1];
+        }
+        if command == parsed[matched] {
+            matched += 1;
+        }
+    }
+    matched
+}
+
+/// Follows the history files and hook capture log while the TUI is open. A
+/// background thread imports each file as it changes, over its own database
+/// connection, and sends the new commands for the run loop to pick up.
+pub struct HistoryWatcher {
+    // Dropping the watcher stops the events, which ends the thread
+    _watcher: RecommendedWatcher,
+    receiver: Receiver<Vec<Command>>,
+}
+
+impl HistoryWatcher {
+    pub async fn start(config: &Config) -> Result<Self> {
+        let parser = HistoryParser::from_config(config);
+        let mut db = Database::new(&config.database_path).await?;
+        let files = parser.history_files();
+
+        let (event_tx, event_rx) = mpsc::channel::<PathBuf>();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let Ok(event) = event else {
+                    return;
+                };
+                if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                    for path in event.paths {
+                        let _ = event_tx.send(path);
+                    }
+                }
+            })?;
+
+        // Watch the directories, so files that get replaced rather than appended
+        // to (zsh, fish) are still followed
+        let dirs: HashSet<&Path> = files
+            .iter()
+            .filter_map(|(_, path)| path.parent())
+            .filter(|dir| dir.is_dir())
+            .collect();
+        for dir in dirs {
+            if let Err(err) = watcher.watch(dir, RecursiveMode::NonRecursive) {
+                log::warn!("Not watching {} for new history: {}", dir.display(), err);
+            }
+        }
+
+        let (command_tx, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            // Parsing and the database aren't Send, so they get this thread's own runtime
+            let runtime = match tokio::runtime::Builder::new_current_thread().build() {
+                Ok(runtime) => runtime,
+                Err(err) => {
+                    log::warn!("Live history import stopped: {}", err);
+                    return;
+                }
+            };
+
+            while let Ok(path) = event_rx.recv() {
+                // A single write usually fires several events
+                let mut changed = HashSet::from([path]);
+                while let Ok(path) = event_rx.try_recv() {
+                    changed.insert(path);
+                }
+
+                // Atuin in WAL mode may only touch the `-wal` file
+                let touched =
+                    |file: &PathBuf| changed.contains(file) || changed.contains(&wal_path(file));
+                for (shell, file) in files.iter().filter(|(_, file)| touched(file)) {
+                    match runtime.block_on(import_appended(&parser, &mut db, shell, file)) {
+                        Ok(commands) if commands.is_empty() => {}
+                        Ok(commands) => {
+                            if command_tx.send(commands).is_err() {
+                                return;
+                            }
+                        }
+                        Err(err) => {
+                            log::warn!("Live import from {} failed: {}", file.display(), err)
+                        }
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            _watcher: watcher,
+            receiver,
+        })
+    }
+
+    /// Commands imported since the last call, oldest batch first.
+    pub fn drain(&mut self) -> Vec<Command> {
+        let mut commands = Vec::new();
+        while let Ok(batch) = self.receiver.try_recv() {
+            commands.extend(batch);
+        }
+        commands
+    }
+}
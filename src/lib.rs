@@ -1,9 +1,16 @@
 // Library exports for testing
+pub mod about;
 pub mod analysis;
 pub mod app;
 pub mod config;
 pub mod db;
+pub mod docs;
+pub mod export;
 pub mod history;
+pub mod metrics;
+pub mod onboarding;
+pub mod report;
+pub mod stats_summary;
 pub mod ui;
 
 // Re-export commonly used types for tests
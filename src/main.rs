@@ -1,6 +1,10 @@
 use anyhow::Result;
+use clap::{CommandFactory, Parser, Subcommand};
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    event::{
+        self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        Event, KeyCode, KeyModifiers,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -9,15 +13,478 @@ use ratatui::{
     Terminal,
 };
 use std::io::{self, Write};
+use std::path::PathBuf;
 
+mod about;
 mod analysis;
 mod app;
 mod config;
 mod db;
+mod docs;
+mod export;
 mod history;
+mod metrics;
+mod onboarding;
+mod report;
+mod stats_summary;
 mod ui;
 
-use app::App;
+use app::{App, Tab};
+use config::Config;
+use db::Database;
+use docs::DocViewer;
+use export::ExportFormat;
+use history::background::BackgroundImport;
+use history::hook::{self, HookShell};
+use report::ReportFormat;
+
+#[derive(Parser)]
+#[command(name = "whiskerlog", version, about = "Terminal history analytics")]
+#[command(group(clap::ArgGroup::new("legacy").args([
+    "export", "report", "stats", "import", "reset_db", "undo_import", "reenrich",
+    "check_db", "prune", "metrics_out", "print_hook",
+])))]
+#[command(group(clap::ArgGroup::new("output").args(["export", "report", "stats"])))]
+#[command(group(clap::ArgGroup::new("windowed").args(["export", "stats"])))]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Mode>,
+
+    /// Read and save settings in this file instead of the default config.toml
+    /// (also $WHISKERLOG_CONFIG)
+    #[arg(long, value_name = "PATH", global = true)]
+    config: Option<PathBuf>,
+
+    /// Use this database instead of the configured `database_path` (also
+    /// $WHISKERLOG_DATA, a directory for the database and hook log)
+    #[arg(long, value_name = "PATH", global = true)]
+    db: Option<PathBuf>,
+
+    /// Deprecated: use `whiskerlog export`
+    #[arg(long, value_enum, hide = true)]
+    export: Option<ExportFormat>,
+
+    /// Deprecated: use `whiskerlog report`
+    #[arg(long, value_enum, hide = true)]
+    report: Option<ReportFormat>,
+
+    /// Deprecated: use `whiskerlog stats`
+    #[arg(long, hide = true)]
+    stats: bool,
+
+    /// Deprecated: use `whiskerlog export --anonymize`
+    #[arg(long, requires = "export", hide = true)]
+    anonymize: bool,
+
+    /// Deprecated: use the `--out` of `whiskerlog export`, `report` or `stats`
+    #[arg(long, requires = "output", hide = true)]
+    out: Option<PathBuf>,
+
+    /// Deprecated: use the `--since` of `whiskerlog export` or `stats`
+    #[arg(long, requires = "windowed", hide = true)]
+    since: Option<String>,
+
+    /// Deprecated: use the `--until` of `whiskerlog export` or `stats`
+    #[arg(long, requires = "windowed", hide = true)]
+    until: Option<String>,
+
+    /// Deprecated: use `whiskerlog import`
+    #[arg(long, value_name = "FILE", hide = true)]
+    import: Option<PathBuf>,
+
+    /// Deprecated: use `whiskerlog reset-db`
+    #[arg(long, hide = true)]
+    reset_db: bool,
+
+    /// Deprecated: use `whiskerlog undo-import`
+    #[arg(long, hide = true)]
+    undo_import: bool,
+
+    /// Deprecated: use `whiskerlog reenrich`
+    #[arg(long, hide = true)]
+    reenrich: bool,
+
+    /// Deprecated: use `whiskerlog check-db`
+    #[arg(long, hide = true)]
+    check_db: bool,
+
+    /// Deprecated: use `whiskerlog check-db --repair`
+    #[arg(long, requires = "check_db", hide = true)]
+    repair: bool,
+
+    /// Deprecated: use `whiskerlog prune`
+    #[arg(long, value_name = "DAYS", num_args = 0..=1, hide = true)]
+    prune: Option<Option<u32>>,
+
+    /// Deprecated: use `whiskerlog metrics --out`
+    #[arg(long, value_name = "PATH", hide = true)]
+    metrics_out: Option<PathBuf>,
+
+    /// Deprecated: use `whiskerlog print-hook`
+    #[arg(long, value_enum, value_name = "SHELL", hide = true)]
+    print_hook: Option<HookShell>,
+}
+
+/// What to do instead of, or before, starting the UI.
+#[derive(Subcommand)]
+enum Mode {
+    /// Dump stored history in the given format
+    Export {
+        #[arg(value_enum)]
+        format: ExportFormat,
+
+        /// Strip arguments and replace directories and hosts with `<dir-N>` and
+        /// `<host-N>`, for sharing
+        #[arg(long)]
+        anonymize: bool,
+
+        /// Write the export to this file (defaults to stdout)
+        #[arg(long)]
+        out: Option<PathBuf>,
+
+        /// Only export commands run on or after this date (YYYY-MM-DD or RFC 3339)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Only export commands run up to the end of this date, or before this
+        /// RFC 3339 time
+        #[arg(long)]
+        until: Option<String>,
+    },
+
+    /// Load commands from a JSON or NDJSON export
+    Import {
+        #[arg(value_name = "FILE")]
+        path: PathBuf,
+    },
+
+    /// Print a summary of the analysis as Markdown or CSV
+    Report {
+        #[arg(value_enum)]
+        format: ReportFormat,
+
+        /// Write the report to this file (defaults to stdout)
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+
+    /// Print totals, top commands and activity as plain text
+    Stats {
+        /// Write the stats to this file (defaults to stdout)
+        #[arg(long)]
+        out: Option<PathBuf>,
+
+        /// Only count commands run on or after this date (YYYY-MM-DD or RFC 3339)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Only count commands run up to the end of this date, or before this
+        /// RFC 3339 time
+        #[arg(long)]
+        until: Option<String>,
+    },
+
+    /// Remove the commands added by the most recent import
+    UndoImport,
+
+    /// Re-run redaction, detection and danger scoring over stored commands, e.g.
+    /// after changing danger rules
+    Reenrich,
+
+    /// Run SQLite's integrity check and list stored commands with malformed JSON
+    CheckDb {
+        /// Reset the malformed JSON columns to empty lists
+        #[arg(long)]
+        repair: bool,
+    },
+
+    /// Delete commands older than DAYS, or than `retention_days` from the config
+    Prune {
+        #[arg(value_name = "DAYS")]
+        days: Option<u32>,
+    },
+
+    /// Write Prometheus textfile-collector metrics
+    Metrics {
+        /// The file to write, e.g. in the node exporter's textfile directory
+        #[arg(long, value_name = "PATH")]
+        out: PathBuf,
+    },
+
+    /// Delete all stored history (after confirming) and re-import from scratch
+    ResetDb,
+
+    /// Print the shell hook that records exit codes, durations, directories and sessions
+    PrintHook {
+        #[arg(value_enum)]
+        shell: HookShell,
+    },
+}
+
+impl Cli {
+    fn load_config(&self) -> Result<Config> {
+        Config::load_or_create(self.config.as_deref(), self.db.as_deref())
+    }
+
+    /// The subcommand, or the one a deprecated flag like `--export` stands for.
+    /// Exits with a usage error when a subcommand comes with another mode's flag.
+    fn mode(&mut self) -> Option<Mode> {
+        match (self.command.take(), self.flag_mode()) {
+            (Some(_), Some(_)) => Cli::command()
+                .error(
+                    clap::error::ErrorKind::ArgumentConflict,
+                    "a subcommand can't be combined with another mode's flag",
+                )
+                .exit(),
+            (command, flag_mode) => command.or(flag_mode),
+        }
+    }
+
+    /// The mode a deprecated flag stands for; the `legacy` group allows one.
+    fn flag_mode(&mut self) -> Option<Mode> {
+        if let Some(shell) = self.print_hook {
+            return Some(Mode::PrintHook { shell });
+        }
+        if let Some(format) = self.export {
+            return Some(Mode::Export {
+                format,
+                anonymize: self.anonymize,
+                out: self.out.take(),
+                since: self.since.take(),
+                until: self.until.take(),
+            });
+        }
+        if let Some(format) = self.report {
+            return Some(Mode::Report {
+                format,
+                out: self.out.take(),
+            });
+        }
+        if self.stats {
+            return Some(Mode::Stats {
+                out: self.out.take(),
+                since: self.since.take(),
+                until: self.until.take(),
+            });
+        }
+        if let Some(path) = self.import.take() {
+            return Some(Mode::Import { path });
+        }
+        if self.undo_import {
+            return Some(Mode::UndoImport);
+        }
+        if self.reenrich {
+            return Some(Mode::Reenrich);
+        }
+        if self.check_db {
+            return Some(Mode::CheckDb {
+                repair: self.repair,
+            });
+        }
+        if let Some(days) = self.prune {
+            return Some(Mode::Prune { days });
+        }
+        if let Some(out) = self.metrics_out.take() {
+            return Some(Mode::Metrics { out });
+        }
+        self.reset_db.then_some(Mode::ResetDb)
+    }
+}
+
+/// Asks before wiping the database. Returns false if the user declined.
+async fn run_reset(config: &Config) -> Result<bool> {
+    print!(
+        "This deletes all stored history in {}. History files will be re-imported. Continue? [y/N] ",
+        config.database_path.display()
+    );
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+        println!("Aborted, nothing was deleted.");
+        return Ok(false);
+    }
+
+    let mut db = Database::new(&config.database_path).await?;
+    db.reset().await?;
+    db.vacuum().await?;
+    println!("Database reset.");
+    Ok(true)
+}
+
+async fn run_import(config: &Config, path: PathBuf) -> Result<()> {
+    let mut db = Database::new(&config.database_path).await?;
+    let result = db.import_json(&path).await?;
+
+    println!(
+        "Imported {} new commands from {}",
+        result.imported,
+        path.display()
+    );
+    if result.failed > 0 {
+        eprintln!("Skipped {} malformed records", result.failed);
+    }
+    Ok(())
+}
+
+async fn run_reenrich(config: &Config) -> Result<()> {
+    let mut db = Database::new(&config.database_path).await?;
+    let updated = db
+        .reenrich(
+            &history::CommandEnricher::from_config(config),
+            &analysis::DangerAnalyzer::from_config(config),
+        )
+        .await?;
+    println!("Updated {} stored commands", updated);
+    Ok(())
+}
+
+async fn run_check_db(config: &Config, repair: bool) -> Result<()> {
+    let mut db = Database::new(&config.database_path).await?;
+    let report = db.check_integrity().await?;
+    if report.is_healthy() {
+        println!(
+            "Checked {} commands, no problems found",
+            report.rows_checked
+        );
+        return Ok(());
+    }
+
+    if report.integrity_errors.is_empty() {
+        println!("SQLite integrity check: ok");
+    } else {
+        println!("SQLite integrity check:");
+        for message in &report.integrity_errors {
+            println!("  {}", message);
+        }
+    }
+    println!(
+        "Checked {} commands, {} with malformed JSON",
+        report.rows_checked,
+        report.bad_json.len()
+    );
+    for row in &report.bad_json {
+        println!("  id {}: {}", row.id, row.columns.join(", "));
+    }
+
+    if repair && !report.bad_json.is_empty() {
+        let repaired = db.repair_json(&report).await?;
+        println!("Reset the malformed columns of {} commands to []", repaired);
+    } else if !report.bad_json.is_empty() {
+        println!("Run `whiskerlog check-db --repair` to reset them to []");
+    }
+
+    if !report.integrity_errors.is_empty() {
+        anyhow::bail!(
+            "{} failed the integrity check",
+            config.database_path.display()
+        );
+    }
+    if !repair && !report.bad_json.is_empty() {
+        anyhow::bail!("{} has malformed JSON", config.database_path.display());
+    }
+    Ok(())
+}
+
+async fn run_undo_import(config: &Config) -> Result<()> {
+    let mut db = Database::new(&config.database_path).await?;
+    match db.undo_last_import().await? {
+        0 => println!("No import to undo."),
+        removed => println!("Removed {} commands from the last import", removed),
+    }
+    Ok(())
+}
+
+async fn run_prune(mut config: Config, days: Option<u32>) -> Result<()> {
+    if days.is_some() {
+        config.retention_days = days;
+    }
+    let Some(cutoff) = config.retention_cutoff() else {
+        anyhow::bail!(
+            "No retention period: pass DAYS to `whiskerlog prune` or set retention_days in the config"
+        );
+    };
+
+    let mut db = Database::new(&config.database_path).await?;
+    let removed = db.prune_older_than(cutoff).await?;
+    if removed > 0 {
+        db.vacuum().await?;
+    }
+    println!(
+        "Removed {} commands from before {}",
+        removed,
+        cutoff.format("%Y-%m-%d")
+    );
+    Ok(())
+}
+
+async fn run_export(
+    config: &Config,
+    format: ExportFormat,
+    out: Option<PathBuf>,
+    since: Option<String>,
+    until: Option<String>,
+    anonymize: bool,
+) -> Result<()> {
+    let since = since.as_deref().map(export::parse_since).transpose()?;
+    let until = until.as_deref().map(export::parse_until).transpose()?;
+
+    let mut db = Database::new(&config.database_path).await?;
+    let mut commands = stats_summary::load_commands(&mut db, since, until).await?;
+    if anonymize {
+        export::anonymize(&mut commands);
+    }
+
+    match out {
+        Some(path) => {
+            let mut writer = io::BufWriter::new(std::fs::File::create(path)?);
+            export::write_commands(&commands, format, &mut writer)
+        }
+        None => export::write_commands(&commands, format, &mut io::stdout().lock()),
+    }
+}
+
+async fn run_report(config: &Config, format: ReportFormat, out: Option<PathBuf>) -> Result<()> {
+    let mut db = Database::new(&config.database_path).await?;
+    let commands = db.get_commands(None).await?;
+    let report = report::Report::build(&commands, config);
+
+    match out {
+        Some(path) => {
+            let mut writer = io::BufWriter::new(std::fs::File::create(path)?);
+            report::write_report(&report, format, &mut writer)
+        }
+        None => report::write_report(&report, format, &mut io::stdout().lock()),
+    }
+}
+
+async fn run_stats(
+    config: &Config,
+    out: Option<PathBuf>,
+    since: Option<String>,
+    until: Option<String>,
+) -> Result<()> {
+    let since = since.as_deref().map(export::parse_since).transpose()?;
+    let until = until.as_deref().map(export::parse_until).transpose()?;
+
+    let mut db = Database::new(&config.database_path).await?;
+    let commands = stats_summary::load_commands(&mut db, since, until).await?;
+    let summary = stats_summary::StatsSummary::build(&commands, config);
+
+    match out {
+        Some(path) => {
+            let mut writer = io::BufWriter::new(std::fs::File::create(path)?);
+            stats_summary::write_summary(&summary, &mut writer)
+        }
+        None => stats_summary::write_summary(&summary, &mut io::stdout().lock()),
+    }
+}
+
+async fn run_metrics(config: &Config, path: PathBuf) -> Result<()> {
+    let mut db = Database::new(&config.database_path).await?;
+    let commands = db.get_commands(None).await?;
+    metrics::write_metrics_file(&metrics::Metrics::build(&commands, config), &path)
+}
 
 fn cleanup_terminal<B: Backend + std::io::Write>(terminal: &mut Terminal<B>) -> Result<()> {
     // Disable raw mode first
@@ -31,6 +498,7 @@ fn cleanup_terminal<B: Backend + std::io::Write>(terminal: &mut Terminal<B>) ->
         crossterm::cursor::MoveTo(0, 0),
         LeaveAlternateScreen,
         DisableMouseCapture,
+        DisableBracketedPaste,
         crossterm::style::ResetColor,
         crossterm::cursor::Show
     )?;
@@ -50,16 +518,59 @@ fn cleanup_terminal<B: Backend + std::io::Write>(terminal: &mut Terminal<B>) ->
 async fn main() -> Result<()> {
     env_logger::init();
 
+    let mut cli = Cli::parse();
+    let config = cli.load_config()?;
+    match cli.mode() {
+        Some(Mode::PrintHook { shell }) => {
+            print!(
+                "{}",
+                hook::hook_script(shell, &hook::hook_log_path(&config))
+            );
+            return Ok(());
+        }
+        Some(Mode::Export {
+            format,
+            anonymize,
+            out,
+            since,
+            until,
+        }) => return run_export(&config, format, out, since, until, anonymize).await,
+        Some(Mode::Report { format, out }) => return run_report(&config, format, out).await,
+        Some(Mode::Stats { out, since, until }) => {
+            return run_stats(&config, out, since, until).await
+        }
+        Some(Mode::Import { path }) => return run_import(&config, path).await,
+        Some(Mode::UndoImport) => return run_undo_import(&config).await,
+        Some(Mode::Reenrich) => return run_reenrich(&config).await,
+        Some(Mode::CheckDb { repair }) => return run_check_db(&config, repair).await,
+        Some(Mode::Prune { days }) => return run_prune(config, days).await,
+        Some(Mode::Metrics { out }) => return run_metrics(&config, out).await,
+        // Carries on into the UI, which imports everything again
+        Some(Mode::ResetDb) if !run_reset(&config).await? => return Ok(()),
+        Some(Mode::ResetDb) | None => {}
+    }
+
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(
+        stdout,
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableBracketedPaste
+    )?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    // Create app and run it
-    let app = App::new().await?;
-    let res = run_app(&mut terminal, app).await;
+    // Create app and run it, after the first-run import if this is one
+    let res = match run_onboarding(&mut terminal, config).await {
+        Ok(Some(config)) => match App::new(config).await {
+            Ok(app) => run_app(&mut terminal, app).await,
+            Err(err) => Err(err),
+        },
+        Ok(None) => Ok(()),
+        Err(err) => Err(err),
+    };
 
     // Restore terminal - ensure cleanup happens even on error
     let cleanup_result = cleanup_terminal(&mut terminal);
@@ -75,25 +586,221 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
-async fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> Result<()> {
+/// Hand the terminal to `viewer` for `tool`'s page, then take it back. Raw mode,
+/// the alternate screen, mouse capture and bracketed paste are undone and
+/// restored around it.
+fn show_docs<B: Backend + std::io::Write>(
+    terminal: &mut Terminal<B>,
+    viewer: DocViewer,
+    tool: &str,
+) -> Result<()> {
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        DisableBracketedPaste,
+        crossterm::cursor::Show
+    )?;
+
+    let status = match docs::pager() {
+        Some(pager) if !viewer.pages_itself() => page(viewer.command(tool), pager),
+        _ => viewer.command(tool).status().and_then(|status| {
+            // With no pager, keep the page on screen until it's been read
+            if !viewer.pages_itself() {
+                println!("\nPress Enter to go back");
+                std::io::stdin().read_line(&mut String::new())?;
+            }
+            Ok(status)
+        }),
+    };
+
+    // Take the terminal back whatever the viewer did
+    enable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableBracketedPaste
+    )?;
+    terminal.clear()?;
+
+    let status = status?;
+    if !status.success() {
+        anyhow::bail!("no page for {} ({})", tool, status);
+    }
+    Ok(())
+}
+
+/// Runs `command` with its output piped into `pager`, returning the
+/// command's own status.
+fn page(
+    mut command: std::process::Command,
+    mut pager: std::process::Command,
+) -> std::io::Result<std::process::ExitStatus> {
+    let mut child = command.stdout(std::process::Stdio::piped()).spawn()?;
+    let output = child.stdout.take().expect("stdout is piped");
+    let paged = pager.stdin(output).status();
+    let status = child.wait()?;
+    paged?;
+    Ok(status)
+}
+
+/// Shows the history files that would be imported and waits for Enter before
+/// importing them, on the first launch with an empty database. Returns the
+/// config to start the app with, or `None` if the user quit.
+async fn run_onboarding<B: Backend>(
+    terminal: &mut Terminal<B>,
+    mut config: Config,
+) -> Result<Option<Config>> {
+    let db = Database::new(&config.database_path).await?;
+    if !onboarding::needs_onboarding(&config, &db).await? {
+        return Ok(Some(config));
+    }
+    drop(db);
+
+    let theme = ui::theme::Theme::from_config(&config.theme);
+    let sources = onboarding::history_sources(&history::HistoryParser::from_config(&config));
+    let mut import: Option<BackgroundImport> = None;
+
+    loop {
+        if let Some(import) = &mut import {
+            import.poll();
+            if matches!(import.finished, Some(Ok(_))) {
+                break;
+            }
+        }
+
+        terminal.draw(|f| ui::onboarding::draw(f, &sources, import.as_ref(), &theme))?;
+
+        if event::poll(config.ui_poll_interval())? {
+            if let Event::Key(key) = event::read()? {
+                let failed = matches!(
+                    import.as_ref().and_then(|import| import.finished.as_ref()),
+                    Some(Err(_))
+                );
+                match key.code {
+                    KeyCode::Char('q' | 'Q') | KeyCode::Esc => return Ok(None),
+                    // Files already read are remembered, so a retry carries on from the failure
+                    KeyCode::Enter if import.is_none() => {}
+                    KeyCode::Char('r' | 'R') if failed => {}
+                    // The UI's own startup import tries the rest again
+                    KeyCode::Char('s' | 'S') if failed => break,
+                    _ => continue,
+                }
+                let files = sources
+                    .iter()
+                    .map(|source| (source.shell, source.path.clone()))
+                    .collect();
+                import = Some(BackgroundImport::start(&config, files).await?);
+            }
+        }
+    }
+
+    config.onboarded = true;
+    if let Err(err) = config.save() {
+        log::warn!("Failed to save config: {}", err);
+    }
+    Ok(Some(config))
+}
+
+async fn run_app<B: Backend + std::io::Write>(
+    terminal: &mut Terminal<B>,
+    mut app: App,
+) -> Result<()> {
     let mut last_analytics_update = std::time::Instant::now();
+    let analytics_interval = app.config.analytics_refresh_interval();
+    let poll_interval = app.config.ui_poll_interval();
 
     loop {
         // Update analytics periodically in background
         let now = std::time::Instant::now();
-        if now.duration_since(last_analytics_update).as_secs() > 60 {
+        if now.duration_since(last_analytics_update) > analytics_interval {
             app.update_analytics_background();
             last_analytics_update = now;
         }
 
+        app.flush_config(false);
+        app.import_live_commands().await;
+        app.finish_reenrich().await;
+        app.refresh_command_page().await;
+
+        if let Some((viewer, tool)) = app.take_doc_request() {
+            if let Err(err) = show_docs(terminal, viewer, &tool) {
+                app.set_status_message(format!("Failed to open {}: {}", viewer.program(), err));
+            }
+        }
+
         terminal.draw(|f| ui::draw(f, &app))?;
 
         // Use timeout to allow periodic updates
-        if let Ok(event) = event::poll(std::time::Duration::from_millis(100)) {
+        if let Ok(event) = event::poll(poll_interval) {
             if event {
-                if let Event::Key(key) = event::read()? {
+                let event = event::read()?;
+                if let Event::Paste(text) = &event {
+                    app.handle_paste(text);
+                    continue;
+                }
+                if let Event::Key(key) = event {
+                    // The command checker takes every key as text until it's closed
+                    if app.danger_check.is_some() {
+                        match key.code {
+                            KeyCode::Esc | KeyCode::Enter => app.close_danger_check(),
+                            KeyCode::Backspace => app.handle_backspace(),
+                            KeyCode::Char(c) => app.handle_char(c),
+                            _ => {}
+                        }
+                        continue;
+                    }
+
+                    if app.tag_input.is_some() {
+                        match key.code {
+                            KeyCode::Enter => app.submit_tag_input().await,
+                            KeyCode::Esc => app.close_tag_input(),
+                            KeyCode::Backspace => app.handle_backspace(),
+                            KeyCode::Char(c) => app.handle_char(c),
+                            _ => {}
+                        }
+                        continue;
+                    }
+
+                    if app.pending_quit {
+                        let quit = match key.code {
+                            KeyCode::Char('y' | 'Y') => app.confirm_quit(true),
+                            KeyCode::Char('n' | 'N') | KeyCode::Esc => app.confirm_quit(false),
+                            _ => false,
+                        };
+                        if quit {
+                            app.flush_config(true);
+                            return Ok(());
+                        }
+                        continue;
+                    }
+
+                    if app.pending_reenrich {
+                        match key.code {
+                            KeyCode::Char('y' | 'Y') => app.confirm_reenrich(true).await,
+                            KeyCode::Char('n' | 'N') | KeyCode::Esc => {
+                                app.confirm_reenrich(false).await
+                            }
+                            _ => {}
+                        }
+                        continue;
+                    }
+
+                    if let Some(c) = app.query_char(&key) {
+                        app.handle_char(c);
+                        continue;
+                    }
+
                     match key.code {
-                        KeyCode::Char('q') | KeyCode::Char('Q') => return Ok(()),
+                        KeyCode::Char('q') | KeyCode::Char('Q') => {
+                            if !app.request_quit() {
+                                continue;
+                            }
+                            app.flush_config(true);
+                            return Ok(());
+                        }
                         KeyCode::Char('/') => app.go_to_search_tab(),
                         KeyCode::Char('?') => app.toggle_help(),
                         KeyCode::Tab => app.next_tab(),
@@ -102,6 +809,13 @@ async fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> Result
                         KeyCode::Down | KeyCode::Char('j') => app.scroll_down(),
                         KeyCode::Left | KeyCode::Char('h') => app.scroll_left(),
                         KeyCode::Right | KeyCode::Char('l') => app.scroll_right(),
+                        KeyCode::Enter
+                            if app.current_tab == Tab::Packages
+                                && app.package_detail_open
+                                && app.package_commands.is_none() =>
+                        {
+                            app.show_package_commands().await
+                        }
                         KeyCode::Enter => app.handle_enter(),
                         KeyCode::Esc => app.handle_escape(),
                         KeyCode::Home => app.scroll_to_top(),
@@ -114,14 +828,50 @@ async fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> Result
                         }
                         KeyCode::Char('0') => app.jump_to_tab(9), // Packages tab
                         KeyCode::Char('-') => app.jump_to_tab(10), // Experiments tab
+                        KeyCode::Char('=') => app.jump_to_tab(11), // Projects tab
                         KeyCode::F(1) => app.handle_function_key(1),
                         KeyCode::F(2) => app.handle_function_key(2),
                         KeyCode::F(3) => app.handle_function_key(3),
                         KeyCode::F(4) => app.handle_function_key(4),
                         KeyCode::F(5) => app.refresh_analytics(), // Manual refresh
-                        KeyCode::Char('r') | KeyCode::Char('R') => {
-                            app.invalidate_analytics_cache();
-                            app.refresh_analytics();
+                        KeyCode::F(12) => app.toggle_about().await,
+                        KeyCode::Char('r')
+                            if app.current_tab == Tab::Search
+                                && key.modifiers.contains(KeyModifiers::CONTROL) =>
+                        {
+                            app.toggle_search_kind()
+                        }
+                        KeyCode::Char('R') if !app.current_tab.binds_shift_r() => {
+                            app.request_reenrich()
+                        }
+                        KeyCode::Char(c @ ('r' | 'R')) => {
+                            if app.current_tab != Tab::Search {
+                                app.invalidate_analytics_cache();
+                                app.refresh_analytics();
+                            }
+                            // Tabs also bind R (Recent, Risk, ...) and Search types it
+                            app.handle_char(c);
+                        }
+                        KeyCode::Char('m' | 'M') if app.detail_visible => {
+                            app.toggle_experiment_mark().await
+                        }
+                        KeyCode::Char('t') if app.detail_visible => app.open_tag_editor(),
+                        KeyCode::Char('#') if app.current_tab == Tab::Commands => {
+                            app.open_tag_filter()
+                        }
+                        // Search types y into the query, so it takes Ctrl+Y there
+                        KeyCode::Char('y')
+                            if (app.current_tab != Tab::Search
+                                || key.modifiers.contains(KeyModifiers::CONTROL))
+                                && app.selected_command().is_some() =>
+                        {
+                            app.yank_selected_command()
+                        }
+                        KeyCode::Char('x' | 'X') if app.current_tab != Tab::Search => {
+                            app.write_report()
+                        }
+                        KeyCode::Char('o' | 'O') if app.current_tab == Tab::Experiments => {
+                            app.request_docs()
                         }
                         KeyCode::Char(c) => app.handle_char(c),
                         KeyCode::Backspace => app.handle_backspace(),
@@ -0,0 +1,160 @@
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::Path;
+
+use crate::analysis::danger::DangerAnalyzer;
+use crate::analysis::package_tracker::PackageTracker;
+use crate::analysis::stats::StatsAnalyzer;
+use crate::config::Config;
+use crate::history::host_aliases::HostAliases;
+use crate::history::Command;
+
+/// Package managers get a label each up to this many, busiest first, so a
+/// history full of obscure tools can't blow up the series count.
+pub const MAX_MANAGER_LABELS: usize = 10;
+
+/// Gauges for the Prometheus node exporter's textfile collector.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Metrics {
+    pub total_commands: usize,
+    pub dangerous_commands: usize,
+    pub unique_hosts: usize,
+    /// Installs per package manager, busiest manager first
+    pub packages_installed: Vec<(String, usize)>,
+    /// Share of commands with a known exit code that exited 0
+    pub success_rate: f32,
+}
+
+impl Metrics {
+    pub fn build(commands: &[Command], config: &Config) -> Self {
+        let mut managers = PackageTracker::new()
+            .analyze_package_usage(commands)
+            .managers_used;
+        managers.sort_by(|a, b| {
+            b.total_operations
+                .cmp(&a.total_operations)
+                .then_with(|| a.manager.cmp(&b.manager))
+        });
+        managers.truncate(MAX_MANAGER_LABELS);
+
+        let aliases = HostAliases::from_config(config);
+
+        Self {
+            total_commands: commands.len(),
+            dangerous_commands: DangerAnalyzer::from_config(config)
+                .analyze_danger_patterns(commands)
+                .total_dangerous,
+            unique_hosts: commands
+                .iter()
+                .map(|cmd| aliases.canonical(&cmd.host_id))
+                .collect::<HashSet<_>>()
+                .len(),
+            packages_installed: managers
+                .into_iter()
+                .map(|manager| (manager.manager, manager.installs))
+                .collect(),
+            success_rate: StatsAnalyzer::from_config(config)
+                .analyze_commands(commands)
+                .success_rate,
+        }
+    }
+}
+
+pub fn write_metrics<W: Write>(metrics: &Metrics, w: &mut W) -> Result<()> {
+    gauge(
+        w,
+        "whiskerlog_total_commands",
+        "Commands stored in the history database.",
+        &[(None, metrics.total_commands)],
+    )?;
+    gauge(
+        w,
+        "whiskerlog_dangerous_commands",
+        "Stored commands flagged as dangerous.",
+        &[(None, metrics.dangerous_commands)],
+    )?;
+    gauge(
+        w,
+        "whiskerlog_unique_hosts",
+        "Hosts commands were recorded on.",
+        &[(None, metrics.unique_hosts)],
+    )?;
+
+    let installs: Vec<(Option<(&str, &str)>, usize)> = metrics
+        .packages_installed
+        .iter()
+        .map(|(manager, installs)| (Some(("manager", manager.as_str())), *installs))
+        .collect();
+    gauge(
+        w,
+        "whiskerlog_packages_installed",
+        "Package installs per package manager, for the busiest managers.",
+        &installs,
+    )?;
+
+    gauge(
+        w,
+        "whiskerlog_success_rate",
+        "Share of commands with a known exit code that succeeded, from 0 to 1.",
+        &[(None, metrics.success_rate)],
+    )?;
+
+    w.flush()?;
+    Ok(())
+}
+
+/// Write the metrics to `path` through a temporary file in the same directory,
+/// so the collector never reads a half-written file.
+pub fn write_metrics_file(metrics: &Metrics, path: &Path) -> Result<()> {
+    let file_name = path
+        .file_name()
+        .with_context(|| format!("{} is not a file path", path.display()))?;
+    let mut temp_name = file_name.to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    let mut writer = std::io::BufWriter::new(
+        std::fs::File::create(&temp_path)
+            .with_context(|| format!("Failed to create {}", temp_path.display()))?,
+    );
+    write_metrics(metrics, &mut writer)?;
+    drop(writer);
+
+    std::fs::rename(&temp_path, path)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(())
+}
+
+/// One gauge with its help and type lines, then a sample per label value.
+fn gauge<W: Write, T: std::fmt::Display>(
+    w: &mut W,
+    name: &str,
+    help: &str,
+    samples: &[(Option<(&str, &str)>, T)],
+) -> Result<()> {
+    writeln!(w, "# HELP {} {}", name, help)?;
+    writeln!(w, "# TYPE {} gauge", name)?;
+    for (label, value) in samples {
+        match label {
+            Some((key, label_value)) => writeln!(
+                w,
+                "{}{{{}=\"{}\"}} {}",
+                name,
+                key,
+                escape_label(label_value),
+                value
+            )?,
+            None => writeln!(w, "{} {}", name, value)?,
+        }
+    }
+    Ok(())
+}
+
+/// Label values escape backslashes, double quotes and line breaks.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
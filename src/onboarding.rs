@@ -0,0 +1,52 @@
+use anyhow::Result;
+use std::path::PathBuf;
+
+use crate::config::Config;
+use crate::db::Database;
+use crate::history::HistoryParser;
+
+/// A history file found on the first run, shown before anything is imported.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistorySource {
+    pub shell: &'static str,
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+/// The history files that exist, in import order, with their sizes.
+pub fn history_sources(parser: &HistoryParser) -> Vec<HistorySource> {
+    parser
+        .history_files()
+        .into_iter()
+        .filter_map(|(shell, path)| {
+            let metadata = std::fs::metadata(&path).ok()?;
+            metadata.is_file().then_some(HistorySource {
+                shell,
+                size: metadata.len(),
+                path,
+            })
+        })
+        .collect()
+}
+
+/// The first run is the first launch with nothing stored. Upgrading users
+/// already have history, so they never see it.
+pub async fn needs_onboarding(config: &Config, db: &Database) -> Result<bool> {
+    Ok(!config.onboarded && db.count_commands().await? == 0)
+}
+
+/// `1.5 MB` style sizes for the file list.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
@@ -0,0 +1,492 @@
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::analysis::danger::{DangerAnalysis, DangerAnalyzer};
+use crate::analysis::heatmap::{ActivityPeriod, HeatmapAnalyzer, TimeRange, ViewMode};
+use crate::analysis::package_tracker::{ManagerStats, PackageTracker};
+use crate::analysis::stats::{CommandFrequency, DurationHistogram, StatsAnalyzer};
+use crate::config::Config;
+use crate::history::Command;
+
+/// Heatmap cells at least this busy relative to the busiest count as peaks.
+const PEAK_THRESHOLD: f32 = 0.5;
+const MAX_PEAKS: usize = 5;
+const MAX_RISKY: usize = 5;
+/// Commands run this many times or fewer are listed as rarely used.
+const RARE_MAX_RUNS: usize = 2;
+const MAX_RARE: usize = 10;
+const MAX_SLOW: usize = 10;
+
+const NO_DATA: &str = "no data";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ReportFormat {
+    Md,
+    Csv,
+}
+
+impl ReportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Md => "md",
+            ReportFormat::Csv => "csv",
+        }
+    }
+}
+
+/// A summary of the history for sharing: the most run commands, package
+/// manager activity, the busiest hours of the last month, what was risky and
+/// what was slow.
+pub struct Report {
+    pub generated: DateTime<Utc>,
+    pub total_commands: usize,
+    pub top_commands: Vec<CommandFrequency>,
+    /// Most operations first, ties broken by name
+    pub managers: Vec<ManagerStats>,
+    pub peaks: Vec<ActivityPeriod>,
+    pub danger: DangerAnalysis,
+    /// The ones that didn't work or were experiments first
+    pub rare_commands: Vec<RareCommand>,
+    /// Longest average duration first, only commands that were timed
+    pub slowest_commands: Vec<CommandFrequency>,
+    pub durations: DurationHistogram,
+}
+
+/// A command that was barely used, for pruning tools tried and abandoned.
+pub struct RareCommand {
+    pub frequency: CommandFrequency,
+    /// "tried once, didn't work", "never worked" or "experiment"
+    pub note: Option<&'static str>,
+}
+
+impl RareCommand {
+    fn new(frequency: CommandFrequency, experiments: &HashSet<&str>) -> Self {
+        let note = if frequency.failures == frequency.count {
+            Some(if frequency.count == 1 {
+                "tried once, didn't work"
+            } else {
+                "never worked"
+            })
+        } else if experiments.contains(frequency.example.as_str()) {
+            Some("experiment")
+        } else {
+            None
+        };
+        Self { frequency, note }
+    }
+}
+
+impl Report {
+    pub fn build(commands: &[Command], config: &Config) -> Self {
+        let tally = StatsAnalyzer::from_config(config).tally(commands);
+        let stats = tally.command_stats();
+
+        let experiments: HashSet<&str> = commands
+            .iter()
+            .filter(|cmd| cmd.is_experiment)
+            .map(|cmd| cmd.command.as_str())
+            .collect();
+        let mut rare_commands: Vec<RareCommand> = tally
+            .rarely_used(RARE_MAX_RUNS)
+            .into_iter()
+            .map(|frequency| RareCommand::new(frequency, &experiments))
+            .collect();
+        rare_commands.sort_by_key(|rare| rare.note.is_none());
+        rare_commands.truncate(MAX_RARE);
+
+        let mut managers = PackageTracker::new()
+            .analyze_package_usage(commands)
+            .managers_used;
+        managers.sort_by(|a, b| {
+            b.total_operations
+                .cmp(&a.total_operations)
+                .then_with(|| a.manager.cmp(&b.manager))
+        });
+
+        let heatmap_analyzer = HeatmapAnalyzer::from_config(config);
+        let heatmap = heatmap_analyzer.generate_heatmap(commands, TimeRange::Month, ViewMode::All);
+        let mut peaks = if heatmap.total_commands == 0 {
+            Vec::new()
+        } else {
+            heatmap_analyzer.get_peak_activity_periods(&heatmap, PEAK_THRESHOLD)
+        };
+        peaks.truncate(MAX_PEAKS);
+
+        Self {
+            generated: Utc::now(),
+            total_commands: stats.total_commands,
+            top_commands: stats.top_commands,
+            managers,
+            peaks,
+            danger: DangerAnalyzer::from_config(config).analyze_danger_patterns(commands),
+            rare_commands,
+            slowest_commands: tally.slowest_commands(MAX_SLOW),
+            durations: tally.duration_histogram(),
+        }
+    }
+
+    /// Danger categories with their counts, most common first.
+    fn danger_categories(&self) -> Vec<(&str, usize)> {
+        let mut categories: Vec<(&str, usize)> = self
+            .danger
+            .danger_by_category
+            .iter()
+            .map(|(category, count)| (category.as_str(), *count))
+            .collect();
+        categories.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        categories
+    }
+}
+
+/// `whiskerlog-report-<date>.<ext>` inside `dir`.
+pub fn report_path(dir: &Path, date: NaiveDate, format: ReportFormat) -> PathBuf {
+    dir.join(format!(
+        "whiskerlog-report-{}.{}",
+        date.format("%Y-%m-%d"),
+        format.extension()
+    ))
+}
+
+pub fn write_report<W: Write>(report: &Report, format: ReportFormat, writer: &mut W) -> Result<()> {
+    match format {
+        ReportFormat::Md => write_markdown(report, writer)?,
+        ReportFormat::Csv => write_csv(report, writer)?,
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_markdown<W: Write>(report: &Report, w: &mut W) -> Result<()> {
+    writeln!(
+        w,
+        "# Whiskerlog report {}",
+        report.generated.format("%Y-%m-%d")
+    )?;
+    writeln!(w)?;
+    writeln!(
+        w,
+        "{} commands recorded, generated {}.",
+        report.total_commands,
+        report.generated.format("%Y-%m-%d %H:%M UTC")
+    )?;
+
+    markdown_table(
+        w,
+        "## Top commands",
+        &["Command", "Runs", "Share"],
+        report.top_commands.iter().map(|cmd| {
+            vec![
+                markdown_code(&cmd.command),
+                cmd.count.to_string(),
+                format!("{:.1}%", cmd.percentage),
+            ]
+        }),
+    )?;
+
+    markdown_table(
+        w,
+        "## Package managers",
+        &["Manager", "Operations", "Installs", "Removes", "Updates"],
+        report.managers.iter().map(|manager| {
+            vec![
+                markdown_escape(&manager.manager),
+                manager.total_operations.to_string(),
+                manager.installs.to_string(),
+                manager.removes.to_string(),
+                manager.updates.to_string(),
+            ]
+        }),
+    )?;
+
+    markdown_table(
+        w,
+        "## Peak activity (last 30 days)",
+        &["When", "Commands", "Activity"],
+        report.peaks.iter().map(|peak| {
+            vec![
+                peak_label(peak),
+                peak.command_count.to_string(),
+                format!("{:.0}%", peak.activity_level * 100.0),
+            ]
+        }),
+    )?;
+
+    writeln!(w)?;
+    writeln!(w, "## Dangerous commands")?;
+    writeln!(w)?;
+    writeln!(
+        w,
+        "{} of {} commands were flagged as dangerous.",
+        report.danger.total_dangerous, report.total_commands
+    )?;
+    markdown_table(
+        w,
+        "### Danger categories",
+        &["Reason", "Commands"],
+        report
+            .danger_categories()
+            .into_iter()
+            .map(|(category, count)| vec![markdown_escape(category), count.to_string()]),
+    )?;
+    markdown_table(
+        w,
+        "### Riskiest commands",
+        &["Command", "Runs", "Score"],
+        report
+            .danger
+            .top_risky_commands
+            .iter()
+            .take(MAX_RISKY)
+            .map(|risky| {
+                vec![
+                    markdown_code(&risky.command),
+                    risky.count.to_string(),
+                    format!("{:.2}", risky.max_danger_score),
+                ]
+            }),
+    )?;
+
+    markdown_table(
+        w,
+        "## Rarely used commands",
+        &["Command", "Runs", "Last used", "Note"],
+        report.rare_commands.iter().map(|rare| {
+            vec![
+                markdown_code(&rare.frequency.command),
+                rare.frequency.count.to_string(),
+                rare.frequency.last_used.format("%Y-%m-%d").to_string(),
+                rare.note.unwrap_or_default().to_string(),
+            ]
+        }),
+    )?;
+
+    markdown_table(
+        w,
+        "## Slowest commands",
+        &["Command", "Runs", "Average"],
+        report.slowest_commands.iter().map(|cmd| {
+            vec![
+                markdown_code(&cmd.command),
+                cmd.count.to_string(),
+                cmd.average_duration.map(duration_label).unwrap_or_default(),
+            ]
+        }),
+    )?;
+    markdown_table(
+        w,
+        "### Durations",
+        &["Took", "Runs"],
+        duration_buckets(&report.durations)
+            .map(|(label, runs)| vec![label.to_string(), runs.to_string()]),
+    )?;
+
+    Ok(())
+}
+
+/// The histogram's buckets, or none when nothing was timed.
+fn duration_buckets(durations: &DurationHistogram) -> impl Iterator<Item = (&'static str, usize)> {
+    let timed = durations.total() > 0;
+    durations.buckets().into_iter().filter(move |_| timed)
+}
+
+/// `850ms` under a second, `12.3s` from there.
+fn duration_label(ms: f64) -> String {
+    if ms < 1000.0 {
+        format!("{:.0}ms", ms)
+    } else {
+        format!("{:.1}s", ms / 1000.0)
+    }
+}
+
+/// `heading` followed by a table, with a single "no data" row when `rows` is empty.
+fn markdown_table<W: Write>(
+    w: &mut W,
+    heading: &str,
+    headers: &[&str],
+    rows: impl Iterator<Item = Vec<String>>,
+) -> Result<()> {
+    writeln!(w)?;
+    writeln!(w, "{}", heading)?;
+    writeln!(w)?;
+    writeln!(w, "| {} |", headers.join(" | "))?;
+    writeln!(w, "|{}", "---|".repeat(headers.len()))?;
+
+    let mut empty = true;
+    for row in rows {
+        empty = false;
+        writeln!(w, "| {} |", row.join(" | "))?;
+    }
+    if empty {
+        let mut row = vec![String::new(); headers.len()];
+        row[0] = format!("_{}_", NO_DATA);
+        writeln!(w, "| {} |", row.join(" | "))?;
+    }
+    Ok(())
+}
+
+/// Table cells can't hold pipes or line breaks.
+fn markdown_escape(text: &str) -> String {
+    text.replace('|', "\\|").replace(['\n', '\r'], " ")
+}
+
+/// A command as inline code, using a longer fence when it contains backticks.
+fn markdown_code(command: &str) -> String {
+    let command = markdown_escape(command);
+    if command.contains('`') {
+        format!("`` {} ``", command)
+    } else {
+        format!("`{}`", command)
+    }
+}
+
+fn peak_label(peak: &ActivityPeriod) -> String {
+    format!("{} {:02}:00", peak.day_of_week, peak.hour)
+}
+
+/// One table for every section: `section,item,count,detail`. A section with
+/// nothing in it gets a single "no data" row.
+fn write_csv<W: Write>(report: &Report, w: &mut W) -> Result<()> {
+    writeln!(w, "section,item,count,detail")?;
+
+    csv_section(
+        w,
+        "top_commands",
+        report.top_commands.iter().map(|cmd| {
+            (
+                cmd.command.clone(),
+                cmd.count,
+                format!("{:.1}%", cmd.percentage),
+            )
+        }),
+    )?;
+    csv_section(
+        w,
+        "package_managers",
+        report.managers.iter().map(|manager| {
+            (
+                manager.manager.clone(),
+                manager.total_operations,
+                format!(
+                    "{} installs; {} removes; {} updates",
+                    manager.installs, manager.removes, manager.updates
+                ),
+            )
+        }),
+    )?;
+    csv_section(
+        w,
+        "peak_activity",
+        report.peaks.iter().map(|peak| {
+            (
+                peak_label(peak),
+                peak.command_count,
+                format!("{:.0}%", peak.activity_level * 100.0),
+            )
+        }),
+    )?;
+    csv_row(
+        w,
+        "dangerous",
+        "total",
+        report.danger.total_dangerous,
+        &format!("of {} commands", report.total_commands),
+    )?;
+    csv_section(
+        w,
+        "danger_categories",
+        report
+            .danger_categories()
+            .into_iter()
+            .map(|(category, count)| (category.to_string(), count, String::new())),
+    )?;
+    csv_section(
+        w,
+        "risky_commands",
+        report
+            .danger
+            .top_risky_commands
+            .iter()
+            .take(MAX_RISKY)
+            .map(|risky| {
+                (
+                    risky.command.clone(),
+                    risky.count,
+                    format!("score {:.2}", risky.max_danger_score),
+                )
+            }),
+    )?;
+    csv_section(
+        w,
+        "rare_commands",
+        report.rare_commands.iter().map(|rare| {
+            let last_used = rare.frequency.last_used.format("last used %Y-%m-%d");
+            (
+                rare.frequency.command.clone(),
+                rare.frequency.count,
+                match rare.note {
+                    Some(note) => format!("{}; {}", last_used, note),
+                    None => last_used.to_string(),
+                },
+            )
+        }),
+    )?;
+    csv_section(
+        w,
+        "slow_commands",
+        report.slowest_commands.iter().map(|cmd| {
+            (
+                cmd.command.clone(),
+                cmd.count,
+                cmd.average_duration
+                    .map(|average| format!("average {}", duration_label(average)))
+                    .unwrap_or_default(),
+            )
+        }),
+    )?;
+    csv_section(
+        w,
+        "durations",
+        duration_buckets(&report.durations)
+            .map(|(label, runs)| (label.to_string(), runs, String::new())),
+    )?;
+
+    Ok(())
+}
+
+fn csv_section<W: Write>(
+    w: &mut W,
+    section: &str,
+    rows: impl Iterator<Item = (String, usize, String)>,
+) -> Result<()> {
+    let mut empty = true;
+    for (item, count, detail) in rows {
+        empty = false;
+        csv_row(w, section, &item, count, &detail)?;
+    }
+    if empty {
+        csv_row(w, section, NO_DATA, 0, "")?;
+    }
+    Ok(())
+}
+
+fn csv_row<W: Write>(
+    w: &mut W,
+    section: &str,
+    item: &str,
+    count: usize,
+    detail: &str,
+) -> Result<()> {
+    writeln!(
+        w,
+        "{},{},{},{}",
+        section,
+        crate::export::csv_escape(item),
+        count,
+        crate::export::csv_escape(detail)
+    )?;
+    Ok(())
+}
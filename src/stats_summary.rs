@@ -0,0 +1,118 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc, Weekday};
+use std::io::Write;
+
+use crate::analysis::stats::{CommandFrequency, StatsAnalyzer};
+use crate::config::Config;
+use crate::db::Database;
+use crate::history::Command;
+
+const TOP_COMMANDS: usize = 10;
+
+/// The plain-text counterpart of the Summary tab, for scripts and cron.
+#[derive(Debug, Clone)]
+pub struct StatsSummary {
+    pub total_commands: usize,
+    /// Oldest and newest command
+    pub span: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    pub top_commands: Vec<CommandFrequency>,
+    /// Share of commands with a known exit code that exited 0
+    pub success_rate: f32,
+    /// In UTC
+    pub most_active_hour: u32,
+    pub most_active_day: Weekday,
+    pub dangerous_commands: usize,
+}
+
+impl StatsSummary {
+    pub fn build(commands: &[Command], config: &Config) -> Self {
+        let stats = StatsAnalyzer::from_config(config).analyze_commands(commands);
+        let first = commands.iter().map(|cmd| cmd.timestamp).min();
+        let last = commands.iter().map(|cmd| cmd.timestamp).max();
+
+        Self {
+            total_commands: stats.total_commands,
+            span: first.zip(last),
+            top_commands: stats.top_commands.into_iter().take(TOP_COMMANDS).collect(),
+            success_rate: stats.success_rate,
+            most_active_hour: stats.most_active_hour,
+            most_active_day: stats.most_active_day,
+            dangerous_commands: commands.iter().filter(|cmd| cmd.is_dangerous).count(),
+        }
+    }
+}
+
+/// Stored commands run at or after `since` and before `until`, oldest first.
+pub async fn load_commands(
+    db: &mut Database,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+) -> Result<Vec<Command>> {
+    let mut commands = if since.is_none() && until.is_none() {
+        db.get_commands(None).await?
+    } else {
+        db.get_commands_between(
+            since.unwrap_or(DateTime::<Utc>::MIN_UTC),
+            until.unwrap_or(DateTime::<Utc>::MAX_UTC),
+        )
+        .await?
+    };
+    commands.reverse();
+    Ok(commands)
+}
+
+pub fn write_summary<W: Write>(summary: &StatsSummary, w: &mut W) -> Result<()> {
+    writeln!(w, "Commands:     {}", summary.total_commands)?;
+    match summary.span {
+        Some((first, last)) => writeln!(
+            w,
+            "Span:         {} to {} ({} days)",
+            first.format("%Y-%m-%d"),
+            last.format("%Y-%m-%d"),
+            (last.date_naive() - first.date_naive()).num_days() + 1
+        )?,
+        None => writeln!(w, "Span:         no commands")?,
+    }
+
+    if summary.total_commands > 0 {
+        writeln!(w, "Success rate: {:.1}%", summary.success_rate * 100.0)?;
+        writeln!(
+            w,
+            "Most active:  {:02}:00 UTC, {}",
+            summary.most_active_hour,
+            weekday_name(summary.most_active_day)
+        )?;
+    }
+    writeln!(w, "Dangerous:    {}", summary.dangerous_commands)?;
+
+    if !summary.top_commands.is_empty() {
+        writeln!(w)?;
+        writeln!(w, "Top commands:")?;
+        let count_width = summary.top_commands[0].count.to_string().len();
+        for (rank, freq) in summary.top_commands.iter().enumerate() {
+            writeln!(
+                w,
+                "{:>4}. {:>width$}  {}",
+                rank + 1,
+                freq.count,
+                freq.command,
+                width = count_width
+            )?;
+        }
+    }
+
+    w.flush()?;
+    Ok(())
+}
+
+fn weekday_name(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "Monday",
+        Weekday::Tue => "Tuesday",
+        Weekday::Wed => "Wednesday",
+        Weekday::Thu => "Thursday",
+        Weekday::Fri => "Friday",
+        Weekday::Sat => "Saturday",
+        Weekday::Sun => "Sunday",
+    }
+}
@@ -5,18 +5,78 @@ use ratatui::{
     widgets::{Block, Borders, Gauge, List, ListItem, Paragraph},
     Frame,
 };
-use std::sync::{Mutex, OnceLock};
-use std::time::{Duration, Instant};
 
-use crate::analysis::alias_suggest::{AliasAnalysis, AliasSuggester};
+use crate::analysis::alias_suggest::{AliasAnalysis, AliasSuggester, AliasSuggestion};
 use crate::app::App;
 use crate::ui::theme::{Icons, Theme};
 
-// Cache for alias analysis to prevent flickering
-static ALIAS_CACHE: OnceLock<Mutex<(AliasAnalysis, Instant)>> = OnceLock::new();
+#[derive(Debug, Clone, PartialEq)]
+pub enum AliasSort {
+    /// The suggester's own ranking (frequency × savings × complexity)
+    Impact,
+    Savings,
+    Frequency,
+    Length,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AliasFilter {
+    All,
+    Git,
+    Docker,
+}
+
+impl AliasFilter {
+    fn matches(&self, command: &str) -> bool {
+        let tool = match self {
+            AliasFilter::All => return true,
+            AliasFilter::Git => "git",
+            AliasFilter::Docker => "docker",
+        };
+        command
+            .split_whitespace()
+            .next()
+            .is_some_and(|first| first == tool || first.starts_with(&format!("{}-", tool)))
+    }
+}
+
+/// Suggestions shown in the Aliases tab under `filter`, ordered by `sort`.
+pub fn alias_rows(
+    suggestions: &[AliasSuggestion],
+    filter: &AliasFilter,
+    sort: &AliasSort,
+) -> Vec<AliasSuggestion> {
+    let mut rows: Vec<AliasSuggestion> = suggestions
+        .iter()
+        .filter(|suggestion| filter.matches(&suggestion.command))
+        .cloned()
+        .collect();
+
+    match sort {
+        AliasSort::Impact => {}
+        AliasSort::Savings => rows.sort_by(|a, b| {
+            b.total_time_saved
+                .cmp(&a.total_time_saved)
+                .then_with(|| a.command.cmp(&b.command))
+        }),
+        AliasSort::Frequency => rows.sort_by(|a, b| {
+            b.frequency
+                .cmp(&a.frequency)
+                .then_with(|| a.command.cmp(&b.command))
+        }),
+        AliasSort::Length => rows.sort_by(|a, b| {
+            b.command
+                .len()
+                .cmp(&a.command.len())
+                .then_with(|| a.command.cmp(&b.command))
+        }),
+    }
+
+    rows
+}
 
 pub fn draw(f: &mut Frame, app: &App, area: Rect) {
-    let theme = Theme::default();
+    let theme = &app.theme;
 
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -24,43 +84,23 @@ pub fn draw(f: &mut Frame, app: &App, area: Rect) {
             [
                 Constraint::Length(5), // Enhanced header with metrics
                 Constraint::Min(0),    // Main content
-                Constraint::Length(4), // Enhanced footer with controls
+                Constraint::Length(5), // Enhanced footer with controls
             ]
             .as_ref(),
         )
         .split(area);
 
-    // Get cached or fresh analysis
-    let analysis = get_cached_analysis(app);
+    let analysis = &app.analysis().aliases;
+    let rows = alias_rows(&analysis.suggestions, &app.alias_filter, &app.alias_sort);
 
     // Enhanced header with alias metrics
-    draw_enhanced_header_cached(f, &analysis, chunks[0], &theme);
+    draw_enhanced_header_cached(f, analysis, chunks[0], theme);
 
     // Main content with comprehensive alias analysis
-    draw_enhanced_content_cached(f, &analysis, chunks[1], &theme);
+    draw_enhanced_content_cached(f, analysis, &rows, app.selected_index, chunks[1], theme);
 
     // Enhanced footer with controls and export options
-    draw_enhanced_footer(f, chunks[2], &theme);
-}
-
-fn get_cached_analysis(app: &App) -> AliasAnalysis {
-    let cache = ALIAS_CACHE.get_or_init(|| {
-        let suggester = AliasSuggester::new();
-        let analysis = suggester.analyze_alias_opportunities(&app.commands);
-        Mutex::new((analysis, Instant::now()))
-    });
-
-    let mut cache_guard = cache.lock().unwrap();
-    let (cached_analysis, last_update) = &mut *cache_guard;
-
-    // Update cache every 5 seconds to prevent excessive recalculation
-    if last_update.elapsed() > Duration::from_secs(5) {
-        let suggester = AliasSuggester::new();
-        *cached_analysis = suggester.analyze_alias_opportunities(&app.commands);
-        *last_update = Instant::now();
-    }
-
-    cached_analysis.clone()
+    draw_enhanced_footer(f, app, chunks[2], theme);
 }
 
 fn draw_enhanced_header_cached(f: &mut Frame, analysis: &AliasAnalysis, area: Rect, theme: &Theme) {
@@ -148,6 +188,8 @@ fn draw_metric_card(
 fn draw_enhanced_content_cached(
     f: &mut Frame,
     analysis: &AliasAnalysis,
+    rows: &[AliasSuggestion],
+    selected: usize,
     area: Rect,
     theme: &Theme,
 ) {
@@ -160,7 +202,7 @@ fn draw_enhanced_content_cached(
         .split(area);
 
     // Left panel: Enhanced alias suggestions
-    draw_alias_suggestions(f, analysis, chunks[0], theme);
+    draw_alias_suggestions(f, rows, selected, chunks[0], theme);
 
     // Right panel: Existing aliases and efficiency analysis
     let suggester = AliasSuggester::new();
@@ -169,13 +211,14 @@ fn draw_enhanced_content_cached(
 
 fn draw_alias_suggestions(
     f: &mut Frame,
-    analysis: &crate::analysis::alias_suggest::AliasAnalysis,
+    suggestions: &[AliasSuggestion],
+    selected: usize,
     area: Rect,
     theme: &Theme,
 ) {
     let mut items = Vec::new();
 
-    if analysis.suggestions.is_empty() {
+    if suggestions.is_empty() {
         items.push(ListItem::new(vec![
             Line::from(""),
             Line::from(vec![
@@ -202,7 +245,7 @@ fn draw_alias_suggestions(
         ]));
     } else {
         // Take only the first 10 suggestions to prevent excessive rendering
-        for (i, suggestion) in analysis.suggestions.iter().enumerate().take(10) {
+        for (i, suggestion) in suggestions.iter().enumerate().take(10) {
             let priority_icon = if suggestion.total_time_saved > 100 {
                 ("🔥", theme.style_danger())
             } else if suggestion.total_time_saved > 50 {
@@ -220,7 +263,7 @@ fn draw_alias_suggestions(
                 suggestion.command.clone()
             };
 
-            items.push(ListItem::new(vec![
+            let item = ListItem::new(vec![
                 Line::from(vec![
                     Span::styled(format!("{:2}. ", i + 1), theme.style_text_dim()),
                     Span::styled(priority_icon.0, priority_icon.1),
@@ -249,7 +292,13 @@ fn draw_alias_suggestions(
                         theme.style_accent(),
                     ),
                 ]),
-            ]));
+            ]);
+
+            items.push(if i == selected {
+                item.style(theme.style_selected())
+            } else {
+                item
+            });
         }
     }
 
@@ -259,10 +308,7 @@ fn draw_alias_suggestions(
                 .title(Line::from(vec![
                     Span::styled(format!("{} ", Icons::ALIASES), theme.style_accent()),
                     Span::styled("Smart Alias Suggestions", theme.style_title()),
-                    Span::styled(
-                        format!(" ({})", analysis.suggestions.len()),
-                        theme.style_text_dim(),
-                    ),
+                    Span::styled(format!(" ({})", suggestions.len()), theme.style_text_dim()),
                 ]))
                 .borders(Borders::ALL)
                 .border_style(theme.style_border()),
@@ -404,8 +450,16 @@ fn draw_existing_aliases(
     f.render_widget(aliases_list, area);
 }
 
-fn draw_enhanced_footer(f: &mut Frame, area: Rect, theme: &Theme) {
-    let footer_text = vec![
+fn draw_enhanced_footer(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    let key_style = |active: bool| {
+        if active {
+            theme.style_selected().add_modifier(Modifier::BOLD)
+        } else {
+            theme.style_primary()
+        }
+    };
+
+    let mut footer_text = vec![
         Line::from(vec![
             Span::styled("Navigation: ", theme.style_accent()),
             Span::styled("↑↓", theme.style_primary()),
@@ -415,6 +469,22 @@ fn draw_enhanced_footer(f: &mut Frame, area: Rect, theme: &Theme) {
             Span::styled("R", theme.style_primary()),
             Span::styled(" Refresh", theme.style_text()),
         ]),
+        Line::from(vec![
+            Span::styled("Sort: ", theme.style_accent()),
+            Span::styled("S", key_style(app.alias_sort == AliasSort::Savings)),
+            Span::styled("avings ", theme.style_text()),
+            Span::styled("U", key_style(app.alias_sort == AliasSort::Frequency)),
+            Span::styled("ses ", theme.style_text()),
+            Span::styled("L", key_style(app.alias_sort == AliasSort::Length)),
+            Span::styled("ength  ", theme.style_text()),
+            Span::styled("Filter: ", theme.style_accent()),
+            Span::styled("G", key_style(app.alias_filter == AliasFilter::Git)),
+            Span::styled("it ", theme.style_text()),
+            Span::styled("D", key_style(app.alias_filter == AliasFilter::Docker)),
+            Span::styled("ocker ", theme.style_text()),
+            Span::styled("A", key_style(app.alias_filter == AliasFilter::All)),
+            Span::styled("ll", theme.style_text()),
+        ]),
         Line::from(vec![
             Span::styled("Export: ", theme.style_accent()),
             Span::styled("B", theme.style_primary()),
@@ -428,6 +498,14 @@ fn draw_enhanced_footer(f: &mut Frame, area: Rect, theme: &Theme) {
         ]),
     ];
 
+    // A recent copy/export result replaces the navigation hints
+    if let Some(status) = app.status_message() {
+        footer_text[0] = Line::from(vec![
+            Span::styled(format!("{} ", Icons::INFO), theme.style_info()),
+            Span::styled(status.to_string(), theme.style_success()),
+        ]);
+    }
+
     let footer = Paragraph::new(footer_text)
         .block(
             Block::default()
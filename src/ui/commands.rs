@@ -9,7 +9,7 @@ use crate::app::App;
 use crate::ui::theme::{get_host_icon, Icons, Theme};
 
 pub fn draw(f: &mut Frame, app: &App, area: Rect) {
-    let theme = Theme::default();
+    let theme = &app.theme;
 
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -17,10 +17,10 @@ pub fn draw(f: &mut Frame, app: &App, area: Rect) {
         .split(area);
 
     // Header with sorting options and filters
-    draw_header(f, chunks[0], &theme);
+    draw_header(f, chunks[0], theme);
 
     // Commands list with enhanced styling
-    draw_commands_list(f, app, chunks[1], &theme);
+    draw_commands_list(f, app, chunks[1], theme);
 }
 
 fn draw_header(f: &mut Frame, area: Rect, theme: &Theme) {
@@ -95,11 +95,9 @@ fn draw_header(f: &mut Frame, area: Rect, theme: &Theme) {
 }
 
 fn draw_commands_list(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
-    let visible_commands = app
-        .get_filtered_commands()
-        .iter()
-        .skip(app.scroll_offset)
-        .take(area.height as usize - 2); // Account for borders
+    let rows = area.height.saturating_sub(2) as usize; // Account for borders
+    app.set_viewport_height(rows);
+    let visible_commands = app.visible_commands(app.scroll_offset, rows).iter();
 
     let command_items: Vec<ListItem> = visible_commands
         .enumerate()
@@ -183,13 +181,17 @@ fn draw_commands_list(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
                 Span::styled(format!("[{}]", duration_str), theme.style_text_dim()),
             ];
 
+            if let Some(count) = app.command_count(cmd) {
+                line_spans.push(Span::styled(format!(" ×{}", count), theme.style_accent()));
+            }
+
             line_spans.extend(indicators);
 
             ListItem::new(Line::from(line_spans))
         })
         .collect();
 
-    let total_commands = app.get_filtered_commands().len();
+    let total_commands = app.command_window.total;
     let showing_start = app.scroll_offset + 1;
     let showing_end = (app.scroll_offset + command_items.len()).min(total_commands);
 
@@ -198,11 +200,34 @@ fn draw_commands_list(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
             Block::default()
                 .title(Line::from(vec![
                     Span::styled(format!("{} ", Icons::COMMANDS), theme.style_accent()),
-                    Span::styled("All Commands", theme.style_title()),
+                    Span::styled(
+                        match &app.host_filter {
+                            Some(host) => format!("Commands on {}", host),
+                            None => "All Commands".to_string(),
+                        },
+                        theme.style_title(),
+                    ),
+                    Span::styled(
+                        app.tag_filter
+                            .as_ref()
+                            .map(|tag| format!(" #{}", tag))
+                            .unwrap_or_default(),
+                        theme.style_accent(),
+                    ),
                     Span::styled(
                         format!(" ({}-{} of {})", showing_start, showing_end, total_commands),
                         theme.style_text_dim(),
                     ),
+                    Span::styled(
+                        if app.tag_filter.is_some() {
+                            " Esc: all tags"
+                        } else if app.host_filter.is_some() {
+                            " Esc: all hosts"
+                        } else {
+                            " #: filter by tag"
+                        },
+                        theme.style_text_dim(),
+                    ),
                 ]))
                 .borders(Borders::ALL)
                 .border_style(theme.style_border()),
@@ -1,12 +1,13 @@
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, Paragraph},
+    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Wrap},
     Frame,
 };
 
 use crate::app::App;
+use crate::ui::theme::{Icons, Theme};
 
 pub fn draw(f: &mut Frame, app: &App, area: Rect) {
     let chunks = Layout::default()
@@ -22,6 +23,8 @@ pub fn draw(f: &mut Frame, app: &App, area: Rect) {
 }
 
 fn draw_risk_summary(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+
     let dangerous_count = app.stats.dangerous_commands;
     let total_count = app.stats.total_commands;
     let risk_percentage = if total_count > 0 {
@@ -31,15 +34,15 @@ fn draw_risk_summary(f: &mut Frame, app: &App, area: Rect) {
     };
 
     let risk_level = match risk_percentage {
-        x if x > 30.0 => ("🔴 HIGH RISK", Color::Red),
-        x if x > 10.0 => ("🟡 MEDIUM RISK", Color::Yellow),
-        x if x > 0.0 => ("🟢 LOW RISK", Color::Green),
-        _ => ("✅ NO RISK", Color::Green),
+        x if x > 30.0 => ("🔴 HIGH RISK", theme.danger),
+        x if x > 10.0 => ("🟡 MEDIUM RISK", theme.warning),
+        x if x > 0.0 => ("🟢 LOW RISK", theme.success),
+        _ => ("✅ NO RISK", theme.success),
     };
 
     let summary_text = vec![
         Line::from(vec![
-            Span::styled("Risk Assessment: ", Style::default().fg(Color::Cyan)),
+            Span::styled("Risk Assessment: ", theme.style_info()),
             Span::styled(
                 risk_level.0,
                 Style::default()
@@ -48,20 +51,21 @@ fn draw_risk_summary(f: &mut Frame, app: &App, area: Rect) {
             ),
         ]),
         Line::from(vec![
-            Span::styled("Dangerous Commands: ", Style::default().fg(Color::Cyan)),
+            Span::styled("Dangerous Commands: ", theme.style_info()),
             Span::styled(
                 format!(
                     "{} / {} ({:.1}%)",
                     dangerous_count, total_count, risk_percentage
                 ),
-                Style::default()
-                    .fg(Color::White)
-                    .add_modifier(Modifier::BOLD),
+                theme.style_value(),
             ),
         ]),
         Line::from(vec![
-            Span::styled("Tip: ", Style::default().fg(Color::Yellow)),
-            Span::raw("Review commands below and consider safer alternatives"),
+            Span::styled("Tip: ", theme.style_warning()),
+            Span::styled("Enter", theme.style_primary()),
+            Span::raw(" shows safer alternatives, "),
+            Span::styled("C", theme.style_primary()),
+            Span::raw(" checks a command before you run it"),
         ]),
     ];
 
@@ -71,29 +75,30 @@ fn draw_risk_summary(f: &mut Frame, app: &App, area: Rect) {
                 .title("Security Overview")
                 .borders(Borders::ALL),
         )
-        .style(Style::default().fg(Color::White));
+        .style(theme.style_text());
 
     f.render_widget(summary, area);
 }
 
 fn draw_dangerous_commands(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+
     let dangerous_commands: Vec<_> = app.commands.iter().filter(|cmd| cmd.is_dangerous).collect();
 
+    let rows = area.height.saturating_sub(2) as usize;
+    app.set_viewport_height(rows);
     let command_items: Vec<ListItem> = dangerous_commands
         .iter()
         .skip(app.scroll_offset)
-        .take(area.height as usize - 2)
+        .take(rows)
         .enumerate()
         .map(|(i, cmd)| {
             let is_selected = app.scroll_offset + i == app.selected_index;
 
             let style = if is_selected {
-                Style::default()
-                    .bg(Color::Red)
-                    .fg(Color::White)
-                    .add_modifier(Modifier::BOLD)
+                theme.style_selected()
             } else {
-                Style::default().fg(Color::Red)
+                theme.style_danger()
             };
 
             let time_str = cmd.timestamp.format("%Y-%m-%d %H:%M:%S").to_string();
@@ -112,16 +117,13 @@ fn draw_dangerous_commands(f: &mut Frame, app: &App, area: Rect) {
             };
 
             ListItem::new(Line::from(vec![
-                Span::styled("⚠️ ", Style::default().fg(Color::Red)),
-                Span::styled(time_str, Style::default().fg(Color::Gray)),
+                Span::styled("⚠️ ", theme.style_danger()),
+                Span::styled(time_str, theme.style_text_dim()),
                 Span::raw(" "),
-                Span::styled(
-                    format!("[{}] ", risk_score),
-                    Style::default().fg(Color::Yellow),
-                ),
+                Span::styled(format!("[{}] ", risk_score), theme.style_warning()),
                 Span::styled(cmd.command.clone(), style),
-                Span::styled(host_indicator, Style::default().fg(Color::Blue)),
-                Span::styled(reasons, Style::default().fg(Color::Gray)),
+                Span::styled(host_indicator, theme.style_secondary()),
+                Span::styled(reasons, theme.style_text_dim()),
             ]))
         })
         .collect();
@@ -135,7 +137,71 @@ fn draw_dangerous_commands(f: &mut Frame, app: &App, area: Rect) {
                 ))
                 .borders(Borders::ALL),
         )
-        .style(Style::default().fg(Color::White));
+        .style(theme.style_text());
 
     f.render_widget(commands_list, area);
 }
+
+/// Popup scoring whatever is typed into the command checker, live.
+pub fn draw_check_prompt(f: &mut Frame, app: &App, theme: &Theme) {
+    let Some(input) = app.danger_check.as_deref() else {
+        return;
+    };
+
+    let area = super::centered_rect(60, 40, f.area());
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled("> ", theme.style_primary()),
+            Span::styled(input, theme.style_text()),
+            Span::styled("_", theme.style_text_dim()),
+        ]),
+        Line::from(""),
+    ];
+
+    if input.trim().is_empty() {
+        lines.push(Line::from(Span::styled(
+            "Type a command to see how it would be scored",
+            theme.style_text_dim(),
+        )));
+    } else {
+        let (score, reasons) = app.danger_analyzer.explain(input);
+        let style = if reasons.is_empty() {
+            theme.style_success()
+        } else {
+            theme.style_danger()
+        };
+        lines.push(Line::from(vec![
+            Span::styled("Danger score ", theme.style_text_dim()),
+            Span::styled(format!("{:.2}", score), style),
+        ]));
+        if reasons.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "No danger rules match",
+                theme.style_success(),
+            )));
+        }
+        for reason in reasons {
+            lines.push(Line::from(vec![
+                Span::raw("  • "),
+                Span::styled(reason, theme.style_text()),
+            ]));
+        }
+    }
+
+    let popup = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title(Line::from(vec![
+                    Span::styled(format!("{} ", Icons::WARNING), theme.style_warning()),
+                    Span::styled("Check Command", theme.style_title()),
+                    Span::styled("  (Esc to close)", theme.style_text_dim()),
+                ]))
+                .borders(Borders::ALL)
+                .border_style(theme.style_border()),
+        )
+        .style(theme.style_text())
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(Clear, area);
+    f.render_widget(popup, area);
+}
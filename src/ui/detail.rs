@@ -0,0 +1,189 @@
+use ratatui::{
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::app::App;
+use crate::ui::theme::{Icons, Theme};
+
+/// Full details of the command picked with Enter in the Commands, Search or
+/// Dangerous tab.
+pub fn draw(f: &mut Frame, app: &App, theme: &Theme) {
+    let Some(cmd) = app.detail_command.as_ref() else {
+        return;
+    };
+
+    let area = super::centered_rect(70, 70, f.area());
+    let label = |text: &'static str| Span::styled(format!("{:<13}", text), theme.style_text_dim());
+    let none = || "-".to_string();
+
+    // Stored text as-is, continuation lines included, in full if it was truncated
+    let mut lines: Vec<Line> = cmd
+        .original_command
+        .as_deref()
+        .unwrap_or(&cmd.command)
+        .lines()
+        .map(|line| Line::from(Span::styled(line.to_string(), theme.style_primary())))
+        .collect();
+    lines.push(Line::from(""));
+    if let Some(original) = &cmd.original_command {
+        lines.push(Line::from(Span::styled(
+            format!(
+                "Truncated in lists and search ({} characters in full)",
+                original.chars().count()
+            ),
+            theme.style_warning(),
+        )));
+        lines.push(Line::from(""));
+    }
+
+    let (exit_text, exit_style) = match cmd.exit_code {
+        Some(0) => ("0".to_string(), theme.style_success()),
+        Some(code) => (code.to_string(), theme.style_danger()),
+        None => (none(), theme.style_text_dim()),
+    };
+
+    lines.extend([
+        Line::from(vec![
+            label("Time"),
+            Span::styled(
+                cmd.timestamp.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+                theme.style_text(),
+            ),
+        ]),
+        Line::from(vec![
+            label("Directory"),
+            Span::styled(
+                cmd.working_directory.clone().unwrap_or_else(none),
+                theme.style_text(),
+            ),
+        ]),
+        Line::from(vec![
+            label("Host"),
+            Span::styled(cmd.host_id.clone(), theme.style_secondary()),
+        ]),
+        Line::from(vec![
+            label("Session"),
+            Span::styled(
+                format!("{} ({})", cmd.session_id, cmd.shell),
+                theme.style_text(),
+            ),
+        ]),
+        Line::from(vec![
+            label("Exit code"),
+            Span::styled(exit_text, exit_style),
+        ]),
+        Line::from(vec![
+            label("Duration"),
+            Span::styled(
+                cmd.duration
+                    .map(|d| format!("{}ms", d))
+                    .unwrap_or_else(none),
+                theme.style_text(),
+            ),
+        ]),
+    ]);
+
+    if !cmd.packages_used.is_empty() {
+        let packages: Vec<String> = cmd
+            .packages_used
+            .iter()
+            .map(|pkg| match &pkg.version {
+                Some(version) => format!("{} {}@{}", pkg.manager, pkg.name, version),
+                None => format!("{} {}", pkg.manager, pkg.name),
+            })
+            .collect();
+        lines.push(Line::from(vec![
+            label("Packages"),
+            Span::styled(packages.join(", "), theme.style_accent()),
+        ]));
+    }
+
+    if !cmd.network_endpoints.is_empty() {
+        lines.push(Line::from(vec![
+            label("Endpoints"),
+            Span::styled(cmd.network_endpoints.join(", "), theme.style_info()),
+        ]));
+    }
+
+    let (experiment_text, experiment_style) =
+        match (cmd.is_experiment, cmd.experiment_tags.is_empty()) {
+            (true, true) => ("yes".to_string(), theme.style_warning()),
+            (true, false) => (
+                format!("yes ({})", cmd.experiment_tags.join(", ")),
+                theme.style_warning(),
+            ),
+            (false, _) => ("no".to_string(), theme.style_text_dim()),
+        };
+    lines.push(Line::from(vec![
+        label("Experiment"),
+        Span::styled(experiment_text, experiment_style),
+        Span::styled("  [m] toggle", theme.style_text_dim()),
+    ]));
+    lines.push(Line::from(vec![
+        label("Tags"),
+        if cmd.tags.is_empty() {
+            Span::styled("none", theme.style_text_dim())
+        } else {
+            Span::styled(
+                cmd.tags
+                    .iter()
+                    .map(|tag| format!("#{}", tag))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                theme.style_accent(),
+            )
+        },
+        Span::styled("  [t] edit", theme.style_text_dim()),
+    ]));
+
+    if cmd.is_dangerous || !cmd.danger_reasons.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(vec![
+            Span::styled(format!("{} ", Icons::WARNING), theme.style_danger()),
+            Span::styled(
+                format!("Danger score {:.2}", cmd.danger_score),
+                theme.style_danger(),
+            ),
+        ]));
+        for reason in &cmd.danger_reasons {
+            lines.push(Line::from(vec![
+                Span::raw("  • "),
+                Span::styled(reason.clone(), theme.style_text()),
+            ]));
+        }
+
+        let safer = app.danger_analyzer.suggest_safer(&cmd.command);
+        if !safer.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                "Safer alternatives",
+                theme.style_success(),
+            )));
+            for suggestion in safer {
+                lines.push(Line::from(vec![
+                    Span::raw("  • "),
+                    Span::styled(suggestion, theme.style_text()),
+                ]));
+            }
+        }
+    }
+
+    let popup = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title(Line::from(vec![
+                    Span::styled(format!("{} ", Icons::INFO), theme.style_info()),
+                    Span::styled("Command Details", theme.style_title()),
+                    Span::styled("  (Esc to close)", theme.style_text_dim()),
+                ]))
+                .borders(Borders::ALL)
+                .border_style(theme.style_border()),
+        )
+        .style(theme.style_text())
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(Clear, area);
+    f.render_widget(popup, area);
+}
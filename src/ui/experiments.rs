@@ -1,13 +1,19 @@
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    style::Modifier,
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, Paragraph},
+    widgets::{Block, Borders, Clear, List, ListItem, Paragraph},
     Frame,
 };
 use std::collections::HashMap;
 
+use crate::analysis::experiment_detector::{KnowledgeGap, Priority, ToolExploration};
 use crate::app::App;
+use crate::docs::DocViewer;
+use crate::ui::theme::Theme;
+
+/// Most recent progression steps shown in the tool detail.
+const MAX_STEPS: usize = 20;
 
 pub fn draw(f: &mut Frame, app: &App, area: Rect) {
     let chunks = Layout::default()
@@ -18,11 +24,26 @@ pub fn draw(f: &mut Frame, app: &App, area: Rect) {
     // Top panel: Experiment summary
     draw_experiment_summary(f, app, chunks[0]);
 
-    // Bottom panel: Experimental commands
-    draw_experimental_commands(f, app, chunks[1]);
+    // Bottom panels: explored tools and recent experimental commands
+    let bottom = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)].as_ref())
+        .split(chunks[1]);
+    draw_explored_tools(f, app, bottom[0]);
+    draw_experimental_commands(f, app, bottom[1]);
+
+    if app.experiment_detail_open {
+        let experiments = &app.analysis().experiments;
+        if let Some(exploration) = experiments.tool_exploration.get(app.selected_index) {
+            let gaps = experiments.knowledge_gaps_for(&exploration.tool);
+            draw_tool_detail(f, exploration, &gaps, app.doc_viewer, area, &app.theme);
+        }
+    }
 }
 
 fn draw_experiment_summary(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+
     let experiment_count = app.commands.iter().filter(|cmd| cmd.is_experiment).count();
     let total_count = app.commands.len();
     let experiment_percentage = if total_count > 0 {
@@ -49,23 +70,23 @@ fn draw_experiment_summary(f: &mut Frame, app: &App, area: Rect) {
 
     let summary_text = vec![
         Line::from(vec![
-            Span::styled("🔬 Learning Mode: ", Style::default().fg(Color::Cyan)),
+            Span::styled("🔬 Learning Mode: ", theme.style_info()),
             Span::styled(format!("{} experimental commands ({:.1}%)", experiment_count, experiment_percentage), 
-                        Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+                        theme.style_warning().add_modifier(Modifier::BOLD)),
         ]),
         Line::from(vec![
-            Span::styled("Top Activities: ", Style::default().fg(Color::Cyan)),
+            Span::styled("Top Activities: ", theme.style_info()),
             Span::styled(
                 top_tags.iter()
                     .map(|(tag, count)| format!("{} ({})", tag, count))
                     .collect::<Vec<_>>()
                     .join(", "),
-                Style::default().fg(Color::White)
+                theme.style_text()
             ),
         ]),
         Line::from(vec![
-            Span::styled("Tip: ", Style::default().fg(Color::Green)),
-            Span::raw("Experimental sessions are auto-detected based on help usage and exploration patterns"),
+            Span::styled("Tip: ", theme.style_success()),
+            Span::raw("Enter shows how you learned the selected tool and where to read up"),
         ]),
     ];
 
@@ -75,36 +96,82 @@ fn draw_experiment_summary(f: &mut Frame, app: &App, area: Rect) {
                 .title("Experiment Overview")
                 .borders(Borders::ALL),
         )
-        .style(Style::default().fg(Color::White));
+        .style(theme.style_text());
 
     f.render_widget(summary, area);
 }
 
+fn draw_explored_tools(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let experiments = &app.analysis().experiments;
+    let explorations = &experiments.tool_exploration;
+
+    let rows = area.height.saturating_sub(2) as usize;
+    app.set_viewport_height(rows);
+    let items: Vec<ListItem> = explorations
+        .iter()
+        .enumerate()
+        .skip(app.scroll_offset)
+        .take(rows)
+        .map(|(i, exploration)| {
+            let style = if i == app.selected_index {
+                theme.style_selected()
+            } else {
+                theme.style_text()
+            };
+
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{:<14}", exploration.tool), style),
+                Span::styled(
+                    format!("{:>4} cmds ", exploration.exploration_commands.len()),
+                    theme.style_text_dim(),
+                ),
+                Span::styled(
+                    format!("{:>3.0}% ok", exploration.success_rate * 100.0),
+                    success_style(exploration.success_rate, theme),
+                ),
+                // Failed often enough to be worth reading up on
+                if experiments.knowledge_gaps_for(&exploration.tool).is_empty() {
+                    Span::raw("")
+                } else {
+                    Span::styled("  read up", theme.style_warning())
+                },
+            ]))
+        })
+        .collect();
+
+    let title = if explorations.is_empty() {
+        "Explored Tools (none yet)".to_string()
+    } else {
+        format!(
+            "Explored Tools ({})  Enter: details  o: docs",
+            explorations.len()
+        )
+    };
+    let list = List::new(items)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .style(theme.style_text());
+
+    f.render_widget(list, area);
+}
+
 fn draw_experimental_commands(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+
     let experimental_commands: Vec<_> = app
         .commands
         .iter()
         .filter(|cmd| cmd.is_experiment)
         .collect();
 
+    // Newest first
+    let rows = area.height.saturating_sub(2) as usize;
     let command_items: Vec<ListItem> = experimental_commands
         .iter()
-        .skip(app.scroll_offset)
-        .take(area.height as usize - 2)
-        .enumerate()
-        .map(|(i, cmd)| {
-            let is_selected = app.scroll_offset + i == app.selected_index;
-
-            let style = if is_selected {
-                Style::default()
-                    .bg(Color::Yellow)
-                    .fg(Color::Black)
-                    .add_modifier(Modifier::BOLD)
-            } else {
-                Style::default().fg(Color::Yellow)
-            };
-
-            let time_str = cmd.timestamp.format("%Y-%m-%d %H:%M:%S").to_string();
+        .rev()
+        .take(rows)
+        .map(|cmd| {
+            let time_str = cmd.timestamp.format("%Y-%m-%d %H:%M").to_string();
 
             let tags_str = if !cmd.experiment_tags.is_empty() {
                 format!(" [{}]", cmd.experiment_tags.join(", "))
@@ -119,12 +186,12 @@ fn draw_experimental_commands(f: &mut Frame, app: &App, area: Rect) {
             };
 
             ListItem::new(Line::from(vec![
-                Span::styled("🔬 ", Style::default().fg(Color::Yellow)),
-                Span::styled(time_str, Style::default().fg(Color::Gray)),
+                Span::styled("🔬 ", theme.style_warning()),
+                Span::styled(time_str, theme.style_text_dim()),
                 Span::raw(" "),
-                Span::styled(cmd.command.clone(), style),
-                Span::styled(tags_str, Style::default().fg(Color::Cyan)),
-                Span::styled(host_indicator, Style::default().fg(Color::Blue)),
+                Span::styled(cmd.command.clone(), theme.style_warning()),
+                Span::styled(tags_str, theme.style_info()),
+                Span::styled(host_indicator, theme.style_secondary()),
             ]))
         })
         .collect();
@@ -138,7 +205,144 @@ fn draw_experimental_commands(f: &mut Frame, app: &App, area: Rect) {
                 ))
                 .borders(Borders::ALL),
         )
-        .style(Style::default().fg(Color::White));
+        .style(theme.style_text());
 
     f.render_widget(commands_list, area);
 }
+
+/// The selected tool's commands in the order they were run, with how complex
+/// each one was and whether it worked, followed by what to read up on.
+fn draw_tool_detail(
+    f: &mut Frame,
+    exploration: &ToolExploration,
+    gaps: &[&KnowledgeGap],
+    viewer: Option<DocViewer>,
+    area: Rect,
+    theme: &Theme,
+) {
+    let label = theme.style_text_dim();
+    let steps = &exploration.learning_progression;
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled("Commands:     ", label),
+            Span::raw(exploration.exploration_commands.len().to_string()),
+            Span::styled("  help ", label),
+            Span::raw(exploration.help_commands.to_string()),
+            Span::styled("  tests ", label),
+            Span::raw(exploration.test_commands.to_string()),
+        ]),
+        Line::from(vec![
+            Span::styled("Success rate: ", label),
+            Span::styled(
+                format!("{:.0}%", exploration.success_rate * 100.0),
+                success_style(exploration.success_rate, theme),
+            ),
+        ]),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Progression (complexity 1-10)",
+            theme.style_warning().add_modifier(Modifier::BOLD),
+        )),
+    ];
+
+    if steps.len() > MAX_STEPS {
+        lines.push(Line::from(Span::styled(
+            format!("  … {} earlier steps", steps.len() - MAX_STEPS),
+            label,
+        )));
+    }
+    for step in steps.iter().skip(steps.len().saturating_sub(MAX_STEPS)) {
+        let (marker, marker_style) = if step.success {
+            ("✓", theme.style_success())
+        } else {
+            ("✗", theme.style_danger())
+        };
+        lines.push(Line::from(vec![
+            Span::raw("  "),
+            Span::styled(step.timestamp.format("%Y-%m-%d %H:%M").to_string(), label),
+            Span::raw("  "),
+            Span::styled(complexity_bar(step.complexity_level), theme.style_info()),
+            Span::raw(" "),
+            Span::styled(marker, marker_style),
+            Span::raw(" "),
+            Span::raw(step.command.clone()),
+        ]));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Knowledge gaps",
+        theme.style_warning().add_modifier(Modifier::BOLD),
+    )));
+    if gaps.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "  None found: no repeated failures with this tool",
+            label,
+        )));
+    }
+    for gap in gaps {
+        let (priority, priority_style) = match gap.priority {
+            Priority::High => ("high", theme.style_danger()),
+            Priority::Medium => ("medium", theme.style_warning()),
+            Priority::Low => ("low", theme.style_info()),
+        };
+        lines.push(Line::from(vec![
+            Span::raw("  "),
+            Span::raw(gap.area.clone()),
+            Span::raw(" "),
+            Span::styled(format!("[{}]", priority), priority_style),
+            Span::styled(format!("  {}", gap.indicators.join(", ")), label),
+        ]));
+        for resource in &gap.suggested_resources {
+            lines.push(Line::from(vec![
+                Span::raw("    • "),
+                Span::styled(resource.clone(), theme.style_info()),
+            ]));
+        }
+    }
+
+    lines.push(Line::from(""));
+    lines.push(match viewer {
+        Some(viewer) => Line::from(vec![
+            Span::styled("o", theme.style_accent()),
+            Span::raw(format!(
+                ": open `{} {}`",
+                viewer.program(),
+                exploration.tool
+            )),
+        ]),
+        None => Line::from(Span::styled(
+            "Install tldr or man to open its docs from here",
+            label,
+        )),
+    });
+
+    let popup_area = super::centered_rect(70, 80, area);
+    let popup = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title(format!("🔬 {}  (Esc to close)", exploration.tool))
+                .borders(Borders::ALL),
+        )
+        .style(theme.style_text());
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(popup, popup_area);
+}
+
+/// `level` out of 10 as a fixed-width bar.
+fn complexity_bar(level: u8) -> String {
+    let filled = level.min(10) as usize;
+    format!("{}{}", "█".repeat(filled), "·".repeat(10 - filled))
+}
+
+fn success_style(rate: f32, theme: &Theme) -> ratatui::style::Style {
+    if rate >= 0.8 {
+        theme.style_success()
+    } else if rate >= 0.5 {
+        theme.style_warning()
+    } else {
+        theme.style_danger()
+    }
+}
@@ -7,8 +7,81 @@ use ratatui::{
     Frame,
 };
 
-use crate::analysis::heatmap::{HeatmapAnalyzer, TimeRange, ViewMode};
+use crate::analysis::heatmap::{
+    HeatmapAnalyzer, HeatmapCell, HeatmapScale, TimeRange, ViewMode, HEATMAP_LEVELS,
+};
 use crate::app::App;
+use crate::ui::theme::Theme;
+
+const DAY_NAMES: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/// The highlighted heatmap cell: a local hour and a weekday, Monday being 0.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct HeatmapCursor {
+    pub hour: usize,
+    pub day: usize,
+}
+
+impl HeatmapCursor {
+    /// Move by whole cells, stopping at the edges of the grid.
+    pub fn move_by(self, hours: isize, days: isize) -> Self {
+        Self {
+            hour: self.hour.saturating_add_signed(hours).min(23),
+            day: self.day.saturating_add_signed(days).min(6),
+        }
+    }
+}
+
+/// How the heatmap draws its activity levels.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HeatmapRamp {
+    /// Denser shade characters for busier cells
+    #[default]
+    Blocks,
+    /// Full cells, busier ones in hotter colors
+    Solid,
+}
+
+const LEVEL_NAMES: [&str; 5] = ["Low", "Med", "High", "Very High", "Extreme"];
+
+impl HeatmapRamp {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "blocks" => Some(Self::Blocks),
+            "solid" => Some(Self::Solid),
+            _ => None,
+        }
+    }
+
+    pub fn from_config(config: &crate::config::Config) -> Self {
+        Self::parse(&config.heatmap_ramp).unwrap_or_else(|| {
+            log::warn!(
+                "Unknown heatmap_ramp {:?}, using blocks",
+                config.heatmap_ramp
+            );
+            Self::Blocks
+        })
+    }
+
+    /// Symbol and color of a cell at `level`, 0 being an empty cell.
+    fn cell(self, level: u8, theme: &Theme) -> (&'static str, Color) {
+        let color = match level {
+            0 => return ("  ", theme.text_dim),
+            1 => theme.secondary,
+            2 => theme.success,
+            3 => theme.warning,
+            4 => theme.danger,
+            _ => theme.accent,
+        };
+        let symbol = match self {
+            HeatmapRamp::Solid => "██",
+            HeatmapRamp::Blocks => {
+                ["··", "░░", "▒▒", "▓▓", "██"][(level.min(HEATMAP_LEVELS) - 1) as usize]
+            }
+        };
+        (symbol, color)
+    }
+}
 
 pub fn draw(f: &mut Frame, app: &App, area: Rect) {
     let chunks = Layout::default()
@@ -30,18 +103,23 @@ pub fn draw(f: &mut Frame, app: &App, area: Rect) {
     // Interactive controls
     draw_heatmap_controls(f, app, chunks[1]);
 
-    // Advanced heatmap visualization
-    draw_advanced_heatmap(f, app, chunks[2]);
+    // Advanced heatmap visualization, with what ran in the highlighted cell beside it
+    let grid_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(0), Constraint::Length(38)].as_ref())
+        .split(chunks[2]);
+    draw_advanced_heatmap(f, app, grid_chunks[0]);
+    draw_cell_detail(f, app, grid_chunks[1]);
 
     // Analysis and insights panel
     draw_heatmap_insights(f, app, chunks[3]);
 }
 
 fn draw_heatmap_metrics(f: &mut Frame, app: &App, area: Rect) {
-    let analyzer = HeatmapAnalyzer::new();
-    let heatmap_data =
-        analyzer.generate_heatmap(&app.commands, app.heatmap_time_range, app.heatmap_view_mode);
-    let work_patterns = analyzer.analyze_work_patterns(&app.commands);
+    let theme = &app.theme;
+
+    let heatmap_data = &app.analysis().heatmap;
+    let work_patterns = &app.analysis().work_patterns;
 
     // Create 4-column layout for metrics
     let metric_chunks = Layout::default()
@@ -62,23 +140,16 @@ fn draw_heatmap_metrics(f: &mut Frame, app: &App, area: Rect) {
     let total_block = Paragraph::new(vec![
         Line::from(vec![Span::styled(
             "📊 TOTAL ACTIVITY",
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
+            theme.style_info().add_modifier(Modifier::BOLD),
         )]),
         Line::from(""),
         Line::from(vec![
-            Span::styled(
-                &total_activity,
-                Style::default()
-                    .fg(Color::White)
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::styled(" commands", Style::default().fg(Color::Gray)),
+            Span::styled(&total_activity, theme.style_value()),
+            Span::styled(" commands", theme.style_text_dim()),
         ]),
         Line::from(vec![Span::styled(
             format!("Peak: {:.0}", heatmap_data.max_activity),
-            Style::default().fg(Color::Yellow),
+            theme.style_warning(),
         )]),
     ])
     .block(Block::default().borders(Borders::ALL))
@@ -91,24 +162,14 @@ fn draw_heatmap_metrics(f: &mut Frame, app: &App, area: Rect) {
     let peak_block = Paragraph::new(vec![
         Line::from(vec![Span::styled(
             "⏰ PEAK ACTIVITY",
-            Style::default()
-                .fg(Color::Green)
-                .add_modifier(Modifier::BOLD),
+            theme.style_success().add_modifier(Modifier::BOLD),
         )]),
         Line::from(""),
         Line::from(vec![
-            Span::styled(
-                format!("{}:00", peak_hour),
-                Style::default()
-                    .fg(Color::White)
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::styled(" hour", Style::default().fg(Color::Gray)),
+            Span::styled(format!("{}:00", peak_hour), theme.style_value()),
+            Span::styled(" hour", theme.style_text_dim()),
         ]),
-        Line::from(vec![Span::styled(
-            &peak_day,
-            Style::default().fg(Color::Yellow),
-        )]),
+        Line::from(vec![Span::styled(&peak_day, theme.style_warning())]),
     ])
     .block(Block::default().borders(Borders::ALL))
     .alignment(Alignment::Center);
@@ -119,23 +180,16 @@ fn draw_heatmap_metrics(f: &mut Frame, app: &App, area: Rect) {
     let pattern_block = Paragraph::new(vec![
         Line::from(vec![Span::styled(
             "💼 WORK PATTERN",
-            Style::default()
-                .fg(Color::Blue)
-                .add_modifier(Modifier::BOLD),
+            theme.style_secondary().add_modifier(Modifier::BOLD),
         )]),
         Line::from(""),
         Line::from(vec![
-            Span::styled(
-                format!("{}%", work_ratio),
-                Style::default()
-                    .fg(Color::White)
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::styled(" work hours", Style::default().fg(Color::Gray)),
+            Span::styled(format!("{}%", work_ratio), theme.style_value()),
+            Span::styled(" work hours", theme.style_text_dim()),
         ]),
         Line::from(vec![Span::styled(
             format!("{}% weekend", (work_patterns.weekend_ratio * 100.0) as u16),
-            Style::default().fg(Color::Yellow),
+            theme.style_warning(),
         )]),
     ])
     .block(Block::default().borders(Borders::ALL))
@@ -147,23 +201,19 @@ fn draw_heatmap_metrics(f: &mut Frame, app: &App, area: Rect) {
     let distribution_block = Paragraph::new(vec![
         Line::from(vec![Span::styled(
             "🌙 NIGHT ACTIVITY",
-            Style::default()
-                .fg(Color::Magenta)
-                .add_modifier(Modifier::BOLD),
+            theme.style_accent().add_modifier(Modifier::BOLD),
         )]),
         Line::from(""),
         Line::from(vec![
-            Span::styled(
-                format!("{}%", late_night_ratio),
-                Style::default()
-                    .fg(Color::White)
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::styled(" late night", Style::default().fg(Color::Gray)),
+            Span::styled(format!("{}%", late_night_ratio), theme.style_value()),
+            Span::styled(" late night", theme.style_text_dim()),
         ]),
         Line::from(vec![Span::styled(
-            "22:00 - 06:00",
-            Style::default().fg(Color::Yellow),
+            format!(
+                "{:02}:00 - {:02}:00",
+                work_patterns.schedule.late_night_start, work_patterns.schedule.late_night_end
+            ),
+            theme.style_warning(),
         )]),
     ])
     .block(Block::default().borders(Borders::ALL))
@@ -172,6 +222,8 @@ fn draw_heatmap_metrics(f: &mut Frame, app: &App, area: Rect) {
 }
 
 fn draw_heatmap_controls(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+
     let time_range_text = match app.heatmap_time_range {
         TimeRange::Day => "Day",
         TimeRange::Week => "Week",
@@ -188,96 +240,92 @@ fn draw_heatmap_controls(f: &mut Frame, app: &App, area: Rect) {
 
     let controls_text = vec![
         Line::from(vec![
-            Span::styled("Time Range: ", Style::default().fg(Color::Cyan)),
-            Span::styled("[D]", Style::default().fg(Color::Yellow)),
+            Span::styled("Time Range: ", theme.style_info()),
+            Span::styled("[D]", theme.style_warning()),
             Span::styled(
                 "ay ",
                 Style::default().fg(if matches!(app.heatmap_time_range, TimeRange::Day) {
-                    Color::Yellow
+                    theme.warning
                 } else {
-                    Color::White
+                    theme.text
                 }),
             ),
-            Span::styled("[W]", Style::default().fg(Color::Yellow)),
+            Span::styled("[W]", theme.style_warning()),
             Span::styled(
                 "eek ",
                 Style::default().fg(if matches!(app.heatmap_time_range, TimeRange::Week) {
-                    Color::Yellow
+                    theme.warning
                 } else {
-                    Color::White
+                    theme.text
                 }),
             ),
-            Span::styled("[M]", Style::default().fg(Color::Yellow)),
+            Span::styled("[M]", theme.style_warning()),
             Span::styled(
                 "onth ",
                 Style::default().fg(if matches!(app.heatmap_time_range, TimeRange::Month) {
-                    Color::Yellow
+                    theme.warning
                 } else {
-                    Color::White
+                    theme.text
                 }),
             ),
-            Span::styled("[Y]", Style::default().fg(Color::Yellow)),
+            Span::styled("[Y]", theme.style_warning()),
             Span::styled(
                 "ear",
                 Style::default().fg(if matches!(app.heatmap_time_range, TimeRange::Year) {
-                    Color::Yellow
+                    theme.warning
                 } else {
-                    Color::White
+                    theme.text
                 }),
             ),
         ]),
         Line::from(vec![
-            Span::styled("View Mode: ", Style::default().fg(Color::Cyan)),
-            Span::styled("[A]", Style::default().fg(Color::Green)),
+            Span::styled("View Mode: ", theme.style_info()),
+            Span::styled("[A]", theme.style_success()),
             Span::styled(
                 "ll ",
                 Style::default().fg(if matches!(app.heatmap_view_mode, ViewMode::All) {
-                    Color::Green
+                    theme.success
                 } else {
-                    Color::White
+                    theme.text
                 }),
             ),
-            Span::styled("[R]", Style::default().fg(Color::Red)),
+            Span::styled("[R]", theme.style_danger()),
             Span::styled(
                 "isky ",
                 Style::default().fg(if matches!(app.heatmap_view_mode, ViewMode::Dangerous) {
-                    Color::Red
+                    theme.danger
                 } else {
-                    Color::White
+                    theme.text
                 }),
             ),
-            Span::styled("[E]", Style::default().fg(Color::Blue)),
+            Span::styled("[E]", theme.style_secondary()),
             Span::styled(
                 "xperiments ",
                 Style::default().fg(if matches!(app.heatmap_view_mode, ViewMode::Experiments) {
-                    Color::Blue
+                    theme.secondary
                 } else {
-                    Color::White
+                    theme.text
                 }),
             ),
-            Span::styled("[F]", Style::default().fg(Color::Magenta)),
+            Span::styled("[F]", theme.style_accent()),
             Span::styled(
                 "ailed",
                 Style::default().fg(if matches!(app.heatmap_view_mode, ViewMode::Failed) {
-                    Color::Magenta
+                    theme.accent
                 } else {
-                    Color::White
+                    theme.text
                 }),
             ),
             Span::raw("  |  "),
-            Span::styled("Current: ", Style::default().fg(Color::Gray)),
+            Span::styled("Current: ", theme.style_text_dim()),
             Span::styled(
                 time_range_text,
-                Style::default()
-                    .fg(Color::Yellow)
-                    .add_modifier(Modifier::BOLD),
+                theme.style_warning().add_modifier(Modifier::BOLD),
             ),
             Span::raw(" • "),
             Span::styled(
                 view_mode_text,
-                Style::default()
-                    .fg(Color::Green)
-                    .add_modifier(Modifier::BOLD),
+                theme.style_success().add_modifier(Modifier::BOLD),
             ),
             Span::raw(" • "),
             Span::styled(
@@ -285,7 +333,7 @@ fn draw_heatmap_controls(f: &mut Frame, app: &App, area: Rect) {
                     "State: {:?}/{:?}",
                     app.heatmap_time_range, app.heatmap_view_mode
                 ),
-                Style::default().fg(Color::Gray),
+                theme.style_text_dim(),
             ),
         ]),
     ];
@@ -296,98 +344,50 @@ fn draw_heatmap_controls(f: &mut Frame, app: &App, area: Rect) {
                 .title("🎛️  Interactive Controls")
                 .borders(Borders::ALL),
         )
-        .style(Style::default().fg(Color::White));
+        .style(theme.style_text());
 
     f.render_widget(controls, area);
 }
 
 fn draw_advanced_heatmap(f: &mut Frame, app: &App, area: Rect) {
-    let analyzer = HeatmapAnalyzer::new();
-    let heatmap_data =
-        analyzer.generate_heatmap(&app.commands, app.heatmap_time_range, app.heatmap_view_mode);
+    let theme = &app.theme;
+
+    let heatmap_data = &app.analysis().heatmap;
+    let levels = heatmap_data.levels(&app.heatmap_scale);
 
     let mut heatmap_lines = Vec::new();
 
     // Enhanced header with day abbreviations and better spacing
     heatmap_lines.push(Line::from(vec![
         Span::raw("      "),
-        Span::styled(
-            "Mon  ",
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-        ),
-        Span::styled(
-            "Tue  ",
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-        ),
-        Span::styled(
-            "Wed  ",
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-        ),
-        Span::styled(
-            "Thu  ",
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-        ),
-        Span::styled(
-            "Fri  ",
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-        ),
-        Span::styled(
-            "Sat  ",
-            Style::default()
-                .fg(Color::Yellow)
-                .add_modifier(Modifier::BOLD),
-        ),
-        Span::styled(
-            "Sun  ",
-            Style::default()
-                .fg(Color::Yellow)
-                .add_modifier(Modifier::BOLD),
-        ),
+        Span::styled("Mon  ", theme.style_info().add_modifier(Modifier::BOLD)),
+        Span::styled("Tue  ", theme.style_info().add_modifier(Modifier::BOLD)),
+        Span::styled("Wed  ", theme.style_info().add_modifier(Modifier::BOLD)),
+        Span::styled("Thu  ", theme.style_info().add_modifier(Modifier::BOLD)),
+        Span::styled("Fri  ", theme.style_info().add_modifier(Modifier::BOLD)),
+        Span::styled("Sat  ", theme.style_warning().add_modifier(Modifier::BOLD)),
+        Span::styled("Sun  ", theme.style_warning().add_modifier(Modifier::BOLD)),
     ]));
 
     heatmap_lines.push(Line::from(""));
 
     // Generate heatmap grid with enhanced visualization
-    for hour in 0..24 {
+    for (hour, row) in levels.iter().enumerate() {
         let mut line_spans = vec![Span::styled(
             format!("{:2}:00 ", hour),
-            Style::default().fg(Color::Gray),
+            theme.style_text_dim(),
         )];
 
         // Real activity data for each day of the week
-        for day in 0..7 {
-            let activity_level = heatmap_data.grid[hour][day];
-            let (color, _symbol) = get_activity_visualization(activity_level);
-
-            // Add tooltip-like information for high activity periods
-            let display_symbol = if activity_level > 0.8 {
-                "██"
-            } else if activity_level > 0.6 {
-                "▓▓"
-            } else if activity_level > 0.4 {
-                "▒▒"
-            } else if activity_level > 0.2 {
-                "░░"
-            } else if activity_level > 0.0 {
-                "··"
-            } else {
-                "  "
-            };
+        for (day, &level) in row.iter().enumerate() {
+            let (symbol, color) = app.heatmap_ramp.cell(level, theme);
+            let mut cell_style = Style::default().fg(color);
+            if app.heatmap_cursor == (HeatmapCursor { hour, day }) {
+                cell_style = cell_style.add_modifier(Modifier::REVERSED);
+            }
 
-            line_spans.push(Span::styled(
-                format!("{} ", display_symbol),
-                Style::default().fg(color),
-            ));
+            line_spans.push(Span::styled(symbol, cell_style));
+            line_spans.push(Span::raw(" "));
         }
 
         // Add activity level indicator
@@ -396,7 +396,7 @@ fn draw_advanced_heatmap(f: &mut Frame, app: &App, area: Rect) {
         let intensity_bar = get_intensity_bar(hour_avg);
         line_spans.push(Span::styled(
             format!(" {}", intensity_bar),
-            Style::default().fg(Color::Gray),
+            theme.style_text_dim(),
         ));
 
         heatmap_lines.push(Line::from(line_spans));
@@ -404,26 +404,28 @@ fn draw_advanced_heatmap(f: &mut Frame, app: &App, area: Rect) {
 
     // Enhanced legend with more detail
     heatmap_lines.push(Line::from(""));
-    heatmap_lines.push(Line::from(vec![
+    let mut legend = vec![
         Span::styled(
             "Activity Levels: ",
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
+            theme.style_info().add_modifier(Modifier::BOLD),
         ),
-        Span::styled("  ", Style::default().fg(Color::Gray)),
-        Span::raw("None  "),
-        Span::styled("··", Style::default().fg(Color::Blue)),
-        Span::raw("Low  "),
-        Span::styled("░░", Style::default().fg(Color::Green)),
-        Span::raw("Med  "),
-        Span::styled("▒▒", Style::default().fg(Color::Yellow)),
-        Span::raw("High  "),
-        Span::styled("▓▓", Style::default().fg(Color::Red)),
-        Span::raw("Very High  "),
-        Span::styled("██", Style::default().fg(Color::Magenta)),
-        Span::raw("Extreme"),
-    ]));
+        Span::raw("  None  "),
+    ];
+    for (level, name) in (1..=HEATMAP_LEVELS).zip(LEVEL_NAMES) {
+        let (symbol, color) = app.heatmap_ramp.cell(level, theme);
+        legend.push(Span::styled(symbol, Style::default().fg(color)));
+        legend.push(Span::raw(format!("{}  ", name)));
+    }
+    legend.push(Span::styled(
+        match app.heatmap_scale {
+            HeatmapScale::Percentile => "(by percentile)".to_string(),
+            HeatmapScale::Absolute([a, b, c, d]) => {
+                format!("(from {}/{}/{}/{} cmds)", a, b, c, d)
+            }
+        },
+        theme.style_text_dim(),
+    ));
+    heatmap_lines.push(Line::from(legend));
 
     // Add time range specific information with fallback indication
     let now = chrono::Utc::now();
@@ -484,14 +486,14 @@ fn draw_advanced_heatmap(f: &mut Frame, app: &App, area: Rect) {
     };
 
     heatmap_lines.push(Line::from(vec![
-        Span::styled(&time_info, Style::default().fg(Color::Yellow)),
+        Span::styled(&time_info, theme.style_warning()),
         Span::raw("  |  "),
         Span::styled(
             format!(
                 "Peak Activity: {:.0} commands/hour",
                 heatmap_data.max_activity
             ),
-            Style::default().fg(Color::Green),
+            theme.style_success(),
         ),
     ]));
 
@@ -500,7 +502,7 @@ fn draw_advanced_heatmap(f: &mut Frame, app: &App, area: Rect) {
         heatmap_lines.push(Line::from(vec![
             Span::styled(
                 "ℹ️  No recent commands found - showing historical data",
-                Style::default().fg(Color::Cyan),
+                theme.style_info(),
             ),
             Span::raw(" (try running some commands to see recent activity)"),
         ]));
@@ -518,16 +520,94 @@ fn draw_advanced_heatmap(f: &mut Frame, app: &App, area: Rect) {
 
     let heatmap = Paragraph::new(heatmap_lines)
         .block(Block::default().title(title).borders(Borders::ALL))
-        .style(Style::default().fg(Color::White));
+        .style(theme.style_text());
 
     f.render_widget(heatmap, area);
 }
 
+/// Count, top commands and risk for the cell under the cursor.
+fn draw_cell_detail(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let cursor = app.heatmap_cursor;
+    let label = theme.style_text_dim();
+
+    let mut lines = Vec::new();
+    match app.analysis().heatmap.cell(cursor.hour, cursor.day) {
+        Some(cell) if cell.command_count > 0 => {
+            lines.push(Line::from(vec![
+                Span::styled("Commands:    ", label),
+                Span::styled(cell.command_count.to_string(), theme.style_value()),
+            ]));
+            lines.push(cell_ratio_line(
+                "Dangerous:   ",
+                cell.dangerous_ratio(),
+                theme.style_danger(),
+                theme,
+            ));
+            lines.push(cell_ratio_line(
+                "Experiments: ",
+                cell.experiment_ratio(),
+                theme.style_warning(),
+                theme,
+            ));
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                "Top commands",
+                theme.style_info().add_modifier(Modifier::BOLD),
+            )));
+            lines.extend(top_command_lines(cell, theme));
+        }
+        _ => lines.push(Line::from(Span::styled("No activity", label))),
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled("←↑↓→ move", label)));
+
+    let title = format!(
+        "{} {:02}:00-{:02}:00",
+        DAY_NAMES[cursor.day.min(6)],
+        cursor.hour,
+        (cursor.hour + 1) % 24
+    );
+    let detail = Paragraph::new(lines)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .style(theme.style_text());
+
+    f.render_widget(detail, area);
+}
+
+fn cell_ratio_line(label: &'static str, ratio: f32, style: Style, theme: &Theme) -> Line<'static> {
+    Line::from(vec![
+        Span::styled(label, theme.style_text_dim()),
+        Span::styled(
+            format!("{:.0}%", ratio * 100.0),
+            if ratio > 0.0 {
+                style
+            } else {
+                theme.style_text()
+            },
+        ),
+    ])
+}
+
+fn top_command_lines<'a>(cell: &'a HeatmapCell, theme: &Theme) -> Vec<Line<'a>> {
+    cell.top_commands
+        .iter()
+        .map(|(command, count)| {
+            Line::from(vec![
+                Span::styled(format!("{:>4}× ", count), theme.style_info()),
+                Span::raw(command.as_str()),
+            ])
+        })
+        .collect()
+}
+
 fn draw_heatmap_insights(f: &mut Frame, app: &App, area: Rect) {
-    let analyzer = HeatmapAnalyzer::new();
-    let work_patterns = analyzer.analyze_work_patterns(&app.commands);
+    let theme = &app.theme;
+
+    let analyzer = HeatmapAnalyzer::from_config(&app.config);
+    let work_patterns = &app.analysis().work_patterns;
     let peak_periods = analyzer.get_peak_activity_periods(
-        &analyzer.generate_heatmap(&app.commands, app.heatmap_time_range, app.heatmap_view_mode),
+        &app.analysis().heatmap,
         0.6, // threshold for "peak" activity
     );
 
@@ -541,73 +621,74 @@ fn draw_heatmap_insights(f: &mut Frame, app: &App, area: Rect) {
     let mut pattern_lines = Vec::new();
     pattern_lines.push(Line::from(vec![Span::styled(
         "📊 Work Pattern Analysis",
-        Style::default()
-            .fg(Color::Cyan)
-            .add_modifier(Modifier::BOLD),
+        theme.style_info().add_modifier(Modifier::BOLD),
     )]));
     pattern_lines.push(Line::from(""));
 
     // Work vs Personal time
+    let schedule = &work_patterns.schedule;
     let work_percentage = (work_patterns.work_hours_ratio * 100.0) as u16;
     pattern_lines.push(Line::from(vec![
-        Span::styled("Business Hours (9-17): ", Style::default().fg(Color::White)),
         Span::styled(
-            format!("{}%", work_percentage),
-            Style::default().fg(Color::Green),
+            format!(
+                "{:<22}",
+                format!(
+                    "Work Hours ({:02}-{:02}):",
+                    schedule.work_hours_start, schedule.work_hours_end
+                )
+            ),
+            theme.style_text(),
         ),
+        Span::styled(format!("{}%", work_percentage), theme.style_success()),
     ]));
 
     let weekday_percentage = (work_patterns.weekday_ratio * 100.0) as u16;
     pattern_lines.push(Line::from(vec![
-        Span::styled("Weekday Activity:     ", Style::default().fg(Color::White)),
-        Span::styled(
-            format!("{}%", weekday_percentage),
-            Style::default().fg(Color::Cyan),
-        ),
+        Span::styled("Weekday Activity:     ", theme.style_text()),
+        Span::styled(format!("{}%", weekday_percentage), theme.style_info()),
     ]));
 
     let weekend_percentage = (work_patterns.weekend_ratio * 100.0) as u16;
     pattern_lines.push(Line::from(vec![
-        Span::styled("Weekend Activity:     ", Style::default().fg(Color::White)),
-        Span::styled(
-            format!("{}%", weekend_percentage),
-            Style::default().fg(Color::Yellow),
-        ),
+        Span::styled("Weekend Activity:     ", theme.style_text()),
+        Span::styled(format!("{}%", weekend_percentage), theme.style_warning()),
     ]));
 
     let night_percentage = (work_patterns.late_night_ratio * 100.0) as u16;
     pattern_lines.push(Line::from(vec![
-        Span::styled("Late Night (22-06):   ", Style::default().fg(Color::White)),
         Span::styled(
-            format!("{}%", night_percentage),
-            Style::default().fg(Color::Magenta),
+            format!(
+                "{:<22}",
+                format!(
+                    "Late Night ({:02}-{:02}):",
+                    schedule.late_night_start, schedule.late_night_end
+                )
+            ),
+            theme.style_text(),
         ),
+        Span::styled(format!("{}%", night_percentage), theme.style_accent()),
     ]));
 
     pattern_lines.push(Line::from(""));
     pattern_lines.push(Line::from(vec![
-        Span::styled("Most Active Day: ", Style::default().fg(Color::White)),
+        Span::styled("Most Active Day: ", theme.style_text()),
         Span::styled(
             format!("{:?}", work_patterns.most_active_day),
-            Style::default()
-                .fg(Color::Green)
-                .add_modifier(Modifier::BOLD),
+            theme.style_success().add_modifier(Modifier::BOLD),
         ),
     ]));
 
     pattern_lines.push(Line::from(vec![
-        Span::styled("Peak Hour:       ", Style::default().fg(Color::White)),
+        Span::styled("Peak Hour:       ", theme.style_text()),
         Span::styled(
             format!("{}:00", work_patterns.most_active_hour),
-            Style::default()
-                .fg(Color::Green)
-                .add_modifier(Modifier::BOLD),
+            theme.style_success().add_modifier(Modifier::BOLD),
         ),
     ]));
 
     let pattern_panel = Paragraph::new(pattern_lines)
         .block(Block::default().title("📈 Insights").borders(Borders::ALL))
-        .style(Style::default().fg(Color::White));
+        .style(theme.style_text());
 
     f.render_widget(pattern_panel, insight_chunks[0]);
 
@@ -615,7 +696,7 @@ fn draw_heatmap_insights(f: &mut Frame, app: &App, area: Rect) {
     let mut peak_lines = Vec::new();
     peak_lines.push(Line::from(vec![Span::styled(
         "🔥 Peak Activity Periods",
-        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        theme.style_danger().add_modifier(Modifier::BOLD),
     )]));
     peak_lines.push(Line::from(""));
 
@@ -631,20 +712,17 @@ fn draw_heatmap_insights(f: &mut Frame, app: &App, area: Rect) {
         };
 
         let intensity_color = if period.activity_level > 0.9 {
-            Color::Red
+            theme.danger
         } else if period.activity_level > 0.8 {
-            Color::Yellow
+            theme.warning
         } else {
-            Color::Green
+            theme.success
         };
 
         peak_lines.push(Line::from(vec![
-            Span::styled(format!("{}. ", i + 1), Style::default().fg(Color::Gray)),
-            Span::styled(day_name, Style::default().fg(Color::White)),
-            Span::styled(
-                format!(" {}:00", period.hour),
-                Style::default().fg(Color::Cyan),
-            ),
+            Span::styled(format!("{}. ", i + 1), theme.style_text_dim()),
+            Span::styled(day_name, theme.style_text()),
+            Span::styled(format!(" {}:00", period.hour), theme.style_info()),
             Span::raw(" - "),
             Span::styled(
                 format!("{} cmds", period.command_count),
@@ -658,7 +736,7 @@ fn draw_heatmap_insights(f: &mut Frame, app: &App, area: Rect) {
     if peak_periods.is_empty() {
         peak_lines.push(Line::from(vec![Span::styled(
             "No significant peak periods found",
-            Style::default().fg(Color::Gray),
+            theme.style_text_dim(),
         )]));
     }
 
@@ -668,25 +746,13 @@ fn draw_heatmap_insights(f: &mut Frame, app: &App, area: Rect) {
                 .title("⚡ Top Periods")
                 .borders(Borders::ALL),
         )
-        .style(Style::default().fg(Color::White));
+        .style(theme.style_text());
 
     f.render_widget(peak_panel, insight_chunks[1]);
 }
 
-fn get_activity_visualization(level: f32) -> (Color, &'static str) {
-    match level {
-        x if x > 0.9 => (Color::Magenta, "██"),
-        x if x > 0.8 => (Color::Red, "▓▓"),
-        x if x > 0.6 => (Color::Yellow, "▒▒"),
-        x if x > 0.4 => (Color::Green, "░░"),
-        x if x > 0.2 => (Color::Blue, "··"),
-        x if x > 0.0 => (Color::Gray, "··"),
-        _ => (Color::Gray, "  "),
-    }
-}
-
 fn get_intensity_bar(intensity: f32) -> String {
-    let bars = (intensity * 5.0) as usize;
+    let bars = ((intensity * 5.0) as usize).min(5);
     let filled = "▰".repeat(bars);
     let empty = "▱".repeat(5 - bars);
     format!("{}{}", filled, empty)
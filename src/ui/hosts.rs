@@ -9,11 +9,12 @@ use ratatui::{
 use std::collections::HashMap;
 
 use crate::app::App;
+use crate::db::HostSummary;
 use crate::history::HostType;
 use crate::ui::theme::{get_host_icon, Icons, Theme};
 
 pub fn draw(f: &mut Frame, app: &App, area: Rect) {
-    let theme = Theme::default();
+    let theme = &app.theme;
 
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -24,7 +25,7 @@ pub fn draw(f: &mut Frame, app: &App, area: Rect) {
         .split(area);
 
     // Header with host statistics
-    draw_host_header(f, app, chunks[0], &theme);
+    draw_host_header(f, app, chunks[0], theme);
 
     let main_chunks = Layout::default()
         .direction(Direction::Horizontal)
@@ -32,14 +33,14 @@ pub fn draw(f: &mut Frame, app: &App, area: Rect) {
         .split(chunks[1]);
 
     // Left panel: Host list
-    draw_hosts_list(f, app, main_chunks[0], &theme);
+    draw_hosts_list(f, app, main_chunks[0], theme);
 
     // Right panel: Host details
-    draw_host_details(f, app, main_chunks[1], &theme);
+    draw_host_details(f, app, main_chunks[1], theme);
 }
 
 fn draw_host_header(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
-    let host_analysis = analyze_hosts(app);
+    let host_analysis = &app.hosts;
 
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
@@ -131,13 +132,12 @@ fn draw_metric_card(
 }
 
 fn draw_hosts_list(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
-    let host_analysis = analyze_hosts(app);
+    let host_analysis = &app.hosts;
     let hosts = &host_analysis.hosts;
 
-    let visible_hosts = hosts
-        .iter()
-        .skip(app.scroll_offset)
-        .take(area.height as usize - 2); // Account for borders
+    let rows = area.height.saturating_sub(2) as usize; // Account for borders
+    app.set_viewport_height(rows);
+    let visible_hosts = hosts.iter().skip(app.scroll_offset).take(rows);
 
     let host_items: Vec<ListItem> = visible_hosts
         .enumerate()
@@ -147,6 +147,8 @@ fn draw_hosts_list(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
 
             let style = if is_selected {
                 theme.style_selected()
+            } else if host_info.over_danger_threshold {
+                theme.style_danger().add_modifier(Modifier::BOLD)
             } else {
                 theme.style_text()
             };
@@ -160,13 +162,14 @@ fn draw_hosts_list(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
                 Span::styled("○", theme.style_text_dim())
             };
 
-            let security_indicator = if host_info.danger_score > 0.7 {
-                Span::styled(format!(" {}", Icons::ERROR), theme.style_danger())
-            } else if host_info.danger_score > 0.3 {
-                Span::styled(format!(" {}", Icons::WARNING), theme.style_warning())
-            } else {
-                Span::styled(format!(" {}", Icons::SUCCESS), theme.style_success())
-            };
+            let security_indicator =
+                if host_info.over_danger_threshold || host_info.danger_score > 0.7 {
+                    Span::styled(format!(" {}", Icons::ERROR), theme.style_danger())
+                } else if host_info.danger_score > 0.3 {
+                    Span::styled(format!(" {}", Icons::WARNING), theme.style_warning())
+                } else {
+                    Span::styled(format!(" {}", Icons::SUCCESS), theme.style_success())
+                };
 
             let performance_bar = create_performance_indicator(host_info.avg_duration_ms, theme);
 
@@ -220,7 +223,7 @@ fn draw_hosts_list(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
 }
 
 fn draw_host_details(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
-    let host_analysis = analyze_hosts(app);
+    let host_analysis = &app.hosts;
 
     if host_analysis.hosts.is_empty() {
         draw_empty_state(f, area, theme);
@@ -538,95 +541,134 @@ fn draw_recent_commands(f: &mut Frame, app: &App, host_info: &HostInfo, area: Re
 // Helper functions and data structures
 
 #[derive(Debug, Clone)]
-struct HostAnalysis {
-    total_hosts: usize,
-    active_hosts: usize,
-    docker_hosts: usize,
-    ssh_hosts: usize,
-    k8s_hosts: usize,
-    hosts: Vec<HostInfo>,
+pub struct HostAnalysis {
+    pub total_hosts: usize,
+    pub active_hosts: usize,
+    pub docker_hosts: usize,
+    pub ssh_hosts: usize,
+    pub k8s_hosts: usize,
+    pub hosts: Vec<HostInfo>,
+}
+
+impl HostAnalysis {
+    /// The Hosts tab's view of per-host aggregates, kept in their order.
+    pub fn from_summaries(summaries: &[HostSummary]) -> Self {
+        let week_ago = Utc::now() - Duration::days(7);
+        let hosts: Vec<HostInfo> = summaries
+            .iter()
+            .map(|summary| HostInfo {
+                host_id: summary.host_id.clone(),
+                total_commands: summary.total_commands,
+                dangerous_commands: summary.dangerous_commands,
+                experiment_commands: summary.experiment_commands,
+                avg_duration_ms: summary.avg_duration_ms.unwrap_or(0),
+                danger_score: summary.avg_danger_score,
+                over_danger_threshold: false,
+                is_active: summary.last_seen > week_ago,
+                last_seen: summary.last_seen,
+            })
+            .collect();
+
+        let count_prefixed = |prefix: &str| {
+            hosts
+                .iter()
+                .filter(|h| h.host_id.starts_with(prefix))
+                .count()
+        };
+        Self {
+            total_hosts: hosts.len(),
+            active_hosts: hosts.iter().filter(|h| h.is_active).count(),
+            docker_hosts: count_prefixed("docker:"),
+            ssh_hosts: count_prefixed("ssh:"),
+            k8s_hosts: count_prefixed("k8s:"),
+            hosts,
+        }
+    }
+
+    /// Mark the hosts whose mean danger score is above `threshold` and move
+    /// them to the top of the list, keeping the order within each group.
+    pub fn flag_dangerous_hosts(&mut self, threshold: f32) {
+        for host in &mut self.hosts {
+            host.over_danger_threshold = host.danger_score > threshold;
+        }
+        self.hosts.sort_by_key(|host| !host.over_danger_threshold);
+    }
+
+    pub fn hosts_over_danger_threshold(&self) -> usize {
+        self.hosts
+            .iter()
+            .filter(|host| host.over_danger_threshold)
+            .count()
+    }
 }
 
 #[derive(Debug, Clone)]
-struct HostInfo {
-    host_id: String,
-    total_commands: usize,
-    dangerous_commands: usize,
-    experiment_commands: usize,
-    avg_duration_ms: u64,
-    danger_score: f32,
-    is_active: bool,
-    last_seen: DateTime<Utc>,
+pub struct HostInfo {
+    pub host_id: String,
+    pub total_commands: usize,
+    pub dangerous_commands: usize,
+    pub experiment_commands: usize,
+    /// Mean over the commands that recorded a duration
+    pub avg_duration_ms: u64,
+    /// Mean danger score over all commands on the host
+    pub danger_score: f32,
+    /// Set by `HostAnalysis::flag_dangerous_hosts`
+    pub over_danger_threshold: bool,
+    pub is_active: bool,
+    pub last_seen: DateTime<Utc>,
 }
 
-fn analyze_hosts(app: &App) -> HostAnalysis {
-    let mut host_stats: HashMap<String, HostInfo> = HashMap::new();
-    let now = Utc::now();
-    let week_ago = now - Duration::days(7);
-
-    for cmd in &app.commands {
-        let entry = host_stats
-            .entry(cmd.host_id.clone())
-            .or_insert_with(|| HostInfo {
-                host_id: cmd.host_id.clone(),
-                total_commands: 0,
-                dangerous_commands: 0,
-                experiment_commands: 0,
-                avg_duration_ms: 0,
-                danger_score: 0.0,
-                is_active: false,
-                last_seen: cmd.timestamp,
+/// The same view built from commands in memory rather than from the database.
+#[allow(dead_code)]
+pub fn analyze_host_commands(commands: &[crate::history::Command]) -> HostAnalysis {
+    // Running (summary, duration sum, danger score sum) per host
+    let mut totals: HashMap<&str, (HostSummary, u64, f32)> = HashMap::new();
+
+    for cmd in commands {
+        let (summary, duration_sum, danger_sum) =
+            totals.entry(cmd.host_id.as_str()).or_insert_with(|| {
+                (
+                    HostSummary {
+                        host_id: cmd.host_id.clone(),
+                        total_commands: 0,
+                        timed_commands: 0,
+                        dangerous_commands: 0,
+                        experiment_commands: 0,
+                        last_seen: cmd.timestamp,
+                        avg_duration_ms: None,
+                        avg_danger_score: 0.0,
+                    },
+                    0,
+                    0.0,
+                )
             });
 
-        entry.total_commands += 1;
-        if cmd.is_dangerous {
-            entry.dangerous_commands += 1;
-        }
-        if cmd.is_experiment {
-            entry.experiment_commands += 1;
-        }
-        if cmd.timestamp > entry.last_seen {
-            entry.last_seen = cmd.timestamp;
-        }
-        if cmd.timestamp > week_ago {
-            entry.is_active = true;
-        }
-
-        // Update average duration
+        summary.total_commands += 1;
+        summary.dangerous_commands += usize::from(cmd.is_dangerous);
+        summary.experiment_commands += usize::from(cmd.is_experiment);
+        summary.last_seen = summary.last_seen.max(cmd.timestamp);
         if let Some(duration) = cmd.duration {
-            entry.avg_duration_ms = (entry.avg_duration_ms + duration) / 2;
+            *duration_sum += duration;
+            summary.timed_commands += 1;
         }
-
-        // Update danger score
-        entry.danger_score = (entry.danger_score + cmd.danger_score) / 2.0;
+        *danger_sum += cmd.danger_score;
     }
 
-    let mut hosts: Vec<_> = host_stats.into_values().collect();
-    hosts.sort_by(|a, b| b.total_commands.cmp(&a.total_commands));
+    let mut summaries: Vec<HostSummary> = totals
+        .into_values()
+        .map(|(mut summary, duration_sum, danger_sum)| {
+            summary.avg_duration_ms = duration_sum.checked_div(summary.timed_commands as u64);
+            summary.avg_danger_score = danger_sum / summary.total_commands as f32;
+            summary
+        })
+        .collect();
+    summaries.sort_by(|a, b| {
+        b.total_commands
+            .cmp(&a.total_commands)
+            .then_with(|| a.host_id.cmp(&b.host_id))
+    });
 
-    let total_hosts = hosts.len();
-    let active_hosts = hosts.iter().filter(|h| h.is_active).count();
-    let docker_hosts = hosts
-        .iter()
-        .filter(|h| h.host_id.starts_with("docker:"))
-        .count();
-    let ssh_hosts = hosts
-        .iter()
-        .filter(|h| h.host_id.starts_with("ssh:"))
-        .count();
-    let k8s_hosts = hosts
-        .iter()
-        .filter(|h| h.host_id.starts_with("k8s:"))
-        .count();
-
-    HostAnalysis {
-        total_hosts,
-        active_hosts,
-        docker_hosts,
-        ssh_hosts,
-        k8s_hosts,
-        hosts,
-    }
+    HostAnalysis::from_summaries(&summaries)
 }
 
 fn parse_host_type(host_id: &str) -> HostType {
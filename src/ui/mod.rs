@@ -1,11 +1,14 @@
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::Modifier,
     text::{Line, Span},
     widgets::{Block, Borders, Clear, Paragraph, Tabs},
     Frame,
 };
 
-use crate::app::{App, Tab};
+use crate::about::AboutInfo;
+use crate::app::{App, Tab, TagInput, TagInputPurpose};
+use crate::onboarding::format_size;
 
 pub mod theme;
 pub use theme::{Icons, Theme};
@@ -13,17 +16,31 @@ pub use theme::{Icons, Theme};
 pub mod aliases;
 pub mod commands;
 pub mod dangerous;
+pub mod detail;
 pub mod experiments;
 pub mod heatmap;
 pub mod hosts;
 pub mod network;
+pub mod onboarding;
 pub mod packages;
+pub mod projects;
 pub mod search;
+pub mod search_query;
 pub mod sessions;
 pub mod summary;
 
+/// Below this many columns the tabs only render a "too small" notice.
+pub const MIN_WIDTH: u16 = 40;
+/// Below this many rows the tabs only render a "too small" notice.
+pub const MIN_HEIGHT: u16 = 10;
+
 pub fn draw(f: &mut Frame, app: &App) {
-    let theme = Theme::default();
+    let theme = &app.theme;
+
+    if f.area().width < MIN_WIDTH || f.area().height < MIN_HEIGHT {
+        draw_too_small(f, theme);
+        return;
+    }
 
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -38,7 +55,7 @@ pub fn draw(f: &mut Frame, app: &App) {
         .split(f.area());
 
     // Draw tabs with enhanced styling
-    draw_tabs(f, app, chunks[0], &theme);
+    draw_tabs(f, app, chunks[0], theme);
 
     // Draw main content based on current tab
     match app.current_tab {
@@ -53,19 +70,71 @@ pub fn draw(f: &mut Frame, app: &App) {
         Tab::Network => network::draw(f, app, chunks[1]),
         Tab::Packages => packages::draw(f, app, chunks[1]),
         Tab::Experiments => experiments::draw(f, app, chunks[1]),
+        Tab::Projects => projects::draw(f, app, chunks[1]),
     }
 
     // Draw bottom navigation bar
-    draw_bottom_nav(f, app, chunks[2], &theme);
+    draw_bottom_nav(f, app, chunks[2], theme);
+
+    if app.detail_visible {
+        detail::draw(f, app, theme);
+    }
+
+    if app.danger_check.is_some() {
+        dangerous::draw_check_prompt(f, app, theme);
+    }
 
     // Draw help overlay if visible
     if app.help_visible {
-        draw_help_overlay(f, &theme);
+        draw_help_overlay(f, theme);
+    }
+
+    if let Some(about) = &app.about {
+        draw_about_overlay(f, about, theme);
+    }
+
+    if let Some(input) = &app.tag_input {
+        draw_tag_prompt(f, input, theme);
+    }
+
+    if app.pending_quit {
+        draw_quit_prompt(f, theme);
+    }
+
+    if app.pending_reenrich {
+        draw_reenrich_prompt(f, theme);
     }
 
     // Search overlay removed - search is now integrated into the Search tab
 }
 
+fn draw_too_small(f: &mut Frame, theme: &Theme) {
+    let area = f.area();
+    let notice = Paragraph::new(vec![
+        Line::from(Span::styled("Terminal too small", theme.style_warning())),
+        Line::from(Span::styled(
+            format!(
+                "{}x{}, need {}x{}",
+                area.width, area.height, MIN_WIDTH, MIN_HEIGHT
+            ),
+            theme.style_text_dim(),
+        )),
+    ])
+    .alignment(Alignment::Center)
+    .wrap(ratatui::widgets::Wrap { trim: true });
+
+    // Center vertically when there's room for both lines
+    let top = area.height.saturating_sub(2) / 2;
+    f.render_widget(
+        notice,
+        Rect {
+            y: area.y + top,
+            height: area.height - top,
+            ..area
+        },
+    );
+}
+
 fn draw_tabs(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
     let titles: Vec<Line> = Tab::all()
         .iter()
@@ -82,6 +151,7 @@ fn draw_tabs(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
                 Tab::Network => "",
                 Tab::Packages => "",
                 Tab::Experiments => "",
+                Tab::Projects => "",
             };
             Line::from(vec![
                 Span::styled(format!("{} ", icon), theme.style_accent()),
@@ -163,6 +233,59 @@ fn draw_help_overlay(f: &mut Frame, theme: &Theme) {
             Span::styled("  Esc", theme.style_primary()),
             Span::styled("     - Cancel/Back", theme.style_text()),
         ]),
+        Line::from(vec![
+            Span::styled("  y", theme.style_primary()),
+            Span::styled(
+                "       - Copy the selected command (Ctrl+Y in Search)",
+                theme.style_text(),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("  m", theme.style_primary()),
+            Span::styled(
+                "       - Mark or unmark the open command as an experiment",
+                theme.style_text(),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("  t", theme.style_primary()),
+            Span::styled("       - Edit the open command's tags", theme.style_text()),
+        ]),
+        Line::from(vec![
+            Span::styled("  #", theme.style_primary()),
+            Span::styled(
+                "       - Filter Commands by tag (Esc clears)",
+                theme.style_text(),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("  o", theme.style_primary()),
+            Span::styled(
+                "       - Open docs for the selected tool (Experiments)",
+                theme.style_text(),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("  x", theme.style_primary()),
+            Span::styled(
+                "       - Write a Markdown report next to the database",
+                theme.style_text(),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("  S-R", theme.style_primary()),
+            Span::styled(
+                "     - Re-score stored history, after a y/n prompt",
+                theme.style_text(),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("  F12", theme.style_primary()),
+            Span::styled(
+                "     - About: version, config and database locations",
+                theme.style_text(),
+            ),
+        ]),
         Line::from(""),
         Line::from(vec![
             Span::styled(format!("{} ", Icons::INFO), theme.style_info()),
@@ -198,10 +321,164 @@ fn draw_help_overlay(f: &mut Frame, theme: &Theme) {
     f.render_widget(paragraph, area);
 }
 
+fn draw_tag_prompt(f: &mut Frame, input: &TagInput, theme: &Theme) {
+    let area = centered_rect(50, 20, f.area());
+    let (title, hint) = match input.purpose {
+        TagInputPurpose::Edit(_) => ("Tags", "Separate tags with commas or spaces"),
+        TagInputPurpose::Filter => ("Filter by tag", "Leave empty to show every command"),
+    };
+    let prompt = Paragraph::new(vec![
+        Line::from(vec![
+            Span::styled("# ", theme.style_accent()),
+            Span::styled(input.text.as_str(), theme.style_text()),
+            Span::styled("_", theme.style_text_dim()),
+        ]),
+        Line::from(""),
+        Line::from(Span::styled(hint, theme.style_text_dim())),
+    ])
+    .block(
+        Block::default()
+            .title(Line::from(vec![
+                Span::styled(title, theme.style_title()),
+                Span::styled("  (Enter to save, Esc to cancel)", theme.style_text_dim()),
+            ]))
+            .borders(Borders::ALL)
+            .border_style(theme.style_border()),
+    );
+
+    f.render_widget(Clear, area);
+    f.render_widget(prompt, area);
+}
+
+fn draw_quit_prompt(f: &mut Frame, theme: &Theme) {
+    let area = centered_rect(30, 20, f.area());
+    let prompt = Paragraph::new(vec![
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("Quit? ", theme.style_warning()),
+            Span::styled("(y/n)", theme.style_text_dim()),
+        ]),
+    ])
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(theme.style_border()),
+    )
+    .alignment(Alignment::Center);
+
+    f.render_widget(Clear, area);
+    f.render_widget(prompt, area);
+}
+
+fn draw_reenrich_prompt(f: &mut Frame, theme: &Theme) {
+    let area = centered_rect(50, 20, f.area());
+    let prompt = Paragraph::new(vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            "Rescore all stored history with the current rules?",
+            theme.style_warning(),
+        )),
+        Line::from(Span::styled("(y/n)", theme.style_text_dim())),
+    ])
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(theme.style_border()),
+    )
+    .alignment(Alignment::Center);
+
+    f.render_widget(Clear, area);
+    f.render_widget(prompt, area);
+}
+
+fn draw_about_overlay(f: &mut Frame, about: &AboutInfo, theme: &Theme) {
+    let area = centered_rect(70, 60, f.area());
+    let label = theme.style_text_dim();
+    let row = |name: &'static str, value: String| {
+        Line::from(vec![
+            Span::styled(format!("{:<11}", name), label),
+            Span::styled(value, theme.style_text()),
+        ])
+    };
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled(format!("{} ", Icons::WHISKER), theme.style_primary()),
+            Span::styled("Whiskerlog ", theme.style_title()),
+            Span::styled(about.version, theme.style_value()),
+        ]),
+        Line::from(""),
+        row("Config", about.config_path.display().to_string()),
+        row("Database", about.database_path.display().to_string()),
+        row(
+            "Size",
+            about
+                .database_size
+                .map_or_else(|| "unknown".to_string(), format_size),
+        ),
+        row("Commands", about.total_commands.to_string()),
+        Line::from(""),
+        Line::from(Span::styled("Import sources", theme.style_highlight())),
+    ];
+    if about.import_sources.is_empty() {
+        lines.push(Line::from(Span::styled("  none found", label)));
+    }
+    for (shell, path) in &about.import_sources {
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {:<11}", shell), theme.style_info()),
+            Span::raw(path.display().to_string()),
+        ]));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled("Press ", label),
+        Span::styled("F12", theme.style_primary()),
+        Span::styled(" or ", label),
+        Span::styled("Esc", theme.style_primary()),
+        Span::styled(" to close", label),
+    ]));
+
+    let paragraph = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title(Line::from(vec![
+                    Span::styled(format!("{} ", Icons::INFO), theme.style_info()),
+                    Span::styled("About", theme.style_title()),
+                ]))
+                .borders(Borders::ALL)
+                .border_style(theme.style_border()),
+        )
+        .style(theme.style_text());
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
 // Search overlay function removed - search is now integrated into the Search tab
 
 fn draw_bottom_nav(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
-    let nav_text = vec![Line::from(vec![
+    let mut spans = Vec::new();
+    if let Some((stored, parsed)) = app.import_progress() {
+        spans.push(Span::styled(
+            format!("importing… {}/{}", stored, parsed),
+            theme.style_warning(),
+        ));
+        spans.push(Span::styled(" | ", theme.style_text_dim()));
+    }
+    let flagged_hosts = app.hosts.hosts_over_danger_threshold();
+    if flagged_hosts > 0 {
+        spans.push(Span::styled(
+            format!(
+                "{} {} over danger threshold",
+                flagged_hosts,
+                if flagged_hosts == 1 { "host" } else { "hosts" }
+            ),
+            theme.style_danger().add_modifier(Modifier::BOLD),
+        ));
+        spans.push(Span::styled(" | ", theme.style_text_dim()));
+    }
+
+    spans.extend([
         Span::styled(
             format!("{} commands", app.stats.total_commands),
             theme.style_text(),
@@ -236,7 +513,17 @@ fn draw_bottom_nav(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
         Span::styled(" Help", theme.style_text_dim()),
         Span::styled(" [q]", theme.style_danger()),
         Span::styled(" Quit", theme.style_text_dim()),
-    ])];
+    ]);
+    let nav_text = vec![Line::from(spans)];
+
+    // The Aliases tab shows messages in its own footer
+    let nav_text = match app.status_message() {
+        Some(status) if app.current_tab != Tab::Aliases => vec![Line::from(vec![
+            Span::styled(format!("{} ", Icons::INFO), theme.style_info()),
+            Span::styled(status.to_string(), theme.style_success()),
+        ])],
+        _ => nav_text,
+    };
 
     let paragraph = Paragraph::new(nav_text)
         .style(theme.style_text())
@@ -6,8 +6,69 @@ use ratatui::{
     Frame,
 };
 
-use crate::analysis::network_analyzer::NetworkAnalyzer;
+use crate::analysis::network_analyzer::{EndpointStats, NetworkAnalysis, NetworkAnalyzer};
 use crate::app::App;
+use crate::ui::theme::Theme;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NetworkFilter {
+    All,
+    Secure,
+    Insecure,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NetworkSort {
+    Usage,
+    Time,
+    Risk,
+}
+
+/// Most endpoints listed in the Network tab at once.
+pub const MAX_ENDPOINT_ROWS: usize = 20;
+
+/// Endpoints shown in the Network tab under `filter`, ordered by `sort`. Risk
+/// puts insecure endpoints first, then the least successful ones. Only the
+/// first [`MAX_ENDPOINT_ROWS`] are kept, after filtering and sorting.
+pub fn endpoint_rows(
+    analysis: &NetworkAnalysis,
+    filter: &NetworkFilter,
+    sort: &NetworkSort,
+) -> Vec<EndpointStats> {
+    let mut rows: Vec<EndpointStats> = analysis
+        .top_endpoints
+        .iter()
+        .filter(|endpoint| match filter {
+            NetworkFilter::All => true,
+            NetworkFilter::Secure => endpoint.is_secure,
+            NetworkFilter::Insecure => !endpoint.is_secure,
+        })
+        .cloned()
+        .collect();
+
+    match sort {
+        NetworkSort::Usage => rows.sort_by(|a, b| {
+            b.usage_count
+                .cmp(&a.usage_count)
+                .then_with(|| a.endpoint.cmp(&b.endpoint))
+        }),
+        NetworkSort::Time => rows.sort_by(|a, b| {
+            b.last_seen
+                .cmp(&a.last_seen)
+                .then_with(|| a.endpoint.cmp(&b.endpoint))
+        }),
+        NetworkSort::Risk => rows.sort_by(|a, b| {
+            a.is_secure
+                .cmp(&b.is_secure)
+                .then_with(|| a.success_rate.total_cmp(&b.success_rate))
+                .then_with(|| b.usage_count.cmp(&a.usage_count))
+                .then_with(|| a.endpoint.cmp(&b.endpoint))
+        }),
+    }
+
+    rows.truncate(MAX_ENDPOINT_ROWS);
+    rows
+}
 
 pub fn draw(f: &mut Frame, app: &App, area: Rect) {
     let chunks = Layout::default()
@@ -37,8 +98,9 @@ pub fn draw(f: &mut Frame, app: &App, area: Rect) {
 }
 
 fn draw_network_metrics(f: &mut Frame, app: &App, area: Rect) {
-    let analyzer = NetworkAnalyzer::new();
-    let analysis = analyzer.analyze_network_activity(&app.commands);
+    let theme = &app.theme;
+
+    let analysis = &app.analysis().network;
 
     // Create 4-column layout for metrics
     let metric_chunks = Layout::default()
@@ -59,38 +121,22 @@ fn draw_network_metrics(f: &mut Frame, app: &App, area: Rect) {
     let endpoints_block = Paragraph::new(vec![
         Line::from(vec![Span::styled(
             "🌐 ENDPOINTS",
-            Style::default()
-                .fg(Color::Blue)
-                .add_modifier(Modifier::BOLD),
+            theme.style_secondary().add_modifier(Modifier::BOLD),
         )]),
         Line::from(""),
         Line::from(vec![
-            Span::styled(
-                format!("{}", endpoints_count),
-                Style::default()
-                    .fg(Color::White)
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::styled(" unique", Style::default().fg(Color::Gray)),
+            Span::styled(format!("{}", endpoints_count), theme.style_value()),
+            Span::styled(" unique", theme.style_text_dim()),
         ]),
-        Line::from(vec![Span::styled(
-            "Discovered",
-            Style::default().fg(Color::Yellow),
-        )]),
+        Line::from(vec![Span::styled("Discovered", theme.style_warning())]),
     ])
     .block(Block::default().borders(Borders::ALL))
     .alignment(Alignment::Center);
     f.render_widget(endpoints_block, metric_chunks[0]);
 
     // Security Score Metric
-    let security_score = analyzer.calculate_network_security_score(&analysis);
-    let security_color = if security_score > 80.0 {
-        Color::Green
-    } else if security_score > 60.0 {
-        Color::Yellow
-    } else {
-        Color::Red
-    };
+    let security_score = NetworkAnalyzer::new().calculate_network_security_score(analysis);
+    let security_color = theme.score_color(security_score);
     let security_block = Paragraph::new(vec![
         Line::from(vec![Span::styled(
             "🔒 SECURITY",
@@ -100,25 +146,17 @@ fn draw_network_metrics(f: &mut Frame, app: &App, area: Rect) {
         )]),
         Line::from(""),
         Line::from(vec![
-            Span::styled(
-                format!("{:.0}%", security_score),
-                Style::default()
-                    .fg(Color::White)
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::styled(" score", Style::default().fg(Color::Gray)),
+            Span::styled(format!("{:.0}%", security_score), theme.style_value()),
+            Span::styled(" score", theme.style_text_dim()),
         ]),
-        Line::from(vec![Span::styled(
-            "Safety rating",
-            Style::default().fg(Color::Yellow),
-        )]),
+        Line::from(vec![Span::styled("Safety rating", theme.style_warning())]),
     ])
     .block(Block::default().borders(Borders::ALL))
     .alignment(Alignment::Center);
     f.render_widget(security_block, metric_chunks[1]);
 
     // Protocol Distribution Metric
-    let https_count = analysis.protocol_breakdown.get("https").unwrap_or(&0);
+    let https_count = analysis.protocol_breakdown.get("HTTPS").unwrap_or(&0);
     let total_protocols = analysis.protocol_breakdown.values().sum::<usize>();
     let https_ratio = if total_protocols > 0 {
         (*https_count as f32 / total_protocols as f32 * 100.0) as u16
@@ -128,24 +166,14 @@ fn draw_network_metrics(f: &mut Frame, app: &App, area: Rect) {
     let protocol_block = Paragraph::new(vec![
         Line::from(vec![Span::styled(
             "📊 PROTOCOLS",
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
+            theme.style_info().add_modifier(Modifier::BOLD),
         )]),
         Line::from(""),
         Line::from(vec![
-            Span::styled(
-                format!("{}%", https_ratio),
-                Style::default()
-                    .fg(Color::White)
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::styled(" secure", Style::default().fg(Color::Gray)),
+            Span::styled(format!("{}%", https_ratio), theme.style_value()),
+            Span::styled(" secure", theme.style_text_dim()),
         ]),
-        Line::from(vec![Span::styled(
-            "HTTPS usage",
-            Style::default().fg(Color::Yellow),
-        )]),
+        Line::from(vec![Span::styled("HTTPS usage", theme.style_warning())]),
     ])
     .block(Block::default().borders(Borders::ALL))
     .alignment(Alignment::Center);
@@ -154,11 +182,11 @@ fn draw_network_metrics(f: &mut Frame, app: &App, area: Rect) {
     // Security Issues Metric
     let issues_count = analysis.security_issues.len();
     let issues_color = if issues_count == 0 {
-        Color::Green
+        theme.success
     } else if issues_count < 5 {
-        Color::Yellow
+        theme.warning
     } else {
-        Color::Red
+        theme.danger
     };
     let issues_block = Paragraph::new(vec![
         Line::from(vec![Span::styled(
@@ -169,41 +197,56 @@ fn draw_network_metrics(f: &mut Frame, app: &App, area: Rect) {
         )]),
         Line::from(""),
         Line::from(vec![
-            Span::styled(
-                format!("{}", issues_count),
-                Style::default()
-                    .fg(Color::White)
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::styled(" found", Style::default().fg(Color::Gray)),
+            Span::styled(format!("{}", issues_count), theme.style_value()),
+            Span::styled(" found", theme.style_text_dim()),
         ]),
-        Line::from(vec![Span::styled(
-            "Security alerts",
-            Style::default().fg(Color::Yellow),
-        )]),
+        Line::from(vec![Span::styled("Security alerts", theme.style_warning())]),
     ])
     .block(Block::default().borders(Borders::ALL))
     .alignment(Alignment::Center);
     f.render_widget(issues_block, metric_chunks[3]);
 }
 
-fn draw_network_controls(f: &mut Frame, _app: &App, area: Rect) {
+fn draw_network_controls(f: &mut Frame, app: &App, area: Rect) {
+    // The active filter and sort keys are highlighted
+    let theme = &app.theme;
+
+    let key_style = |active: bool, color: Color| theme.style_key(active, color);
+
     let controls_text = vec![Line::from(vec![
-        Span::styled("Filter: ", Style::default().fg(Color::Cyan)),
-        Span::styled("[S]", Style::default().fg(Color::Green)),
-        Span::styled("ecure ", Style::default().fg(Color::White)),
-        Span::styled("[I]", Style::default().fg(Color::Red)),
-        Span::styled("nsecure ", Style::default().fg(Color::White)),
-        Span::styled("[A]", Style::default().fg(Color::Blue)),
-        Span::styled("ll ", Style::default().fg(Color::White)),
+        Span::styled("Filter: ", theme.style_info()),
+        Span::styled(
+            "[S]",
+            key_style(app.network_filter == NetworkFilter::Secure, theme.success),
+        ),
+        Span::styled("ecure ", theme.style_text()),
+        Span::styled(
+            "[I]",
+            key_style(app.network_filter == NetworkFilter::Insecure, theme.danger),
+        ),
+        Span::styled("nsecure ", theme.style_text()),
+        Span::styled(
+            "[A]",
+            key_style(app.network_filter == NetworkFilter::All, theme.secondary),
+        ),
+        Span::styled("ll ", theme.style_text()),
         Span::raw("  |  "),
-        Span::styled("Sort: ", Style::default().fg(Color::Cyan)),
-        Span::styled("[U]", Style::default().fg(Color::Yellow)),
-        Span::styled("sage ", Style::default().fg(Color::White)),
-        Span::styled("[T]", Style::default().fg(Color::Magenta)),
-        Span::styled("ime ", Style::default().fg(Color::White)),
-        Span::styled("[R]", Style::default().fg(Color::Green)),
-        Span::styled("isk", Style::default().fg(Color::White)),
+        Span::styled("Sort: ", theme.style_info()),
+        Span::styled(
+            "[U]",
+            key_style(app.network_sort == NetworkSort::Usage, theme.warning),
+        ),
+        Span::styled("sage ", theme.style_text()),
+        Span::styled(
+            "[T]",
+            key_style(app.network_sort == NetworkSort::Time, theme.accent),
+        ),
+        Span::styled("ime ", theme.style_text()),
+        Span::styled(
+            "[R]",
+            key_style(app.network_sort == NetworkSort::Risk, theme.success),
+        ),
+        Span::styled("isk", theme.style_text()),
     ])];
 
     let controls = Paragraph::new(controls_text)
@@ -212,7 +255,7 @@ fn draw_network_controls(f: &mut Frame, _app: &App, area: Rect) {
                 .title("🎛️  Network Controls")
                 .borders(Borders::ALL),
         )
-        .style(Style::default().fg(Color::White));
+        .style(theme.style_text());
 
     f.render_widget(controls, area);
 }
@@ -237,31 +280,45 @@ fn draw_network_content(f: &mut Frame, app: &App, area: Rect) {
     // Middle panel: Security issues
     draw_security_issues_panel(f, app, content_chunks[1]);
 
-    // Right panel: Connection patterns
-    draw_connection_patterns_panel(f, app, content_chunks[2]);
+    // Right panel: what the host picked on the Hosts tab talked to, or
+    // connection patterns across all hosts
+    match &app.host_filter {
+        Some(host) => draw_host_endpoints_panel(f, app, host, content_chunks[2]),
+        None => draw_connection_patterns_panel(f, app, content_chunks[2]),
+    }
 }
 
 fn draw_enhanced_endpoints_list(f: &mut Frame, app: &App, area: Rect) {
-    let analyzer = NetworkAnalyzer::new();
-    let analysis = analyzer.analyze_network_activity(&app.commands);
+    let theme = &app.theme;
+
+    let analysis = &app.analysis().network;
+
+    let rows = endpoint_rows(analysis, &app.network_filter, &app.network_sort);
 
     let mut items = Vec::new();
 
-    for (i, endpoint_stats) in analysis.top_endpoints.iter().enumerate() {
+    for (i, endpoint_stats) in rows.iter().enumerate() {
         let is_selected = i == app.selected_index;
 
         // Protocol icon and security indicator
-        let (protocol_icon, security_color) = match endpoint_stats.protocol.as_str() {
-            "https" => ("🔒", Color::Green),
-            "http" => ("🔓", Color::Red),
-            "ssh" => ("🔗", Color::Blue),
-            "ftp" => ("📁", Color::Yellow),
-            "telnet" => ("⚠️", Color::Red),
-            _ => ("🌐", Color::Gray),
+        let protocol_icon = match endpoint_stats.protocol.to_lowercase().as_str() {
+            "https" => "🔒",
+            "http" => "🔓",
+            "ssh" => "🔗",
+            "ftp" => "📁",
+            "telnet" => "⚠️",
+            _ => "🌐",
+        };
+        let security_style = if endpoint_stats.is_secure {
+            theme.style_secure()
+        } else {
+            theme.style_insecure()
         };
 
-        // Risk level indicator
-        let risk_indicator = if !endpoint_stats.is_secure {
+        // Risk level indicator; trusted hosts get a badge instead of a warning
+        let risk_indicator = if endpoint_stats.is_trusted {
+            ""
+        } else if !endpoint_stats.is_secure {
             " 🚨"
         } else if endpoint_stats.success_rate < 0.9 {
             " ⚠️"
@@ -277,7 +334,7 @@ fn draw_enhanced_endpoints_list(f: &mut Frame, app: &App, area: Rect) {
         };
 
         let item_style = if is_selected {
-            Style::default().bg(Color::DarkGray)
+            theme.style_row_highlight()
         } else {
             Style::default()
         };
@@ -285,21 +342,22 @@ fn draw_enhanced_endpoints_list(f: &mut Frame, app: &App, area: Rect) {
         items.push(
             ListItem::new(vec![
                 Line::from(vec![
-                    Span::styled(protocol_icon, Style::default().fg(security_color)),
+                    Span::styled(protocol_icon, security_style),
                     Span::raw(" "),
-                    Span::styled(display_endpoint, Style::default().fg(Color::White)),
-                    Span::styled(risk_indicator, Style::default().fg(Color::Red)),
+                    Span::styled(display_endpoint, theme.style_text()),
+                    Span::styled(risk_indicator, theme.style_insecure()),
+                    Span::styled(trusted_badge(endpoint_stats), theme.style_info()),
                 ]),
                 Line::from(vec![
                     Span::raw("   "),
                     Span::styled(
                         format!("{}× used", endpoint_stats.usage_count),
-                        Style::default().fg(Color::Gray),
+                        theme.style_text_dim(),
                     ),
                     Span::raw(" • "),
                     Span::styled(
                         format!("{:.1}% success", endpoint_stats.success_rate * 100.0),
-                        Style::default().fg(Color::Green),
+                        theme.style_success(),
                     ),
                 ]),
             ])
@@ -315,11 +373,11 @@ fn draw_enhanced_endpoints_list(f: &mut Frame, app: &App, area: Rect) {
         items.push(ListItem::new(vec![
             Line::from(vec![Span::styled(
                 "🔍 No network endpoints found",
-                Style::default().fg(Color::Yellow),
+                theme.style_warning(),
             )]),
             Line::from(vec![Span::styled(
                 "   Run commands with network activity",
-                Style::default().fg(Color::Gray),
+                theme.style_text_dim(),
             )]),
         ]));
     }
@@ -330,39 +388,35 @@ fn draw_enhanced_endpoints_list(f: &mut Frame, app: &App, area: Rect) {
                 .title("🌐 Network Endpoints")
                 .borders(Borders::ALL),
         )
-        .style(Style::default().fg(Color::White));
+        .style(theme.style_text());
 
     f.render_widget(endpoints_list, area);
 }
 
 fn draw_security_issues_panel(f: &mut Frame, app: &App, area: Rect) {
-    let analyzer = NetworkAnalyzer::new();
-    let analysis = analyzer.analyze_network_activity(&app.commands);
+    let theme = &app.theme;
+
+    let analysis = &app.analysis().network;
 
     let mut items = Vec::new();
 
     for (i, issue) in analysis.security_issues.iter().enumerate() {
         let (severity_icon, severity_color) = match issue.severity {
-            crate::analysis::network_analyzer::SecuritySeverity::Critical => ("🚨", Color::Red),
-            crate::analysis::network_analyzer::SecuritySeverity::High => ("⚠️", Color::Red),
-            crate::analysis::network_analyzer::SecuritySeverity::Medium => ("⚡", Color::Yellow),
-            crate::analysis::network_analyzer::SecuritySeverity::Low => ("ℹ️", Color::Blue),
+            crate::analysis::network_analyzer::SecuritySeverity::Critical => ("🚨", theme.danger),
+            crate::analysis::network_analyzer::SecuritySeverity::High => ("⚠️", theme.danger),
+            crate::analysis::network_analyzer::SecuritySeverity::Medium => ("⚡", theme.warning),
+            crate::analysis::network_analyzer::SecuritySeverity::Low => ("ℹ️", theme.secondary),
         };
 
         items.push(ListItem::new(vec![
             Line::from(vec![
                 Span::styled(severity_icon, Style::default().fg(severity_color)),
                 Span::raw(" "),
-                Span::styled(
-                    &issue.issue_type,
-                    Style::default()
-                        .fg(Color::White)
-                        .add_modifier(Modifier::BOLD),
-                ),
+                Span::styled(&issue.issue_type, theme.style_value()),
             ]),
             Line::from(vec![
                 Span::raw("   "),
-                Span::styled(&issue.description, Style::default().fg(Color::Gray)),
+                Span::styled(&issue.description, theme.style_text_dim()),
             ]),
         ]));
 
@@ -375,11 +429,11 @@ fn draw_security_issues_panel(f: &mut Frame, app: &App, area: Rect) {
         items.push(ListItem::new(vec![
             Line::from(vec![Span::styled(
                 "✅ No security issues found",
-                Style::default().fg(Color::Green),
+                theme.style_success(),
             )]),
             Line::from(vec![Span::styled(
                 "   Your network usage looks secure",
-                Style::default().fg(Color::Gray),
+                theme.style_text_dim(),
             )]),
         ]));
     }
@@ -390,44 +444,37 @@ fn draw_security_issues_panel(f: &mut Frame, app: &App, area: Rect) {
                 .title("🔒 Security Issues")
                 .borders(Borders::ALL),
         )
-        .style(Style::default().fg(Color::White));
+        .style(theme.style_text());
 
     f.render_widget(issues_list, area);
 }
 
 fn draw_connection_patterns_panel(f: &mut Frame, app: &App, area: Rect) {
-    let analyzer = NetworkAnalyzer::new();
-    let analysis = analyzer.analyze_network_activity(&app.commands);
+    let theme = &app.theme;
+
+    let analysis = &app.analysis().network;
 
     let mut items = Vec::new();
 
     for (i, pattern) in analysis.connection_patterns.iter().enumerate() {
         let (risk_icon, risk_color) = match pattern.risk_level {
-            crate::analysis::network_analyzer::SecuritySeverity::Critical => ("🚨", Color::Red),
-            crate::analysis::network_analyzer::SecuritySeverity::High => ("⚠️", Color::Red),
-            crate::analysis::network_analyzer::SecuritySeverity::Medium => ("⚡", Color::Yellow),
-            crate::analysis::network_analyzer::SecuritySeverity::Low => ("✅", Color::Green),
+            crate::analysis::network_analyzer::SecuritySeverity::Critical => ("🚨", theme.danger),
+            crate::analysis::network_analyzer::SecuritySeverity::High => ("⚠️", theme.danger),
+            crate::analysis::network_analyzer::SecuritySeverity::Medium => ("⚡", theme.warning),
+            crate::analysis::network_analyzer::SecuritySeverity::Low => ("✅", theme.success),
         };
 
         items.push(ListItem::new(vec![
             Line::from(vec![
                 Span::styled(risk_icon, Style::default().fg(risk_color)),
                 Span::raw(" "),
-                Span::styled(
-                    &pattern.pattern_type,
-                    Style::default()
-                        .fg(Color::White)
-                        .add_modifier(Modifier::BOLD),
-                ),
+                Span::styled(&pattern.pattern_type, theme.style_value()),
             ]),
             Line::from(vec![
                 Span::raw("   "),
-                Span::styled(&pattern.description, Style::default().fg(Color::Gray)),
+                Span::styled(&pattern.description, theme.style_text_dim()),
                 Span::raw(" "),
-                Span::styled(
-                    format!("({}×)", pattern.frequency),
-                    Style::default().fg(Color::Cyan),
-                ),
+                Span::styled(format!("({}×)", pattern.frequency), theme.style_info()),
             ]),
         ]));
 
@@ -440,11 +487,11 @@ fn draw_connection_patterns_panel(f: &mut Frame, app: &App, area: Rect) {
         items.push(ListItem::new(vec![
             Line::from(vec![Span::styled(
                 "📊 No patterns detected",
-                Style::default().fg(Color::Yellow),
+                theme.style_warning(),
             )]),
             Line::from(vec![Span::styled(
                 "   More data needed for analysis",
-                Style::default().fg(Color::Gray),
+                theme.style_text_dim(),
             )]),
         ]));
     }
@@ -455,14 +502,66 @@ fn draw_connection_patterns_panel(f: &mut Frame, app: &App, area: Rect) {
                 .title("📊 Connection Patterns")
                 .borders(Borders::ALL),
         )
-        .style(Style::default().fg(Color::White));
+        .style(theme.style_text());
 
     f.render_widget(patterns_list, area);
 }
 
+fn draw_host_endpoints_panel(f: &mut Frame, app: &App, host: &str, area: Rect) {
+    let theme = &app.theme;
+
+    let analysis = app.analysis();
+    let endpoints = analysis
+        .endpoints_by_host
+        .get(host)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    let rows = area.height.saturating_sub(2) as usize;
+    let mut items: Vec<ListItem> = endpoints
+        .iter()
+        .take(rows)
+        .map(|stats| {
+            let security_style = if stats.is_secure {
+                theme.style_success()
+            } else {
+                theme.style_warning()
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{:<6}", stats.protocol), security_style),
+                Span::styled(format!("{:>4}× ", stats.usage_count), theme.style_info()),
+                Span::styled(&stats.endpoint, theme.style_value()),
+                Span::styled(trusted_badge(stats), theme.style_info()),
+            ]))
+        })
+        .collect();
+
+    if items.is_empty() {
+        items.push(ListItem::new(Line::from(Span::styled(
+            "No network endpoints from this host",
+            theme.style_text_dim(),
+        ))));
+    }
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title(format!(
+                    "🖥 Endpoints from {} ({})  Esc: all hosts",
+                    host,
+                    endpoints.len()
+                ))
+                .borders(Borders::ALL),
+        )
+        .style(theme.style_text());
+
+    f.render_widget(list, area);
+}
+
 fn draw_network_analytics(f: &mut Frame, app: &App, area: Rect) {
-    let analyzer = NetworkAnalyzer::new();
-    let analysis = analyzer.analyze_network_activity(&app.commands);
+    let theme = &app.theme;
+
+    let analysis = &app.analysis().network;
 
     let analytics_chunks = Layout::default()
         .direction(Direction::Horizontal)
@@ -477,19 +576,26 @@ fn draw_network_analytics(f: &mut Frame, app: &App, area: Rect) {
         .split(area);
 
     // Left: Protocol distribution chart
-    draw_protocol_chart(f, &analysis, analytics_chunks[0]);
+    draw_protocol_chart(f, analysis, analytics_chunks[0], theme);
 
     // Middle: Security gauge
-    draw_security_gauge(f, &analysis, &analyzer, analytics_chunks[1]);
+    draw_security_gauge(
+        f,
+        analysis,
+        &NetworkAnalyzer::new(),
+        analytics_chunks[1],
+        theme,
+    );
 
     // Right: Real-time insights
-    draw_realtime_insights(f, &analysis, analytics_chunks[2]);
+    draw_realtime_insights(f, analysis, analytics_chunks[2], theme);
 }
 
 fn draw_protocol_chart(
     f: &mut Frame,
     analysis: &crate::analysis::network_analyzer::NetworkAnalysis,
     area: Rect,
+    theme: &Theme,
 ) {
     let mut chart_data = Vec::new();
 
@@ -512,26 +618,20 @@ fn draw_protocol_chart(
             )
             .data(&chart_data)
             .bar_width(8)
-            .bar_style(Style::default().fg(Color::Cyan))
-            .value_style(
-                Style::default()
-                    .fg(Color::White)
-                    .add_modifier(Modifier::BOLD),
-            );
+            .bar_style(theme.style_info())
+            .value_style(theme.style_value());
 
         f.render_widget(bar_chart, area);
     } else {
         let no_data = Paragraph::new(vec![
             Line::from(vec![Span::styled(
                 "📊 Protocol Usage",
-                Style::default()
-                    .fg(Color::Cyan)
-                    .add_modifier(Modifier::BOLD),
+                theme.style_info().add_modifier(Modifier::BOLD),
             )]),
             Line::from(""),
             Line::from(vec![Span::styled(
                 "No protocol data available",
-                Style::default().fg(Color::Gray),
+                theme.style_text_dim(),
             )]),
         ])
         .block(Block::default().borders(Borders::ALL))
@@ -541,22 +641,25 @@ fn draw_protocol_chart(
     }
 }
 
+fn trusted_badge(stats: &EndpointStats) -> &'static str {
+    if stats.is_trusted {
+        " [trusted]"
+    } else {
+        ""
+    }
+}
+
 fn draw_security_gauge(
     f: &mut Frame,
     analysis: &crate::analysis::network_analyzer::NetworkAnalysis,
     analyzer: &NetworkAnalyzer,
     area: Rect,
+    theme: &Theme,
 ) {
     let security_score = analyzer.calculate_network_security_score(analysis);
     let security_percentage = security_score as u16;
 
-    let gauge_color = if security_score > 80.0 {
-        Color::Green
-    } else if security_score > 60.0 {
-        Color::Yellow
-    } else {
-        Color::Red
-    };
+    let gauge_color = theme.score_color(security_score);
 
     let security_gauge = Gauge::default()
         .block(
@@ -575,24 +678,23 @@ fn draw_realtime_insights(
     f: &mut Frame,
     analysis: &crate::analysis::network_analyzer::NetworkAnalysis,
     area: Rect,
+    theme: &Theme,
 ) {
     let mut insights = Vec::new();
 
     insights.push(Line::from(vec![Span::styled(
         "⚡ Real-time Insights",
-        Style::default()
-            .fg(Color::Yellow)
-            .add_modifier(Modifier::BOLD),
+        theme.style_warning().add_modifier(Modifier::BOLD),
     )]));
     insights.push(Line::from(""));
 
     // Network activity insight
     if analysis.total_network_commands > 0 {
         insights.push(Line::from(vec![
-            Span::styled("📈 Activity: ", Style::default().fg(Color::Cyan)),
+            Span::styled("📈 Activity: ", theme.style_info()),
             Span::styled(
                 format!("{} network commands", analysis.total_network_commands),
-                Style::default().fg(Color::White),
+                theme.style_text(),
             ),
         ]));
     }
@@ -610,41 +712,38 @@ fn draw_realtime_insights(
     };
 
     insights.push(Line::from(vec![
-        Span::styled("🔒 Security: ", Style::default().fg(Color::Cyan)),
+        Span::styled("🔒 Security: ", theme.style_info()),
         Span::styled(
             format!("{}% endpoints secure", secure_ratio),
-            Style::default().fg(Color::Green),
+            theme.style_secure(),
         ),
     ]));
 
     // Protocol insight
-    let https_count = analysis.protocol_breakdown.get("https").unwrap_or(&0);
+    let https_count = analysis.protocol_breakdown.get("HTTPS").unwrap_or(&0);
     let total_protocols = analysis.protocol_breakdown.values().sum::<usize>();
     if total_protocols > 0 {
         let https_ratio = (*https_count as f32 / total_protocols as f32 * 100.0) as u16;
         insights.push(Line::from(vec![
-            Span::styled("🌐 HTTPS: ", Style::default().fg(Color::Cyan)),
-            Span::styled(
-                format!("{}% of traffic", https_ratio),
-                Style::default().fg(Color::Green),
-            ),
+            Span::styled("🌐 HTTPS: ", theme.style_info()),
+            Span::styled(format!("{}% of traffic", https_ratio), theme.style_secure()),
         ]));
     }
 
     // Issues insight
     if !analysis.security_issues.is_empty() {
         insights.push(Line::from(vec![
-            Span::styled("⚠️  Issues: ", Style::default().fg(Color::Red)),
+            Span::styled("⚠️  Issues: ", theme.style_danger()),
             Span::styled(
                 format!("{} security alerts", analysis.security_issues.len()),
-                Style::default().fg(Color::Red),
+                theme.style_danger(),
             ),
         ]));
     }
 
     let insights_panel = Paragraph::new(insights)
         .block(Block::default().title("💡 Insights").borders(Borders::ALL))
-        .style(Style::default().fg(Color::White));
+        .style(theme.style_text());
 
     f.render_widget(insights_panel, area);
 }
@@ -0,0 +1,120 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    style::Modifier,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Gauge, Paragraph},
+    Frame,
+};
+
+use crate::history::background::BackgroundImport;
+use crate::onboarding::{format_size, HistorySource};
+use crate::ui::theme::Theme;
+
+/// The first-run screen: the history files that will be imported, then the
+/// import's progress once it's been started.
+pub fn draw(
+    f: &mut Frame,
+    sources: &[HistorySource],
+    import: Option<&BackgroundImport>,
+    theme: &Theme,
+) {
+    let area = super::centered_rect(70, 70, f.area());
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(3)].as_ref())
+        .split(area);
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            "Welcome to Whiskerlog",
+            theme.style_title().add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+    ];
+
+    if sources.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "No shell history files were found. Commands show up here once your shell writes them.",
+            theme.style_text_dim(),
+        )));
+    } else {
+        lines.push(Line::from(
+            "These history files will be imported into the local database:",
+        ));
+        lines.push(Line::from(""));
+        for (index, source) in sources.iter().enumerate() {
+            let style = match import {
+                Some(import) if import.current_file == index && import.finished.is_none() => {
+                    theme.style_selected()
+                }
+                _ => theme.style_text(),
+            };
+            lines.push(Line::from(vec![
+                Span::styled(format!("  {:<11}", source.shell), theme.style_info()),
+                Span::styled(
+                    format!("{:>9}  ", format_size(source.size)),
+                    theme.style_text_dim(),
+                ),
+                Span::styled(source.path.display().to_string(), style),
+            ]));
+        }
+    }
+
+    lines.push(Line::from(""));
+    lines.push(match import.and_then(|import| import.finished.as_ref()) {
+        Some(Err(err)) => Line::from(vec![
+            Span::styled("Import failed: ", theme.style_danger()),
+            Span::raw(err.clone()),
+            Span::styled("  r: retry  s: skip  q: quit", theme.style_text_dim()),
+        ]),
+        Some(Ok(_)) => Line::from(Span::styled("Import finished", theme.style_success())),
+        None if import.is_some() => Line::from(Span::styled(
+            "Importing… q quits and picks up where it left off next time",
+            theme.style_text_dim(),
+        )),
+        None => Line::from(vec![
+            Span::styled("Enter", theme.style_accent()),
+            Span::raw(": start importing   "),
+            Span::styled("q", theme.style_accent()),
+            Span::raw(": quit without importing"),
+        ]),
+    });
+
+    let welcome = Paragraph::new(lines)
+        .block(Block::default().title("First run").borders(Borders::ALL))
+        .style(theme.style_text());
+
+    let (ratio, label) = match import {
+        Some(import) => (
+            import_ratio(sources, import),
+            format!("{} commands imported", import.stored),
+        ),
+        None => (0.0, "Waiting to start".to_string()),
+    };
+    let gauge = Gauge::default()
+        .block(Block::default().borders(Borders::ALL))
+        .gauge_style(theme.style_success())
+        .ratio(ratio)
+        .label(label);
+
+    f.render_widget(Clear, area);
+    f.render_widget(welcome, chunks[0]);
+    f.render_widget(gauge, chunks[1]);
+}
+
+/// How far through the files the import is, weighted by their sizes.
+fn import_ratio(sources: &[HistorySource], import: &BackgroundImport) -> f64 {
+    if import.finished.is_some() {
+        return 1.0;
+    }
+    let total: u64 = sources.iter().map(|source| source.size).sum();
+    if total == 0 {
+        return 0.0;
+    }
+    let done: u64 = sources
+        .iter()
+        .take(import.current_file)
+        .map(|source| source.size)
+        .sum();
+    done as f64 / total as f64
+}
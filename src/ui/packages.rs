@@ -1,18 +1,17 @@
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{BarChart, Block, Borders, Gauge, List, ListItem, Paragraph},
+    widgets::{BarChart, Block, Borders, Clear, Gauge, List, ListItem, Paragraph},
     Frame,
 };
 use std::collections::HashMap;
 
-use crate::analysis::package_tracker::PackageTracker;
-use crate::app::App;
-use crate::ui::theme::get_manager_info;
+use crate::analysis::package_tracker::{PackageStats, PackageTracker};
+use crate::app::{App, PackageCommands};
+use crate::ui::theme::{get_manager_info, Icons, Theme};
 
 #[derive(Debug, Clone, PartialEq)]
-#[allow(dead_code)]
 pub enum PackageFilter {
     All,
     Linux,
@@ -21,6 +20,19 @@ pub enum PackageFilter {
     System,
 }
 
+impl PackageFilter {
+    /// Ecosystem name as returned by `categorize_manager`, or `None` for All.
+    fn ecosystem(&self) -> Option<&'static str> {
+        match self {
+            PackageFilter::All => None,
+            PackageFilter::Linux => Some("Linux"),
+            PackageFilter::Programming => Some("Programming"),
+            PackageFilter::Container => Some("Container"),
+            PackageFilter::System => Some("System"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 #[allow(dead_code)]
 pub enum PackageSortMode {
@@ -44,12 +56,10 @@ pub fn draw(f: &mut Frame, app: &App, area: Rect) {
         )
         .split(area);
 
-    // Compute analysis once for efficiency
     let tracker = PackageTracker::new();
-    let analysis = tracker.analyze_package_usage(&app.commands);
 
     // Apply filtering based on current filter mode
-    let filtered_analysis = apply_package_filter(&analysis, &PackageFilter::All);
+    let filtered_analysis = apply_package_filter(&app.analysis().packages, &app.package_filter);
 
     // Enhanced header with package ecosystem metrics
     draw_package_metrics(f, app, &filtered_analysis, chunks[0]);
@@ -62,14 +72,56 @@ pub fn draw(f: &mut Frame, app: &App, area: Rect) {
 
     // Analytics and insights footer
     draw_package_analytics(f, app, &filtered_analysis, &tracker, chunks[3]);
+
+    if app.package_detail_open {
+        let rows = package_rows(&filtered_analysis);
+        if let Some((manager, stats)) = rows.get(app.selected_index) {
+            draw_package_detail(f, app, &tracker, manager, stats, area);
+        }
+    }
+
+    if let Some(package_commands) = &app.package_commands {
+        draw_package_commands(f, app, package_commands, area);
+    }
+}
+
+/// Packages across all managers, most used first. The Packages tab's selection
+/// indexes into this list.
+pub fn package_rows(
+    analysis: &crate::analysis::package_tracker::PackageAnalysis,
+) -> Vec<(String, PackageStats)> {
+    let mut rows: Vec<(String, PackageStats)> = analysis
+        .managers_used
+        .iter()
+        .flat_map(|manager_stats| {
+            manager_stats
+                .top_packages
+                .iter()
+                .map(|package| (manager_stats.manager.clone(), package.clone()))
+        })
+        .collect();
+
+    rows.sort_by(|a, b| {
+        let usage_a = a.1.install_count + a.1.remove_count;
+        let usage_b = b.1.install_count + b.1.remove_count;
+        usage_b
+            .cmp(&usage_a)
+            .then_with(|| a.1.name.cmp(&b.1.name))
+            .then_with(|| a.0.cmp(&b.0))
+    });
+    rows
 }
 
+const PACKAGES_PER_PAGE: usize = 10;
+
 fn draw_package_metrics(
     f: &mut Frame,
-    _app: &App,
+    app: &App,
     analysis: &crate::analysis::package_tracker::PackageAnalysis,
     area: Rect,
 ) {
+    let theme = &app.theme;
+
     // Create 4-column layout for metrics
     let metric_chunks = Layout::default()
         .direction(Direction::Horizontal)
@@ -89,24 +141,14 @@ fn draw_package_metrics(
     let operations_block = Paragraph::new(vec![
         Line::from(vec![Span::styled(
             "📦 OPERATIONS",
-            Style::default()
-                .fg(Color::Green)
-                .add_modifier(Modifier::BOLD),
+            theme.style_success().add_modifier(Modifier::BOLD),
         )]),
         Line::from(""),
         Line::from(vec![
-            Span::styled(
-                format!("{}", operations_count),
-                Style::default()
-                    .fg(Color::White)
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::styled(" total", Style::default().fg(Color::Gray)),
+            Span::styled(format!("{}", operations_count), theme.style_value()),
+            Span::styled(" total", theme.style_text_dim()),
         ]),
-        Line::from(vec![Span::styled(
-            "Package actions",
-            Style::default().fg(Color::Yellow),
-        )]),
+        Line::from(vec![Span::styled("Package actions", theme.style_warning())]),
     ])
     .block(Block::default().borders(Borders::ALL))
     .alignment(Alignment::Center);
@@ -117,24 +159,14 @@ fn draw_package_metrics(
     let managers_block = Paragraph::new(vec![
         Line::from(vec![Span::styled(
             "🛠️  MANAGERS",
-            Style::default()
-                .fg(Color::Blue)
-                .add_modifier(Modifier::BOLD),
+            theme.style_secondary().add_modifier(Modifier::BOLD),
         )]),
         Line::from(""),
         Line::from(vec![
-            Span::styled(
-                format!("{}", managers_count),
-                Style::default()
-                    .fg(Color::White)
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::styled(" active", Style::default().fg(Color::Gray)),
+            Span::styled(format!("{}", managers_count), theme.style_value()),
+            Span::styled(" active", theme.style_text_dim()),
         ]),
-        Line::from(vec![Span::styled(
-            "Ecosystems",
-            Style::default().fg(Color::Yellow),
-        )]),
+        Line::from(vec![Span::styled("Ecosystems", theme.style_warning())]),
     ])
     .block(Block::default().borders(Borders::ALL))
     .alignment(Alignment::Center);
@@ -143,13 +175,7 @@ fn draw_package_metrics(
     // Package Health Metric
     let tracker = PackageTracker::new();
     let health_score = tracker.calculate_package_health_score(analysis);
-    let health_color = if health_score > 80.0 {
-        Color::Green
-    } else if health_score > 60.0 {
-        Color::Yellow
-    } else {
-        Color::Red
-    };
+    let health_color = theme.score_color(health_score);
     let health_block = Paragraph::new(vec![
         Line::from(vec![Span::styled(
             "💊 HEALTH",
@@ -159,17 +185,12 @@ fn draw_package_metrics(
         )]),
         Line::from(""),
         Line::from(vec![
-            Span::styled(
-                format!("{:.0}%", health_score),
-                Style::default()
-                    .fg(Color::White)
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::styled(" score", Style::default().fg(Color::Gray)),
+            Span::styled(format!("{:.0}%", health_score), theme.style_value()),
+            Span::styled(" score", theme.style_text_dim()),
         ]),
         Line::from(vec![Span::styled(
             "Ecosystem health",
-            Style::default().fg(Color::Yellow),
+            theme.style_warning(),
         )]),
     ])
     .block(Block::default().borders(Borders::ALL))
@@ -182,11 +203,11 @@ fn draw_package_metrics(
     let security_issues = count_security_issues(analysis);
     let total_issues = trends_count + conflicts_count + security_issues;
     let issues_color = if total_issues == 0 {
-        Color::Green
+        theme.success
     } else if total_issues < 3 {
-        Color::Yellow
+        theme.warning
     } else {
-        Color::Red
+        theme.danger
     };
     let issues_block = Paragraph::new(vec![
         Line::from(vec![Span::styled(
@@ -197,17 +218,12 @@ fn draw_package_metrics(
         )]),
         Line::from(""),
         Line::from(vec![
-            Span::styled(
-                format!("{}", total_issues),
-                Style::default()
-                    .fg(Color::White)
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::styled(" issues", Style::default().fg(Color::Gray)),
+            Span::styled(format!("{}", total_issues), theme.style_value()),
+            Span::styled(" issues", theme.style_text_dim()),
         ]),
         Line::from(vec![Span::styled(
             "Security & conflicts",
-            Style::default().fg(Color::Yellow),
+            theme.style_warning(),
         )]),
     ])
     .block(Block::default().borders(Borders::ALL))
@@ -215,27 +231,29 @@ fn draw_package_metrics(
     f.render_widget(issues_block, metric_chunks[3]);
 }
 
-fn draw_package_controls(f: &mut Frame, _app: &App, area: Rect) {
-    let current_filter = PackageFilter::All; // Would be stored in app state
+fn draw_package_controls(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+
+    let current_filter = &app.package_filter;
     let current_sort = PackageSortMode::Usage; // Would be stored in app state
 
     let filter_indicator = match current_filter {
-        PackageFilter::All => ("🌐", "All Ecosystems", Color::White),
-        PackageFilter::Linux => ("🐧", "Linux Packages", Color::Blue),
-        PackageFilter::Programming => ("💻", "Programming", Color::Green),
-        PackageFilter::Container => ("🐳", "Containers", Color::Cyan),
-        PackageFilter::System => ("⚙️", "System Tools", Color::Yellow),
+        PackageFilter::All => ("🌐", "All Ecosystems", theme.text),
+        PackageFilter::Linux => ("🐧", "Linux Packages", theme.secondary),
+        PackageFilter::Programming => ("💻", "Programming", theme.success),
+        PackageFilter::Container => ("🐳", "Containers", theme.info),
+        PackageFilter::System => ("⚙️", "System Tools", theme.warning),
     };
 
     let sort_indicator = match current_sort {
-        PackageSortMode::Usage => ("📊", "Usage", Color::Green),
-        PackageSortMode::Trends => ("📈", "Trends", Color::Yellow),
-        PackageSortMode::Health => ("💊", "Health", Color::Blue),
-        PackageSortMode::Recent => ("🕒", "Recent", Color::Magenta),
+        PackageSortMode::Usage => ("📊", "Usage", theme.success),
+        PackageSortMode::Trends => ("📈", "Trends", theme.warning),
+        PackageSortMode::Health => ("💊", "Health", theme.secondary),
+        PackageSortMode::Recent => ("🕒", "Recent", theme.accent),
     };
 
     let controls_text = vec![Line::from(vec![
-        Span::styled("Active Filter: ", Style::default().fg(Color::Cyan)),
+        Span::styled("Active Filter: ", theme.style_info()),
         Span::styled(filter_indicator.0, Style::default().fg(filter_indicator.2)),
         Span::raw(" "),
         Span::styled(
@@ -245,7 +263,7 @@ fn draw_package_controls(f: &mut Frame, _app: &App, area: Rect) {
                 .add_modifier(Modifier::BOLD),
         ),
         Span::raw("  |  "),
-        Span::styled("Sort: ", Style::default().fg(Color::Cyan)),
+        Span::styled("Sort: ", theme.style_info()),
         Span::styled(sort_indicator.0, Style::default().fg(sort_indicator.2)),
         Span::raw(" "),
         Span::styled(
@@ -255,15 +273,17 @@ fn draw_package_controls(f: &mut Frame, _app: &App, area: Rect) {
                 .add_modifier(Modifier::BOLD),
         ),
         Span::raw("  |  "),
-        Span::styled("Controls: ", Style::default().fg(Color::Gray)),
-        Span::styled("[L]", Style::default().fg(Color::Blue)),
-        Span::styled("inux ", Style::default().fg(Color::White)),
-        Span::styled("[P]", Style::default().fg(Color::Green)),
-        Span::styled("rog ", Style::default().fg(Color::White)),
-        Span::styled("[C]", Style::default().fg(Color::Cyan)),
-        Span::styled("ontainer ", Style::default().fg(Color::White)),
-        Span::styled("[S]", Style::default().fg(Color::Yellow)),
-        Span::styled("ystem", Style::default().fg(Color::White)),
+        Span::styled("Controls: ", theme.style_text_dim()),
+        Span::styled("[L]", theme.style_secondary()),
+        Span::styled("inux ", theme.style_text()),
+        Span::styled("[P]", theme.style_success()),
+        Span::styled("rog ", theme.style_text()),
+        Span::styled("[C]", theme.style_info()),
+        Span::styled("ontainer ", theme.style_text()),
+        Span::styled("[S]", theme.style_warning()),
+        Span::styled("ystem ", theme.style_text()),
+        Span::styled("[A]", theme.style_text()),
+        Span::styled("ll", theme.style_text()),
     ])];
 
     let controls = Paragraph::new(controls_text)
@@ -272,17 +292,19 @@ fn draw_package_controls(f: &mut Frame, _app: &App, area: Rect) {
                 .title("🎛️  Package Controls & Filters")
                 .borders(Borders::ALL),
         )
-        .style(Style::default().fg(Color::White));
+        .style(theme.style_text());
 
     f.render_widget(controls, area);
 }
 
 fn draw_package_content(
     f: &mut Frame,
-    _app: &App,
+    app: &App,
     analysis: &crate::analysis::package_tracker::PackageAnalysis,
     area: Rect,
 ) {
+    let theme = &app.theme;
+
     // Split into three panels: managers, packages, and trends
     let content_chunks = Layout::default()
         .direction(Direction::Horizontal)
@@ -296,29 +318,35 @@ fn draw_package_content(
         )
         .split(area);
 
+    let rows = package_rows(analysis);
+    let selected_manager = rows
+        .get(app.selected_index)
+        .map(|(manager, _)| manager.as_str());
+
     // Left panel: Enhanced package managers
-    draw_enhanced_managers_list(f, _app, analysis, content_chunks[0]);
+    draw_enhanced_managers_list(f, selected_manager, analysis, content_chunks[0], theme);
 
     // Middle panel: Top packages with details
-    draw_enhanced_packages_list(f, _app, analysis, content_chunks[1]);
+    draw_enhanced_packages_list(f, app, &rows, content_chunks[1]);
 
     // Right panel: Trends and version conflicts
-    draw_trends_and_conflicts(f, _app, analysis, content_chunks[2]);
+    draw_trends_and_conflicts(f, app, analysis, content_chunks[2]);
 }
 
 fn draw_enhanced_managers_list(
     f: &mut Frame,
-    _app: &App,
+    selected_manager: Option<&str>,
     analysis: &crate::analysis::package_tracker::PackageAnalysis,
     area: Rect,
+    theme: &Theme,
 ) {
     let mut items = Vec::new();
 
     for (i, manager_stats) in analysis.managers_used.iter().enumerate() {
-        let is_selected = i == 0; // Remove selection logic for now
+        let is_selected = selected_manager == Some(manager_stats.manager.as_str());
 
         // Enhanced manager icons with ecosystem classification
-        let (manager_icon, ecosystem_color) = get_manager_info(&manager_stats.manager);
+        let (manager_icon, ecosystem_color) = get_manager_info(&manager_stats.manager, theme);
 
         // Calculate activity score
         let total_activity = manager_stats.total_operations;
@@ -331,7 +359,7 @@ fn draw_enhanced_managers_list(
         };
 
         let item_style = if is_selected {
-            Style::default().bg(Color::DarkGray)
+            theme.style_row_highlight()
         } else {
             Style::default()
         };
@@ -341,35 +369,24 @@ fn draw_enhanced_managers_list(
                 Line::from(vec![
                     Span::styled(manager_icon, Style::default().fg(ecosystem_color)),
                     Span::raw(" "),
-                    Span::styled(
-                        &manager_stats.manager,
-                        Style::default()
-                            .fg(Color::White)
-                            .add_modifier(Modifier::BOLD),
-                    ),
+                    Span::styled(&manager_stats.manager, theme.style_value()),
                     Span::raw(" "),
-                    Span::styled(activity_level, Style::default().fg(Color::Yellow)),
+                    Span::styled(activity_level, theme.style_warning()),
                 ]),
                 Line::from(vec![
                     Span::raw("   "),
                     Span::styled(
                         format!("↗{}", manager_stats.installs),
-                        Style::default().fg(Color::Green),
+                        theme.style_success(),
                     ),
                     Span::raw(" "),
-                    Span::styled(
-                        format!("↘{}", manager_stats.removes),
-                        Style::default().fg(Color::Red),
-                    ),
+                    Span::styled(format!("↘{}", manager_stats.removes), theme.style_danger()),
                     Span::raw(" "),
-                    Span::styled(
-                        format!("↻{}", manager_stats.updates),
-                        Style::default().fg(Color::Yellow),
-                    ),
+                    Span::styled(format!("↻{}", manager_stats.updates), theme.style_warning()),
                     Span::raw(" • "),
                     Span::styled(
                         format!("{} pkgs", manager_stats.top_packages.len()),
-                        Style::default().fg(Color::Cyan),
+                        theme.style_info(),
                     ),
                 ]),
             ])
@@ -385,11 +402,11 @@ fn draw_enhanced_managers_list(
         items.push(ListItem::new(vec![
             Line::from(vec![Span::styled(
                 "📦 No package managers detected",
-                Style::default().fg(Color::Yellow),
+                theme.style_warning(),
             )]),
             Line::from(vec![Span::styled(
                 "   Run package installation commands",
-                Style::default().fg(Color::Gray),
+                theme.style_text_dim(),
             )]),
         ]));
     }
@@ -400,39 +417,34 @@ fn draw_enhanced_managers_list(
                 .title("🛠️  Package Managers")
                 .borders(Borders::ALL),
         )
-        .style(Style::default().fg(Color::White));
+        .style(theme.style_text());
 
     f.render_widget(managers_list, area);
 }
 
 fn draw_enhanced_packages_list(
     f: &mut Frame,
-    _app: &App,
-    analysis: &crate::analysis::package_tracker::PackageAnalysis,
+    app: &App,
+    rows: &[(String, PackageStats)],
     area: Rect,
 ) {
-    let mut items = Vec::new();
+    let theme = &app.theme;
 
-    // Collect all packages from all managers
-    let mut all_packages = Vec::new();
-    for manager_stats in &analysis.managers_used {
-        for package_stats in &manager_stats.top_packages {
-            all_packages.push((manager_stats.manager.clone(), package_stats));
-        }
-    }
+    let mut items = Vec::new();
 
-    // Sort by total usage (install + remove counts)
-    all_packages.sort_by(|a, b| {
-        let usage_a = a.1.install_count + a.1.remove_count;
-        let usage_b = b.1.install_count + b.1.remove_count;
-        usage_b.cmp(&usage_a)
-    });
+    // Page through the list so the selection is always visible
+    let page_start = (app.selected_index / PACKAGES_PER_PAGE) * PACKAGES_PER_PAGE;
 
-    for (i, (manager, package_stats)) in all_packages.iter().enumerate() {
-        let is_selected = i == 0; // Remove selection logic for now
+    for (i, (manager, package_stats)) in rows
+        .iter()
+        .enumerate()
+        .skip(page_start)
+        .take(PACKAGES_PER_PAGE)
+    {
+        let is_selected = i == app.selected_index;
 
         // Get manager info for styling
-        let (manager_icon, ecosystem_color) = get_manager_info(manager);
+        let (manager_icon, ecosystem_color) = get_manager_info(manager, theme);
 
         // Calculate package popularity
         let total_usage = package_stats.install_count + package_stats.remove_count;
@@ -446,15 +458,15 @@ fn draw_enhanced_packages_list(
 
         // Version stability indicator
         let stability = if package_stats.versions_seen.len() > 3 {
-            ("🔄", Color::Red) // Version churn
+            ("🔄", theme.danger) // Version churn
         } else if package_stats.versions_seen.len() > 1 {
-            ("📈", Color::Yellow) // Some versions
+            ("📈", theme.warning) // Some versions
         } else {
-            ("✅", Color::Green) // Stable
+            ("✅", theme.success) // Stable
         };
 
         let item_style = if is_selected {
-            Style::default().bg(Color::DarkGray)
+            theme.style_row_highlight()
         } else {
             Style::default()
         };
@@ -464,14 +476,9 @@ fn draw_enhanced_packages_list(
                 Line::from(vec![
                     Span::styled(manager_icon, Style::default().fg(ecosystem_color)),
                     Span::raw(" "),
-                    Span::styled(
-                        &package_stats.name,
-                        Style::default()
-                            .fg(Color::White)
-                            .add_modifier(Modifier::BOLD),
-                    ),
+                    Span::styled(&package_stats.name, theme.style_value()),
                     Span::raw(" "),
-                    Span::styled(popularity, Style::default().fg(Color::Yellow)),
+                    Span::styled(popularity, theme.style_warning()),
                     Span::raw(" "),
                     Span::styled(stability.0, Style::default().fg(stability.1)),
                 ]),
@@ -482,34 +489,27 @@ fn draw_enhanced_packages_list(
                         Style::default().fg(ecosystem_color),
                     ),
                     Span::raw(" "),
-                    Span::styled(
-                        format!("{}× used", total_usage),
-                        Style::default().fg(Color::Gray),
-                    ),
+                    Span::styled(format!("{}× used", total_usage), theme.style_text_dim()),
                     Span::raw(" • "),
                     Span::styled(
                         format!("{} vers", package_stats.versions_seen.len()),
-                        Style::default().fg(Color::Cyan),
+                        theme.style_info(),
                     ),
                 ]),
             ])
             .style(item_style),
         );
-
-        if i >= 9 {
-            break;
-        } // Limit to 10 packages for display
     }
 
     if items.is_empty() {
         items.push(ListItem::new(vec![
             Line::from(vec![Span::styled(
                 "📦 No packages detected",
-                Style::default().fg(Color::Yellow),
+                theme.style_warning(),
             )]),
             Line::from(vec![Span::styled(
                 "   Install packages to see analysis",
-                Style::default().fg(Color::Gray),
+                theme.style_text_dim(),
             )]),
         ]));
     }
@@ -517,20 +517,31 @@ fn draw_enhanced_packages_list(
     let packages_list = List::new(items)
         .block(
             Block::default()
-                .title("📦 Top Packages")
+                .title(if rows.len() > PACKAGES_PER_PAGE {
+                    format!(
+                        "📦 Top Packages ({}-{} of {})",
+                        page_start + 1,
+                        (page_start + PACKAGES_PER_PAGE).min(rows.len()),
+                        rows.len()
+                    )
+                } else {
+                    "📦 Top Packages".to_string()
+                })
                 .borders(Borders::ALL),
         )
-        .style(Style::default().fg(Color::White));
+        .style(theme.style_text());
 
     f.render_widget(packages_list, area);
 }
 
 fn draw_trends_and_conflicts(
     f: &mut Frame,
-    _app: &App,
+    app: &App,
     analysis: &crate::analysis::package_tracker::PackageAnalysis,
     area: Rect,
 ) {
+    let theme = &app.theme;
+
     // Split into trends and conflicts sections
     let trend_chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -538,25 +549,26 @@ fn draw_trends_and_conflicts(
         .split(area);
 
     // Top: Package trends
-    draw_package_trends(f, analysis, trend_chunks[0]);
+    draw_package_trends(f, analysis, trend_chunks[0], theme);
 
     // Bottom: Version conflicts
-    draw_version_conflicts(f, analysis, trend_chunks[1]);
+    draw_version_conflicts(f, analysis, trend_chunks[1], theme);
 }
 
 fn draw_package_trends(
     f: &mut Frame,
     analysis: &crate::analysis::package_tracker::PackageAnalysis,
     area: Rect,
+    theme: &Theme,
 ) {
     let mut items = Vec::new();
 
     for (i, trend) in analysis.package_trends.iter().enumerate() {
         let (trend_icon, trend_color) = match trend.trend_type {
-            crate::analysis::package_tracker::TrendType::FrequentInstalls => ("📈", Color::Green),
-            crate::analysis::package_tracker::TrendType::RepeatedInstalls => ("🔄", Color::Yellow),
-            crate::analysis::package_tracker::TrendType::QuickRemoval => ("⚡", Color::Red),
-            crate::analysis::package_tracker::TrendType::VersionChurn => ("🌀", Color::Magenta),
+            crate::analysis::package_tracker::TrendType::FrequentInstalls => ("📈", theme.success),
+            crate::analysis::package_tracker::TrendType::RepeatedInstalls => ("🔄", theme.warning),
+            crate::analysis::package_tracker::TrendType::QuickRemoval => ("⚡", theme.danger),
+            crate::analysis::package_tracker::TrendType::VersionChurn => ("🌀", theme.accent),
         };
 
         let trend_description = match trend.trend_type {
@@ -567,7 +579,9 @@ fn draw_package_trends(
         };
 
         // Add time span information for better context
-        let time_info = if trend.time_span_days > 0 {
+        let time_info = if let Some(hours) = trend.hours_to_removal {
+            format!(" - installed then removed after {}h", hours)
+        } else if trend.time_span_days > 0 {
             format!(" over {} days", trend.time_span_days)
         } else {
             " recently".to_string()
@@ -577,27 +591,16 @@ fn draw_package_trends(
             Line::from(vec![
                 Span::styled(trend_icon, Style::default().fg(trend_color)),
                 Span::raw(" "),
-                Span::styled(
-                    trend.package.clone(),
-                    Style::default()
-                        .fg(Color::White)
-                        .add_modifier(Modifier::BOLD),
-                ),
+                Span::styled(trend.package.clone(), theme.style_value()),
                 Span::raw(" "),
-                Span::styled(
-                    format!("[{}]", trend.manager),
-                    Style::default().fg(Color::Gray),
-                ),
+                Span::styled(format!("[{}]", trend.manager), theme.style_text_dim()),
             ]),
             Line::from(vec![
                 Span::raw("   "),
-                Span::styled(trend_description, Style::default().fg(Color::Gray)),
+                Span::styled(trend_description, theme.style_text_dim()),
                 Span::raw(" • "),
-                Span::styled(
-                    format!("{}×", trend.frequency),
-                    Style::default().fg(Color::Cyan),
-                ),
-                Span::styled(time_info, Style::default().fg(Color::Yellow)),
+                Span::styled(format!("{}×", trend.frequency), theme.style_info()),
+                Span::styled(time_info, theme.style_warning()),
             ]),
         ]));
 
@@ -609,7 +612,7 @@ fn draw_package_trends(
     if items.is_empty() {
         items.push(ListItem::new(vec![Line::from(vec![Span::styled(
             "📊 No trends detected",
-            Style::default().fg(Color::Yellow),
+            theme.style_warning(),
         )])]));
     }
 
@@ -619,7 +622,7 @@ fn draw_package_trends(
                 .title("📊 Package Trends")
                 .borders(Borders::ALL),
         )
-        .style(Style::default().fg(Color::White));
+        .style(theme.style_text());
 
     f.render_widget(trends_list, area);
 }
@@ -628,17 +631,20 @@ fn draw_version_conflicts(
     f: &mut Frame,
     analysis: &crate::analysis::package_tracker::PackageAnalysis,
     area: Rect,
+    theme: &Theme,
 ) {
     let mut items = Vec::new();
 
     for (i, conflict) in analysis.version_conflicts.iter().enumerate() {
         let (conflict_icon, conflict_color) = match conflict.conflict_type {
-            crate::analysis::package_tracker::ConflictType::DowngradeDetected => ("⬇️", Color::Red),
+            crate::analysis::package_tracker::ConflictType::DowngradeDetected => {
+                ("⬇️", theme.danger)
+            }
             crate::analysis::package_tracker::ConflictType::MultipleVersions => {
-                ("🔀", Color::Yellow)
+                ("🔀", theme.warning)
             }
             crate::analysis::package_tracker::ConflictType::InconsistentVersioning => {
-                ("⚠️", Color::Magenta)
+                ("⚠️", theme.accent)
             }
         };
 
@@ -646,16 +652,11 @@ fn draw_version_conflicts(
             Line::from(vec![
                 Span::styled(conflict_icon, Style::default().fg(conflict_color)),
                 Span::raw(" "),
-                Span::styled(
-                    &conflict.package,
-                    Style::default()
-                        .fg(Color::White)
-                        .add_modifier(Modifier::BOLD),
-                ),
+                Span::styled(&conflict.package, theme.style_value()),
             ]),
             Line::from(vec![
                 Span::raw("   "),
-                Span::styled(&conflict.recommendation, Style::default().fg(Color::Gray)),
+                Span::styled(&conflict.recommendation, theme.style_text_dim()),
             ]),
         ]));
 
@@ -667,7 +668,7 @@ fn draw_version_conflicts(
     if items.is_empty() {
         items.push(ListItem::new(vec![Line::from(vec![Span::styled(
             "✅ No conflicts detected",
-            Style::default().fg(Color::Green),
+            theme.style_success(),
         )])]));
     }
 
@@ -677,18 +678,20 @@ fn draw_version_conflicts(
                 .title("⚠️  Version Conflicts")
                 .borders(Borders::ALL),
         )
-        .style(Style::default().fg(Color::White));
+        .style(theme.style_text());
 
     f.render_widget(conflicts_list, area);
 }
 
 fn draw_package_analytics(
     f: &mut Frame,
-    _app: &App,
+    app: &App,
     analysis: &crate::analysis::package_tracker::PackageAnalysis,
     tracker: &PackageTracker,
     area: Rect,
 ) {
+    let theme = &app.theme;
+
     let analytics_chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints(
@@ -702,19 +705,20 @@ fn draw_package_analytics(
         .split(area);
 
     // Left: Manager distribution chart
-    draw_manager_distribution_chart(f, analysis, analytics_chunks[0]);
+    draw_manager_distribution_chart(f, analysis, analytics_chunks[0], theme);
 
     // Middle: Package health gauge
-    draw_package_health_gauge(f, analysis, tracker, analytics_chunks[1]);
+    draw_package_health_gauge(f, analysis, tracker, analytics_chunks[1], theme);
 
     // Right: Ecosystem health and recommendations
-    draw_ecosystem_health_and_recommendations(f, analysis, analytics_chunks[2]);
+    draw_ecosystem_health_and_recommendations(f, analysis, analytics_chunks[2], theme);
 }
 
 fn draw_manager_distribution_chart(
     f: &mut Frame,
     analysis: &crate::analysis::package_tracker::PackageAnalysis,
     area: Rect,
+    theme: &Theme,
 ) {
     let mut chart_data = Vec::new();
 
@@ -742,26 +746,20 @@ fn draw_manager_distribution_chart(
             )
             .data(&chart_data)
             .bar_width(6)
-            .bar_style(Style::default().fg(Color::Green))
-            .value_style(
-                Style::default()
-                    .fg(Color::White)
-                    .add_modifier(Modifier::BOLD),
-            );
+            .bar_style(theme.style_success())
+            .value_style(theme.style_value());
 
         f.render_widget(bar_chart, area);
     } else {
         let no_data = Paragraph::new(vec![
             Line::from(vec![Span::styled(
                 "📊 Manager Usage",
-                Style::default()
-                    .fg(Color::Green)
-                    .add_modifier(Modifier::BOLD),
+                theme.style_success().add_modifier(Modifier::BOLD),
             )]),
             Line::from(""),
             Line::from(vec![Span::styled(
                 "No package data",
-                Style::default().fg(Color::Gray),
+                theme.style_text_dim(),
             )]),
         ])
         .block(Block::default().borders(Borders::ALL))
@@ -776,17 +774,12 @@ fn draw_package_health_gauge(
     analysis: &crate::analysis::package_tracker::PackageAnalysis,
     tracker: &PackageTracker,
     area: Rect,
+    theme: &Theme,
 ) {
     let health_score = tracker.calculate_package_health_score(analysis);
     let health_percentage = health_score as u16;
 
-    let gauge_color = if health_score > 80.0 {
-        Color::Green
-    } else if health_score > 60.0 {
-        Color::Yellow
-    } else {
-        Color::Red
-    };
+    let gauge_color = theme.score_color(health_score);
 
     let health_gauge = Gauge::default()
         .block(
@@ -805,6 +798,7 @@ fn draw_ecosystem_health_and_recommendations(
     f: &mut Frame,
     analysis: &crate::analysis::package_tracker::PackageAnalysis,
     area: Rect,
+    theme: &Theme,
 ) {
     // Split into ecosystem health and recommendations
     let health_chunks = Layout::default()
@@ -813,50 +807,43 @@ fn draw_ecosystem_health_and_recommendations(
         .split(area);
 
     // Top: Ecosystem health breakdown
-    draw_ecosystem_health_breakdown(f, analysis, health_chunks[0]);
+    draw_ecosystem_health_breakdown(f, analysis, health_chunks[0], theme);
 
     // Bottom: Recommendations
-    draw_package_recommendations(f, analysis, health_chunks[1]);
+    draw_package_recommendations(f, analysis, health_chunks[1], theme);
 }
 
 fn draw_ecosystem_health_breakdown(
     f: &mut Frame,
     analysis: &crate::analysis::package_tracker::PackageAnalysis,
     area: Rect,
+    theme: &Theme,
 ) {
     let ecosystem_health = calculate_ecosystem_health(analysis);
     let mut items = Vec::new();
 
     items.push(ListItem::new(vec![Line::from(vec![Span::styled(
         "🌐 Ecosystem Health",
-        Style::default()
-            .fg(Color::Cyan)
-            .add_modifier(Modifier::BOLD),
+        theme.style_info().add_modifier(Modifier::BOLD),
     )])]));
 
     for (ecosystem, health) in ecosystem_health.iter() {
         let (icon, color) = match ecosystem.as_str() {
-            "Linux" => ("🐧", Color::Blue),
-            "Programming" => ("💻", Color::Green),
-            "Container" => ("🐳", Color::Cyan),
-            "System" => ("⚙️", Color::Yellow),
-            "macOS" => ("🍎", Color::White),
-            _ => ("📦", Color::Gray),
+            "Linux" => ("🐧", theme.secondary),
+            "Programming" => ("💻", theme.success),
+            "Container" => ("🐳", theme.info),
+            "System" => ("⚙️", theme.warning),
+            "macOS" => ("🍎", theme.text),
+            _ => ("📦", theme.text_dim),
         };
 
-        let health_color = if *health > 80.0 {
-            Color::Green
-        } else if *health > 60.0 {
-            Color::Yellow
-        } else {
-            Color::Red
-        };
+        let health_color = theme.score_color(*health);
         let health_bar = "█".repeat((*health / 10.0) as usize);
 
         items.push(ListItem::new(vec![Line::from(vec![
             Span::styled(icon, Style::default().fg(color)),
             Span::raw(" "),
-            Span::styled(ecosystem, Style::default().fg(Color::White)),
+            Span::styled(ecosystem, theme.style_text()),
             Span::raw(" "),
             Span::styled(
                 format!("{:.0}%", health),
@@ -872,7 +859,7 @@ fn draw_ecosystem_health_breakdown(
     if ecosystem_health.is_empty() {
         items.push(ListItem::new(vec![Line::from(vec![Span::styled(
             "No ecosystem data available",
-            Style::default().fg(Color::Gray),
+            theme.style_text_dim(),
         )])]));
     }
 
@@ -882,7 +869,7 @@ fn draw_ecosystem_health_breakdown(
                 .title("🌐 Ecosystem Health")
                 .borders(Borders::ALL),
         )
-        .style(Style::default().fg(Color::White));
+        .style(theme.style_text());
 
     f.render_widget(health_list, area);
 }
@@ -891,14 +878,13 @@ fn draw_package_recommendations(
     f: &mut Frame,
     analysis: &crate::analysis::package_tracker::PackageAnalysis,
     area: Rect,
+    theme: &Theme,
 ) {
     let mut recommendations = Vec::new();
 
     recommendations.push(Line::from(vec![Span::styled(
         "💡 Recommendations",
-        Style::default()
-            .fg(Color::Yellow)
-            .add_modifier(Modifier::BOLD),
+        theme.style_warning().add_modifier(Modifier::BOLD),
     )]));
     recommendations.push(Line::from(""));
 
@@ -917,20 +903,20 @@ fn draw_package_recommendations(
         };
 
         recommendations.push(Line::from(vec![
-            Span::styled(icon, Style::default().fg(Color::Yellow)),
+            Span::styled(icon, theme.style_warning()),
             Span::raw(" "),
-            Span::styled(recommendation, Style::default().fg(Color::White)),
+            Span::styled(recommendation, theme.style_text()),
         ]));
     }
 
     if analysis.recommendations.is_empty() {
         recommendations.push(Line::from(vec![Span::styled(
             "✅ No issues detected",
-            Style::default().fg(Color::Green),
+            theme.style_success(),
         )]));
         recommendations.push(Line::from(vec![Span::styled(
             "   Your package management looks healthy",
-            Style::default().fg(Color::Gray),
+            theme.style_text_dim(),
         )]));
     }
 
@@ -940,20 +926,194 @@ fn draw_package_recommendations(
                 .title("💡 Recommendations")
                 .borders(Borders::ALL),
         )
-        .style(Style::default().fg(Color::White));
+        .style(theme.style_text());
 
     f.render_widget(recommendations_panel, area);
 }
 
+fn draw_package_detail(
+    f: &mut Frame,
+    app: &App,
+    tracker: &PackageTracker,
+    manager: &str,
+    stats: &PackageStats,
+    area: Rect,
+) {
+    let theme = &app.theme;
+
+    let timeline = tracker.package_timeline(&app.commands, &stats.name);
+    let date = |ts: chrono::DateTime<chrono::Utc>| ts.format("%Y-%m-%d %H:%M").to_string();
+    let label = theme.style_text_dim();
+
+    let mut managers: Vec<&str> = timeline.iter().map(|e| e.manager.as_str()).collect();
+    managers.sort_unstable();
+    managers.dedup();
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled("Managers:       ", label),
+            Span::styled(managers.join(", "), theme.style_info()),
+        ]),
+        Line::from(vec![
+            Span::styled("First installed: ", label),
+            Span::raw(
+                stats
+                    .first_installed
+                    .map(date)
+                    .unwrap_or_else(|| "never".to_string()),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("Last used:      ", label),
+            Span::raw(date(stats.last_used)),
+        ]),
+        Line::from(vec![
+            Span::styled("Operations:     ", label),
+            Span::styled(format!("↗{}", stats.install_count), theme.style_success()),
+            Span::raw(" "),
+            Span::styled(format!("↘{}", stats.remove_count), theme.style_danger()),
+        ]),
+        Line::from(""),
+    ];
+
+    // Group the timeline by version, in the order each version first appeared
+    let mut versions: Vec<Option<&str>> = Vec::new();
+    for event in &timeline {
+        let version = event.version.as_deref();
+        if !versions.contains(&version) {
+            versions.push(version);
+        }
+    }
+
+    for version in versions {
+        lines.push(Line::from(Span::styled(
+            version.map_or("unversioned".to_string(), |v| format!("v{}", v)),
+            theme.style_warning().add_modifier(Modifier::BOLD),
+        )));
+        for event in timeline.iter().filter(|e| e.version.as_deref() == version) {
+            let action_color = match event.action.as_str() {
+                "install" => theme.success,
+                "remove" | "uninstall" => theme.danger,
+                _ => theme.warning,
+            };
+            lines.push(Line::from(vec![
+                Span::raw("  "),
+                Span::styled(date(event.timestamp), label),
+                Span::raw("  "),
+                Span::styled(
+                    format!("{:<8}", event.action),
+                    Style::default().fg(action_color),
+                ),
+                Span::styled(format!("[{}]", event.manager), theme.style_info()),
+            ]));
+        }
+    }
+
+    let popup_area = super::centered_rect(60, 70, area);
+    let (manager_icon, _) = get_manager_info(manager, theme);
+    let popup = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title(format!(
+                    "{} {}  (Enter: commands, Esc to close)",
+                    manager_icon, stats.name
+                ))
+                .borders(Borders::ALL),
+        )
+        .style(theme.style_text());
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(popup, popup_area);
+}
+
+/// The stored commands that touched a package, newest first.
+fn draw_package_commands(f: &mut Frame, app: &App, package_commands: &PackageCommands, area: Rect) {
+    let theme = &app.theme;
+
+    let lines: Vec<Line> = if package_commands.commands.is_empty() {
+        vec![Line::from(Span::styled(
+            "No stored commands reference this package",
+            theme.style_text_dim(),
+        ))]
+    } else {
+        package_commands
+            .commands
+            .iter()
+            .skip(package_commands.offset)
+            .map(|cmd| {
+                let status = match cmd.exit_code {
+                    Some(0) => Span::styled("✓ ", theme.style_success()),
+                    Some(_) => Span::styled("✗ ", theme.style_danger()),
+                    None => Span::raw("  "),
+                };
+                Line::from(vec![
+                    Span::styled(
+                        cmd.timestamp.format("%Y-%m-%d %H:%M  ").to_string(),
+                        theme.style_text_dim(),
+                    ),
+                    status,
+                    Span::styled(format!("[{}] ", cmd.host_id), theme.style_info()),
+                    Span::raw(cmd.command.as_str()),
+                ])
+            })
+            .collect()
+    };
+
+    let popup_area = super::centered_rect(80, 70, area);
+    let popup = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title(format!(
+                    "{} {} via {}: {} commands  (↑↓ scroll, Esc to close)",
+                    Icons::COMMANDS,
+                    package_commands.name,
+                    package_commands.manager,
+                    package_commands.commands.len()
+                ))
+                .borders(Borders::ALL),
+        )
+        .style(theme.style_text());
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(popup, popup_area);
+}
+
 // Helper functions
 
-fn apply_package_filter(
+pub fn apply_package_filter(
     analysis: &crate::analysis::package_tracker::PackageAnalysis,
-    _filter: &PackageFilter,
+    filter: &PackageFilter,
 ) -> crate::analysis::package_tracker::PackageAnalysis {
-    // For now, return the analysis as-is
-    // In a full implementation, this would filter based on ecosystem
-    analysis.clone()
+    let Some(ecosystem) = filter.ecosystem() else {
+        return analysis.clone();
+    };
+
+    let in_ecosystem = |manager: &str| categorize_manager(manager) == ecosystem;
+
+    let managers_used: Vec<_> = analysis
+        .managers_used
+        .iter()
+        .filter(|stats| in_ecosystem(&stats.manager))
+        .cloned()
+        .collect();
+
+    crate::analysis::package_tracker::PackageAnalysis {
+        total_package_operations: managers_used.iter().map(|m| m.total_operations).sum(),
+        managers_used,
+        package_trends: analysis
+            .package_trends
+            .iter()
+            .filter(|trend| in_ecosystem(&trend.manager))
+            .cloned()
+            .collect(),
+        version_conflicts: analysis
+            .version_conflicts
+            .iter()
+            .filter(|conflict| in_ecosystem(&conflict.manager))
+            .cloned()
+            .collect(),
+        recommendations: analysis.recommendations.clone(),
+    }
 }
 
 fn count_security_issues(analysis: &crate::analysis::package_tracker::PackageAnalysis) -> usize {
@@ -0,0 +1,154 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, Paragraph},
+    Frame,
+};
+
+use crate::analysis::projects::ProjectStats;
+use crate::app::App;
+use crate::ui::theme::{Icons, Theme};
+
+pub fn draw(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(45), Constraint::Percentage(55)].as_ref())
+        .split(area);
+
+    draw_project_list(f, app, chunks[0], theme);
+    draw_project_details(f, app, chunks[1], theme);
+}
+
+fn draw_project_list(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    let projects = &app.analysis().projects.projects;
+
+    let rows = area.height.saturating_sub(2) as usize;
+    app.set_viewport_height(rows);
+    let items: Vec<ListItem> = projects
+        .iter()
+        .enumerate()
+        .skip(app.scroll_offset)
+        .take(rows)
+        .map(|(i, project)| {
+            let style = if i == app.selected_index {
+                theme.style_selected()
+            } else {
+                theme.style_text()
+            };
+
+            let danger_style = if project.danger_ratio() > 0.1 {
+                theme.style_danger()
+            } else if project.dangerous_count > 0 {
+                theme.style_warning()
+            } else {
+                theme.style_text_dim()
+            };
+
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{} ", Icons::FOLDER), theme.style_accent()),
+                Span::styled(format!("{:<24}", project.name), style),
+                Span::styled(
+                    format!("{:>6} cmds", project.command_count),
+                    theme.style_text_dim(),
+                ),
+                Span::styled(
+                    format!("  {:>5.1}% risky", project.danger_ratio() * 100.0),
+                    danger_style,
+                ),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title(Line::from(vec![
+                    Span::styled(format!("{} ", Icons::FOLDER), theme.style_accent()),
+                    Span::styled("Projects", theme.style_title()),
+                    Span::styled(format!(" ({})", projects.len()), theme.style_text_dim()),
+                ]))
+                .borders(Borders::ALL)
+                .border_style(theme.style_border()),
+        )
+        .style(theme.style_text());
+
+    f.render_widget(list, area);
+}
+
+fn draw_project_details(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    let block = Block::default()
+        .title("Project Details")
+        .borders(Borders::ALL)
+        .border_style(theme.style_border());
+
+    let Some(project) = app.analysis().projects.projects.get(app.selected_index) else {
+        let empty = Paragraph::new(Line::from(Span::styled(
+            "No commands recorded yet",
+            theme.style_text_dim(),
+        )))
+        .block(block);
+        f.render_widget(empty, area);
+        return;
+    };
+
+    let details = Paragraph::new(project_lines(project, theme))
+        .block(block)
+        .style(theme.style_text());
+    f.render_widget(details, area);
+}
+
+fn project_lines<'a>(project: &'a ProjectStats, theme: &Theme) -> Vec<Line<'a>> {
+    let label = |text: &'static str| Span::styled(format!("{:<13}", text), theme.style_text_dim());
+
+    let mut lines = vec![
+        Line::from(Span::styled(project.name.as_str(), theme.style_primary())),
+        Line::from(""),
+        Line::from(vec![
+            label("Path"),
+            Span::styled(
+                project.root.as_deref().unwrap_or("(no working directory)"),
+                theme.style_text(),
+            ),
+        ]),
+        Line::from(vec![
+            label("Commands"),
+            Span::styled(project.command_count.to_string(), theme.style_text()),
+        ]),
+        Line::from(vec![
+            label("Dangerous"),
+            Span::styled(
+                format!(
+                    "{} ({:.1}%)",
+                    project.dangerous_count,
+                    project.danger_ratio() * 100.0
+                ),
+                if project.dangerous_count > 0 {
+                    theme.style_danger()
+                } else {
+                    theme.style_success()
+                },
+            ),
+        ]),
+        Line::from(vec![
+            label("Last active"),
+            Span::styled(
+                project.last_active.format("%Y-%m-%d %H:%M UTC").to_string(),
+                theme.style_text(),
+            ),
+        ]),
+        Line::from(""),
+        Line::from(Span::styled("Top tools", theme.style_highlight())),
+    ];
+
+    for (tool, count) in &project.top_tools {
+        lines.push(Line::from(vec![
+            Span::raw("  "),
+            Span::styled(format!("{:<16}", tool), theme.style_accent()),
+            Span::styled(count.to_string(), theme.style_text_dim()),
+        ]));
+    }
+
+    lines
+}
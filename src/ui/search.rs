@@ -6,12 +6,198 @@ use ratatui::{
     widgets::{Block, Borders, List, ListItem, Paragraph},
     Frame,
 };
+use regex::Regex;
 
-use crate::app::App;
+use crate::app::{App, SearchFilter, SearchKind};
+use crate::history::Command;
+use crate::ui::search_query::SearchQuery;
 use crate::ui::theme::{get_host_icon, Icons, Theme};
 
+/// How many of the newest commands are scored on each keystroke.
+pub const SEARCH_CANDIDATE_LIMIT: usize = 20_000;
+pub const MAX_SEARCH_RESULTS: usize = 100;
+/// Matches scoring below this per query character are mostly scattered letters.
+pub(crate) const MIN_SCORE_PER_CHAR: i64 = 8;
+
+#[derive(Debug, Clone)]
+pub struct SearchMatch {
+    pub command: Command,
+    pub score: i64,
+    /// Char positions in the command text that matched the query
+    pub indices: Vec<usize>,
+}
+
+/// The matches a search shows, and how many commands matched in all.
+#[derive(Debug, Clone, Default)]
+pub struct SearchResults {
+    /// At most `MAX_SEARCH_RESULTS`
+    pub matches: Vec<SearchMatch>,
+    pub total: usize,
+}
+
+/// Fuzzy-matches `query` against `commands` (oldest first, as held by `App`),
+/// best score first and newest first among equal scores, with the number of
+/// matches before they're cut down to `MAX_SEARCH_RESULTS`. `filter` narrows
+/// the candidates before anything is scored.
+pub fn fuzzy_search(commands: &[Command], query: &str, filter: &SearchFilter) -> SearchResults {
+    let query = query.trim();
+    if query.is_empty() {
+        return SearchResults::default();
+    }
+
+    let matcher = SkimMatcherV2::default();
+    let min_score = query.chars().count() as i64 * MIN_SCORE_PER_CHAR;
+
+    let mut results: Vec<SearchMatch> = candidates(commands, filter)
+        .filter_map(|cmd| {
+            let (score, indices) = matcher.fuzzy_indices(&cmd.command, query)?;
+            (score >= min_score).then(|| SearchMatch {
+                command: cmd.clone(),
+                score,
+                indices,
+            })
+        })
+        .collect();
+
+    // Stable, so equal scores stay newest first
+    results.sort_by_key(|m| std::cmp::Reverse(m.score));
+    let total = results.len();
+    results.truncate(MAX_SEARCH_RESULTS);
+    SearchResults {
+        matches: results,
+        total,
+    }
+}
+
+/// Commands whose text matches the regex `query`, newest first, narrowed by
+/// `filter` the same way as the fuzzy search, with the number of matching
+/// commands counting past `MAX_SEARCH_RESULTS`. Every match is marked in
+/// `indices`; `score` is unused and left at 0.
+pub fn regex_search(
+    commands: &[Command],
+    query: &str,
+    filter: &SearchFilter,
+) -> Result<SearchResults, regex::Error> {
+    if query.trim().is_empty() {
+        return Ok(SearchResults::default());
+    }
+    let pattern = Regex::new(query)?;
+
+    let mut results = SearchResults::default();
+    for cmd in candidates(commands, filter) {
+        if results.matches.len() == MAX_SEARCH_RESULTS {
+            results.total += usize::from(pattern.is_match(&cmd.command));
+            continue;
+        }
+
+        let mut matched = false;
+        let mut indices = Vec::new();
+        for found in pattern.find_iter(&cmd.command) {
+            // An empty match (`^`, `x*`) still counts, it just marks nothing
+            matched = true;
+            let start = cmd.command[..found.start()].chars().count();
+            indices.extend(start..start + found.as_str().chars().count());
+        }
+        if matched {
+            results.total += 1;
+            results.matches.push(SearchMatch {
+                command: cmd.clone(),
+                score: 0,
+                indices,
+            });
+        }
+    }
+    Ok(results)
+}
+
+/// Commands matching a parsed [`SearchQuery`], newest first, narrowed by
+/// `filter` like the other searches. `score` is unused and left at 0.
+pub fn query_search(
+    commands: &[Command],
+    query: &SearchQuery,
+    filter: &SearchFilter,
+) -> SearchResults {
+    let mut results = SearchResults::default();
+    if query.is_empty() {
+        return results;
+    }
+
+    let matcher = SkimMatcherV2::default();
+    for cmd in candidates(commands, filter) {
+        let Some(indices) = query.match_indices(&cmd.command, &matcher) else {
+            continue;
+        };
+        results.total += 1;
+        if results.matches.len() < MAX_SEARCH_RESULTS {
+            results.matches.push(SearchMatch {
+                command: cmd.clone(),
+                score: 0,
+                indices,
+            });
+        }
+    }
+    results
+}
+
+/// The match count shown in the Search header, e.g. "42 matches" or
+/// "no matches for 'xyz' in Failed".
+pub fn match_summary(query: &str, total: usize, filter: &SearchFilter) -> String {
+    let mut summary = match total {
+        0 => format!("no matches for '{}'", query.trim()),
+        1 => "1 match".to_string(),
+        _ => format!("{} matches", total),
+    };
+    if *filter != SearchFilter::None {
+        summary.push_str(&format!(" in {}", filter.label()));
+    }
+    if total > MAX_SEARCH_RESULTS {
+        summary.push_str(&format!(", showing {}", MAX_SEARCH_RESULTS));
+    }
+    summary
+}
+
+/// What to try when a search finds nothing.
+pub fn no_match_suggestions(kind: SearchKind, filter: &SearchFilter) -> Vec<String> {
+    let mut suggestions = vec!["Check the spelling, or try part of the command name".to_string()];
+    if let Some(key) = filter.function_key() {
+        suggestions.push(format!(
+            "Press F{} to clear the {} filter",
+            key,
+            filter.label()
+        ));
+    }
+    suggestions.push(match kind {
+        SearchKind::Fuzzy => "Press Ctrl+R to search with a regex".to_string(),
+        SearchKind::Regex => "Press Ctrl+R for fuzzy matching, which forgives typos".to_string(),
+    });
+    suggestions
+}
+
+/// The commands a search looks at, newest first: those `filter` lets through,
+/// capped so a keystroke never walks the whole history.
+fn candidates<'a>(
+    commands: &'a [Command],
+    filter: &'a SearchFilter,
+) -> impl Iterator<Item = &'a Command> + 'a {
+    let limit = if *filter == SearchFilter::Recent {
+        100
+    } else {
+        SEARCH_CANDIDATE_LIMIT
+    };
+    commands
+        .iter()
+        .rev()
+        .filter(move |cmd| match filter {
+            SearchFilter::None | SearchFilter::Recent => true,
+            SearchFilter::Failed => cmd.exit_code.is_some_and(|code| code != 0),
+            SearchFilter::Dangerous => cmd.is_dangerous,
+            SearchFilter::Experiments => cmd.is_experiment,
+        })
+        .take(limit)
+}
+
 pub fn draw(f: &mut Frame, app: &App, area: Rect) {
-    let theme = Theme::default();
+    let theme = &app.theme;
 
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -23,13 +209,13 @@ pub fn draw(f: &mut Frame, app: &App, area: Rect) {
         .split(area);
 
     // Search input area
-    draw_search_input(f, app, chunks[0], &theme);
+    draw_search_input(f, app, chunks[0], theme);
 
     // Search results
-    draw_search_results(f, app, chunks[1], &theme);
+    draw_search_results(f, app, chunks[1], theme);
 
     // Search status
-    draw_search_status(f, app, chunks[2], &theme);
+    draw_search_status(f, app, chunks[2], theme);
 }
 
 fn draw_search_input(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
@@ -58,13 +244,46 @@ fn draw_search_input(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
         theme.style_text()
     };
 
-    let search_input = Paragraph::new(search_text)
+    let mut title = vec![
+        Span::styled(format!("{} ", Icons::SEARCH), theme.style_primary()),
+        Span::styled("Search ", theme.style_title()),
+        match app.search_kind {
+            SearchKind::Fuzzy => Span::styled("[fuzzy]", theme.style_text_dim()),
+            SearchKind::Regex => Span::styled("[regex]", theme.style_accent()),
+        },
+        Span::styled(" Ctrl+R switches", theme.style_text_dim()),
+    ];
+    // Kept in step with every keystroke and filter change
+    if !app.search_query.trim().is_empty() && app.search_error.is_none() {
+        title.push(Span::styled(" · ", theme.style_text_dim()));
+        title.push(Span::styled(
+            match_summary(&app.search_query, app.search_total, &app.search_filter),
+            if app.search_total == 0 {
+                theme.style_warning()
+            } else {
+                theme.style_accent()
+            },
+        ));
+    }
+
+    let mut input_lines = vec![Line::from(search_text)];
+    if app.search_kind == SearchKind::Fuzzy {
+        input_lines.push(Line::from(vec![
+            Span::styled("a b", theme.style_primary()),
+            Span::styled(" both  ", theme.style_text_dim()),
+            Span::styled("a | b", theme.style_primary()),
+            Span::styled(" either  ", theme.style_text_dim()),
+            Span::styled("-a", theme.style_primary()),
+            Span::styled(" not  ", theme.style_text_dim()),
+            Span::styled("\"a b\"", theme.style_primary()),
+            Span::styled(" as written", theme.style_text_dim()),
+        ]));
+    }
+
+    let search_input = Paragraph::new(input_lines)
         .block(
             Block::default()
-                .title(Line::from(vec![
-                    Span::styled(format!("{} ", Icons::SEARCH), theme.style_primary()),
-                    Span::styled("Search", theme.style_title()),
-                ]))
+                .title(Line::from(title))
                 .borders(Borders::ALL)
                 .border_style(if app.search_mode {
                     theme.style_primary()
@@ -82,16 +301,7 @@ fn draw_search_input(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
     let filter_text = vec![
         Line::from(vec![
             Span::styled("Filters: ", theme.style_accent()),
-            Span::styled(
-                match active_filter {
-                    crate::app::SearchFilter::None => "None",
-                    crate::app::SearchFilter::Failed => "Failed",
-                    crate::app::SearchFilter::Dangerous => "Dangerous",
-                    crate::app::SearchFilter::Recent => "Recent",
-                    crate::app::SearchFilter::Experiments => "Experiments",
-                },
-                theme.style_primary(),
-            ),
+            Span::styled(active_filter.label(), theme.style_primary()),
         ]),
         Line::from(vec![
             Span::styled(
@@ -179,34 +389,64 @@ fn draw_search_input(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
 }
 
 fn draw_search_results(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
-    let results = perform_search(app);
+    let results = &app.search_results;
+
+    if let Some(err) = &app.search_error {
+        // The regex crate points at the problem on the lines below the pattern;
+        // query errors are a single line
+        let mut lines = vec![
+            Line::from(Span::styled(
+                match app.search_kind {
+                    SearchKind::Fuzzy => "Invalid query",
+                    SearchKind::Regex => "Invalid regex",
+                },
+                theme.style_danger(),
+            )),
+            Line::from(""),
+        ];
+        lines.extend(
+            err.lines()
+                .map(|line| Line::from(Span::styled(line.to_string(), theme.style_text_dim()))),
+        );
+        let error = Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .title(Line::from(vec![
+                        Span::styled(format!("{} ", Icons::ERROR), theme.style_danger()),
+                        Span::styled("Search Results", theme.style_title()),
+                    ]))
+                    .borders(Borders::ALL)
+                    .border_style(theme.style_border()),
+            )
+            .style(theme.style_text());
 
-    if results.is_empty() && !app.search_query.is_empty() {
-        // No results found
-        let no_results = vec![
+        f.render_widget(error, area);
+        return;
+    }
+
+    if results.is_empty() && !app.search_query.trim().is_empty() {
+        let mut no_results = vec![
             Line::from(""),
             Line::from(vec![
                 Span::styled(format!("{} ", Icons::SEARCH), theme.style_text_dim()),
                 Span::styled(
-                    "No commands found matching your search",
-                    theme.style_text_dim(),
+                    match_summary(&app.search_query, 0, &app.search_filter),
+                    theme.style_warning(),
                 ),
             ]),
             Line::from(""),
             Line::from(vec![Span::styled("Try: ", theme.style_accent())]),
-            Line::from(vec![
-                Span::styled("• ", theme.style_text_dim()),
-                Span::styled("Different keywords", theme.style_text()),
-            ]),
-            Line::from(vec![
-                Span::styled("• ", theme.style_text_dim()),
-                Span::styled("Partial command names", theme.style_text()),
-            ]),
-            Line::from(vec![
-                Span::styled("• ", theme.style_text_dim()),
-                Span::styled("Use filters (F1-F4)", theme.style_text()),
-            ]),
         ];
+        no_results.extend(
+            no_match_suggestions(app.search_kind, &app.search_filter)
+                .into_iter()
+                .map(|suggestion| {
+                    Line::from(vec![
+                        Span::styled("• ", theme.style_text_dim()),
+                        Span::styled(suggestion, theme.style_text()),
+                    ])
+                }),
+        );
 
         let paragraph = Paragraph::new(no_results)
             .block(
@@ -226,12 +466,17 @@ fn draw_search_results(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
     }
 
     // Display search results
-    let results_count = results.len();
+    let best_score = results.first().map_or(1, |m| m.score.max(1));
+    let uses_query_syntax = app.search_uses_query_syntax();
+    let rows = area.height.saturating_sub(2) as usize; // Account for borders
+    app.set_viewport_height(rows);
     let result_items: Vec<ListItem> = results
-        .into_iter()
+        .iter()
         .enumerate()
-        .take(area.height as usize - 2) // Account for borders
-        .map(|(i, (cmd, score))| {
+        .skip(app.scroll_offset)
+        .take(rows)
+        .map(|(i, search_match)| {
+            let cmd = &search_match.command;
             let is_selected = i == app.selected_index;
 
             let style = if is_selected {
@@ -253,18 +498,28 @@ fn draw_search_results(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
                 None => ("", theme.style_text_dim()),
             };
 
-            // Highlight matching parts (simplified)
-            let highlighted_command = highlight_matches(&cmd.command, &app.search_query, theme);
-
-            ListItem::new(Line::from(vec![
+            let mut spans = vec![
                 Span::styled(format!("{:2}. ", i + 1), theme.style_text_dim()),
                 Span::styled(format!("{} ", status_icon), status_style),
                 Span::styled(time_str, theme.style_text_dim()),
                 Span::raw(" "),
                 Span::styled(format!("{} ", host_icon), theme.style_secondary()),
-                Span::styled(highlighted_command, style),
-                Span::styled(format!(" ({:.0}%)", score * 100.0), theme.style_text_dim()),
-            ]))
+            ];
+            spans.extend(highlight_matches(
+                &cmd.command,
+                &search_match.indices,
+                style,
+                theme,
+            ));
+            // Relative to the best match, so the top result reads 100%
+            if app.search_kind == SearchKind::Fuzzy && !uses_query_syntax {
+                spans.push(Span::styled(
+                    format!(" ({}%)", search_match.score * 100 / best_score),
+                    theme.style_text_dim(),
+                ));
+            }
+
+            ListItem::new(Line::from(spans))
         })
         .collect();
 
@@ -274,7 +529,7 @@ fn draw_search_results(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
                 .title(Line::from(vec![
                     Span::styled(format!("{} ", Icons::SEARCH), theme.style_accent()),
                     Span::styled("Search Results", theme.style_title()),
-                    Span::styled(format!(" ({})", results_count), theme.style_text_dim()),
+                    Span::styled(format!(" ({})", app.search_total), theme.style_text_dim()),
                 ]))
                 .borders(Borders::ALL)
                 .border_style(theme.style_border()),
@@ -296,14 +551,7 @@ fn draw_search_status(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
             Span::styled(" to clear", theme.style_text_dim()),
         ])]
     } else {
-        let results_count = perform_search(app).len();
         vec![Line::from(vec![
-            Span::styled(
-                format!("{} results for '", results_count),
-                theme.style_text(),
-            ),
-            Span::styled(&app.search_query, theme.style_primary()),
-            Span::styled("' • ", theme.style_text()),
             Span::styled("↑↓", theme.style_primary()),
             Span::styled(" navigate • ", theme.style_text_dim()),
             Span::styled("Enter", theme.style_primary()),
@@ -320,61 +568,36 @@ fn draw_search_status(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
     f.render_widget(status, area);
 }
 
-fn perform_search(app: &App) -> Vec<(&crate::history::Command, f64)> {
-    if app.search_query.is_empty() {
-        return Vec::new();
-    }
-
-    let matcher = SkimMatcherV2::default();
-
-    // First apply search filter
-    let filtered_commands: Vec<_> = match app.search_filter {
-        crate::app::SearchFilter::None => app.commands.iter().collect(),
-        crate::app::SearchFilter::Failed => app
-            .commands
-            .iter()
-            .filter(|cmd| cmd.exit_code.is_some() && cmd.exit_code.unwrap() != 0)
-            .collect(),
-        crate::app::SearchFilter::Dangerous => {
-            app.commands.iter().filter(|cmd| cmd.is_dangerous).collect()
-        }
-        crate::app::SearchFilter::Recent => {
-            let mut recent: Vec<_> = app.commands.iter().collect();
-            recent.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
-            recent.into_iter().take(100).collect()
+/// Splits `text` into spans, styling the matched characters so they stand out.
+fn highlight_matches(
+    text: &str,
+    indices: &[usize],
+    style: ratatui::style::Style,
+    theme: &Theme,
+) -> Vec<Span<'static>> {
+    let matched_style = style
+        .patch(theme.style_accent())
+        .add_modifier(ratatui::style::Modifier::BOLD | ratatui::style::Modifier::UNDERLINED);
+
+    let mut spans = Vec::new();
+    let mut run = String::new();
+    let mut run_matched = false;
+
+    for (i, c) in text.chars().enumerate() {
+        let matched = indices.binary_search(&i).is_ok();
+        if matched != run_matched && !run.is_empty() {
+            let run_style = if run_matched { matched_style } else { style };
+            spans.push(Span::styled(std::mem::take(&mut run), run_style));
         }
-        crate::app::SearchFilter::Experiments => app
-            .commands
-            .iter()
-            .filter(|cmd| cmd.is_experiment)
-            .collect(),
-    };
-
-    let mut results: Vec<_> = filtered_commands
-        .into_iter()
-        .filter_map(|cmd| {
-            matcher
-                .fuzzy_match(&cmd.command, &app.search_query)
-                .map(|score| (cmd, score as f64 / 100.0))
-        })
-        .collect();
-
-    // Sort by score (highest first)
-    results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
-
-    // Limit results
-    results.truncate(100);
-
-    results
-}
-
-fn highlight_matches(text: &str, query: &str, _theme: &Theme) -> String {
-    // Simple highlighting - in a real implementation, you'd want more sophisticated matching
-    if query.is_empty() {
-        return text.to_string();
+        run_matched = matched;
+        run.push(c);
+    }
+    if !run.is_empty() {
+        spans.push(Span::styled(
+            run,
+            if run_matched { matched_style } else { style },
+        ));
     }
 
-    // For now, just return the original text
-    // In a full implementation, you'd highlight matching characters
-    text.to_string()
+    spans
 }
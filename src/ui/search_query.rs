@@ -0,0 +1,198 @@
+use anyhow::{bail, Result};
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+
+use super::search::MIN_SCORE_PER_CHAR;
+
+/// One word of a [`SearchQuery`], lowercased.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryTerm {
+    pub text: String,
+    /// Commands containing it are left out
+    pub negated: bool,
+    /// Quoted or a flag: only the text as written counts, never a fuzzy match
+    pub exact: bool,
+}
+
+/// The Search tab's fuzzy-mode query: space-separated words must all match,
+/// `|` separates alternatives, and a leading `-` leaves out commands
+/// containing the rest of the word.
+///
+/// `-x` and `--long` are flags to look for rather than exclusions, so
+/// `git -m -rebase` finds commits with a message that aren't rebases. Quotes
+/// keep spaces and dashes as written: `"-rf"`, `-"git push"`. Words match as
+/// case-insensitive substrings, falling back to fuzzy matching unless exact.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchQuery {
+    /// Alternatives, each a list of words that must all match
+    pub groups: Vec<Vec<QueryTerm>>,
+}
+
+impl SearchQuery {
+    pub fn parse(query: &str) -> Result<Self> {
+        let mut groups = vec![Vec::new()];
+        let mut chars = query.chars().peekable();
+
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() {
+                chars.next();
+                continue;
+            }
+            if c == '|' {
+                chars.next();
+                if groups.last().is_some_and(Vec::is_empty) {
+                    bail!("Nothing before '|'");
+                }
+                groups.push(Vec::new());
+                continue;
+            }
+
+            let mut word = String::new();
+            let mut quoted = false;
+            let leading_dash = c == '-';
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() || c == '|' {
+                    break;
+                }
+                chars.next();
+                if c != '"' {
+                    word.push(c);
+                    continue;
+                }
+                quoted = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => word.push(c),
+                        None => bail!("Missing closing quote"),
+                    }
+                }
+            }
+
+            let term = QueryTerm::from_word(&word, quoted, leading_dash)?;
+            groups.last_mut().expect("at least one group").push(term);
+        }
+
+        if groups.len() > 1 && groups.last().is_some_and(Vec::is_empty) {
+            bail!("Nothing after '|'");
+        }
+        groups.retain(|group| !group.is_empty());
+        Ok(Self { groups })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// A lone unquoted word, which the Search tab fuzzy-matches as before.
+    pub fn is_single_term(&self) -> bool {
+        matches!(self.groups.as_slice(), [group] if matches!(
+            group.as_slice(),
+            [term] if !term.negated && !term.exact
+        ))
+    }
+
+    /// The char positions to highlight when `command` matches, from the first
+    /// alternative that does, or `None` when none do.
+    pub fn match_indices(&self, command: &str, matcher: &SkimMatcherV2) -> Option<Vec<usize>> {
+        let lowered = Lowered::new(command);
+        self.groups.iter().find_map(|group| {
+            let mut indices = Vec::new();
+            for term in group {
+                let found = term.find(command, &lowered, matcher);
+                match (term.negated, found) {
+                    (true, Some(_)) | (false, None) => return None,
+                    (true, None) => {}
+                    (false, Some(found)) => indices.extend(found),
+                }
+            }
+            indices.sort_unstable();
+            indices.dedup();
+            Some(indices)
+        })
+    }
+}
+
+impl QueryTerm {
+    fn from_word(word: &str, quoted: bool, leading_dash: bool) -> Result<Self> {
+        if word.is_empty() {
+            bail!("Empty quotes");
+        }
+
+        // The dash is one byte, so this only ever strips it
+        let rest = if leading_dash { &word[1..] } else { word };
+        let is_flag = rest.starts_with('-') || rest.chars().count() == 1;
+        let (text, negated, exact) = match (leading_dash, quoted) {
+            (true, _) if rest.is_empty() => bail!("Nothing to leave out after '-'"),
+            (true, true) => (rest, true, true),
+            (true, false) if is_flag => (word, false, true),
+            (true, false) => (rest, true, false),
+            (false, quoted) => (word, false, quoted),
+        };
+
+        Ok(Self {
+            text: lowercase(text),
+            negated,
+            exact,
+        })
+    }
+
+    /// Where the term matches `command`: every occurrence as a substring of
+    /// its lowercased form, else a fuzzy match unless the term is exact.
+    fn find(
+        &self,
+        command: &str,
+        lowered: &Lowered,
+        matcher: &SkimMatcherV2,
+    ) -> Option<Vec<usize>> {
+        let len = self.text.chars().count();
+        let mut indices: Vec<usize> = lowered
+            .text
+            .match_indices(self.text.as_str())
+            .flat_map(|(start, _)| {
+                let start = lowered.text[..start].chars().count();
+                lowered.positions[start..start + len].iter().copied()
+            })
+            .collect();
+        // A char that lowercases to several can be matched more than once
+        indices.dedup();
+        if !indices.is_empty() {
+            return Some(indices);
+        }
+        if self.exact || self.negated {
+            return None;
+        }
+
+        let (score, indices) = matcher.fuzzy_indices(command, &self.text)?;
+        (score >= len as i64 * MIN_SCORE_PER_CHAR).then_some(indices)
+    }
+}
+
+/// `command` lowercased a char at a time, remembering which char of `command`
+/// each lowered char came from. Some chars lowercase to more than one (`İ`
+/// gives `i̇`), so positions in the lowered text aren't the original's.
+struct Lowered {
+    text: String,
+    /// For each char of `text`, the position of the char it came from
+    positions: Vec<usize>,
+}
+
+impl Lowered {
+    fn new(command: &str) -> Self {
+        let mut text = String::with_capacity(command.len());
+        let mut positions = Vec::with_capacity(command.len());
+        for (position, c) in command.chars().enumerate() {
+            for lower in c.to_lowercase() {
+                text.push(lower);
+                positions.push(position);
+            }
+        }
+        Self { text, positions }
+    }
+}
+
+/// Lowercases a char at a time, the same way as [`Lowered`], so a term
+/// matches the command it was copied from.
+fn lowercase(text: &str) -> String {
+    text.chars().flat_map(char::to_lowercase).collect()
+}
@@ -4,13 +4,13 @@ use ratatui::{
     widgets::{Block, Borders, Gauge, List, ListItem, Paragraph},
     Frame,
 };
-use std::collections::HashMap;
 
+use crate::analysis::sessions::SessionSummary;
 use crate::app::App;
 use crate::ui::theme::{Icons, Theme};
 
 pub fn draw(f: &mut Frame, app: &App, area: Rect) {
-    let theme = Theme::default();
+    let theme = &app.theme;
 
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
@@ -18,110 +18,89 @@ pub fn draw(f: &mut Frame, app: &App, area: Rect) {
         .split(area);
 
     // Left panel: Sessions list
-    draw_sessions_list(f, app, chunks[0], &theme);
+    draw_sessions_list(f, app, chunks[0], theme);
 
     // Right panel: Session details and timeline
-    draw_session_details(f, app, chunks[1], &theme);
+    draw_session_details(f, app, chunks[1], theme);
 }
 
 fn draw_sessions_list(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
-    // Group commands by session
-    let mut sessions: HashMap<String, Vec<&crate::history::Command>> = HashMap::new();
-    for cmd in &app.commands {
-        sessions
-            .entry(cmd.session_id.clone())
-            .or_default()
-            .push(cmd);
-    }
+    // Split on idle gaps so one long-lived history file isn't a single session;
+    // most recent first
+    let sessions = &app.analysis().sessions;
 
-    let mut session_data: Vec<_> = sessions
-        .into_iter()
-        .map(|(session_id, commands)| {
-            let start_time = commands.iter().map(|c| c.timestamp).min().unwrap();
-            let end_time = commands.iter().map(|c| c.timestamp).max().unwrap();
-            let duration = (end_time - start_time).num_minutes();
-            let command_count = commands.len();
-            let dangerous_count = commands.iter().filter(|c| c.is_dangerous).count();
-            let experiment_count = commands.iter().filter(|c| c.is_experiment).count();
-            let hosts: std::collections::HashSet<_> = commands.iter().map(|c| &c.host_id).collect();
+    let session_count = sessions.len();
+    let session_items: Vec<ListItem> = sessions
+        .iter()
+        .enumerate()
+        .take(area.height.saturating_sub(2) as usize)
+        .map(|(i, session)| {
+            let SessionSummary {
+                id: session_id,
+                start: start_time,
+                duration_minutes: duration,
+                command_count: cmd_count,
+                dangerous_count: dangerous,
+                experiment_count: experiments,
+                host_count: hosts,
+            } = session;
+            let (duration, cmd_count, dangerous, experiments, hosts) =
+                (*duration, *cmd_count, *dangerous, *experiments, *hosts);
 
-            (
-                session_id,
-                start_time,
-                duration,
-                command_count,
-                dangerous_count,
-                experiment_count,
-                hosts.len(),
-            )
-        })
-        .collect();
+            let is_selected = i == app.selected_index;
 
-    // Sort by start time (most recent first)
-    session_data.sort_by(|a, b| b.1.cmp(&a.1));
+            let style = if is_selected {
+                theme.style_selected()
+            } else {
+                theme.style_text()
+            };
 
-    let session_count = session_data.len();
-    let session_items: Vec<ListItem> = session_data
-        .into_iter()
-        .enumerate()
-        .take(area.height as usize - 2)
-        .map(
-            |(i, (session_id, start_time, duration, cmd_count, dangerous, experiments, hosts))| {
-                let is_selected = i == app.selected_index;
+            let session_type = if session_id.starts_with("bash") {
+                ("", theme.style_accent())
+            } else if session_id.starts_with("zsh") {
+                ("", theme.style_secondary())
+            } else if session_id.starts_with("fish") {
+                ("", theme.style_primary())
+            } else {
+                (Icons::TERMINAL, theme.style_text())
+            };
 
-                let style = if is_selected {
-                    theme.style_selected()
-                } else {
-                    theme.style_text()
-                };
+            let time_str = start_time.format("%m-%d %H:%M").to_string();
+            let duration_str = if duration > 60 {
+                format!("{}h{}m", duration / 60, duration % 60)
+            } else {
+                format!("{}m", duration)
+            };
 
-                let session_type = if session_id.starts_with("bash") {
-                    ("", theme.style_accent())
-                } else if session_id.starts_with("zsh") {
-                    ("", theme.style_secondary())
-                } else if session_id.starts_with("fish") {
-                    ("", theme.style_primary())
-                } else {
-                    (Icons::TERMINAL, theme.style_text())
-                };
+            let activity_level = (cmd_count as f32 / 50.0).min(1.0);
+            let activity_icon = crate::ui::theme::get_activity_icon(activity_level);
 
-                let time_str = start_time.format("%m-%d %H:%M").to_string();
-                let duration_str = if duration > 60 {
-                    format!("{}h{}m", duration / 60, duration % 60)
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{:2}. ", i + 1), theme.style_text_dim()),
+                Span::styled(format!("{} ", session_type.0), session_type.1),
+                Span::styled(time_str, theme.style_text_dim()),
+                Span::raw(" "),
+                Span::styled(duration_str, style),
+                Span::raw(" "),
+                Span::styled(format!("{}cmd", cmd_count), theme.style_info()),
+                if dangerous > 0 {
+                    Span::styled(format!(" {}⚠", dangerous), theme.style_danger())
                 } else {
-                    format!("{}m", duration)
-                };
-
-                let activity_level = (cmd_count as f32 / 50.0).min(1.0);
-                let activity_icon = crate::ui::theme::get_activity_icon(activity_level);
-
-                ListItem::new(Line::from(vec![
-                    Span::styled(format!("{:2}. ", i + 1), theme.style_text_dim()),
-                    Span::styled(format!("{} ", session_type.0), session_type.1),
-                    Span::styled(time_str, theme.style_text_dim()),
-                    Span::raw(" "),
-                    Span::styled(duration_str, style),
-                    Span::raw(" "),
-                    Span::styled(format!("{}cmd", cmd_count), theme.style_info()),
-                    if dangerous > 0 {
-                        Span::styled(format!(" {}⚠", dangerous), theme.style_danger())
-                    } else {
-                        Span::raw("")
-                    },
-                    if experiments > 0 {
-                        Span::styled(format!(" {}🔬", experiments), theme.style_warning())
-                    } else {
-                        Span::raw("")
-                    },
-                    if hosts > 1 {
-                        Span::styled(format!(" {}🌐", hosts), theme.style_secondary())
-                    } else {
-                        Span::raw("")
-                    },
-                    Span::styled(format!(" {}", activity_icon), theme.style_accent()),
-                ]))
-            },
-        )
+                    Span::raw("")
+                },
+                if experiments > 0 {
+                    Span::styled(format!(" {}🔬", experiments), theme.style_warning())
+                } else {
+                    Span::raw("")
+                },
+                if hosts > 1 {
+                    Span::styled(format!(" {}🌐", hosts), theme.style_secondary())
+                } else {
+                    Span::raw("")
+                },
+                Span::styled(format!(" {}", activity_icon), theme.style_accent()),
+            ]))
+        })
         .collect();
 
     let sessions_list = List::new(session_items)
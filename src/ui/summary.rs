@@ -1,16 +1,38 @@
+use chrono::Utc;
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, Paragraph},
+    widgets::{Block, Borders, Gauge, List, ListItem, Paragraph, Sparkline},
     Frame,
 };
 
+use crate::analysis::stats::CommandFrequency;
 use crate::app::App;
 use crate::ui::theme::Theme;
 
+/// Days covered by the activity trend.
+pub const TREND_DAYS: usize = 30;
+
+/// Days covered by each top command's sparkline.
+const COMMAND_TREND_DAYS: usize = 14;
+
+/// Rows in the failing commands panel.
+const FAILURE_ROWS: usize = 5;
+
+/// Shells compared side by side at most.
+const SHELL_ROWS: usize = 4;
+
+/// Below this width the Summary tab drops to a single column.
+const NARROW_WIDTH: u16 = 100;
+
+/// Below this height the activity trend is left out.
+const SHORT_HEIGHT: u16 = 24;
+
 pub fn draw(f: &mut Frame, app: &App, area: Rect) {
-    let theme = Theme::default();
+    let theme = &app.theme;
+    let narrow = area.width < NARROW_WIDTH;
+    let short = area.height < SHORT_HEIGHT;
 
     let main_chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -18,7 +40,25 @@ pub fn draw(f: &mut Frame, app: &App, area: Rect) {
         .split(area);
 
     // Top panel: Compact metrics row
-    draw_compact_metrics(f, app, main_chunks[0], &theme);
+    draw_compact_metrics(f, app, main_chunks[0], theme, narrow);
+
+    if narrow {
+        // One column: the score, then the trend if there's room, then top commands
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Length(if short { 0 } else { 10 }),
+                Constraint::Min(0),
+            ])
+            .split(main_chunks[1]);
+        draw_productivity_gauge(f, app, chunks[0], theme);
+        if !short {
+            draw_activity_trend(f, app, chunks[1], theme);
+        }
+        draw_top_commands(f, app, chunks[2], theme);
+        return;
+    }
 
     let content_chunks = Layout::default()
         .direction(Direction::Horizontal)
@@ -26,82 +66,86 @@ pub fn draw(f: &mut Frame, app: &App, area: Rect) {
         .split(main_chunks[1]);
 
     // Left panel: Activity chart and top commands
-    draw_left_panel(f, app, content_chunks[0], &theme);
+    draw_left_panel(f, app, content_chunks[0], theme, short);
 
-    // Right panel: Recent activity and AI insights
-    draw_right_panel(f, app, content_chunks[1], &theme);
+    // Right panel: Productivity, sessions and insights
+    draw_right_panel(f, app, content_chunks[1], theme);
 }
 
-fn draw_compact_metrics(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
-    let chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Percentage(20),
-            Constraint::Percentage(20),
-            Constraint::Percentage(20),
-            Constraint::Percentage(20),
-            Constraint::Percentage(20),
-        ])
-        .split(area);
-
-    // Commands
-    draw_compact_metric(
-        f,
-        chunks[0],
-        theme,
-        "Commands",
-        app.stats.total_commands.to_string(),
-        theme.style_primary(),
-    );
-
-    // Sessions
-    draw_compact_metric(
-        f,
-        chunks[1],
-        theme,
-        "Sessions",
-        app.stats.total_sessions.to_string(),
-        theme.style_secondary(),
-    );
-
-    // Hosts
-    draw_compact_metric(
-        f,
-        chunks[2],
-        theme,
-        "Hosts",
-        app.stats.hosts_count.to_string(),
-        theme.style_accent(),
-    );
-
-    // Risk
+fn draw_compact_metrics(f: &mut Frame, app: &App, area: Rect, theme: &Theme, narrow: bool) {
+    let stats = app.command_stats.as_ref();
     let danger_ratio = if app.stats.total_commands > 0 {
         (app.stats.dangerous_commands as f32 / app.stats.total_commands as f32) * 100.0
     } else {
         0.0
     };
-    draw_compact_metric(
-        f,
-        chunks[3],
-        theme,
-        "Risk",
-        format!("{:.1}%", danger_ratio),
-        if danger_ratio > 30.0 {
-            theme.style_danger()
-        } else {
-            theme.style_success()
-        },
-    );
 
-    // Learning
-    draw_compact_metric(
-        f,
-        chunks[4],
-        theme,
-        "Learning",
-        app.stats.experiment_sessions.to_string(),
-        theme.style_info(),
-    );
+    // Label, value, style, and whether it stays on a narrow terminal
+    let metrics = [
+        (
+            "Commands",
+            stats
+                .map_or(app.stats.total_commands, |s| s.total_commands)
+                .to_string(),
+            theme.style_primary(),
+            true,
+        ),
+        (
+            "Unique",
+            stats.map_or("-".to_string(), |s| s.unique_commands.to_string()),
+            theme.style_secondary(),
+            false,
+        ),
+        (
+            "Success",
+            stats.map_or("-".to_string(), |s| {
+                format!("{:.1}%", s.success_rate * 100.0)
+            }),
+            match stats {
+                Some(s) if s.success_rate < 0.8 => theme.style_warning(),
+                _ => theme.style_success(),
+            },
+            true,
+        ),
+        (
+            "Per day",
+            stats.map_or("-".to_string(), |s| format!("{:.1}", s.commands_per_day)),
+            theme.style_accent(),
+            false,
+        ),
+        (
+            "Sessions",
+            app.session_stats
+                .as_ref()
+                .map_or(app.stats.total_sessions, |s| s.total_sessions)
+                .to_string(),
+            theme.style_info(),
+            false,
+        ),
+        (
+            "Risk",
+            format!("{:.1}%", danger_ratio),
+            if danger_ratio > 30.0 {
+                theme.style_danger()
+            } else {
+                theme.style_success()
+            },
+            true,
+        ),
+    ];
+    let shown: Vec<_> = metrics
+        .into_iter()
+        .filter(|(_, _, _, keep_narrow)| !narrow || *keep_narrow)
+        .collect();
+
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(vec![Constraint::Ratio(1, shown.len() as u32); shown.len()])
+        .split(area);
+
+    for (chunk, (label, value, style, _)) in chunks.iter().zip(shown) {
+        draw_compact_metric(f, *chunk, theme, label, value, style);
+    }
 }
 
 fn draw_compact_metric(
@@ -131,69 +175,110 @@ fn draw_compact_metric(
     f.render_widget(paragraph, area);
 }
 
-fn draw_left_panel(f: &mut Frame, _app: &App, area: Rect, theme: &Theme) {
+fn draw_left_panel(f: &mut Frame, app: &App, area: Rect, theme: &Theme, short: bool) {
+    // A single shell has nothing to be compared with
+    let shells = app.analysis().shells.len().min(SHELL_ROWS);
+    let shell_rows = if shells < 2 || short { 0 } else { 3 + shells };
+
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(10), // Activity chart
-            Constraint::Min(0),     // Top commands
+            Constraint::Length(if short { 0 } else { 10 }), // Activity chart
+            Constraint::Length(shell_rows as u16),          // Shell comparison
+            Constraint::Min(0),                             // Top commands
         ])
         .split(area);
 
-    // Activity trend chart (ASCII bar chart style) - removed duplicate title
-    let activity_text = vec![
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            "█████████████████████████████████████████████████",
-            theme.style_primary(),
-        )]),
-        Line::from(vec![Span::styled(
-            "████████████████████████████████████████████",
-            theme.style_primary(),
-        )]),
-        Line::from(vec![Span::styled(
-            "██████████████████████████████████████████████████████",
-            theme.style_primary(),
-        )]),
-        Line::from(vec![Span::styled(
-            "████████████████████████████████████████",
-            theme.style_primary(),
-        )]),
-        Line::from(vec![Span::styled(
-            "██████████████████████████████████████████████████",
-            theme.style_primary(),
-        )]),
-    ];
+    if !short {
+        draw_activity_trend(f, app, chunks[0], theme);
+    }
+    if shell_rows > 0 {
+        draw_shell_comparison(f, app, chunks[1], theme);
+    }
+    draw_top_commands(f, app, chunks[2], theme);
+}
 
-    let activity_chart = Paragraph::new(activity_text).block(
-        Block::default()
-            .title("Activity Trend")
-            .borders(Borders::ALL)
-            .border_style(theme.style_border()),
+/// The busiest shells side by side: how much each ran, how often it
+/// succeeded, its share of dangerous commands and its favourite command.
+fn draw_shell_comparison(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    let mut lines = vec![Line::from(Span::styled(
+        format!(
+            "{:<8} {:>8} {:>8} {:>7}  Top command",
+            "Shell", "Commands", "Success", "Danger"
+        ),
+        theme.style_text_dim(),
+    ))];
+    lines.extend(
+        app.analysis()
+            .shells
+            .iter()
+            .take(SHELL_ROWS)
+            .map(|(shell, stats)| {
+                let danger_ratio = if stats.total_commands == 0 {
+                    0.0
+                } else {
+                    stats.dangerous_commands as f32 / stats.total_commands as f32
+                };
+                Line::from(vec![
+                    Span::styled(format!("{:<8.8} ", shell), theme.style_primary()),
+                    Span::styled(format!("{:>8} ", stats.total_commands), theme.style_value()),
+                    Span::styled(
+                        format!("{:>7.1}% ", stats.success_rate * 100.0),
+                        theme.style_success(),
+                    ),
+                    Span::styled(
+                        format!("{:>6.1}%  ", danger_ratio * 100.0),
+                        if stats.dangerous_commands > 0 {
+                            theme.style_danger()
+                        } else {
+                            theme.style_text_dim()
+                        },
+                    ),
+                    Span::styled(
+                        stats
+                            .top_commands
+                            .first()
+                            .map_or("", |freq| freq.command.as_str()),
+                        theme.style_text(),
+                    ),
+                ])
+            }),
     );
 
-    f.render_widget(activity_chart, chunks[0]);
-
-    // Top commands - minimal style
-    let top_commands: Vec<ListItem> = vec![
-        ("git status", 45),
-        ("ls -la", 32),
-        ("cd ..", 28),
-        ("npm install", 21),
-        ("docker ps", 18),
-    ]
-    .into_iter()
-    .enumerate()
-    .map(|(i, (cmd, count))| {
-        ListItem::new(Line::from(vec![
-            Span::styled(format!("{}. ", i + 1), theme.style_text_dim()),
-            Span::styled(cmd, theme.style_text()),
-            Span::styled(format!(" ({})", count), theme.style_accent()),
-        ]))
-    })
-    .collect();
-
-    let top_commands_list = List::new(top_commands)
+    let comparison = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title("Shells")
+                .borders(Borders::ALL)
+                .border_style(theme.style_border()),
+        )
+        .style(theme.style_text());
+
+    f.render_widget(comparison, area);
+}
+
+/// The most run commands with their share of the history and a sparkline of
+/// the last `COMMAND_TREND_DAYS` days.
+fn draw_top_commands(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    let top_commands = app
+        .command_stats
+        .as_ref()
+        .map_or(&[][..], |stats| stats.top_commands.as_slice());
+    let end = app
+        .analysis()
+        .daily_activity
+        .last()
+        .map_or_else(|| Utc::now().date_naive(), |(date, _)| *date);
+
+    // Rank, percentage and sparkline take a fixed width; the command gets the rest
+    let command_width = (area.width as usize).saturating_sub(2 + 4 + 8 + COMMAND_TREND_DAYS + 1);
+    let items: Vec<ListItem> = top_commands
+        .iter()
+        .enumerate()
+        .map(|(i, freq)| top_command_item(i + 1, freq, end, command_width, theme))
+        .collect();
+
+    let top_commands_list = List::new(items)
         .block(
             Block::default()
                 .title("Top Commands")
@@ -202,63 +287,245 @@ fn draw_left_panel(f: &mut Frame, _app: &App, area: Rect, theme: &Theme) {
         )
         .style(theme.style_text());
 
-    f.render_widget(top_commands_list, chunks[1]);
+    f.render_widget(top_commands_list, area);
+}
+
+fn top_command_item<'a>(
+    rank: usize,
+    freq: &'a CommandFrequency,
+    end: chrono::NaiveDate,
+    command_width: usize,
+    theme: &Theme,
+) -> ListItem<'a> {
+    let command: String = if freq.command.chars().count() > command_width {
+        let kept: String = freq
+            .command
+            .chars()
+            .take(command_width.saturating_sub(1))
+            .collect();
+        format!("{}…", kept)
+    } else {
+        freq.command.clone()
+    };
+
+    ListItem::new(Line::from(vec![
+        Span::styled(format!("{:>2}. ", rank), theme.style_text_dim()),
+        Span::styled(
+            format!("{:<width$}", command, width = command_width),
+            theme.style_text(),
+        ),
+        Span::styled(format!("{:>6.1}% ", freq.percentage), theme.style_accent()),
+        Span::styled(
+            spark_text(&freq.runs_until(end, COMMAND_TREND_DAYS)),
+            theme.style_primary(),
+        ),
+    ]))
+}
+
+/// A one-line sparkline, scaled to the busiest value.
+fn spark_text(values: &[u64]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let peak = values.iter().copied().max().unwrap_or(0);
+    values
+        .iter()
+        .map(|&value| {
+            if value == 0 || peak == 0 {
+                ' '
+            } else {
+                BARS[((value * 7).div_ceil(peak)) as usize]
+            }
+        })
+        .collect()
+}
+
+/// Commands per day over the last `TREND_DAYS` days, zeros included.
+fn draw_activity_trend(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    let series = &app.analysis().daily_activity;
+    let data: Vec<u64> = series.iter().map(|(_, count)| *count as u64).collect();
+    let total: u64 = data.iter().sum();
+    let peak = data.iter().copied().max().unwrap_or(0);
+
+    let title = match (series.first(), series.last()) {
+        (Some((first, _)), Some((last, _))) => format!(
+            "Activity {} – {} ({} commands, peak {}/day)",
+            first.format("%b %d"),
+            last.format("%b %d"),
+            total,
+            peak
+        ),
+        _ => "Activity".to_string(),
+    };
+
+    let sparkline = Sparkline::default()
+        .block(
+            Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_style(theme.style_border()),
+        )
+        .data(&data)
+        .style(theme.style_primary());
+
+    f.render_widget(sparkline, area);
 }
 
 fn draw_right_panel(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Percentage(60), // Recent activity
-            Constraint::Percentage(40), // AI Insights
+            Constraint::Length(3),                       // Productivity score
+            Constraint::Length(7),                       // Sessions
+            Constraint::Length(2 + FAILURE_ROWS as u16), // Failing commands
+            Constraint::Min(0),                          // Insights and suggestions
         ])
         .split(area);
 
-    // Recent activity - minimal style with colorized timestamps
-    let recent_commands: Vec<ListItem> = vec![
-        ("17:50:37", "ls"),
-        ("17:50:38", "clear"),
-        ("17:50:18", "sudo pacman -Syu"),
-        ("17:50:31", "yay -Syu"),
-        ("17:59:31", "clear"),
-        ("17:57:32", "exit"),
-        ("18:02:42", "yay -Syu"),
-        ("19:28:00", "clear"),
-        ("19:28:18", "exit"),
-        ("19:22:37", "local"),
-        ("19:22:40", "ls"),
-        ("19:22:43", "z share"),
-    ]
-    .into_iter()
-    .enumerate()
-    .map(|(i, (time, cmd))| {
-        ListItem::new(Line::from(vec![
-            Span::styled(format!("{}. ", i + 1), theme.style_text_dim()),
-            Span::styled(time, theme.style_primary()), // Colorized timestamp
-            Span::raw(" "),
-            Span::styled(cmd, theme.style_text()),
-        ]))
-    })
-    .collect();
-
-    let recent_list = List::new(recent_commands)
+    draw_productivity_gauge(f, app, chunks[0], theme);
+    draw_session_averages(f, app, chunks[1], theme);
+    draw_failure_hotspots(f, app, chunks[2], theme);
+    draw_insights(f, app, chunks[3], theme);
+}
+
+/// The commands that failed most often, with how often they fail when run.
+fn draw_failure_hotspots(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    let hotspots = app
+        .command_stats
+        .as_ref()
+        .map_or(&[][..], |stats| stats.failure_hotspots.as_slice());
+
+    let items: Vec<ListItem> = if hotspots.is_empty() {
+        vec![ListItem::new(Span::styled(
+            "No failed commands recorded",
+            theme.style_text_dim(),
+        ))]
+    } else {
+        hotspots
+            .iter()
+            .take(FAILURE_ROWS)
+            .map(|(command, failures, rate)| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("{:>5}× ", failures), theme.style_danger()),
+                    Span::styled(format!("{:>4.0}%  ", rate * 100.0), theme.style_warning()),
+                    Span::styled(command.as_str(), theme.style_text()),
+                ]))
+            })
+            .collect()
+    };
+
+    let list = List::new(items)
         .block(
             Block::default()
-                .title("Recent Activity")
+                .title("Most Failing Commands")
                 .borders(Borders::ALL)
                 .border_style(theme.style_border()),
         )
         .style(theme.style_text());
 
-    f.render_widget(recent_list, chunks[0]);
+    f.render_widget(list, area);
+}
 
-    // AI Insights - minimal style (fixed double text issue)
-    let insights = vec![
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            "High Docker activity detected",
-            theme.style_text(),
-        )]),
+fn draw_productivity_gauge(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    let score = app
+        .productivity_stats
+        .as_ref()
+        .map_or(0.0, |stats| stats.productivity_score);
+    let style = if score >= 70.0 {
+        theme.style_success()
+    } else if score >= 40.0 {
+        theme.style_warning()
+    } else {
+        theme.style_danger()
+    };
+
+    let gauge = Gauge::default()
+        .block(
+            Block::default()
+                .title("Productivity Score")
+                .borders(Borders::ALL)
+                .border_style(theme.style_border()),
+        )
+        .gauge_style(style)
+        .ratio((score as f64 / 100.0).clamp(0.0, 1.0))
+        .label(format!("{:.0}%", score));
+
+    f.render_widget(gauge, area);
+}
+
+fn draw_session_averages(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    let lines = match &app.session_stats {
+        Some(stats) if stats.total_sessions > 0 => {
+            let longest = stats.longest_session;
+            vec![
+                session_line("Sessions:      ", stats.total_sessions.to_string(), theme),
+                session_line(
+                    "Avg length:    ",
+                    format!("{:.0} min", stats.average_session_length),
+                    theme,
+                ),
+                session_line(
+                    "Avg commands:  ",
+                    format!("{:.1}", stats.average_commands_per_session),
+                    theme,
+                ),
+                session_line(
+                    "Longest:       ",
+                    format!(
+                        "{}h {:02}m",
+                        longest.num_hours(),
+                        longest.num_minutes() % 60
+                    ),
+                    theme,
+                ),
+                session_line(
+                    "Busiest:       ",
+                    stats.most_productive_session.clone(),
+                    theme,
+                ),
+            ]
+        }
+        _ => vec![Line::from(Span::styled(
+            "No sessions yet",
+            theme.style_text_dim(),
+        ))],
+    };
+
+    let sessions = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title("Sessions")
+                .borders(Borders::ALL)
+                .border_style(theme.style_border()),
+        )
+        .style(theme.style_text());
+
+    f.render_widget(sessions, area);
+}
+
+fn session_line(label: &'static str, value: String, theme: &Theme) -> Line<'static> {
+    Line::from(vec![
+        Span::styled(label, theme.style_text_dim()),
+        Span::styled(value, theme.style_value()),
+    ])
+}
+
+fn draw_insights(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    let (current_streak, longest_streak) = app
+        .productivity_stats
+        .as_ref()
+        .map(|stats| (stats.current_streak_days, stats.longest_streak_days))
+        .unwrap_or_default();
+    let mut insights = vec![
+        Line::from(vec![
+            Span::styled("Streak: ", theme.style_text()),
+            Span::styled(
+                format!("{} days", current_streak),
+                theme.style_accent().add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!(" (longest {})", longest_streak),
+                theme.style_text_dim(),
+            ),
+        ]),
         Line::from(vec![Span::styled(
             format!(
                 "{} risky commands need review",
@@ -277,21 +544,35 @@ fn draw_right_panel(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
             format!("{} unique endpoints accessed", app.stats.network_endpoints),
             theme.style_info(),
         )]),
-        Line::from(""),
-        Line::from(vec![
-            Span::styled("Productivity Score: ", theme.style_text()),
-            Span::styled("87%", theme.style_success().add_modifier(Modifier::BOLD)),
-        ]),
     ];
 
+    let suggestions = app
+        .productivity_stats
+        .as_ref()
+        .map_or(&[][..], |stats| stats.improvement_suggestions.as_slice());
+    if !suggestions.is_empty() {
+        insights.push(Line::from(""));
+        insights.push(Line::from(Span::styled(
+            "Suggestions",
+            theme.style_info().add_modifier(Modifier::BOLD),
+        )));
+        insights.extend(suggestions.iter().map(|suggestion| {
+            Line::from(vec![
+                Span::styled("• ", theme.style_accent()),
+                Span::styled(suggestion.as_str(), theme.style_text()),
+            ])
+        }));
+    }
+
     let insights_paragraph = Paragraph::new(insights)
         .block(
             Block::default()
-                .title("AI Insights")
+                .title("Insights")
                 .borders(Borders::ALL)
                 .border_style(theme.style_border()),
         )
+        .wrap(ratatui::widgets::Wrap { trim: true })
         .style(theme.style_text());
 
-    f.render_widget(insights_paragraph, chunks[1]);
+    f.render_widget(insights_paragraph, area);
 }
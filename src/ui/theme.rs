@@ -15,6 +15,8 @@ pub struct Theme {
     pub text_dim: Color,
     pub border: Color,
     pub highlight: Color,
+    /// No colors at all; emphasis comes from bold, dim and reverse video instead
+    pub monochrome: bool,
 }
 
 impl Default for Theme {
@@ -23,7 +25,39 @@ impl Default for Theme {
     }
 }
 
+/// Names accepted by the `theme` config key.
+pub const THEME_NAMES: &[&str] = &["dark", "cyberpunk", "matrix", "monochrome"];
+
 impl Theme {
+    /// The theme named in the config, or monochrome when `NO_COLOR` is set to
+    /// anything non-empty (see <https://no-color.org>). Unknown names fall back
+    /// to the default theme.
+    pub fn from_config(name: &str) -> Self {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        if no_color {
+            return Self::monochrome();
+        }
+
+        Self::from_name(name).unwrap_or_else(|| {
+            log::warn!(
+                "Unknown theme '{}' (expected one of {}), using the default",
+                name,
+                THEME_NAMES.join(", ")
+            );
+            Self::default()
+        })
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "dark" | "default" => Some(Self::minimal_dark()),
+            "cyberpunk" => Some(Self::cyberpunk()),
+            "matrix" => Some(Self::matrix()),
+            "monochrome" | "mono" => Some(Self::monochrome()),
+            _ => None,
+        }
+    }
+
     pub fn minimal_dark() -> Self {
         Self {
             primary: Color::Rgb(135, 206, 250),   // Light blue
@@ -39,10 +73,10 @@ impl Theme {
             text_dim: Color::Rgb(176, 196, 222),  // Light steel blue
             border: Color::Rgb(135, 206, 250),    // Light blue borders
             highlight: Color::Rgb(255, 99, 132),  // Red highlight
+            monochrome: false,
         }
     }
 
-    #[allow(dead_code)]
     pub fn cyberpunk() -> Self {
         Self {
             primary: Color::Rgb(0, 255, 255),    // Cyan
@@ -58,10 +92,10 @@ impl Theme {
             text_dim: Color::Rgb(160, 160, 160), // Gray
             border: Color::Rgb(64, 224, 208),    // Turquoise
             highlight: Color::Rgb(255, 215, 0),  // Gold
+            monochrome: false,
         }
     }
 
-    #[allow(dead_code)]
     pub fn matrix() -> Self {
         Self {
             primary: Color::Rgb(0, 255, 0),     // Matrix Green
@@ -77,6 +111,36 @@ impl Theme {
             text_dim: Color::Rgb(0, 150, 0),    // Dim Green
             border: Color::Rgb(0, 200, 0),      // Green Border
             highlight: Color::Rgb(0, 255, 127), // Bright Green
+            monochrome: false,
+        }
+    }
+
+    /// The terminal's own foreground and background everywhere.
+    pub fn monochrome() -> Self {
+        Self {
+            primary: Color::Reset,
+            secondary: Color::Reset,
+            accent: Color::Reset,
+            success: Color::Reset,
+            warning: Color::Reset,
+            danger: Color::Reset,
+            info: Color::Reset,
+            background: Color::Reset,
+            surface: Color::Reset,
+            text: Color::Reset,
+            text_dim: Color::Reset,
+            border: Color::Reset,
+            highlight: Color::Reset,
+            monochrome: true,
+        }
+    }
+
+    /// Bold in monochrome, where it's the only way to make something stand out.
+    fn emphasized(&self, style: Style) -> Style {
+        if self.monochrome {
+            style.add_modifier(Modifier::BOLD)
+        } else {
+            style
         }
     }
 
@@ -101,7 +165,7 @@ impl Theme {
     }
 
     pub fn style_danger(&self) -> Style {
-        Style::default().fg(self.danger)
+        self.emphasized(Style::default().fg(self.danger))
     }
 
     pub fn style_info(&self) -> Style {
@@ -113,7 +177,11 @@ impl Theme {
     }
 
     pub fn style_text_dim(&self) -> Style {
-        Style::default().fg(self.text_dim)
+        if self.monochrome {
+            Style::default().add_modifier(Modifier::DIM)
+        } else {
+            Style::default().fg(self.text_dim)
+        }
     }
 
     pub fn style_border(&self) -> Style {
@@ -127,6 +195,9 @@ impl Theme {
     }
 
     pub fn style_selected(&self) -> Style {
+        if self.monochrome {
+            return Style::default().add_modifier(Modifier::REVERSED | Modifier::BOLD);
+        }
         Style::default()
             .bg(self.primary)
             .fg(self.background)
@@ -138,6 +209,53 @@ impl Theme {
             .fg(self.accent)
             .add_modifier(Modifier::BOLD)
     }
+
+    /// Background for the selected row in lists whose spans carry their own colors.
+    pub fn style_row_highlight(&self) -> Style {
+        if self.monochrome {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default().bg(Color::DarkGray)
+        }
+    }
+
+    /// A shortcut key in a tab's control bar, filled in when it's the active choice.
+    pub fn style_key(&self, active: bool, color: Color) -> Style {
+        match (active, self.monochrome) {
+            (false, _) => Style::default().fg(color),
+            (true, true) => self.style_selected(),
+            (true, false) => Style::default()
+                .fg(self.background)
+                .bg(color)
+                .add_modifier(Modifier::BOLD),
+        }
+    }
+
+    /// Headline numbers in metric cards.
+    pub fn style_value(&self) -> Style {
+        Style::default().fg(self.text).add_modifier(Modifier::BOLD)
+    }
+
+    /// Encrypted connections and other things that are fine as they are.
+    pub fn style_secure(&self) -> Style {
+        Style::default().fg(self.success)
+    }
+
+    /// Plain-text connections and other things worth a second look.
+    pub fn style_insecure(&self) -> Style {
+        self.emphasized(Style::default().fg(self.danger))
+    }
+
+    /// Green, amber or red for a 0-100 score where higher is better.
+    pub fn score_color(&self, score: f32) -> Color {
+        if score > 80.0 {
+            self.success
+        } else if score > 60.0 {
+            self.warning
+        } else {
+            self.danger
+        }
+    }
 }
 
 // Nerd Font Icons
@@ -268,14 +386,14 @@ pub fn get_activity_icon(level: f32) -> &'static str {
     }
 }
 
-pub fn get_manager_info(manager: &str) -> (&'static str, ratatui::style::Color) {
+pub fn get_manager_info(manager: &str, theme: &Theme) -> (&'static str, Color) {
     match manager {
-        "npm" | "yarn" | "pnpm" => (Icons::NPM, Color::Red),
-        "pip" | "pip3" | "pipenv" | "poetry" => (Icons::PYTHON, Color::Blue),
-        "cargo" => (Icons::RUST, Color::Yellow),
-        "apt" | "apt-get" | "yum" | "dnf" | "pacman" => (Icons::LINUX, Color::Green),
-        "brew" => (Icons::APPLE, Color::White),
-        "docker" => (Icons::DOCKER, Color::Cyan),
-        _ => (Icons::PACKAGE, Color::Gray),
+        "npm" | "yarn" | "pnpm" => (Icons::NPM, theme.danger),
+        "pip" | "pip3" | "pipenv" | "poetry" => (Icons::PYTHON, theme.secondary),
+        "cargo" => (Icons::RUST, theme.warning),
+        "apt" | "apt-get" | "yum" | "dnf" | "pacman" => (Icons::LINUX, theme.success),
+        "brew" => (Icons::APPLE, theme.text),
+        "docker" => (Icons::DOCKER, theme.info),
+        _ => (Icons::PACKAGE, theme.text_dim),
     }
 }
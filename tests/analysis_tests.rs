@@ -1,4 +1,7 @@
+mod common;
+
 use chrono::{DateTime, TimeZone, Utc};
+use common::command_at;
 use whiskerlog::analysis::heatmap::*;
 use whiskerlog::analysis::package_tracker::*;
 use whiskerlog::*;
@@ -21,6 +24,8 @@ fn create_test_command(cmd: &str, timestamp: DateTime<Utc>, packages: Vec<Packag
         danger_reasons: vec![],
         is_experiment: false,
         experiment_tags: vec![],
+        original_command: None,
+        tags: vec![],
     }
 }
 
@@ -384,3 +389,1209 @@ fn test_heatmap_analysis_with_multiple_days() {
     assert!(analysis.weekday_ratio >= 0.0 && analysis.weekday_ratio <= 1.0);
     assert!(analysis.weekend_ratio >= 0.0 && analysis.weekend_ratio <= 1.0);
 }
+
+#[test]
+fn test_alias_suggestions_have_stable_order_and_quoting() {
+    use whiskerlog::analysis::alias_suggest::{AliasSuggester, AliasSuggestion};
+
+    let now = Utc::now();
+    let commands: Vec<Command> = [
+        "git status --short",
+        "docker compose up -d",
+        "kubectl get pods -A",
+    ]
+    .iter()
+    .flat_map(|cmd| std::iter::repeat_n(*cmd, 4))
+    .map(|cmd| create_test_command(cmd, now, vec![]))
+    .collect();
+
+    let suggester = AliasSuggester::new();
+    let first: Vec<String> = suggester
+        .analyze_alias_opportunities(&commands)
+        .suggestions
+        .into_iter()
+        .map(|s| s.command)
+        .collect();
+    for _ in 0..5 {
+        let again: Vec<String> = suggester
+            .analyze_alias_opportunities(&commands)
+            .suggestions
+            .into_iter()
+            .map(|s| s.command)
+            .collect();
+        assert_eq!(first, again);
+    }
+
+    let suggestion = AliasSuggestion {
+        command: "grep -r 'TODO' .".to_string(),
+        suggested_alias: "gt".to_string(),
+        frequency: 5,
+        time_saved_per_use: 14,
+        total_time_saved: 70,
+    };
+    assert_eq!(
+        suggester.format_alias(&suggestion, "bash"),
+        r#"alias gt='grep -r '\''TODO'\'' .'"#
+    );
+}
+
+#[test]
+fn test_generate_shell_aliases_quoting_per_shell() {
+    use whiskerlog::analysis::alias_suggest::{AliasSuggester, AliasSuggestion};
+
+    let suggestions = vec![AliasSuggestion {
+        command: "git log --format='%h %s'".to_string(),
+        suggested_alias: "glog".to_string(),
+        frequency: 4,
+        time_saved_per_use: 19,
+        total_time_saved: 76,
+    }];
+    let suggester = AliasSuggester::new();
+
+    let bash = suggester.generate_shell_aliases(&suggestions, "bash");
+    assert!(bash.contains(r#"alias glog='git log --format='\''%h %s'\'''"#));
+    assert_eq!(bash, suggester.generate_shell_aliases(&suggestions, "zsh"));
+
+    let fish = suggester.generate_shell_aliases(&suggestions, "fish");
+    assert!(fish.contains(r#"alias glog 'git log --format=\'%h %s\''"#));
+
+    assert!(suggester
+        .generate_shell_aliases(&suggestions, "tcsh")
+        .contains("not supported"));
+}
+
+#[test]
+fn test_append_shell_aliases_skips_existing_names() {
+    use whiskerlog::analysis::alias_suggest::{AliasSuggester, AliasSuggestion};
+
+    let suggestion = |alias: &str, command: &str| AliasSuggestion {
+        command: command.to_string(),
+        suggested_alias: alias.to_string(),
+        frequency: 3,
+        time_saved_per_use: 10,
+        total_time_saved: 30,
+    };
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("aliases.bash");
+    std::fs::write(&path, "alias gs='git status -sb'").unwrap();
+
+    let suggester = AliasSuggester::new();
+    let suggestions = vec![
+        suggestion("gs", "git status"),
+        suggestion("dps", "docker ps --all"),
+    ];
+    let written = suggester
+        .append_shell_aliases(&suggestions, "bash", &path)
+        .unwrap();
+    assert_eq!(written, 1);
+
+    let content = std::fs::read_to_string(&path).unwrap();
+    assert!(content.starts_with("alias gs='git status -sb'\n"));
+    assert!(content.contains("# Whiskerlog generated "));
+    assert!(content.contains("alias dps='docker ps --all'"));
+    assert_eq!(content.matches("alias gs").count(), 1);
+
+    // Nothing new the second time round, and the file is left alone
+    let written = suggester
+        .append_shell_aliases(&suggestions, "bash", &path)
+        .unwrap();
+    assert_eq!(written, 0);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), content);
+}
+
+#[test]
+fn test_alias_suggestions_resolve_collisions_and_overlaps() {
+    use whiskerlog::analysis::alias_suggest::AliasSuggester;
+
+    let now = Utc::now();
+    let commands: Vec<Command> = [
+        ("docker logs --tail 100 web", 5),
+        ("deploy latest-build", 4),
+        ("git commit -m wip", 6),
+        ("git commit", 3),
+    ]
+    .iter()
+    .flat_map(|(cmd, count)| std::iter::repeat_n(*cmd, *count))
+    .map(|cmd| create_test_command(cmd, now, vec![]))
+    .collect();
+
+    let analysis = AliasSuggester::new().analyze_alias_opportunities(&commands);
+    let alias_for = |command: &str| {
+        analysis
+            .suggestions
+            .iter()
+            .find(|s| s.command == command)
+            .map(|s| s.suggested_alias.as_str())
+    };
+
+    // Both generate `dl`; the more valuable one keeps it
+    assert_eq!(alias_for("docker logs --tail 100 web"), Some("dl"));
+    assert_eq!(alias_for("deploy latest-build"), Some("dl2"));
+    // `gc` only covers a shorter form of what `gcm` already does
+    assert_eq!(alias_for("git commit -m wip"), Some("gcm"));
+    assert_eq!(alias_for("git commit"), None);
+
+    let names: std::collections::HashSet<&str> = analysis
+        .suggestions
+        .iter()
+        .map(|s| s.suggested_alias.as_str())
+        .collect();
+    assert_eq!(names.len(), analysis.suggestions.len());
+    assert_eq!(
+        analysis.potential_savings,
+        analysis
+            .suggestions
+            .iter()
+            .map(|s| s.total_time_saved)
+            .sum::<usize>()
+    );
+}
+
+#[test]
+fn test_alias_suggestions_stop_before_varying_arguments() {
+    use whiskerlog::analysis::alias_suggest::AliasSuggester;
+
+    let now = Utc::now();
+    let commands: Vec<Command> = [
+        "git commit -m \"fix parser\"",
+        "git commit -m 'typo'",
+        "git commit -m \"bump deps\"",
+    ]
+    .iter()
+    .map(|cmd| create_test_command(cmd, now, vec![]))
+    .collect();
+
+    let analysis = AliasSuggester::new().analyze_alias_opportunities(&commands);
+    let commands: Vec<&str> = analysis
+        .suggestions
+        .iter()
+        .map(|s| s.command.as_str())
+        .collect();
+    assert_eq!(commands, vec!["git commit -m"]);
+}
+
+#[test]
+fn test_alias_suggestions_avoid_shadowing_common_commands() {
+    use whiskerlog::analysis::alias_suggest::AliasSuggester;
+
+    let now = Utc::now();
+    let commands: Vec<Command> = std::iter::repeat_n("ls -lh /var/www/html", 4)
+        .map(|cmd| create_test_command(cmd, now, vec![]))
+        .collect();
+
+    let analysis = AliasSuggester::new().analyze_alias_opportunities(&commands);
+    assert_eq!(analysis.suggestions.len(), 1);
+    let suggestion = &analysis.suggestions[0];
+    assert_eq!(suggestion.suggested_alias, "l2");
+    assert_eq!(
+        suggestion.time_saved_per_use,
+        "ls -lh /var/www/html".len() - 2
+    );
+    assert_eq!(
+        suggestion.total_time_saved,
+        suggestion.time_saved_per_use * 4
+    );
+}
+
+#[test]
+fn test_heatmap_buckets_in_configured_timezone() {
+    // Monday 2024-01-15 23:30 UTC
+    let timestamp = Utc.with_ymd_and_hms(2024, 1, 15, 23, 30, 0).unwrap();
+    let commands = vec![create_test_command("ls", timestamp, vec![])];
+
+    // Tokyo is UTC+9: 08:30 on Tuesday
+    let tokyo = HeatmapAnalyzer::with_timezone(chrono_tz::Asia::Tokyo);
+    let heatmap = tokyo.generate_heatmap(&commands, TimeRange::Year, ViewMode::All);
+    assert_eq!(heatmap.grid[8][1], 1.0);
+    assert_eq!(heatmap.grid[23][0], 0.0);
+    let patterns = tokyo.analyze_work_patterns(&commands);
+    assert_eq!(patterns.most_active_hour, 8);
+    assert_eq!(patterns.most_active_day, chrono::Weekday::Tue);
+
+    // New York is UTC-5 in January: 18:30 on Monday
+    let new_york = HeatmapAnalyzer::with_timezone(chrono_tz::America::New_York);
+    let heatmap = new_york.generate_heatmap(&commands, TimeRange::Year, ViewMode::All);
+    assert_eq!(heatmap.grid[18][0], 1.0);
+    let patterns = new_york.analyze_work_patterns(&commands);
+    assert_eq!(patterns.most_active_hour, 18);
+    assert_eq!(patterns.most_active_day, chrono::Weekday::Mon);
+
+    // Zone names come from the config; bad ones fall back to local time
+    let config = Config {
+        timezone: Some("Asia/Tokyo".to_string()),
+        ..Config::default()
+    };
+    let from_config = HeatmapAnalyzer::from_config(&config);
+    assert_eq!(
+        from_config
+            .analyze_work_patterns(&commands)
+            .most_active_hour,
+        8
+    );
+    let config = Config {
+        timezone: Some("Mars/Olympus_Mons".to_string()),
+        ..Config::default()
+    };
+    let _ = HeatmapAnalyzer::from_config(&config);
+}
+
+#[test]
+fn test_version_downgrade_uses_install_order() {
+    let tracker = PackageTracker::new();
+    let versions = |vs: &[&str]| vs.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+
+    assert!(!tracker.has_version_downgrade(&versions(&["1.2.0", "1.3.0"])));
+    assert!(tracker.has_version_downgrade(&versions(&["1.3.0", "1.2.0"])));
+    // Numeric, not lexical: 1.10 is newer than 1.9
+    assert!(!tracker.has_version_downgrade(&versions(&["1.9.0", "1.10.0"])));
+    // Pre-releases sort before their release
+    assert!(tracker.has_version_downgrade(&versions(&["2.0.0", "2.0.0-rc.1"])));
+    // Non-semver strings fall back to a component-wise compare
+    assert!(!tracker.has_version_downgrade(&versions(&["3.9", "3.11", "v3.12"])));
+    assert!(tracker.has_version_downgrade(&versions(&["20.04", "18.04"])));
+    // Going back to an older version after an upgrade counts
+    assert!(tracker.has_version_downgrade(&versions(&["1.2.0", "1.3.0", "1.2.0"])));
+
+    let base = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let install = |day: i64, version: &str| {
+        create_test_command(
+            &format!("npm install lodash@{}", version),
+            base + chrono::Duration::days(day),
+            vec![create_test_package(
+                "npm",
+                "lodash",
+                "install",
+                Some(version),
+            )],
+        )
+    };
+
+    // Upgrades only, listed out of order: not a downgrade
+    let analysis = tracker.analyze_package_usage(&[install(2, "4.17.21"), install(1, "4.17.0")]);
+    assert_eq!(analysis.version_conflicts.len(), 1);
+    assert!(matches!(
+        analysis.version_conflicts[0].conflict_type,
+        ConflictType::MultipleVersions
+    ));
+
+    let analysis = tracker.analyze_package_usage(&[install(1, "4.17.21"), install(2, "4.17.0")]);
+    assert!(matches!(
+        analysis.version_conflicts[0].conflict_type,
+        ConflictType::DowngradeDetected
+    ));
+}
+
+#[test]
+fn test_quick_removal_trend_names_the_package() {
+    let tracker = PackageTracker::new();
+    let base = Utc.with_ymd_and_hms(2024, 5, 1, 9, 0, 0).unwrap();
+
+    let commands = vec![
+        create_test_command(
+            "sudo apt remove cowsay",
+            base + chrono::Duration::hours(2),
+            vec![create_test_package("apt", "cowsay", "remove", None)],
+        ),
+        create_test_command(
+            "sudo apt install cowsay",
+            base,
+            vec![create_test_package("apt", "cowsay", "install", None)],
+        ),
+    ];
+
+    let analysis = tracker.analyze_package_usage(&commands);
+    let trend = analysis
+        .package_trends
+        .iter()
+        .find(|t| matches!(t.trend_type, TrendType::QuickRemoval))
+        .expect("quick removal trend");
+
+    assert_eq!(trend.package, "cowsay");
+    assert_eq!(trend.manager, "apt");
+    assert_eq!(trend.hours_to_removal, Some(2));
+}
+
+#[test]
+fn test_sessions_split_on_idle_gap() {
+    use whiskerlog::analysis::sessions::{
+        split_sessions, summarize_sessions, DEFAULT_IDLE_MINUTES,
+    };
+    use whiskerlog::analysis::stats::StatsAnalyzer;
+
+    let at = |h, m| Utc.with_ymd_and_hms(2024, 5, 1, h, m, 0).unwrap();
+    let commands = vec![
+        create_test_command("git status", at(10, 0), vec![]),
+        create_test_command("git diff", at(10, 5), vec![]),
+        create_test_command("cargo build", at(13, 0), vec![]),
+    ];
+
+    let sessions = split_sessions(&commands, chrono::Duration::minutes(DEFAULT_IDLE_MINUTES));
+    assert_eq!(sessions.len(), 2);
+    assert_eq!(sessions[0].id, "test-session");
+    assert_eq!(sessions[0].commands.len(), 2);
+    assert_eq!(sessions[1].id, "test-session-2");
+    assert_eq!(sessions[1].commands[0].command, "cargo build");
+
+    let stats = StatsAnalyzer::new().analyze_sessions(&commands);
+    assert_eq!(stats.total_sessions, 2);
+
+    // A wider threshold keeps everything together
+    let stats = StatsAnalyzer::with_idle_threshold(240).analyze_sessions(&commands);
+    assert_eq!(stats.total_sessions, 1);
+
+    // The Sessions tab's rows, most recent first
+    let rows = summarize_sessions(&commands, chrono::Duration::minutes(DEFAULT_IDLE_MINUTES));
+    let ids: Vec<&str> = rows.iter().map(|row| row.id.as_str()).collect();
+    assert_eq!(ids, vec!["test-session-2", "test-session"]);
+    assert_eq!(rows[1].command_count, 2);
+    assert_eq!(rows[1].duration_minutes, 5);
+}
+
+#[test]
+fn test_projects_group_by_repo_root_and_projects_dir() {
+    use whiskerlog::analysis::projects::{ProjectAnalyzer, UNKNOWN_PROJECT};
+
+    let home = tempfile::TempDir::new().unwrap();
+    let projects_dir = home.path().join("projects");
+    let repo = home.path().join("code/whiskerlog");
+    std::fs::create_dir_all(repo.join(".git")).unwrap();
+    std::fs::create_dir_all(repo.join("src/ui")).unwrap();
+    std::fs::create_dir_all(projects_dir.join("site/assets")).unwrap();
+
+    let base = Utc.with_ymd_and_hms(2024, 5, 1, 9, 0, 0).unwrap();
+    let in_dir = |cmd: &str, minutes: i64, dir: Option<&std::path::Path>| {
+        let mut command =
+            create_test_command(cmd, base + chrono::Duration::minutes(minutes), vec![]);
+        command.working_directory = dir.map(|d| d.display().to_string());
+        command
+    };
+
+    let mut risky = in_dir("sudo rm -rf target", 3, Some(&repo));
+    risky.is_dangerous = true;
+    let commands = vec![
+        in_dir("cargo build", 0, Some(&repo)),
+        in_dir("cargo test", 1, Some(&repo.join("src/ui"))),
+        in_dir("git status", 2, Some(&repo.join("src"))),
+        risky,
+        in_dir("npm run build", 4, Some(&projects_dir.join("site/assets"))),
+        in_dir("ls", 5, None),
+    ];
+
+    let analysis = ProjectAnalyzer::with_projects_dir(&projects_dir).analyze_projects(&commands);
+    assert_eq!(analysis.projects.len(), 3);
+
+    let repo_stats = &analysis.projects[0];
+    assert_eq!(repo_stats.name, "whiskerlog");
+    assert_eq!(repo_stats.root, Some(repo.display().to_string()));
+    assert_eq!(repo_stats.command_count, 4);
+    assert_eq!(repo_stats.top_tools[0], ("cargo".to_string(), 2));
+    assert!(repo_stats.top_tools.contains(&("rm".to_string(), 1)));
+    assert!((repo_stats.danger_ratio() - 0.25).abs() < f64::EPSILON);
+    assert_eq!(repo_stats.last_active, base + chrono::Duration::minutes(3));
+
+    let site = analysis.projects.iter().find(|p| p.name == "site").unwrap();
+    assert_eq!(
+        site.root,
+        Some(projects_dir.join("site").display().to_string())
+    );
+
+    let unknown = analysis
+        .projects
+        .iter()
+        .find(|p| p.name == UNKNOWN_PROJECT)
+        .unwrap();
+    assert_eq!(unknown.root, None);
+    assert_eq!(unknown.command_count, 1);
+}
+
+#[test]
+fn test_projects_only_look_up_local_directories_once() {
+    use whiskerlog::analysis::projects::ProjectAnalyzer;
+
+    let home = tempfile::TempDir::new().unwrap();
+    let repo = home.path().join("code/whiskerlog");
+    std::fs::create_dir_all(repo.join(".git")).unwrap();
+    std::fs::create_dir_all(repo.join("src")).unwrap();
+    let src = repo.join("src").display().to_string();
+
+    let base = Utc.with_ymd_and_hms(2024, 5, 1, 9, 0, 0).unwrap();
+    let mut local = create_test_command("cargo build", base, vec![]);
+    local.working_directory = Some(src.clone());
+    let mut remote = local.clone();
+    remote.host_id = "ssh:deploy@prod".to_string();
+
+    let mut analyzer = ProjectAnalyzer::with_projects_dir(home.path().join("projects"));
+    let analysis = analyzer.analyze_projects(&[local.clone(), remote]);
+    let roots: Vec<_> = analysis.projects.iter().map(|p| p.root.clone()).collect();
+    assert!(roots.contains(&Some(repo.display().to_string())));
+    // The same path on another machine isn't this checkout
+    assert!(roots.contains(&Some(src)));
+
+    // Resolved roots are kept, so the disk isn't walked again
+    std::fs::remove_dir(repo.join(".git")).unwrap();
+    let analysis = analyzer.analyze_projects(&[local]);
+    assert_eq!(analysis.projects[0].root, Some(repo.display().to_string()));
+}
+
+#[test]
+fn test_work_patterns_use_configured_schedule() {
+    // Friday and Saturday at 10:00, 12:00 and 18:00 UTC, plus Sunday 23:00
+    let at = |d, h| {
+        create_test_command(
+            "ls",
+            Utc.with_ymd_and_hms(2024, 5, d, h, 0, 0).unwrap(),
+            vec![],
+        )
+    };
+    let commands = vec![
+        at(3, 10),
+        at(3, 12),
+        at(3, 18),
+        at(4, 10),
+        at(4, 12),
+        at(4, 18),
+        at(5, 23),
+    ];
+
+    let default = Config {
+        timezone: Some("UTC".to_string()),
+        ..Config::default()
+    };
+    let patterns = HeatmapAnalyzer::from_config(&default).analyze_work_patterns(&commands);
+    // 9-17 on any day; Sat/Sun weekend; 22-06 late night
+    assert!((patterns.work_hours_ratio - 4.0 / 7.0).abs() < 1e-6);
+    assert!((patterns.weekend_ratio - 4.0 / 7.0).abs() < 1e-6);
+    assert!((patterns.late_night_ratio - 1.0 / 7.0).abs() < 1e-6);
+
+    let custom = Config {
+        timezone: Some("UTC".to_string()),
+        work_hours_start: 11,
+        work_hours_end: 19,
+        weekend_days: vec!["fri".to_string(), "Saturday".to_string()],
+        late_night_start: 17,
+        late_night_end: 1,
+        ..Config::default()
+    };
+    let patterns = HeatmapAnalyzer::from_config(&custom).analyze_work_patterns(&commands);
+    assert!((patterns.work_hours_ratio - 4.0 / 7.0).abs() < 1e-6); // 12:00 and 18:00
+    assert!((patterns.weekend_ratio - 6.0 / 7.0).abs() < 1e-6);
+    assert!((patterns.late_night_ratio - 3.0 / 7.0).abs() < 1e-6); // 18:00 twice, 23:00
+    assert_eq!(patterns.schedule.work_hours_start, 11);
+}
+
+#[test]
+fn test_danger_explain_uses_import_rules() {
+    use whiskerlog::analysis::DangerAnalyzer;
+    use whiskerlog::config::DangerRule;
+
+    let analyzer = DangerAnalyzer::new();
+    let (score, reasons) = analyzer.explain("sudo rm -rf /");
+    assert_eq!(score, 1.0);
+    assert!(reasons.contains(&"Recursive delete from root".to_string()));
+
+    let (score, reasons) = analyzer.explain("ls -la");
+    assert_eq!(score, 0.0);
+    assert!(reasons.is_empty());
+
+    let config = Config {
+        danger_rules: vec![DangerRule {
+            pattern: "terraform destroy".to_string(),
+            is_regex: false,
+            score: 0.9,
+            reason: "Infrastructure teardown".to_string(),
+            enabled: true,
+        }],
+        ..Config::default()
+    };
+    let (score, reasons) = DangerAnalyzer::from_config(&config).explain("terraform destroy");
+    assert_eq!(score, 0.9);
+    assert_eq!(reasons, vec!["Infrastructure teardown".to_string()]);
+}
+
+#[test]
+fn test_suggest_safer_alternatives() {
+    use whiskerlog::analysis::DangerAnalyzer;
+
+    let analyzer = DangerAnalyzer::new();
+    let rm = analyzer.suggest_safer("rm -rf build/");
+    assert!(rm.iter().any(|s| s.contains("`trash`")));
+    assert!(rm.iter().any(|s| s.contains("`-i`")));
+    assert_eq!(analyzer.suggest_safer("rm -Rf build/"), rm);
+
+    // Both rm rules apply, each suggestion once
+    let sudo_rm = analyzer.suggest_safer("sudo rm -rf /var/cache/app");
+    assert_eq!(sudo_rm.len(), rm.len() + 1);
+
+    assert!(analyzer.suggest_safer("chmod -R 777 public")[0].contains("755"));
+    assert!(
+        analyzer.suggest_safer("git push --force origin main")[0].contains("--force-with-lease")
+    );
+    assert!(analyzer.suggest_safer("git push -f")[0].contains("--force-with-lease"));
+
+    // Already the safe form, or nothing known about it
+    assert!(analyzer
+        .suggest_safer("git push --force-with-lease")
+        .is_empty());
+    assert!(analyzer.suggest_safer("git add .").is_empty());
+    assert!(analyzer.suggest_safer("chmod 755 script.sh").is_empty());
+    assert!(analyzer.suggest_safer("ls -la").is_empty());
+}
+
+#[test]
+fn test_top_commands_group_normalized_commands() {
+    use whiskerlog::analysis::normalize::{normalize_command, strip_arguments};
+    use whiskerlog::analysis::stats::StatsAnalyzer;
+
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap();
+    let commands: Vec<Command> = [
+        r#"git commit -m "first draft""#,
+        "git commit -m 'fix typo'",
+        "ls",
+    ]
+    .iter()
+    .enumerate()
+    .map(|(i, cmd)| create_test_command(cmd, start + chrono::Duration::minutes(i as i64), vec![]))
+    .collect();
+
+    let config = Config::default();
+    let stats = StatsAnalyzer::from_config(&config).analyze_commands(&commands);
+    let top = &stats.top_commands[0];
+    assert_eq!(top.command, "git commit -m <str>");
+    assert_eq!(top.count, 2);
+    assert_eq!(top.example, "git commit -m 'fix typo'");
+    assert_eq!(top.last_used, start + chrono::Duration::minutes(1));
+
+    let raw = Config {
+        normalize_top_commands: false,
+        ..Config::default()
+    };
+    let stats = StatsAnalyzer::from_config(&raw).analyze_commands(&commands);
+    assert_eq!(stats.top_commands.len(), 3);
+    assert!(stats.top_commands.iter().all(|c| c.count == 1));
+
+    assert_eq!(
+        normalize_command("sudo docker logs -f --tail 100 web"),
+        "sudo docker logs -f --tail N web"
+    );
+    assert_eq!(normalize_command("cp ~/a.txt ./b/c.txt /tmp/"), "cp <path>");
+    assert_eq!(
+        normalize_command("kubectl apply --filename=k8s/deploy.yaml"),
+        "kubectl apply --filename=<path>"
+    );
+    assert_eq!(
+        normalize_command("./build.sh release"),
+        "./build.sh release"
+    );
+
+    // Only multi-command tools keep the word after them
+    let keep = |_: &str| true;
+    assert_eq!(
+        strip_arguments("/usr/bin/git push origin main", keep),
+        "git push"
+    );
+    assert_eq!(
+        strip_arguments("sudo docker logs web", keep),
+        "sudo docker logs"
+    );
+    assert_eq!(strip_arguments("ssh prod", keep), "ssh");
+    assert_eq!(strip_arguments("echo hello", keep), "echo");
+}
+
+#[test]
+fn test_most_active_hour_and_day_ties_pick_earliest() {
+    use whiskerlog::analysis::stats::StatsAnalyzer;
+
+    // Two runs each at 14:00 on Thursday and 09:00 on Tuesday, listed late first
+    let commands: Vec<Command> = [(16, 14), (16, 14), (14, 9), (14, 9)]
+        .iter()
+        .map(|&(day, hour)| {
+            let timestamp = Utc.with_ymd_and_hms(2024, 1, day, hour, 0, 0).unwrap();
+            create_test_command("make", timestamp, vec![])
+        })
+        .collect();
+
+    let stats = StatsAnalyzer::new().analyze_commands(&commands);
+    assert_eq!(stats.most_active_hour, 9);
+    assert_eq!(stats.most_active_day, chrono::Weekday::Tue);
+
+    let config = Config {
+        timezone: Some("UTC".to_string()),
+        ..Config::default()
+    };
+    for _ in 0..5 {
+        let patterns = HeatmapAnalyzer::from_config(&config).analyze_work_patterns(&commands);
+        assert_eq!(patterns.most_active_hour, 9);
+        assert_eq!(patterns.most_active_day, chrono::Weekday::Tue);
+    }
+}
+
+#[test]
+fn test_tool_exploration_order_and_knowledge_gaps() {
+    use whiskerlog::analysis::experiment_detector::ExperimentDetector;
+
+    let base = Utc.with_ymd_and_hms(2024, 1, 15, 10, 0, 0).unwrap();
+    let mut commands = Vec::new();
+    for (i, cmd) in ["jq --help", "jq .", "jq '.a | keys'", "awk --help"]
+        .iter()
+        .chain(["awk '{print $1}'", "awk -F: '{print $2}' /etc/passwd"].iter())
+        .enumerate()
+    {
+        let mut command =
+            create_test_command(cmd, base + chrono::Duration::minutes(i as i64), vec![]);
+        command.is_experiment = true;
+        if cmd.starts_with("jq") {
+            command.exit_code = Some(1);
+        }
+        commands.push(command);
+    }
+
+    let analysis = ExperimentDetector::new().analyze_experiments(&commands);
+
+    // Equal command counts fall back to the tool name
+    let tools: Vec<&str> = analysis
+        .tool_exploration
+        .iter()
+        .map(|e| e.tool.as_str())
+        .collect();
+    assert_eq!(tools, vec!["awk", "jq"]);
+
+    let jq = &analysis.tool_exploration[1];
+    assert_eq!(jq.learning_progression.len(), 3);
+    assert!(jq.learning_progression.iter().all(|step| !step.success));
+    assert!(jq
+        .learning_progression
+        .windows(2)
+        .all(|pair| pair[0].timestamp <= pair[1].timestamp));
+
+    let gaps = analysis.knowledge_gaps_for("jq");
+    assert_eq!(gaps.len(), 1);
+    assert!(gaps[0].suggested_resources.contains(&"man jq".to_string()));
+    assert!(analysis.knowledge_gaps_for("awk").is_empty());
+}
+
+#[test]
+fn test_commands_per_day_series_keeps_empty_days() {
+    use chrono::NaiveDate;
+    use whiskerlog::analysis::stats::StatsAnalyzer;
+
+    let day = |d: u32, hour: u32| Utc.with_ymd_and_hms(2024, 3, d, hour, 0, 0).unwrap();
+    // Two on the 10th, none on the 11th, one on the 12th, and one too old to show
+    let commands: Vec<Command> = [day(10, 9), day(10, 23), day(12, 0), day(1, 12)]
+        .into_iter()
+        .map(|timestamp| create_test_command("ls", timestamp, vec![]))
+        .collect();
+
+    let end = NaiveDate::from_ymd_opt(2024, 3, 13).unwrap();
+    let series = StatsAnalyzer::new().commands_per_day_until(&commands, end, 4);
+    let date = |d: u32| NaiveDate::from_ymd_opt(2024, 3, d).unwrap();
+    assert_eq!(
+        series,
+        vec![(date(10), 2), (date(11), 0), (date(12), 1), (date(13), 0)]
+    );
+
+    assert!(StatsAnalyzer::new()
+        .commands_per_day_until(&commands, end, 0)
+        .is_empty());
+    assert_eq!(
+        StatsAnalyzer::new()
+            .commands_per_day_series(&commands, 30)
+            .len(),
+        30
+    );
+}
+
+#[test]
+fn test_activity_streaks_across_a_gap() {
+    use chrono::NaiveDate;
+    use whiskerlog::analysis::stats::StatsAnalyzer;
+
+    let analyzer = StatsAnalyzer::new().with_timezone(chrono_tz::Tz::UTC);
+    let day = |d: u32, hour: u32| Utc.with_ymd_and_hms(2024, 3, d, hour, 0, 0).unwrap();
+    // Four days running from the 1st, nothing on the 5th, then the 6th to the 8th
+    let commands: Vec<Command> = [
+        day(1, 9),
+        day(2, 9),
+        day(2, 23),
+        day(3, 12),
+        day(4, 0),
+        day(6, 8),
+        day(7, 8),
+        day(8, 22),
+    ]
+    .into_iter()
+    .map(|timestamp| create_test_command("ls", timestamp, vec![]))
+    .collect();
+    let date = |d: u32| NaiveDate::from_ymd_opt(2024, 3, d).unwrap();
+
+    assert_eq!(analyzer.activity_streaks(&commands, date(8)), (3, 4));
+    // Nothing run yet today doesn't end the streak
+    assert_eq!(analyzer.activity_streaks(&commands, date(9)), (3, 4));
+    assert_eq!(analyzer.activity_streaks(&commands, date(10)), (0, 4));
+    assert_eq!(analyzer.activity_streaks(&[], date(8)), (0, 0));
+}
+
+#[test]
+fn test_endpoints_are_grouped_by_host() {
+    use whiskerlog::analysis::network_analyzer::NetworkAnalyzer;
+
+    let runs: &[(&str, &[&str])] = &[
+        ("local", &["https://api.github.com"]),
+        ("local", &["https://api.github.com", "http://example.com"]),
+        ("ssh:deploy@web", &["db://postgres:5432"]),
+        ("ssh:deploy@web", &["db://postgres:5432"]),
+        ("ssh:deploy@web", &["https://api.github.com"]),
+        // No host recorded falls under local
+        ("", &["http://example.com"]),
+        // Commands without endpoints don't create a group
+        ("docker:app", &[]),
+    ];
+    let commands: Vec<Command> = runs
+        .iter()
+        .zip(0..)
+        .map(|((host, endpoints), minute)| Command {
+            host_id: host.to_string(),
+            network_endpoints: endpoints.iter().map(|e| e.to_string()).collect(),
+            ..command_at("bash", "curl", minute)
+        })
+        .collect();
+
+    let by_host = NetworkAnalyzer::new().endpoints_by_host(&commands);
+    let mut hosts: Vec<_> = by_host.keys().map(String::as_str).collect();
+    hosts.sort_unstable();
+    assert_eq!(hosts, vec!["local", "ssh:deploy@web"]);
+
+    let local: Vec<(&str, usize)> = by_host["local"]
+        .iter()
+        .map(|stats| (stats.endpoint.as_str(), stats.usage_count))
+        .collect();
+    assert_eq!(
+        local,
+        vec![("http://example.com", 2), ("https://api.github.com", 2)]
+    );
+
+    let web = &by_host["ssh:deploy@web"];
+    assert_eq!(web[0].endpoint, "db://postgres:5432");
+    assert_eq!(web[0].usage_count, 2);
+    assert_eq!(web[0].first_seen, commands[2].timestamp);
+    assert_eq!(web[0].last_seen, commands[3].timestamp);
+    assert_eq!(web[1].endpoint, "https://api.github.com");
+    assert_eq!(web[1].usage_count, 1);
+}
+
+#[test]
+fn test_trusted_hosts_raise_no_cleartext_issue() {
+    use whiskerlog::analysis::network_analyzer::NetworkAnalyzer;
+
+    let base = Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap();
+    let fetch = |endpoint: &str| Command {
+        network_endpoints: vec![endpoint.to_string()],
+        ..create_test_command(&format!("curl {}", endpoint), base, vec![])
+    };
+    let analyzer = NetworkAnalyzer::with_trusted_hosts(&["*.corp.local".to_string()]);
+    let cleartext = |commands: &[Command]| {
+        analyzer
+            .analyze_network_activity(commands)
+            .security_issues
+            .into_iter()
+            .find(|issue| issue.issue_type == "Insecure HTTP")
+    };
+
+    let trusted = [
+        fetch("http://wiki.corp.local"),
+        fetch("http://CI.Corp.Local:8080"),
+    ];
+    assert!(cleartext(&trusted).is_none());
+    // Still counted, and marked as trusted
+    let analysis = analyzer.analyze_network_activity(&trusted);
+    assert_eq!(analysis.total_network_commands, 2);
+    assert!(analysis.top_endpoints.iter().all(|stats| stats.is_trusted));
+
+    // The wildcard needs a subdomain, and other hosts are still flagged
+    let untrusted = [fetch("http://corp.local"), fetch("http://evilcorp.local")];
+    let issue = cleartext(&untrusted).expect("untrusted http is flagged");
+    assert_eq!(issue.affected_commands.len(), 2);
+    assert!(!analyzer.is_trusted_endpoint("http://wiki.corp.local.evil.com"));
+}
+
+#[test]
+fn test_incremental_stats_match_a_full_recompute() {
+    use whiskerlog::analysis::sessions::split_sessions;
+    use whiskerlog::analysis::stats::StatsAnalyzer;
+
+    let at = |d, h, m| Utc.with_ymd_and_hms(2024, 5, d, h, m, 0).unwrap();
+    let run = |cmd: &str, timestamp, session: &str, exit_code, duration| Command {
+        session_id: session.to_string(),
+        exit_code,
+        duration,
+        ..create_test_command(cmd, timestamp, vec![])
+    };
+    let commands = vec![
+        run("git status", at(1, 9, 0), "a", Some(0), Some(40)),
+        run("git commit -m \"one\"", at(1, 9, 5), "a", Some(1), None),
+        run("cargo build", at(1, 9, 6), "b", None, Some(9000)),
+        run(
+            "git commit -m \"two\"",
+            at(1, 9, 20),
+            "a",
+            Some(0),
+            Some(300),
+        ),
+        // Past the idle gap: a second part of session a
+        run("git status", at(1, 14, 0), "a", Some(0), Some(35)),
+        run("cargo test", at(2, 10, 0), "b", Some(101), Some(12000)),
+        run("git status", at(3, 8, 30), "a", Some(0), None),
+    ];
+
+    let analyzer = StatsAnalyzer::new();
+    let mut tally = analyzer.tally(&commands[..3]);
+    for cmd in &commands[3..] {
+        tally.observe(cmd);
+    }
+
+    let batch = analyzer.analyze_commands(&commands);
+    let incremental = tally.command_stats();
+    assert_eq!(incremental.total_commands, batch.total_commands);
+    assert_eq!(incremental.unique_commands, batch.unique_commands);
+    assert_eq!(incremental.success_rate, batch.success_rate);
+    assert_eq!(incremental.average_duration, batch.average_duration);
+    assert_eq!(incremental.commands_per_day, batch.commands_per_day);
+    assert_eq!(incremental.most_active_hour, batch.most_active_hour);
+    assert_eq!(incremental.most_active_day, batch.most_active_day);
+    assert_eq!(incremental.shell_distribution, batch.shell_distribution);
+    assert_eq!(incremental.host_distribution, batch.host_distribution);
+    let top = |stats: &whiskerlog::analysis::stats::CommandStats| -> Vec<(String, String, usize)> {
+        stats
+            .top_commands
+            .iter()
+            .map(|freq| (freq.command.clone(), freq.example.clone(), freq.count))
+            .collect()
+    };
+    assert_eq!(top(&incremental), top(&batch));
+    assert_eq!(batch.top_commands[0].command, "git status");
+    assert_eq!(batch.top_commands[1].example, "git commit -m \"two\"");
+
+    let batch = analyzer.analyze_sessions(&commands);
+    let incremental = tally.session_stats();
+    assert_eq!(incremental.total_sessions, batch.total_sessions);
+    assert_eq!(
+        incremental.average_session_length,
+        batch.average_session_length
+    );
+    assert_eq!(
+        incremental.average_commands_per_session,
+        batch.average_commands_per_session
+    );
+    assert_eq!(incremental.longest_session, batch.longest_session);
+    assert_eq!(
+        incremental.most_productive_session,
+        batch.most_productive_session
+    );
+    assert_eq!(incremental.session_distribution, batch.session_distribution);
+
+    // And both agree with how the session splitter cuts the history
+    let sessions = split_sessions(&commands, chrono::Duration::minutes(30));
+    assert_eq!(batch.total_sessions, sessions.len());
+    assert_eq!(batch.total_sessions, 5);
+    assert_eq!(batch.most_productive_session, "a");
+    assert_eq!(batch.longest_session, chrono::Duration::minutes(20));
+}
+
+#[test]
+fn test_heatmap_cell_breakdown() {
+    // Monday 2024-01-15, all in the 14:00 hour
+    let at = |minute| Utc.with_ymd_and_hms(2024, 1, 15, 14, minute, 0).unwrap();
+    let mut commands = vec![
+        create_test_command("git status", at(0), vec![]),
+        create_test_command("git status", at(1), vec![]),
+        create_test_command("cargo build", at(2), vec![]),
+        create_test_command("cargo build", at(3), vec![]),
+        create_test_command("ls", at(4), vec![]),
+        create_test_command("rm -rf target", at(5), vec![]),
+    ];
+    commands[5].is_dangerous = true;
+    commands[4].is_experiment = true;
+    commands[3].is_experiment = true;
+
+    let analyzer = HeatmapAnalyzer::with_timezone(chrono_tz::UTC);
+    let heatmap = analyzer.generate_heatmap(&commands, TimeRange::Year, ViewMode::All);
+
+    let cell = heatmap.cell(14, 0).unwrap();
+    assert_eq!(cell.command_count, 6);
+    // Ties go alphabetically, and only the top three are kept
+    assert_eq!(
+        cell.top_commands,
+        vec![
+            ("cargo build".to_string(), 2),
+            ("git status".to_string(), 2),
+            ("ls".to_string(), 1),
+        ]
+    );
+    assert!((cell.dangerous_ratio() - 1.0 / 6.0).abs() < 1e-6);
+    assert!((cell.experiment_ratio() - 2.0 / 6.0).abs() < 1e-6);
+
+    // Empty cells have nothing to divide by
+    let empty = heatmap.cell(3, 4).unwrap();
+    assert_eq!(empty.command_count, 0);
+    assert!(empty.top_commands.is_empty());
+    assert_eq!(empty.dangerous_ratio(), 0.0);
+
+    assert!(heatmap.cell(24, 0).is_none());
+    assert!(heatmap.cell(0, 7).is_none());
+}
+
+#[test]
+fn test_top_command_daily_runs() {
+    use whiskerlog::analysis::stats::StatsAnalyzer;
+
+    let day = |d| Utc.with_ymd_and_hms(2024, 3, d, 9, 0, 0).unwrap();
+    let commands = vec![
+        create_test_command("make", day(1), vec![]),
+        create_test_command("make", day(3), vec![]),
+        create_test_command("make", day(3), vec![]),
+        create_test_command("ls", day(2), vec![]),
+    ];
+
+    let stats = StatsAnalyzer::new().analyze_commands(&commands);
+    let make = &stats.top_commands[0];
+    assert_eq!(make.command, "make");
+
+    // Oldest first, empty days kept, days after the window ignored
+    let end = chrono::NaiveDate::from_ymd_opt(2024, 3, 3).unwrap();
+    assert_eq!(make.runs_until(end, 4), vec![0, 1, 0, 2]);
+    let end = chrono::NaiveDate::from_ymd_opt(2024, 3, 2).unwrap();
+    assert_eq!(make.runs_until(end, 2), vec![1, 0]);
+    assert!(make.runs_until(end, 0).is_empty());
+}
+
+#[test]
+fn test_failure_hotspots_order_and_rates() {
+    use whiskerlog::analysis::stats::StatsAnalyzer;
+
+    let base = Utc.with_ymd_and_hms(2024, 5, 1, 9, 0, 0).unwrap();
+    let runs: &[(&str, Option<i32>)] = &[
+        // 2 failures out of 4 runs with a known exit code
+        ("cargo build", Some(101)),
+        ("cargo build", Some(0)),
+        ("cargo build", Some(101)),
+        ("cargo build", Some(0)),
+        ("cargo build", None),
+        // 2 failures out of 2: same count, higher rate
+        ("make  deploy", Some(2)),
+        ("make deploy", Some(2)),
+        // 1 failure; the missing exit codes aren't successes
+        ("ssh prod", Some(255)),
+        ("ssh prod", None),
+        ("ssh prod", None),
+        // Never fails
+        ("ls", Some(0)),
+        ("ls", Some(0)),
+    ];
+    let commands: Vec<Command> = runs
+        .iter()
+        .enumerate()
+        .map(|(i, (cmd, exit_code))| Command {
+            exit_code: *exit_code,
+            ..create_test_command(cmd, base + chrono::Duration::minutes(i as i64), vec![])
+        })
+        .collect();
+
+    let hotspots = StatsAnalyzer::new().failure_hotspots(&commands);
+    assert_eq!(
+        hotspots,
+        vec![
+            ("make deploy".to_string(), 2, 1.0),
+            ("cargo build".to_string(), 2, 0.5),
+            ("ssh prod".to_string(), 1, 1.0),
+        ]
+    );
+
+    let stats = StatsAnalyzer::new().analyze_commands(&commands);
+    assert_eq!(stats.failure_hotspots, hotspots);
+}
+
+#[test]
+fn test_one_off_and_rarely_used_commands() {
+    use whiskerlog::analysis::stats::StatsAnalyzer;
+
+    let base = Utc.with_ymd_and_hms(2024, 5, 1, 9, 0, 0).unwrap();
+    let runs: &[(&str, Option<i32>)] = &[
+        ("git status", Some(0)),
+        ("git status", Some(0)),
+        ("git status", Some(0)),
+        ("hyperfine ls", Some(127)),
+        ("make test", Some(0)),
+        ("make test", Some(2)),
+        ("jq .", Some(0)),
+    ];
+    let commands: Vec<Command> = runs
+        .iter()
+        .enumerate()
+        .map(|(i, (cmd, exit_code))| Command {
+            exit_code: *exit_code,
+            ..create_test_command(cmd, base + chrono::Duration::minutes(i as i64), vec![])
+        })
+        .collect();
+
+    let analyzer = StatsAnalyzer::new();
+    let one_off = analyzer.one_off_commands(&commands);
+    let names: Vec<&str> = one_off.iter().map(|freq| freq.command.as_str()).collect();
+    // Longest unused first
+    assert_eq!(names, vec!["hyperfine ls", "jq ."]);
+    assert!(one_off.iter().all(|freq| freq.count == 1));
+    assert_eq!(one_off[0].failures, 1);
+    assert_eq!(one_off[1].failures, 0);
+
+    let rare = analyzer.rarely_used(&commands, 2);
+    let names: Vec<&str> = rare.iter().map(|freq| freq.command.as_str()).collect();
+    assert_eq!(names, vec!["hyperfine ls", "jq .", "make test"]);
+    assert!(!names.contains(&"git status"));
+}
+
+#[test]
+fn test_heatmap_percentile_levels_ignore_absolute_volume() {
+    // Monday 09:00 is the busiest cell, then Tuesday 14:00, then Monday 10:00
+    let monday_nine = Utc.with_ymd_and_hms(2024, 1, 15, 9, 0, 0).unwrap();
+    let monday_ten = Utc.with_ymd_and_hms(2024, 1, 15, 10, 0, 0).unwrap();
+    let tuesday_two = Utc.with_ymd_and_hms(2024, 1, 16, 14, 0, 0).unwrap();
+    let history = |scale: usize| {
+        let mut commands = Vec::new();
+        for (timestamp, runs) in [(monday_nine, 3), (monday_ten, 1), (tuesday_two, 2)] {
+            for _ in 0..runs * scale {
+                commands.push(create_test_command("ls", timestamp, vec![]));
+            }
+        }
+        commands
+    };
+
+    let analyzer = HeatmapAnalyzer::with_timezone(chrono_tz::UTC);
+    let light = analyzer.generate_heatmap(&history(1), TimeRange::Year, ViewMode::All);
+    let heavy = analyzer.generate_heatmap(&history(100), TimeRange::Year, ViewMode::All);
+
+    for heatmap in [&light, &heavy] {
+        let levels = heatmap.levels(&HeatmapScale::Percentile);
+        assert_eq!(levels[9][0], HEATMAP_LEVELS);
+        assert_eq!(levels[14][1], 4);
+        assert_eq!(levels[10][0], 2);
+        assert_eq!(levels[3][3], 0);
+    }
+
+    // Absolute thresholds keep a light history cold
+    let absolute = HeatmapScale::Absolute([5, 20, 50, 100]);
+    assert_eq!(light.levels(&absolute)[9][0], 1);
+    assert_eq!(heavy.levels(&absolute)[9][0], HEATMAP_LEVELS);
+    assert_eq!(heavy.levels(&absolute)[10][0], 5);
+
+    let config = Config {
+        heatmap_thresholds: Some(vec![5, 20, 50, 100]),
+        ..Config::default()
+    };
+    assert_eq!(HeatmapScale::from_config(&config), absolute);
+    let config = Config {
+        heatmap_thresholds: Some(vec![50, 20]),
+        ..Config::default()
+    };
+    assert_eq!(HeatmapScale::from_config(&config), HeatmapScale::Percentile);
+}
+
+#[test]
+fn test_slowest_commands_skip_untimed_runs() {
+    use whiskerlog::analysis::stats::{DurationHistogram, StatsAnalyzer};
+
+    let base = Utc.with_ymd_and_hms(2024, 5, 1, 9, 0, 0).unwrap();
+    let runs: &[(&str, Option<u64>)] = &[
+        ("cargo build", Some(42_000)),
+        ("cargo build", Some(18_000)),
+        ("git status", Some(40)),
+        ("git status", None),
+        ("npm install", Some(9_500)),
+        ("ls", Some(3)),
+        // Never timed: left out, not ranked as instant
+        ("vim notes.md", None),
+    ];
+    let commands: Vec<Command> = runs
+        .iter()
+        .enumerate()
+        .map(|(i, (cmd, duration))| Command {
+            duration: *duration,
+            ..create_test_command(cmd, base + chrono::Duration::minutes(i as i64), vec![])
+        })
+        .collect();
+
+    let analyzer = StatsAnalyzer::new();
+    let slowest = analyzer.slowest_commands(&commands, 10);
+    let ranked: Vec<(&str, Option<f64>)> = slowest
+        .iter()
+        .map(|freq| (freq.command.as_str(), freq.average_duration))
+        .collect();
+    assert_eq!(
+        ranked,
+        vec![
+            ("cargo build", Some(30_000.0)),
+            ("npm install", Some(9_500.0)),
+            ("git status", Some(40.0)),
+            ("ls", Some(3.0)),
+        ]
+    );
+    // Runs count every run, timed or not
+    assert_eq!(slowest[2].count, 2);
+    assert_eq!(analyzer.slowest_commands(&commands, 1).len(), 1);
+
+    assert_eq!(
+        analyzer.duration_histogram(&commands),
+        DurationHistogram {
+            under_100ms: 2,
+            under_1s: 0,
+            under_10s: 1,
+            over_10s: 2,
+        }
+    );
+}
+
+#[test]
+fn test_per_shell_stats_keep_each_shell_apart() {
+    use whiskerlog::analysis::stats::StatsAnalyzer;
+
+    let runs: &[(&str, &str, i32, bool)] = &[
+        ("zsh", "git status", 0, false),
+        ("zsh", "git status", 0, false),
+        ("zsh", "cargo test", 1, false),
+        ("zsh", "vim src/main.rs", 0, false),
+        ("bash", "./deploy.sh", 0, false),
+        ("bash", "rm -rf build", 0, true),
+        ("bash", "./deploy.sh", 2, false),
+    ];
+    let commands: Vec<Command> = runs
+        .iter()
+        .zip(0..)
+        .map(|((shell, cmd, exit_code, dangerous), minute)| Command {
+            exit_code: Some(*exit_code),
+            is_dangerous: *dangerous,
+            ..command_at(shell, cmd, minute)
+        })
+        .collect();
+
+    let analyzer = StatsAnalyzer::new();
+    let per_shell = analyzer.tally(&commands).shell_stats();
+    let shells: Vec<&str> = per_shell.iter().map(|(shell, _)| shell.as_str()).collect();
+    // Busiest first
+    assert_eq!(shells, vec!["zsh", "bash"]);
+
+    let zsh = &per_shell[0].1;
+    assert_eq!(zsh.total_commands, 4);
+    assert_eq!(zsh.success_rate, 0.75);
+    assert_eq!(zsh.dangerous_commands, 0);
+    assert_eq!(zsh.top_commands[0].command, "git status");
+    assert_eq!(zsh.shell_distribution.len(), 1);
+
+    let bash = &per_shell[1].1;
+    assert_eq!(bash.total_commands, 3);
+    assert!((bash.success_rate - 2.0 / 3.0).abs() < 1e-6);
+    assert_eq!(bash.dangerous_commands, 1);
+    assert_eq!(bash.top_commands[0].command, "./deploy.sh");
+
+    // Together they add up to the whole history
+    let all = analyzer.analyze_commands(&commands);
+    assert_eq!(all.total_commands, zsh.total_commands + bash.total_commands);
+    assert_eq!(all.dangerous_commands, 1);
+    assert!(analyzer.tally(&[]).shell_stats().is_empty());
+}
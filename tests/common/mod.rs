@@ -0,0 +1,15 @@
+use chrono::{Duration, TimeZone, Utc};
+use whiskerlog::Command;
+
+/// A successful `command` typed in `shell` on this machine, `minute` minutes
+/// after 09:00 UTC on 2024-03-01, in session `s1`.
+pub fn command_at(shell: &str, command: &str, minute: i64) -> Command {
+    let start = Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap();
+    Command::builder(command, start + Duration::minutes(minute))
+        .session_id("s1")
+        .shell(shell)
+        .host_id("local")
+        .exit_code(0)
+        .build()
+        .unwrap()
+}
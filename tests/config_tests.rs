@@ -9,6 +9,7 @@ fn test_config_default_values() {
     assert_eq!(config.danger_threshold, 0.7);
     assert!(config.experiment_detection);
     assert!(!config.history_paths.is_empty());
+    assert_eq!(config.session_idle_minutes, 30);
 }
 
 #[test]
@@ -23,6 +24,7 @@ fn test_config_serialization() {
         auto_import: true,
         danger_threshold: 0.5,
         experiment_detection: false,
+        ..Config::default()
     };
 
     let toml_string = toml::to_string(&config).unwrap();
@@ -48,6 +50,7 @@ fn test_config_toml_format() {
         auto_import: false,
         danger_threshold: 0.8,
         experiment_detection: true,
+        ..Config::default()
     };
 
     let toml_string = toml::to_string_pretty(&config).unwrap();
@@ -80,6 +83,7 @@ fn test_config_with_multiple_history_paths() {
         auto_import: true,
         danger_threshold: 0.6,
         experiment_detection: true,
+        ..Config::default()
     };
 
     let toml_string = toml::to_string(&config).unwrap();
@@ -110,6 +114,7 @@ fn test_config_danger_threshold_bounds() {
         auto_import: true,
         danger_threshold: 0.0,
         experiment_detection: true,
+        ..Config::default()
     };
 
     let toml_string = toml::to_string(&config_min).unwrap();
@@ -124,6 +129,7 @@ fn test_config_danger_threshold_bounds() {
         auto_import: true,
         danger_threshold: 1.0,
         experiment_detection: true,
+        ..Config::default()
     };
 
     let toml_string = toml::to_string(&config_max).unwrap();
@@ -152,6 +158,7 @@ fn test_config_boolean_combinations() {
             auto_import,
             danger_threshold: 0.5,
             experiment_detection: experiment,
+            ..Config::default()
         };
 
         let toml_string = toml::to_string(&config).unwrap();
@@ -172,6 +179,7 @@ fn test_config_empty_history_paths() {
         auto_import: true,
         danger_threshold: 0.7,
         experiment_detection: true,
+        ..Config::default()
     };
 
     let toml_string = toml::to_string(&config).unwrap();
@@ -193,6 +201,7 @@ fn test_config_path_with_spaces_and_special_chars() {
         auto_import: true,
         danger_threshold: 0.7,
         experiment_detection: true,
+        ..Config::default()
     };
 
     let toml_string = toml::to_string(&config).unwrap();
@@ -264,6 +273,48 @@ fn test_config_extra_fields_ignored() {
     assert_eq!(config.danger_threshold, 0.7);
 }
 
+#[test]
+fn test_config_heatmap_state_defaults_when_missing() {
+    let toml_without_heatmap = r#"
+        database_path = "/tmp/test.db"
+        history_paths = []
+        redaction_enabled = true
+        auto_import = true
+        danger_threshold = 0.7
+        experiment_detection = true
+    "#;
+
+    let config: Config = toml::from_str(toml_without_heatmap).unwrap();
+    assert_eq!(config.heatmap_time_range, "week");
+    assert_eq!(config.heatmap_view_mode, "all");
+}
+
+#[test]
+fn test_config_heatmap_state_round_trip() {
+    use whiskerlog::analysis::heatmap::{TimeRange, ViewMode};
+
+    let config = Config {
+        heatmap_time_range: TimeRange::Month.as_str().to_string(),
+        heatmap_view_mode: ViewMode::Dangerous.as_str().to_string(),
+        ..Config::default()
+    };
+
+    let toml_string = toml::to_string(&config).unwrap();
+    let deserialized: Config = toml::from_str(&toml_string).unwrap();
+
+    assert_eq!(
+        TimeRange::from_name(&deserialized.heatmap_time_range),
+        Some(TimeRange::Month)
+    );
+    assert_eq!(
+        ViewMode::from_name(&deserialized.heatmap_view_mode),
+        Some(ViewMode::Dangerous)
+    );
+
+    // Unknown values are left for the caller to fall back on
+    assert_eq!(TimeRange::from_name("fortnight"), None);
+}
+
 #[test]
 fn test_config_round_trip_precision() {
     let original_config = Config {
@@ -276,6 +327,7 @@ fn test_config_round_trip_precision() {
         auto_import: false,
         danger_threshold: 0.123_456_79,
         experiment_detection: true,
+        ..Config::default()
     };
 
     let toml_string = toml::to_string(&original_config).unwrap();
@@ -296,6 +348,102 @@ fn test_config_display_debug() {
     assert!(debug_string.contains("redaction_enabled"));
 }
 
-// Note: The actual file I/O tests for Config::load_or_create() and Config::save()
-// would require mocking the directories or using integration tests with proper
-// file system setup. These tests focus on the serialization/deserialization logic.
+#[test]
+fn test_config_danger_rules_from_toml() {
+    let toml_with_rules = r#"
+        database_path = "/tmp/test.db"
+        history_paths = []
+        redaction_enabled = true
+        auto_import = true
+        danger_threshold = 0.7
+        experiment_detection = true
+
+        [[danger_rules]]
+        pattern = "kubectl delete ns prod"
+        score = 1.0
+        reason = "Deleting the production namespace"
+
+        [[danger_rules]]
+        pattern = "^mv(?:\\s|$)"
+        is_regex = true
+        score = 0.3
+        reason = "File movement"
+        enabled = false
+    "#;
+
+    let config: Config = toml::from_str(toml_with_rules).unwrap();
+    assert_eq!(config.danger_rules.len(), 2);
+    assert!(!config.danger_rules[0].is_regex);
+    assert!(config.danger_rules[0].enabled);
+    assert!(!config.danger_rules[1].enabled);
+
+    // Rules are optional
+    assert!(Config::default().danger_rules.is_empty());
+}
+
+#[test]
+fn test_config_at_custom_path_keeps_configured_database() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let path = temp_dir.path().join("nested").join("config.toml");
+
+    let mut config = Config::load_from(&path).unwrap();
+    assert!(path.exists());
+    let configured = config.database_path.clone();
+
+    // A --db override is used for the run but not written back
+    config.override_database_path(temp_dir.path().join("other.db"));
+    config.session_idle_minutes = 45;
+    config.save().unwrap();
+    assert_eq!(config.database_path, temp_dir.path().join("other.db"));
+
+    let reloaded = Config::load_from(&path).unwrap();
+    assert_eq!(reloaded.database_path, configured);
+    assert_eq!(reloaded.session_idle_minutes, 45);
+    assert_eq!(reloaded.config_path.as_deref(), Some(path.as_path()));
+}
+
+#[test]
+fn test_config_rejects_non_positive_session_idle_minutes() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let path = temp_dir.path().join("config.toml");
+
+    let mut config = Config::load_from(&path).unwrap();
+    for minutes in [0, -5] {
+        config.session_idle_minutes = minutes;
+        config.save().unwrap();
+        let error = Config::load_from(&path).unwrap_err().to_string();
+        assert!(error.contains("session_idle_minutes"), "{error}");
+    }
+}
+
+#[test]
+fn test_config_env_vars_redirect_config_and_data() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("profile").join("config.toml");
+    let data_dir = temp_dir.path().join("data");
+
+    // The only test that reads these variables
+    std::env::set_var("WHISKERLOG_CONFIG", &config_path);
+    std::env::set_var("WHISKERLOG_DATA", &data_dir);
+    let config = Config::load_or_create(None, None);
+    let flagged = Config::load_or_create(None, Some(&temp_dir.path().join("flag.db")));
+    std::env::remove_var("WHISKERLOG_CONFIG");
+    std::env::remove_var("WHISKERLOG_DATA");
+
+    let mut config = config.unwrap();
+    assert!(config_path.exists());
+    assert_eq!(config.database_path, data_dir.join("history.db"));
+    assert!(data_dir.exists());
+
+    config.theme = "light".to_string();
+    config.save().unwrap();
+    let saved = Config::load_from(&config_path).unwrap();
+    assert_eq!(saved.theme, "light");
+    assert_ne!(saved.database_path, data_dir.join("history.db"));
+
+    // The flag wins over the environment
+    assert_eq!(
+        flagged.unwrap().database_path,
+        temp_dir.path().join("flag.db")
+    );
+}
@@ -1,5 +1,9 @@
+mod common;
+
 use chrono::{DateTime, TimeZone, Utc};
+use common::command_at;
 use tempfile::TempDir;
+use whiskerlog::db::{CommandFilter, ImportKind};
 use whiskerlog::*;
 
 async fn create_test_database() -> (Database, TempDir) {
@@ -27,6 +31,8 @@ fn create_test_command_with_id(id: i64, cmd: &str, timestamp: DateTime<Utc>) ->
         danger_reasons: vec![],
         is_experiment: false,
         experiment_tags: vec![],
+        original_command: None,
+        tags: vec![],
     }
 }
 
@@ -63,6 +69,8 @@ async fn test_insert_and_retrieve_command() {
         danger_reasons: vec![],
         is_experiment: false,
         experiment_tags: vec![],
+        original_command: None,
+        tags: vec![],
     };
 
     // Insert command
@@ -114,6 +122,8 @@ async fn test_insert_command_with_packages() {
         danger_reasons: vec![],
         is_experiment: false,
         experiment_tags: vec![],
+        original_command: None,
+        tags: vec![],
     };
 
     db.insert_command(&test_command).await.unwrap();
@@ -152,6 +162,8 @@ async fn test_insert_command_with_network_endpoints() {
         danger_reasons: vec![],
         is_experiment: false,
         experiment_tags: vec![],
+        original_command: None,
+        tags: vec![],
     };
 
     db.insert_command(&test_command).await.unwrap();
@@ -220,6 +232,8 @@ async fn test_dangerous_command_storage() {
         danger_reasons: vec!["destructive operation".to_string()],
         is_experiment: false,
         experiment_tags: vec![],
+        original_command: None,
+        tags: vec![],
     };
 
     db.insert_command(&dangerous_command).await.unwrap();
@@ -255,6 +269,8 @@ async fn test_experiment_command_storage() {
         danger_reasons: vec![],
         is_experiment: true,
         experiment_tags: vec!["learning".to_string(), "python".to_string()],
+        original_command: None,
+        tags: vec![],
     };
 
     db.insert_command(&experiment_command).await.unwrap();
@@ -320,6 +336,8 @@ async fn test_command_with_null_fields() {
         danger_reasons: vec![],
         is_experiment: false,
         experiment_tags: vec![],
+        original_command: None,
+        tags: vec![],
     };
 
     db.insert_command(&minimal_command).await.unwrap();
@@ -357,6 +375,8 @@ async fn test_large_command_storage() {
         danger_reasons: vec![],
         is_experiment: false,
         experiment_tags: vec![],
+        original_command: None,
+        tags: vec![],
     };
 
     db.insert_command(&test_command).await.unwrap();
@@ -384,3 +404,1053 @@ async fn test_limited_command_retrieval() {
     let all_commands = db.get_commands(None).await.unwrap();
     assert_eq!(all_commands.len(), 5);
 }
+
+#[tokio::test]
+async fn test_dedup_import_skips_existing_commands() {
+    let (mut db, _temp_dir) = create_test_database().await;
+
+    let timestamp = Utc.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap();
+    let commands = vec![
+        create_test_command_with_id(1, "ls -la", timestamp),
+        create_test_command_with_id(2, "git status", timestamp),
+    ];
+
+    assert_eq!(db.insert_commands_dedup(&commands).await.unwrap(), 2);
+    assert!(db.command_exists(&commands[0]).await.unwrap());
+
+    // Re-importing the same batch must not add rows
+    assert_eq!(db.insert_commands_dedup(&commands).await.unwrap(), 0);
+    assert_eq!(db.get_commands(None).await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_prune_removes_only_commands_before_cutoff() {
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().join("test.db");
+    let mut db = Database::new(&db_path).await.unwrap();
+
+    let day = |d: u32| Utc.with_ymd_and_hms(2024, 1, d, 12, 0, 0).unwrap();
+    let cutoff = Utc.with_ymd_and_hms(2024, 1, 3, 12, 0, 0).unwrap();
+    let commands: Vec<Command> = (1..=5)
+        .map(|d| create_test_command_with_id(d as i64, &format!("echo {}", d), day(d)))
+        .collect();
+    db.insert_new_commands("~/.bash_history", ImportKind::History, commands.clone())
+        .await
+        .unwrap();
+    let mut state = whiskerlog::db::ImportState::new("~/.bash_history");
+    state.byte_offset = 42;
+    db.set_import_state(&state).await.unwrap();
+
+    // A command exactly at the cutoff is kept
+    assert_eq!(db.prune_older_than(cutoff).await.unwrap(), 2);
+    let left: Vec<String> = db
+        .get_commands(None)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|c| c.command)
+        .collect();
+    assert_eq!(left, vec!["echo 5", "echo 4", "echo 3"]);
+
+    let conn = rusqlite::Connection::open(&db_path).unwrap();
+    let fts_rows: i64 = conn
+        .query_row("SELECT COUNT(*) FROM commands_fts", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(fts_rows, 3);
+
+    // Import offsets stay put, and re-reading a whole source doesn't restore what was pruned
+    assert_eq!(
+        db.get_import_state("~/.bash_history").await.unwrap(),
+        Some(state)
+    );
+    let mut new_command = create_test_command_with_id(6, "echo 6", day(6));
+    new_command.id = None;
+    let mut reread = commands;
+    reread.push(new_command);
+    let added = db
+        .insert_new_commands("atuin", ImportKind::History, reread)
+        .await
+        .unwrap();
+    assert_eq!(added.len(), 1);
+    assert_eq!(added[0].command, "echo 6");
+
+    // An older cutoff doesn't move the mark back
+    assert_eq!(db.prune_older_than(day(1)).await.unwrap(), 0);
+    let old = create_test_command_with_id(2, "echo 2", day(2));
+    assert!(db
+        .insert_new_commands("x", ImportKind::History, vec![old])
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_dedup_keeps_identical_commands_from_other_sessions() {
+    let (mut db, _temp_dir) = create_test_database().await;
+
+    let timestamp = Utc.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap();
+    let first = create_test_command_with_id(1, "make", timestamp);
+    let mut second = create_test_command_with_id(2, "make", timestamp);
+    second.session_id = "other-session".to_string();
+
+    assert_eq!(db.insert_commands_dedup(&[first, second]).await.unwrap(), 2);
+}
+
+#[tokio::test]
+async fn test_undo_last_import_removes_only_the_last_manual_batch() {
+    let (mut db, _temp_dir) = create_test_database().await;
+
+    let timestamp = Utc.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap();
+    let first = vec![
+        create_test_command_with_id(1, "ls", timestamp),
+        create_test_command_with_id(2, "pwd", timestamp),
+    ];
+    let second = vec![
+        // Already stored, so not part of the second batch
+        create_test_command_with_id(1, "ls", timestamp),
+        create_test_command_with_id(3, "rm -rf build", timestamp),
+        create_test_command_with_id(4, "make", timestamp),
+    ];
+    let third = vec![create_test_command_with_id(5, "git status", timestamp)];
+    db.insert_new_commands("wrong.json", ImportKind::Manual, first)
+        .await
+        .unwrap();
+    let added = db
+        .insert_new_commands("wrong.json", ImportKind::Manual, second)
+        .await
+        .unwrap();
+    assert_eq!(added.len(), 2);
+    // The watcher's imports since then aren't what the user means to undo
+    db.insert_new_commands("~/.bash_history", ImportKind::History, third)
+        .await
+        .unwrap();
+    // Nothing new, so nothing to undo later
+    db.insert_new_commands("more.json", ImportKind::Manual, Vec::new())
+        .await
+        .unwrap();
+
+    assert_eq!(db.undo_last_import().await.unwrap(), 2);
+    let left: Vec<String> = db
+        .get_commands(None)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|c| c.command)
+        .collect();
+    assert_eq!(left.len(), 3);
+    assert!(["ls", "pwd", "git status"]
+        .iter()
+        .all(|cmd| left.contains(&cmd.to_string())));
+
+    assert_eq!(db.undo_last_import().await.unwrap(), 2);
+    assert_eq!(db.undo_last_import().await.unwrap(), 0);
+    assert_eq!(db.get_commands(None).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_import_state_round_trip() {
+    let (mut db, _temp_dir) = create_test_database().await;
+
+    assert!(db.get_import_state("/tmp/history").await.unwrap().is_none());
+
+    let mut state = whiskerlog::db::ImportState {
+        path: "/tmp/history".to_string(),
+        byte_offset: 128,
+        last_line_hash: Some("abc".to_string()),
+        modified: Some(1_700_000_000),
+    };
+    db.set_import_state(&state).await.unwrap();
+    assert_eq!(
+        db.get_import_state("/tmp/history").await.unwrap(),
+        Some(state.clone())
+    );
+
+    // Updating replaces the stored offset
+    state.byte_offset = 256;
+    db.set_import_state(&state).await.unwrap();
+    assert_eq!(
+        db.get_import_state("/tmp/history")
+            .await
+            .unwrap()
+            .unwrap()
+            .byte_offset,
+        256
+    );
+}
+
+#[tokio::test]
+async fn test_batch_insert_commands() {
+    let (mut db, _temp_dir) = create_test_database().await;
+
+    let commands: Vec<Command> = (0..500)
+        .map(|i| {
+            create_test_command_with_id(
+                i,
+                &format!("echo {}", i),
+                Utc.timestamp_opt(1_700_000_000 + i, 0).unwrap(),
+            )
+        })
+        .collect();
+
+    assert_eq!(db.insert_commands(&commands).await.unwrap(), 500);
+
+    let retrieved = db.get_commands(None).await.unwrap();
+    assert_eq!(retrieved.len(), 500);
+    assert_eq!(retrieved[0].command, "echo 499");
+}
+
+#[tokio::test]
+async fn test_paginated_commands_and_count() {
+    let (mut db, _temp_dir) = create_test_database().await;
+
+    let commands: Vec<Command> = (0..250)
+        .map(|i| {
+            let mut cmd = create_test_command_with_id(
+                i,
+                &format!("echo {}", i),
+                Utc.timestamp_opt(1_700_000_000 + i, 0).unwrap(),
+            );
+            if i % 10 == 0 {
+                cmd.exit_code = Some(1);
+            }
+            cmd
+        })
+        .collect();
+    db.insert_commands(&commands).await.unwrap();
+
+    assert_eq!(db.count_commands().await.unwrap(), 250);
+    let failed = CommandFilter::new().and("exit_code IS NOT NULL AND exit_code != 0");
+    assert_eq!(db.count_commands_where(&failed).await.unwrap(), 25);
+
+    // Pages are contiguous and don't overlap
+    let first = db
+        .load_commands_paginated(&CommandFilter::new(), "timestamp DESC", 0, 100)
+        .await
+        .unwrap();
+    let second = db
+        .load_commands_paginated(&CommandFilter::new(), "timestamp DESC", 100, 100)
+        .await
+        .unwrap();
+    let last = db
+        .load_commands_paginated(&CommandFilter::new(), "timestamp DESC", 200, 100)
+        .await
+        .unwrap();
+    assert_eq!(first.len(), 100);
+    assert_eq!(last.len(), 50);
+    assert_eq!(first[0].command, "echo 249");
+    assert_eq!(second[0].command, "echo 149");
+    assert_eq!(last[49].command, "echo 0");
+
+    let failed_page = db
+        .load_commands_paginated(&failed, "timestamp ASC", 20, 100)
+        .await
+        .unwrap();
+    let names: Vec<&str> = failed_page.iter().map(|c| c.command.as_str()).collect();
+    assert_eq!(
+        names,
+        vec!["echo 200", "echo 210", "echo 220", "echo 230", "echo 240"]
+    );
+
+    // The old entry point is the unfiltered, newest-first case
+    let legacy = db.get_commands_paginated(100, 100).await.unwrap();
+    assert_eq!(legacy[0].command, second[0].command);
+}
+
+#[tokio::test]
+async fn test_paged_query_binds_filter_values() {
+    let (mut db, _temp_dir) = create_test_database().await;
+
+    let commands: Vec<Command> = (0..6)
+        .map(|i| {
+            let mut cmd = create_test_command_with_id(
+                i,
+                &format!("echo {}", i),
+                Utc.timestamp_opt(1_700_000_000 + i, 0).unwrap(),
+            );
+            cmd.host_id = if i % 2 == 0 { "o'brien" } else { "prod" }.to_string();
+            cmd
+        })
+        .collect();
+    db.insert_commands(&commands).await.unwrap();
+
+    // A quote in the value is data, not SQL
+    let host = CommandFilter::new().and_bound("host_id = ?", ["o'brien".to_string()]);
+    assert_eq!(db.count_commands_where(&host).await.unwrap(), 3);
+    let injected = CommandFilter::new().and_bound("host_id = ?", ["x' OR '1'='1".to_string()]);
+    assert_eq!(db.count_commands_where(&injected).await.unwrap(), 0);
+
+    // Bound values sit before LIMIT and OFFSET, nested or not
+    let newest = CommandFilter::newest(&host, 2);
+    let page = db
+        .load_commands_paginated(&newest, "timestamp ASC", 0, 10)
+        .await
+        .unwrap();
+    let texts: Vec<&str> = page.iter().map(|c| c.command.as_str()).collect();
+    assert_eq!(texts, vec!["echo 2", "echo 4"]);
+
+    let failed = newest.and("exit_code != 0");
+    assert_eq!(db.count_commands_where(&failed).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn test_commands_between() {
+    let (mut db, _temp_dir) = create_test_database().await;
+
+    let base = Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+    let commands: Vec<Command> = (0..10)
+        .map(|i| {
+            create_test_command_with_id(i, &format!("cmd {}", i), base + chrono::Duration::hours(i))
+        })
+        .collect();
+    db.insert_commands(&commands).await.unwrap();
+
+    // Half-open: start is included, end is not
+    let start = base + chrono::Duration::hours(2);
+    let end = base + chrono::Duration::hours(5);
+    let window = db.get_commands_between(start, end).await.unwrap();
+    let names: Vec<&str> = window.iter().map(|c| c.command.as_str()).collect();
+    assert_eq!(names, vec!["cmd 4", "cmd 3", "cmd 2"]);
+    assert_eq!(db.count_commands_between(start, end).await.unwrap(), 3);
+
+    // Empty and inverted ranges return nothing rather than erroring
+    assert!(db
+        .get_commands_between(start, start)
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(db
+        .get_commands_between(end, start)
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(db.count_commands_between(end, start).await.unwrap(), 0);
+
+    // A range outside the stored history
+    let later = base + chrono::Duration::days(30);
+    assert!(db
+        .get_commands_between(later, later + chrono::Duration::days(1))
+        .await
+        .unwrap()
+        .is_empty());
+
+    assert_eq!(db.count_commands().await.unwrap(), 10);
+}
+
+fn query_plan(conn: &rusqlite::Connection, sql: &str) -> String {
+    let mut stmt = conn
+        .prepare(&format!("EXPLAIN QUERY PLAN {}", sql))
+        .unwrap();
+    let rows = stmt
+        .query_map([], |row| row.get::<_, String>(3))
+        .unwrap()
+        .map(|r| r.unwrap())
+        .collect::<Vec<_>>();
+    rows.join("\n")
+}
+
+#[tokio::test]
+async fn test_recency_and_range_queries_use_timestamp_index() {
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().join("test.db");
+    let _db = Database::new(&db_path).await.unwrap();
+
+    let conn = rusqlite::Connection::open(&db_path).unwrap();
+
+    let recency = query_plan(
+        &conn,
+        "SELECT * FROM commands ORDER BY timestamp DESC LIMIT 100",
+    );
+    assert!(recency.contains("idx_commands_timestamp"), "{}", recency);
+    assert!(!recency.contains("TEMP B-TREE"), "{}", recency);
+
+    // The paged view's id tie-breaker only sorts rows sharing a timestamp
+    let paged = query_plan(
+        &conn,
+        "SELECT * FROM commands ORDER BY timestamp DESC, id LIMIT 100 OFFSET 200",
+    );
+    assert!(paged.contains("idx_commands_timestamp"), "{}", paged);
+    assert!(!paged.contains("TEMP B-TREE FOR ORDER BY"), "{}", paged);
+
+    let range = query_plan(
+        &conn,
+        "SELECT * FROM commands WHERE timestamp >= 1 AND timestamp < 2",
+    );
+    assert!(range.contains("idx_commands_timestamp"), "{}", range);
+
+    let session = query_plan(&conn, "SELECT * FROM commands WHERE session_id = 'a'");
+    assert!(session.contains("idx_commands_session"), "{}", session);
+}
+
+#[tokio::test]
+async fn test_opening_old_database_adds_missing_indexes() {
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().join("old.db");
+
+    // A database from before the indexes existed
+    {
+        let conn = rusqlite::Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE commands (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                command TEXT NOT NULL,
+                timestamp INTEGER NOT NULL,
+                exit_code INTEGER,
+                duration INTEGER,
+                working_directory TEXT,
+                session_id TEXT NOT NULL,
+                host_id TEXT NOT NULL DEFAULT 'local',
+                network_endpoints TEXT DEFAULT '[]',
+                packages_used TEXT DEFAULT '[]',
+                is_experiment BOOLEAN DEFAULT FALSE,
+                experiment_tags TEXT DEFAULT '[]',
+                is_dangerous BOOLEAN DEFAULT FALSE,
+                danger_score REAL DEFAULT 0.0,
+                danger_reasons TEXT DEFAULT '[]',
+                shell TEXT NOT NULL DEFAULT 'unknown',
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            );
+            INSERT INTO commands (command, timestamp, session_id) VALUES ('ls', 1700000000, 's1');",
+        )
+        .unwrap();
+    }
+
+    let db = Database::new(&db_path).await.unwrap();
+    assert_eq!(db.count_commands().await.unwrap(), 1);
+
+    let conn = rusqlite::Connection::open(&db_path).unwrap();
+    let indexes: Vec<String> = conn
+        .prepare("SELECT name FROM sqlite_master WHERE type = 'index' AND tbl_name = 'commands'")
+        .unwrap()
+        .query_map([], |row| row.get(0))
+        .unwrap()
+        .map(|r| r.unwrap())
+        .collect();
+    assert!(indexes.contains(&"idx_commands_timestamp".to_string()));
+    assert!(indexes.contains(&"idx_commands_session".to_string()));
+}
+
+#[tokio::test]
+async fn test_database_uses_wal_and_tolerates_concurrent_writers() {
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().join("test.db");
+
+    let mut ui = Database::new(&db_path).await.unwrap();
+    let mut importer = Database::new(&db_path).await.unwrap();
+
+    let conn = rusqlite::Connection::open(&db_path).unwrap();
+    let mode: String = conn
+        .query_row("PRAGMA journal_mode", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(mode.to_lowercase(), "wal");
+
+    // Two connections writing and reading the same file don't hit "database is locked"
+    let now = Utc::now();
+    importer
+        .insert_commands(&[create_test_command_with_id(1, "ls", now)])
+        .await
+        .unwrap();
+    ui.insert_commands(&[create_test_command_with_id(2, "pwd", now)])
+        .await
+        .unwrap();
+    assert_eq!(ui.count_commands().await.unwrap(), 2);
+    assert_eq!(importer.get_commands(None).await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_unversioned_database_is_migrated_and_keeps_rows() {
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().join("legacy.db");
+    let latest = whiskerlog::db::MIGRATIONS.last().unwrap().0;
+
+    // A database written before schema_version existed
+    {
+        let conn = rusqlite::Connection::open(&db_path).unwrap();
+        conn.execute_batch(include_str!("../src/db/schema.sql"))
+            .unwrap();
+        conn.execute(
+            "INSERT INTO commands (command, timestamp, session_id) VALUES ('git status', 1700000000, 's1')",
+            [],
+        )
+        .unwrap();
+    }
+
+    let mut db = Database::new(&db_path).await.unwrap();
+    assert_eq!(db.schema_version().await.unwrap(), latest);
+    let commands = db.get_commands(None).await.unwrap();
+    assert_eq!(commands.len(), 1);
+    assert_eq!(commands[0].command, "git status");
+    drop(db);
+
+    // Reopening is a no-op
+    let db = Database::new(&db_path).await.unwrap();
+    assert_eq!(db.schema_version().await.unwrap(), latest);
+    let conn = rusqlite::Connection::open(&db_path).unwrap();
+    let rows: i64 = conn
+        .query_row("SELECT COUNT(*) FROM schema_version", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(rows, 1);
+}
+
+#[tokio::test]
+async fn test_reset_clears_history_and_keeps_schema_current() {
+    let (mut db, _temp_dir) = create_test_database().await;
+    let latest = whiskerlog::db::MIGRATIONS.last().unwrap().0;
+
+    let now = Utc::now();
+    db.insert_commands(&[
+        create_test_command_with_id(1, "ls", now),
+        create_test_command_with_id(2, "pwd", now),
+    ])
+    .await
+    .unwrap();
+    let state = whiskerlog::db::ImportState::new("/home/user/.bash_history");
+    db.set_import_state(&state).await.unwrap();
+
+    db.reset().await.unwrap();
+    db.vacuum().await.unwrap();
+
+    assert_eq!(db.count_commands().await.unwrap(), 0);
+    assert!(db.get_import_state(&state.path).await.unwrap().is_none());
+    assert_eq!(db.schema_version().await.unwrap(), latest);
+
+    // Tables, indexes and FTS triggers are back and usable
+    db.insert_commands(&[create_test_command_with_id(3, "whoami", now)])
+        .await
+        .unwrap();
+    assert_eq!(db.get_commands(None).await.unwrap()[0].command, "whoami");
+}
+
+#[tokio::test]
+async fn test_import_json_round_trips_export_and_skips_bad_records() {
+    use whiskerlog::export::{write_commands, ExportFormat};
+
+    let (mut db, temp_dir) = create_test_database().await;
+    let base = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+    let commands = vec![
+        create_test_command_with_id(1, "ls", base),
+        create_test_command_with_id(2, "git status", base + chrono::Duration::minutes(1)),
+    ];
+
+    let json_path = temp_dir.path().join("export.json");
+    let mut json = Vec::new();
+    write_commands(&commands, ExportFormat::Json, &mut json).unwrap();
+    std::fs::write(&json_path, json).unwrap();
+
+    let result = db.import_json(&json_path).await.unwrap();
+    assert_eq!(result.imported, 2);
+    assert_eq!(result.failed, 0);
+
+    // Importing again adds nothing
+    let result = db.import_json(&json_path).await.unwrap();
+    assert_eq!(result.imported, 0);
+
+    // NDJSON with one broken line and one record missing fields
+    let mut ndjson = Vec::new();
+    write_commands(
+        &[create_test_command_with_id(
+            3,
+            "pwd",
+            base + chrono::Duration::minutes(2),
+        )],
+        ExportFormat::Ndjson,
+        &mut ndjson,
+    )
+    .unwrap();
+    ndjson.extend_from_slice(b"{not json\n{\"command\": \"ls\"}\n");
+    // Parses, but the danger score is out of range
+    let mut out_of_range = create_test_command_with_id(4, "rm -rf /", base);
+    out_of_range.danger_score = 2.0;
+    ndjson.extend_from_slice(serde_json::to_string(&out_of_range).unwrap().as_bytes());
+    ndjson.push(b'\n');
+    // Exported without a session, so one is made up for it
+    let mut sessionless = create_test_command_with_id(5, "whoami", base);
+    sessionless.session_id = String::new();
+    sessionless.shell = "zsh".to_string();
+    ndjson.extend_from_slice(serde_json::to_string(&sessionless).unwrap().as_bytes());
+    ndjson.push(b'\n');
+    let ndjson_path = temp_dir.path().join("export.ndjson");
+    std::fs::write(&ndjson_path, ndjson).unwrap();
+
+    let result = db.import_json(&ndjson_path).await.unwrap();
+    assert_eq!(result.imported, 2);
+    assert_eq!(result.failed, 3);
+    assert_eq!(db.count_commands().await.unwrap(), 4);
+    let whoami = db
+        .get_commands(None)
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|c| c.command == "whoami")
+        .unwrap();
+    assert_eq!(whoami.session_id, "zsh-20240301-120000");
+
+    // Inserting directly checks the same things
+    let mut invalid = create_test_command_with_id(6, "ls", base);
+    invalid.session_id = String::new();
+    assert!(db.insert_commands(&[invalid]).await.is_err());
+    assert_eq!(db.count_commands().await.unwrap(), 4);
+}
+
+#[tokio::test]
+async fn test_set_experiment_overrides_detection() {
+    let (mut db, temp_dir) = create_test_database().await;
+    let now = Utc::now();
+    let mut detected = create_test_command_with_id(1, "strace -f ls", now);
+    detected.is_experiment = true;
+    db.insert_commands(&[detected, create_test_command_with_id(2, "make", now)])
+        .await
+        .unwrap();
+
+    assert!(db.set_experiment(1, false).await.unwrap());
+    assert!(db.set_experiment(2, true).await.unwrap());
+    assert!(!db.set_experiment(99, true).await.unwrap());
+
+    let commands = db.get_commands(None).await.unwrap();
+    let marked = |id: i64| {
+        commands
+            .iter()
+            .find(|cmd| cmd.id == Some(id))
+            .unwrap()
+            .is_experiment
+    };
+    assert!(!marked(1));
+    assert!(marked(2));
+
+    let conn = rusqlite::Connection::open(temp_dir.path().join("test.db")).unwrap();
+    let manual: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM commands WHERE manual_experiment_override = 1",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(manual, 2);
+}
+
+#[tokio::test]
+async fn test_host_summaries_aggregate_per_host() {
+    let (mut db, _temp_dir) = create_test_database().await;
+
+    let runs: &[(&str, Option<u64>, f32)] = &[
+        ("local", Some(10), 0.0),
+        ("local", Some(20), 0.0),
+        ("local", None, 0.3),
+        ("ssh:deploy@web", Some(1000), 0.9),
+        ("ssh:deploy@web", Some(3000), 0.1),
+        ("docker:web", None, 0.0),
+    ];
+    let commands: Vec<Command> = runs
+        .iter()
+        .zip(0..)
+        .map(|((host, duration, danger_score), minute)| Command {
+            host_id: host.to_string(),
+            duration: *duration,
+            is_dangerous: *danger_score > 0.5,
+            danger_score: *danger_score,
+            is_experiment: *host == "docker:web",
+            ..command_at("bash", &format!("cmd-{}", minute), minute)
+        })
+        .collect();
+    db.insert_commands_dedup(&commands).await.unwrap();
+
+    let summaries = db.host_summaries().await.unwrap();
+    let hosts: Vec<&str> = summaries.iter().map(|s| s.host_id.as_str()).collect();
+    // Busiest first, then by name
+    assert_eq!(hosts, vec!["local", "ssh:deploy@web", "docker:web"]);
+
+    let local = &summaries[0];
+    assert_eq!(local.total_commands, 3);
+    assert_eq!(local.dangerous_commands, 0);
+    // Only commands with a duration count towards the mean
+    assert_eq!(local.avg_duration_ms, Some(15));
+    assert!((local.avg_danger_score - 0.1).abs() < 1e-6);
+    assert_eq!(local.last_seen, commands[2].timestamp);
+
+    let web = &summaries[1];
+    assert_eq!(web.total_commands, 2);
+    assert_eq!(web.dangerous_commands, 1);
+    assert_eq!(web.avg_duration_ms, Some(2000));
+    assert!((web.avg_danger_score - 0.5).abs() < 1e-6);
+
+    let docker = &summaries[2];
+    assert_eq!(docker.experiment_commands, 1);
+    assert_eq!(docker.avg_duration_ms, None);
+
+    // The Hosts tab's view keeps the database's order and numbers
+    let analysis = whiskerlog::ui::hosts::HostAnalysis::from_summaries(&summaries);
+    assert_eq!(analysis.total_hosts, 3);
+    assert_eq!((analysis.ssh_hosts, analysis.docker_hosts), (1, 1));
+    assert_eq!(analysis.hosts[1].avg_duration_ms, 2000);
+    assert_eq!(analysis.hosts[2].avg_duration_ms, 0);
+}
+
+#[tokio::test]
+async fn test_host_aliases_collapse_into_one_summary() {
+    use std::collections::HashMap;
+    use whiskerlog::history::host_aliases::HostAliases;
+
+    let (mut db, _temp_dir) = create_test_database().await;
+
+    let runs: &[(&str, Option<u64>, f32)] = &[
+        ("ssh:deploy@prod", Some(100), 0.0),
+        ("ssh:deploy@prod.internal", Some(300), 0.9),
+        ("ssh:root@10.0.0.5", None, 0.3),
+        ("ssh:root@10.0.0.5", None, 0.0),
+        ("local", Some(10), 0.0),
+        ("ssh:deploy@staging", None, 0.0),
+    ];
+    let commands: Vec<Command> = runs
+        .iter()
+        .zip(0..)
+        .map(|((host, duration, danger_score), minute)| Command {
+            host_id: host.to_string(),
+            duration: *duration,
+            is_dangerous: *danger_score > 0.5,
+            danger_score: *danger_score,
+            ..command_at("bash", &format!("cmd-{}", minute), minute)
+        })
+        .collect();
+    db.insert_commands_dedup(&commands).await.unwrap();
+
+    let aliases = HostAliases::new(&HashMap::from([
+        ("prod".to_string(), "prod".to_string()),
+        ("prod.internal".to_string(), "prod".to_string()),
+        ("10.0.0.*".to_string(), "prod".to_string()),
+    ]));
+    let summaries = aliases.merge_summaries(db.host_summaries().await.unwrap());
+    let hosts: Vec<&str> = summaries.iter().map(|s| s.host_id.as_str()).collect();
+    // Unmatched hosts pass through under their own ids
+    assert_eq!(hosts, vec!["prod", "local", "ssh:deploy@staging"]);
+
+    let prod = &summaries[0];
+    assert_eq!(prod.total_commands, 4);
+    assert_eq!(prod.dangerous_commands, 1);
+    assert_eq!(prod.timed_commands, 2);
+    assert_eq!(prod.avg_duration_ms, Some(200));
+    assert!((prod.avg_danger_score - 0.3).abs() < 1e-6);
+    assert_eq!(prod.last_seen, commands[3].timestamp);
+
+    // Loaded commands carry the same names, so filtering on "prod" finds all four
+    let mut loaded = db.get_commands(None).await.unwrap();
+    aliases.apply(&mut loaded);
+    assert_eq!(loaded.iter().filter(|cmd| cmd.host_id == "prod").count(), 4);
+    assert_eq!(
+        aliases.canonical("ssh:deploy@production"),
+        "ssh:deploy@production"
+    );
+}
+
+#[tokio::test]
+async fn test_reenrich_applies_changed_danger_rules_and_keeps_manual_marks() {
+    use whiskerlog::analysis::DangerAnalyzer;
+    use whiskerlog::config::DangerRule;
+    use whiskerlog::history::CommandEnricher;
+
+    let (mut db, _temp_dir) = create_test_database().await;
+    let base = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+    db.insert_commands(&[
+        create_test_command_with_id(1, "terraform destroy -auto-approve", base),
+        create_test_command_with_id(2, "ls", base + chrono::Duration::minutes(1)),
+        create_test_command_with_id(3, "git status", base + chrono::Duration::minutes(2)),
+    ])
+    .await
+    .unwrap();
+    let ids: Vec<i64> = db
+        .get_commands(None)
+        .await
+        .unwrap()
+        .iter()
+        .filter(|cmd| cmd.command == "ls")
+        .filter_map(|cmd| cmd.id)
+        .collect();
+    assert!(db.set_experiment(ids[0], true).await.unwrap());
+
+    let config = Config {
+        danger_rules: vec![DangerRule {
+            pattern: "terraform destroy".to_string(),
+            is_regex: false,
+            score: 0.9,
+            reason: "Infrastructure teardown".to_string(),
+            enabled: true,
+        }],
+        ..Config::default()
+    };
+    let updated = db
+        .reenrich(
+            &CommandEnricher::from_config(&config),
+            &DangerAnalyzer::from_config(&config),
+        )
+        .await
+        .unwrap();
+    assert_eq!(updated, 1);
+
+    let stored = db.get_commands(None).await.unwrap();
+    let by_text = |text: &str| stored.iter().find(|cmd| cmd.command == text).unwrap();
+    let terraform = by_text("terraform destroy -auto-approve");
+    assert!(terraform.is_dangerous);
+    assert_eq!(terraform.danger_score, 0.9);
+    assert_eq!(terraform.danger_reasons, vec!["Infrastructure teardown"]);
+    // Hosts from the import stay, and the hand-made mark survives detection
+    assert_eq!(terraform.host_id, "test-host");
+    assert!(by_text("ls").is_experiment);
+    assert!(!by_text("git status").is_dangerous);
+
+    // Nothing left to change
+    let updated = db
+        .reenrich(
+            &CommandEnricher::from_config(&config),
+            &DangerAnalyzer::from_config(&config),
+        )
+        .await
+        .unwrap();
+    assert_eq!(updated, 0);
+}
+
+#[tokio::test]
+async fn test_commands_with_package_returns_only_that_package() {
+    let (mut db, _temp_dir) = create_test_database().await;
+    let base = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+    let package = |manager: &str, name: &str, action: &str| PackageRef {
+        manager: manager.to_string(),
+        name: name.to_string(),
+        version: None,
+        action: action.to_string(),
+    };
+
+    let mut install = create_test_command_with_id(1, "sudo apt install docker", base);
+    install.packages_used = vec![package("apt", "docker", "install")];
+    let mut pip =
+        create_test_command_with_id(2, "pip install docker", base + chrono::Duration::hours(1));
+    pip.packages_used = vec![package("pip", "docker", "install")];
+    let mut compose = create_test_command_with_id(
+        3,
+        "sudo apt install docker-compose",
+        base + chrono::Duration::hours(2),
+    );
+    compose.packages_used = vec![package("apt", "docker-compose", "install")];
+    let mut remove = create_test_command_with_id(
+        4,
+        "sudo apt remove docker jq",
+        base + chrono::Duration::hours(3),
+    );
+    remove.packages_used = vec![
+        package("apt", "docker", "remove"),
+        package("apt", "jq", "remove"),
+    ];
+    let plain = create_test_command_with_id(5, "docker ps", base + chrono::Duration::hours(4));
+    db.insert_commands(&[install, pip, compose, remove, plain])
+        .await
+        .unwrap();
+
+    let texts = |commands: Vec<Command>| -> Vec<String> {
+        commands.into_iter().map(|cmd| cmd.command).collect()
+    };
+    assert_eq!(
+        texts(db.commands_with_package(None, "docker").await.unwrap()),
+        vec![
+            "sudo apt remove docker jq",
+            "pip install docker",
+            "sudo apt install docker"
+        ]
+    );
+    assert_eq!(
+        texts(
+            db.commands_with_package(Some("apt"), "Docker")
+                .await
+                .unwrap()
+        ),
+        vec!["sudo apt remove docker jq", "sudo apt install docker"]
+    );
+    assert!(db
+        .commands_with_package(Some("brew"), "docker")
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_tags_round_trip_filter_and_survive_reenrich() {
+    use whiskerlog::analysis::DangerAnalyzer;
+    use whiskerlog::export::{write_commands, ExportFormat};
+    use whiskerlog::history::CommandEnricher;
+
+    let (mut db, _temp_dir) = create_test_database().await;
+    let base = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+    db.insert_commands(&[
+        create_test_command_with_id(1, "kubectl get pods", base),
+        create_test_command_with_id(2, "kubectl logs api", base + chrono::Duration::minutes(1)),
+        create_test_command_with_id(3, "ls", base + chrono::Duration::minutes(2)),
+    ])
+    .await
+    .unwrap();
+    let id_of = |commands: &[Command], text: &str| {
+        commands
+            .iter()
+            .find(|cmd| cmd.command == text)
+            .and_then(|cmd| cmd.id)
+            .unwrap()
+    };
+    let stored = db.get_commands(None).await.unwrap();
+    let pods = id_of(&stored, "kubectl get pods");
+    let logs = id_of(&stored, "kubectl logs api");
+
+    assert!(db
+        .set_tags(
+            pods,
+            &[
+                "incident-2024".to_string(),
+                " #learning-k8s ".to_string(),
+                "incident-2024".to_string(),
+            ],
+        )
+        .await
+        .unwrap());
+    assert!(db
+        .set_tags(logs, &["learning-k8s".to_string()])
+        .await
+        .unwrap());
+    assert!(!db.set_tags(9999, &["missing".to_string()]).await.unwrap());
+
+    let stored = db.get_commands(None).await.unwrap();
+    let tags_of = |text: &str| {
+        stored
+            .iter()
+            .find(|cmd| cmd.command == text)
+            .unwrap()
+            .tags
+            .clone()
+    };
+    assert_eq!(
+        tags_of("kubectl get pods"),
+        vec!["incident-2024", "learning-k8s"]
+    );
+    assert!(tags_of("ls").is_empty());
+
+    let incident = CommandFilter::new().tagged("incident-2024");
+    assert_eq!(db.count_commands_where(&incident).await.unwrap(), 1);
+    let learning = CommandFilter::new().tagged("learning-k8s");
+    let tagged = db
+        .load_commands_paginated(&learning, "timestamp DESC", 0, 10)
+        .await
+        .unwrap();
+    let texts: Vec<&str> = tagged.iter().map(|cmd| cmd.command.as_str()).collect();
+    assert_eq!(texts, vec!["kubectl logs api", "kubectl get pods"]);
+    assert_eq!(
+        db.count_commands_where(&CommandFilter::new().tagged("learning"))
+            .await
+            .unwrap(),
+        0
+    );
+    // Bound, so quotes in a tag are just text
+    assert_eq!(
+        db.count_commands_where(&CommandFilter::new().tagged("x') OR 1=1 --"))
+            .await
+            .unwrap(),
+        0
+    );
+
+    // Re-detection rewrites the derived fields but leaves the user's tags
+    let config = Config::default();
+    db.reenrich(
+        &CommandEnricher::from_config(&config),
+        &DangerAnalyzer::from_config(&config),
+    )
+    .await
+    .unwrap();
+    assert_eq!(db.count_commands_where(&learning).await.unwrap(), 2);
+
+    // Clearing them
+    assert!(db.set_tags(logs, &[]).await.unwrap());
+    assert_eq!(db.count_commands_where(&learning).await.unwrap(), 1);
+
+    let stored = db.get_commands(None).await.unwrap();
+    let mut csv = Vec::new();
+    write_commands(&stored, ExportFormat::Csv, &mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    assert!(csv.lines().next().unwrap().ends_with(",tags"));
+    assert!(csv.contains("incident-2024;learning-k8s"));
+    let mut json = Vec::new();
+    write_commands(&stored, ExportFormat::Json, &mut json).unwrap();
+    let json = String::from_utf8(json).unwrap();
+    assert!(json.contains("\"incident-2024\""));
+}
+
+#[tokio::test]
+async fn test_check_integrity_flags_and_repairs_malformed_json() {
+    use whiskerlog::db::BadJsonRow;
+
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().join("test.db");
+    let mut db = Database::new(&db_path).await.unwrap();
+    let base = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+    let mut curl = create_test_command_with_id(1, "curl https://example.com", base);
+    curl.network_endpoints = vec!["example.com".to_string()];
+    db.insert_commands(&[
+        curl,
+        create_test_command_with_id(2, "apt install jq", base + chrono::Duration::minutes(1)),
+        create_test_command_with_id(3, "ls", base + chrono::Duration::minutes(2)),
+    ])
+    .await
+    .unwrap();
+
+    let report = db.check_integrity().await.unwrap();
+    assert!(report.is_healthy());
+    assert_eq!(report.rows_checked, 3);
+
+    let ids: Vec<i64> = {
+        let conn = rusqlite::Connection::open(&db_path).unwrap();
+        let ids: Vec<i64> = conn
+            .prepare("SELECT id FROM commands ORDER BY id")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        conn.execute(
+            "UPDATE commands SET network_endpoints = '[\"example.com\"' WHERE id = ?1",
+            [ids[0]],
+        )
+        .unwrap();
+        // Valid JSON, but not a list of packages
+        conn.execute(
+            "UPDATE commands SET packages_used = '[{\"manager\": 1}]', tags = 'x' WHERE id = ?1",
+            [ids[1]],
+        )
+        .unwrap();
+        ids
+    };
+
+    let report = db.check_integrity().await.unwrap();
+    assert!(report.integrity_errors.is_empty());
+    assert!(!report.is_healthy());
+    assert_eq!(report.rows_checked, 3);
+    assert_eq!(
+        report.bad_json,
+        vec![
+            BadJsonRow {
+                id: ids[0],
+                columns: vec!["network_endpoints"],
+            },
+            BadJsonRow {
+                id: ids[1],
+                columns: vec!["packages_used", "tags"],
+            },
+        ]
+    );
+
+    assert_eq!(db.repair_json(&report).await.unwrap(), 2);
+    assert!(db.check_integrity().await.unwrap().is_healthy());
+    let stored = db.get_commands(None).await.unwrap();
+    let curl = stored.iter().find(|cmd| cmd.id == Some(ids[0])).unwrap();
+    assert!(curl.network_endpoints.is_empty());
+    // Rows that were fine are left alone
+    assert_eq!(stored.len(), 3);
+    assert_eq!(
+        stored
+            .iter()
+            .find(|cmd| cmd.id == Some(ids[2]))
+            .unwrap()
+            .command,
+        "ls"
+    );
+}
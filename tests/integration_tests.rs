@@ -16,6 +16,7 @@ async fn test_app_initialization() {
         auto_import: false,
         danger_threshold: 0.7,
         experiment_detection: true,
+        ..Config::default()
     };
 
     config.save().unwrap();
@@ -46,6 +47,7 @@ fn test_config_save_and_load() {
         auto_import: true,
         danger_threshold: 0.5,
         experiment_detection: false,
+        ..Config::default()
     };
 
     // Test TOML serialization/deserialization directly
@@ -106,6 +108,8 @@ async fn test_database_operations() {
         danger_reasons: vec![],
         is_experiment: false,
         experiment_tags: vec![],
+        original_command: None,
+        tags: vec![],
     };
 
     db.insert_command(&test_command).await.unwrap();
@@ -136,6 +140,8 @@ fn test_command_creation() {
         danger_reasons: vec![],
         is_experiment: false,
         experiment_tags: vec![],
+        original_command: None,
+        tags: vec![],
     };
 
     assert_eq!(cmd.command, "git status");
@@ -208,6 +214,8 @@ fn test_command_with_packages() {
         danger_reasons: vec![],
         is_experiment: false,
         experiment_tags: vec![],
+        original_command: None,
+        tags: vec![],
     };
 
     assert_eq!(cmd.packages_used.len(), 1);
@@ -234,6 +242,8 @@ fn test_command_with_network_endpoints() {
         danger_reasons: vec![],
         is_experiment: false,
         experiment_tags: vec![],
+        original_command: None,
+        tags: vec![],
     };
 
     assert_eq!(cmd.network_endpoints.len(), 1);
@@ -259,6 +269,8 @@ fn test_dangerous_command() {
         danger_reasons: vec!["destructive operation".to_string()],
         is_experiment: false,
         experiment_tags: vec![],
+        original_command: None,
+        tags: vec![],
     };
 
     assert!(cmd.is_dangerous);
@@ -285,9 +297,774 @@ fn test_experiment_command() {
         danger_reasons: vec![],
         is_experiment: true,
         experiment_tags: vec!["learning".to_string(), "python".to_string()],
+        original_command: None,
+        tags: vec![],
     };
 
     assert!(cmd.is_experiment);
     assert_eq!(cmd.experiment_tags.len(), 2);
     assert!(cmd.experiment_tags.contains(&"learning".to_string()));
 }
+
+#[test]
+fn test_collapse_by_frequency() {
+    use chrono::TimeZone;
+
+    let at = |minute: u32, cmd: &str| Command {
+        command: cmd.to_string(),
+        timestamp: Utc.with_ymd_and_hms(2024, 1, 1, 10, minute, 0).unwrap(),
+        ..Default::default()
+    };
+
+    let commands = vec![
+        at(0, "ls"),
+        at(1, "git status"),
+        at(2, "ls"),
+        at(3, "git  status"),
+        at(4, "cargo build"),
+        at(5, "ls"),
+    ];
+
+    let (collapsed, counts) = whiskerlog::app::collapse_by_frequency(commands);
+
+    let order: Vec<&str> = collapsed.iter().map(|c| c.command.as_str()).collect();
+    assert_eq!(order, vec!["ls", "git  status", "cargo build"]);
+    assert_eq!(counts["ls"], 3);
+    assert_eq!(counts["git status"], 2);
+
+    // Collapsed rows keep the most recent run
+    assert_eq!(collapsed[0].timestamp.format("%M").to_string(), "05");
+}
+
+#[test]
+fn test_export_formats() {
+    use whiskerlog::export::{parse_since, write_commands, ExportFormat};
+
+    let create_test_command = |command: &str| Command {
+        id: None,
+        command: command.to_string(),
+        timestamp: Utc::now(),
+        exit_code: Some(0),
+        duration: None,
+        working_directory: None,
+        host_id: "local".to_string(),
+        session_id: "session-1".to_string(),
+        shell: "bash".to_string(),
+        packages_used: vec![],
+        network_endpoints: vec![],
+        is_dangerous: false,
+        danger_score: 0.0,
+        danger_reasons: vec![],
+        is_experiment: false,
+        experiment_tags: vec![],
+        original_command: None,
+        tags: vec![],
+    };
+
+    let mut first = create_test_command("echo \"hello, world\"");
+    first.packages_used = vec![PackageRef {
+        manager: "npm".to_string(),
+        name: "react".to_string(),
+        version: Some("18.2.0".to_string()),
+        action: "install".to_string(),
+    }];
+    first.network_endpoints = vec!["https://a.example".to_string(), "ssh://b".to_string()];
+    let second = create_test_command("ls -la");
+    let commands = vec![first, second];
+
+    let mut json = Vec::new();
+    write_commands(&commands, ExportFormat::Json, &mut json).unwrap();
+    let parsed: Vec<Command> = serde_json::from_slice(&json).unwrap();
+    assert_eq!(parsed.len(), 2);
+    assert_eq!(parsed[1].command, "ls -la");
+
+    let mut ndjson = Vec::new();
+    write_commands(&commands, ExportFormat::Ndjson, &mut ndjson).unwrap();
+    let lines: Vec<&str> = std::str::from_utf8(&ndjson).unwrap().lines().collect();
+    assert_eq!(lines.len(), 2);
+    let line: Command = serde_json::from_str(lines[0]).unwrap();
+    assert_eq!(line.packages_used[0].name, "react");
+
+    let mut csv = Vec::new();
+    write_commands(&commands, ExportFormat::Csv, &mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    let rows: Vec<&str> = csv.lines().collect();
+    assert_eq!(rows.len(), 3);
+    assert!(rows[0].starts_with("id,command,timestamp"));
+    assert!(rows[1].contains(r#""echo ""hello, world""""#));
+    assert!(rows[1].contains("https://a.example;ssh://b"));
+    assert!(rows[1].contains("npm:react@18.2.0"));
+
+    assert!(parse_since("2024-01-31").is_ok());
+    assert!(parse_since("2024-01-31T12:00:00Z").is_ok());
+    assert!(parse_since("last tuesday").is_err());
+}
+
+#[test]
+fn test_command_window_paging() {
+    use whiskerlog::app::CommandWindow;
+
+    let row = |i: usize| Command {
+        command: format!("echo {}", i),
+        ..Default::default()
+    };
+
+    let mut window = CommandWindow::default();
+    // A fresh window always needs its first page
+    assert_eq!(window.page_needed(0, 20), Some(0));
+
+    window.set_page(0, (0..CommandWindow::PAGE_SIZE).map(row).collect(), 1000);
+    assert_eq!(window.page_needed(0, 20), None);
+    assert_eq!(window.page_needed(150, 20), None);
+    assert_eq!(window.visible(150, 3)[0].command, "echo 150");
+
+    // Scrolling past the loaded rows asks for a page that starts a bit above the offset
+    let start = window.page_needed(190, 20).unwrap();
+    assert!(start <= 190 && start > 0);
+    window.set_page(
+        start,
+        (start..start + CommandWindow::PAGE_SIZE).map(row).collect(),
+        1000,
+    );
+    assert_eq!(window.visible(190, 1)[0].command, "echo 190");
+    assert!(window.visible(start - 1, 5).is_empty());
+
+    // The tail of the result set is covered even if shorter than a screen
+    window.set_page(950, (950..1000).map(row).collect(), 1000);
+    assert_eq!(window.page_needed(980, 20), None);
+    assert_eq!(window.visible(990, 20).len(), 10);
+
+    window.invalidate();
+    assert!(window.page_needed(980, 20).is_some());
+}
+
+#[test]
+fn test_cached_analyses_match_direct_analysis() {
+    use whiskerlog::analysis::heatmap::{TimeRange, ViewMode};
+    use whiskerlog::app::CachedAnalyses;
+
+    let now = chrono::Utc::now();
+    let commands: Vec<Command> = [
+        ("git status", "laptop"),
+        ("git status", "laptop"),
+        ("curl https://api.example.com/v1", "laptop"),
+        ("sudo apt install ripgrep", "server"),
+    ]
+    .iter()
+    .enumerate()
+    .map(|(i, (cmd, host))| Command {
+        command: cmd.to_string(),
+        timestamp: now - chrono::Duration::minutes(i as i64),
+        host_id: host.to_string(),
+        network_endpoints: if cmd.starts_with("curl") {
+            vec!["https://api.example.com/v1".to_string()]
+        } else {
+            vec![]
+        },
+        ..Default::default()
+    })
+    .collect();
+
+    let config = Config::default();
+    let mut cached = CachedAnalyses::new(
+        &commands,
+        &whiskerlog::analysis::stats::StatsAnalyzer::from_config(&config).tally(&commands),
+        &config,
+        TimeRange::Week,
+        ViewMode::All,
+        &mut whiskerlog::analysis::projects::ProjectAnalyzer::new(),
+    );
+
+    assert_eq!(cached.network.top_endpoints.len(), 1);
+    assert_eq!(cached.heatmap.total_commands, 4);
+
+    // Switching heatmap mode only regenerates the grid
+    cached.update_heatmap(&commands, &config, TimeRange::Week, ViewMode::Dangerous);
+    assert_eq!(cached.heatmap.total_commands, 0);
+    assert_eq!(cached.network.top_endpoints.len(), 1);
+}
+
+#[test]
+fn test_report_markdown_and_csv() {
+    use whiskerlog::report::{report_path, write_report, Report, ReportFormat};
+
+    let now = chrono::Utc::now();
+    let config = Config::default();
+    let commands: Vec<Command> = [
+        "git status",
+        "git status",
+        "echo a|b",
+        "sudo rm -rf /tmp/build",
+    ]
+    .iter()
+    .enumerate()
+    .map(|(i, cmd)| Command {
+        command: cmd.to_string(),
+        timestamp: now - chrono::Duration::minutes(i as i64),
+        is_dangerous: cmd.starts_with("sudo rm"),
+        danger_score: if cmd.starts_with("sudo rm") { 0.9 } else { 0.0 },
+        danger_reasons: if cmd.starts_with("sudo rm") {
+            vec!["Recursive delete".to_string()]
+        } else {
+            vec![]
+        },
+        ..Default::default()
+    })
+    .collect();
+
+    let report = Report::build(&commands, &config);
+    let mut markdown = Vec::new();
+    write_report(&report, ReportFormat::Md, &mut markdown).unwrap();
+    let markdown = String::from_utf8(markdown).unwrap();
+    assert!(markdown.contains("## Top commands"));
+    assert!(markdown.contains("| `git status` | 2 | 50.0% |"));
+    assert!(markdown.contains(r"`echo a\|b`"));
+    // No package commands, so that table only has the placeholder row
+    assert!(markdown.contains("## Package managers\n\n| Manager | Operations | Installs | Removes | Updates |\n|---|---|---|---|---|\n| _no data_ |  |  |  |  |\n"));
+    assert!(markdown.contains("1 of 4 commands were flagged as dangerous."));
+    assert!(markdown.contains("| Recursive delete | 1 |"));
+
+    let mut csv = Vec::new();
+    write_report(&report, ReportFormat::Csv, &mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    assert!(csv.starts_with("section,item,count,detail\n"));
+    assert!(csv.contains("top_commands,git status,2,50.0%"));
+    assert!(csv.contains("package_managers,no data,0,"));
+    assert!(csv.contains("dangerous,total,1,of 4 commands"));
+    assert!(csv.contains("rare_commands,echo a|b,1,last used "));
+
+    // Nothing recorded yet still gives a complete report
+    let empty = Report::build(&[], &config);
+    let mut markdown = Vec::new();
+    write_report(&empty, ReportFormat::Md, &mut markdown).unwrap();
+    let markdown = String::from_utf8(markdown).unwrap();
+    assert_eq!(markdown.matches("| _no data_ |").count(), 8);
+
+    let date = chrono::NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+    assert_eq!(
+        report_path(std::path::Path::new("/data"), date, ReportFormat::Csv),
+        PathBuf::from("/data/whiskerlog-report-2024-03-01.csv")
+    );
+}
+
+#[test]
+fn test_list_scroll_keeps_selection_in_short_viewport() {
+    use whiskerlog::app::ListScroll;
+
+    // A 100 row list in a terminal with room for 7 rows
+    let (count, height) = (100, 7);
+    let in_view = |scroll: ListScroll| {
+        scroll.selected >= scroll.offset && scroll.selected < scroll.offset + height
+    };
+
+    let mut scroll = ListScroll::default();
+    for _ in 0..20 {
+        scroll = scroll.move_by(height as isize, count, height);
+        assert!(in_view(scroll), "{:?}", scroll);
+    }
+    assert_eq!(scroll.selected, count - 1);
+    assert_eq!(scroll.offset, count - height);
+
+    for _ in 0..3 {
+        scroll = scroll.move_by(-(height as isize), count, height);
+        assert!(in_view(scroll), "{:?}", scroll);
+    }
+    assert_eq!(scroll.selected, count - 1 - 3 * height);
+
+    // Single steps only scroll once the selection reaches an edge
+    let scroll = ListScroll::default().move_by(height as isize - 1, count, height);
+    assert_eq!(scroll.offset, 0);
+    let scroll = scroll.move_by(1, count, height);
+    assert_eq!(scroll.offset, 1);
+
+    // Jumping to the end of a short list, and an empty one
+    assert_eq!(
+        ListScroll::default().move_by(isize::MAX, 3, height),
+        ListScroll {
+            selected: 2,
+            offset: 0
+        }
+    );
+    assert_eq!(
+        ListScroll::default().move_by(1, 0, height),
+        ListScroll::default()
+    );
+}
+
+#[test]
+fn test_analytics_refresh_honours_configured_window() {
+    use std::time::{Duration, Instant};
+    use whiskerlog::app::AnalyticsFreshness;
+
+    let config = Config {
+        analytics_refresh_secs: 5,
+        ..Config::default()
+    };
+    let window = config.analytics_refresh_interval();
+    assert_eq!(window, Duration::from_secs(5));
+
+    let start = Instant::now();
+    let mut freshness = AnalyticsFreshness::new(window);
+    freshness.mark_fresh(start);
+    assert!(!freshness.needs_refresh(start + Duration::from_secs(4)));
+    assert!(freshness.needs_refresh(start + Duration::from_secs(6)));
+
+    freshness.mark_fresh(start + Duration::from_secs(6));
+    assert!(!freshness.needs_refresh(start + Duration::from_secs(7)));
+    freshness.invalidate();
+    assert!(freshness.needs_refresh(start + Duration::from_secs(7)));
+
+    // Out-of-range settings are clamped
+    let config = Config {
+        ui_poll_ms: 1,
+        analytics_refresh_secs: 0,
+        ..Config::default()
+    };
+    assert_eq!(config.ui_poll_interval(), Duration::from_millis(16));
+    assert_eq!(config.analytics_refresh_interval(), Duration::from_secs(1));
+    let config = Config {
+        ui_poll_ms: 5000,
+        ..Config::default()
+    };
+    assert_eq!(config.ui_poll_interval(), Duration::from_millis(500));
+}
+
+#[tokio::test]
+async fn test_onboarding_only_for_empty_database() {
+    use whiskerlog::onboarding::{format_size, needs_onboarding};
+
+    let temp_dir = TempDir::new().unwrap();
+    let mut config = Config {
+        database_path: temp_dir.path().join("test.db"),
+        ..Config::default()
+    };
+    let mut db = Database::new(&config.database_path).await.unwrap();
+    assert!(needs_onboarding(&config, &db).await.unwrap());
+
+    // Upgrading users already have history
+    let command = Command::builder("ls", Utc::now())
+        .session_id("s1")
+        .shell("bash")
+        .exit_code(0)
+        .build()
+        .unwrap();
+    db.insert_commands(&[command]).await.unwrap();
+    assert!(!needs_onboarding(&config, &db).await.unwrap());
+
+    db.reset().await.unwrap();
+    config.onboarded = true;
+    assert!(!needs_onboarding(&config, &db).await.unwrap());
+
+    assert_eq!(format_size(512), "512 B");
+    assert_eq!(format_size(1536), "1.5 KB");
+    assert_eq!(format_size(3 * 1024 * 1024), "3.0 MB");
+}
+
+#[test]
+fn test_metrics_are_valid_textfile_lines() {
+    use whiskerlog::metrics::{write_metrics, write_metrics_file, Metrics, MAX_MANAGER_LABELS};
+
+    let now = chrono::Utc::now();
+    let mut commands: Vec<Command> = (0..15)
+        .map(|i| Command {
+            command: format!("tool{} install pkg", i),
+            timestamp: now - chrono::Duration::minutes(i),
+            host_id: if i % 2 == 0 { "laptop" } else { "server\"1" }.to_string(),
+            exit_code: Some(if i < 12 { 0 } else { 1 }),
+            packages_used: vec![PackageRef {
+                manager: format!("mgr{:02}", i),
+                name: "pkg".to_string(),
+                version: None,
+                action: "install".to_string(),
+            }],
+            ..Default::default()
+        })
+        .collect();
+    commands[0].command = "sudo rm -rf /".to_string();
+
+    let metrics = Metrics::build(&commands, &Config::default());
+    assert_eq!(metrics.total_commands, 15);
+    assert_eq!(metrics.unique_hosts, 2);
+    assert_eq!(metrics.packages_installed.len(), MAX_MANAGER_LABELS);
+
+    let mut out = Vec::new();
+    write_metrics(&metrics, &mut out).unwrap();
+    let text = String::from_utf8(out).unwrap();
+
+    let sample = regex::Regex::new(
+        r#"^[a-zA-Z_:][a-zA-Z0-9_:]*(\{[a-zA-Z_][a-zA-Z0-9_]*="([^"\\\n]|\\.)*"\})? -?[0-9]+(\.[0-9]+)?$"#,
+    )
+    .unwrap();
+    let mut names = std::collections::HashSet::new();
+    for line in text.lines() {
+        if let Some(comment) = line.strip_prefix("# ") {
+            assert!(
+                comment.starts_with("HELP whiskerlog_") || comment.starts_with("TYPE whiskerlog_"),
+                "{}",
+                line
+            );
+            continue;
+        }
+        assert!(sample.is_match(line), "not a metric line: {}", line);
+        names.insert(line.split(['{', ' ']).next().unwrap().to_string());
+    }
+    for name in [
+        "whiskerlog_total_commands",
+        "whiskerlog_dangerous_commands",
+        "whiskerlog_unique_hosts",
+        "whiskerlog_packages_installed",
+        "whiskerlog_success_rate",
+    ] {
+        assert!(names.contains(name), "missing {}", name);
+    }
+    assert!(text.contains("whiskerlog_total_commands 15\n"));
+    assert!(text.contains("whiskerlog_success_rate 0.8\n"));
+    assert!(text.contains("whiskerlog_packages_installed{manager=\"mgr00\"} 1\n"));
+
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("whiskerlog.prom");
+    write_metrics_file(&metrics, &path).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), text);
+    assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+}
+
+#[tokio::test]
+async fn test_stats_summary_over_a_window() {
+    use chrono::TimeZone;
+    use whiskerlog::export::{parse_since, parse_until};
+    use whiskerlog::stats_summary::{load_commands, write_summary, StatsSummary};
+
+    let temp_dir = TempDir::new().unwrap();
+    let mut db = Database::new(&temp_dir.path().join("test.db"))
+        .await
+        .unwrap();
+    let at = |month, day, hour, minute| {
+        Utc.with_ymd_and_hms(2024, month, day, hour, minute, 0)
+            .unwrap()
+    };
+    let seeded = [
+        ("ls", at(2, 20, 9, 0), Some(0)),
+        ("git status", at(3, 4, 9, 0), Some(0)),
+        ("git status", at(3, 4, 9, 10), Some(0)),
+        ("cargo build", at(3, 4, 14, 0), Some(1)),
+        ("git status", at(3, 5, 9, 30), Some(0)),
+        ("sudo rm -rf /tmp/x", at(3, 5, 10, 0), Some(0)),
+        ("cargo build", at(3, 6, 9, 45), None),
+        ("ls", at(3, 7, 0, 0), Some(0)),
+    ];
+    for (command, timestamp, exit_code) in seeded {
+        let mut builder = Command::builder(command, timestamp)
+            .session_id("s1")
+            .exit_code(exit_code);
+        if command.starts_with("sudo rm") {
+            builder = builder.dangerous(1.0, vec![]);
+        }
+        db.insert_command(&builder.build().unwrap()).await.unwrap();
+    }
+
+    let since = parse_since("2024-03-01").unwrap();
+    let until = parse_until("2024-03-06").unwrap();
+    let commands = load_commands(&mut db, Some(since), Some(until))
+        .await
+        .unwrap();
+    let summary = StatsSummary::build(&commands, &Config::default());
+    let mut out = Vec::new();
+    write_summary(&summary, &mut out).unwrap();
+
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "\
+Commands:     6
+Span:         2024-03-04 to 2024-03-06 (3 days)
+Success rate: 80.0%
+Most active:  09:00 UTC, Monday
+Dangerous:    1
+
+Top commands:
+   1. 3  git status
+   2. 2  cargo build
+   3. 1  sudo rm -rf <path>
+"
+    );
+
+    // No window reads everything; an empty one still prints the totals
+    assert_eq!(load_commands(&mut db, None, None).await.unwrap().len(), 8);
+    let empty = load_commands(&mut db, Some(until), Some(since))
+        .await
+        .unwrap();
+    let mut out = Vec::new();
+    write_summary(&StatsSummary::build(&empty, &Config::default()), &mut out).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "Commands:     0\nSpan:         no commands\nDangerous:    0\n"
+    );
+}
+
+#[test]
+fn test_anonymized_export_leaks_no_paths_or_hosts() {
+    use whiskerlog::export::{anonymize, write_commands, ExportFormat};
+
+    let command = |text: &str, dir: &str, host: &str, endpoints: &[&str]| Command {
+        command: text.to_string(),
+        working_directory: Some(dir.to_string()),
+        host_id: host.to_string(),
+        network_endpoints: endpoints.iter().map(|e| e.to_string()).collect(),
+        ..Default::default()
+    };
+    let mut commands = vec![
+        command(
+            "git commit -m 'fix the acme billing bug'",
+            "/home/alice/acme-billing",
+            "local",
+            &[],
+        ),
+        command(
+            "AWS_PROFILE=acme /home/alice/bin/deploy.sh --env prod",
+            "/home/alice/acme-billing/infra",
+            "local",
+            &[],
+        ),
+        command(
+            "ssh db-primary.acme.internal",
+            "/home/alice",
+            "local",
+            &["ssh://db-primary.acme.internal"],
+        ),
+        command("ssh prodbox", "/home/alice", "local", &["ssh://prodbox"]),
+        command(
+            "sudo systemctl restart nginx",
+            "/etc/nginx",
+            "ssh:deploy@prodbox",
+            &[],
+        ),
+        command(
+            "curl https://api.acme.internal/v1/users",
+            "/home/alice/acme-billing",
+            "ssh:deploy@prodbox",
+            &["https://api.acme.internal"],
+        ),
+    ];
+
+    commands[0].tags = vec!["acme-billing-outage".to_string()];
+
+    anonymize(&mut commands);
+
+    assert!(commands.iter().all(|c| c.tags.is_empty()));
+    let texts: Vec<&str> = commands.iter().map(|c| c.command.as_str()).collect();
+    assert_eq!(
+        texts,
+        vec![
+            "git commit",
+            "deploy.sh",
+            "ssh",
+            "ssh",
+            "sudo systemctl restart",
+            "curl"
+        ]
+    );
+    // The same directory and host get the same token within an export
+    assert_eq!(commands[0].working_directory.as_deref(), Some("<dir-1>"));
+    assert_eq!(commands[1].working_directory.as_deref(), Some("<dir-2>"));
+    assert_eq!(commands[5].working_directory.as_deref(), Some("<dir-1>"));
+    assert_eq!(commands[4].host_id, commands[5].host_id);
+    assert!(commands[4].host_id.starts_with("<host-"));
+    assert_eq!(commands[0].host_id, "local");
+
+    let mut json = Vec::new();
+    write_commands(&commands, ExportFormat::Json, &mut json).unwrap();
+    let mut csv = Vec::new();
+    write_commands(&commands, ExportFormat::Csv, &mut csv).unwrap();
+    for output in [
+        String::from_utf8(json).unwrap(),
+        String::from_utf8(csv).unwrap(),
+    ] {
+        for secret in [
+            "alice",
+            "acme",
+            "prodbox",
+            "deploy@",
+            "/etc",
+            "nginx",
+            "db-primary",
+        ] {
+            assert!(
+                !output.contains(secret),
+                "{} leaked into {}",
+                secret,
+                output
+            );
+        }
+        assert!(output.contains("ssh://<host-"));
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_doc_viewer_prefers_tldr_and_needs_an_executable() {
+    use std::os::unix::fs::PermissionsExt;
+    use whiskerlog::docs::{find_program, pager_in, DocViewer};
+
+    let temp_dir = TempDir::new().unwrap();
+    let install = |dir: &str, program: &str, mode: u32| {
+        let dir = temp_dir.path().join(dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(program);
+        std::fs::write(&path, "#!/bin/sh\n").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode)).unwrap();
+        dir
+    };
+    let man_dir = install("a", "man", 0o755);
+    let tldr_dir = install("b", "tldr", 0o755);
+    let broken_dir = install("c", "tldr", 0o644);
+
+    let path = |dirs: &[&PathBuf]| std::env::join_paths(dirs.iter()).unwrap();
+
+    assert_eq!(DocViewer::find_in(&path(&[&man_dir])), Some(DocViewer::Man));
+    // tldr wins wherever it is on the path
+    assert_eq!(
+        DocViewer::find_in(&path(&[&man_dir, &tldr_dir])),
+        Some(DocViewer::Tldr)
+    );
+    // A file that isn't executable doesn't count
+    assert_eq!(
+        DocViewer::find_in(&path(&[&broken_dir, &man_dir])),
+        Some(DocViewer::Man)
+    );
+    assert_eq!(DocViewer::find_in(&path(&[&broken_dir])), None);
+    assert_eq!(
+        find_program("tldr", &path(&[&broken_dir, &tldr_dir])),
+        Some(tldr_dir.join("tldr"))
+    );
+
+    let command = DocViewer::Tldr.command("git");
+    assert_eq!(command.get_program(), "tldr");
+    assert_eq!(command.get_args().collect::<Vec<_>>(), vec!["git"]);
+    assert!(!DocViewer::Tldr.pages_itself());
+    assert!(DocViewer::Man.pages_itself());
+
+    // `$PAGER` with its arguments, else `less -R`, as long as it's installed
+    let pager_dir = install("d", "most", 0o755);
+    let less_dir = install("e", "less", 0o755);
+    let pager = pager_in(Some("most -s"), &path(&[&pager_dir])).unwrap();
+    assert_eq!(pager.get_program(), pager_dir.join("most"));
+    assert_eq!(pager.get_args().collect::<Vec<_>>(), vec!["-s"]);
+    let pager = pager_in(None, &path(&[&less_dir])).unwrap();
+    assert_eq!(pager.get_program(), less_dir.join("less"));
+    assert_eq!(pager.get_args().collect::<Vec<_>>(), vec!["-R"]);
+    assert!(pager_in(Some(" "), &path(&[&less_dir])).is_some());
+    assert!(pager_in(Some("most"), &path(&[&less_dir])).is_none());
+    assert!(pager_in(None, &path(&[&man_dir])).is_none());
+}
+
+#[tokio::test]
+async fn test_about_info_reports_locations_and_counts() {
+    use whiskerlog::about::AboutInfo;
+
+    let temp_dir = TempDir::new().unwrap();
+    let config = Config {
+        database_path: temp_dir.path().join("history.db"),
+        config_path: Some(temp_dir.path().join("config.toml")),
+        ..Config::default()
+    };
+    let mut db = Database::new(&config.database_path).await.unwrap();
+    let commands: Vec<Command> = ["ls", "git status"]
+        .iter()
+        .map(|cmd| {
+            Command::builder(*cmd, Utc::now())
+                .session_id("s1")
+                .build()
+                .unwrap()
+        })
+        .collect();
+    db.insert_commands(&commands).await.unwrap();
+
+    let about = AboutInfo::gather(&config, &db).await.unwrap();
+    assert_eq!(about.version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(about.config_path, temp_dir.path().join("config.toml"));
+    assert_eq!(about.database_path, config.database_path);
+    assert!(about.database_size.is_some_and(|size| size > 0));
+    assert_eq!(about.total_commands, 2);
+}
+
+/// Run the built binary on a scratch config and database in `dir`.
+fn run_whiskerlog(dir: &std::path::Path, args: &[&str]) -> std::process::Output {
+    std::process::Command::new(env!("CARGO_BIN_EXE_whiskerlog"))
+        .arg("--config")
+        .arg(dir.join("config.toml"))
+        .arg("--db")
+        .arg(dir.join("history.db"))
+        .args(args)
+        .env_remove("WHISKERLOG_CONFIG")
+        .env_remove("WHISKERLOG_DATA")
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_stats_subcommand_and_deprecated_flag() {
+    let temp_dir = TempDir::new().unwrap();
+    let out = temp_dir.path().join("stats.txt");
+
+    let output = run_whiskerlog(
+        temp_dir.path(),
+        &[
+            "stats",
+            "--since",
+            "2024-03-01",
+            "--out",
+            out.to_str().unwrap(),
+        ],
+    );
+    assert!(output.status.success(), "{:?}", output);
+    assert!(std::fs::read_to_string(&out)
+        .unwrap()
+        .starts_with("Commands:     0\n"));
+
+    let output = run_whiskerlog(temp_dir.path(), &["--stats", "--until", "2024-03-31"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .starts_with("Commands:     0\n"));
+
+    // Still one mode at a time
+    let output = run_whiskerlog(temp_dir.path(), &["--report", "md", "stats"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_maintenance_subcommands_and_deprecated_flags() {
+    let temp_dir = TempDir::new().unwrap();
+    let stdout = |args: &[&str]| {
+        let output = run_whiskerlog(temp_dir.path(), args);
+        assert!(output.status.success(), "{:?}: {:?}", args, output);
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    assert_eq!(stdout(&["undo-import"]), "No import to undo.\n");
+    assert_eq!(stdout(&["--undo-import"]), "No import to undo.\n");
+    assert_eq!(stdout(&["reenrich"]), "Updated 0 stored commands\n");
+    assert!(stdout(&["check-db", "--repair"]).contains("no problems found"));
+    assert!(stdout(&["--check-db", "--repair"]).contains("no problems found"));
+    assert!(stdout(&["prune", "30"]).starts_with("Removed 0 commands"));
+    assert!(stdout(&["--prune", "30"]).starts_with("Removed 0 commands"));
+
+    let metrics = temp_dir.path().join("whiskerlog.prom");
+    stdout(&["metrics", "--out", metrics.to_str().unwrap()]);
+    assert!(std::fs::read_to_string(&metrics)
+        .unwrap()
+        .contains("whiskerlog_"));
+    std::fs::remove_file(&metrics).unwrap();
+    stdout(&["--metrics-out", metrics.to_str().unwrap()]);
+    assert!(metrics.exists());
+
+    // One mode at a time, however it's asked for
+    for args in [
+        &["--undo-import", "reenrich"][..],
+        &["--reenrich", "--check-db"],
+        &["--prune", "30", "--undo-import"],
+        &["metrics"],
+    ] {
+        let output = run_whiskerlog(temp_dir.path(), args);
+        assert!(!output.status.success(), "{:?}", args);
+    }
+}
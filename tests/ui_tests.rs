@@ -1,3 +1,6 @@
+mod common;
+
+use common::command_at;
 use ratatui::style::Color;
 use whiskerlog::app::*;
 use whiskerlog::ui::theme::*;
@@ -59,15 +62,17 @@ fn test_tab_enum() {
         Tab::Packages,
         Tab::Aliases,
         Tab::Experiments,
+        Tab::Projects,
     ];
 
-    assert_eq!(tabs.len(), 11);
+    assert_eq!(tabs.len(), 12);
 
     // Test tab titles
     assert_eq!(Tab::Summary.title(), "Summary");
     assert_eq!(Tab::Commands.title(), "Commands");
     assert_eq!(Tab::Network.title(), "Network");
     assert_eq!(Tab::Packages.title(), "Packages");
+    assert_eq!(Tab::Projects.title(), "Projects");
 }
 
 #[test]
@@ -112,7 +117,7 @@ fn test_filter_by_enum() {
 #[test]
 fn test_tab_all_method() {
     let all_tabs = Tab::all();
-    assert_eq!(all_tabs.len(), 11);
+    assert_eq!(all_tabs.len(), 12);
 
     // Check that all expected tabs are present
     assert!(all_tabs.contains(&Tab::Summary));
@@ -120,6 +125,7 @@ fn test_tab_all_method() {
     assert!(all_tabs.contains(&Tab::Network));
     assert!(all_tabs.contains(&Tab::Packages));
     assert!(all_tabs.contains(&Tab::Experiments));
+    assert!(all_tabs.contains(&Tab::Projects));
 }
 
 #[test]
@@ -208,3 +214,1116 @@ fn test_enum_partial_eq_trait() {
     assert_eq!(SearchFilter::None, SearchFilter::None);
     assert_ne!(SearchFilter::None, SearchFilter::Failed);
 }
+
+#[test]
+fn test_package_filter_by_ecosystem() {
+    use whiskerlog::analysis::package_tracker::PackageTracker;
+    use whiskerlog::history::{Command, PackageRef};
+    use whiskerlog::ui::packages::{apply_package_filter, PackageFilter};
+
+    let with_package = |manager: &str, name: &str| Command {
+        command: format!("{} install {}", manager, name),
+        packages_used: vec![PackageRef {
+            manager: manager.to_string(),
+            name: name.to_string(),
+            version: None,
+            action: "install".to_string(),
+        }],
+        ..Default::default()
+    };
+
+    let commands = vec![
+        with_package("apt", "curl"),
+        with_package("npm", "react"),
+        with_package("cargo", "ripgrep"),
+    ];
+    let analysis = PackageTracker::new().analyze_package_usage(&commands);
+
+    let linux = apply_package_filter(&analysis, &PackageFilter::Linux);
+    assert_eq!(linux.managers_used.len(), 1);
+    assert_eq!(linux.managers_used[0].manager, "apt");
+    assert_eq!(linux.total_package_operations, 1);
+
+    let programming = apply_package_filter(&analysis, &PackageFilter::Programming);
+    assert_eq!(programming.managers_used.len(), 2);
+
+    assert!(apply_package_filter(&analysis, &PackageFilter::Container)
+        .managers_used
+        .is_empty());
+
+    let all = apply_package_filter(&analysis, &PackageFilter::All);
+    assert_eq!(all.managers_used.len(), analysis.managers_used.len());
+    assert_eq!(
+        all.total_package_operations,
+        analysis.total_package_operations
+    );
+}
+
+#[test]
+fn test_package_rows_include_every_package_and_timeline() {
+    use chrono::{TimeZone, Utc};
+    use whiskerlog::analysis::package_tracker::PackageTracker;
+    use whiskerlog::history::{Command, PackageRef};
+    use whiskerlog::ui::packages::package_rows;
+
+    let op = |day: u32, manager: &str, name: &str, version: Option<&str>, action: &str| Command {
+        command: format!("{} {} {}", manager, action, name),
+        timestamp: Utc.with_ymd_and_hms(2024, 3, day, 12, 0, 0).unwrap(),
+        packages_used: vec![PackageRef {
+            manager: manager.to_string(),
+            name: name.to_string(),
+            version: version.map(str::to_string),
+            action: action.to_string(),
+        }],
+        ..Default::default()
+    };
+
+    // More packages than a single manager used to keep
+    let mut commands: Vec<Command> = (1..=15)
+        .map(|i| op(1, "npm", &format!("pkg{:02}", i), None, "install"))
+        .collect();
+    commands.push(op(2, "pip", "requests", Some("2.30.0"), "install"));
+    commands.push(op(5, "pip", "requests", None, "remove"));
+    commands.push(op(3, "pip", "requests", Some("2.31.0"), "install"));
+
+    let tracker = PackageTracker::new();
+    let rows = package_rows(&tracker.analyze_package_usage(&commands));
+    assert_eq!(rows.len(), 16);
+    assert_eq!(rows[0].1.name, "requests");
+    assert_eq!(rows[0].0, "pip");
+    assert_eq!(rows[0].1.versions_seen, vec!["2.30.0", "2.31.0"]);
+
+    let timeline = tracker.package_timeline(&commands, "requests");
+    let actions: Vec<&str> = timeline.iter().map(|e| e.action.as_str()).collect();
+    assert_eq!(actions, vec!["install", "install", "remove"]);
+    assert_eq!(timeline[1].version.as_deref(), Some("2.31.0"));
+}
+
+#[test]
+fn test_host_analysis_uses_true_means() {
+    use whiskerlog::history::Command;
+    use whiskerlog::ui::hosts::analyze_host_commands;
+
+    let on_host = |host: &str, duration: Option<u64>, danger_score: f32| Command {
+        host_id: host.to_string(),
+        duration,
+        danger_score,
+        ..Default::default()
+    };
+
+    let commands = vec![
+        on_host("ssh:deploy@web", Some(10), 0.0),
+        on_host("ssh:deploy@web", Some(10), 0.0),
+        on_host("ssh:deploy@web", Some(1000), 0.9),
+        // Commands without a duration don't count towards the mean
+        on_host("ssh:deploy@web", None, 0.3),
+        on_host("local", Some(250), 0.2),
+    ];
+
+    let analysis = analyze_host_commands(&commands);
+    let web = analysis
+        .hosts
+        .iter()
+        .find(|h| h.host_id == "ssh:deploy@web")
+        .unwrap();
+    assert_eq!(web.total_commands, 4);
+    assert_eq!(web.avg_duration_ms, 340);
+    assert!((web.danger_score - 0.3).abs() < 1e-6);
+
+    let local = analysis
+        .hosts
+        .iter()
+        .find(|h| h.host_id == "local")
+        .unwrap();
+    assert_eq!(local.avg_duration_ms, 250);
+    assert!((local.danger_score - 0.2).abs() < 1e-6);
+}
+
+#[test]
+fn test_network_endpoint_filter_and_sort() {
+    use chrono::{Duration, Utc};
+    use std::collections::HashMap;
+    use whiskerlog::analysis::network_analyzer::{EndpointStats, NetworkAnalysis};
+    use whiskerlog::ui::network::{endpoint_rows, NetworkFilter, NetworkSort};
+
+    let now = Utc::now();
+    let endpoint =
+        |name: &str, uses: usize, age_hours: i64, secure: bool, success: f32| EndpointStats {
+            endpoint: name.to_string(),
+            protocol: if secure { "HTTPS" } else { "HTTP" }.to_string(),
+            usage_count: uses,
+            first_seen: now - Duration::days(30),
+            last_seen: now - Duration::hours(age_hours),
+            is_secure: secure,
+            is_trusted: false,
+            success_rate: success,
+        };
+
+    let analysis = NetworkAnalysis {
+        total_network_commands: 0,
+        unique_endpoints: 4,
+        protocol_breakdown: HashMap::new(),
+        security_issues: vec![],
+        top_endpoints: vec![
+            endpoint("https://busy.example", 50, 48, true, 1.0),
+            endpoint("https://flaky.example", 5, 24, true, 0.5),
+            endpoint("http://plain.example", 2, 72, false, 1.0),
+            endpoint("https://fresh.example", 1, 1, true, 1.0),
+        ],
+        connection_patterns: vec![],
+    };
+
+    let names = |rows: Vec<EndpointStats>| -> Vec<String> {
+        rows.into_iter().map(|e| e.endpoint).collect()
+    };
+
+    assert_eq!(
+        names(endpoint_rows(
+            &analysis,
+            &NetworkFilter::All,
+            &NetworkSort::Usage
+        ))[0],
+        "https://busy.example"
+    );
+    assert_eq!(
+        names(endpoint_rows(
+            &analysis,
+            &NetworkFilter::All,
+            &NetworkSort::Time
+        ))[0],
+        "https://fresh.example"
+    );
+    assert_eq!(
+        names(endpoint_rows(
+            &analysis,
+            &NetworkFilter::All,
+            &NetworkSort::Risk
+        ))[..2],
+        ["http://plain.example", "https://flaky.example"]
+    );
+
+    let insecure = endpoint_rows(&analysis, &NetworkFilter::Insecure, &NetworkSort::Usage);
+    assert_eq!(names(insecure), vec!["http://plain.example"]);
+    let secure = endpoint_rows(&analysis, &NetworkFilter::Secure, &NetworkSort::Usage);
+    assert_eq!(secure.len(), 3);
+    assert!(secure.iter().all(|e| e.is_secure));
+
+    // A rarely used insecure endpoint still shows up behind 25 busier ones
+    let mut crowded = analysis.clone();
+    crowded.top_endpoints = (0..25)
+        .map(|i| endpoint(&format!("https://api{}.example", i), 100 - i, 1, true, 1.0))
+        .chain([endpoint("http://plain.example", 1, 72, false, 1.0)])
+        .collect();
+    let all = endpoint_rows(&crowded, &NetworkFilter::All, &NetworkSort::Usage);
+    assert_eq!(all.len(), 20);
+    let insecure = endpoint_rows(&crowded, &NetworkFilter::Insecure, &NetworkSort::Usage);
+    assert_eq!(names(insecure), vec!["http://plain.example"]);
+    let risky = endpoint_rows(&crowded, &NetworkFilter::All, &NetworkSort::Risk);
+    assert_eq!(risky[0].endpoint, "http://plain.example");
+}
+
+#[test]
+fn test_alias_rows_filter_and_sort() {
+    use whiskerlog::analysis::alias_suggest::AliasSuggestion;
+    use whiskerlog::ui::aliases::{alias_rows, AliasFilter, AliasSort};
+
+    let suggestion = |command: &str, frequency: usize, per_use: usize| AliasSuggestion {
+        command: command.to_string(),
+        suggested_alias: "x".to_string(),
+        frequency,
+        time_saved_per_use: per_use,
+        total_time_saved: frequency * per_use,
+    };
+    let suggestions = vec![
+        suggestion("git status", 40, 7),
+        suggestion("docker compose up -d", 5, 15),
+        suggestion("gitk --all", 3, 5),
+        suggestion("kubectl get pods --all-namespaces", 10, 25),
+        suggestion("docker-compose logs -f", 8, 12),
+    ];
+    let commands = |rows: Vec<AliasSuggestion>| -> Vec<String> {
+        rows.into_iter().map(|s| s.command).collect()
+    };
+
+    // Impact keeps the suggester's order
+    assert_eq!(
+        commands(alias_rows(
+            &suggestions,
+            &AliasFilter::All,
+            &AliasSort::Impact
+        )),
+        commands(suggestions.clone())
+    );
+    assert_eq!(
+        commands(alias_rows(
+            &suggestions,
+            &AliasFilter::All,
+            &AliasSort::Savings
+        ))[0],
+        "git status"
+    );
+    assert_eq!(
+        commands(alias_rows(
+            &suggestions,
+            &AliasFilter::All,
+            &AliasSort::Frequency
+        ))[1],
+        "kubectl get pods --all-namespaces"
+    );
+    assert_eq!(
+        commands(alias_rows(
+            &suggestions,
+            &AliasFilter::All,
+            &AliasSort::Length
+        ))[0],
+        "kubectl get pods --all-namespaces"
+    );
+
+    // Filters match the tool, not any command that merely starts with its letters
+    assert_eq!(
+        commands(alias_rows(
+            &suggestions,
+            &AliasFilter::Git,
+            &AliasSort::Savings
+        )),
+        vec!["git status"]
+    );
+    assert_eq!(
+        commands(alias_rows(
+            &suggestions,
+            &AliasFilter::Docker,
+            &AliasSort::Frequency
+        )),
+        vec!["docker-compose logs -f", "docker compose up -d"]
+    );
+}
+
+#[test]
+fn test_fuzzy_search_scores_filters_and_highlights() {
+    use chrono::{Duration, Utc};
+    use whiskerlog::history::Command;
+    use whiskerlog::ui::search::fuzzy_search;
+
+    let now = Utc::now();
+    // Oldest first, as App holds them
+    let commands: Vec<Command> = [
+        ("git status", Some(0), false),
+        ("rm -rf build", Some(0), true),
+        ("git stash pop", Some(1), false),
+        ("grep -r todo src", Some(0), false),
+        ("git status", Some(0), false),
+    ]
+    .iter()
+    .enumerate()
+    .map(|(i, (cmd, exit_code, dangerous))| Command {
+        command: cmd.to_string(),
+        timestamp: now - Duration::minutes(10 - i as i64),
+        exit_code: *exit_code,
+        is_dangerous: *dangerous,
+        ..Default::default()
+    })
+    .collect();
+
+    assert!(fuzzy_search(&commands, "  ", &SearchFilter::None)
+        .matches
+        .is_empty());
+
+    let results = fuzzy_search(&commands, "gst", &SearchFilter::None).matches;
+    assert!(!results.is_empty());
+    assert!(results.windows(2).all(|w| w[0].score >= w[1].score));
+    assert!(results.iter().all(|m| m.command.command.starts_with("git")));
+    // Among equal scores the newest run comes first
+    assert_eq!(results[0].command.command, "git status");
+    assert_eq!(results[0].command.timestamp, commands[4].timestamp);
+
+    // Matched positions point at the query's characters
+    let text: Vec<char> = results[0].command.command.chars().collect();
+    let matched: String = results[0].indices.iter().map(|&i| text[i]).collect();
+    assert_eq!(matched, "gst");
+
+    // Letters scattered across an unrelated command don't clear the threshold
+    assert!(fuzzy_search(&commands, "gitstatus", &SearchFilter::None)
+        .matches
+        .iter()
+        .all(|m| m.command.command == "git status"));
+
+    // Filters narrow candidates before scoring
+    let failed = fuzzy_search(&commands, "git", &SearchFilter::Failed).matches;
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].command.command, "git stash pop");
+    let dangerous = fuzzy_search(&commands, "rm", &SearchFilter::Dangerous).matches;
+    assert_eq!(dangerous.len(), 1);
+    assert!(fuzzy_search(&commands, "git", &SearchFilter::Dangerous)
+        .matches
+        .is_empty());
+}
+
+#[test]
+fn test_monochrome_theme_and_no_color() {
+    use ratatui::style::Modifier;
+
+    let mono = Theme::monochrome();
+    assert!(mono.monochrome);
+    assert_eq!(mono.danger, Color::Reset);
+    assert_eq!(mono.style_insecure().fg, Some(Color::Reset));
+    assert!(mono.style_insecure().add_modifier.contains(Modifier::BOLD));
+    // Selection still shows without colors
+    assert!(mono
+        .style_selected()
+        .add_modifier
+        .contains(Modifier::REVERSED));
+    assert!(mono
+        .style_key(true, mono.success)
+        .add_modifier
+        .contains(Modifier::REVERSED));
+
+    let matrix = Theme::matrix();
+    assert!(!Theme::default().monochrome);
+    assert_eq!(Theme::from_name("Matrix").unwrap().primary, matrix.primary);
+    assert!(Theme::from_name("solarized").is_none());
+
+    // NO_COLOR wins over the configured theme; only this test touches it
+    std::env::set_var("NO_COLOR", "1");
+    assert!(Theme::from_config("matrix").monochrome);
+    std::env::set_var("NO_COLOR", "");
+    assert_eq!(Theme::from_config("matrix").primary, matrix.primary);
+    std::env::remove_var("NO_COLOR");
+    assert_eq!(
+        Theme::from_config("no-such-theme").primary,
+        Theme::default().primary
+    );
+}
+
+#[test]
+fn test_host_over_danger_threshold_is_flagged_first() {
+    use whiskerlog::history::Command;
+    use whiskerlog::ui::hosts::analyze_host_commands;
+
+    let mut commands: Vec<Command> = [
+        ("local", "ls"),
+        ("local", "git status"),
+        ("local", "cargo build"),
+        ("ssh:root@db", "ls"),
+    ]
+    .iter()
+    .zip(0..)
+    .map(|((host, command), minute)| Command {
+        host_id: host.to_string(),
+        ..command_at("bash", command, minute)
+    })
+    .collect();
+    let threshold = whiskerlog::config::Config::default().host_danger_threshold;
+
+    let mut analysis = analyze_host_commands(&commands);
+    analysis.flag_dangerous_hosts(threshold);
+    assert_eq!(analysis.hosts_over_danger_threshold(), 0);
+    assert_eq!(analysis.hosts[0].host_id, "local");
+
+    commands.extend(
+        [
+            ("rm -rf /var/lib/postgresql", 0.9),
+            ("chmod -R 777 /etc", 0.8),
+            ("dd if=/dev/zero of=/dev/sda", 1.0),
+        ]
+        .iter()
+        .zip(10..)
+        .map(|((command, danger_score), minute)| Command {
+            host_id: "ssh:root@db".to_string(),
+            is_dangerous: true,
+            danger_score: *danger_score,
+            ..command_at("bash", command, minute)
+        }),
+    );
+
+    let mut analysis = analyze_host_commands(&commands);
+    analysis.flag_dangerous_hosts(threshold);
+    assert_eq!(analysis.hosts_over_danger_threshold(), 1);
+    let db = &analysis.hosts[0];
+    assert_eq!(db.host_id, "ssh:root@db");
+    assert!(db.over_danger_threshold);
+    assert!(db.danger_score > threshold);
+    assert!(!analysis.hosts[1].over_danger_threshold);
+}
+
+#[test]
+fn test_regex_search_matches_structure_and_reports_errors() {
+    use whiskerlog::history::Command;
+    use whiskerlog::ui::search::regex_search;
+
+    // Oldest first, as App holds them
+    let commands: Vec<Command> = [
+        ("docker run -p 8080:80 nginx", Some(0)),
+        ("docker run --rm alpine", Some(0)),
+        ("sudo docker run -p 5432:5432 postgres", Some(0)),
+        ("docker run -p 3000:3000 app", Some(1)),
+    ]
+    .iter()
+    .map(|(cmd, exit_code)| Command {
+        command: cmd.to_string(),
+        exit_code: *exit_code,
+        ..Default::default()
+    })
+    .collect();
+
+    let results = regex_search(&commands, r"^docker run .*-p \d+", &SearchFilter::None)
+        .unwrap()
+        .matches;
+    let matched: Vec<&str> = results.iter().map(|m| m.command.command.as_str()).collect();
+    // Newest first; the anchor leaves out the sudo run
+    assert_eq!(
+        matched,
+        vec!["docker run -p 3000:3000 app", "docker run -p 8080:80 nginx"]
+    );
+    let text: Vec<char> = results[0].command.command.chars().collect();
+    let highlighted: String = results[0].indices.iter().map(|&i| text[i]).collect();
+    assert_eq!(highlighted, "docker run -p 3000");
+
+    // Filters still narrow the candidates
+    let failed = regex_search(&commands, r"-p \d+", &SearchFilter::Failed)
+        .unwrap()
+        .matches;
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].command.command, "docker run -p 3000:3000 app");
+
+    assert!(regex_search(&commands, "docker run (", &SearchFilter::None).is_err());
+    assert!(regex_search(&commands, " ", &SearchFilter::None)
+        .unwrap()
+        .matches
+        .is_empty());
+}
+
+#[test]
+fn test_search_counts_every_match_and_explains_empty_results() {
+    use whiskerlog::history::Command;
+    use whiskerlog::ui::search::{
+        fuzzy_search, match_summary, no_match_suggestions, regex_search, MAX_SEARCH_RESULTS,
+    };
+
+    let commands: Vec<Command> = (0..150)
+        .map(|i| Command {
+            command: format!("cargo build --bin tool{}", i),
+            exit_code: Some(if i % 3 == 0 { 1 } else { 0 }),
+            ..Default::default()
+        })
+        .chain(std::iter::once(Command {
+            command: "ls".to_string(),
+            ..Default::default()
+        }))
+        .collect();
+
+    // The total counts past the results shown
+    let fuzzy = fuzzy_search(&commands, "cargo build", &SearchFilter::None);
+    assert_eq!(fuzzy.matches.len(), MAX_SEARCH_RESULTS);
+    assert_eq!(fuzzy.total, 150);
+    let regex = regex_search(&commands, r"tool\d+$", &SearchFilter::None).unwrap();
+    assert_eq!(regex.matches.len(), MAX_SEARCH_RESULTS);
+    assert_eq!(regex.total, 150);
+
+    // And follows the filter
+    let failed = fuzzy_search(&commands, "cargo build", &SearchFilter::Failed);
+    assert_eq!(failed.total, 50);
+    assert_eq!(
+        match_summary("cargo build", failed.total, &SearchFilter::Failed),
+        "50 matches in Failed"
+    );
+    assert_eq!(
+        match_summary("cargo build", 150, &SearchFilter::None),
+        "150 matches, showing 100"
+    );
+    assert_eq!(match_summary("ls", 1, &SearchFilter::None), "1 match");
+
+    let none = fuzzy_search(&commands, "xyzzy", &SearchFilter::None);
+    assert_eq!(none.total, 0);
+    assert_eq!(
+        match_summary(" xyzzy ", none.total, &SearchFilter::None),
+        "no matches for 'xyzzy'"
+    );
+
+    let suggestions = no_match_suggestions(SearchKind::Fuzzy, &SearchFilter::Dangerous);
+    assert!(suggestions.iter().any(|s| s.contains("spelling")));
+    assert!(suggestions.contains(&"Press F2 to clear the Dangerous filter".to_string()));
+    assert!(suggestions.iter().any(|s| s.contains("regex")));
+    let suggestions = no_match_suggestions(SearchKind::Regex, &SearchFilter::None);
+    assert_eq!(suggestions.len(), 2);
+    assert!(suggestions[1].contains("fuzzy"));
+}
+
+#[test]
+fn test_pasted_text_becomes_a_single_line_query() {
+    assert_eq!(paste_text("git log --oneline"), "git log --oneline");
+    assert_eq!(paste_text("docker compose\r\nup -d\n"), "docker compose up -d");
+    assert_eq!(paste_text("kubectl\tget pods\u{1b}[A\u{7}"), "kubectl get pods[A");
+    assert_eq!(paste_text("\n\n  \n"), "");
+}
+
+#[tokio::test]
+async fn test_every_tab_renders_on_tiny_terminals() {
+    use chrono::TimeZone;
+    use ratatui::{backend::TestBackend, Terminal};
+    use whiskerlog::ui::{draw, MIN_HEIGHT, MIN_WIDTH};
+    use whiskerlog::{Command, Config, Database};
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let config = Config {
+        database_path: temp_dir.path().join("history.db"),
+        ..Config::default()
+    };
+    let mut db = Database::new(&config.database_path).await.unwrap();
+    let base = chrono::Utc.with_ymd_and_hms(2024, 3, 4, 10, 0, 0).unwrap();
+    let commands: Vec<Command> = ["git status", "sudo rm -rf /tmp/build", "cargo test"]
+        .iter()
+        .enumerate()
+        .map(|(i, cmd)| {
+            Command::builder(*cmd, base + chrono::Duration::minutes(i as i64))
+                .session_id("s1")
+                .exit_code(i as i32)
+                .build()
+                .unwrap()
+        })
+        .collect();
+    db.insert_commands(&commands).await.unwrap();
+    drop(db);
+
+    let mut app = App::new(config).await.unwrap();
+    for (width, height) in [(1, 1), (10, 3), (MIN_WIDTH, MIN_HEIGHT), (80, 24)] {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        for index in 0..Tab::all().len() {
+            app.jump_to_tab(index);
+            terminal.draw(|f| draw(f, &app)).unwrap();
+        }
+
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        // Too small for the tabs: only the notice, as much of it as fits
+        let too_small = width < MIN_WIDTH || height < MIN_HEIGHT;
+        assert_eq!(
+            screen.contains("Whiskerlog"),
+            !too_small,
+            "{}x{}",
+            width,
+            height
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_quit_asks_first_only_with_confirm_quit() {
+    use whiskerlog::Config;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let config = |confirm_quit: bool| Config {
+        database_path: temp_dir.path().join("history.db"),
+        confirm_quit,
+        ..Config::default()
+    };
+    assert!(!Config::default().confirm_quit);
+
+    let mut app = App::new(config(false)).await.unwrap();
+    assert!(app.request_quit());
+    assert!(!app.pending_quit);
+
+    let mut app = App::new(config(true)).await.unwrap();
+    assert!(!app.request_quit());
+    assert!(app.pending_quit);
+    assert!(!app.confirm_quit(false));
+    assert!(!app.pending_quit);
+
+    assert!(!app.request_quit());
+    assert!(app.confirm_quit(true));
+}
+
+#[tokio::test]
+async fn test_startup_import_adds_each_command_once() {
+    use whiskerlog::Config;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let history = temp_dir.path().join(".bash_history");
+    let lines: Vec<String> = (0..200).map(|i| format!("echo {}", i)).collect();
+    std::fs::write(&history, lines.join("\n") + "\n").unwrap();
+    let config = Config {
+        database_path: temp_dir.path().join("history.db"),
+        history_paths: vec![history],
+        auto_import: false,
+        ..Config::default()
+    };
+
+    let mut app = App::new(config).await.unwrap();
+    let started = std::time::Instant::now();
+    while app.import_progress().is_some() {
+        assert!(started.elapsed() < std::time::Duration::from_secs(10));
+        app.import_live_commands().await;
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+
+    let ids: std::collections::HashSet<_> = app.commands.iter().map(|cmd| cmd.id).collect();
+    assert_eq!(app.commands.len(), 200);
+    assert_eq!(ids.len(), 200);
+    // Folded into the stats as they came in
+    assert_eq!(app.stats.total_commands, 200);
+    assert_eq!(app.stats.unique_commands, 200);
+    assert_eq!(app.command_stats.as_ref().unwrap().total_commands, 200);
+    assert_eq!(
+        app.stats.total_sessions,
+        app.session_stats.as_ref().unwrap().total_sessions
+    );
+}
+
+#[tokio::test]
+async fn test_reenrich_asks_first_and_runs_in_the_background() {
+    use whiskerlog::{Command, Config, Database};
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let config = Config {
+        database_path: temp_dir.path().join("history.db"),
+        history_paths: vec![],
+        auto_import: false,
+        ..Config::default()
+    };
+    // Stored before the rule that flags it
+    let mut db = Database::new(&config.database_path).await.unwrap();
+    let command = Command::builder("rm -rf /", chrono::Utc::now())
+        .session_id("s1")
+        .build()
+        .unwrap();
+    db.insert_commands(&[command]).await.unwrap();
+    drop(db);
+
+    let mut app = App::new(config).await.unwrap();
+    app.request_reenrich();
+    assert!(app.pending_reenrich);
+    app.confirm_reenrich(false).await;
+    assert!(!app.pending_reenrich);
+    assert!(!app.is_reenriching());
+
+    app.request_reenrich();
+    app.confirm_reenrich(true).await;
+    assert!(app.is_reenriching());
+    let started = std::time::Instant::now();
+    while app.is_reenriching() {
+        assert!(started.elapsed() < std::time::Duration::from_secs(10));
+        app.finish_reenrich().await;
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert!(app.commands[0].is_dangerous);
+    assert_eq!(app.stats.dangerous_commands, 1);
+}
+
+#[tokio::test]
+async fn test_typed_query_keys_go_to_the_search_box() {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use whiskerlog::Config;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let config = Config {
+        database_path: temp_dir.path().join("history.db"),
+        ..Config::default()
+    };
+    let mut app = App::new(config).await.unwrap();
+    let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+
+    // Shortcuts until a query is being typed
+    app.go_to_search_tab();
+    assert_eq!(app.query_char(&key('j')), None);
+
+    app.handle_char('g');
+    for c in "-1j=/?qhkl".chars() {
+        assert_eq!(app.query_char(&key(c)), Some(c));
+        app.handle_char(c);
+    }
+    assert_eq!(app.search_query, "g-1j=/?qhkl");
+    assert_eq!(
+        app.query_char(&KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL)),
+        None
+    );
+    assert_eq!(app.query_char(&KeyEvent::from(KeyCode::Down)), None);
+
+    app.handle_escape();
+    assert_eq!(app.query_char(&key('-')), None);
+    app.jump_to_tab(0);
+    assert_eq!(app.query_char(&key('-')), None);
+}
+
+#[tokio::test]
+async fn test_host_filter_covers_every_aliased_host_id() {
+    use whiskerlog::db::Database;
+    use whiskerlog::history::Command;
+    use whiskerlog::Config;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let config = Config {
+        database_path: temp_dir.path().join("history.db"),
+        host_aliases: [("prod.internal", "prod"), ("10.0.0.*", "prod")]
+            .iter()
+            .map(|(pattern, host)| (pattern.to_string(), host.to_string()))
+            .collect(),
+        ..Config::default()
+    };
+    let commands: Vec<Command> = [
+        "ssh:deploy@prod.internal",
+        "ssh:root@10.0.0.5",
+        "ssh:root@10.0.0.5",
+        "local",
+    ]
+    .iter()
+    .zip(0..)
+    .map(|(host, minute)| Command {
+        host_id: host.to_string(),
+        ..command_at("bash", "uptime", minute)
+    })
+    .collect();
+    let mut db = Database::new(&config.database_path).await.unwrap();
+    db.insert_commands(&commands).await.unwrap();
+    drop(db);
+
+    let mut app = App::new(config).await.unwrap();
+    let hosts_tab = Tab::all()
+        .iter()
+        .position(|tab| *tab == Tab::Hosts)
+        .unwrap();
+    app.jump_to_tab(hosts_tab);
+    assert_eq!(app.hosts.hosts[0].host_id, "prod");
+    app.handle_enter();
+    app.refresh_command_page().await;
+
+    assert_eq!(app.current_tab, Tab::Commands);
+    let rows = app.visible_commands(0, 10);
+    assert_eq!(rows.len(), 3);
+    assert!(rows.iter().all(|cmd| cmd.host_id == "prod"));
+}
+
+#[test]
+fn test_search_query_and_or_not_syntax() {
+    use whiskerlog::history::Command;
+    use whiskerlog::ui::search::query_search;
+    use whiskerlog::ui::search_query::{QueryTerm, SearchQuery};
+
+    let term = |text: &str, negated: bool, exact: bool| QueryTerm {
+        text: text.to_string(),
+        negated,
+        exact,
+    };
+    // Short and long flags are looked for; other dashed words are left out
+    assert_eq!(
+        SearchQuery::parse("git -m -rebase").unwrap().groups,
+        vec![vec![
+            term("git", false, false),
+            term("-m", false, true),
+            term("rebase", true, false),
+        ]]
+    );
+    assert_eq!(
+        SearchQuery::parse("Docker --rm | \"-rf\" -\"git push\"")
+            .unwrap()
+            .groups,
+        vec![
+            vec![term("docker", false, false), term("--rm", false, true)],
+            vec![term("-rf", false, true), term("git push", true, true)],
+        ]
+    );
+    assert!(SearchQuery::parse("cargo").unwrap().is_single_term());
+    assert!(!SearchQuery::parse("-cargo").unwrap().is_single_term());
+    assert!(!SearchQuery::parse("cargo build").unwrap().is_single_term());
+    assert!(SearchQuery::parse("   ").unwrap().is_empty());
+
+    for bad in [
+        "docker |",
+        "| docker",
+        "a || b",
+        "\"unclosed",
+        "git -",
+        "\"\"",
+    ] {
+        assert!(
+            SearchQuery::parse(bad).is_err(),
+            "{:?} should not parse",
+            bad
+        );
+    }
+
+    // Oldest first, as App holds them
+    let commands: Vec<Command> = [
+        "git commit -m 'fix build'",
+        "git rebase -i main -m",
+        "git log --oneline",
+        "GIT COMMIT -M 'Loud'",
+        "docker run -p 80:80 nginx",
+        "docker compose up -p web",
+        "git status",
+        "git stash pop",
+    ]
+    .iter()
+    .map(|cmd| Command {
+        command: cmd.to_string(),
+        ..Default::default()
+    })
+    .collect();
+    let search = |query: &str| -> Vec<String> {
+        query_search(
+            &commands,
+            &SearchQuery::parse(query).unwrap(),
+            &SearchFilter::None,
+        )
+        .matches
+        .into_iter()
+        .map(|m| m.command.command)
+        .collect()
+    };
+
+    // Newest first, case-insensitive
+    assert_eq!(
+        search("git -m -rebase"),
+        vec!["GIT COMMIT -M 'Loud'", "git commit -m 'fix build'"]
+    );
+    assert_eq!(
+        search("docker -p -compose"),
+        vec!["docker run -p 80:80 nginx"]
+    );
+    assert_eq!(
+        search("compose | oneline"),
+        vec!["docker compose up -p web", "git log --oneline"]
+    );
+    // Words that aren't substrings still match fuzzily
+    assert_eq!(search("gst -stash"), vec!["git status"]);
+    assert!(search("\"gst\"").is_empty());
+
+    // Every occurrence of a word is highlighted
+    let results = query_search(
+        &commands,
+        &SearchQuery::parse("git -m").unwrap(),
+        &SearchFilter::None,
+    );
+    let first = &results.matches[0];
+    let text: Vec<char> = first.command.command.chars().collect();
+    let highlighted: String = first.indices.iter().map(|&i| text[i]).collect();
+    assert_eq!(highlighted, "GIT-M");
+    assert_eq!(results.total, 3);
+
+    // Letters that change length when lowercased still highlight where they are
+    let commands: Vec<Command> = ["echo İSTANBUL STRAẞE done"]
+        .iter()
+        .map(|cmd| Command {
+            command: cmd.to_string(),
+            ..Default::default()
+        })
+        .collect();
+    for (query, expected) in [
+        ("stanbul", "STANBUL"),
+        ("straße", "STRAẞE"),
+        ("done", "done"),
+    ] {
+        let results = query_search(
+            &commands,
+            &SearchQuery::parse(query).unwrap(),
+            &SearchFilter::None,
+        );
+        let found = &results.matches[0];
+        let text: Vec<char> = found.command.command.chars().collect();
+        let highlighted: String = found.indices.iter().map(|&i| text[i]).collect();
+        assert_eq!(highlighted, expected, "{query}");
+    }
+}
+
+#[tokio::test]
+async fn test_selected_command_follows_the_selected_row() {
+    use whiskerlog::{Config, Database};
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let config = || Config {
+        database_path: temp_dir.path().join("history.db"),
+        history_paths: vec![],
+        auto_import: false,
+        ..Config::default()
+    };
+
+    let commands_tab = Tab::all()
+        .iter()
+        .position(|tab| *tab == Tab::Commands)
+        .unwrap();
+    // Nothing stored, nothing to copy
+    let mut app = App::new(config()).await.unwrap();
+    app.jump_to_tab(commands_tab);
+    app.refresh_command_page().await;
+    assert!(app.selected_command().is_none());
+    drop(app);
+
+    let commands: Vec<_> = ["git status", "cargo build", "cargo test"]
+        .iter()
+        .zip(0..)
+        .map(|(text, minute)| command_at("bash", text, minute))
+        .collect();
+    let mut db = Database::new(&config().database_path).await.unwrap();
+    db.insert_commands(&commands).await.unwrap();
+    drop(db);
+
+    let mut app = App::new(config()).await.unwrap();
+    app.jump_to_tab(commands_tab);
+    app.refresh_command_page().await;
+    // Newest first
+    assert_eq!(app.selected_command().unwrap().command, "cargo test");
+    app.scroll_down();
+    app.refresh_command_page().await;
+    assert_eq!(app.selected_command().unwrap().command, "cargo build");
+
+    app.selected_index = 3;
+    assert!(app.selected_command().is_none());
+}
+
+#[tokio::test]
+async fn test_host_pivot_filters_commands_until_escape() {
+    use whiskerlog::{Command, Config, Database};
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let config = Config {
+        database_path: temp_dir.path().join("history.db"),
+        history_paths: vec![],
+        auto_import: false,
+        ..Config::default()
+    };
+    let commands: Vec<_> = ["ssh:deploy@web", "local", "ssh:deploy@web"]
+        .iter()
+        .zip(0..)
+        .map(|(host, minute)| Command {
+            host_id: host.to_string(),
+            ..command_at("bash", "uptime", minute)
+        })
+        .collect();
+    let mut db = Database::new(&config.database_path).await.unwrap();
+    db.insert_commands(&commands).await.unwrap();
+    drop(db);
+
+    let mut app = App::new(config).await.unwrap();
+    let hosts_tab = Tab::all()
+        .iter()
+        .position(|tab| *tab == Tab::Hosts)
+        .unwrap();
+    app.jump_to_tab(hosts_tab);
+    let host = app.hosts.hosts[app.selected_index].host_id.clone();
+    app.handle_enter();
+    app.refresh_command_page().await;
+
+    assert_eq!(app.current_tab, Tab::Commands);
+    assert_eq!(app.host_filter.as_deref(), Some(host.as_str()));
+    let rows = app.visible_commands(0, 10);
+    assert!(!rows.is_empty());
+    assert!(rows.iter().all(|cmd| cmd.host_id == host));
+
+    app.handle_escape();
+    app.refresh_command_page().await;
+    assert_eq!(app.current_tab, Tab::Commands);
+    assert!(app.host_filter.is_none());
+    assert_eq!(app.visible_commands(0, 10).len(), 3);
+}
+
+#[tokio::test]
+async fn test_detail_popup_renders_the_open_command() {
+    use ratatui::{backend::TestBackend, Terminal};
+    use whiskerlog::ui::draw;
+    use whiskerlog::{Command, Config, Database};
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let config = Config {
+        database_path: temp_dir.path().join("history.db"),
+        history_paths: vec![],
+        auto_import: false,
+        ..Config::default()
+    };
+    let command = Command {
+        working_directory: Some("/srv/app".to_string()),
+        tags: vec!["deploy".to_string()],
+        ..command_at("bash", "kubectl rollout restart deploy/web", 0)
+    };
+    let mut db = Database::new(&config.database_path).await.unwrap();
+    db.insert_commands(&[command]).await.unwrap();
+    drop(db);
+
+    let mut app = App::new(config).await.unwrap();
+    let commands_tab = Tab::all()
+        .iter()
+        .position(|tab| *tab == Tab::Commands)
+        .unwrap();
+    app.jump_to_tab(commands_tab);
+    app.refresh_command_page().await;
+    app.handle_enter();
+    assert!(app.detail_visible);
+
+    let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+    terminal.draw(|f| draw(f, &app)).unwrap();
+    let buffer = terminal.backend().buffer();
+    let screen: String = (0..buffer.area.height)
+        .map(|y| {
+            (0..buffer.area.width)
+                .map(|x| buffer[(x, y)].symbol())
+                .collect::<String>()
+                + "\n"
+        })
+        .collect();
+
+    assert!(screen.contains("Command Details"));
+    assert!(screen.contains("kubectl rollout restart deploy/web"));
+    assert!(screen.contains("/srv/app"));
+    assert!(screen.contains("#deploy"));
+    assert!(screen.contains("[m] toggle"));
+    assert!(screen.contains("[t] edit"));
+}
+
+#[tokio::test]
+async fn test_detail_popup_marks_and_tags_the_open_command() {
+    use whiskerlog::app::{TagInput, TagInputPurpose};
+    use whiskerlog::{Config, Database};
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let config = Config {
+        database_path: temp_dir.path().join("history.db"),
+        history_paths: vec![],
+        auto_import: false,
+        ..Config::default()
+    };
+    let mut db = Database::new(&config.database_path).await.unwrap();
+    db.insert_commands(&[command_at("bash", "terraform plan", 0)])
+        .await
+        .unwrap();
+    drop(db);
+
+    let mut app = App::new(config).await.unwrap();
+    let commands_tab = Tab::all()
+        .iter()
+        .position(|tab| *tab == Tab::Commands)
+        .unwrap();
+    app.jump_to_tab(commands_tab);
+    app.refresh_command_page().await;
+    app.handle_enter();
+    let is_experiment = app.detail_command.as_ref().unwrap().is_experiment;
+
+    app.toggle_experiment_mark().await;
+    assert_eq!(
+        app.detail_command.as_ref().unwrap().is_experiment,
+        !is_experiment
+    );
+    assert_eq!(app.commands[0].is_experiment, !is_experiment);
+    app.toggle_experiment_mark().await;
+    assert_eq!(app.commands[0].is_experiment, is_experiment);
+
+    app.open_tag_editor();
+    let id = app.detail_command.as_ref().unwrap().id.unwrap();
+    assert_eq!(
+        app.tag_input,
+        Some(TagInput {
+            purpose: TagInputPurpose::Edit(id),
+            text: String::new(),
+        })
+    );
+    app.tag_input.as_mut().unwrap().text = "infra, #plan".to_string();
+    app.submit_tag_input().await;
+    assert!(app.tag_input.is_none());
+    assert_eq!(app.detail_command.as_ref().unwrap().tags, ["infra", "plan"]);
+    assert_eq!(app.commands[0].tags, ["infra", "plan"]);
+
+    app.handle_escape();
+    assert!(!app.detail_visible);
+    assert!(app.detail_command.is_none());
+}